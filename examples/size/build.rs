@@ -0,0 +1,8 @@
+fn main() {
+    // Lets the linker find `link.x` next to this manifest.
+    println!(
+        "cargo:rustc-link-search={}",
+        std::env::var("CARGO_MANIFEST_DIR").unwrap()
+    );
+    println!("cargo:rerun-if-changed=link.x");
+}
@@ -0,0 +1,16 @@
+/* Just enough layout to link the size firmware; it is measured, never flashed. */
+MEMORY
+{
+  FLASH : ORIGIN = 0x08000000, LENGTH = 256K
+  RAM : ORIGIN = 0x20000000, LENGTH = 64K
+}
+
+ENTRY(_start)
+
+SECTIONS
+{
+  .text : { *(.text .text.*) } > FLASH
+  .rodata : { *(.rodata .rodata.*) } > FLASH
+  .data : { *(.data .data.*) } > RAM
+  .bss : { *(.bss .bss.*) } > RAM
+}
@@ -0,0 +1,77 @@
+//! Code-size firmware for the "Minimal builds" figures in the crate README.
+//!
+//! The bus is a pair of volatile registers so that nothing is optimised away; the
+//! firmware is linked to be measured, not to run.
+
+#![no_std]
+#![no_main]
+
+use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, Operation};
+use tca6424::{Pin, PinDirection, PinState, Port, Tca6424};
+
+const DATA: *mut u8 = 0x4000_0000 as *mut u8;
+const STATUS: *const u8 = 0x4000_0004 as *const u8;
+const SINK: *mut u8 = 0x4000_0008 as *mut u8;
+
+struct Bus;
+
+impl ErrorType for Bus {
+    type Error = ErrorKind;
+}
+
+impl I2c for Bus {
+    fn transaction(&mut self, address: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        for op in ops {
+            match op {
+                Operation::Write(bytes) => {
+                    for byte in bytes.iter() {
+                        unsafe { core::ptr::write_volatile(DATA, *byte ^ address) }
+                    }
+                }
+                Operation::Read(buffer) => {
+                    for byte in buffer.iter_mut() {
+                        *byte = unsafe { core::ptr::read_volatile(DATA) };
+                    }
+                }
+            }
+        }
+        if unsafe { core::ptr::read_volatile(STATUS) } != 0 {
+            Err(ErrorKind::Other)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[panic_handler]
+fn panic(_: &core::panic::PanicInfo) -> ! {
+    loop {}
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn _start() -> ! {
+    let Ok(mut tca) = Tca6424::new(Bus, 0x22) else {
+        loop {}
+    };
+    let _ = tca.set_pin_direction(Pin::P00, PinDirection::Output);
+    let _ = tca.set_pin_output(Pin::P00, PinState::High);
+    let _ = tca.set_port_output(Port::Port1, 0x55);
+    #[cfg(feature = "extended")]
+    {
+        let _ = tca.set_port_polarity_inversion(Port::Port2, 0x0F);
+        let _ = tca.set_port_interrupt_mask(Port::Port2, 0xF0);
+        let _ = tca.set_ports_output_ai(Port::Port0, &[1, 2, 3]);
+        let mut inputs = [0u8; 3];
+        let _ = tca.get_ports_input_state_ai(Port::Port0, &mut inputs);
+        if let Ok(snapshot) = tca.snapshot() {
+            unsafe { core::ptr::write_volatile(SINK, snapshot.outputs[0]) }
+        }
+        let healthy = tca.health_check().is_healthy();
+        unsafe { core::ptr::write_volatile(SINK, u8::from(healthy)) }
+    }
+    loop {
+        if let Ok(value) = tca.get_port_input_state(Port::Port2) {
+            unsafe { core::ptr::write_volatile(SINK, value) }
+        }
+    }
+}
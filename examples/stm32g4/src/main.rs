@@ -15,7 +15,7 @@ use {defmt_rtt as _, panic_probe as _}; // Import panic_probe for panic handling
 
 // Updated import to tca6424
 use tca6424::{
-    Tca6424,
+    examples_support, // Shared bring-up, input scan and output drive steps
     Pin, PinDirection, PinState, Port, DEFAULT_ADDRESS, // Import relevant types and default address
 };
 use tca6424::errors::Error; // Import Error type
@@ -50,9 +50,11 @@ async fn main(_spawner: Spawner) {
     // Instantiate TCA6424 driver and handle the Result
     // TCA6424 address is typically 0x22
     let address = DEFAULT_ADDRESS;
-    let mut tca = match Tca6424::new(&mut i2c, address).await { // Correctly handle the async Result
+    // P10-P17 are outputs (driven low first), every other pin is an input
+    let outputs = 0x00_FF00;
+    let mut tca = match examples_support::init(&mut i2c, address, outputs).await {
         Ok(driver) => {
-            info!("TCA6424 driver instance created successfully.");
+            info!("TCA6424 driver instance created and initialised.");
             driver
         }
         Err(e) => {
@@ -85,8 +87,22 @@ async fn main(_spawner: Spawner) {
         Err(e) => error!("Failed to set Port1 output: {:?}", e),
     }
 
-    // Example 3: Read Port2 input state
-    info!("\n--- Example 3: Read Port2 Input State ---");
+    // Example 3: Scan all inputs and drive the outputs from them
+    info!("\n--- Example 3: Scan Inputs, Drive Outputs ---");
+    match examples_support::scan_inputs(&mut tca).await {
+        Ok(levels) => {
+            info!("Input levels: {:024b}", levels);
+            // Mirror P00-P07 onto the P10-P17 outputs
+            match examples_support::drive_outputs(&mut tca, (levels & 0xFF) << 8).await {
+                Ok(_) => info!("Outputs driven from the Port0 inputs."),
+                Err(e) => error!("Failed to drive outputs: {:?}", e),
+            }
+        }
+        Err(e) => error!("Failed to scan inputs: {:?}", e),
+    }
+
+    // Example 3b: Read Port2 input state
+    info!("\n--- Example 3b: Read Port2 Input State ---");
     match tca.get_port_input_state(Port::Port2).await {
         Ok(input_mask) => info!("Read Port2 input state: {:08b}", input_mask),
         Err(e) => error!("Failed to read Port2 input state: {:?}", e),
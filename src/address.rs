@@ -0,0 +1,125 @@
+//! Address validation and non-invasive presence checks.
+//!
+//! The I2C specification reserves the addresses `0x00`-`0x07` and `0x78`-`0x7F`. The most
+//! dangerous of them is `0x00`, the general call address: every device that implements
+//! general call listens to it, so register writes meant for one expander would reach
+//! unrelated chips on the same bus (a general call `0x06` byte even resets them). A TCA6424
+//! only answers at `0x22` or `0x23`, but the address often comes from configuration data or a
+//! command line; [`Tca6424::new_checked`] refuses reserved addresses before any transfer is
+//! made.
+
+use embedded_hal::i2c::{ErrorKind, ErrorType};
+
+use crate::errors::Error;
+use crate::registers::Register;
+use crate::{BusAccess, DelayNs, Tca6424};
+
+/// Returns `true` if `address` is reserved by the I2C specification: the general call and
+/// START byte addresses, CBUS, high-speed master codes and the 10-bit address prefixes
+/// (`0x00`-`0x07` and `0x78`-`0x7F`), or is not a 7-bit address at all.
+///
+/// ```
+/// use tca6424::{DEFAULT_ADDRESS, is_reserved_address};
+///
+/// assert!(is_reserved_address(0x00));
+/// assert!(is_reserved_address(0x7C));
+/// assert!(!is_reserved_address(DEFAULT_ADDRESS));
+/// ```
+pub const fn is_reserved_address(address: u8) -> bool {
+    address < 0x08 || address >= 0x78
+}
+
+impl<I2C: ErrorType> Tca6424<I2C> {
+    /// Creates a driver like [`Tca6424::new`], rejecting reserved addresses (see
+    /// [`is_reserved_address`]).
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Self)` on success, or `Error::ReservedAddress(address)` if the address is
+    /// reserved; no bus transfer is made either way.
+    pub fn new_checked(i2c: I2C, address: u8) -> Result<Self, Error<I2C::Error>> {
+        if is_reserved_address(address) {
+            return Err(Error::ReservedAddress(address));
+        }
+        Self::new(i2c, address)
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca6424",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> Tca6424<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Checks whether the device acknowledges its address.
+    ///
+    /// The check is a zero-length write: the address byte and a stop, which selects no
+    /// register and changes nothing on the device. Some I2C peripherals cannot emit a
+    /// transfer without data and report an error other than a NACK; the check then falls
+    /// back to reading the Configuration Port 0 register, which has no side effects either
+    /// (unlike an Input Port read, it does not clear a pending interrupt). In write-only mode
+    /// (see [`Tca6424::set_write_only`]) there is no fallback and the error is returned.
+    ///
+    /// Works while the device is offline (see [`Tca6424::enable_hot_plug`]), and does not
+    /// change the hot-plug state.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(true)` if the device acknowledged, `Ok(false)` if nothing answered at the
+    /// address, or an `Error` if the bus failed otherwise.
+    pub async fn ping(&mut self) -> Result<bool, Error<I2C::Error>> {
+        #[cfg(not(feature = "async"))]
+        let result = Some(self.i2c.write(self.address, &[]));
+        #[cfg(feature = "async")]
+        let result = crate::timeout::with_timeout(
+            &mut self.delay,
+            self.timeout_us,
+            self.i2c.write(self.address, &[]),
+        )
+        .await;
+        let error = match result.ok_or(Error::Timeout)? {
+            Ok(()) => return Ok(true),
+            Err(error) => error,
+        };
+        if is_nack(&error) {
+            return Ok(false);
+        }
+        if self.write_only {
+            return Err(Error::I2c(error));
+        }
+
+        let mut value = [0u8];
+        #[cfg(not(feature = "async"))]
+        let result = Some(self.i2c.write_read(
+            self.address,
+            &[Register::ConfigurationPort0 as u8],
+            &mut value,
+        ));
+        #[cfg(feature = "async")]
+        let result = crate::timeout::with_timeout(
+            &mut self.delay,
+            self.timeout_us,
+            self.i2c.write_read(
+                self.address,
+                &[Register::ConfigurationPort0 as u8],
+                &mut value,
+            ),
+        )
+        .await;
+        match result.ok_or(Error::Timeout)? {
+            Ok(()) => Ok(true),
+            Err(error) if is_nack(&error) => Ok(false),
+            Err(error) => Err(Error::I2c(error)),
+        }
+    }
+}
+
+fn is_nack<E: embedded_hal::i2c::Error>(error: &E) -> bool {
+    matches!(error.kind(), ErrorKind::NoAcknowledge(_))
+}
@@ -0,0 +1,136 @@
+//! Background-maintained input image with lock-free reads.
+
+use portable_atomic::{AtomicU32, Ordering};
+
+use crate::errors::Error;
+use crate::registers::Register;
+use crate::{BusAccess, DelayNs, InterruptThrottle, Pin, PinState, Tca6424};
+
+/// Mask of the 24 pin bits in an input image.
+const PIN_BITS: u32 = 0x00FF_FFFF;
+/// Marker stored in the image while the input levels are unknown.
+const UNKNOWN: u32 = 1 << 31;
+
+/// A 24-bit input image kept fresh by one task and read from RAM by everyone else.
+///
+/// The owner of the driver refreshes the image with [`AtomicInputs::refresh`] (polling) or
+/// [`AtomicInputs::service`] (interrupt-driven, through an [`InterruptThrottle`]). Other tasks
+/// and ISRs call [`AtomicInputs::cached_input`], which only loads an atomic word, so readers
+/// never wait for the bus. Because every method takes `&self`, an `AtomicInputs` can live in
+/// a `static`.
+///
+/// The image is empty until the first successful read, and is emptied again when a refresh
+/// fails, so readers never mistake stale levels for current ones.
+///
+/// Bit `n` of the image corresponds to the pin with index `n` (`P00` = bit 0, `P27` = bit 23).
+///
+/// Requires the `portable-atomic` feature.
+#[derive(Debug)]
+pub struct AtomicInputs {
+    image: AtomicU32,
+}
+
+impl AtomicInputs {
+    /// Creates an empty input image.
+    pub const fn new() -> Self {
+        Self {
+            image: AtomicU32::new(UNKNOWN),
+        }
+    }
+
+    /// Returns the last read level of `pin`, or `None` if no valid image is stored.
+    pub fn cached_input(&self, pin: Pin) -> Option<PinState> {
+        self.image()
+            .map(|inputs| PinState::from(inputs & (1 << pin as u8) != 0))
+    }
+
+    /// Returns the last read 24 input levels, or `None` if no valid image is stored.
+    pub fn image(&self) -> Option<u32> {
+        let image = self.image.load(Ordering::Acquire);
+        (image & UNKNOWN == 0).then_some(image)
+    }
+
+    /// Stores input levels read elsewhere, for example the result of
+    /// [`Tca6424::service_interrupt`].
+    pub fn store(&self, inputs: u32) {
+        self.image.store(inputs & PIN_BITS, Ordering::Release);
+    }
+
+    /// Empties the image; [`AtomicInputs::cached_input`] returns `None` until the next refresh.
+    pub fn invalidate(&self) {
+        self.image.store(UNKNOWN, Ordering::Release);
+    }
+}
+
+impl Default for AtomicInputs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "AtomicInputs",),
+    async(feature = "async", keep_self)
+)]
+impl AtomicInputs {
+    /// Reads the three Input Port registers in one auto-increment transaction and stores them.
+    ///
+    /// A failed read empties the image. This method must not be called from interrupt
+    /// context. It is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the 24 input levels, or an `Error` if the I2C bus operation fails.
+    pub async fn refresh<I2C, D>(&self, tca: &mut Tca6424<I2C, D>) -> Result<u32, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let mut values = [0u8; 4];
+        if let Err(error) = tca
+            .read_registers_ai(Register::InputPort0, &mut values[..3])
+            .await
+        {
+            self.invalidate();
+            return Err(error);
+        }
+        let inputs = u32::from_le_bytes(values);
+        self.store(inputs);
+        Ok(inputs)
+    }
+
+    /// Refreshes the image through [`Tca6424::service_interrupt`] if `throttle` has a read due.
+    ///
+    /// A failed read empties the image and leaves the interrupt pending. This method must not
+    /// be called from interrupt context. It is `async` when the `async` feature is enabled,
+    /// and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(true)` if the image was refreshed, `Ok(false)` if no read was due, or an
+    /// `Error` if the I2C bus operation fails.
+    pub async fn service<I2C, D>(
+        &self,
+        tca: &mut Tca6424<I2C, D>,
+        throttle: &mut InterruptThrottle,
+        now_ms: u32,
+    ) -> Result<bool, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        match tca.service_interrupt(throttle, now_ms).await {
+            Ok(Some(inputs)) => {
+                self.store(inputs);
+                Ok(true)
+            }
+            Ok(None) => Ok(false),
+            Err(error) => {
+                self.invalidate();
+                Err(error)
+            }
+        }
+    }
+}
@@ -0,0 +1,239 @@
+//! ISR-safe deferred output updates.
+
+use portable_atomic::{AtomicU32, Ordering};
+
+use crate::errors::Error;
+use crate::registers::Register;
+use crate::{BusAccess, DelayNs, Pin, PinState, Port, RegisterKind, Tca6424};
+
+/// Mask of the 24 pin bits in an output image.
+const PIN_BITS: u32 = 0x00FF_FFFF;
+/// Marker stored in `flushed` while the device state is unknown.
+const UNKNOWN: u32 = 1 << 31;
+
+/// A 24-bit output image that can be modified from interrupt context.
+///
+/// Setting or clearing bits never touches the I2C bus: it only updates an atomic word. The
+/// main loop (or a task) then calls [`AtomicOutputs::flush`], which writes only the ports whose
+/// byte changed since the previous flush. Because every method takes `&self`, an
+/// `AtomicOutputs` can live in a `static` and be shared between ISRs and the main context.
+///
+/// Bit `n` of the image corresponds to the pin with index `n` (`P00` = bit 0, `P27` = bit 23).
+///
+/// Requires the `portable-atomic` feature.
+#[derive(Debug)]
+pub struct AtomicOutputs {
+    desired: AtomicU32,
+    flushed: AtomicU32,
+}
+
+/// How long an [`AtomicOutputs`] image may stay dirty before [`AtomicOutputs::flush_coalesced`]
+/// writes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CoalesceWindow {
+    /// Flush on the `n`-th call after the image became dirty (`0` flushes at once).
+    Ticks(u32),
+    /// Flush once the image has been dirty for at least this many milliseconds.
+    Millis(u32),
+}
+
+/// Flush scheduling state for [`AtomicOutputs::flush_coalesced`], owned by the flushing task.
+///
+/// A window opens at the first call that finds the image dirty. Every change made while it
+/// is open, including repeated changes of the same port, is merged into the image and
+/// written once when the window closes. For outputs updated faster than they need to reach
+/// the pins (bit-banged PWM, multiplexed displays), this bounds the bus traffic to one flush
+/// per window, at the cost of up to one window of latency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Coalescer {
+    window: CoalesceWindow,
+    /// Ticks counted and timestamp of the call that opened the window.
+    opened: Option<(u32, u32)>,
+}
+
+impl Coalescer {
+    /// Creates a scheduler with the given window and no window open.
+    pub const fn new(window: CoalesceWindow) -> Self {
+        Self {
+            window,
+            opened: None,
+        }
+    }
+
+    /// Returns the window length.
+    pub const fn window(&self) -> CoalesceWindow {
+        self.window
+    }
+
+    /// Returns `true` while changes are being collected.
+    pub const fn is_open(&self) -> bool {
+        self.opened.is_some()
+    }
+
+    /// Counts one call at `now_ms` for an image that is `dirty`, and returns `true` if it
+    /// should be flushed now. The window stays open until [`Coalescer::close`].
+    pub fn poll(&mut self, dirty: bool, now_ms: u32) -> bool {
+        if !dirty {
+            self.opened = None;
+            return false;
+        }
+        let (ticks, opened_ms) = self.opened.get_or_insert((0, now_ms));
+        let due = match self.window {
+            CoalesceWindow::Ticks(n) => *ticks >= n,
+            CoalesceWindow::Millis(ms) => now_ms.wrapping_sub(*opened_ms) >= ms,
+        };
+        *ticks = ticks.saturating_add(1);
+        due
+    }
+
+    /// Closes the window after a flush.
+    pub fn close(&mut self) {
+        self.opened = None;
+    }
+}
+
+impl AtomicOutputs {
+    /// Creates a new output image. Nothing is known about the device yet, so the first
+    /// [`flush`](AtomicOutputs::flush) writes all three ports.
+    pub const fn new(initial: u32) -> Self {
+        Self {
+            desired: AtomicU32::new(initial & PIN_BITS),
+            flushed: AtomicU32::new(UNKNOWN),
+        }
+    }
+
+    /// Drives `pin` high on the next flush.
+    pub fn set_high(&self, pin: Pin) {
+        self.set_pins(1 << pin as u8);
+    }
+
+    /// Drives `pin` low on the next flush.
+    pub fn set_low(&self, pin: Pin) {
+        self.clear_pins(1 << pin as u8);
+    }
+
+    /// Sets `pin` to `state` (a [`PinState`], an `embedded_hal::digital::PinState` or a `bool`)
+    /// on the next flush.
+    pub fn set(&self, pin: Pin, state: impl Into<PinState>) {
+        match state.into() {
+            PinState::High => self.set_high(pin),
+            PinState::Low => self.set_low(pin),
+        }
+    }
+
+    /// Inverts `pin` on the next flush.
+    pub fn toggle(&self, pin: Pin) {
+        self.desired.fetch_xor(1 << pin as u8, Ordering::AcqRel);
+    }
+
+    /// Sets every pin whose bit is `1` in `mask`.
+    pub fn set_pins(&self, mask: u32) {
+        self.desired.fetch_or(mask & PIN_BITS, Ordering::AcqRel);
+    }
+
+    /// Clears every pin whose bit is `1` in `mask`.
+    pub fn clear_pins(&self, mask: u32) {
+        self.desired.fetch_and(!(mask & PIN_BITS), Ordering::AcqRel);
+    }
+
+    /// Returns the current (not necessarily flushed) output image.
+    pub fn image(&self) -> u32 {
+        self.desired.load(Ordering::Acquire)
+    }
+
+    /// Returns `true` if the image differs from what was last flushed.
+    pub fn is_dirty(&self) -> bool {
+        self.image() != self.flushed.load(Ordering::Acquire)
+    }
+
+    /// Forgets what was last flushed, so the next flush rewrites all three ports.
+    pub fn invalidate(&self) {
+        self.flushed.store(UNKNOWN, Ordering::Release);
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "AtomicOutputs",),
+    async(feature = "async", keep_self)
+)]
+impl AtomicOutputs {
+    /// Writes the ports whose byte changed since the last flush to the Output Port registers.
+    ///
+    /// A single changed port is written with one register write; several changed ports are
+    /// written with one auto-increment transaction spanning the first to the last changed port.
+    /// If the write fails, the image stays dirty and the next flush retries it.
+    ///
+    /// This method must not be called from interrupt context. It is `async` when the `async`
+    /// feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(true)` if anything was written, `Ok(false)` if the image was clean, or an
+    /// `Error` if the I2C bus operation fails.
+    pub async fn flush<I2C, D>(&self, tca: &mut Tca6424<I2C, D>) -> Result<bool, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let desired = self.desired.load(Ordering::Acquire);
+        let flushed = self.flushed.load(Ordering::Acquire);
+
+        let bytes = desired.to_le_bytes();
+        let changed = Port::ALL
+            .map(|port| flushed & UNKNOWN != 0 || port.byte_of(desired) != port.byte_of(flushed));
+        let (Some(first), Some(last)) = (
+            changed.iter().position(|&c| c),
+            changed.iter().rposition(|&c| c),
+        ) else {
+            return Ok(false);
+        };
+
+        let port = Port::wrapping(first);
+        if first == last {
+            tca.set_port_output(port, port.byte_of(desired)).await?;
+        } else {
+            // An empty payload would mark the ISR's changes flushed without writing them.
+            let payload = bytes.get(first..=last).ok_or(Error::InvalidRegisterOrPin)?;
+            tca.write_registers_ai(Register::of(RegisterKind::Output, port), payload)
+                .await?;
+        }
+        self.flushed.store(desired, Ordering::Release);
+        Ok(true)
+    }
+
+    /// Flushes like [`AtomicOutputs::flush`], but only once the image has stayed dirty for the
+    /// window of `coalescer`, merging every change made in between into one write.
+    ///
+    /// Call it periodically (every tick of the window's unit), with `now_ms` a free-running
+    /// millisecond timestamp; it is ignored for [`CoalesceWindow::Ticks`]. A failed write
+    /// keeps the window open, so the next call retries at once.
+    ///
+    /// This method must not be called from interrupt context. It is `async` when the `async`
+    /// feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(true)` if anything was written, `Ok(false)` if the image was clean or the
+    /// window is still open, or an `Error` if the I2C bus operation fails.
+    pub async fn flush_coalesced<I2C, D>(
+        &self,
+        tca: &mut Tca6424<I2C, D>,
+        coalescer: &mut Coalescer,
+        now_ms: u32,
+    ) -> Result<bool, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        if !coalescer.poll(self.is_dirty(), now_ms) {
+            return Ok(false);
+        }
+        let written = self.flush(tca).await?;
+        coalescer.close();
+        Ok(written)
+    }
+}
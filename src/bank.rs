@@ -0,0 +1,652 @@
+//! Management of several expanders as one bank.
+
+#[cfg(feature = "hot-plug")]
+use crate::LinkState;
+use crate::errors::Error;
+use crate::registers::Register;
+use crate::{
+    BusAccess, DelayNs, InputChanges, InputWatcher, IoExpander, NoDelay, Pin, PinDirection,
+    PinState, PlannedWrite, Port, RegisterKind, Tca6424,
+};
+
+/// A pin of an [`ExpanderBank`]: the index of its device and the pin on that device.
+///
+/// Across the bank, pins are also numbered by a flat index, `device_index * 24` plus the pin
+/// index, so a keypad matrix or relay array wired over several expanders can be described
+/// as one list of pins and handed to the bank-level pin methods. Helpers that drive a single
+/// expander take the per-device masks of such a list from [`BankPin::device_masks`].
+///
+/// ```
+/// use tca6424::{BankPin, Pin};
+///
+/// let pin = BankPin::new(1, Pin::P03);
+/// assert_eq!(pin.flat_index(), 27);
+/// assert_eq!(BankPin::from_flat_index(27), pin);
+/// assert_eq!(pin.to_string(), "1:P03");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BankPin {
+    /// Index of the device in the bank.
+    pub device_index: usize,
+    /// Pin on that device.
+    pub pin: Pin,
+}
+
+impl BankPin {
+    /// Creates the bank pin for `pin` of device `device_index`.
+    pub const fn new(device_index: usize, pin: Pin) -> Self {
+        Self { device_index, pin }
+    }
+
+    /// Returns the bank pin with the given flat index.
+    pub const fn from_flat_index(index: usize) -> Self {
+        Self {
+            device_index: index / 24,
+            pin: match Pin::from_index((index % 24) as u8) {
+                Some(pin) => pin,
+                None => Pin::P00,
+            },
+        }
+    }
+
+    /// Returns the flat index of the pin across the bank.
+    pub const fn flat_index(self) -> usize {
+        self.device_index * 24 + self.pin as usize
+    }
+
+    /// Splits `pins` into one pin mask per device (bit `n` = pin index `n`); pins of devices
+    /// beyond `N` are ignored.
+    pub fn device_masks<const N: usize>(pins: &[BankPin]) -> [u32; N] {
+        let mut masks = [0u32; N];
+        for pin in pins {
+            if let Some(mask) = masks.get_mut(pin.device_index) {
+                *mask |= 1 << pin.pin as u32;
+            }
+        }
+        masks
+    }
+}
+
+/// Input levels read from an [`ExpanderBank`] in which some devices may be missing.
+///
+/// Returned by [`ExpanderBank::read_inputs`] and [`ExpanderBank::get_pin_inputs`]. Devices
+/// marked optional with [`ExpanderBank::set_optional`] that could not be read are left out
+/// of the validity mask instead of failing the whole read, so a system with removable
+/// expansion cards keeps working on the cards that are present.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BankInputs<const N: usize> {
+    values: [u32; N],
+    valid: u32,
+    missing: u32,
+}
+
+impl<const N: usize> BankInputs<N> {
+    /// Returns the levels of device `index` (bit `n` = pin index `n`), or `None` if it was
+    /// not read (it failed, or the read requested none of its pins) or is out of range.
+    pub fn device(&self, index: usize) -> Option<u32> {
+        if self.valid & (1 << index) != 0 {
+            self.values.get(index).copied()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the level of `pin`, or `None` if its device was not read.
+    pub fn pin(&self, pin: BankPin) -> Option<PinState> {
+        let levels = self.device(pin.device_index)?;
+        Some(if levels & (1 << pin.pin as u32) != 0 {
+            PinState::High
+        } else {
+            PinState::Low
+        })
+    }
+
+    /// Returns the levels of every device; devices that were not read report 0.
+    pub fn values(&self) -> [u32; N] {
+        self.values
+    }
+
+    /// Returns the devices that were read (bit `i` = device `i`).
+    pub fn valid_mask(&self) -> u32 {
+        self.valid
+    }
+
+    /// Returns the optional devices left out because they failed (bit `i` = device `i`).
+    pub fn missing_mask(&self) -> u32 {
+        self.missing
+    }
+
+    /// Returns `true` if no optional device was left out.
+    pub fn is_complete(&self) -> bool {
+        self.missing == 0
+    }
+}
+
+impl core::fmt::Display for BankPin {
+    /// Prints `1:P03`.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}:{}", self.device_index, self.pin)
+    }
+}
+
+/// A fixed set of `N` expanders that are driven together.
+///
+/// Each device keeps its own driver instance (and therefore its own [`RegisterCache`](crate::RegisterCache)).
+/// The devices usually share one physical bus through a bus-sharing wrapper such as
+/// `embedded-hal-bus`'s `RefCellDevice`, one wrapper per device.
+///
+/// State is kept per device: besides its driver's cache and link health, every device has
+/// its own last input image and [`InputWatcher`]. A device failing in
+/// [`ExpanderBank::all_inputs`] keeps its previous image and reports no events, so one flaky
+/// expander cannot corrupt the view of the others.
+pub struct ExpanderBank<I2C, const N: usize, D = NoDelay> {
+    devices: [Tca6424<I2C, D>; N],
+    inputs: [Option<u32>; N],
+    watchers: [InputWatcher; N],
+    changes: [InputChanges; N],
+    failed: u32,
+    optional: u32,
+}
+
+impl<I2C, const N: usize, D> ExpanderBank<I2C, N, D> {
+    /// Creates a bank from `N` driver instances (at most 32). Device indices follow the
+    /// array order.
+    pub fn new(devices: [Tca6424<I2C, D>; N]) -> Self {
+        const { assert!(N <= 32, "a bank holds at most 32 devices") };
+        Self {
+            devices,
+            inputs: [None; N],
+            watchers: [InputWatcher::new(); N],
+            changes: [InputChanges::default(); N],
+            failed: 0,
+            optional: 0,
+        }
+    }
+
+    /// Returns the number of devices in the bank.
+    pub const fn len(&self) -> usize {
+        N
+    }
+
+    /// Returns `true` if the bank holds no devices.
+    pub const fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /// Returns the driver of device `index`, or `None` if out of range.
+    pub fn device(&mut self, index: usize) -> Option<&mut Tca6424<I2C, D>> {
+        self.devices.get_mut(index)
+    }
+
+    /// Returns all drivers in device order.
+    pub fn devices_mut(&mut self) -> &mut [Tca6424<I2C, D>; N] {
+        &mut self.devices
+    }
+
+    /// Returns the writes [`ExpanderBank::apply_all`] issues for `frames`, as
+    /// `(device index, write)` pairs in bus order.
+    pub fn plan(
+        &self,
+        frames: &[[u8; 3]; N],
+    ) -> impl Iterator<Item = (usize, PlannedWrite)> + use<I2C, N, D> {
+        let frames = *frames;
+        frames.into_iter().enumerate().filter_map(|(index, frame)| {
+            Some((index, PlannedWrite::new(Register::OutputPort0, &frame)?))
+        })
+    }
+
+    /// Returns the input watcher of device `index`, to change its subscriptions, or `None` if
+    /// out of range.
+    pub fn watcher_mut(&mut self, index: usize) -> Option<&mut InputWatcher> {
+        self.watchers.get_mut(index)
+    }
+
+    /// Returns the last input image read from device `index` by
+    /// [`ExpanderBank::all_inputs`], or `None` before its first successful read.
+    pub fn inputs(&self, index: usize) -> Option<u32> {
+        self.inputs.get(index).copied().flatten()
+    }
+
+    /// Returns the subscribed changes of device `index` found by the last
+    /// [`ExpanderBank::all_inputs`]; empty if its read failed or it is out of range.
+    pub fn changes(&self, index: usize) -> InputChanges {
+        self.changes.get(index).copied().unwrap_or_default()
+    }
+
+    /// Returns the devices whose read failed in the last [`ExpanderBank::all_inputs`] (bit
+    /// `i` = device `i`).
+    pub fn failed_devices(&self) -> u32 {
+        self.failed
+    }
+
+    /// Marks device `index` as optional, or as required again. Ignored if there is no such
+    /// device.
+    ///
+    /// A failing optional device, such as an unplugged expansion card, is left out of the
+    /// result of [`ExpanderBank::read_inputs`] and [`ExpanderBank::get_pin_inputs`]; a failing
+    /// required device fails the read. Devices are required by default.
+    pub fn set_optional(&mut self, index: usize, optional: bool) {
+        if index < N {
+            if optional {
+                self.optional |= 1 << index;
+            } else {
+                self.optional &= !(1 << index);
+            }
+        }
+    }
+
+    /// Returns the optional devices (bit `i` = device `i`).
+    pub fn optional_devices(&self) -> u32 {
+        self.optional
+    }
+
+    /// Returns the link state of every device.
+    #[cfg(feature = "hot-plug")]
+    pub fn link_states(&self) -> [LinkState; N] {
+        self.devices.each_ref().map(|device| device.link_state())
+    }
+
+    /// Returns the driver of the device holding `pin`.
+    fn device_of<E: core::fmt::Debug>(
+        &mut self,
+        pin: BankPin,
+    ) -> Result<&mut Tca6424<I2C, D>, Error<E>> {
+        self.devices
+            .get_mut(pin.device_index)
+            .ok_or(Error::InvalidRegisterOrPin)
+    }
+
+    /// Releases the driver instances.
+    pub fn into_inner(self) -> [Tca6424<I2C, D>; N] {
+        self.devices
+    }
+}
+
+/// Interleaved input polling across the devices of an [`ExpanderBank`] under a bus budget.
+///
+/// Every call to [`ExpanderBank::scan`] is one cycle of the application loop and reads the
+/// inputs of at most `budget` devices, one auto-increment transaction each, picking up
+/// round-robin where the previous cycle stopped. The bus time a cycle spends on input
+/// polling therefore stays bounded when devices are added; what grows is the time until
+/// every device has been read once, [`ScanScheduler::sweep_cycles`] cycles.
+///
+/// By default every device is polled on all three ports; restrict a device to the ports
+/// holding inputs, or exclude it, with [`ScanScheduler::with_ports`].
+#[derive(Debug, Clone)]
+pub struct ScanScheduler<const N: usize> {
+    ports: [u8; N],
+    budget: usize,
+    next: usize,
+    inputs: [Option<u32>; N],
+    changed: u32,
+}
+
+impl<const N: usize> ScanScheduler<N> {
+    /// Creates a scheduler reading at most `budget` devices per cycle (at least one).
+    pub const fn new(budget: usize) -> Self {
+        const { assert!(N <= 32, "a scan scheduler covers at most 32 devices") };
+        Self {
+            ports: [0b111; N],
+            budget: if budget == 0 { 1 } else { budget },
+            next: 0,
+            inputs: [None; N],
+            changed: 0,
+        }
+    }
+
+    /// Polls only the ports of `ports` (bit `p` = port `p`) on device `index`; `0` excludes
+    /// the device. Ignored if there is no such device.
+    pub fn with_ports(mut self, index: usize, ports: u8) -> Self {
+        if let (Some(device_ports), Some(inputs)) =
+            (self.ports.get_mut(index), self.inputs.get_mut(index))
+        {
+            *device_ports = ports & 0b111;
+            *inputs = None;
+        }
+        self
+    }
+
+    /// Returns the maximum number of devices read per cycle.
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    /// Sets the maximum number of devices read per cycle (at least one).
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget.max(1);
+    }
+
+    /// Returns the number of cycles until every polled device has been read once: the
+    /// worst-case input latency in cycles.
+    pub fn sweep_cycles(&self) -> usize {
+        self.polled().count().div_ceil(self.budget)
+    }
+
+    /// Returns the last input image of device `index` (bit `n` = pin index `n`, unpolled
+    /// ports read as 0), or `None` before its first read.
+    pub fn inputs(&self, index: usize) -> Option<u32> {
+        self.inputs.get(index).copied().flatten()
+    }
+
+    /// Returns the devices whose inputs changed since the last call (bit `i` = device `i`),
+    /// and clears the set. A device's first read counts as a change.
+    pub fn take_changed(&mut self) -> u32 {
+        core::mem::take(&mut self.changed)
+    }
+
+    /// Returns the indices of the polled devices.
+    fn polled(&self) -> impl Iterator<Item = usize> + '_ {
+        self.ports
+            .iter()
+            .enumerate()
+            .filter(|&(_, &ports)| ports != 0)
+            .map(|(index, _)| index)
+    }
+
+    /// Records the bytes read from device `index`, starting at port `first`.
+    fn record(&mut self, index: usize, first: usize, bytes: &[u8]) -> bool {
+        let (Some(&ports), Some(inputs)) = (self.ports.get(index), self.inputs.get_mut(index))
+        else {
+            return false;
+        };
+        let mut image = 0u32;
+        for (offset, byte) in bytes.iter().enumerate() {
+            let port = first + offset;
+            if ports & (1 << port) != 0 {
+                image |= u32::from(*byte) << (port * 8);
+            }
+        }
+        let changed = *inputs != Some(image);
+        *inputs = Some(image);
+        if changed {
+            self.changed |= 1 << index;
+        }
+        changed
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "ExpanderBank",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, const N: usize, D> ExpanderBank<I2C, N, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Writes a complete output frame to every device, back to back.
+    ///
+    /// `frames[i]` holds the Output Port 0-2 values of device `i`. Each device is updated with
+    /// a single auto-increment write straight from its frame, and no work is done between the
+    /// writes, which keeps the skew between expanders driving parts of the same display or
+    /// actuator array as small as the bus allows.
+    ///
+    /// The update stops at the first failing device; devices with a lower index have already
+    /// been written.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn apply_all(&mut self, frames: &[[u8; 3]; N]) -> Result<(), Error<I2C::Error>> {
+        let start_register = Register::of(RegisterKind::Output, Port::Port0);
+        for (device, frame) in self.devices.iter_mut().zip(frames) {
+            device.write_registers_ai(start_register, frame).await?;
+        }
+        Ok(())
+    }
+
+    /// Reads the inputs of every device, one auto-increment transaction each, and feeds each
+    /// image to the device's own watcher (see [`ExpanderBank::changes`]).
+    ///
+    /// A failing device does not stop the others: it keeps its previous image (0 before the
+    /// first successful read), reports no changes and is listed in
+    /// [`ExpanderBank::failed_devices`]. Its driver tracks the failure as usual, so hot-plug
+    /// tracking and automatic re-initialisation apply per device.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the input image of every device (bit `n` = pin index `n`).
+    pub async fn all_inputs(&mut self) -> [u32; N] {
+        self.failed = 0;
+        let state = (self.inputs.iter_mut())
+            .zip(self.changes.iter_mut())
+            .zip(self.watchers.iter_mut());
+        for (index, (device, ((inputs, changes), watcher))) in
+            self.devices.iter_mut().zip(state).enumerate()
+        {
+            let mut values = [0u8; 4];
+            let result = device
+                .read_registers_ai(Register::InputPort0, &mut values[..3])
+                .await;
+            if result.is_err() {
+                self.failed |= 1 << index;
+                *changes = InputChanges::default();
+                continue;
+            }
+            let image = u32::from_le_bytes(values);
+            *inputs = Some(image);
+            *changes = watcher.update_with_inversion(image, u32::MAX, device.inverted_inputs());
+        }
+        self.inputs.map(Option::unwrap_or_default)
+    }
+
+    /// Configures the direction of a pin of the bank.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails or the bank
+    /// has no device `pin.device_index`.
+    pub async fn set_pin_direction(
+        &mut self,
+        pin: BankPin,
+        direction: PinDirection,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.device_of(pin)?
+            .set_pin_direction(pin.pin, direction)
+            .await
+    }
+
+    /// Drives a pin of the bank high or low; `state` is taken as by
+    /// [`Tca6424::set_pin_output`].
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails or the bank
+    /// has no device `pin.device_index`.
+    pub async fn set_pin_output(
+        &mut self,
+        pin: BankPin,
+        state: impl Into<PinState>,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.device_of(pin)?.set_pin_output(pin.pin, state).await
+    }
+
+    /// Reads the input level of a pin of the bank.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(PinState)` on success, or an `Error` if an I2C bus operation fails or the
+    /// bank has no device `pin.device_index`.
+    pub async fn get_pin_input_state(
+        &mut self,
+        pin: BankPin,
+    ) -> Result<PinState, Error<I2C::Error>> {
+        self.device_of(pin)?.get_pin_input_state(pin.pin).await
+    }
+
+    /// Drives several pins of the bank, each to its own level.
+    ///
+    /// The pins are grouped per device and each device is updated with one masked write per
+    /// touched port, in device order. Every pin is checked before the first write, so an
+    /// out-of-range device leaves all outputs unchanged; a bus failure stops the update with
+    /// the devices before it already written.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails or a pin
+    /// names a device the bank does not have.
+    pub async fn set_pin_outputs(
+        &mut self,
+        pins: &[(BankPin, PinState)],
+    ) -> Result<(), Error<I2C::Error>> {
+        let mut high = [0u32; N];
+        let mut low = [0u32; N];
+        for &(pin, state) in pins {
+            let masks = match state {
+                PinState::High => &mut high,
+                PinState::Low => &mut low,
+            };
+            let mask = masks
+                .get_mut(pin.device_index)
+                .ok_or(Error::InvalidRegisterOrPin)?;
+            *mask |= 1 << pin.pin as u32;
+        }
+        for ((device, high), low) in self.devices.iter_mut().zip(high).zip(low) {
+            if high | low != 0 {
+                device.write(high, low).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the inputs of every device, one auto-increment transaction each.
+    ///
+    /// Unlike [`ExpanderBank::all_inputs`], this read leaves the stored images and watchers
+    /// alone. A failing optional device (see [`ExpanderBank::set_optional`]) is left out of
+    /// the validity mask; a failing required device fails the read.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the levels of the devices that were read, or an `Error` if a required
+    /// device fails.
+    pub async fn read_inputs(&mut self) -> Result<BankInputs<N>, Error<I2C::Error>> {
+        self.read_masked([u32::MAX; N]).await
+    }
+
+    /// Reads the input levels of several pins of the bank, reading only the ports that hold
+    /// them.
+    ///
+    /// A failing optional device (see [`ExpanderBank::set_optional`]) is left out of the
+    /// validity mask; a failing required device fails the read.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the levels of the requested pins (unrequested pins read as low), or an
+    /// `Error` if an I2C bus operation on a required device fails or a pin names a device
+    /// the bank does not have.
+    pub async fn get_pin_inputs(
+        &mut self,
+        pins: &[BankPin],
+    ) -> Result<BankInputs<N>, Error<I2C::Error>> {
+        if pins.iter().any(|pin| pin.device_index >= N) {
+            return Err(Error::InvalidRegisterOrPin);
+        }
+        self.read_masked(BankPin::device_masks::<N>(pins)).await
+    }
+
+    /// Reads the pins of `masks` per device, leaving failing optional devices out.
+    async fn read_masked(&mut self, masks: [u32; N]) -> Result<BankInputs<N>, Error<I2C::Error>> {
+        let mut inputs = BankInputs {
+            values: [0; N],
+            valid: 0,
+            missing: 0,
+        };
+        let devices = self.devices.iter_mut().zip(inputs.values.iter_mut());
+        for (index, ((device, value), mask)) in devices.zip(masks).enumerate() {
+            let mask = mask & 0x00FF_FFFF;
+            if mask == 0 {
+                continue;
+            }
+            let result = if mask == 0x00FF_FFFF {
+                let mut values = [0u8; 4];
+                device
+                    .read_registers_ai(Register::InputPort0, &mut values[..3])
+                    .await
+                    .map(|()| u32::from_le_bytes(values))
+            } else {
+                device.read(mask).await
+            };
+            match result {
+                Ok(levels) => {
+                    *value = levels;
+                    inputs.valid |= 1 << index;
+                }
+                Err(_) if self.optional & (1 << index) != 0 => inputs.missing |= 1 << index,
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(inputs)
+    }
+
+    /// Runs one cycle of `scheduler`: reads the inputs of the next devices in round-robin
+    /// order, at most [`ScanScheduler::budget`] of them, one auto-increment transaction each
+    /// spanning the device's polled ports.
+    ///
+    /// The cycle stops at the first failing device; the next cycle continues with the device
+    /// after it, so an unplugged expander cannot starve the others.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the devices whose inputs changed in this cycle (bit `i` = device `i`), or an
+    /// `Error` if an I2C bus operation fails.
+    pub async fn scan(
+        &mut self,
+        scheduler: &mut ScanScheduler<N>,
+    ) -> Result<u32, Error<I2C::Error>> {
+        let mut changed = 0;
+        let polled = scheduler.polled().count();
+        let mut reads = 0;
+        let mut index = scheduler.next;
+        while reads < scheduler.budget.min(polled) {
+            index %= N;
+            let ports = scheduler.ports.get(index).copied().unwrap_or(0);
+            if ports == 0 {
+                index += 1;
+                continue;
+            }
+            scheduler.next = (index + 1) % N;
+            reads += 1;
+            // The first and last polled port.
+            let (first, last) = (
+                ports.trailing_zeros() as usize,
+                7 - ports.leading_zeros() as usize,
+            );
+            let mut bytes = [0u8; 3];
+            let bytes = bytes.get_mut(..=last - first).unwrap_or_default();
+            if let Some(device) = self.devices.get_mut(index) {
+                device
+                    .read_registers_ai(
+                        Register::of(RegisterKind::Input, Port::wrapping(first)),
+                        bytes,
+                    )
+                    .await?;
+            }
+            if scheduler.record(index, first, bytes) {
+                changed |= 1 << index;
+            }
+            index += 1;
+        }
+        Ok(changed)
+    }
+}
@@ -0,0 +1,79 @@
+//! Interactive bring-up shell for a TCA6424 on a Linux I2C bus.
+//!
+//! ```text
+//! tca6424-cli [/dev/i2c-N] [address]
+//! ```
+//!
+//! The bus defaults to `/dev/i2c-1` and the address to `0x22`. Type `help` for the commands.
+
+use std::io::{BufRead, Write};
+
+use linux_embedded_hal::I2cdev;
+use tca6424::cli::{self, Command, Flow, StdDelay};
+use tca6424::shared_bus::Eh02I2c;
+use tca6424::{DEFAULT_ADDRESS, Tca6424};
+
+fn parse_address(text: &str) -> Option<u8> {
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u8::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+fn main() -> std::process::ExitCode {
+    let mut args = std::env::args().skip(1);
+    let path = args.next().unwrap_or_else(|| "/dev/i2c-1".into());
+    let address = match args.next().map(|text| parse_address(&text)) {
+        Some(Some(address)) => address,
+        Some(None) => {
+            eprintln!("invalid address, expected e.g. 0x22");
+            return std::process::ExitCode::FAILURE;
+        }
+        None => DEFAULT_ADDRESS,
+    };
+
+    let bus = match I2cdev::new(&path) {
+        Ok(bus) => bus,
+        Err(error) => {
+            eprintln!("cannot open {path}: {error}");
+            return std::process::ExitCode::FAILURE;
+        }
+    };
+    let mut tca = match Tca6424::new_checked(Eh02I2c::new(bus), address) {
+        Ok(tca) => tca.with_delay(StdDelay),
+        Err(error) => {
+            eprintln!("cannot create driver: {error:?}");
+            return std::process::ExitCode::FAILURE;
+        }
+    };
+
+    println!("tca6424 at {address:#04x} on {path}; type `help` for commands");
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    loop {
+        print!("> ");
+        let _ = stdout.flush();
+        let mut line = String::new();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) => return std::process::ExitCode::SUCCESS,
+            Ok(_) => {}
+            Err(error) => {
+                eprintln!("{error}");
+                return std::process::ExitCode::FAILURE;
+            }
+        }
+        let command = match line.parse::<Command>() {
+            Ok(command) => command,
+            Err(cli::ParseError::Empty) => continue,
+            Err(error) => {
+                println!("{error}");
+                continue;
+            }
+        };
+        match cli::execute(&mut tca, command, &mut stdout) {
+            Ok(Flow::Continue) => {}
+            Ok(Flow::Quit) => return std::process::ExitCode::SUCCESS,
+            Err(error) => println!("{error}"),
+        }
+    }
+}
@@ -0,0 +1,216 @@
+//! Worst-case number of bus transfers of the driver methods, for bus-occupancy budgets.
+
+use crate::{RegisterKind, Tca6424};
+
+/// Which register reads a driver answers without a bus transfer, returned by
+/// [`Tca6424::cache_mode`]; selects the figure of a [`TransferBound`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CacheMode {
+    /// Every register read is a transfer (the default).
+    #[default]
+    Uncached,
+    /// Output Port reads are answered from the register cache, which holds all three
+    /// Output Port registers (see [`Tca6424::set_cached_output_reads`]).
+    CachedOutputs,
+    /// No read is a transfer: reads are answered from the register cache or fail (see
+    /// [`Tca6424::set_write_only`]).
+    WriteOnly,
+}
+
+impl<I2C, D> Tca6424<I2C, D> {
+    /// Returns the cache mode the driver is in now.
+    ///
+    /// With cached output reads enabled the driver is only in [`CacheMode::CachedOutputs`]
+    /// once the cache holds every Output Port register; before that an output read can
+    /// still go to the device, as in [`CacheMode::Uncached`]. No transfer is made.
+    pub fn cache_mode(&self) -> CacheMode {
+        if self.write_only {
+            CacheMode::WriteOnly
+        } else if self.cached_output_reads && self.cache.family(RegisterKind::Output).is_some() {
+            CacheMode::CachedOutputs
+        } else {
+            CacheMode::Uncached
+        }
+    }
+}
+
+/// The maximum number of I2C transactions one call of a driver method issues, in each
+/// [`CacheMode`].
+///
+/// Every bus-facing method of [`Tca6424`] has its bound as an associated const named after
+/// it, so a hard-real-time budget can be checked where the call is made:
+///
+/// ```
+/// use tca6424::{CacheMode, TransferBound};
+///
+/// // A read-modify-write: read the Output Port register, write it back
+/// assert_eq!(TransferBound::SET_PIN_OUTPUT.max(CacheMode::Uncached), 2);
+/// // Output reads from the cache are free
+/// assert_eq!(TransferBound::GET_PORT_OUTPUT_STATE.max(CacheMode::CachedOutputs), 0);
+/// ```
+///
+/// A bound holds whatever the outcome, errors included, with the transfer options at their
+/// defaults. Each option multiplies the transfers it affects, and the bounds do not:
+///
+/// - A [`RetryPolicy`](crate::RetryPolicy) repeats a transfer up to
+///   [`RetryPolicy::retries`](crate::RetryPolicy::retries) more times, and an
+///   [`InputFallback`](crate::InputFallback) repeats an input read up to its `retries`.
+/// - [`ReadSequence::WriteThenRead`](crate::ReadSequence::WriteThenRead) makes every read
+///   two transactions.
+/// - [`TransferStrategy::PerRegisterOnly`](crate::TransferStrategy::PerRegisterOnly) makes a
+///   transfer of the three registers of a family three transactions, and
+///   [`TransferStrategy::Auto`](crate::TransferStrategy::Auto) up to four (the failed
+///   auto-increment transfer and its split retry).
+/// - An armed fail-safe policy or automatic re-initialisation adds its own writes after a
+///   failed transfer.
+///
+/// Each bound has a test that counts the transactions of its method in every cache mode,
+/// and the bounds are checked at compile time for consistency: no cache mode needs more
+/// transfers than [`CacheMode::Uncached`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TransferBound {
+    uncached: u8,
+    cached_outputs: u8,
+    write_only: u8,
+}
+
+impl TransferBound {
+    /// A read-modify-write of one register, reading it from the device.
+    const READ_MODIFY_WRITE: Self = Self::new(2, 2, 1);
+    /// A read of one register, or one auto-increment read, not served from the cache.
+    const READ: Self = Self::new(1, 1, 0);
+    /// A read of the Output Port registers served from the cache when possible.
+    const CACHED_OUTPUT_READ: Self = Self::new(1, 0, 0);
+    /// One write, single-register or auto-increment.
+    const WRITE: Self = Self::new(1, 1, 1);
+
+    /// `Tca6424::set_pin_direction`.
+    pub const SET_PIN_DIRECTION: Self = Self::READ_MODIFY_WRITE;
+    /// `Tca6424::get_pin_direction`.
+    pub const GET_PIN_DIRECTION: Self = Self::READ;
+    /// `Tca6424::set_pin_output`; the Output Port is read from the device in every mode
+    /// but write-only.
+    pub const SET_PIN_OUTPUT: Self = Self::READ_MODIFY_WRITE;
+    /// `Tca6424::get_pin_output_state`.
+    pub const GET_PIN_OUTPUT_STATE: Self = Self::CACHED_OUTPUT_READ;
+    /// `Tca6424::get_pin_input_state`.
+    pub const GET_PIN_INPUT_STATE: Self = Self::READ;
+    /// `Tca6424::set_pin`.
+    pub const SET_PIN: Self = Self::SET_PIN_OUTPUT;
+    /// `Tca6424::is_high`.
+    pub const IS_HIGH: Self = Self::GET_PIN_INPUT_STATE;
+    /// `Tca6424::is_low`.
+    pub const IS_LOW: Self = Self::GET_PIN_INPUT_STATE;
+    /// `Tca6424::set_pin_polarity_inversion`.
+    pub const SET_PIN_POLARITY_INVERSION: Self = Self::READ_MODIFY_WRITE;
+    /// `Tca6424::get_pin_polarity_inversion`.
+    pub const GET_PIN_POLARITY_INVERSION: Self = Self::READ;
+    /// `Tca6424::get_pin_input_raw`: the Polarity Inversion register when it is not cached,
+    /// then the Input Port.
+    pub const GET_PIN_INPUT_RAW: Self = Self::new(2, 2, 0);
+    /// `Tca6424::set_port_direction`.
+    pub const SET_PORT_DIRECTION: Self = Self::WRITE;
+    /// `Tca6424::get_port_direction`.
+    pub const GET_PORT_DIRECTION: Self = Self::READ;
+    /// `Tca6424::set_port_output`.
+    pub const SET_PORT_OUTPUT: Self = Self::WRITE;
+    /// `Tca6424::get_port_output_state`.
+    pub const GET_PORT_OUTPUT_STATE: Self = Self::CACHED_OUTPUT_READ;
+    /// `Tca6424::get_port_input_state`.
+    pub const GET_PORT_INPUT_STATE: Self = Self::READ;
+    /// `Tca6424::read_port_inputs`.
+    pub const READ_PORT_INPUTS: Self = Self::GET_PORT_INPUT_STATE;
+    /// `Tca6424::read_port_outputs`.
+    pub const READ_PORT_OUTPUTS: Self = Self::GET_PORT_OUTPUT_STATE;
+    /// `Tca6424::read_ports`.
+    pub const READ_PORTS: Self = Self::READ;
+    /// `Tca6424::write_pins_masked`; a full mask is a single write.
+    pub const WRITE_PINS_MASKED: Self = Self::READ_MODIFY_WRITE;
+    /// `Tca6424::write_directions_masked`; a full mask is a single write.
+    pub const WRITE_DIRECTIONS_MASKED: Self = Self::READ_MODIFY_WRITE;
+    /// `Tca6424::get_register`, for every register kind.
+    pub const GET_REGISTER: Self = Self::READ;
+    /// `Tca6424::set_register`.
+    pub const SET_REGISTER: Self = Self::WRITE;
+    /// `Tca6424::modify`, for every register kind.
+    pub const MODIFY: Self = Self::READ_MODIFY_WRITE;
+    /// `Tca6424::set_port_polarity_inversion`.
+    pub const SET_PORT_POLARITY_INVERSION: Self = Self::WRITE;
+    /// `Tca6424::get_port_polarity_inversion`.
+    pub const GET_PORT_POLARITY_INVERSION: Self = Self::READ;
+    /// `Tca6424::set_ports_direction_ai`.
+    pub const SET_PORTS_DIRECTION_AI: Self = Self::WRITE;
+    /// `Tca6424::get_ports_direction_ai`.
+    pub const GET_PORTS_DIRECTION_AI: Self = Self::READ;
+    /// `Tca6424::get_ports_direction`.
+    pub const GET_PORTS_DIRECTION: Self = Self::READ;
+    /// `Tca6424::set_ports_output_ai`.
+    pub const SET_PORTS_OUTPUT_AI: Self = Self::WRITE;
+    /// `Tca6424::get_ports_output_state_ai`; always read from the device, except in
+    /// write-only mode.
+    pub const GET_PORTS_OUTPUT_STATE_AI: Self = Self::READ;
+    /// `Tca6424::get_ports_output_state`.
+    pub const GET_PORTS_OUTPUT_STATE: Self = Self::CACHED_OUTPUT_READ;
+    /// `Tca6424::get_ports_input_state_ai`.
+    pub const GET_PORTS_INPUT_STATE_AI: Self = Self::READ;
+    /// `Tca6424::get_ports_input_state`.
+    pub const GET_PORTS_INPUT_STATE: Self = Self::READ;
+    /// `Tca6424::set_ports_polarity_inversion_ai`.
+    pub const SET_PORTS_POLARITY_INVERSION_AI: Self = Self::WRITE;
+    /// `Tca6424::get_ports_polarity_inversion_ai`.
+    pub const GET_PORTS_POLARITY_INVERSION_AI: Self = Self::READ;
+    /// `Tca6424::get_ports_polarity_inversion`.
+    pub const GET_PORTS_POLARITY_INVERSION: Self = Self::READ;
+    /// `Tca6424::set_pin_interrupt_mask`.
+    pub const SET_PIN_INTERRUPT_MASK: Self = Self::READ_MODIFY_WRITE;
+    /// `Tca6424::get_pin_interrupt_mask`.
+    pub const GET_PIN_INTERRUPT_MASK: Self = Self::READ;
+    /// `Tca6424::set_port_interrupt_mask`.
+    pub const SET_PORT_INTERRUPT_MASK: Self = Self::WRITE;
+    /// `Tca6424::get_port_interrupt_mask`.
+    pub const GET_PORT_INTERRUPT_MASK: Self = Self::READ;
+    /// `Tca6424::set_ports_interrupt_mask_ai`.
+    pub const SET_PORTS_INTERRUPT_MASK_AI: Self = Self::WRITE;
+    /// `Tca6424::get_ports_interrupt_mask_ai`.
+    pub const GET_PORTS_INTERRUPT_MASK_AI: Self = Self::READ;
+    /// `Tca6424::get_ports_interrupt_mask`.
+    pub const GET_PORTS_INTERRUPT_MASK: Self = Self::READ;
+    /// `Tca6424::set_initial_output_state`.
+    pub const SET_INITIAL_OUTPUT_STATE: Self = Self::WRITE;
+    /// `Tca6424::snapshot`: one auto-increment read per register family. In write-only mode
+    /// the Input family fails first.
+    pub const SNAPSHOT: Self = Self::new(5, 5, 0);
+    /// `Tca6424::device_state`: the Input, Output and Configuration families.
+    pub const DEVICE_STATE: Self = Self::new(3, 3, 0);
+    /// `Tca6424::refresh_output_cache`.
+    pub const REFRESH_OUTPUT_CACHE: Self = Self::READ;
+    /// `Tca6424::refresh_polarity_cache`.
+    pub const REFRESH_POLARITY_CACHE: Self = Self::READ;
+    /// `Tca6424::poll_input_events`.
+    pub const POLL_INPUT_EVENTS: Self = Self::READ;
+    /// `Tca6424::get_interrupt_sources`.
+    pub const GET_INTERRUPT_SOURCES: Self = Self::READ;
+    /// `PinScheduler::run`: a read-modify-write per port with a due change.
+    pub const PIN_SCHEDULER_RUN: Self = Self::new(6, 6, 3);
+
+    const fn new(uncached: u8, cached_outputs: u8, write_only: u8) -> Self {
+        // A cache only ever saves transfers
+        assert!(cached_outputs <= uncached && write_only <= uncached);
+        Self {
+            uncached,
+            cached_outputs,
+            write_only,
+        }
+    }
+
+    /// Returns the maximum number of transactions in cache mode `mode`.
+    pub const fn max(&self, mode: CacheMode) -> u8 {
+        match mode {
+            CacheMode::Uncached => self.uncached,
+            CacheMode::CachedOutputs => self.cached_outputs,
+            CacheMode::WriteOnly => self.write_only,
+        }
+    }
+}
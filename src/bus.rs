@@ -0,0 +1,27 @@
+//! The bus the driver talks through.
+
+#[cfg(not(feature = "async"))]
+use embedded_hal::i2c::I2c;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c;
+
+/// An I2C bus the driver can talk through: the bound of the `I2C` parameter of the driver's
+/// methods.
+///
+/// Implemented for every `I2c` implementation (`embedded-hal` in sync mode,
+/// `embedded-hal-async` with the `async` feature), which covers each way of handing the
+/// driver a bus:
+///
+/// - `&mut I2C`, borrowing a bus for the lifetime of the driver,
+/// - an owned `I2C`, moved into the driver and returned by [`Tca64xx::into_inner`],
+/// - the bus-sharing devices of `embedded-hal-bus` (`RefCellDevice`, `CriticalSectionDevice`,
+///   `MutexDevice`, `AtomicDevice`) and `embassy-embedded-hal` (`I2cDevice`,
+///   `I2cDeviceWithConfig`), to share the bus with other drivers.
+///
+/// The trait adds no methods: it names the requirement once, so driver code and application
+/// code generic over the bus write `I2C: BusAccess` in both the sync and the async build.
+///
+/// [`Tca64xx::into_inner`]: crate::Tca64xx::into_inner
+pub trait BusAccess: I2c {}
+
+impl<I2C: I2c + ?Sized> BusAccess for I2C {}
@@ -0,0 +1,102 @@
+//! Shadow copies of the values written to the device registers.
+
+use crate::{Port, RegisterKind};
+
+/// Shadow of the register values the driver has written to the device.
+///
+/// Every successful write through the driver updates the corresponding entry, so the
+/// cache always describes the configuration the application *intended*. It is never
+/// updated from reads: comparing it with what the device reports is how a device reset
+/// or an external reconfiguration is detected.
+///
+/// The Input Port registers are read-only and are never cached.
+///
+/// A register whose write was started but never finished, because the future driving it was
+/// dropped or timed out, is *dirty*: the device may or may not hold the new value, so the
+/// cache reports it as unknown until [`Tca6424::sync_after_cancel`](crate::Tca6424::sync_after_cancel)
+/// reads it back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RegisterCache {
+    values: [[Option<u8>; 3]; 5],
+    dirty: [u8; 5],
+}
+
+impl RegisterCache {
+    /// Creates an empty cache with no known register values.
+    pub const fn new() -> Self {
+        Self {
+            values: [[None; 3]; 5],
+            dirty: [0; 5],
+        }
+    }
+
+    /// Returns the last value written to a register, or `None` if it is unknown or dirty.
+    pub fn get(&self, kind: RegisterKind, port: Port) -> Option<u8> {
+        if self.is_register_dirty(kind, port) {
+            return None;
+        }
+        *port.entry(kind.entry(&self.values))
+    }
+
+    /// Returns the cached values of all three ports of a family, if all are known.
+    pub fn family(&self, kind: RegisterKind) -> Option<[u8; 3]> {
+        match (
+            self.get(kind, Port::Port0),
+            self.get(kind, Port::Port1),
+            self.get(kind, Port::Port2),
+        ) {
+            (Some(p0), Some(p1), Some(p2)) => Some([p0, p1, p2]),
+            _ => None,
+        }
+    }
+
+    /// Records a value for a register and clears its dirty flag. Writes to the Input family
+    /// are ignored.
+    pub fn set(&mut self, kind: RegisterKind, port: Port, value: u8) {
+        if kind != RegisterKind::Input {
+            *port.entry_mut(kind.entry_mut(&mut self.values)) = Some(value);
+            *kind.entry_mut(&mut self.dirty) &= !(1 << port as u8);
+        }
+    }
+
+    /// Forgets the cached value of a single register.
+    pub fn invalidate(&mut self, kind: RegisterKind, port: Port) {
+        *port.entry_mut(kind.entry_mut(&mut self.values)) = None;
+        *kind.entry_mut(&mut self.dirty) &= !(1 << port as u8);
+    }
+
+    /// Returns `true` if a write to the register was interrupted before it completed.
+    pub fn is_register_dirty(&self, kind: RegisterKind, port: Port) -> bool {
+        kind.entry(&self.dirty) & (1 << port as u8) != 0
+    }
+
+    /// Returns `true` if any register is dirty.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.iter().any(|&ports| ports != 0)
+    }
+
+    /// Marks a register as being written; `set` or `abort_write` clears the mark.
+    pub(crate) fn begin_write(&mut self, kind: RegisterKind, port: Port) {
+        if kind != RegisterKind::Input {
+            *kind.entry_mut(&mut self.dirty) |= 1 << port as u8;
+        }
+    }
+
+    /// Clears the mark of a write that failed with an error, keeping the previous value.
+    pub(crate) fn abort_write(&mut self, kind: RegisterKind, port: Port) {
+        *kind.entry_mut(&mut self.dirty) &= !(1 << port as u8);
+    }
+
+    /// Forgets every cached value.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Returns `true` if no register value is known.
+    pub fn is_empty(&self) -> bool {
+        RegisterKind::ALL
+            .iter()
+            .all(|&kind| Port::ALL.iter().all(|&port| self.get(kind, port).is_none()))
+    }
+}
@@ -0,0 +1,182 @@
+//! Capture of the most recent bus transfers for bug reports.
+
+use core::fmt;
+
+use crate::Tca6424;
+
+/// Number of data bytes kept per [`TransferRecord`]; longer transfers are truncated.
+pub const CAPTURED_BYTES: usize = 4;
+
+/// Direction of a captured transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TransferDirection {
+    /// A register read (command byte, then data read after a repeated start).
+    #[default]
+    Read,
+    /// A register write (command byte followed by the data).
+    Write,
+}
+
+/// Outcome of a captured transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TransferOutcome {
+    /// The transfer completed.
+    #[default]
+    Ok,
+    /// The device did not acknowledge.
+    Nack,
+    /// Another bus error was reported.
+    BusError,
+    /// The transfer timed out (async `set_timeout_us` only).
+    Timeout,
+}
+
+/// One bus transfer, as recorded in the capture buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TransferRecord {
+    /// Command byte: register address, with bit 7 set for auto-increment.
+    pub command: u8,
+    /// Read or write.
+    pub direction: TransferDirection,
+    /// Number of data bytes transferred (may exceed [`CAPTURED_BYTES`]).
+    pub len: u8,
+    /// The first data bytes; for a failed read their content is undefined.
+    pub bytes: [u8; CAPTURED_BYTES],
+    /// How the transfer ended.
+    pub outcome: TransferOutcome,
+}
+
+impl TransferRecord {
+    /// Returns the captured data bytes.
+    pub fn data(&self) -> &[u8] {
+        self.bytes
+            .get(..usize::from(self.len))
+            .unwrap_or(&self.bytes)
+    }
+}
+
+impl fmt::Display for TransferRecord {
+    /// Prints `W 0x85 [0x0f 0xf0] Ok`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = match self.direction {
+            TransferDirection::Read => 'R',
+            TransferDirection::Write => 'W',
+        };
+        write!(f, "{} {:#04x} [", direction, self.command)?;
+        for (i, byte) in self.data().iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{:#04x}", byte)?;
+        }
+        if usize::from(self.len) > CAPTURED_BYTES {
+            f.write_str(" ..")?;
+        }
+        write!(f, "] {:?}", self.outcome)
+    }
+}
+
+/// The user-provided ring buffer and its write position.
+#[derive(Debug, Default)]
+pub(crate) struct Capture {
+    buffer: Option<&'static mut [TransferRecord]>,
+    next: usize,
+    len: usize,
+}
+
+impl Capture {
+    pub(crate) const fn new() -> Self {
+        Self {
+            buffer: None,
+            next: 0,
+            len: 0,
+        }
+    }
+
+    /// Appends a transfer, overwriting the oldest record once the buffer is full.
+    pub(crate) fn record<E: embedded_hal::i2c::Error>(
+        &mut self,
+        direction: TransferDirection,
+        command: u8,
+        data: &[u8],
+        result: Option<&Result<(), E>>,
+    ) {
+        let Some(buffer) = self.buffer.as_deref_mut() else {
+            return;
+        };
+        if buffer.is_empty() {
+            return;
+        }
+        let outcome = match result {
+            Some(Ok(())) => TransferOutcome::Ok,
+            Some(Err(error)) => match error.kind() {
+                embedded_hal::i2c::ErrorKind::NoAcknowledge(_) => TransferOutcome::Nack,
+                _ => TransferOutcome::BusError,
+            },
+            None => TransferOutcome::Timeout,
+        };
+        let mut bytes = [0u8; CAPTURED_BYTES];
+        for (byte, value) in bytes.iter_mut().zip(data) {
+            *byte = *value;
+        }
+        let Some(slot) = buffer.get_mut(self.next) else {
+            return;
+        };
+        *slot = TransferRecord {
+            command,
+            direction,
+            len: data.len().min(usize::from(u8::MAX)) as u8,
+            bytes,
+            outcome,
+        };
+        self.next = (self.next + 1) % buffer.len();
+        self.len = (self.len + 1).min(buffer.len());
+    }
+}
+
+impl<I2C, D> Tca6424<I2C, D> {
+    /// Starts recording every bus transfer into `buffer`, keeping the last `buffer.len()`.
+    ///
+    /// Any previous buffer is returned. The buffer is typically a `static` array handed out
+    /// once (for example with `static_cell`), so the history survives until it is read with
+    /// [`Tca6424::trace`] and attached to a field failure report. Requires the `capture`
+    /// feature.
+    pub fn enable_capture(
+        &mut self,
+        buffer: &'static mut [TransferRecord],
+    ) -> Option<&'static mut [TransferRecord]> {
+        let previous = self.capture.buffer.replace(buffer);
+        self.capture.next = 0;
+        self.capture.len = 0;
+        previous
+    }
+
+    /// Stops recording and returns the capture buffer.
+    pub fn disable_capture(&mut self) -> Option<&'static mut [TransferRecord]> {
+        self.capture.next = 0;
+        self.capture.len = 0;
+        self.capture.buffer.take()
+    }
+
+    /// Returns the recorded transfers, oldest first.
+    pub fn trace(&self) -> impl DoubleEndedIterator<Item = &TransferRecord> + '_ {
+        let buffer = self.capture.buffer.as_deref().unwrap_or(&[]);
+        let (newer, older) = if self.capture.len < buffer.len() {
+            (&[][..], buffer.get(..self.capture.len).unwrap_or(buffer))
+        } else {
+            buffer
+                .split_at_checked(self.capture.next)
+                .unwrap_or((buffer, &[]))
+        };
+        older.iter().chain(newer)
+    }
+
+    /// Forgets the recorded transfers, keeping the buffer.
+    pub fn clear_trace(&mut self) {
+        self.capture.next = 0;
+        self.capture.len = 0;
+    }
+}
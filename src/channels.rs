@@ -0,0 +1,140 @@
+//! Logical channel numbering on top of physical pins.
+
+use crate::errors::{ConfigError, Error};
+use crate::{BusAccess, DelayNs, Pin, PinDirection, PinState, Tca6424};
+
+/// Assignment of logical channel numbers to physical pins.
+///
+/// Channel `n` is `pins[n]`. The application protocol addresses channels `0..len()`, and each
+/// board revision supplies its own table, so the same firmware runs on boards with different
+/// pin assignments. A pin may only appear once; this is checked when the map is built, at
+/// compile time for a `const` map.
+///
+/// ```
+/// use tca6424::{ChannelMap, Pin};
+///
+/// const REV_A: ChannelMap = ChannelMap::new(&[Pin::P00, Pin::P01, Pin::P02]);
+/// const REV_B: ChannelMap = ChannelMap::new(&[Pin::P10, Pin::P01, Pin::P27]);
+///
+/// assert_eq!(REV_B.pin(2), Some(Pin::P27));
+/// assert_eq!(REV_A.channel(Pin::P01), Some(1));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ChannelMap {
+    pins: &'static [Pin],
+}
+
+impl ChannelMap {
+    /// Creates a map where channel `n` is `pins[n]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a pin appears more than once.
+    pub const fn new(pins: &'static [Pin]) -> Self {
+        let mut seen = 0u32;
+        let mut rest = pins;
+        while let [pin, tail @ ..] = rest {
+            let bit = 1 << *pin as u8;
+            assert!(seen & bit == 0, "pin assigned to more than one channel");
+            seen |= bit;
+            rest = tail;
+        }
+        Self { pins }
+    }
+
+    /// Returns the number of channels.
+    pub const fn len(&self) -> usize {
+        self.pins.len()
+    }
+
+    /// Returns `true` if the map has no channels.
+    pub const fn is_empty(&self) -> bool {
+        self.pins.is_empty()
+    }
+
+    /// Returns the pin of `channel`, or `None` if the channel is not mapped.
+    pub const fn pin(&self, channel: u8) -> Option<Pin> {
+        match self.pins.split_at_checked(channel as usize) {
+            Some((_, [pin, ..])) => Some(*pin),
+            _ => None,
+        }
+    }
+
+    /// Returns the channel assigned to `pin`, if any.
+    pub fn channel(&self, pin: Pin) -> Option<u8> {
+        self.pins.iter().position(|&p| p == pin).map(|c| c as u8)
+    }
+}
+
+impl<I2C, D> Tca6424<I2C, D> {
+    /// Sets the channel table used by the `*_channel_*` methods.
+    pub fn set_channel_map(&mut self, map: ChannelMap) {
+        self.channels = Some(map);
+    }
+
+    /// Returns the channel table, if one is set.
+    pub fn channel_map(&self) -> Option<ChannelMap> {
+        self.channels
+    }
+}
+
+impl<I2C: embedded_hal::i2c::ErrorType, D> Tca6424<I2C, D> {
+    /// Resolves a logical channel to its pin through the channel table.
+    ///
+    /// # Returns
+    ///
+    /// Returns the pin, or `ConfigError::UnmappedChannel(channel)` if no table is set or the
+    /// table has no such channel.
+    pub fn channel_pin(&self, channel: u8) -> Result<Pin, ConfigError> {
+        self.channels
+            .and_then(|map| map.pin(channel))
+            .ok_or(ConfigError::UnmappedChannel(channel))
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca6424",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> Tca6424<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Sets the direction of a logical channel. See [`Tca6424::set_pin_direction`].
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    pub async fn set_channel_direction(
+        &mut self,
+        channel: u8,
+        direction: PinDirection,
+    ) -> Result<(), Error<I2C::Error>> {
+        let pin = self.channel_pin(channel).map_err(Error::from_config)?;
+        self.set_pin_direction(pin, direction).await
+    }
+
+    /// Sets the output state of a logical channel. See [`Tca6424::set_pin_output`].
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    pub async fn set_channel_output(
+        &mut self,
+        channel: u8,
+        state: impl Into<PinState>,
+    ) -> Result<(), Error<I2C::Error>> {
+        let pin = self.channel_pin(channel).map_err(Error::from_config)?;
+        self.set_pin_output(pin, state).await
+    }
+
+    /// Reads the input state of a logical channel. See [`Tca6424::get_pin_input_state`].
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    pub async fn get_channel_input_state(
+        &mut self,
+        channel: u8,
+    ) -> Result<PinState, Error<I2C::Error>> {
+        let pin = self.channel_pin(channel).map_err(Error::from_config)?;
+        self.get_pin_input_state(pin).await
+    }
+}
@@ -0,0 +1,64 @@
+//! The parts of the TCA64xx family a [`Tca64xx`](crate::Tca64xx) driver can address.
+
+#[cfg(feature = "tca6416")]
+use crate::registers::AUTO_INCREMENT;
+use crate::registers::Register;
+#[cfg(feature = "tca6416")]
+use crate::{Port, RegisterKind};
+
+/// A part of the TCA64xx family, selecting the register map of a
+/// [`Tca64xx`](crate::Tca64xx) driver.
+///
+/// The driver works with the TCA6424 register numbering throughout, so the register cache,
+/// captures and statistics read the same for every part; the chip translates each command
+/// byte into its own just before it goes on the bus.
+pub trait Chip {
+    /// Number of 8-bit ports of the part.
+    const PORTS: usize;
+
+    /// Returns `true` if the part has `register`.
+    fn has(register: Register) -> bool;
+
+    /// Translates a TCA6424 command byte (see [`command_byte`](crate::command_byte)) into the
+    /// part's command byte for the same register.
+    fn command(command_byte: u8) -> u8;
+}
+
+/// The 24-bit TCA6424 and TCA6424A, the default part of a [`Tca64xx`](crate::Tca64xx)
+/// driver.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tca6424Chip;
+
+impl Chip for Tca6424Chip {
+    const PORTS: usize = 3;
+
+    fn has(_register: Register) -> bool {
+        true
+    }
+
+    fn command(command_byte: u8) -> u8 {
+        command_byte
+    }
+}
+
+/// The 16-bit TCA6416A: the register families of the TCA6424 minus Interrupt Mask, over two
+/// ports, and no auto-increment bit (consecutive bytes alternate between the two registers
+/// of a family). Requires the `tca6416` feature.
+#[cfg(feature = "tca6416")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tca6416Chip;
+
+#[cfg(feature = "tca6416")]
+impl Chip for Tca6416Chip {
+    const PORTS: usize = 2;
+
+    fn has(register: Register) -> bool {
+        register.kind() != RegisterKind::InterruptMask && register.port() != Port::Port2
+    }
+
+    fn command(command_byte: u8) -> u8 {
+        // TCA6424 registers are 4 apart per family, TCA6416A registers 2 apart
+        let register = command_byte & !AUTO_INCREMENT;
+        (register >> 2) * 2 + (register & 0x03)
+    }
+}
@@ -0,0 +1,201 @@
+//! Pin ownership tracking for helper subsystems.
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use embedded_hal::i2c::ErrorType;
+
+use crate::errors::ConfigError;
+use crate::{Pin, Tca6424};
+
+/// Proof that a pin has been claimed through [`Tca6424::claim_pin`].
+///
+/// A claim is not `Clone`, so at most one owner can hold it. Helper subsystems take claims
+/// for the pins they drive, which turns two subsystems wired to the same pin into an
+/// initialization error instead of surprising runtime behaviour. The claim remembers the
+/// driver it was taken from, and only that driver accepts it back.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PinClaim {
+    pin: Pin,
+    registry: u32,
+}
+
+impl PinClaim {
+    /// Returns the claimed pin.
+    pub const fn pin(&self) -> Pin {
+        self.pin
+    }
+
+    /// Returns the pin to the pool of `tca`, so it can be claimed for a different role.
+    ///
+    /// Equivalent to [`Tca6424::release_pin`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the claim unchanged if it was taken from another driver; nothing is released.
+    pub fn release<I2C: ErrorType, D>(self, tca: &mut Tca6424<I2C, D>) -> Result<(), Self> {
+        tca.release_pin(self)
+    }
+}
+
+/// Proof that a set of pins has been claimed through [`Tca6424::claim_mask`].
+///
+/// Like [`PinClaim`], a mask claim is not `Clone` and remembers the driver it was taken
+/// from. Releasing it is the only way to return its pins to the pool.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MaskClaim {
+    mask: u32,
+    registry: u32,
+}
+
+impl MaskClaim {
+    /// Returns the claimed pins (bit `n` = pin index `n`).
+    pub const fn mask(&self) -> u32 {
+        self.mask
+    }
+
+    /// Returns the pins to the pool of `tca`.
+    ///
+    /// Equivalent to [`Tca6424::release_mask_claim`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the claim unchanged if it was taken from another driver; nothing is released.
+    pub fn release<I2C: ErrorType, D>(self, tca: &mut Tca6424<I2C, D>) -> Result<(), Self> {
+        tca.release_mask_claim(self)
+    }
+}
+
+/// Identity of the next registry created.
+static NEXT_REGISTRY: AtomicU32 = AtomicU32::new(0);
+
+/// Returns a registry identity not handed out before (until the counter wraps).
+fn next_registry() -> u32 {
+    #[cfg(target_has_atomic = "32")]
+    {
+        NEXT_REGISTRY.fetch_add(1, Ordering::Relaxed)
+    }
+    // Targets without compare-and-swap (thumbv6m) are single-core: a driver created in an
+    // interrupt handler at the wrong moment can at worst share an identity.
+    #[cfg(not(target_has_atomic = "32"))]
+    {
+        let registry = NEXT_REGISTRY.load(Ordering::Relaxed);
+        NEXT_REGISTRY.store(registry.wrapping_add(1), Ordering::Relaxed);
+        registry
+    }
+}
+
+/// Set of claimed pins, one bit per pin index, and the identity stamped on its claims.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ClaimRegistry {
+    claimed: u32,
+    id: u32,
+}
+
+impl ClaimRegistry {
+    pub(crate) fn new() -> Self {
+        Self {
+            claimed: 0,
+            id: next_registry(),
+        }
+    }
+
+    /// Returns the first pin of `mask` that is already claimed.
+    fn first_conflict(&self, mask: u32) -> Option<Pin> {
+        let conflicts = self.claimed & mask;
+        if conflicts == 0 {
+            None
+        } else {
+            Pin::from_index(conflicts.trailing_zeros() as u8)
+        }
+    }
+}
+
+impl<I2C: ErrorType, D> Tca6424<I2C, D> {
+    /// Claims `pin` for exclusive use.
+    ///
+    /// # Returns
+    ///
+    /// Returns the [`PinClaim`], or `ConfigError::PinAlreadyClaimed(pin)` if the pin is already
+    /// owned.
+    pub fn claim_pin(&mut self, pin: Pin) -> Result<PinClaim, ConfigError> {
+        self.claim_bits(1 << pin as u8)?;
+        Ok(self.pin_claim(pin))
+    }
+
+    /// Claims every pin whose bit is set in `mask` (bit `n` = pin index `n`), all or nothing.
+    ///
+    /// # Returns
+    ///
+    /// Returns the [`MaskClaim`] if every pin was free, or `ConfigError::PinAlreadyClaimed`
+    /// naming the lowest conflicting pin, in which case nothing is claimed.
+    pub fn claim_mask(&mut self, mask: u32) -> Result<MaskClaim, ConfigError> {
+        self.claim_bits(mask)?;
+        Ok(MaskClaim {
+            mask: mask & 0x00FF_FFFF,
+            registry: self.claims.id,
+        })
+    }
+
+    /// Releases a claimed pin back into the pool.
+    ///
+    /// The pin keeps its current direction and output level; only its ownership changes.
+    ///
+    /// # Errors
+    ///
+    /// Returns the claim unchanged if it was taken from another driver; nothing is released.
+    pub fn release_pin(&mut self, claim: PinClaim) -> Result<(), PinClaim> {
+        if claim.registry != self.claims.id {
+            return Err(claim);
+        }
+        self.release_mask(1 << claim.pin as u8);
+        Ok(())
+    }
+
+    /// Releases the pins of a claim taken with [`Tca6424::claim_mask`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the claim unchanged if it was taken from another driver; nothing is released.
+    pub fn release_mask_claim(&mut self, claim: MaskClaim) -> Result<(), MaskClaim> {
+        if claim.registry != self.claims.id {
+            return Err(claim);
+        }
+        self.release_mask(claim.mask);
+        Ok(())
+    }
+
+    /// Claims the pins of `mask`, all or nothing, for a helper subsystem that tracks them
+    /// itself.
+    pub(crate) fn claim_bits(&mut self, mask: u32) -> Result<(), ConfigError> {
+        if let Some(pin) = self.claims.first_conflict(mask) {
+            return Err(ConfigError::PinAlreadyClaimed(pin));
+        }
+        self.claims.claimed |= mask & 0x00FF_FFFF;
+        Ok(())
+    }
+
+    /// Returns the claim of `pin`, claimed through [`Tca6424::claim_bits`].
+    pub(crate) fn pin_claim(&self, pin: Pin) -> PinClaim {
+        PinClaim {
+            pin,
+            registry: self.claims.id,
+        }
+    }
+
+    /// Releases the pins of `mask`, claimed through [`Tca6424::claim_bits`].
+    pub(crate) fn release_mask(&mut self, mask: u32) {
+        self.claims.claimed &= !mask;
+    }
+
+    /// Returns `true` if `pin` is currently claimed.
+    pub fn is_claimed(&self, pin: Pin) -> bool {
+        self.claims.claimed & (1 << pin as u8) != 0
+    }
+
+    /// Returns the mask of claimed pins (bit `n` = pin index `n`).
+    pub fn claimed_mask(&self) -> u32 {
+        self.claims.claimed
+    }
+}
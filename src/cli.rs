@@ -0,0 +1,273 @@
+//! Command interpreter of the `tca6424-cli` bring-up shell.
+//!
+//! The shell reads one command per line:
+//!
+//! | Command                 | Effect                                                    |
+//! |-------------------------|-----------------------------------------------------------|
+//! | `dir P04 out`           | Makes a pin an output (`in` for an input).                |
+//! | `set P04 high`          | Drives an output pin (`low`, `1`, `0` are accepted too).  |
+//! | `get P04`               | Prints the input level of a pin.                          |
+//! | `watch port2 [seconds]` | Prints every input change of a port (10 s by default).    |
+//! | `dump`                  | Prints every register.                                    |
+//! | `help`, `quit`          |                                                           |
+//!
+//! Parsing and execution are independent of the terminal, so the interpreter also runs
+//! against a mock bus or a [`RegisterModel`](crate::testing::RegisterModel) in tests.
+
+use std::fmt;
+use std::io::Write;
+
+use embedded_hal::delay::DelayNs;
+
+use crate::errors::Error;
+use crate::{
+    BusAccess, InputWatcher, Pin, PinDirection, PinState, Port, PortState, RegisterKind, Tca6424,
+};
+
+/// Interval between two input reads of `watch`.
+pub const WATCH_INTERVAL_MS: u32 = 20;
+
+/// Duration of `watch` when no duration is given.
+pub const DEFAULT_WATCH_SECONDS: u32 = 10;
+
+/// Help text printed by `help`.
+pub const HELP: &str = "\
+commands:
+  dir <pin> in|out         set the direction of a pin, e.g. `dir P04 out`
+  set <pin> high|low       drive an output pin, e.g. `set P04 high`
+  get <pin>                read the input level of a pin
+  watch <port> [seconds]   print input changes of a port, e.g. `watch port2 5`
+  dump                     print every register
+  help                     print this text
+  quit                     leave the shell";
+
+/// A parsed shell command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// `dir <pin> in|out`
+    Direction(Pin, PinDirection),
+    /// `set <pin> high|low`
+    Set(Pin, PinState),
+    /// `get <pin>`
+    Get(Pin),
+    /// `watch <port> [seconds]`
+    Watch(Port, u32),
+    /// `dump`
+    Dump,
+    /// `help`
+    Help,
+    /// `quit`
+    Quit,
+}
+
+/// Why a line is not a valid [`Command`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The line holds no command.
+    Empty,
+    /// The first word is not a command.
+    UnknownCommand(String),
+    /// An argument is missing; holds its name.
+    MissingArgument(&'static str),
+    /// An argument could not be parsed.
+    InvalidArgument(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "empty command"),
+            ParseError::UnknownCommand(word) => {
+                write!(f, "unknown command `{word}`, try `help`")
+            }
+            ParseError::MissingArgument(name) => write!(f, "missing argument <{name}>"),
+            ParseError::InvalidArgument(word) => write!(f, "invalid argument `{word}`"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl core::str::FromStr for Command {
+    type Err = ParseError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace();
+        let command = words.next().ok_or(ParseError::Empty)?;
+        let mut argument = |name| words.next().ok_or(ParseError::MissingArgument(name));
+        let parsed = match command.to_ascii_lowercase().as_str() {
+            "dir" => {
+                let pin = parse_pin(argument("pin")?)?;
+                let direction = match argument("in|out")? {
+                    word if word.eq_ignore_ascii_case("in") => PinDirection::Input,
+                    word if word.eq_ignore_ascii_case("out") => PinDirection::Output,
+                    word => return Err(ParseError::InvalidArgument(word.to_string())),
+                };
+                Command::Direction(pin, direction)
+            }
+            "set" => {
+                let pin = parse_pin(argument("pin")?)?;
+                let state = match argument("high|low")? {
+                    word if word.eq_ignore_ascii_case("high") || word == "1" => PinState::High,
+                    word if word.eq_ignore_ascii_case("low") || word == "0" => PinState::Low,
+                    word => return Err(ParseError::InvalidArgument(word.to_string())),
+                };
+                Command::Set(pin, state)
+            }
+            "get" => Command::Get(parse_pin(argument("pin")?)?),
+            "watch" => {
+                let port = parse_port(argument("port")?)?;
+                let seconds = match words.next() {
+                    Some(word) => word
+                        .parse()
+                        .map_err(|_| ParseError::InvalidArgument(word.to_string()))?,
+                    None => DEFAULT_WATCH_SECONDS,
+                };
+                Command::Watch(port, seconds)
+            }
+            "dump" => Command::Dump,
+            "help" | "?" => Command::Help,
+            "quit" | "exit" => Command::Quit,
+            _ => return Err(ParseError::UnknownCommand(command.to_string())),
+        };
+        Ok(parsed)
+    }
+}
+
+/// Parses `P04`-style pin names: port digit, then bit digit.
+fn parse_pin(word: &str) -> Result<Pin, ParseError> {
+    let invalid = || ParseError::InvalidArgument(word.to_string());
+    let digits = word.strip_prefix(['P', 'p']).ok_or_else(invalid)?;
+    match digits.as_bytes() {
+        [port @ b'0'..=b'2', bit @ b'0'..=b'7'] => {
+            Pin::from_index((port - b'0') * 8 + (bit - b'0')).ok_or_else(invalid)
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// Parses `port2`, `P2` or `2`.
+fn parse_port(word: &str) -> Result<Port, ParseError> {
+    let lower = word.to_ascii_lowercase();
+    let digit = lower
+        .strip_prefix("port")
+        .or_else(|| lower.strip_prefix('p'))
+        .unwrap_or(&lower);
+    match digit {
+        "0" => Ok(Port::Port0),
+        "1" => Ok(Port::Port1),
+        "2" => Ok(Port::Port2),
+        _ => Err(ParseError::InvalidArgument(word.to_string())),
+    }
+}
+
+/// Whether the shell keeps reading commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    /// Read the next command.
+    Continue,
+    /// Leave the shell.
+    Quit,
+}
+
+/// Error of [`execute`].
+#[derive(Debug)]
+pub enum CliError<E: fmt::Debug> {
+    /// The driver call failed.
+    Driver(Error<E>),
+    /// Writing the output failed.
+    Io(std::io::Error),
+}
+
+impl<E: fmt::Debug> From<Error<E>> for CliError<E> {
+    fn from(error: Error<E>) -> Self {
+        CliError::Driver(error)
+    }
+}
+
+impl<E: fmt::Debug> From<std::io::Error> for CliError<E> {
+    fn from(error: std::io::Error) -> Self {
+        CliError::Io(error)
+    }
+}
+
+impl<E: fmt::Debug> fmt::Display for CliError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Driver(error) => write!(f, "device error: {error:?}"),
+            CliError::Io(error) => write!(f, "output error: {error}"),
+        }
+    }
+}
+
+/// `DelayNs` implementation sleeping the current thread, used as the delay source of the
+/// shell's driver.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdDelay;
+
+impl DelayNs for StdDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        std::thread::sleep(std::time::Duration::from_nanos(ns.into()));
+    }
+}
+
+/// Runs `command` on `tca`, writing its output to `out`.
+///
+/// `watch` paces its reads with the driver's delay source (see [`Tca6424::with_delay`]).
+///
+/// # Returns
+///
+/// Returns whether the shell continues, or a [`CliError`] if the device or the output fails.
+pub fn execute<I2C, D, W>(
+    tca: &mut Tca6424<I2C, D>,
+    command: Command,
+    out: &mut W,
+) -> Result<Flow, CliError<I2C::Error>>
+where
+    I2C: BusAccess,
+    I2C::Error: fmt::Debug,
+    D: DelayNs,
+    W: Write,
+{
+    match command {
+        Command::Direction(pin, direction) => {
+            tca.set_pin_direction(pin, direction)?;
+            writeln!(out, "{pin} {direction:?}")?;
+        }
+        Command::Set(pin, state) => {
+            tca.set_pin_output(pin, state)?;
+            writeln!(out, "{pin} {state:?}")?;
+        }
+        Command::Get(pin) => {
+            let state = tca.get_pin_input_state(pin)?;
+            writeln!(out, "{pin} {state:?}")?;
+        }
+        Command::Watch(port, seconds) => {
+            let value = tca.get_port_input_state(port)?;
+            writeln!(out, "{}", PortState::new(port, value))?;
+            let mut watcher = InputWatcher::new().with_port(port);
+            watcher.update(u32::from(value) << (port as u8 * 8));
+            let polls = seconds.saturating_mul(1_000) / WATCH_INTERVAL_MS;
+            for _ in 0..polls {
+                tca.delay().delay_ms(WATCH_INTERVAL_MS);
+                for event in tca.poll_input_events(&mut watcher)?.iter() {
+                    writeln!(out, "{} {:?}", event.pin, event.state)?;
+                }
+            }
+        }
+        Command::Dump => {
+            let snapshot = tca.snapshot()?;
+            for kind in RegisterKind::ALL {
+                let [p0, p1, p2] = *snapshot.family(kind);
+                writeln!(
+                    out,
+                    "{:<18} {p0:#010b} {p1:#010b} {p2:#010b}",
+                    format!("{kind:?}")
+                )?;
+            }
+        }
+        Command::Help => writeln!(out, "{HELP}")?,
+        Command::Quit => return Ok(Flow::Quit),
+    }
+    Ok(Flow::Continue)
+}
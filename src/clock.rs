@@ -0,0 +1,67 @@
+//! Millisecond time sources for the timed helpers.
+
+use core::cell::Cell;
+
+/// A free-running millisecond clock.
+///
+/// The timed helpers take a `now_ms` timestamp on every call; their `*_from` variants read it
+/// from a `TimeSource` instead, so application code can be written against the trait and
+/// driven by [`ManualClock`] in host tests, or by a hardware timer on the target. With the
+/// `embassy-time` feature, `EmbassyClock` reads `embassy_time::Instant`.
+///
+/// Timestamps may wrap around; the helpers compare them with wrapping arithmetic.
+pub trait TimeSource {
+    /// Returns the current time in milliseconds.
+    fn now_ms(&self) -> u32;
+}
+
+impl<T: TimeSource + ?Sized> TimeSource for &T {
+    fn now_ms(&self) -> u32 {
+        (**self).now_ms()
+    }
+}
+
+/// A clock that only moves when told to, for stepping timed behaviour in tests.
+///
+/// Stepping takes `&self`, so a test can keep a shared reference to the clock while a helper
+/// under test reads it.
+///
+/// ```
+/// use tca6424::{ManualClock, TimeSource};
+///
+/// let clock = ManualClock::new(1_000);
+/// clock.advance(250);
+/// assert_eq!(clock.now_ms(), 1_250);
+/// clock.set(u32::MAX);
+/// clock.advance(2);
+/// assert_eq!(clock.now_ms(), 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    now_ms: Cell<u32>,
+}
+
+impl ManualClock {
+    /// Creates a clock standing at `start_ms`.
+    pub const fn new(start_ms: u32) -> Self {
+        Self {
+            now_ms: Cell::new(start_ms),
+        }
+    }
+
+    /// Moves the clock forward by `ms`, wrapping around at `u32::MAX`.
+    pub fn advance(&self, ms: u32) {
+        self.now_ms.set(self.now_ms.get().wrapping_add(ms));
+    }
+
+    /// Sets the clock to `now_ms`.
+    pub fn set(&self, now_ms: u32) {
+        self.now_ms.set(now_ms);
+    }
+}
+
+impl TimeSource for ManualClock {
+    fn now_ms(&self) -> u32 {
+        self.now_ms.get()
+    }
+}
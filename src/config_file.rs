@@ -0,0 +1,197 @@
+//! TOML and JSON board files for host tools and test rigs.
+//!
+//! A [`BoardConfig`] is written as the pins it uses, by name: the input pins, the
+//! [event inputs](BoardConfig::event_input) if there are any, and the output pins with their
+//! power-up level.
+//!
+//! ```toml
+//! inputs = ["P00", "P01"]
+//! events = ["P02"]
+//!
+//! [outputs]
+//! P10 = "high"
+//! P11 = "low"
+//! ```
+//!
+//! The same structure is produced by the [`board!`](crate::board) macro in firmware builds,
+//! so a host tool can load the file a test rig was configured with and compare it against
+//! `board::CONFIG`. `BoardConfig` also implements `serde::Serialize` and
+//! `serde::Deserialize` with this layout, for embedding in larger configuration files.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{BoardConfig, Pin, pins_from_mask};
+
+/// Error returned when a board file cannot be parsed.
+#[derive(Debug)]
+pub enum ConfigFileError {
+    /// The JSON text is malformed or does not describe a board.
+    Json(serde_json::Error),
+    /// The TOML text is malformed or does not describe a board.
+    Toml(toml::de::Error),
+}
+
+impl fmt::Display for ConfigFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigFileError::Json(error) => write!(f, "invalid JSON board file: {error}"),
+            ConfigFileError::Toml(error) => write!(f, "invalid TOML board file: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigFileError::Json(error) => Some(error),
+            ConfigFileError::Toml(error) => Some(error),
+        }
+    }
+}
+
+/// Power-up level of an output pin.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Level {
+    Low,
+    High,
+}
+
+/// On-disk layout of a [`BoardConfig`].
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BoardFile {
+    #[serde(default)]
+    inputs: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    events: Vec<String>,
+    #[serde(default)]
+    outputs: BTreeMap<String, Level>,
+}
+
+impl From<&BoardConfig> for BoardFile {
+    fn from(config: &BoardConfig) -> Self {
+        let inputs = pins_from_mask(config.pins() & !config.outputs() & !config.events())
+            .map(|pin| pin.to_string())
+            .collect();
+        let events = pins_from_mask(config.events())
+            .map(|pin| pin.to_string())
+            .collect();
+        let outputs = pins_from_mask(config.outputs())
+            .map(|pin| {
+                let level = if config.levels() & (1 << pin as u8) != 0 {
+                    Level::High
+                } else {
+                    Level::Low
+                };
+                (pin.to_string(), level)
+            })
+            .collect();
+        Self {
+            inputs,
+            events,
+            outputs,
+        }
+    }
+}
+
+impl TryFrom<BoardFile> for BoardConfig {
+    type Error = String;
+
+    fn try_from(file: BoardFile) -> Result<Self, String> {
+        let mut config = BoardConfig::new();
+        for name in &file.inputs {
+            let pin = parse_pin(name)?;
+            check_unused(&config, pin)?;
+            config = config.input(pin, false);
+        }
+        for name in &file.events {
+            let pin = parse_pin(name)?;
+            check_unused(&config, pin)?;
+            config = config.event_input(pin, false);
+        }
+        for (name, level) in &file.outputs {
+            let pin = parse_pin(name)?;
+            check_unused(&config, pin)?;
+            config = config.output(pin, false, matches!(level, Level::High));
+        }
+        Ok(config)
+    }
+}
+
+/// Parses `P04`-style pin names.
+fn parse_pin(name: &str) -> Result<Pin, String> {
+    let invalid = || format!("unknown pin `{name}`, expected `P00`-`P27`");
+    let digits = name.strip_prefix(['P', 'p']).ok_or_else(invalid)?;
+    match digits.as_bytes() {
+        [port @ b'0'..=b'2', bit @ b'0'..=b'7'] => {
+            Pin::from_index((port - b'0') * 8 + (bit - b'0')).ok_or_else(invalid)
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// Rejects a pin listed twice, which [`BoardConfig`] would otherwise panic on.
+fn check_unused(config: &BoardConfig, pin: Pin) -> Result<(), String> {
+    if config.pins() & (1 << pin as u8) != 0 {
+        return Err(format!("pin {pin} used by more than one net"));
+    }
+    Ok(())
+}
+
+impl Serialize for BoardConfig {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BoardFile::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BoardConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        BoardFile::deserialize(deserializer)?
+            .try_into()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl BoardConfig {
+    /// Returns the configuration as a pretty-printed JSON board file.
+    #[allow(
+        clippy::expect_used,
+        reason = "a BoardConfig has no unserializable values"
+    )]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("board files always serialize")
+    }
+
+    /// Parses a JSON board file.
+    ///
+    /// # Returns
+    ///
+    /// Returns the configuration, or [`ConfigFileError::Json`] if the text is malformed,
+    /// names an unknown pin or uses a pin twice.
+    pub fn from_json(text: &str) -> Result<Self, ConfigFileError> {
+        serde_json::from_str(text).map_err(ConfigFileError::Json)
+    }
+
+    /// Returns the configuration as a TOML board file.
+    #[allow(
+        clippy::expect_used,
+        reason = "a BoardConfig has no unserializable values"
+    )]
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("board files always serialize")
+    }
+
+    /// Parses a TOML board file.
+    ///
+    /// # Returns
+    ///
+    /// Returns the configuration, or [`ConfigFileError::Toml`] if the text is malformed,
+    /// names an unknown pin or uses a pin twice.
+    pub fn from_toml(text: &str) -> Result<Self, ConfigFileError> {
+        toml::from_str(text).map_err(ConfigFileError::Toml)
+    }
+}
@@ -0,0 +1,249 @@
+//! What the driver was doing when an error occurred, for logs from deep call stacks.
+//! Without the `error-context` feature nothing is recorded.
+
+use core::fmt;
+use core::fmt::Debug;
+
+#[cfg(feature = "error-context")]
+use crate::RegisterKind;
+use crate::errors::Error;
+use crate::registers::Register;
+use crate::{Chip, Tca64xx};
+
+/// An expander activity, as recorded in an [`ErrorContext`].
+///
+/// Register accesses are labelled by the register family and direction; the driver methods
+/// that combine several accesses label all of them with their own activity instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum Activity {
+    /// Reading the Input Port registers.
+    ReadInput,
+    /// Reading the Output Port registers.
+    ReadOutput,
+    /// Writing the Output Port registers.
+    WriteOutput,
+    /// Reading the Configuration registers.
+    ReadDirection,
+    /// Writing the Configuration registers.
+    SetDirection,
+    /// Reading the Polarity Inversion registers.
+    ReadPolarity,
+    /// Writing the Polarity Inversion registers.
+    SetPolarity,
+    /// Reading the Interrupt Mask registers.
+    ReadInterruptMask,
+    /// Writing the Interrupt Mask registers.
+    SetInterruptMask,
+    /// Applying a board configuration or a [`ConfigTransaction`](crate::ConfigTransaction).
+    ApplyConfig,
+    /// Reading the inputs after INT (`Tca6424::service_interrupt`,
+    /// `Tca6424::get_interrupt_sources`).
+    ServiceInterrupt,
+    /// Taking a register snapshot.
+    Snapshot,
+    /// Writing registers back from a snapshot or the register cache.
+    Restore,
+}
+
+impl Activity {
+    /// Returns the activity of a single register access.
+    #[cfg(feature = "error-context")]
+    const fn of(kind: RegisterKind, write: bool) -> Self {
+        match (kind, write) {
+            // The Input Port registers are read-only
+            (RegisterKind::Input, _) => Activity::ReadInput,
+            (RegisterKind::Output, false) => Activity::ReadOutput,
+            (RegisterKind::Output, true) => Activity::WriteOutput,
+            (RegisterKind::Configuration, false) => Activity::ReadDirection,
+            (RegisterKind::Configuration, true) => Activity::SetDirection,
+            (RegisterKind::PolarityInversion, false) => Activity::ReadPolarity,
+            (RegisterKind::PolarityInversion, true) => Activity::SetPolarity,
+            (RegisterKind::InterruptMask, false) => Activity::ReadInterruptMask,
+            (RegisterKind::InterruptMask, true) => Activity::SetInterruptMask,
+        }
+    }
+
+    /// Returns a lowercase description, such as `"servicing an interrupt"`.
+    pub const fn description(self) -> &'static str {
+        match self {
+            Activity::ReadInput => "reading inputs",
+            Activity::ReadOutput => "reading outputs",
+            Activity::WriteOutput => "writing outputs",
+            Activity::ReadDirection => "reading directions",
+            Activity::SetDirection => "setting directions",
+            Activity::ReadPolarity => "reading polarity inversion",
+            Activity::SetPolarity => "setting polarity inversion",
+            Activity::ReadInterruptMask => "reading interrupt masks",
+            Activity::SetInterruptMask => "setting interrupt masks",
+            Activity::ApplyConfig => "applying a configuration",
+            Activity::ServiceInterrupt => "servicing an interrupt",
+            Activity::Snapshot => "taking a snapshot",
+            Activity::Restore => "restoring registers",
+        }
+    }
+}
+
+impl fmt::Display for Activity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+/// Where the last error returned by a driver method came from, returned by
+/// [`Tca64xx::last_error_context`].
+#[cfg(feature = "error-context")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ErrorContext {
+    /// What the driver was doing.
+    pub activity: Activity,
+    /// The first register of the access that failed; `None` if the error was raised before
+    /// any register access, such as a pin that is already claimed.
+    pub register: Option<Register>,
+}
+
+#[cfg(feature = "error-context")]
+impl fmt::Display for ErrorContext {
+    /// Prints `servicing an interrupt (Input Port 0)`, without the register if it is unknown.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.activity.description())?;
+        if let Some(register) = self.register {
+            write!(
+                f,
+                " ({} Port {})",
+                register.kind().name(),
+                register.port() as u8
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The context of the last error, and how many register accesses have failed so far.
+#[cfg(feature = "error-context")]
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct ContextState {
+    /// Wraps around; only compared against the mark of an [`ActivityScope`].
+    failed_accesses: u32,
+    last: Option<ErrorContext>,
+}
+
+/// A driver method labelling its register accesses, from [`Tca64xx::enter_activity`].
+///
+/// The scope is a value rather than driver state, so an `async` method whose future is
+/// dropped half-way leaves nothing behind to mislabel later errors.
+#[cfg(feature = "error-context")]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ActivityScope {
+    activity: Activity,
+    /// `ContextState::failed_accesses` when the scope was entered.
+    mark: u32,
+}
+
+#[cfg(feature = "error-context")]
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C> {
+    /// Returns the context of the last error a register access or labelled driver method
+    /// returned, or `None` if there was none since the driver was created or the context was
+    /// taken.
+    ///
+    /// Errors are returned unchanged; the driver keeps the context of the last one, so a log
+    /// line can say which expander activity failed without wrapping every call:
+    ///
+    /// ```ignore
+    /// if let Err(error) = tca.service_interrupt(&mut throttle, now_ms) {
+    ///     // "I2C error NoAcknowledge(Address) while servicing an interrupt (Input Port 0)"
+    ///     log::warn!("I2C error {error:?} while {}", tca.last_error_context().unwrap());
+    /// }
+    /// ```
+    ///
+    /// The context is not part of [`Error`] because its variants are matched and compared
+    /// throughout applications; extra fields would break every such match. It describes the
+    /// last error returned, so read it right after the failing call, or clear it with
+    /// [`Tca64xx::take_error_context`] once logged. Successful calls leave it in place.
+    pub fn last_error_context(&self) -> Option<ErrorContext> {
+        self.context.last
+    }
+
+    /// Returns the context of the last error like [`Tca64xx::last_error_context`] and clears
+    /// it.
+    pub fn take_error_context(&mut self) -> Option<ErrorContext> {
+        self.context.last.take()
+    }
+
+    /// Starts labelling the register accesses of a driver method with `activity`; pass the
+    /// scope to [`Tca64xx::leave_activity`] with the method's result.
+    pub(crate) fn enter_activity(&self, activity: Activity) -> ActivityScope {
+        ActivityScope {
+            activity,
+            mark: self.context.failed_accesses,
+        }
+    }
+
+    /// Labels the context of an error in `result` with the activity of `scope`, and passes
+    /// `result` through.
+    ///
+    /// The register is kept if an access failed within the scope, and `None` otherwise.
+    /// Nested scopes are left innermost first, so the outermost method's label wins.
+    pub(crate) fn leave_activity<T, E: Debug>(
+        &mut self,
+        scope: ActivityScope,
+        result: Result<T, Error<E>>,
+    ) -> Result<T, Error<E>> {
+        if result.is_err() {
+            let register = match self.context.last {
+                Some(last) if self.context.failed_accesses != scope.mark => last.register,
+                _ => None,
+            };
+            self.context.last = Some(ErrorContext {
+                activity: scope.activity,
+                register,
+            });
+        }
+        result
+    }
+
+    /// Records the context of a failed access starting at `register`, and passes `result`
+    /// through.
+    pub(crate) fn note_access<T, E: Debug>(
+        &mut self,
+        register: Register,
+        write: bool,
+        result: Result<T, Error<E>>,
+    ) -> Result<T, Error<E>> {
+        if result.is_err() {
+            self.context.last = Some(ErrorContext {
+                activity: Activity::of(register.kind(), write),
+                register: Some(register),
+            });
+            self.context.failed_accesses = self.context.failed_accesses.wrapping_add(1);
+        }
+        result
+    }
+}
+
+// Without the `error-context` feature the labels are dropped and results pass through.
+#[cfg(not(feature = "error-context"))]
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C> {
+    pub(crate) fn enter_activity(&self, activity: Activity) -> Activity {
+        activity
+    }
+
+    pub(crate) fn leave_activity<T, E: Debug>(
+        &mut self,
+        _activity: Activity,
+        result: Result<T, Error<E>>,
+    ) -> Result<T, Error<E>> {
+        result
+    }
+
+    pub(crate) fn note_access<T, E: Debug>(
+        &mut self,
+        _register: Register,
+        _write: bool,
+        result: Result<T, Error<E>>,
+    ) -> Result<T, Error<E>> {
+        result
+    }
+}
@@ -20,11 +20,44 @@ pub enum PinState {
     High,
 }
 
+impl From<bool> for PinState {
+    /// `true` is [`PinState::High`], `false` is [`PinState::Low`].
+    fn from(high: bool) -> Self {
+        if high { PinState::High } else { PinState::Low }
+    }
+}
+
+impl From<PinState> for bool {
+    fn from(state: PinState) -> Self {
+        state == PinState::High
+    }
+}
+
+impl From<embedded_hal::digital::PinState> for PinState {
+    fn from(state: embedded_hal::digital::PinState) -> Self {
+        match state {
+            embedded_hal::digital::PinState::Low => PinState::Low,
+            embedded_hal::digital::PinState::High => PinState::High,
+        }
+    }
+}
+
+impl From<PinState> for embedded_hal::digital::PinState {
+    fn from(state: PinState) -> Self {
+        match state {
+            PinState::Low => embedded_hal::digital::PinState::Low,
+            PinState::High => embedded_hal::digital::PinState::High,
+        }
+    }
+}
+
 /// Defines the individual pins of the TCA6424 I/O expander (P00-P27).
 ///
 /// Pins are grouped into three 8-bit ports: Port 0 (P00-P07), Port 1 (P10-P17),
-/// and Port 2 (P20-P27).
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// and Port 2 (P20-P27). Pins order by index (`P00 < P07 < P10`) and implement `Hash`, so
+/// they can key maps and sets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum Pin {
     /// Port 0, Pin 0
@@ -57,7 +90,8 @@ pub enum Pin {
 }
 
 /// Defines the 8-bit ports of the TCA6424 I/O expander.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum Port {
     /// Port 0 (Pins P00-P07).
@@ -66,4 +100,434 @@ pub enum Port {
     Port1 = 1,
     /// Port 2 (Pins P20-P27).
     Port2 = 2,
-}
\ No newline at end of file
+}
+impl Pin {
+    /// All 24 pins in index order (P00 first, P27 last).
+    pub const ALL: [Pin; 24] = [
+        Pin::P00, Pin::P01, Pin::P02, Pin::P03, Pin::P04, Pin::P05, Pin::P06, Pin::P07,
+        Pin::P10, Pin::P11, Pin::P12, Pin::P13, Pin::P14, Pin::P15, Pin::P16, Pin::P17,
+        Pin::P20, Pin::P21, Pin::P22, Pin::P23, Pin::P24, Pin::P25, Pin::P26, Pin::P27,
+    ];
+
+    /// Returns the pin with the given flat index (0-23), or `None` if out of range.
+    pub const fn from_index(index: u8) -> Option<Pin> {
+        let mut pins: &[Pin] = &Pin::ALL;
+        while let [pin, rest @ ..] = pins {
+            if *pin as u8 == index {
+                return Some(*pin);
+            }
+            pins = rest;
+        }
+        None
+    }
+
+    /// Returns the pin at `bit` (0-7) of `port`, or `None` if `bit` is out of range.
+    pub const fn from_port_bit(port: Port, bit: u8) -> Option<Pin> {
+        if bit < 8 {
+            Pin::from_index(port as u8 * 8 + bit)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the port this pin belongs to.
+    pub const fn port(self) -> Port {
+        match self as u8 / 8 {
+            0 => Port::Port0,
+            1 => Port::Port1,
+            _ => Port::Port2,
+        }
+    }
+
+    /// Returns the bit position (0-7) of this pin within its port.
+    pub const fn bit(self) -> u8 {
+        self as u8 % 8
+    }
+
+    /// Returns the entry of a per-pin array that belongs to this pin.
+    // Pin discriminants are 0-23, so the index is always in bounds.
+    #[allow(clippy::indexing_slicing)]
+    pub(crate) const fn entry<T>(self, entries: &[T; 24]) -> &T {
+        &entries[self as usize]
+    }
+
+    /// Returns the entry of a per-pin array that belongs to this pin, mutably.
+    #[allow(clippy::indexing_slicing)]
+    pub(crate) const fn entry_mut<T>(self, entries: &mut [T; 24]) -> &mut T {
+        &mut entries[self as usize]
+    }
+}
+
+impl core::fmt::Display for Pin {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "P{}{}", self.port() as u8, self.bit())
+    }
+}
+
+/// Builds the 24-bit mask of the listed pins (bit `n` = pin index `n`) at compile time.
+///
+/// Pins are named as in [`Pin`]. An unknown name or a pin listed twice fails to compile, and
+/// the result is a `u32` constant usable in `const` items.
+///
+/// ```
+/// use tca6424::pins;
+///
+/// const LEDS: u32 = pins!(P00, P03, P17);
+/// assert_eq!(LEDS, (1 << 0) | (1 << 3) | (1 << 15));
+/// assert_eq!(pins!(), 0);
+/// ```
+///
+/// ```compile_fail
+/// const TWICE: u32 = tca6424::pins!(P00, P00);
+/// ```
+///
+/// ```compile_fail
+/// const UNKNOWN: u32 = tca6424::pins!(P30);
+/// ```
+#[macro_export]
+macro_rules! pins {
+    ($($pin:ident),* $(,)?) => {
+        const {
+            let pins: &[$crate::Pin] = &[$($crate::Pin::$pin),*];
+            let mut mask = 0u32;
+            let mut i = 0;
+            while i < pins.len() {
+                let bit = 1u32 << pins[i] as u8;
+                assert!(mask & bit == 0, "pin listed twice in pins!");
+                mask |= bit;
+                i += 1;
+            }
+            mask
+        }
+    };
+}
+
+/// Returns an iterator over the pins whose bit is `1` in `mask` (bit `n` = pin index `n`),
+/// lowest pin first. Bits above 23 are ignored.
+///
+/// ```
+/// use tca6424::{Pin, pins_from_mask};
+///
+/// let pins: Vec<Pin> = pins_from_mask(0x80_0101).collect();
+/// assert_eq!(pins, [Pin::P00, Pin::P10, Pin::P27]);
+/// ```
+pub const fn pins_from_mask(mask: u32) -> PinIter {
+    PinIter {
+        mask: mask & 0x00FF_FFFF,
+    }
+}
+
+/// Returns the 24-bit mask of pin indices `from` to `to`, both inclusive.
+///
+/// Usable in `const` items, so board masks are computed and checked at compile time.
+///
+/// ```
+/// use tca6424::mask_range;
+///
+/// const PORT1: u32 = mask_range(8, 15);
+/// assert_eq!(PORT1, 0x00_FF00);
+/// assert_eq!(mask_range(0, 23), 0xFF_FFFF);
+/// ```
+///
+/// # Panics
+///
+/// Panics if `from > to` or `to > 23`; in a `const` item this is a compile error.
+pub const fn mask_range(from: u8, to: u8) -> u32 {
+    assert!(from <= to && to < 24, "pin index range out of bounds");
+    (u32::MAX >> (31 - to)) & (u32::MAX << from)
+}
+
+/// Iterator over the pins of a 24-bit mask, returned by [`pins_from_mask`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinIter {
+    mask: u32,
+}
+
+impl Iterator for PinIter {
+    type Item = Pin;
+
+    fn next(&mut self) -> Option<Pin> {
+        if self.mask == 0 {
+            return None;
+        }
+        let index = self.mask.trailing_zeros() as u8;
+        self.mask &= self.mask - 1;
+        Pin::from_index(index)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.mask.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for PinIter {
+    fn next_back(&mut self) -> Option<Pin> {
+        if self.mask == 0 {
+            return None;
+        }
+        let index = 31 - self.mask.leading_zeros() as u8;
+        self.mask &= !(1 << index);
+        Pin::from_index(index)
+    }
+}
+
+impl ExactSizeIterator for PinIter {}
+
+impl core::iter::FusedIterator for PinIter {}
+
+/// An 8-bit port mask (bit `n` = pin `n` of the port), built with `const fn`s.
+///
+/// The port-level methods take a plain `u8`; [`PortMask::bits`] or `u8::from` converts.
+///
+/// ```
+/// use tca6424::PortMask;
+///
+/// const LEDS: PortMask = PortMask::of(&[0, 3, 7]);
+/// const OUTPUTS: u8 = LEDS.union(PortMask::range(4, 5)).bits();
+/// assert_eq!(OUTPUTS, 0b1011_1001);
+/// ```
+///
+/// ```compile_fail
+/// const BAD: tca6424::PortMask = tca6424::PortMask::of(&[8]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PortMask(u8);
+
+impl PortMask {
+    /// The mask with no bit set.
+    pub const EMPTY: PortMask = PortMask(0);
+    /// The mask with all 8 bits set.
+    pub const ALL: PortMask = PortMask(0xFF);
+
+    /// Returns the mask with the listed `bits` (0-7) set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a bit is above 7; in a `const` item this is a compile error.
+    pub const fn of(bits: &[u8]) -> PortMask {
+        let mut mask = 0u8;
+        let mut bits = bits;
+        while let [bit, rest @ ..] = bits {
+            assert!(*bit < 8, "port bit out of range");
+            mask |= 1 << *bit;
+            bits = rest;
+        }
+        PortMask(mask)
+    }
+
+    /// Returns the mask with bits `from` to `to` set, both inclusive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from > to` or `to > 7`; in a `const` item this is a compile error.
+    pub const fn range(from: u8, to: u8) -> PortMask {
+        assert!(from <= to && to < 8, "port bit range out of bounds");
+        PortMask((u8::MAX >> (7 - to)) & (u8::MAX << from))
+    }
+
+    /// Returns the bits set in either mask.
+    pub const fn union(self, other: PortMask) -> PortMask {
+        PortMask(self.0 | other.0)
+    }
+
+    /// Returns the bits not set in this mask.
+    pub const fn invert(self) -> PortMask {
+        PortMask(!self.0)
+    }
+
+    /// Returns the raw mask.
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+}
+
+impl From<PortMask> for u8 {
+    fn from(mask: PortMask) -> u8 {
+        mask.0
+    }
+}
+
+impl Port {
+    /// All three ports in register order.
+    pub const ALL: [Port; 3] = [Port::Port0, Port::Port1, Port::Port2];
+
+    /// Returns the port `index` places after Port 0, wrapping around after Port 2 as
+    /// auto-increment does.
+    pub(crate) const fn wrapping(index: usize) -> Port {
+        match index % 3 {
+            0 => Port::Port0,
+            1 => Port::Port1,
+            _ => Port::Port2,
+        }
+    }
+
+    /// Returns the entry of a per-port array that belongs to this port.
+    pub(crate) const fn entry<T>(self, entries: &[T; 3]) -> &T {
+        let [port0, port1, port2] = entries;
+        match self {
+            Port::Port0 => port0,
+            Port::Port1 => port1,
+            Port::Port2 => port2,
+        }
+    }
+
+    /// Returns the entry of a per-port array that belongs to this port, mutably.
+    pub(crate) const fn entry_mut<T>(self, entries: &mut [T; 3]) -> &mut T {
+        let [port0, port1, port2] = entries;
+        match self {
+            Port::Port0 => port0,
+            Port::Port1 => port1,
+            Port::Port2 => port2,
+        }
+    }
+
+    /// Returns the byte of a 24-bit pin word that belongs to this port.
+    pub(crate) const fn byte_of(self, word: u32) -> u8 {
+        (word >> (self as u32 * 8)) as u8
+    }
+}
+
+/// The 8 pin levels of one port, as returned by [`crate::Tca6424::read_ports`],
+/// [`crate::Tca6424::read_port_inputs`] and [`crate::Tca6424::read_port_outputs`].
+///
+/// `Display` prints the port followed by its bits, most significant first
+/// (`P1: 0b00101100`); `Binary` formats the raw value, so `{:08b}` gives `00101100`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PortState {
+    port: Port,
+    value: u8,
+}
+
+impl PortState {
+    /// Wraps the register value `value` of `port`.
+    pub const fn new(port: Port, value: u8) -> Self {
+        Self { port, value }
+    }
+
+    /// Returns the port.
+    pub const fn port(&self) -> Port {
+        self.port
+    }
+
+    /// Returns the raw register value (bit `n` = pin `n` of the port).
+    pub const fn value(&self) -> u8 {
+        self.value
+    }
+
+    /// Returns `true` if `bit` (0-7) is high; bits out of range read as low.
+    pub const fn is_high(&self, bit: u8) -> bool {
+        bit < 8 && self.value & (1 << bit) != 0
+    }
+
+    /// Returns the level of `bit` (0-7); bits out of range read as low.
+    pub const fn get(&self, bit: u8) -> PinState {
+        if self.is_high(bit) {
+            PinState::High
+        } else {
+            PinState::Low
+        }
+    }
+
+    /// Returns `true` if `bit` (0-7) is low.
+    pub const fn is_low(&self, bit: u8) -> bool {
+        !self.is_high(bit)
+    }
+
+    /// Returns the level of `pin`, or `None` if it belongs to another port.
+    pub fn pin(&self, pin: Pin) -> Option<PinState> {
+        (pin.port() == self.port).then(|| self.is_high(pin.bit()).into())
+    }
+
+    /// Iterates over the pins of the port that are high, lowest bit first.
+    pub fn pins_high(&self) -> impl Iterator<Item = Pin> + '_ {
+        pins_from_mask(u32::from(self.value) << (self.port as u8 * 8))
+    }
+}
+
+impl From<PortState> for u8 {
+    fn from(state: PortState) -> Self {
+        state.value
+    }
+}
+
+impl core::fmt::Display for PortState {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "P{}: {:#010b}", self.port as u8, self.value)
+    }
+}
+
+impl core::fmt::Binary for PortState {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Binary::fmt(&self.value, f)
+    }
+}
+
+/// Identifies one of the register families of the TCA6424.
+///
+/// Each family consists of three consecutive registers, one per port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RegisterKind {
+    /// Input Port registers (read-only, reflect the pin levels).
+    Input,
+    /// Output Port registers (output latch).
+    Output,
+    /// Polarity Inversion registers.
+    PolarityInversion,
+    /// Configuration registers (Input=1, Output=0).
+    Configuration,
+    /// Interrupt Mask registers (Masked=1, Enabled=0).
+    InterruptMask,
+}
+
+impl RegisterKind {
+    /// All register families in register map order.
+    pub const ALL: [RegisterKind; 5] = [
+        RegisterKind::Input,
+        RegisterKind::Output,
+        RegisterKind::PolarityInversion,
+        RegisterKind::Configuration,
+        RegisterKind::InterruptMask,
+    ];
+
+    /// Returns the entry of a per-family array that belongs to this family.
+    pub(crate) const fn entry<T>(self, entries: &[T; 5]) -> &T {
+        let [input, output, polarity, configuration, interrupt_mask] = entries;
+        match self {
+            RegisterKind::Input => input,
+            RegisterKind::Output => output,
+            RegisterKind::PolarityInversion => polarity,
+            RegisterKind::Configuration => configuration,
+            RegisterKind::InterruptMask => interrupt_mask,
+        }
+    }
+
+    /// Returns the entry of a per-family array that belongs to this family, mutably.
+    pub(crate) const fn entry_mut<T>(self, entries: &mut [T; 5]) -> &mut T {
+        let [input, output, polarity, configuration, interrupt_mask] = entries;
+        match self {
+            RegisterKind::Input => input,
+            RegisterKind::Output => output,
+            RegisterKind::PolarityInversion => polarity,
+            RegisterKind::Configuration => configuration,
+            RegisterKind::InterruptMask => interrupt_mask,
+        }
+    }
+
+    /// Returns a human-readable name for the register family.
+    ///
+    /// Combined with a port number this gives the datasheet register name,
+    /// e.g. `"Configuration"` + `" Port 1"`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            RegisterKind::Input => "Input",
+            RegisterKind::Output => "Output",
+            RegisterKind::PolarityInversion => "Polarity Inversion",
+            RegisterKind::Configuration => "Configuration",
+            RegisterKind::InterruptMask => "Interrupt Mask",
+        }
+    }
+}
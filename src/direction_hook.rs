@@ -0,0 +1,52 @@
+//! Notification of pin direction changes, whatever API path made them.
+
+use crate::{Chip, Pin, PinDirection, Port, RegisterKind, Tca64xx};
+
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C> {
+    /// Calls `hook` for every pin whose direction a successful Configuration write changed,
+    /// with the new direction. `None` (the default) disables the hook.
+    ///
+    /// Every path that writes the Configuration registers reports through the hook: the pin,
+    /// port, masked, register and auto-increment methods, configuration transactions, board
+    /// bring-up, restores and the fail-safe frame. Code that reacts to direction changes,
+    /// such as open-drain emulation or a level shifter's direction line, can therefore sit
+    /// in one place instead of wrapping every call site.
+    ///
+    /// Changes are taken against the register cache. A write to a register the cache does
+    /// not know reports every pin of the port, since any of them may have changed; a write
+    /// of the cached value reports nothing. The hook runs after the write, on the caller's
+    /// stack while the driver is borrowed; it must not use this driver.
+    pub fn set_direction_hook(&mut self, hook: Option<fn(Pin, PinDirection)>) {
+        self.direction_hook = hook;
+    }
+
+    /// Returns the hook set with [`Tca64xx::set_direction_hook`], if any.
+    pub fn direction_hook(&self) -> Option<fn(Pin, PinDirection)> {
+        self.direction_hook
+    }
+
+    /// Records a value written to a register in the cache, reporting the direction changes
+    /// from `previous`, the cached value before the write started, to the hook.
+    pub(crate) fn record_write(
+        &mut self,
+        kind: RegisterKind,
+        port: Port,
+        previous: Option<u8>,
+        value: u8,
+    ) {
+        if let (RegisterKind::Configuration, Some(hook)) = (kind, self.direction_hook) {
+            let changed = previous.map_or(0xFF, |previous| previous ^ value);
+            for bit in (0..8).filter(|bit| changed & (1 << bit) != 0) {
+                let direction = if value & (1 << bit) != 0 {
+                    PinDirection::Input
+                } else {
+                    PinDirection::Output
+                };
+                if let Some(pin) = Pin::from_index(port as u8 * 8 + bit) {
+                    hook(pin, direction);
+                }
+            }
+        }
+        self.cache.set(kind, port, value);
+    }
+}
@@ -0,0 +1,256 @@
+//! Dry runs: recording the transfers an operation would issue without touching the bus.
+
+use core::fmt;
+
+use crate::cache::RegisterCache;
+use crate::claims::ClaimRegistry;
+use crate::plan;
+use crate::registers::{self, AUTO_INCREMENT, Register};
+use crate::{BusCost, Chip, Tca64xx};
+
+/// Number of transfers a [`DryRunPlan`] holds; later transfers are counted but not kept.
+pub const DRY_RUN_CAPACITY: usize = 32;
+
+/// Whether a planned transfer reads or writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PlannedAccess {
+    /// A register read (command byte, then data read after a repeated start).
+    Read,
+    /// A register write (command byte followed by the data).
+    Write,
+}
+
+/// One transfer the driver would have issued, as recorded in a [`DryRunPlan`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PlannedTransfer {
+    /// Read or write.
+    pub access: PlannedAccess,
+    /// First register of the transfer.
+    pub register: Register,
+    /// `true` if the command byte has the auto-increment bit set.
+    pub auto_increment: bool,
+    /// Number of data bytes.
+    pub len: u8,
+    /// The first three data bytes: the values written, or the values a read was answered
+    /// with.
+    pub bytes: [u8; 3],
+}
+
+impl PlannedTransfer {
+    /// Returns the command byte of the transfer.
+    pub fn command(&self) -> u8 {
+        crate::command_byte(self.register, self.auto_increment)
+    }
+
+    /// Returns the data bytes.
+    pub fn data(&self) -> &[u8] {
+        self.bytes
+            .get(..usize::from(self.len))
+            .unwrap_or(&self.bytes)
+    }
+
+    /// Returns the bus cost of the transfer.
+    pub fn cost(&self) -> BusCost {
+        match self.access {
+            PlannedAccess::Read => BusCost::read(self.len),
+            PlannedAccess::Write => BusCost::write(self.len),
+        }
+    }
+}
+
+impl fmt::Display for PlannedTransfer {
+    /// Prints `W Output Port 0 (AI) [0x01 0x00 0x00]`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let access = match self.access {
+            PlannedAccess::Read => 'R',
+            PlannedAccess::Write => 'W',
+        };
+        write!(
+            f,
+            "{} {} Port {}",
+            access,
+            self.register.kind().name(),
+            self.register.port() as u8
+        )?;
+        if self.auto_increment {
+            f.write_str(" (AI)")?;
+        }
+        f.write_str(" [")?;
+        for (i, byte) in self.data().iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{:#04x}", byte)?;
+        }
+        if usize::from(self.len) > self.bytes.len() {
+            f.write_str(" ..")?;
+        }
+        f.write_str("]")
+    }
+}
+
+/// The transfers recorded by a dry run, in issue order.
+///
+/// The `Display` implementation prints one transfer per line, so a plan can be pasted into
+/// a code review or compared against a golden file in a host test.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DryRunPlan {
+    transfers: heapless::Vec<PlannedTransfer, DRY_RUN_CAPACITY>,
+    total: usize,
+    cost: BusCost,
+}
+
+impl DryRunPlan {
+    /// Returns the recorded transfers, oldest first.
+    pub fn transfers(&self) -> &[PlannedTransfer] {
+        &self.transfers
+    }
+
+    /// Returns the recorded writes, oldest first.
+    pub fn writes(&self) -> impl Iterator<Item = &PlannedTransfer> + '_ {
+        self.transfers
+            .iter()
+            .filter(|transfer| transfer.access == PlannedAccess::Write)
+    }
+
+    /// Returns the number of transfers the operations would have issued, including those
+    /// beyond [`DRY_RUN_CAPACITY`].
+    pub fn len(&self) -> usize {
+        self.total
+    }
+
+    /// Returns `true` if no transfer would have been issued.
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    /// Returns the bus cost of every transfer the operations would have issued, including
+    /// those beyond [`DRY_RUN_CAPACITY`]; [`BusCost::estimated_time`] turns it into the bus
+    /// time of the recorded operations.
+    pub fn cost(&self) -> BusCost {
+        self.cost
+    }
+
+    /// Returns `true` if more transfers were issued than the plan could keep.
+    pub fn is_truncated(&self) -> bool {
+        self.total > self.transfers.len()
+    }
+
+    fn push(&mut self, transfer: PlannedTransfer) {
+        self.total += 1;
+        self.cost += transfer.cost();
+        // Transfers beyond the capacity are only counted.
+        let _ = self.transfers.push(transfer);
+    }
+}
+
+impl fmt::Display for DryRunPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, transfer) in self.transfers.iter().enumerate() {
+            if i > 0 {
+                f.write_str("\n")?;
+            }
+            write!(f, "{transfer}")?;
+        }
+        if self.is_truncated() {
+            write!(
+                f,
+                "\n... {} more transfers",
+                self.total - self.transfers.len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A dry run in progress: the plan so far and the state to restore when it finishes.
+#[derive(Debug)]
+pub(crate) struct DryRun {
+    plan: DryRunPlan,
+    cache: RegisterCache,
+    claims: ClaimRegistry,
+}
+
+impl DryRun {
+    /// Records a write of `payload` with `command`.
+    pub(crate) fn record_write(&mut self, command: u8, payload: &[u8]) {
+        self.plan
+            .push(planned(PlannedAccess::Write, command, payload));
+    }
+
+    /// Answers a read with `command` from `cache`, falling back to the power-on defaults,
+    /// and records it.
+    pub(crate) fn answer_read(&mut self, command: u8, cache: &RegisterCache, buffer: &mut [u8]) {
+        let registers = registers::command_registers(command, buffer.len());
+        for (byte, register) in buffer.iter_mut().zip(registers) {
+            let kind = register.kind();
+            *byte = cache
+                .get(kind, register.port())
+                .unwrap_or(plan::power_on_value(kind));
+        }
+        self.plan
+            .push(planned(PlannedAccess::Read, command, buffer));
+    }
+}
+
+fn planned(access: PlannedAccess, command: u8, data: &[u8]) -> PlannedTransfer {
+    let mut bytes = [0u8; 3];
+    for (byte, value) in bytes.iter_mut().zip(data) {
+        *byte = *value;
+    }
+    PlannedTransfer {
+        access,
+        register: registers::register_of(command),
+        auto_increment: command & AUTO_INCREMENT != 0,
+        len: data.len().min(usize::from(u8::MAX)) as u8,
+        bytes,
+    }
+}
+
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C> {
+    /// Starts a dry run: until [`Tca64xx::finish_dry_run`], every transfer is recorded
+    /// instead of being sent, so board bring-up, configuration transactions and queued
+    /// operations can be checked in host tests or code review without a device.
+    ///
+    /// Writes update the register cache as if they had succeeded, so later steps plan
+    /// against the earlier ones. Reads are answered from the cache, or with the power-on
+    /// defaults when a value is unknown (Output and Configuration `0xFF`, Polarity Inversion
+    /// `0x00`, inputs low). Does nothing if a dry run is already in progress. Requires the
+    /// `dry-run` feature.
+    ///
+    /// ```ignore
+    /// tca.begin_dry_run();
+    /// tca.apply_board_config(&FrontPanel::CONFIG)?;
+    /// let plan = tca.finish_dry_run().unwrap();
+    /// assert_eq!(plan.writes().count(), 2);
+    /// ```
+    pub fn begin_dry_run(&mut self) {
+        if self.dry_run.is_none() {
+            self.dry_run = Some(DryRun {
+                plan: DryRunPlan::default(),
+                cache: self.cache,
+                claims: self.claims,
+            });
+        }
+    }
+
+    /// Returns `true` while a dry run is in progress.
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run.is_some()
+    }
+
+    /// Ends the dry run, restoring the register cache and pin claims to their state at
+    /// [`Tca64xx::begin_dry_run`].
+    ///
+    /// # Returns
+    ///
+    /// Returns the recorded transfers, or `None` if no dry run was in progress.
+    pub fn finish_dry_run(&mut self) -> Option<DryRunPlan> {
+        let dry_run = self.dry_run.take()?;
+        self.cache = dry_run.cache;
+        self.claims = dry_run.claims;
+        Some(dry_run.plan)
+    }
+}
@@ -0,0 +1,67 @@
+//! Drivers on an I2C bus shared through `embassy-embedded-hal`.
+//!
+//! `embassy_embedded_hal::shared_bus` hands out one `I2cDevice` per device on the bus, each
+//! borrowing the bus mutex. The driver takes an `I2cDevice` like any other bus, so no adapter
+//! is needed; this module names the types and adds a constructor that creates the device.
+//!
+//! ```ignore
+//! use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+//! use static_cell::StaticCell;
+//! use tca6424::{DEFAULT_ADDRESS, SharedI2cBus, Tca6424};
+//!
+//! static I2C_BUS: StaticCell<SharedI2cBus<NoopRawMutex, I2c<'static, Async>>> = StaticCell::new();
+//! let bus = I2C_BUS.init(SharedI2cBus::new(i2c));
+//!
+//! let mut tca = Tca6424::new_on_shared_bus(bus, DEFAULT_ADDRESS)?;
+//! let mut sensor = OtherSensor::new(I2cDevice::new(bus));
+//! ```
+//!
+//! With the `async` feature, work on the expander and on the other devices can be awaited
+//! together with `join`. Each transaction locks the bus, so transfers never overlap; they
+//! interleave between transactions, and one driver's read-modify-write keeps its order:
+//!
+//! ```ignore
+//! let (set, sample) = join(tca.set_pin_output(Pin::P10, PinState::High), sensor.read()).await;
+//! ```
+
+use embassy_sync::blocking_mutex::raw::RawMutex;
+
+use crate::Tca6424;
+use crate::errors::Error;
+
+/// The mutex-protected bus shared by the devices (`embassy_sync::mutex::Mutex` with the
+/// `async` feature, `embassy_sync::blocking_mutex::Mutex` over a `RefCell` otherwise).
+#[cfg(feature = "async")]
+pub type SharedI2cBus<M, BUS> = embassy_sync::mutex::Mutex<M, BUS>;
+/// The mutex-protected bus shared by the devices (`embassy_sync::mutex::Mutex` with the
+/// `async` feature, `embassy_sync::blocking_mutex::Mutex` over a `RefCell` otherwise).
+#[cfg(not(feature = "async"))]
+pub type SharedI2cBus<M, BUS> = embassy_sync::blocking_mutex::Mutex<M, core::cell::RefCell<BUS>>;
+
+/// One device on a [`SharedI2cBus`]; locks the bus for the duration of each transaction.
+#[cfg(feature = "async")]
+pub type SharedI2cDevice<'a, M, BUS> =
+    embassy_embedded_hal::shared_bus::asynch::i2c::I2cDevice<'a, M, BUS>;
+/// One device on a [`SharedI2cBus`]; locks the bus for the duration of each transaction.
+#[cfg(not(feature = "async"))]
+pub type SharedI2cDevice<'a, M, BUS> =
+    embassy_embedded_hal::shared_bus::blocking::i2c::I2cDevice<'a, M, BUS>;
+
+impl<'a, M, BUS> Tca6424<SharedI2cDevice<'a, M, BUS>>
+where
+    M: RawMutex,
+    BUS: embedded_hal::i2c::ErrorType,
+{
+    /// Creates a driver on `bus`, wrapping it in a [`SharedI2cDevice`].
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Self)` on success, or an `Error` if the I2C bus operation fails.
+    pub fn new_on_shared_bus(
+        bus: &'a SharedI2cBus<M, BUS>,
+        address: u8,
+    ) -> Result<Self, Error<<SharedI2cDevice<'a, M, BUS> as embedded_hal::i2c::ErrorType>::Error>>
+    {
+        Self::new(SharedI2cDevice::new(bus), address)
+    }
+}
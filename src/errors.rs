@@ -5,19 +5,202 @@ use core::fmt::Debug;
 use defmt;
 
 /// Represents possible errors that can occur when interacting with the TCA6424 driver.
-#[derive(Debug)]
+///
+/// `Clone`, `PartialEq` and `Eq` are implemented when the I2C error type implements them, so
+/// tests can compare errors with `assert_eq!`.
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Error<I2cError: Debug> {
     /// An error occurred during an underlying I2C bus operation.
     I2c(I2cError),
     /// An attempt was made to access a reserved register address or an invalid pin.
     InvalidRegisterOrPin,
-    // TODO: Add more specific error types as needed, e.g., for invalid arguments
+    /// The device has been marked offline by hot-plug tracking; no bus transfer was attempted.
+    DeviceOffline,
+    /// The pin is already owned by another subsystem (see [`crate::Tca6424::claim_pin`]).
+    PinAlreadyClaimed(crate::Pin),
+    /// The logical channel has no pin in the channel table (see [`crate::ChannelMap`]).
+    UnmappedChannel(u8),
+    /// An I2C transfer did not complete within the timeout set with
+    /// `Tca6424::set_timeout_us` (async only). A register write that timed out leaves the
+    /// register dirty in the cache (see [`crate::Tca6424::sync_after_cancel`]). Also returned
+    /// when a display driven by the HD44780 helper keeps its busy flag set.
+    Timeout,
+    /// The fail-safe latch is engaged (see [`crate::FailSafePolicy`]); register writes are
+    /// refused until [`crate::Tca6424::clear_fault`] is called.
+    FailSafeLatched,
+    /// Consecutive reads of a verified or filtered read never agreed (see
+    /// [`crate::Tca6424::read_inputs_verified`] and [`crate::Tca6424::get_pin_input_stable`]).
+    InputsUnstable,
+    /// The address is reserved by the I2C specification, such as the general call address
+    /// (see [`crate::is_reserved_address`]).
+    ReservedAddress(u8),
+    /// A read was requested in write-only mode and the register cache does not hold the
+    /// value (see [`crate::Tca6424::set_write_only`]); no bus transfer was attempted.
+    WriteOnly,
+    /// A shared driver was accessed again from inside its own access closure (see
+    /// `SharedTca6424::lock` and `Pin::access_port_driver`); no bus transfer was attempted.
+    Busy,
 }
 
-// TODO: Implement From trait for I2cError if possible
-// impl<I2cError: Debug> From<I2cError> for Error<I2cError> {
-//     fn from(err: I2cError) -> Self {
-//         Error::I2c(err)
-//     }
-// }
\ No newline at end of file
+/// A configuration mistake, detected before any bus transfer is made.
+///
+/// Claiming pins, building helpers, looking up channels and checking addresses can only fail
+/// because of how the application set things up, never because of the bus. These operations
+/// return `ConfigError` instead of [`Error`], so applications can treat them as bugs (unwrap
+/// or assert at startup) while bus failures are retried or degraded at run time.
+///
+/// Runtime operations that also validate configuration, such as
+/// [`crate::Tca6424::apply_board_config`], report the same mistakes as the matching
+/// [`Error`] variants; [`Error::config_error`] recovers the `ConfigError` from them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ConfigError {
+    /// A register or pin argument is invalid, or two roles were given the same pin.
+    InvalidRegisterOrPin,
+    /// The pin is already owned by another subsystem (see [`crate::Tca6424::claim_pin`]).
+    PinAlreadyClaimed(crate::Pin),
+    /// The logical channel has no pin in the channel table (see [`crate::ChannelMap`]).
+    UnmappedChannel(u8),
+    /// The address is reserved by the I2C specification (see
+    /// [`crate::is_reserved_address`]).
+    ReservedAddress(u8),
+}
+
+impl ConfigError {
+    /// Returns the classification of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ConfigError::PinAlreadyClaimed(_) => ErrorKind::PinClaimed,
+            ConfigError::InvalidRegisterOrPin
+            | ConfigError::UnmappedChannel(_)
+            | ConfigError::ReservedAddress(_) => ErrorKind::InvalidArgument,
+        }
+    }
+}
+
+/// Classification of an [`Error`], independent of the I2C implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// An I2C transfer failed, classified by the HAL.
+    Bus(embedded_hal::i2c::ErrorKind),
+    /// The device has been marked offline by hot-plug tracking.
+    DeviceOffline,
+    /// An I2C transfer timed out.
+    Timeout,
+    /// The pin is owned by another subsystem.
+    PinClaimed,
+    /// A register, pin, channel or address argument is invalid.
+    InvalidArgument,
+    /// The fail-safe latch is engaged.
+    FailSafeLatched,
+    /// Repeated reads of the inputs disagreed.
+    InputsUnstable,
+    /// A read was refused in write-only mode.
+    WriteOnly,
+    /// The shared driver is already in use by the caller.
+    Busy,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ErrorKind {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{}", defmt::Debug2Format(self))
+    }
+}
+
+impl<I2cError: embedded_hal::i2c::Error> Error<I2cError> {
+    /// Returns the classification of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::I2c(error) => ErrorKind::Bus(error.kind()),
+            Error::DeviceOffline => ErrorKind::DeviceOffline,
+            Error::Timeout => ErrorKind::Timeout,
+            Error::PinAlreadyClaimed(_) => ErrorKind::PinClaimed,
+            Error::InvalidRegisterOrPin | Error::UnmappedChannel(_) | Error::ReservedAddress(_) => {
+                ErrorKind::InvalidArgument
+            }
+            Error::FailSafeLatched => ErrorKind::FailSafeLatched,
+            Error::InputsUnstable => ErrorKind::InputsUnstable,
+            Error::WriteOnly => ErrorKind::WriteOnly,
+            Error::Busy => ErrorKind::Busy,
+        }
+    }
+}
+
+/// Lets middleware that only understands HAL error kinds (retry layers, loggers) classify
+/// driver errors.
+///
+/// Bus errors forward the kind of the underlying I2C error, and an offline device reports
+/// `NoAcknowledge(Address)`, which is what it would produce on the bus. Every other error is
+/// `Other`. The inherent [`Error::kind`] takes precedence in method calls; call this one as
+/// `embedded_hal::i2c::Error::kind(&error)`.
+impl<I2cError: embedded_hal::i2c::Error> embedded_hal::i2c::Error for Error<I2cError> {
+    fn kind(&self) -> embedded_hal::i2c::ErrorKind {
+        match self {
+            Error::I2c(error) => error.kind(),
+            Error::DeviceOffline => embedded_hal::i2c::ErrorKind::NoAcknowledge(
+                embedded_hal::i2c::NoAcknowledgeSource::Address,
+            ),
+            _ => embedded_hal::i2c::ErrorKind::Other,
+        }
+    }
+}
+
+impl<I2cError: Debug> Error<I2cError> {
+    /// Converts a [`ConfigError`] into the matching variant, for use with `?` in functions
+    /// returning `Error`:
+    /// `tca.claim_pin(pin).map_err(Error::from_config)?`.
+    pub const fn from_config(error: ConfigError) -> Self {
+        match error {
+            ConfigError::InvalidRegisterOrPin => Error::InvalidRegisterOrPin,
+            ConfigError::PinAlreadyClaimed(pin) => Error::PinAlreadyClaimed(pin),
+            ConfigError::UnmappedChannel(channel) => Error::UnmappedChannel(channel),
+            ConfigError::ReservedAddress(address) => Error::ReservedAddress(address),
+        }
+    }
+
+    /// Returns the configuration mistake, if the error is one (see [`ConfigError`]).
+    pub fn config_error(&self) -> Option<ConfigError> {
+        match self {
+            Error::InvalidRegisterOrPin => Some(ConfigError::InvalidRegisterOrPin),
+            Error::PinAlreadyClaimed(pin) => Some(ConfigError::PinAlreadyClaimed(*pin)),
+            Error::UnmappedChannel(channel) => Some(ConfigError::UnmappedChannel(*channel)),
+            Error::ReservedAddress(address) => Some(ConfigError::ReservedAddress(*address)),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the error is a configuration mistake rather than a runtime failure.
+    pub fn is_config(&self) -> bool {
+        self.config_error().is_some()
+    }
+
+    /// Returns `true` if the error came from the I2C bus.
+    pub fn is_i2c(&self) -> bool {
+        matches!(self, Error::I2c(_))
+    }
+
+    /// Returns the I2C error, if the operation failed on the bus.
+    pub fn i2c_error(&self) -> Option<&I2cError> {
+        match self {
+            Error::I2c(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// Returns the I2C error by value, if the operation failed on the bus.
+    pub fn into_inner(self) -> Option<I2cError> {
+        match self {
+            Error::I2c(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl<I2cError: Debug> From<I2cError> for Error<I2cError> {
+    fn from(error: I2cError) -> Self {
+        Error::I2c(error)
+    }
+}
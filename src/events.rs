@@ -0,0 +1,506 @@
+//! Input change events filtered by pin and port subscriptions.
+
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::errors::Error;
+use crate::registers::Register;
+use crate::{BusAccess, Chip, DelayNs, Pin, PinState, Port, RegisterKind, Tca64xx, pins_from_mask};
+
+/// One pin level change reported by an [`InputWatcher`].
+///
+/// Every event reported by a watcher carries the next value of a wrapping sequence counter.
+/// A consumer that stores events in a bounded queue, and discards some when it is full, can
+/// see the loss as a gap in the sequence numbers ([`InputEvent::missed_since`]) and resync
+/// its view of the inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InputEvent {
+    /// The pin that changed.
+    pub pin: Pin,
+    /// Its new level.
+    pub state: PinState,
+    /// Sequence number: one more than that of the previous event of the same watcher.
+    pub seq: u32,
+}
+
+impl InputEvent {
+    /// Returns how many events were reported between the one numbered `previous_seq` and
+    /// this one; `0` when none were lost.
+    pub const fn missed_since(&self, previous_seq: u32) -> u32 {
+        self.seq.wrapping_sub(previous_seq).wrapping_sub(1)
+    }
+}
+
+/// The subscribed changes between two input images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InputChanges {
+    changed: u32,
+    levels: u32,
+    first_seq: u32,
+    resync: bool,
+}
+
+impl InputChanges {
+    /// Returns the mask of subscribed pins that changed (bit `n` = pin index `n`).
+    pub const fn changed_mask(&self) -> u32 {
+        self.changed
+    }
+
+    /// Returns the changed pins of one port as an 8-bit mask.
+    pub const fn port_mask(&self, port: Port) -> u8 {
+        (self.changed >> (port as u8 * 8)) as u8
+    }
+
+    /// Returns `true` if no subscribed pin changed.
+    pub const fn is_empty(&self) -> bool {
+        self.changed == 0
+    }
+
+    /// Returns `true` if these are the events of a full resync (see
+    /// [`InputWatcher::request_full_resync`]): every subscribed pin with its current level,
+    /// changed or not.
+    pub const fn is_resync(&self) -> bool {
+        self.resync
+    }
+
+    /// Returns the sequence number of the first event; the others follow consecutively.
+    pub const fn first_seq(&self) -> u32 {
+        self.first_seq
+    }
+
+    /// Iterates over the changes, lowest pin first.
+    pub fn iter(&self) -> impl Iterator<Item = InputEvent> + '_ {
+        pins_from_mask(self.changed)
+            .zip(0u32..)
+            .map(|(pin, index)| InputEvent {
+                pin,
+                state: PinState::from(self.levels & (1 << pin as u8) != 0),
+                seq: self.first_seq.wrapping_add(index),
+            })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl InputChanges {
+    /// Collects the changes into a vector, lowest pin first.
+    pub fn to_vec(&self) -> Vec<InputEvent> {
+        self.iter().collect()
+    }
+}
+
+/// Routes input events to boxed callbacks registered per pin or per port.
+///
+/// ```
+/// use tca6424::{EventDispatcher, InputWatcher, Port};
+///
+/// let mut presses = 0;
+/// let mut watcher = InputWatcher::new().with_port(Port::Port2);
+/// let mut dispatcher = EventDispatcher::new();
+/// dispatcher.on_port(Port::Port2, |_event| presses += 1);
+///
+/// watcher.update(0);
+/// dispatcher.dispatch(&watcher.update(0x03_00_00));
+/// drop(dispatcher);
+/// assert_eq!(presses, 2);
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Default)]
+pub struct EventDispatcher<'a> {
+    handlers: Vec<(u32, Callback<'a>)>,
+}
+
+#[cfg(feature = "alloc")]
+type Callback<'a> = Box<dyn FnMut(InputEvent) + 'a>;
+
+#[cfg(feature = "alloc")]
+impl<'a> EventDispatcher<'a> {
+    /// Creates a dispatcher without callbacks.
+    pub const fn new() -> Self {
+        Self {
+            handlers: Vec::new(),
+        }
+    }
+
+    /// Calls `callback` for every change of `pin`.
+    pub fn on_pin(&mut self, pin: Pin, callback: impl FnMut(InputEvent) + 'a) {
+        self.on_mask(1 << pin as u8, callback);
+    }
+
+    /// Calls `callback` for every change of a pin of `port`.
+    pub fn on_port(&mut self, port: Port, callback: impl FnMut(InputEvent) + 'a) {
+        self.on_mask(0xFF << (port as u8 * 8), callback);
+    }
+
+    /// Calls `callback` for every change of a pin in `mask` (bit `n` = pin index `n`).
+    pub fn on_mask(&mut self, mask: u32, callback: impl FnMut(InputEvent) + 'a) {
+        self.handlers.push((mask, Box::new(callback)));
+    }
+
+    /// Passes each change to the callbacks registered for its pin, in registration order.
+    pub fn dispatch(&mut self, changes: &InputChanges) {
+        for event in changes.iter() {
+            for (mask, callback) in &mut self.handlers {
+                if *mask & (1 << event.pin as u8) != 0 {
+                    callback(event);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl core::fmt::Debug for EventDispatcher<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EventDispatcher")
+            .field("handlers", &self.handlers.len())
+            .finish()
+    }
+}
+
+/// Transitions reported on one port by an [`InputWatcher`], returned by
+/// [`InputWatcher::port_activity`].
+///
+/// Counters saturate at `u32::MAX` instead of wrapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PortActivity {
+    /// Low-to-high transitions.
+    pub rising: u32,
+    /// High-to-low transitions.
+    pub falling: u32,
+}
+
+impl PortActivity {
+    /// Returns the total number of transitions.
+    pub const fn total(&self) -> u32 {
+        self.rising.saturating_add(self.falling)
+    }
+
+    /// Returns `true` if no transition was reported.
+    pub const fn is_idle(&self) -> bool {
+        self.rising == 0 && self.falling == 0
+    }
+}
+
+/// Tracks the input levels and reports changes on subscribed pins and ports.
+///
+/// Feed it the 24-bit input images returned by [`Tca6424::service_interrupt`] or
+/// [`Tca64xx::poll_input_events`]. Subscriptions are a pin mask, so subscribing a whole
+/// [`Port`] (for example the rows of a keypad) masks out the other two ports before any
+/// change is reported. The first image only sets the baseline and reports nothing.
+///
+/// A watcher built with [`InputWatcher::with_interrupt_mask`] also drops changes of pins
+/// whose interrupt is masked, so the events match what would assert INT.
+///
+/// The watcher also counts the rising and falling transitions it reports per port, for
+/// quick "is anything happening on this connector" diagnostics
+/// ([`InputWatcher::port_activity`]).
+///
+/// The Input Port registers report levels after the device's Polarity Inversion. Changing
+/// the inversion of a pin flips its reported level although nothing happened on the pin, so
+/// the watcher compares levels with the inversion undone (see
+/// [`InputWatcher::update_with_inversion`]) and reports a full resync instead of
+/// transitions when the inversion of a subscribed pin changed.
+///
+/// A watcher built with [`InputWatcher::with_latching`] also keeps a "changed" flag per pin.
+/// Every reported transition sets the pin's flag, and only [`InputWatcher::acknowledge`]
+/// clears it: reading the inputs again, which clears the device's INT, leaves it set. A
+/// consumer that handles the flags at its own pace, after an interrupt handler or a faster
+/// loop fed the watcher, sees a pulse that came and went between two of its own looks.
+///
+/// [`Tca6424::service_interrupt`]: crate::Tca6424::service_interrupt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InputWatcher {
+    subscribed: u32,
+    last: Option<u32>,
+    follow_interrupt_mask: bool,
+    next_seq: u32,
+    resync: bool,
+    activity: [PortActivity; 3],
+    inversion: u32,
+    latching: bool,
+    latched: u32,
+}
+
+impl InputWatcher {
+    /// Creates a watcher with no subscriptions.
+    pub const fn new() -> Self {
+        Self {
+            subscribed: 0,
+            last: None,
+            follow_interrupt_mask: false,
+            next_seq: 0,
+            resync: false,
+            activity: [PortActivity {
+                rising: 0,
+                falling: 0,
+            }; 3],
+            inversion: 0,
+            latching: false,
+            latched: 0,
+        }
+    }
+
+    /// Reports only changes of pins whose interrupt is enabled when `follow` is `true`.
+    ///
+    /// [`Tca64xx::poll_input_events`] then takes the enabled pins from the cached Interrupt
+    /// Mask registers (see [`Tca64xx::enabled_interrupts`]). Masked pins still update the
+    /// baseline, so unmasking a pin does not report a change that happened while it was
+    /// masked.
+    pub const fn with_interrupt_mask(mut self, follow: bool) -> Self {
+        self.follow_interrupt_mask = follow;
+        self
+    }
+
+    /// Returns `true` if the watcher reports only pins with their interrupt enabled.
+    pub const fn follows_interrupt_mask(&self) -> bool {
+        self.follow_interrupt_mask
+    }
+
+    /// Latches the pins whose change is reported until they are acknowledged when `latch` is
+    /// `true` (see [`InputWatcher::latched_mask`]). Disabling latching clears the flags.
+    pub const fn with_latching(mut self, latch: bool) -> Self {
+        self.latching = latch;
+        if !latch {
+            self.latched = 0;
+        }
+        self
+    }
+
+    /// Returns `true` if the watcher latches reported changes.
+    pub const fn is_latching(&self) -> bool {
+        self.latching
+    }
+
+    /// Returns the pins with a latched change not yet acknowledged (bit `n` = pin index `n`).
+    ///
+    /// A pin is latched by every transition the watcher reports, so unsubscribed pins and,
+    /// with [`InputWatcher::with_interrupt_mask`], masked pins never are. Full-resync events
+    /// are not transitions and latch nothing. Always `0` unless the watcher was built with
+    /// [`InputWatcher::with_latching`].
+    pub const fn latched_mask(&self) -> u32 {
+        self.latched
+    }
+
+    /// Returns `true` if `pin` has a latched change not yet acknowledged.
+    pub const fn is_latched(&self, pin: Pin) -> bool {
+        self.latched & (1 << pin as u8) != 0
+    }
+
+    /// Clears the latched change of `pin`, returning `true` if it was set.
+    pub fn acknowledge(&mut self, pin: Pin) -> bool {
+        self.acknowledge_mask(1 << pin as u8) != 0
+    }
+
+    /// Clears the latched changes of the pins of `mask` and returns those that were set.
+    pub fn acknowledge_mask(&mut self, mask: u32) -> u32 {
+        let acknowledged = self.latched & mask;
+        self.latched &= !mask;
+        acknowledged
+    }
+
+    /// Subscribes to changes of `pin`.
+    pub const fn with_pin(mut self, pin: Pin) -> Self {
+        self.subscribed |= 1 << pin as u8;
+        self
+    }
+
+    /// Subscribes to changes of every pin of `port`.
+    pub const fn with_port(mut self, port: Port) -> Self {
+        self.subscribed |= 0xFF << (port as u8 * 8);
+        self
+    }
+
+    /// Subscribes to changes of `pin`.
+    pub fn subscribe_pin(&mut self, pin: Pin) {
+        *self = self.with_pin(pin);
+    }
+
+    /// Subscribes to changes of every pin of `port`.
+    pub fn subscribe_port(&mut self, port: Port) {
+        *self = self.with_port(port);
+    }
+
+    /// Removes the subscription of `pin`, including one obtained through its port.
+    pub fn unsubscribe_pin(&mut self, pin: Pin) {
+        self.subscribed &= !(1 << pin as u8);
+    }
+
+    /// Removes the subscriptions of every pin of `port`.
+    pub fn unsubscribe_port(&mut self, port: Port) {
+        self.subscribed &= !(0xFF << (port as u8 * 8));
+    }
+
+    /// Returns the mask of subscribed pins.
+    pub const fn subscribed_mask(&self) -> u32 {
+        self.subscribed
+    }
+
+    /// Makes the next update report every subscribed pin with its current level, whether it
+    /// changed or not, so a consumer that detected lost events can rebuild its state.
+    ///
+    /// The resync events ignore the interrupt mask and are numbered like any other; the
+    /// [`InputChanges`] carrying them reports [`InputChanges::is_resync`].
+    pub fn request_full_resync(&mut self) {
+        self.resync = true;
+    }
+
+    /// Returns `true` if a full resync was requested and not yet reported.
+    pub const fn is_resync_pending(&self) -> bool {
+        self.resync
+    }
+
+    /// Returns the sequence number the next reported event will carry.
+    pub const fn next_seq(&self) -> u32 {
+        self.next_seq
+    }
+
+    /// Returns the transitions reported on `port` since the watcher was created or the
+    /// port's counters were last taken.
+    ///
+    /// Only reported changes count, so unsubscribed pins and, with
+    /// [`InputWatcher::with_interrupt_mask`], masked pins are left out. Full-resync events
+    /// are not transitions and are not counted.
+    pub const fn port_activity(&self, port: Port) -> PortActivity {
+        *port.entry(&self.activity)
+    }
+
+    /// Returns the transitions reported on `port` and resets its counters.
+    pub fn take_port_activity(&mut self, port: Port) -> PortActivity {
+        core::mem::take(port.entry_mut(&mut self.activity))
+    }
+
+    /// Resets the transition counters of every port.
+    pub fn reset_activity(&mut self) {
+        self.activity = [PortActivity::default(); 3];
+    }
+
+    /// Records a new input image and returns the subscribed changes since the previous one.
+    ///
+    /// The interrupt mask is not consulted; see [`InputWatcher::update_enabled`].
+    pub fn update(&mut self, inputs: u32) -> InputChanges {
+        self.update_enabled(inputs, u32::MAX)
+    }
+
+    /// Records a new input image and returns the subscribed changes since the previous one,
+    /// restricted to the pins of `enabled` (bit `n` = pin index `n`), such as the pins with
+    /// their interrupt enabled.
+    ///
+    /// The Polarity Inversion is taken as unchanged since the previous image.
+    pub fn update_enabled(&mut self, inputs: u32, enabled: u32) -> InputChanges {
+        self.update_with_inversion(inputs, enabled, self.inversion)
+    }
+
+    /// Records a new input image read with the Polarity Inversion `inversion` (bit `n` = pin
+    /// index `n`, `1` = inverted) and returns the subscribed changes since the previous one,
+    /// restricted to the pins of `enabled`.
+    ///
+    /// A pin is reported when its level on the pin changed, whatever the inversion of either
+    /// image; events carry the levels of `inputs`, inversion included. If the inversion of a
+    /// subscribed pin differs from the previous image, the update is a full resync (see
+    /// [`InputWatcher::request_full_resync`]), so consumers pick up the flipped levels.
+    pub fn update_with_inversion(
+        &mut self,
+        inputs: u32,
+        enabled: u32,
+        inversion: u32,
+    ) -> InputChanges {
+        let inversion_changed =
+            self.last.is_some() && (self.inversion ^ inversion) & self.subscribed != 0;
+        let resync = core::mem::take(&mut self.resync) || inversion_changed;
+        let transitions = match self.last {
+            Some(last) => (last ^ self.inversion ^ inputs ^ inversion) & self.subscribed & enabled,
+            None => 0,
+        };
+        let changed = if resync { self.subscribed } else { transitions };
+        self.last = Some(inputs);
+        self.inversion = inversion;
+        if self.latching {
+            self.latched |= transitions;
+        }
+        self.count(transitions, inputs);
+        let first_seq = self.next_seq;
+        self.next_seq = first_seq.wrapping_add(changed.count_ones());
+        InputChanges {
+            changed,
+            levels: inputs,
+            first_seq,
+            resync,
+        }
+    }
+
+    /// Adds the rising and falling `transitions` to `inputs` to the per-port counters.
+    fn count(&mut self, transitions: u32, inputs: u32) {
+        for (port, activity) in Port::ALL.into_iter().zip(&mut self.activity) {
+            let (changed, levels) = (port.byte_of(transitions), port.byte_of(inputs));
+            let rising = (changed & levels).count_ones();
+            let falling = (changed & !levels).count_ones();
+            activity.rising = activity.rising.saturating_add(rising);
+            activity.falling = activity.falling.saturating_add(falling);
+        }
+    }
+}
+
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C> {
+    /// Returns the pins whose interrupt is enabled according to the register cache (bit `n` =
+    /// pin index `n`).
+    ///
+    /// A pin is enabled when its Interrupt Mask bit is `0`. Ports whose Interrupt Mask register
+    /// is not in the cache are reported as enabled, since the driver cannot tell which of
+    /// their pins are masked; on the TCA6416A, which has no Interrupt Mask, every pin of its
+    /// two ports is enabled. Ports the part does not have are left out. No transfer is made.
+    pub fn enabled_interrupts(&self) -> u32 {
+        Port::ALL.iter().take(C::PORTS).fold(0, |enabled, &port| {
+            let mask = self
+                .cache
+                .get(RegisterKind::InterruptMask, port)
+                .unwrap_or(0);
+            enabled | u32::from(!mask) << (port as u8 * 8)
+        })
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca64xx",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Reads the inputs and returns the changes `watcher` is subscribed to.
+    ///
+    /// The Input Port registers of the part (three on the TCA6424, two on the TCA6416A) are
+    /// read in one transaction. If the watcher follows the interrupt mask, changes of pins
+    /// masked in the cached Interrupt Mask registers are not reported. Levels are compared with the cached Polarity Inversion
+    /// undone (see [`Tca64xx::inverted_inputs`]), so changing the inversion between polls
+    /// reports a full resync rather than transitions.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the subscribed changes, or an `Error` if the I2C bus operation fails.
+    pub async fn poll_input_events(
+        &mut self,
+        watcher: &mut InputWatcher,
+    ) -> Result<InputChanges, Error<I2C::Error>> {
+        let mut values = [0u8; 4];
+        let inputs = values
+            .get_mut(..C::PORTS)
+            .ok_or(Error::InvalidRegisterOrPin)?;
+        self.read_registers_ai(Register::InputPort0, inputs).await?;
+        let enabled = if watcher.follows_interrupt_mask() {
+            self.enabled_interrupts()
+        } else {
+            u32::MAX
+        };
+        let inversion = self.inverted_inputs();
+        Ok(watcher.update_with_inversion(u32::from_le_bytes(values), enabled, inversion))
+    }
+}
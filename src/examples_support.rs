@@ -0,0 +1,106 @@
+//! The steps every example performs (bring-up, input scan, output drive), as functions
+//! shared by the MCU examples and user quick-starts.
+//!
+//! The examples call these instead of repeating the register sequences, so an example can
+//! not drift from what the library does. Each function works on any bus and in both sync
+//! and async mode; logging and error reporting stay in the example.
+//!
+//! ```ignore
+//! use tca6424::{DEFAULT_ADDRESS, examples_support};
+//!
+//! // P00-P07 drive LEDs, the other pins are buttons
+//! let mut tca = examples_support::init(i2c, DEFAULT_ADDRESS, 0x00_00FF).await?;
+//! loop {
+//!     let buttons = examples_support::scan_inputs(&mut tca).await?;
+//!     examples_support::drive_outputs(&mut tca, buttons >> 8).await?;
+//! }
+//! ```
+
+use crate::errors::Error;
+use crate::{BusAccess, DelayNs, Port, Tca6424};
+
+/// Creates a driver and makes the pins of `outputs` (bit `n` = pin index `n`) outputs
+/// driving low; every other pin is an input.
+///
+/// The Output Port registers are written before the Configuration registers, so no pin
+/// drives the power-on-high latch, even briefly.
+///
+/// This function is `async` when the `async` feature is enabled, and synchronous otherwise.
+///
+/// # Returns
+///
+/// Returns `Ok(Tca6424)` on success, or an `Error` if the I2C bus operation fails.
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), keep_self),
+    async(feature = "async", keep_self)
+)]
+pub async fn init<I2C>(
+    i2c: I2C,
+    address: u8,
+    outputs: u32,
+) -> Result<Tca6424<I2C>, Error<I2C::Error>>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+{
+    let mut tca = Tca6424::new(i2c, address)?;
+    for port in Port::ALL {
+        tca.set_port_output(port, 0x00).await?;
+    }
+    for port in Port::ALL {
+        tca.set_port_direction(port, !port.byte_of(outputs)).await?;
+    }
+    Ok(tca)
+}
+
+/// Reads the input levels of all 24 pins (bit `n` = pin index `n`), one port at a time.
+///
+/// This function is `async` when the `async` feature is enabled, and synchronous otherwise.
+///
+/// # Returns
+///
+/// Returns `Ok(levels)` on success, or an `Error` if the I2C bus operation fails.
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), keep_self),
+    async(feature = "async", keep_self)
+)]
+pub async fn scan_inputs<I2C, D>(tca: &mut Tca6424<I2C, D>) -> Result<u32, Error<I2C::Error>>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    let mut levels = 0;
+    for port in Port::ALL {
+        let value = tca.get_port_input_state(port).await?;
+        levels |= u32::from(value) << (port as u32 * 8);
+    }
+    Ok(levels)
+}
+
+/// Writes the Output Port registers of all three ports from `values` (bit `n` = pin index
+/// `n`). Only pins configured as outputs change level.
+///
+/// This function is `async` when the `async` feature is enabled, and synchronous otherwise.
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an `Error` if the I2C bus operation fails.
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), keep_self),
+    async(feature = "async", keep_self)
+)]
+pub async fn drive_outputs<I2C, D>(
+    tca: &mut Tca6424<I2C, D>,
+    values: u32,
+) -> Result<(), Error<I2C::Error>>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    for port in Port::ALL {
+        tca.set_port_output(port, port.byte_of(values)).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,124 @@
+//! Device-agnostic pin interface for application code.
+
+use crate::errors::Error;
+use crate::{BusAccess, Chip, DelayNs, Pin, PinDirection, PinState, Tca64xx};
+
+/// A pin-by-pin interface shared by every supported expander.
+///
+/// Pins are addressed by flat index (`0` is `P00`, `8` is `P10`, ...), up to
+/// [`Expander::PIN_COUNT`]. Application logic written against this trait can be tested on the
+/// simulator (`Tca6424<RegisterModel>`, `testing` feature) and deployed on a TCA6424 or, with
+/// the `tca6416` feature, a TCA6416A. Indices outside the device return
+/// `Error::InvalidRegisterOrPin`.
+#[cfg(not(feature = "async"))]
+pub trait Expander {
+    /// Error returned by device operations.
+    type Error;
+
+    /// Number of pins provided by the device.
+    const PIN_COUNT: u8;
+
+    /// Sets the direction of pin `index`.
+    fn set_direction(&mut self, index: u8, direction: PinDirection) -> Result<(), Self::Error>;
+
+    /// Returns the direction of pin `index`.
+    fn direction(&mut self, index: u8) -> Result<PinDirection, Self::Error>;
+
+    /// Sets the output latch of pin `index`.
+    fn set_output(&mut self, index: u8, state: PinState) -> Result<(), Self::Error>;
+
+    /// Returns the output latch of pin `index`.
+    fn output(&mut self, index: u8) -> Result<PinState, Self::Error>;
+
+    /// Returns the input level of pin `index`.
+    fn input(&mut self, index: u8) -> Result<PinState, Self::Error>;
+}
+
+/// A pin-by-pin interface shared by every supported expander.
+///
+/// Pins are addressed by flat index (`0` is `P00`, `8` is `P10`, ...), up to
+/// [`Expander::PIN_COUNT`]. Application logic written against this trait can be tested on the
+/// simulator (`Tca6424<RegisterModel>`, `testing` feature) and deployed on a TCA6424 or, with
+/// the `tca6416` feature, a TCA6416A. Indices outside the device return
+/// `Error::InvalidRegisterOrPin`.
+#[cfg(feature = "async")]
+#[allow(async_fn_in_trait)]
+pub trait Expander {
+    /// Error returned by device operations.
+    type Error;
+
+    /// Number of pins provided by the device.
+    const PIN_COUNT: u8;
+
+    /// Sets the direction of pin `index`.
+    async fn set_direction(
+        &mut self,
+        index: u8,
+        direction: PinDirection,
+    ) -> Result<(), Self::Error>;
+
+    /// Returns the direction of pin `index`.
+    async fn direction(&mut self, index: u8) -> Result<PinDirection, Self::Error>;
+
+    /// Sets the output latch of pin `index`.
+    async fn set_output(&mut self, index: u8, state: PinState) -> Result<(), Self::Error>;
+
+    /// Returns the output latch of pin `index`.
+    async fn output(&mut self, index: u8) -> Result<PinState, Self::Error>;
+
+    /// Returns the input level of pin `index`.
+    async fn input(&mut self, index: u8) -> Result<PinState, Self::Error>;
+}
+
+/// Returns the pin with flat index `index`, if it is below `count`.
+fn pin<E: core::fmt::Debug>(index: u8, count: u8) -> Result<Pin, Error<E>> {
+    match Pin::from_index(index) {
+        Some(pin) if index < count => Ok(pin),
+        _ => Err(Error::InvalidRegisterOrPin),
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca64xx",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D, C> Expander for Tca64xx<I2C, D, C>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+    C: Chip,
+{
+    type Error = Error<I2C::Error>;
+
+    const PIN_COUNT: u8 = C::PORTS as u8 * 8;
+
+    async fn set_direction(
+        &mut self,
+        index: u8,
+        direction: PinDirection,
+    ) -> Result<(), Self::Error> {
+        let pin = pin(index, Self::PIN_COUNT)?;
+        self.set_pin_direction(pin, direction).await
+    }
+
+    async fn direction(&mut self, index: u8) -> Result<PinDirection, Self::Error> {
+        let pin = pin(index, Self::PIN_COUNT)?;
+        self.get_pin_direction(pin).await
+    }
+
+    async fn set_output(&mut self, index: u8, state: PinState) -> Result<(), Self::Error> {
+        let pin = pin(index, Self::PIN_COUNT)?;
+        self.set_pin_output(pin, state).await
+    }
+
+    async fn output(&mut self, index: u8) -> Result<PinState, Self::Error> {
+        let pin = pin(index, Self::PIN_COUNT)?;
+        self.get_pin_output_state(pin).await
+    }
+
+    async fn input(&mut self, index: u8) -> Result<PinState, Self::Error> {
+        let pin = pin(index, Self::PIN_COUNT)?;
+        self.get_pin_input_state(pin).await
+    }
+}
@@ -0,0 +1,252 @@
+//! Fail-safe latch: drives a safe output frame when the driver hits a hazardous error.
+
+use bitflags::bitflags;
+
+use crate::errors::{ConfigError, Error};
+use crate::registers::{self, Register};
+use crate::{BusAccess, Chip, DelayNs, Port, RegisterKind, Tca64xx};
+
+bitflags! {
+    /// Error classes that engage the fail-safe latch.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct FailSafeTriggers: u8 {
+        /// An I2C transfer failed.
+        const BUS_ERROR = 0b0001;
+        /// An I2C transfer timed out (async only).
+        const TIMEOUT = 0b0010;
+        /// Hot-plug tracking marked the device offline (`hot-plug` feature).
+        const DEVICE_OFFLINE = 0b0100;
+        /// `Tca6424::health_check` found the device reset or its configuration differing from
+        /// the cache (`diagnostics` feature).
+        const VERIFY_FAILURE = 0b1000;
+    }
+}
+
+/// The safe output frame and the errors that apply it.
+///
+/// When a bus call fails with an error of one of the `triggers` classes, the driver
+/// immediately drives the pins in `mask_high` high and those in `mask_low` low, makes them
+/// outputs, and latches itself faulted: further register writes fail with
+/// [`Error::FailSafeLatched`] until [`Tca64xx::clear_fault`] is called. Reads keep working.
+/// Pins outside both masks are left untouched.
+///
+/// ```
+/// use tca6424::{FailSafePolicy, FailSafeTriggers, Pin};
+///
+/// // Heater off (P00 high = off), pump off (P01 low = off)
+/// let policy = FailSafePolicy::new(
+///     1 << Pin::P00 as u8,
+///     1 << Pin::P01 as u8,
+///     FailSafeTriggers::DEVICE_OFFLINE | FailSafeTriggers::VERIFY_FAILURE,
+/// );
+/// assert_eq!(policy.mask(), 0b11);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FailSafePolicy {
+    mask_high: u32,
+    mask_low: u32,
+    triggers: FailSafeTriggers,
+}
+
+impl FailSafePolicy {
+    /// Creates a policy driving `mask_high` high and `mask_low` low (bit `n` = pin index `n`)
+    /// on any error in `triggers`. A pin in both masks is driven low.
+    pub const fn new(mask_high: u32, mask_low: u32, triggers: FailSafeTriggers) -> Self {
+        Self {
+            mask_high: mask_high & !mask_low & 0x00FF_FFFF,
+            mask_low: mask_low & 0x00FF_FFFF,
+            triggers,
+        }
+    }
+
+    /// Returns the pins driven by the safe frame.
+    pub const fn mask(&self) -> u32 {
+        self.mask_high | self.mask_low
+    }
+
+    /// Returns the pins driven high by the safe frame.
+    pub const fn mask_high(&self) -> u32 {
+        self.mask_high
+    }
+
+    /// Returns the pins driven low by the safe frame.
+    pub const fn mask_low(&self) -> u32 {
+        self.mask_low
+    }
+
+    /// Returns the error classes that engage the latch.
+    pub const fn triggers(&self) -> FailSafeTriggers {
+        self.triggers
+    }
+}
+
+/// Why and how the fail-safe latch engaged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FailSafeFault {
+    /// The error class that engaged the latch (a single flag).
+    pub cause: FailSafeTriggers,
+    /// `true` if the safe frame was written to the device. `false` means the device did not
+    /// accept it, which is expected when it is offline.
+    pub applied: bool,
+}
+
+/// Policy and latch state stored in the driver.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct FailSafe {
+    policy: Option<FailSafePolicy>,
+    fault: Option<FailSafeFault>,
+}
+
+impl FailSafe {
+    pub(crate) const fn new() -> Self {
+        Self {
+            policy: None,
+            fault: None,
+        }
+    }
+
+    /// Refuses writes while the latch is engaged.
+    pub(crate) fn check<E: core::fmt::Debug>(&self) -> Result<(), Error<E>> {
+        if self.fault.is_some() {
+            Err(Error::FailSafeLatched)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the armed policy if `cause` engages it and the latch is not engaged yet.
+    fn armed_for(&self, cause: FailSafeTriggers) -> Option<FailSafePolicy> {
+        match self.policy {
+            Some(policy) if self.fault.is_none() && policy.triggers.intersects(cause) => {
+                Some(policy)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C> {
+    /// Arms the fail-safe latch with `policy`, or disarms it with `None`.
+    ///
+    /// Disarming does not clear an engaged latch; see [`Tca64xx::clear_fault`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::InvalidRegisterOrPin`], and keeps the previous policy, if the
+    /// safe frame drives a pin the part does not have, such as `P20`-`P27` on the TCA6416A.
+    pub fn set_fail_safe(&mut self, policy: Option<FailSafePolicy>) -> Result<(), ConfigError> {
+        if let Some(policy) = policy
+            && policy.mask() >> (C::PORTS * 8) != 0
+        {
+            return Err(ConfigError::InvalidRegisterOrPin);
+        }
+        self.fail_safe.policy = policy;
+        Ok(())
+    }
+
+    /// Returns the armed fail-safe policy.
+    pub fn fail_safe(&self) -> Option<FailSafePolicy> {
+        self.fail_safe.policy
+    }
+
+    /// Returns `true` while the fail-safe latch is engaged.
+    pub fn is_faulted(&self) -> bool {
+        self.fail_safe.fault.is_some()
+    }
+
+    /// Returns why the fail-safe latch engaged, or `None` if it is not engaged.
+    pub fn fail_safe_fault(&self) -> Option<FailSafeFault> {
+        self.fail_safe.fault
+    }
+
+    /// Releases the fail-safe latch so register writes are accepted again, and returns the
+    /// fault it held.
+    ///
+    /// The safe frame stays on the pins until the application drives them again.
+    pub fn clear_fault(&mut self) -> Option<FailSafeFault> {
+        self.fail_safe.fault.take()
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca64xx",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Engages the fail-safe latch if the armed policy triggers on the class of `error`.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    pub(crate) async fn trip_fail_safe_on(&mut self, error: &Error<I2C::Error>) {
+        let cause = match error {
+            Error::I2c(_) => FailSafeTriggers::BUS_ERROR,
+            Error::Timeout => FailSafeTriggers::TIMEOUT,
+            Error::DeviceOffline => FailSafeTriggers::DEVICE_OFFLINE,
+            _ => FailSafeTriggers::empty(),
+        };
+        // The NACK that takes the device offline is also reported as a bus error.
+        #[cfg(feature = "hot-plug")]
+        let cause = match self.link_state() {
+            crate::LinkState::Offline => cause | FailSafeTriggers::DEVICE_OFFLINE,
+            crate::LinkState::Online => cause,
+        };
+        self.trip_fail_safe(cause).await;
+    }
+
+    /// Drives the safe frame and engages the latch if the armed policy triggers on `cause`.
+    ///
+    /// The frame is written port by port, Output register first so the pins switch to
+    /// outputs at their safe level. Bits outside the frame keep their cached value, or the
+    /// power-on default (`0xFF`) if it is unknown. Ports and registers the part does not have
+    /// are skipped. Offline tracking is bypassed so the frame is attempted even on a device
+    /// that stopped answering.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    pub(crate) async fn trip_fail_safe(&mut self, cause: FailSafeTriggers) {
+        let Some(policy) = self.fail_safe.armed_for(cause) else {
+            return;
+        };
+        let cause = FailSafeTriggers::from_bits_truncate(
+            1 << (policy.triggers & cause).bits().trailing_zeros(),
+        );
+
+        let mut applied = true;
+        for port in Port::ALL.into_iter().take(C::PORTS) {
+            let shift = port as u8 * 8;
+            let mask = (policy.mask() >> shift) as u8;
+            if mask == 0 {
+                continue;
+            }
+            let high = (policy.mask_high >> shift) as u8;
+            let output = self.cache.get(RegisterKind::Output, port).unwrap_or(0xFF) & !mask | high;
+            let configuration = self
+                .cache
+                .get(RegisterKind::Configuration, port)
+                .unwrap_or(0xFF)
+                & !mask;
+            for (kind, value) in [
+                (RegisterKind::Output, output),
+                (RegisterKind::Configuration, configuration),
+            ] {
+                let register = Register::of(kind, port);
+                if !C::has(register) {
+                    continue;
+                }
+                let command_byte = registers::command_byte(register, false);
+                let previous = self.cache.get(kind, port);
+                match self.send(command_byte, &[value]).await {
+                    Ok(()) => self.record_write(kind, port, previous, value),
+                    Err(_) => {
+                        self.cache.invalidate(kind, port);
+                        applied = false;
+                    }
+                }
+            }
+        }
+        self.fail_safe.fault = Some(FailSafeFault { cause, applied });
+    }
+}
@@ -0,0 +1,168 @@
+//! Fallback input values for control loops that must keep running through failed reads.
+//! Without the `fallback` feature a failed input read returns its error.
+
+use crate::errors::Error;
+use crate::registers::Register;
+use crate::{BusAccess, Chip, DelayNs, Port, RegisterKind, Tca64xx};
+#[cfg(feature = "fallback")]
+use crate::{Pin, PinState};
+
+/// Per-pin input values substituted for a failed input read.
+///
+/// With a fallback set (see [`Tca64xx::set_input_fallback`]), a failing input read is
+/// retried up to [`InputFallback::retries`] times; if every attempt fails, the read returns
+/// the fallback values instead of an error and the driver enters degraded mode
+/// ([`Tca64xx::is_degraded`]). Pick each pin's fallback as its safe state, such as "limit
+/// switch reached" or "door open", so a loop acting on stale inputs errs on the safe side.
+///
+/// ```
+/// use tca6424::{InputFallback, Pin, PinState};
+///
+/// let fallback = InputFallback::new(0)
+///     .with_pin(Pin::P10, PinState::High)
+///     .with_retries(2);
+/// assert_eq!(fallback.values(), 1 << 8);
+/// ```
+#[cfg(feature = "fallback")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InputFallback {
+    values: u32,
+    retries: u8,
+}
+
+#[cfg(feature = "fallback")]
+impl InputFallback {
+    /// Creates a fallback substituting `values` (bit `n` = pin index `n`) without retrying.
+    pub const fn new(values: u32) -> Self {
+        Self {
+            values: values & 0x00FF_FFFF,
+            retries: 0,
+        }
+    }
+
+    /// Sets the fallback value of `pin`.
+    pub const fn with_pin(mut self, pin: Pin, state: PinState) -> Self {
+        let bit = 1 << pin as u32;
+        self.values = match state {
+            PinState::High => self.values | bit,
+            PinState::Low => self.values & !bit,
+        };
+        self
+    }
+
+    /// Retries a failed read `retries` times before substituting the fallback values.
+    pub const fn with_retries(mut self, retries: u8) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Returns the fallback values (bit `n` = pin index `n`).
+    pub const fn values(&self) -> u32 {
+        self.values
+    }
+
+    /// Returns the number of retries before the fallback values are used.
+    pub const fn retries(&self) -> u8 {
+        self.retries
+    }
+}
+
+/// Fallback configuration and degraded-mode state of a driver.
+#[cfg(feature = "fallback")]
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct FallbackState {
+    config: Option<InputFallback>,
+    degraded: bool,
+    substitutions: u32,
+}
+
+#[cfg(feature = "fallback")]
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C> {
+    /// Enables fallback values for failed input reads (see [`InputFallback`]).
+    ///
+    /// Applies to [`Tca64xx::get_pin_input_state`], [`Tca64xx::get_port_input_state`] and
+    /// the methods built on them. Verified and filtered reads
+    /// ([`Tca64xx::read_inputs_verified`], [`Tca64xx::get_pin_input_stable`]) never
+    /// substitute values: they exist for signals where a made-up level is worse than an
+    /// error.
+    pub fn set_input_fallback(&mut self, fallback: InputFallback) {
+        self.fallback.config = Some(fallback);
+    }
+
+    /// Disables fallback values, so failed input reads return their error again, and leaves
+    /// degraded mode.
+    pub fn clear_input_fallback(&mut self) {
+        self.fallback = FallbackState::default();
+    }
+
+    /// Returns the fallback set with [`Tca64xx::set_input_fallback`], if any.
+    pub fn input_fallback(&self) -> Option<InputFallback> {
+        self.fallback.config
+    }
+
+    /// Returns `true` if the last input read returned fallback values. The flag clears with
+    /// the next successful input read.
+    pub fn is_degraded(&self) -> bool {
+        self.fallback.degraded
+    }
+
+    /// Returns the number of input reads answered with fallback values since the fallback
+    /// was set.
+    pub fn fallback_count(&self) -> u32 {
+        self.fallback.substitutions
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca64xx",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Reads the Input Port register of `port`, retrying and substituting the fallback
+    /// values when a fallback is set.
+    #[cfg(feature = "fallback")]
+    pub(crate) async fn read_input_register(
+        &mut self,
+        port: Port,
+    ) -> Result<u8, Error<I2C::Error>> {
+        let register = Register::of(RegisterKind::Input, port);
+        let mut result = self.read_register(register).await;
+        let fallback = match self.fallback.config {
+            Some(fallback) => fallback,
+            None => return result,
+        };
+        for _ in 0..fallback.retries {
+            if result.is_ok() {
+                break;
+            }
+            result = self.read_register(register).await;
+        }
+        match result {
+            Ok(value) => {
+                self.fallback.degraded = false;
+                Ok(value)
+            }
+            Err(_) => {
+                self.fallback.degraded = true;
+                self.fallback.substitutions = self.fallback.substitutions.saturating_add(1);
+                Ok(port.byte_of(fallback.values))
+            }
+        }
+    }
+
+    /// Reads the Input Port register of `port`.
+    #[cfg(not(feature = "fallback"))]
+    pub(crate) async fn read_input_register(
+        &mut self,
+        port: Port,
+    ) -> Result<u8, Error<I2C::Error>> {
+        self.read_register(Register::of(RegisterKind::Input, port))
+            .await
+    }
+}
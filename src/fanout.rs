@@ -0,0 +1,140 @@
+//! Input events shared by several consumers through one ring buffer.
+
+use crate::{InputChanges, InputEvent};
+
+/// Identifies a subscriber of an [`EventFanout`], returned by [`EventFanout::subscribe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SubscriberId(usize);
+
+impl SubscriberId {
+    /// Returns the slot of the subscriber, below the fan-out's subscriber count.
+    pub const fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// Hands every input event to up to `S` subscribers, each reading at its own pace.
+///
+/// The events live once, in a ring buffer of the last `N`; every subscriber has its own
+/// cursor into it, so a logger and the control logic can both consume the changes of one
+/// [`InputWatcher`](crate::InputWatcher) without coordinating. Publishing never waits for a
+/// subscriber: once a subscriber is `N` events behind, the oldest events are overwritten and
+/// it continues with the oldest one left. It sees the loss as a gap in the sequence numbers
+/// ([`InputEvent::missed_since`]) and can request a
+/// [full resync](crate::InputWatcher::request_full_resync), which the others receive too.
+///
+/// ```
+/// use tca6424::{EventFanout, InputWatcher, Port};
+///
+/// let mut watcher = InputWatcher::new().with_port(Port::Port0);
+/// let mut fanout: EventFanout<16, 2> = EventFanout::new();
+/// let logger = fanout.subscribe().unwrap();
+/// let control = fanout.subscribe().unwrap();
+///
+/// watcher.update(0);
+/// fanout.publish_changes(&watcher.update(0x03));
+/// assert_eq!(fanout.pending(logger), 2);
+/// while let Some(_event) = fanout.next(control) {}
+/// assert_eq!(fanout.pending(logger), 2);
+/// assert_eq!(fanout.pending(control), 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct EventFanout<const N: usize, const S: usize> {
+    events: [Option<InputEvent>; N],
+    /// Slot of the next event to publish.
+    next_slot: usize,
+    /// Number of events published so far, wrapping.
+    published: u32,
+    /// Value of `published` up to which each subscriber has read; `None` for a free slot.
+    cursors: [Option<u32>; S],
+}
+
+impl<const N: usize, const S: usize> EventFanout<N, S> {
+    /// Creates a fan-out with no subscribers, buffering the last `N` (at least 1) events.
+    pub const fn new() -> Self {
+        const { assert!(N > 0, "a fan-out buffers at least one event") };
+        Self {
+            events: [None; N],
+            next_slot: 0,
+            published: 0,
+            cursors: [None; S],
+        }
+    }
+
+    /// Adds a subscriber, which receives the events published from now on. Returns `None`
+    /// if all `S` subscriber slots are taken.
+    pub fn subscribe(&mut self) -> Option<SubscriberId> {
+        let (index, cursor) = self
+            .cursors
+            .iter_mut()
+            .enumerate()
+            .find(|(_, cursor)| cursor.is_none())?;
+        *cursor = Some(self.published);
+        Some(SubscriberId(index))
+    }
+
+    /// Removes a subscriber, freeing its slot. Returns `false` if it was not subscribed.
+    pub fn unsubscribe(&mut self, id: SubscriberId) -> bool {
+        self.cursors.get_mut(id.0).and_then(Option::take).is_some()
+    }
+
+    /// Returns the number of subscribers.
+    pub fn subscribers(&self) -> usize {
+        self.cursors.iter().flatten().count()
+    }
+
+    /// Appends `event` for every subscriber, overwriting the oldest event if the buffer is
+    /// full.
+    pub fn publish(&mut self, event: InputEvent) {
+        if let Some(slot) = self.events.get_mut(self.next_slot) {
+            *slot = Some(event);
+        }
+        self.next_slot = (self.next_slot + 1) % N;
+        self.published = self.published.wrapping_add(1);
+    }
+
+    /// Appends the events of `changes`, lowest pin first.
+    pub fn publish_changes(&mut self, changes: &InputChanges) {
+        for event in changes.iter() {
+            self.publish(event);
+        }
+    }
+
+    /// Returns the number of events `id` has not read yet; at most `N`, and `0` if it is not
+    /// subscribed.
+    pub fn pending(&self, id: SubscriberId) -> usize {
+        self.cursors
+            .get(id.0)
+            .copied()
+            .flatten()
+            .map_or(0, |cursor| self.backlog(cursor))
+    }
+
+    /// Returns the oldest event `id` has not read yet and advances its cursor, or `None` if
+    /// it has read every event or is not subscribed.
+    pub fn next(&mut self, id: SubscriberId) -> Option<InputEvent> {
+        let cursor = self.cursors.get(id.0).copied().flatten()?;
+        let backlog = self.backlog(cursor);
+        if backlog == 0 {
+            return None;
+        }
+        let slot = (self.next_slot + N - backlog) % N;
+        let event = self.events.get(slot).copied().flatten();
+        if let Some(cursor) = self.cursors.get_mut(id.0) {
+            *cursor = Some(self.published.wrapping_sub(backlog as u32 - 1));
+        }
+        event
+    }
+
+    /// Number of events after `cursor` still in the buffer.
+    fn backlog(&self, cursor: u32) -> usize {
+        (self.published.wrapping_sub(cursor) as usize).min(N)
+    }
+}
+
+impl<const N: usize, const S: usize> Default for EventFanout<N, S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,182 @@
+//! Periodic device health checks.
+
+#[cfg(not(feature = "async"))]
+use embedded_hal::delay::DelayNs;
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs;
+
+#[cfg(feature = "fail-safe")]
+use crate::FailSafeTriggers;
+use crate::errors::Error;
+use crate::registers::Register;
+use crate::{BusAccess, Port, RegisterChange, RegisterKind, Tca6424};
+
+/// Register families compared against the cache by [`Tca6424::health_check`].
+const CHECKED_FAMILIES: [RegisterKind; 4] = [
+    RegisterKind::Configuration,
+    RegisterKind::Output,
+    RegisterKind::PolarityInversion,
+    RegisterKind::InterruptMask,
+];
+
+/// Result of a [`Tca6424::health_check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HealthStatus {
+    /// The device responded and every cached register matches.
+    Healthy,
+    /// The device did not respond (NACK or other bus error).
+    Unresponsive,
+    /// The Configuration registers are back at their power-on defaults while the cache
+    /// holds a different configuration: the device has been reset.
+    ResetDetected,
+    /// A register differs from the cached value. `before` holds the cached (expected)
+    /// value and `after` the value read from the device. Only the first mismatch is reported.
+    ConfigMismatch(RegisterChange),
+}
+
+impl HealthStatus {
+    /// Returns `true` for [`HealthStatus::Healthy`].
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, HealthStatus::Healthy)
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca6424",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> Tca6424<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Pings the device and verifies its configuration against the [`RegisterCache`](crate::RegisterCache).
+    ///
+    /// The Configuration, Output, Polarity Inversion and Interrupt Mask families are read with
+    /// one auto-increment transaction each (the first read doubles as the ping) and compared
+    /// with every cached value. A detected reset or mismatch engages the fail-safe latch if
+    /// the armed [`FailSafePolicy`](crate::FailSafePolicy) triggers on
+    /// [`FailSafeTriggers::VERIFY_FAILURE`]. Otherwise this method does not modify the
+    /// device; use [`Tca6424::check_and_recover`] or [`Tca6424::restore_from_cache`] to act
+    /// on the result.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the [`HealthStatus`]. Bus errors are reported as [`HealthStatus::Unresponsive`].
+    pub async fn health_check(&mut self) -> HealthStatus {
+        let mut first_mismatch = None;
+        for kind in CHECKED_FAMILIES {
+            let mut values = [0u8; 3];
+            if self
+                .read_registers_ai(Register::of(kind, Port::Port0), &mut values)
+                .await
+                .is_err()
+            {
+                return HealthStatus::Unresponsive;
+            }
+
+            if kind == RegisterKind::Configuration && values == [0xFF; 3] {
+                let cache_differs = Port::ALL.iter().any(
+                    |&port| matches!(self.cache.get(kind, port), Some(value) if value != 0xFF),
+                );
+                if cache_differs {
+                    #[cfg(feature = "fail-safe")]
+                    {
+                        self.trip_fail_safe(FailSafeTriggers::VERIFY_FAILURE).await;
+                    }
+                    return HealthStatus::ResetDetected;
+                }
+            }
+
+            if first_mismatch.is_none() {
+                first_mismatch = Port::ALL.into_iter().find_map(|port| {
+                    let expected = self.cache.get(kind, port)?;
+                    let actual = *port.entry(&values);
+                    (expected != actual).then_some(RegisterChange {
+                        kind,
+                        port,
+                        before: expected,
+                        after: actual,
+                    })
+                });
+            }
+        }
+
+        match first_mismatch {
+            Some(change) => {
+                #[cfg(feature = "fail-safe")]
+                {
+                    self.trip_fail_safe(FailSafeTriggers::VERIFY_FAILURE).await;
+                }
+                HealthStatus::ConfigMismatch(change)
+            }
+            None => HealthStatus::Healthy,
+        }
+    }
+
+    /// Compares the level of every output pin with its commanded level, to find shorted or
+    /// overloaded outputs.
+    ///
+    /// Reads the Configuration, Output Port, Polarity Inversion and Input Port families with
+    /// one auto-increment transaction each. The Input register reflects the actual pin level
+    /// even for outputs; after undoing the polarity inversion it is compared with the Output
+    /// register for every pin configured as an output. A pin shorted to the opposite rail, or
+    /// loaded beyond the drive strength of the port, reads back differently. Call it
+    /// periodically and not right after switching outputs with slow (capacitive) loads.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the output pins whose level does not match the commanded one (bit `n` = pin
+    /// index `n`, `0` when every output is fine), or an `Error` if an I2C bus operation fails.
+    pub async fn check_output_readback(&mut self) -> Result<u32, Error<I2C::Error>> {
+        let mut words = [0u32; 4];
+        for (word, kind) in words.iter_mut().zip([
+            RegisterKind::Configuration,
+            RegisterKind::Output,
+            RegisterKind::PolarityInversion,
+            RegisterKind::Input,
+        ]) {
+            let mut values = [0u8; 4];
+            self.read_registers_ai(Register::of(kind, Port::Port0), &mut values[..3])
+                .await?;
+            *word = u32::from_le_bytes(values);
+        }
+        let [configuration, output, inversion, input] = words;
+        let outputs = !configuration & 0x00FF_FFFF;
+        Ok((input ^ inversion ^ output) & outputs)
+    }
+
+    /// Runs [`Tca6424::health_check`] forever, once every `period_ms` milliseconds, waiting
+    /// with the driver's delay source (see [`Tca6424::with_delay`]).
+    ///
+    /// Every status is passed to `report`. When `auto_recover` is `true`, a detected reset is
+    /// handled immediately with [`Tca6424::restore_from_cache`] (the status is still reported).
+    /// Intended to be spawned as a supervision task, or run on a dedicated thread in sync mode.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    pub async fn run_health_checks<F>(
+        &mut self,
+        period_ms: u32,
+        auto_recover: bool,
+        mut report: F,
+    ) -> !
+    where
+        F: FnMut(HealthStatus),
+    {
+        loop {
+            let status = self.health_check().await;
+            if auto_recover && status == HealthStatus::ResetDetected {
+                // A failure here shows up as `Unresponsive` on the next check.
+                let _ = self.restore_from_cache().await;
+            }
+            report(status);
+            self.delay.delay_ms(period_ms).await;
+        }
+    }
+}
@@ -0,0 +1,753 @@
+//! Declarative board descriptions: see the [`board!`](crate::board) macro.
+
+use core::fmt;
+
+use crate::errors::Error;
+use crate::registers::Register;
+use crate::{
+    Activity, BusAccess, DelayNs, Pin, PinState, PlannedWrite, Port, RegisterKind, Tca6424,
+};
+
+/// Pin directions and power-up output levels of a whole board.
+///
+/// Usually generated by [`board!`](crate::board); built with `const` methods, so a pin used
+/// twice is a compile-time error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BoardConfig {
+    pins: u32,
+    outputs: u32,
+    levels: u32,
+    events: u32,
+}
+
+impl BoardConfig {
+    /// Creates an empty configuration.
+    pub const fn new() -> Self {
+        Self {
+            pins: 0,
+            outputs: 0,
+            levels: 0,
+            events: 0,
+        }
+    }
+
+    const fn add(mut self, pin: Pin) -> Self {
+        let bit = 1 << pin as u8;
+        assert!(self.pins & bit == 0, "pin used by more than one net");
+        self.pins |= bit;
+        self
+    }
+
+    /// Adds an input net on `pin`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pin` is already used by another net.
+    pub const fn input(self, pin: Pin, _active_low: bool) -> Self {
+        self.add(pin)
+    }
+
+    /// Adds an input net on `pin` whose changes raise the INT line; see
+    /// [`BoardConfig::interrupt_mask`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pin` is already used by another net.
+    pub const fn event_input(self, pin: Pin, _active_low: bool) -> Self {
+        let mut config = self.add(pin);
+        config.events |= 1 << pin as u8;
+        config
+    }
+
+    /// Adds an output net on `pin`, initially active if `active` is `true`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pin` is already used by another net.
+    pub const fn output(self, pin: Pin, active_low: bool, active: bool) -> Self {
+        let mut config = self.add(pin);
+        let bit = 1 << pin as u8;
+        config.outputs |= bit;
+        if active != active_low {
+            config.levels |= bit;
+        }
+        config
+    }
+
+    /// Returns the mask of all pins used by the board (bit `n` = pin index `n`).
+    pub const fn pins(&self) -> u32 {
+        self.pins
+    }
+
+    /// Returns the mask of output pins.
+    pub const fn outputs(&self) -> u32 {
+        self.outputs
+    }
+
+    /// Returns the initial Output Port levels (bit `n` = pin index `n`).
+    pub const fn levels(&self) -> u32 {
+        self.levels
+    }
+
+    /// Returns the mask of event inputs, added with [`BoardConfig::event_input`].
+    pub const fn events(&self) -> u32 {
+        self.events
+    }
+
+    /// Returns the Interrupt Mask register values for the board as a 24-bit mask (`1` =
+    /// masked): every pin is masked except the event inputs, so plain inputs, outputs and
+    /// unused pins never raise the INT line.
+    pub const fn interrupt_mask(&self) -> u32 {
+        !self.events & 0x00FF_FFFF
+    }
+
+    /// Returns the writes [`Tca6424::apply_board_config`] issues for this configuration, in
+    /// order: the Output Port registers, then the Configuration registers, each as one
+    /// auto-increment write.
+    pub fn plan(&self) -> impl Iterator<Item = PlannedWrite> + use<> {
+        let levels = self.levels.to_le_bytes();
+        let directions = (!self.outputs).to_le_bytes();
+        [
+            PlannedWrite::new(Register::OutputPort0, &levels[..3]),
+            PlannedWrite::new(Register::ConfigurationPort0, &directions[..3]),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+impl Default for BoardConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One register written by [`Tca6424::apply_board_config_reported`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RegisterWrite {
+    /// Register family.
+    pub kind: RegisterKind,
+    /// Port of the register.
+    pub port: Port,
+    /// Value before the write: read from the device, or taken from the register cache;
+    /// `None` if neither was available.
+    pub before: Option<u8>,
+    /// Value written.
+    pub after: u8,
+}
+
+impl RegisterWrite {
+    /// Returns `Some(true)` if the write changed the register, or `None` if the previous
+    /// value is unknown.
+    pub fn changed(&self) -> Option<bool> {
+        self.before.map(|before| before != self.after)
+    }
+}
+
+impl fmt::Display for RegisterWrite {
+    /// Prints `Output Port 0: 11111111 -> 00000001`, with `????????` for an unknown value.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} Port {}: ", self.kind.name(), self.port as u8)?;
+        match self.before {
+            Some(before) => write!(f, "{before:08b}")?,
+            None => f.write_str("????????")?,
+        }
+        write!(f, " -> {:08b}", self.after)
+    }
+}
+
+/// What [`Tca6424::apply_board_config_reported`] wrote, for bring-up logs.
+///
+/// The `Display` implementation prints a summary line followed by one line per register, in
+/// write order.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct InitReport {
+    writes: heapless::Vec<RegisterWrite, 6>,
+    transactions: u32,
+}
+
+impl InitReport {
+    /// Returns the registers written, in write order: the Output Port registers, then the
+    /// Configuration registers.
+    pub fn writes(&self) -> &[RegisterWrite] {
+        &self.writes
+    }
+
+    /// Returns the write of a specific register, if it was written.
+    pub fn get(&self, kind: RegisterKind, port: Port) -> Option<&RegisterWrite> {
+        self.writes
+            .iter()
+            .find(|write| write.kind == kind && write.port == port)
+    }
+
+    /// Returns the number of I2C transactions issued, reads included.
+    pub fn transactions(&self) -> u32 {
+        self.transactions
+    }
+
+    /// Records the ports of a register family with a written value in `after`.
+    fn record(&mut self, kind: RegisterKind, before: [Option<u8>; 3], after: [Option<u8>; 3]) {
+        for port in Port::ALL {
+            if let Some(after) = *port.entry(&after) {
+                // At most six registers are written, matching the capacity.
+                let _ = self.writes.push(RegisterWrite {
+                    kind,
+                    port,
+                    before: *port.entry(&before),
+                    after,
+                });
+            }
+        }
+    }
+}
+
+impl fmt::Display for InitReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} registers written in {} transactions",
+            self.writes.len(),
+            self.transactions
+        )?;
+        for write in &self.writes {
+            write!(f, "\n  {write}")?;
+        }
+        Ok(())
+    }
+}
+
+/// An input net of a board, such as a button or a sense line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InputLine {
+    pin: Pin,
+    active_low: bool,
+}
+
+/// An output net of a board, such as an LED or a relay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct OutputLine {
+    pin: Pin,
+    active_low: bool,
+}
+
+impl InputLine {
+    /// Creates a net on `pin`; `active_low` nets read active when the pin is low.
+    pub const fn new(pin: Pin, active_low: bool) -> Self {
+        Self { pin, active_low }
+    }
+
+    /// Returns the pin of the net.
+    pub const fn pin(&self) -> Pin {
+        self.pin
+    }
+
+    /// Returns `true` if the net is active when the pin is low.
+    pub const fn is_active_low(&self) -> bool {
+        self.active_low
+    }
+}
+
+impl OutputLine {
+    /// Creates a net on `pin`; `active_low` nets are activated by driving the pin low.
+    pub const fn new(pin: Pin, active_low: bool) -> Self {
+        Self { pin, active_low }
+    }
+
+    /// Returns the pin of the net.
+    pub const fn pin(&self) -> Pin {
+        self.pin
+    }
+
+    /// Returns `true` if the net is activated by driving the pin low.
+    pub const fn is_active_low(&self) -> bool {
+        self.active_low
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "InputLine",),
+    async(feature = "async", keep_self)
+)]
+impl InputLine {
+    /// Returns `true` if the net is active.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    pub async fn is_active<I2C, D>(
+        &self,
+        tca: &mut Tca6424<I2C, D>,
+    ) -> Result<bool, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let level = tca.get_pin_input_state(self.pin).await?;
+        Ok((level == PinState::High) != self.active_low)
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "OutputLine",),
+    async(feature = "async", keep_self)
+)]
+impl OutputLine {
+    /// Activates or deactivates the net.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    pub async fn set_active<I2C, D>(
+        &self,
+        tca: &mut Tca6424<I2C, D>,
+        active: bool,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        tca.set_pin_output(self.pin, active != self.active_low)
+            .await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca6424",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> Tca6424<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Claims the pins of `config` and applies it with two auto-increment writes: the Output
+    /// Port registers first, then the Configuration registers.
+    ///
+    /// The order is guaranteed: every output latch holds its initial level before any pin
+    /// becomes an output, so pins never drive the power-on-high latch, even briefly. If the
+    /// Output write fails, the Configuration registers are not written and every pin stays
+    /// as it was.
+    ///
+    /// Pins not used by the board are configured as inputs with a low output latch. If a pin
+    /// is already claimed nothing is written; if a write fails the claims are released again.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    pub async fn apply_board_config(
+        &mut self,
+        config: &BoardConfig,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.apply_board_config_reported(config, false)
+            .await
+            .map(|_| ())
+    }
+
+    /// Writes the Interrupt Mask registers derived from the pin roles of `config` (see
+    /// [`BoardConfig::interrupt_mask`]) with one auto-increment write, so only the event
+    /// inputs raise the INT line. Claims nothing; call it after
+    /// [`Tca6424::apply_board_config`].
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if the I2C bus operation fails.
+    #[cfg(feature = "interrupt-mask")]
+    pub async fn apply_board_interrupt_mask(
+        &mut self,
+        config: &BoardConfig,
+    ) -> Result<(), Error<I2C::Error>> {
+        let masks = config.interrupt_mask().to_le_bytes();
+        self.write_registers_ai(Register::InterruptMaskPort0, &masks[..3])
+            .await
+    }
+
+    /// Applies `config` like [`Tca6424::apply_board_config`] and reports what was written.
+    ///
+    /// With `read_before`, the Output Port and Configuration registers are read first (two
+    /// more auto-increment transactions), so the report shows every register's previous
+    /// value. Otherwise the previous values come from the register cache, and are unknown
+    /// for registers the driver has not accessed yet.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the [`InitReport`], `Error::PinAlreadyClaimed` if a pin of the board is already
+    /// owned, or an `Error` if an I2C bus operation fails.
+    pub async fn apply_board_config_reported(
+        &mut self,
+        config: &BoardConfig,
+        read_before: bool,
+    ) -> Result<InitReport, Error<I2C::Error>> {
+        let scope = self.enter_activity(Activity::ApplyConfig);
+        let result = self.write_board_config(config, read_before).await;
+        self.leave_activity(scope, result)
+    }
+
+    async fn write_board_config(
+        &mut self,
+        config: &BoardConfig,
+        read_before: bool,
+    ) -> Result<InitReport, Error<I2C::Error>> {
+        self.claim_bits(config.pins).map_err(Error::from_config)?;
+        let mut report = InitReport::default();
+        let mut before = [[None; 3]; 2];
+        for (values, kind) in before
+            .iter_mut()
+            .zip([RegisterKind::Output, RegisterKind::Configuration])
+        {
+            *values = Port::ALL.map(|port| self.cache.get(kind, port));
+            if read_before {
+                let mut read = [0u8; 3];
+                let result = self
+                    .read_registers_ai(Register::of(kind, Port::Port0), &mut read)
+                    .await;
+                report.transactions += 1;
+                if let Err(error) = result {
+                    self.release_mask(config.pins);
+                    return Err(error);
+                }
+                *values = read.map(Some);
+            }
+        }
+
+        let levels = config.levels.to_le_bytes();
+        let directions = (!config.outputs).to_le_bytes();
+        let mut result = self
+            .write_registers_ai(Register::OutputPort0, &levels[..3])
+            .await;
+        report.transactions += 1;
+        if result.is_ok() {
+            report.record(RegisterKind::Output, before[0], bytes(config.levels));
+            result = self
+                .write_registers_ai(Register::ConfigurationPort0, &directions[..3])
+                .await;
+            report.transactions += 1;
+        }
+        if let Err(error) = result {
+            self.release_mask(config.pins);
+            return Err(error);
+        }
+        report.record(
+            RegisterKind::Configuration,
+            before[1],
+            bytes(!config.outputs),
+        );
+        Ok(report)
+    }
+
+    /// Claims the pins of `config` and brings the device to it with as few writes as
+    /// possible, for warm restarts where the expander kept most of its configuration.
+    ///
+    /// The Output Port and Configuration registers are read first (two auto-increment
+    /// transactions); only ports whose value differs from `config` are then written, Output
+    /// registers before Configuration registers as in [`Tca6424::apply_board_config`].
+    /// Adjacent changed ports share one auto-increment write. Pins not used by the board are
+    /// expected to be inputs with a low output latch, as `apply_board_config` leaves them.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns an [`InitReport`] listing only the registers written (empty if the device
+    /// already matched), `Error::PinAlreadyClaimed` if a pin of the board is already owned, or
+    /// an `Error` if an I2C bus operation fails, in which case the claims are released again.
+    pub async fn reconcile(
+        &mut self,
+        config: &BoardConfig,
+    ) -> Result<InitReport, Error<I2C::Error>> {
+        let scope = self.enter_activity(Activity::ApplyConfig);
+        let result = match self.claim_bits(config.pins) {
+            Ok(()) => {
+                let result = self.reconcile_claimed(config).await;
+                if result.is_err() {
+                    self.release_mask(config.pins);
+                }
+                result
+            }
+            Err(error) => Err(Error::from_config(error)),
+        };
+        self.leave_activity(scope, result)
+    }
+
+    async fn reconcile_claimed(
+        &mut self,
+        config: &BoardConfig,
+    ) -> Result<InitReport, Error<I2C::Error>> {
+        let mut report = InitReport::default();
+        let families = [
+            (RegisterKind::Output, bytes(config.levels)),
+            (RegisterKind::Configuration, bytes(!config.outputs)),
+        ];
+        let mut current = [[None; 3]; 2];
+        for (values, (kind, _)) in current.iter_mut().zip(families) {
+            let mut read = [0u8; 3];
+            self.read_registers_ai(Register::of(kind, Port::Port0), &mut read)
+                .await?;
+            report.transactions += 1;
+            *values = read.map(Some);
+        }
+        for (current, (kind, target)) in current.into_iter().zip(families) {
+            report.transactions += self.write_changed(kind, current, target).await?;
+            let written = Port::ALL.map(|port| {
+                port.entry(&target)
+                    .filter(|&value| *port.entry(&current) != Some(value))
+            });
+            report.record(kind, current, written);
+        }
+        Ok(report)
+    }
+}
+
+/// Returns the three port bytes of a 24-bit pin mask.
+fn bytes(mask: u32) -> [Option<u8>; 3] {
+    core::array::from_fn(|port| Some((mask >> (port * 8)) as u8))
+}
+
+#[doc(hidden)]
+pub mod __private {
+    pub use embedded_hal::i2c::ErrorType;
+    #[cfg(not(feature = "async"))]
+    pub use embedded_hal::{delay::DelayNs, i2c::I2c};
+    #[cfg(feature = "async")]
+    pub use embedded_hal_async::{delay::DelayNs, i2c::I2c};
+
+    /// Releases the pins of a board claimed by `apply_board_config`, for the `release` method
+    /// generated by [`board!`](crate::board).
+    pub fn release_board<I2C: ErrorType, D>(
+        tca: &mut crate::Tca6424<I2C, D>,
+        config: &super::BoardConfig,
+    ) {
+        tca.release_mask(config.pins);
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __board_line {
+    (input) => {
+        $crate::InputLine
+    };
+    (event_input) => {
+        $crate::InputLine
+    };
+    (output) => {
+        $crate::OutputLine
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __board_active_low {
+    (active_low) => {
+        true
+    };
+    (active_high) => {
+        false
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __board_active {
+    (on) => {
+        true
+    };
+    (off) => {
+        false
+    };
+}
+
+#[doc(hidden)]
+#[cfg(not(feature = "async"))]
+#[macro_export]
+macro_rules! __board_init {
+    () => {
+        /// Claims the board pins and applies [`Self::CONFIG`] to the device.
+        pub fn init<I2C, D>(
+            tca: &mut $crate::Tca6424<I2C, D>,
+        ) -> Result<Self, $crate::errors::Error<I2C::Error>>
+        where
+            I2C: $crate::__private::I2c,
+            I2C::Error: core::fmt::Debug,
+            D: $crate::__private::DelayNs,
+        {
+            tca.apply_board_config(&Self::CONFIG)?;
+            Ok(Self::new())
+        }
+
+        /// Like `init`, reading the registers first and reporting what was written.
+        pub fn init_with_report<I2C, D>(
+            tca: &mut $crate::Tca6424<I2C, D>,
+        ) -> Result<(Self, $crate::InitReport), $crate::errors::Error<I2C::Error>>
+        where
+            I2C: $crate::__private::I2c,
+            I2C::Error: core::fmt::Debug,
+            D: $crate::__private::DelayNs,
+        {
+            let report = tca.apply_board_config_reported(&Self::CONFIG, true)?;
+            Ok((Self::new(), report))
+        }
+
+        /// Like `init`, writing only the registers that differ from [`Self::CONFIG`].
+        pub fn reconcile<I2C, D>(
+            tca: &mut $crate::Tca6424<I2C, D>,
+        ) -> Result<(Self, $crate::InitReport), $crate::errors::Error<I2C::Error>>
+        where
+            I2C: $crate::__private::I2c,
+            I2C::Error: core::fmt::Debug,
+            D: $crate::__private::DelayNs,
+        {
+            let report = tca.reconcile(&Self::CONFIG)?;
+            Ok((Self::new(), report))
+        }
+    };
+}
+
+#[doc(hidden)]
+#[cfg(feature = "async")]
+#[macro_export]
+macro_rules! __board_init {
+    () => {
+        /// Claims the board pins and applies [`Self::CONFIG`] to the device.
+        pub async fn init<I2C, D>(
+            tca: &mut $crate::Tca6424<I2C, D>,
+        ) -> Result<Self, $crate::errors::Error<I2C::Error>>
+        where
+            I2C: $crate::__private::I2c,
+            I2C::Error: core::fmt::Debug,
+            D: $crate::__private::DelayNs,
+        {
+            tca.apply_board_config(&Self::CONFIG).await?;
+            Ok(Self::new())
+        }
+
+        /// Like `init`, reading the registers first and reporting what was written.
+        pub async fn init_with_report<I2C, D>(
+            tca: &mut $crate::Tca6424<I2C, D>,
+        ) -> Result<(Self, $crate::InitReport), $crate::errors::Error<I2C::Error>>
+        where
+            I2C: $crate::__private::I2c,
+            I2C::Error: core::fmt::Debug,
+            D: $crate::__private::DelayNs,
+        {
+            let report = tca.apply_board_config_reported(&Self::CONFIG, true).await?;
+            Ok((Self::new(), report))
+        }
+
+        /// Like `init`, writing only the registers that differ from [`Self::CONFIG`].
+        pub async fn reconcile<I2C, D>(
+            tca: &mut $crate::Tca6424<I2C, D>,
+        ) -> Result<(Self, $crate::InitReport), $crate::errors::Error<I2C::Error>>
+        where
+            I2C: $crate::__private::I2c,
+            I2C::Error: core::fmt::Debug,
+            D: $crate::__private::DelayNs,
+        {
+            let report = tca.reconcile(&Self::CONFIG).await?;
+            Ok((Self::new(), report))
+        }
+    };
+}
+
+/// Describes the nets of a board and generates a typed struct for them.
+///
+/// Every net names a pin, its kind (`input`, `event_input` or `output`) and active level
+/// (`active_high` or `active_low`); outputs also give their initial state (`on` or `off`).
+/// Event inputs are the inputs whose changes should raise the INT line: `CONFIG`'s
+/// [`interrupt_mask`](crate::BoardConfig::interrupt_mask) masks every other pin, and
+/// [`Tca6424::apply_board_interrupt_mask`](crate::Tca6424::apply_board_interrupt_mask)
+/// writes it. The macro generates:
+///
+/// - a struct with one [`InputLine`](crate::InputLine) or [`OutputLine`](crate::OutputLine)
+///   field per net,
+/// - `CONFIG`, the whole board as a [`BoardConfig`](crate::BoardConfig) (a pin used twice
+///   fails to compile),
+/// - `init(&mut tca)`, which claims the pins and applies the configuration with
+///   [`Tca6424::apply_board_config`](crate::Tca6424::apply_board_config) (`async` with the
+///   `async` feature),
+/// - `init_with_report(&mut tca)`, which also returns an [`InitReport`](crate::InitReport)
+///   with the previous and written value of every register,
+/// - `reconcile(&mut tca)`, which claims the pins and writes only the registers that differ
+///   from `CONFIG`, for warm restarts ([`Tca6424::reconcile`](crate::Tca6424::reconcile)),
+/// - `release(self, &mut tca)`, which returns the pins to the driver.
+///
+/// ```ignore
+/// tca6424::board! {
+///     pub struct FrontPanel {
+///         pub status_led: output(P00, active_high, off),
+///         pub relay_main: output(P12, active_low, off),
+///         pub start_button: input(P20, active_low),
+///         pub door_switch: event_input(P21, active_low),
+///     }
+/// }
+///
+/// let panel = FrontPanel::init(&mut tca)?;
+/// tca.apply_board_interrupt_mask(&FrontPanel::CONFIG)?;
+/// if panel.start_button.is_active(&mut tca)? {
+///     panel.relay_main.set_active(&mut tca, true)?;
+/// }
+/// ```
+#[macro_export]
+macro_rules! board {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$field_meta:meta])*
+                $field_vis:vis $field:ident : $kind:ident ( $pin:ident, $active_low:ident $(, $initial:ident)? )
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $(
+                $(#[$field_meta])*
+                $field_vis $field: $crate::__board_line!($kind),
+            )*
+        }
+
+        impl $name {
+            /// Directions and initial levels of every net of the board.
+            pub const CONFIG: $crate::BoardConfig = $crate::BoardConfig::new()
+                $(
+                    .$kind(
+                        $crate::Pin::$pin,
+                        $crate::__board_active_low!($active_low)
+                        $(, $crate::__board_active!($initial))?
+                    )
+                )*;
+
+            /// Creates the nets without touching the device; use `init` to configure it.
+            pub const fn new() -> Self {
+                Self {
+                    $(
+                        $field: <$crate::__board_line!($kind)>::new(
+                            $crate::Pin::$pin,
+                            $crate::__board_active_low!($active_low),
+                        ),
+                    )*
+                }
+            }
+
+            $crate::__board_init!();
+
+            /// Returns the board pins to the driver's pool of unclaimed pins.
+            pub fn release<I2C: $crate::__private::ErrorType, D>(
+                self,
+                tca: &mut $crate::Tca6424<I2C, D>,
+            ) {
+                $crate::__private::release_board(tca, &Self::CONFIG);
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+    };
+}
@@ -0,0 +1,99 @@
+//! Variants of the timed helpers reading the time from a [`TimeSource`].
+//!
+//! Each `*_from` method is the timestamped method it names with `clock.now_ms()` as the
+//! timestamp, so code written against [`TimeSource`] runs unchanged on the target clock and
+//! on a [`ManualClock`](crate::ManualClock) stepped by a host test. Debouncers get
+//! [`Debounce::update_from`](crate::Debounce::update_from) from the trait.
+
+use crate::errors::Error;
+use crate::{BusAccess, DelayNs, InputWatchdog, SoftPwm, Tca6424, TimeSource, WaveformPlayer};
+
+impl WaveformPlayer {
+    /// [`WaveformPlayer::process`] at the time of `clock`.
+    pub fn process_from<T: TimeSource + ?Sized>(&mut self, clock: &T) -> Option<(u32, u32)> {
+        self.process(clock.now_ms())
+    }
+}
+
+impl SoftPwm {
+    /// [`SoftPwm::process`] at the time of `clock`.
+    pub fn process_from<T: TimeSource + ?Sized>(&mut self, clock: &T) -> Option<u32> {
+        self.process(clock.now_ms())
+    }
+}
+
+impl InputWatchdog {
+    /// [`InputWatchdog::process`] at the time of `clock`.
+    pub fn process_from<T: TimeSource + ?Sized>(&mut self, inputs: u32, clock: &T) {
+        self.process(inputs, clock.now_ms())
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "WaveformPlayer",),
+    async(feature = "async", keep_self)
+)]
+impl WaveformPlayer {
+    /// [`WaveformPlayer::tick`] at the time of `clock`.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    pub async fn tick_from<I2C, D, T>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        clock: &T,
+    ) -> Result<bool, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+        T: TimeSource + ?Sized,
+    {
+        self.tick(tca, clock.now_ms()).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "SoftPwm",),
+    async(feature = "async", keep_self)
+)]
+impl SoftPwm {
+    /// [`SoftPwm::tick`] at the time of `clock`.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    pub async fn tick_from<I2C, D, T>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        clock: &T,
+    ) -> Result<bool, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+        T: TimeSource + ?Sized,
+    {
+        self.tick(tca, clock.now_ms()).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "InputWatchdog",),
+    async(feature = "async", keep_self)
+)]
+impl InputWatchdog {
+    /// [`InputWatchdog::poll`] at the time of `clock`.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    pub async fn poll_from<I2C, D, T>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        clock: &T,
+    ) -> Result<u32, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+        T: TimeSource + ?Sized,
+    {
+        self.poll(tca, clock.now_ms()).await
+    }
+}
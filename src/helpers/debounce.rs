@@ -0,0 +1,229 @@
+//! Debouncing of a single digital signal, with interchangeable algorithms.
+
+use crate::TimeSource;
+
+/// A debounce algorithm turning raw samples of a digital signal into a stable level.
+///
+/// [`LitButton`](crate::LitButton) and [`RotaryEncoder`](crate::RotaryEncoder) take any
+/// implementation (see their `with_debouncer` methods), so noisy inputs can use a different
+/// algorithm, or a custom one, without changing the event code. The crate provides
+/// [`Debouncer`] (settle time), [`IntegratorDebouncer`], [`StableSamplesDebouncer`] and
+/// [`LockoutDebouncer`].
+pub trait Debounce {
+    /// Processes one raw sample taken at `now_ms`, a free-running millisecond timestamp that
+    /// may wrap around.
+    ///
+    /// Returns the new stable level when it changes, `None` otherwise.
+    fn update(&mut self, raw: bool, now_ms: u32) -> Option<bool>;
+
+    /// Processes one raw sample taken now according to `clock`; see [`Debounce::update`].
+    fn update_from<T: TimeSource + ?Sized>(&mut self, raw: bool, clock: &T) -> Option<bool>
+    where
+        Self: Sized,
+    {
+        self.update(raw, clock.now_ms())
+    }
+
+    /// Returns the current stable level.
+    fn is_high(&self) -> bool;
+}
+
+/// Debounces a digital signal by requiring it to stay unchanged for a settle time.
+///
+/// Feed raw samples with [`Debouncer::update`]; a new level is accepted once every sample
+/// taken during `settle_ms` milliseconds agreed with it. Timestamps are a free-running
+/// millisecond counter and may wrap around.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Debouncer {
+    stable: bool,
+    last_raw: bool,
+    changed_at_ms: u32,
+    settle_ms: u32,
+}
+
+impl Debouncer {
+    /// Creates a debouncer whose stable level starts at `initial`.
+    pub const fn new(initial: bool, settle_ms: u32) -> Self {
+        Self {
+            stable: initial,
+            last_raw: initial,
+            changed_at_ms: 0,
+            settle_ms,
+        }
+    }
+
+    /// Processes one raw sample taken at `now_ms`.
+    ///
+    /// Returns the new stable level when it changes, `None` otherwise.
+    pub fn update(&mut self, raw: bool, now_ms: u32) -> Option<bool> {
+        if raw != self.last_raw {
+            self.last_raw = raw;
+            self.changed_at_ms = now_ms;
+        }
+        if self.last_raw != self.stable && now_ms.wrapping_sub(self.changed_at_ms) >= self.settle_ms
+        {
+            self.stable = self.last_raw;
+            return Some(self.stable);
+        }
+        None
+    }
+
+    /// Returns the current stable level.
+    pub const fn is_high(&self) -> bool {
+        self.stable
+    }
+}
+
+impl Debounce for Debouncer {
+    fn update(&mut self, raw: bool, now_ms: u32) -> Option<bool> {
+        Debouncer::update(self, raw, now_ms)
+    }
+
+    fn is_high(&self) -> bool {
+        Debouncer::is_high(self)
+    }
+}
+
+/// Debounces a signal with a saturating counter, the classic integrator.
+///
+/// Every high sample counts up and every low sample counts down, between 0 and `limit`. The
+/// stable level becomes high when the counter reaches `limit` and low when it reaches 0, so
+/// isolated glitches only delay a change instead of restarting it. Time is not used: the
+/// debounce time is `limit` sampling periods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct IntegratorDebouncer {
+    stable: bool,
+    count: u8,
+    limit: u8,
+}
+
+impl IntegratorDebouncer {
+    /// Creates an integrator counting up to `limit` (at least 1), whose stable level starts at
+    /// `initial`.
+    pub const fn new(initial: bool, limit: u8) -> Self {
+        let limit = if limit == 0 { 1 } else { limit };
+        Self {
+            stable: initial,
+            count: if initial { limit } else { 0 },
+            limit,
+        }
+    }
+}
+
+impl Debounce for IntegratorDebouncer {
+    fn update(&mut self, raw: bool, _now_ms: u32) -> Option<bool> {
+        if raw {
+            self.count = (self.count + 1).min(self.limit);
+        } else {
+            self.count = self.count.saturating_sub(1);
+        }
+        let level = match self.count {
+            0 => false,
+            count if count == self.limit => true,
+            _ => return None,
+        };
+        if level == self.stable {
+            return None;
+        }
+        self.stable = level;
+        Some(level)
+    }
+
+    fn is_high(&self) -> bool {
+        self.stable
+    }
+}
+
+/// Debounces a signal by requiring a number of consecutive identical samples.
+///
+/// A new level is accepted once `samples` samples in a row agreed with it; any differing
+/// sample restarts the count. Time is not used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct StableSamplesDebouncer {
+    stable: bool,
+    run: u8,
+    samples: u8,
+}
+
+impl StableSamplesDebouncer {
+    /// Creates a debouncer requiring `samples` (at least 1) agreeing samples, whose stable
+    /// level starts at `initial`.
+    pub const fn new(initial: bool, samples: u8) -> Self {
+        Self {
+            stable: initial,
+            run: 0,
+            samples: if samples == 0 { 1 } else { samples },
+        }
+    }
+}
+
+impl Debounce for StableSamplesDebouncer {
+    fn update(&mut self, raw: bool, _now_ms: u32) -> Option<bool> {
+        if raw == self.stable {
+            self.run = 0;
+            return None;
+        }
+        self.run += 1;
+        if self.run < self.samples {
+            return None;
+        }
+        self.run = 0;
+        self.stable = raw;
+        Some(raw)
+    }
+
+    fn is_high(&self) -> bool {
+        self.stable
+    }
+}
+
+/// Debounces a signal by accepting a change at once and then ignoring the input for a
+/// lockout time.
+///
+/// Reacts on the first edge, with no added latency, which suits buttons and limit switches
+/// whose contacts bounce but do not pick up noise while idle. After the lockout the current
+/// raw level is taken over, so a change that happened during the lockout is reported late
+/// rather than lost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LockoutDebouncer {
+    stable: bool,
+    changed_at_ms: Option<u32>,
+    lockout_ms: u32,
+}
+
+impl LockoutDebouncer {
+    /// Creates a debouncer ignoring the input for `lockout_ms` after each change, whose stable
+    /// level starts at `initial`.
+    pub const fn new(initial: bool, lockout_ms: u32) -> Self {
+        Self {
+            stable: initial,
+            changed_at_ms: None,
+            lockout_ms,
+        }
+    }
+}
+
+impl Debounce for LockoutDebouncer {
+    fn update(&mut self, raw: bool, now_ms: u32) -> Option<bool> {
+        if let Some(changed_at_ms) = self.changed_at_ms {
+            if now_ms.wrapping_sub(changed_at_ms) < self.lockout_ms {
+                return None;
+            }
+            self.changed_at_ms = None;
+        }
+        if raw == self.stable {
+            return None;
+        }
+        self.stable = raw;
+        self.changed_at_ms = Some(now_ms);
+        Some(raw)
+    }
+
+    fn is_high(&self) -> bool {
+        self.stable
+    }
+}
@@ -0,0 +1,262 @@
+//! Rotary encoder with an integrated push switch.
+
+use heapless::Deque;
+
+use super::{Debounce, Debouncer};
+use crate::errors::{ConfigError, Error};
+use crate::registers::Register;
+use crate::{BusAccess, DelayNs, Pin, PinClaim, PinDirection, Tca6424};
+
+/// Quadrature decoding table indexed by `previous << 2 | current`, where a state is `A << 1 | B`.
+const QUADRATURE: [i8; 16] = [0, -1, 1, 0, 1, 0, 0, -1, -1, 0, 0, 1, 0, 1, -1, 0];
+
+/// Configuration of a [`RotaryEncoder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EncoderConfig {
+    /// Quadrature transitions per detent (4 for most mechanical encoders).
+    pub steps_per_detent: u8,
+    /// Debounce settle time of the push switch.
+    pub debounce_ms: u32,
+    /// Hold time after which a press is reported as [`EncoderEvent::LongPress`].
+    pub long_press_ms: u32,
+    /// `true` if the switch pulls its input low when pressed.
+    pub switch_active_low: bool,
+}
+
+impl Default for EncoderConfig {
+    fn default() -> Self {
+        Self {
+            steps_per_detent: 4,
+            debounce_ms: 5,
+            long_press_ms: 600,
+            switch_active_low: true,
+        }
+    }
+}
+
+/// An event produced by a [`RotaryEncoder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum EncoderEvent {
+    /// The knob moved by the given number of detents (positive = clockwise).
+    Step(i8),
+    /// The switch was pressed and released before the long-press time.
+    Click,
+    /// The switch has been held for the long-press time. No `Click` follows on release.
+    LongPress,
+}
+
+/// A front-panel knob: quadrature encoder on two pins plus an optional push switch.
+///
+/// The pins are claimed from the driver on construction. [`RotaryEncoder::poll`] samples all
+/// three input ports in one auto-increment read and queues the resulting events; drain them
+/// with [`RotaryEncoder::next_event`]. Poll fast enough to see every quadrature state
+/// (typically every 1-2 ms while the knob turns).
+///
+/// Clockwise means A leads B; swap the pins to reverse the direction.
+///
+/// The switch is debounced with a [`Debouncer`] using `debounce_ms` of the configuration;
+/// [`RotaryEncoder::with_debouncer`] swaps in any other [`Debounce`] algorithm.
+#[derive(Debug)]
+pub struct RotaryEncoder<B = Debouncer> {
+    a: PinClaim,
+    b: PinClaim,
+    switch: Option<PinClaim>,
+    config: EncoderConfig,
+    state: u8,
+    accumulated: i8,
+    debouncer: B,
+    pressed_at_ms: u32,
+    long_press_sent: bool,
+    events: Deque<EncoderEvent, 8>,
+}
+
+impl RotaryEncoder {
+    /// Creates an encoder on pins `a` and `b` with an optional push `switch`, claiming them
+    /// from `tca`.
+    ///
+    /// No bus transfer is made; call [`RotaryEncoder::init`] to configure the pins.
+    ///
+    /// # Returns
+    ///
+    /// Returns the helper, or `ConfigError::PinAlreadyClaimed` if any pin is already owned (in which
+    /// case nothing is claimed).
+    pub fn new<I2C: embedded_hal::i2c::ErrorType, D>(
+        tca: &mut Tca6424<I2C, D>,
+        a: Pin,
+        b: Pin,
+        switch: Option<Pin>,
+        config: EncoderConfig,
+    ) -> Result<Self, ConfigError> {
+        let mask = (1 << a as u8) | (1 << b as u8) | switch.map_or(0, |pin| 1 << pin as u8);
+        tca.claim_bits(mask)?;
+        Ok(Self {
+            a: tca.pin_claim(a),
+            b: tca.pin_claim(b),
+            switch: switch.map(|pin| tca.pin_claim(pin)),
+            config,
+            state: 0,
+            accumulated: 0,
+            debouncer: Debouncer::new(false, config.debounce_ms),
+            pressed_at_ms: 0,
+            long_press_sent: false,
+            events: Deque::new(),
+        })
+    }
+}
+
+impl<B: Debounce> RotaryEncoder<B> {
+    /// Debounces the switch with `debouncer` instead, which should start released (low).
+    ///
+    /// `debounce_ms` of the configuration is then unused.
+    pub fn with_debouncer<B2: Debounce>(self, debouncer: B2) -> RotaryEncoder<B2> {
+        RotaryEncoder {
+            a: self.a,
+            b: self.b,
+            switch: self.switch,
+            config: self.config,
+            state: self.state,
+            accumulated: self.accumulated,
+            debouncer,
+            pressed_at_ms: self.pressed_at_ms,
+            long_press_sent: self.long_press_sent,
+            events: self.events,
+        }
+    }
+
+    /// Processes one sample of the 24 input levels (bit `n` = pin index `n`) taken at `now_ms`.
+    ///
+    /// The resulting events are queued for [`RotaryEncoder::next_event`]. If the queue is full,
+    /// new events are dropped.
+    pub fn process(&mut self, inputs: u32, now_ms: u32) {
+        let level = |claim: &PinClaim| inputs & (1 << claim.pin() as u8) != 0;
+
+        let state = (u8::from(level(&self.a)) << 1) | u8::from(level(&self.b));
+        self.accumulated += QUADRATURE
+            .get(usize::from(self.state << 2 | state))
+            .copied()
+            .unwrap_or(0);
+        self.state = state;
+        let per_detent = self.config.steps_per_detent.clamp(1, 64) as i8;
+        if self.accumulated >= per_detent {
+            self.accumulated -= per_detent;
+            let _ = self.events.push_back(EncoderEvent::Step(1));
+        } else if self.accumulated <= -per_detent {
+            self.accumulated += per_detent;
+            let _ = self.events.push_back(EncoderEvent::Step(-1));
+        }
+
+        let Some(switch) = &self.switch else {
+            return;
+        };
+        let pressed = level(switch) != self.config.switch_active_low;
+        match self.debouncer.update(pressed, now_ms) {
+            Some(true) => {
+                self.pressed_at_ms = now_ms;
+                self.long_press_sent = false;
+            }
+            Some(false) if !self.long_press_sent => {
+                let _ = self.events.push_back(EncoderEvent::Click);
+            }
+            _ => {}
+        }
+        if self.debouncer.is_high()
+            && !self.long_press_sent
+            && now_ms.wrapping_sub(self.pressed_at_ms) >= self.config.long_press_ms
+        {
+            self.long_press_sent = true;
+            let _ = self.events.push_back(EncoderEvent::LongPress);
+        }
+    }
+
+    /// Takes the oldest queued event.
+    pub fn next_event(&mut self) -> Option<EncoderEvent> {
+        self.events.pop_front()
+    }
+
+    /// Returns `true` while the debounced switch is pressed.
+    pub fn is_pressed(&self) -> bool {
+        self.debouncer.is_high()
+    }
+
+    /// Returns the encoder and switch pins to the pool of `tca`; pins claimed from
+    /// another driver stay claimed there.
+    pub fn release<I2C: embedded_hal::i2c::ErrorType, D>(self, tca: &mut Tca6424<I2C, D>) {
+        let _ = tca.release_pin(self.a);
+        let _ = tca.release_pin(self.b);
+        if let Some(switch) = self.switch {
+            let _ = tca.release_pin(switch);
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "RotaryEncoder",),
+    async(feature = "async", keep_self)
+)]
+impl<B: Debounce> RotaryEncoder<B> {
+    /// Configures the encoder and switch pins as inputs and samples the initial
+    /// quadrature state.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn init<I2C, D>(&mut self, tca: &mut Tca6424<I2C, D>) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        tca.set_pin_direction(self.a.pin(), PinDirection::Input)
+            .await?;
+        tca.set_pin_direction(self.b.pin(), PinDirection::Input)
+            .await?;
+        if let Some(switch) = &self.switch {
+            tca.set_pin_direction(switch.pin(), PinDirection::Input)
+                .await?;
+        }
+        let inputs = Self::read_inputs(tca).await?;
+        let level = |pin: Pin| inputs & (1 << pin as u8) != 0;
+        self.state = (u8::from(level(self.a.pin())) << 1) | u8::from(level(self.b.pin()));
+        self.accumulated = 0;
+        Ok(())
+    }
+
+    /// Samples the inputs with one auto-increment read, processes them and returns the oldest
+    /// queued event.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the event, if any, or an `Error` if the I2C bus operation fails.
+    pub async fn poll<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        now_ms: u32,
+    ) -> Result<Option<EncoderEvent>, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let inputs = Self::read_inputs(tca).await?;
+        self.process(inputs, now_ms);
+        Ok(self.next_event())
+    }
+
+    async fn read_inputs<I2C, D>(tca: &mut Tca6424<I2C, D>) -> Result<u32, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let mut inputs = [0u8; 4];
+        tca.read_registers_ai(Register::InputPort0, &mut inputs[..3])
+            .await?;
+        Ok(u32::from_le_bytes(inputs))
+    }
+}
@@ -0,0 +1,278 @@
+//! Bank of fans, each with an enable output and a fault input.
+
+use heapless::Deque;
+
+use crate::errors::{ConfigError, Error};
+use crate::{BusAccess, DelayNs, IoExpander, Pin, PinDirection, Tca6424};
+
+/// The pins of one fan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FanPins {
+    /// Output switching the fan on.
+    pub enable: Pin,
+    /// Input reporting a fault (stalled rotor, missing tach) from the fan controller.
+    pub fault: Pin,
+}
+
+/// Signal polarities of a [`FanBank`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FanBankConfig {
+    /// `true` if a fan is switched on by driving its enable pin low.
+    pub enable_active_low: bool,
+    /// `true` if a fault pulls the fault input low (the usual open-drain wiring).
+    pub fault_active_low: bool,
+}
+
+impl Default for FanBankConfig {
+    fn default() -> Self {
+        Self {
+            enable_active_low: false,
+            fault_active_low: true,
+        }
+    }
+}
+
+/// A change of the fault state of one fan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FanEvent {
+    /// The fan with this index reported a fault.
+    Fault(u8),
+    /// The fault of the fan with this index cleared.
+    Recovered(u8),
+}
+
+/// Summary of a [`FanBank`]: which fans are on and which report a fault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FanStatus {
+    enabled: u16,
+    faulted: u16,
+}
+
+impl FanStatus {
+    /// Returns the fans that are switched on (bit `n` = fan `n`).
+    pub const fn enabled(&self) -> u16 {
+        self.enabled
+    }
+
+    /// Returns the fans that report a fault (bit `n` = fan `n`).
+    pub const fn faulted(&self) -> u16 {
+        self.faulted
+    }
+
+    /// Returns `true` if no fan reports a fault.
+    pub const fn is_ok(&self) -> bool {
+        self.faulted == 0
+    }
+
+    /// Returns the status as one word: enabled fans in the low half, faulted fans in the high
+    /// half.
+    pub const fn bits(&self) -> u32 {
+        self.enabled as u32 | (self.faulted as u32) << 16
+    }
+}
+
+/// Up to 12 fans, each switched by an output pin and monitored through a fault input pin.
+///
+/// All pins are claimed from the driver on construction. [`FanBank::poll`] reads the fault
+/// inputs of every fan in one pass and queues a [`FanEvent`] for every change; drain them with
+/// [`FanBank::next_event`].
+#[derive(Debug)]
+pub struct FanBank<const N: usize> {
+    fans: [FanPins; N],
+    config: FanBankConfig,
+    status: FanStatus,
+    events: Deque<FanEvent, 8>,
+}
+
+impl<const N: usize> FanBank<N> {
+    /// Creates a fan bank, claiming every enable and fault pin from `tca`.
+    ///
+    /// No bus transfer is made; call [`FanBank::init`] to configure the pins.
+    ///
+    /// `N` must be at most 12; larger banks fail to compile.
+    ///
+    /// # Returns
+    ///
+    /// Returns the helper, or `ConfigError::PinAlreadyClaimed` if a pin is already owned or used
+    /// twice.
+    pub fn new<I2C: embedded_hal::i2c::ErrorType, D>(
+        tca: &mut Tca6424<I2C, D>,
+        fans: [FanPins; N],
+        config: FanBankConfig,
+    ) -> Result<Self, ConfigError> {
+        const { assert!(N <= 12, "a fan bank has at most 12 fans") };
+        let mut mask = 0u32;
+        for fan in &fans {
+            for pin in [fan.enable, fan.fault] {
+                if mask & (1 << pin as u8) != 0 {
+                    return Err(ConfigError::PinAlreadyClaimed(pin));
+                }
+                mask |= 1 << pin as u8;
+            }
+        }
+        tca.claim_bits(mask)?;
+        Ok(Self {
+            fans,
+            config,
+            status: FanStatus::default(),
+            events: Deque::new(),
+        })
+    }
+
+    /// Processes a 24-bit input image (bit `n` = pin index `n`), updating the fault state and
+    /// queueing events for changes.
+    pub fn process(&mut self, inputs: u32) {
+        let mut faulted = 0u16;
+        for (index, fan) in self.fans.iter().enumerate() {
+            let high = inputs & (1 << fan.fault as u8) != 0;
+            if high != self.config.fault_active_low {
+                faulted |= 1 << index;
+            }
+        }
+        let changed = faulted ^ self.status.faulted;
+        for index in 0..N {
+            if changed & (1 << index) != 0 {
+                let event = if faulted & (1 << index) != 0 {
+                    FanEvent::Fault(index as u8)
+                } else {
+                    FanEvent::Recovered(index as u8)
+                };
+                // When the queue is full the oldest event is dropped.
+                if self.events.is_full() {
+                    self.events.pop_front();
+                }
+                let _ = self.events.push_back(event);
+            }
+        }
+        self.status.faulted = faulted;
+    }
+
+    /// Returns the next queued event.
+    pub fn next_event(&mut self) -> Option<FanEvent> {
+        self.events.pop_front()
+    }
+
+    /// Returns the current status.
+    pub fn status(&self) -> FanStatus {
+        self.status
+    }
+
+    /// Returns the pins of every fan.
+    pub fn fans(&self) -> &[FanPins; N] {
+        &self.fans
+    }
+
+    /// Returns every pin to the pool of `tca`.
+    pub fn release<I2C: embedded_hal::i2c::ErrorType, D>(self, tca: &mut Tca6424<I2C, D>) {
+        tca.release_mask(self.enable_mask() | self.fault_mask());
+    }
+
+    fn enable_mask(&self) -> u32 {
+        self.fans
+            .iter()
+            .fold(0, |mask, fan| mask | 1 << fan.enable as u8)
+    }
+
+    fn fault_mask(&self) -> u32 {
+        self.fans
+            .iter()
+            .fold(0, |mask, fan| mask | 1 << fan.fault as u8)
+    }
+
+    /// Returns `(mask_high, mask_low)` driving the enable pins in `mask` to `on`.
+    fn enable_levels(&self, mask: u32, on: bool) -> (u32, u32) {
+        if on != self.config.enable_active_low {
+            (mask, 0)
+        } else {
+            (0, mask)
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "FanBank",),
+    async(feature = "async", keep_self)
+)]
+impl<const N: usize> FanBank<N> {
+    /// Configures the fault pins as inputs and the enable pins as outputs with every fan off.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn init<I2C, D>(&mut self, tca: &mut Tca6424<I2C, D>) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let enables = self.enable_mask();
+        let (high, low) = self.enable_levels(enables, false);
+        tca.set_direction(self.fault_mask(), PinDirection::Input)
+            .await?;
+        tca.write(high, low).await?;
+        self.status.enabled = 0;
+        tca.set_direction(enables, PinDirection::Output).await
+    }
+
+    /// Switches fan `index` on or off.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, `Error::InvalidRegisterOrPin` if there is no fan `index`,
+    /// or an `Error` if an I2C bus operation fails.
+    pub async fn set_fan<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        index: u8,
+        on: bool,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let fan = match self.fans.get(index as usize) {
+            Some(fan) => *fan,
+            None => return Err(Error::InvalidRegisterOrPin),
+        };
+        let (high, low) = self.enable_levels(1 << fan.enable as u8, on);
+        tca.write(high, low).await?;
+        if on {
+            self.status.enabled |= 1 << index;
+        } else {
+            self.status.enabled &= !(1 << index);
+        }
+        Ok(())
+    }
+
+    /// Reads the fault inputs and queues events for every change.
+    ///
+    /// Costs one register read per port that holds fault pins.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the updated status, or an `Error` if an I2C bus operation fails.
+    pub async fn poll<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+    ) -> Result<FanStatus, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let inputs = tca.read(self.fault_mask()).await?;
+        self.process(inputs);
+        Ok(self.status)
+    }
+}
@@ -0,0 +1,136 @@
+//! Click, double-click and long-press classification of a debounced button.
+
+use heapless::Deque;
+
+/// A high-level gesture recognised by a [`GestureDetector`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ButtonGesture {
+    /// A single short press, reported once the double-click window has passed.
+    Click,
+    /// Two short presses, the second starting within the double-click window.
+    DoubleClick,
+    /// The button has been held for the long-press time. Reported while it is still held;
+    /// nothing follows on release.
+    LongPress,
+}
+
+/// Timing of a [`GestureDetector`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GestureConfig {
+    /// Hold time after which a press is reported as [`ButtonGesture::LongPress`].
+    pub long_press_ms: u32,
+    /// Time after a release within which the next press makes a
+    /// [`ButtonGesture::DoubleClick`]. `0` disables double clicks, so clicks are reported on
+    /// release without delay.
+    pub double_click_ms: u32,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            long_press_ms: 600,
+            double_click_ms: 300,
+        }
+    }
+}
+
+/// Turns the debounced state of a button into clicks, double clicks and long presses.
+///
+/// Feed it the debounced pressed state with a millisecond timestamp on every poll, not only on
+/// edges: a click is only known to be single once the double-click window has passed, and a
+/// long press is reported while the button is still held. Drain the gestures with
+/// [`GestureDetector::next_gesture`].
+///
+/// A click followed by a press held for the long-press time is reported as
+/// [`ButtonGesture::Click`] then [`ButtonGesture::LongPress`].
+#[derive(Debug)]
+pub struct GestureDetector {
+    config: GestureConfig,
+    pressed: bool,
+    pressed_at_ms: u32,
+    /// Release time of a click that may still become a double click.
+    pending_click: Option<u32>,
+    /// The current press started within the double-click window.
+    second_press: bool,
+    long_press_sent: bool,
+    gestures: Deque<ButtonGesture, 4>,
+}
+
+impl GestureDetector {
+    /// Creates a detector with the given timing, starting released.
+    pub fn new(config: GestureConfig) -> Self {
+        Self {
+            config,
+            pressed: false,
+            pressed_at_ms: 0,
+            pending_click: None,
+            second_press: false,
+            long_press_sent: false,
+            gestures: Deque::new(),
+        }
+    }
+
+    /// Returns the timing of the detector.
+    pub fn config(&self) -> GestureConfig {
+        self.config
+    }
+
+    /// Processes the debounced state of the button at `now_ms`, queueing any recognised
+    /// gesture.
+    pub fn update(&mut self, pressed: bool, now_ms: u32) {
+        if pressed != self.pressed {
+            self.pressed = pressed;
+            if pressed {
+                self.pressed_at_ms = now_ms;
+                self.long_press_sent = false;
+                self.second_press = self.pending_click.take().is_some();
+            } else if !self.long_press_sent {
+                if self.second_press {
+                    self.push(ButtonGesture::DoubleClick);
+                } else if self.config.double_click_ms == 0 {
+                    self.push(ButtonGesture::Click);
+                } else {
+                    self.pending_click = Some(now_ms);
+                }
+            }
+        }
+
+        if let Some(released_at) = self.pending_click
+            && now_ms.wrapping_sub(released_at) >= self.config.double_click_ms
+        {
+            self.pending_click = None;
+            self.push(ButtonGesture::Click);
+        }
+        if self.pressed
+            && !self.long_press_sent
+            && now_ms.wrapping_sub(self.pressed_at_ms) >= self.config.long_press_ms
+        {
+            self.long_press_sent = true;
+            if self.second_press {
+                // The first press of the pair was a click after all.
+                self.push(ButtonGesture::Click);
+            }
+            self.push(ButtonGesture::LongPress);
+        }
+    }
+
+    /// Takes the oldest queued gesture.
+    pub fn next_gesture(&mut self) -> Option<ButtonGesture> {
+        self.gestures.pop_front()
+    }
+
+    /// Forgets the press in progress and every queued gesture.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.config);
+    }
+
+    fn push(&mut self, gesture: ButtonGesture) {
+        // When the queue is full the oldest gesture is dropped.
+        if self.gestures.is_full() {
+            self.gestures.pop_front();
+        }
+        let _ = self.gestures.push_back(gesture);
+    }
+}
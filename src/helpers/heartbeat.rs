@@ -0,0 +1,188 @@
+//! Heartbeat LED: one output pin blinking at a fixed period.
+
+use crate::errors::{ConfigError, Error};
+use crate::registers::Register;
+use crate::{BusAccess, DelayNs, Pin, PinClaim, PinDirection, PinState, RegisterKind, Tca6424};
+
+/// A heartbeat LED that shows the firmware is alive.
+///
+/// The LED is lit for the first half of every `period_ms` and dark for the second half.
+/// Drive it from a periodic timer with [`Heartbeat::tick`], or spawn [`Heartbeat::run`] as a
+/// task. Writes go through the driver's register cache: once the Output register of the
+/// LED's port is known, a toggle is a single register write, and ticks that do not change
+/// the level cost nothing.
+#[derive(Debug)]
+pub struct Heartbeat {
+    led: PinClaim,
+    period_ms: u32,
+    active_low: bool,
+    started_ms: Option<u32>,
+    /// LED level last written to the device; `None` until the first write.
+    written: Option<bool>,
+}
+
+impl Heartbeat {
+    /// Creates a heartbeat on `led` blinking every `period_ms`, claiming the pin from `tca`.
+    ///
+    /// No bus transfer is made; call [`Heartbeat::init`] to configure the pin. Periods below
+    /// 2 ms are raised to 2 ms.
+    ///
+    /// # Returns
+    ///
+    /// Returns the helper, or `ConfigError::PinAlreadyClaimed` if the pin is already owned.
+    pub fn new<I2C: embedded_hal::i2c::ErrorType, D>(
+        tca: &mut Tca6424<I2C, D>,
+        led: Pin,
+        period_ms: u32,
+    ) -> Result<Self, ConfigError> {
+        Ok(Self {
+            led: tca.claim_pin(led)?,
+            period_ms: period_ms.max(2),
+            active_low: false,
+            started_ms: None,
+            written: None,
+        })
+    }
+
+    /// Lights the LED by driving the pin low instead of high.
+    pub fn with_active_low(mut self, active_low: bool) -> Self {
+        self.active_low = active_low;
+        self
+    }
+
+    /// Returns whether the LED should be lit at `now_ms`. The first call starts the period.
+    pub fn process(&mut self, now_ms: u32) -> bool {
+        let started = *self.started_ms.get_or_insert(now_ms);
+        now_ms.wrapping_sub(started) % self.period_ms < self.period_ms / 2
+    }
+
+    /// Returns the LED pin.
+    pub fn pin(&self) -> Pin {
+        self.led.pin()
+    }
+
+    /// Returns the blink period.
+    pub fn period_ms(&self) -> u32 {
+        self.period_ms
+    }
+
+    /// Returns the pin to the pool of `tca`; pins claimed from
+    /// another driver stay claimed there.
+    pub fn release<I2C: embedded_hal::i2c::ErrorType, D>(self, tca: &mut Tca6424<I2C, D>) {
+        let _ = tca.release_pin(self.led);
+    }
+
+    fn level(&self, on: bool) -> PinState {
+        if on != self.active_low {
+            PinState::High
+        } else {
+            PinState::Low
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Heartbeat",),
+    async(feature = "async", keep_self)
+)]
+impl Heartbeat {
+    /// Configures the LED pin as an output, LED off.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn init<I2C, D>(&mut self, tca: &mut Tca6424<I2C, D>) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        tca.set_pin_output(self.led.pin(), self.level(false))
+            .await?;
+        self.written = Some(false);
+        tca.set_pin_direction(self.led.pin(), PinDirection::Output)
+            .await
+    }
+
+    /// Updates the LED for `now_ms`, a free-running millisecond timestamp.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(true)` if the LED was written, `Ok(false)` if its level did not change, or
+    /// an `Error` if an I2C bus operation fails.
+    pub async fn tick<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        now_ms: u32,
+    ) -> Result<bool, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let on = self.process(now_ms);
+        if self.written == Some(on) {
+            return Ok(false);
+        }
+        self.write(tca, on).await?;
+        Ok(true)
+    }
+
+    /// Blinks the LED forever, waiting half a period between toggles with the driver's delay
+    /// source (see [`Tca6424::with_delay`]).
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns only if an I2C bus operation fails.
+    pub async fn run<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+    ) -> Result<core::convert::Infallible, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let mut on = true;
+        loop {
+            self.write(tca, on).await?;
+            on = !on;
+            tca.delay().delay_ms(self.period_ms / 2).await;
+        }
+    }
+
+    /// Writes the LED level, merging it into the cached Output register when it is known and
+    /// falling back to a read-modify-write otherwise.
+    async fn write<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        on: bool,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let pin = self.led.pin();
+        let level = self.level(on);
+        match tca.cache().get(RegisterKind::Output, pin.port()) {
+            Some(cached) => {
+                let value = match level {
+                    PinState::High => cached | 1 << pin.bit(),
+                    PinState::Low => cached & !(1 << pin.bit()),
+                };
+                tca.write_register(Register::of(RegisterKind::Output, pin.port()), value)
+                    .await?
+            }
+            None => tca.set_pin_output(pin, level).await?,
+        }
+        self.written = Some(on);
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+//! Stuck-input detection for signals that should toggle periodically.
+
+use heapless::Deque;
+
+use crate::errors::Error;
+use crate::{BusAccess, DelayNs, IoExpander, Pin, Tca6424, pins_from_mask};
+
+/// A diagnostic event of an [`InputWatchdog`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WatchdogEvent {
+    /// The pin has not changed level within the window.
+    StuckInput(Pin),
+    /// A stuck pin changed level again.
+    Recovered(Pin),
+}
+
+/// Flags inputs that should change periodically but have not changed within a window.
+///
+/// Use it for tach outputs, heartbeats from another MCU and similar signals whose silence
+/// means a fault. [`InputWatchdog::process`] is fed with 24-bit input images and a
+/// millisecond timestamp; [`InputWatchdog::poll`] reads the watched pins itself. A pin that
+/// keeps its level for `window_ms` is reported once with [`WatchdogEvent::StuckInput`], and
+/// with [`WatchdogEvent::Recovered`] on its next change. Drain events with
+/// [`InputWatchdog::next_event`].
+///
+/// The window of every pin starts with the first processed image. The pins are only read,
+/// not claimed, so they can be watched alongside the code that uses them.
+#[derive(Debug)]
+pub struct InputWatchdog {
+    pins: u32,
+    window_ms: u32,
+    last: Option<u32>,
+    changed_ms: [u32; 24],
+    stuck: u32,
+    events: Deque<WatchdogEvent, 8>,
+}
+
+impl InputWatchdog {
+    /// Creates a watchdog for the pins of `pins` (bit `n` = pin index `n`), reporting pins that
+    /// keep their level for `window_ms`.
+    pub fn new(pins: u32, window_ms: u32) -> Self {
+        Self {
+            pins: pins & 0x00FF_FFFF,
+            window_ms,
+            last: None,
+            changed_ms: [0; 24],
+            stuck: 0,
+            events: Deque::new(),
+        }
+    }
+
+    /// Returns the watched pins.
+    pub fn pins(&self) -> u32 {
+        self.pins
+    }
+
+    /// Returns the window after which an unchanged pin is reported.
+    pub fn window_ms(&self) -> u32 {
+        self.window_ms
+    }
+
+    /// Processes a 24-bit input image read at `now_ms`, queueing an event for every pin that
+    /// became stuck or recovered.
+    pub fn process(&mut self, inputs: u32, now_ms: u32) {
+        let Some(last) = self.last.replace(inputs) else {
+            self.changed_ms = [now_ms; 24];
+            return;
+        };
+        for pin in pins_from_mask((inputs ^ last) & self.pins) {
+            *pin.entry_mut(&mut self.changed_ms) = now_ms;
+            if self.stuck & (1 << pin as u8) != 0 {
+                self.stuck &= !(1 << pin as u8);
+                self.push(WatchdogEvent::Recovered(pin));
+            }
+        }
+        for pin in pins_from_mask(self.pins & !self.stuck) {
+            if now_ms.wrapping_sub(*pin.entry(&self.changed_ms)) >= self.window_ms {
+                self.stuck |= 1 << pin as u8;
+                self.push(WatchdogEvent::StuckInput(pin));
+            }
+        }
+    }
+
+    /// Returns `true` if `pin` is currently reported stuck.
+    pub fn is_stuck(&self, pin: Pin) -> bool {
+        self.stuck & (1 << pin as u8) != 0
+    }
+
+    /// Returns the pins currently reported stuck.
+    pub fn stuck_mask(&self) -> u32 {
+        self.stuck
+    }
+
+    /// Returns the next queued event.
+    pub fn next_event(&mut self) -> Option<WatchdogEvent> {
+        self.events.pop_front()
+    }
+
+    /// Forgets every level, stuck pin and queued event; the windows restart with the next
+    /// processed image.
+    pub fn reset(&mut self) {
+        self.last = None;
+        self.stuck = 0;
+        self.events.clear();
+    }
+
+    fn push(&mut self, event: WatchdogEvent) {
+        // When the queue is full the oldest event is dropped.
+        if self.events.is_full() {
+            self.events.pop_front();
+        }
+        let _ = self.events.push_back(event);
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "InputWatchdog",),
+    async(feature = "async", keep_self)
+)]
+impl InputWatchdog {
+    /// Reads the watched pins and processes them at `now_ms`.
+    ///
+    /// Costs one register read per port that holds watched pins; poll well within the
+    /// window, at least twice per period of the slowest signal.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the pins currently reported stuck, or an `Error` if an I2C bus operation
+    /// fails.
+    pub async fn poll<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        now_ms: u32,
+    ) -> Result<u32, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let inputs = tca.read(self.pins).await?;
+        self.process(inputs, now_ms);
+        Ok(self.stuck)
+    }
+}
@@ -0,0 +1,459 @@
+//! Matrix keypad scanner with chord detection.
+
+use heapless::{Deque, Vec};
+
+use crate::errors::{ConfigError, Error};
+use crate::{BusAccess, DelayNs, IoExpander, Pin, PinDirection, Tca6424};
+
+/// An event produced by a [`Keypad`]. Keys are numbered `row * COLS + column`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum KeypadEvent {
+    /// The key was pressed.
+    Pressed(u8),
+    /// The key was released.
+    Released(u8),
+    /// Every key of the chord with the given index (see [`Keypad::add_chord`]) went down
+    /// within the chord window.
+    Chord(u8),
+}
+
+/// An event of a [`Keypad`] with the key translated through its layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum KeyEvent<K> {
+    /// The key was pressed.
+    Pressed(K),
+    /// The key was released.
+    Released(K),
+    /// The chord with the given index went down, as [`KeypadEvent::Chord`].
+    Chord(u8),
+}
+
+/// A registered key combination.
+#[derive(Debug, Clone, Copy)]
+struct Chord {
+    keys: u64,
+    /// Time the first key of the chord went down, while any of them is held.
+    first_down_ms: Option<u32>,
+    /// The chord was reported, or its keys came down too slowly; cleared once all are up.
+    done: bool,
+}
+
+/// A `ROWS` x `COLS` key matrix scanned through the expander, with chord detection.
+///
+/// The rows are strobed one at a time by turning the row pin into an output driving low; the
+/// other rows stay high-impedance inputs, so two keys pressed in one column never short two
+/// driven outputs. The columns are inputs and need external pull-ups. With the register cache
+/// warm, [`Keypad::scan`] costs one Configuration write and one Input read per row, plus a
+/// final write returning the rows to inputs.
+///
+/// [`Keypad::process`] debounces the scanned key image as a whole and queues press, release
+/// and chord events; drain them with [`Keypad::next_event`]. Chords are combinations such as
+/// service-menu codes registered with [`Keypad::add_chord`]: one is reported when all of its
+/// keys are down and the last went down within the chord window of the first. The individual
+/// key events are still reported.
+///
+/// # Ghosting
+///
+/// Without a diode per key, three keys pressed on the corners of a rectangle (two rows, two
+/// columns) make the fourth corner read as pressed too. Such key images are ambiguous:
+/// [`Keypad::is_ambiguous`] reports them and no chord is recognised while one is held, but
+/// the press events include the phantom key. Choose chords whose keys cannot complete a
+/// rectangle with one more key (for example keys on distinct rows and columns, or at most two
+/// keys), or fit diodes.
+///
+/// # Layouts
+///
+/// A layout table maps each `(row, column)` to an application key code `K`, such as a `char`
+/// or an enum of the front-panel keys. Set it with [`Keypad::with_layout`] and drain
+/// [`KeyEvent`]s with [`Keypad::next_key_event`]; by default the layout yields the key
+/// numbers.
+///
+/// ```ignore
+/// let mut keypad = Keypad::new(&mut tca, ROWS, COLS, 10)?.with_layout([
+///     ['1', '2', '3'],
+///     ['4', '5', '6'],
+///     ['7', '8', '9'],
+///     ['*', '0', '#'],
+/// ]);
+/// if let Some(KeyEvent::Pressed(c)) = keypad.next_key_event() { /* ... */ }
+/// ```
+///
+/// # Rollover and buffering
+///
+/// Every scan captures the whole matrix, so any number of keys held together is reported
+/// (n-key rollover, subject to ghosting above), and keys pressed between two scans are all
+/// queued from the one image. The events wait in a buffer of `DEPTH` entries (16 by default,
+/// see [`Keypad::with_event_buffer`]) until drained; when it overflows the oldest events are
+/// dropped and counted in [`Keypad::dropped_events`]. [`Keypad::with_scan_interval_ms`]
+/// limits how often [`Keypad::poll`] scans.
+#[derive(Debug)]
+pub struct Keypad<const ROWS: usize, const COLS: usize, K = u8, const DEPTH: usize = 16> {
+    rows: [Pin; ROWS],
+    cols: [Pin; COLS],
+    layout: [[K; COLS]; ROWS],
+    debounce_ms: u32,
+    chord_window_ms: u32,
+    candidate: u64,
+    candidate_since_ms: u32,
+    keys: u64,
+    chords: Vec<Chord, 8>,
+    scan_interval_ms: u32,
+    last_scan_ms: Option<u32>,
+    events: Deque<KeypadEvent, DEPTH>,
+    dropped: u32,
+}
+
+impl<const ROWS: usize, const COLS: usize> Keypad<ROWS, COLS> {
+    /// Creates a keypad on the `rows` and `cols` pins, claiming them from `tca`.
+    ///
+    /// The key image settles after `debounce_ms` without change; the chord window defaults to
+    /// 200 ms. No bus transfer is made; call [`Keypad::init`] to configure the pins. The matrix
+    /// can hold at most 64 keys.
+    ///
+    /// # Returns
+    ///
+    /// Returns the helper, or `ConfigError::PinAlreadyClaimed` if any pin is already owned (in which
+    /// case nothing is claimed).
+    pub fn new<I2C: embedded_hal::i2c::ErrorType, D>(
+        tca: &mut Tca6424<I2C, D>,
+        rows: [Pin; ROWS],
+        cols: [Pin; COLS],
+        debounce_ms: u32,
+    ) -> Result<Self, ConfigError> {
+        const { assert!(ROWS * COLS <= 64, "a keypad holds at most 64 keys") };
+        let keypad = Self {
+            rows,
+            cols,
+            layout: core::array::from_fn(|row| core::array::from_fn(|col| Self::key(row, col))),
+            debounce_ms,
+            chord_window_ms: 200,
+            candidate: 0,
+            candidate_since_ms: 0,
+            keys: 0,
+            chords: Vec::new(),
+            scan_interval_ms: 0,
+            last_scan_ms: None,
+            events: Deque::new(),
+            dropped: 0,
+        };
+        tca.claim_bits(keypad.row_mask() | keypad.col_mask())?;
+        Ok(keypad)
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize, K: Copy, const DEPTH: usize>
+    Keypad<ROWS, COLS, K, DEPTH>
+{
+    /// Translates keys through `layout`, indexed `[row][column]`, in
+    /// [`Keypad::next_key_event`].
+    pub fn with_layout<K2: Copy>(
+        self,
+        layout: [[K2; COLS]; ROWS],
+    ) -> Keypad<ROWS, COLS, K2, DEPTH> {
+        Keypad {
+            rows: self.rows,
+            cols: self.cols,
+            layout,
+            debounce_ms: self.debounce_ms,
+            chord_window_ms: self.chord_window_ms,
+            candidate: self.candidate,
+            candidate_since_ms: self.candidate_since_ms,
+            keys: self.keys,
+            chords: self.chords,
+            scan_interval_ms: self.scan_interval_ms,
+            last_scan_ms: self.last_scan_ms,
+            events: self.events,
+            dropped: self.dropped,
+        }
+    }
+
+    /// Buffers up to `DEPTH2` events between drains instead.
+    ///
+    /// Queued events are moved over; if they do not fit, the oldest are dropped and counted.
+    pub fn with_event_buffer<const DEPTH2: usize>(mut self) -> Keypad<ROWS, COLS, K, DEPTH2> {
+        let mut events = Deque::new();
+        while let Some(event) = self.events.pop_back() {
+            if events.push_front(event).is_err() {
+                self.dropped = self.dropped.wrapping_add(1);
+            }
+        }
+        Keypad {
+            rows: self.rows,
+            cols: self.cols,
+            layout: self.layout,
+            debounce_ms: self.debounce_ms,
+            chord_window_ms: self.chord_window_ms,
+            candidate: self.candidate,
+            candidate_since_ms: self.candidate_since_ms,
+            keys: self.keys,
+            chords: self.chords,
+            scan_interval_ms: self.scan_interval_ms,
+            last_scan_ms: self.last_scan_ms,
+            events,
+            dropped: self.dropped,
+        }
+    }
+
+    /// Makes [`Keypad::poll`] scan at most once every `interval_ms`; `0` (the default) scans on
+    /// every call.
+    ///
+    /// Keep the interval below the debounce time and the shortest expected key press.
+    pub fn with_scan_interval_ms(mut self, interval_ms: u32) -> Self {
+        self.scan_interval_ms = interval_ms;
+        self
+    }
+
+    /// Returns the minimum time between two scans of [`Keypad::poll`].
+    pub fn scan_interval_ms(&self) -> u32 {
+        self.scan_interval_ms
+    }
+
+    /// Returns `true` if [`Keypad::poll`] would scan at `now_ms`.
+    pub fn is_scan_due(&self, now_ms: u32) -> bool {
+        self.last_scan_ms
+            .is_none_or(|last| now_ms.wrapping_sub(last) >= self.scan_interval_ms)
+    }
+
+    /// Returns the number of queued events.
+    pub fn pending_events(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns the number of events dropped because the buffer was full.
+    pub fn dropped_events(&self) -> u32 {
+        self.dropped
+    }
+
+    /// Returns the layout entry of key number `key`, or `None` if the matrix has no such key.
+    pub fn key_code(&self, key: u8) -> Option<K> {
+        let key = usize::from(key);
+        self.layout
+            .get(key / COLS.max(1))?
+            .get(key % COLS.max(1))
+            .copied()
+    }
+
+    /// Sets the time within which every key of a chord must go down.
+    pub fn with_chord_window_ms(mut self, window_ms: u32) -> Self {
+        self.chord_window_ms = window_ms;
+        self
+    }
+
+    /// Returns the number of the key at `row` and `col`.
+    pub const fn key(row: usize, col: usize) -> u8 {
+        (row * COLS + col) as u8
+    }
+
+    /// Registers a chord of the keys in `keys` (bit `n` = key `n`).
+    ///
+    /// # Returns
+    ///
+    /// Returns the chord index reported by [`KeypadEvent::Chord`], or `None` if eight chords
+    /// are already registered or `keys` has fewer than two keys.
+    pub fn add_chord(&mut self, keys: u64) -> Option<u8> {
+        if keys.count_ones() < 2 {
+            return None;
+        }
+        let index = self.chords.len() as u8;
+        self.chords
+            .push(Chord {
+                keys,
+                first_down_ms: None,
+                done: false,
+            })
+            .ok()?;
+        Some(index)
+    }
+
+    /// Processes a scanned key image (bit `n` = key `n` pressed) taken at `now_ms`.
+    ///
+    /// The image is accepted once it has not changed for the debounce time; the events of an
+    /// accepted change are queued for [`Keypad::next_event`]. When the queue is full the oldest
+    /// event is dropped.
+    pub fn process(&mut self, keys: u64, now_ms: u32) {
+        if keys != self.candidate {
+            self.candidate = keys;
+            self.candidate_since_ms = now_ms;
+        }
+        if self.candidate == self.keys
+            || now_ms.wrapping_sub(self.candidate_since_ms) < self.debounce_ms
+        {
+            return;
+        }
+        let previous = core::mem::replace(&mut self.keys, self.candidate);
+        for key in 0..(ROWS * COLS) as u8 {
+            let bit = 1 << key;
+            if (previous ^ self.keys) & bit != 0 {
+                self.push(if self.keys & bit != 0 {
+                    KeypadEvent::Pressed(key)
+                } else {
+                    KeypadEvent::Released(key)
+                });
+            }
+        }
+        self.update_chords(now_ms);
+    }
+
+    /// Returns the debounced key image.
+    pub fn keys(&self) -> u64 {
+        self.keys
+    }
+
+    /// Returns `true` if `key` is pressed.
+    pub fn is_pressed(&self, key: u8) -> bool {
+        key < 64 && self.keys & (1 << key) != 0
+    }
+
+    /// Returns `true` if the debounced key image may contain ghost keys: two rows with at
+    /// least two pressed columns in common.
+    pub fn is_ambiguous(&self) -> bool {
+        let row = 1u64.checked_shl(COLS as u32).unwrap_or(0).wrapping_sub(1);
+        let row_bits = |index: usize| (self.keys >> (index * COLS)) & row;
+        (0..ROWS).any(|a| (a + 1..ROWS).any(|b| (row_bits(a) & row_bits(b)).count_ones() >= 2))
+    }
+
+    /// Takes the oldest queued event.
+    pub fn next_event(&mut self) -> Option<KeypadEvent> {
+        self.events.pop_front()
+    }
+
+    /// Takes the oldest queued event, translated through the layout.
+    pub fn next_key_event(&mut self) -> Option<KeyEvent<K>> {
+        Some(match self.next_event()? {
+            KeypadEvent::Pressed(key) => KeyEvent::Pressed(self.key_code(key)?),
+            KeypadEvent::Released(key) => KeyEvent::Released(self.key_code(key)?),
+            KeypadEvent::Chord(index) => KeyEvent::Chord(index),
+        })
+    }
+
+    /// Returns the row and column pins to the pool of `tca`.
+    pub fn release<I2C: embedded_hal::i2c::ErrorType, D>(self, tca: &mut Tca6424<I2C, D>) {
+        tca.release_mask(self.row_mask() | self.col_mask());
+    }
+
+    fn update_chords(&mut self, now_ms: u32) {
+        let ambiguous = self.is_ambiguous();
+        let mut reported: Vec<u8, 8> = Vec::new();
+        for (index, chord) in self.chords.iter_mut().enumerate() {
+            let down = self.keys & chord.keys;
+            if down == 0 {
+                chord.first_down_ms = None;
+                chord.done = false;
+                continue;
+            }
+            let first_down_ms = *chord.first_down_ms.get_or_insert(now_ms);
+            if down == chord.keys && !chord.done && !ambiguous {
+                chord.done = true;
+                if now_ms.wrapping_sub(first_down_ms) <= self.chord_window_ms {
+                    let _ = reported.push(index as u8);
+                }
+            }
+        }
+        for index in reported {
+            self.push(KeypadEvent::Chord(index));
+        }
+    }
+
+    fn push(&mut self, event: KeypadEvent) {
+        if self.events.is_full() {
+            self.events.pop_front();
+            self.dropped = self.dropped.wrapping_add(1);
+        }
+        let _ = self.events.push_back(event);
+    }
+
+    fn row_mask(&self) -> u32 {
+        self.rows.iter().fold(0, |mask, &pin| mask | 1 << pin as u8)
+    }
+
+    fn col_mask(&self) -> u32 {
+        self.cols.iter().fold(0, |mask, &pin| mask | 1 << pin as u8)
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Keypad",),
+    async(feature = "async", keep_self)
+)]
+impl<const ROWS: usize, const COLS: usize, K: Copy, const DEPTH: usize>
+    Keypad<ROWS, COLS, K, DEPTH>
+{
+    /// Latches the row outputs low and makes every row and column pin an input.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn init<I2C, D>(&mut self, tca: &mut Tca6424<I2C, D>) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        tca.drive_pins(0, self.row_mask()).await?;
+        tca.set_direction(self.row_mask() | self.col_mask(), PinDirection::Input)
+            .await
+    }
+
+    /// Strobes every row and returns the raw key image (bit `n` = key `n` pressed).
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the key image, or an `Error` if an I2C bus operation fails (the rows are then
+    /// left as they were at the failure; the next scan recovers them).
+    pub async fn scan<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+    ) -> Result<u64, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let row_mask = self.row_mask();
+        let mut keys = 0;
+        for (row, &row_pin) in self.rows.iter().enumerate() {
+            let strobe = 1 << row_pin as u8;
+            tca.drive_directions(strobe, row_mask & !strobe).await?;
+            let levels = tca.read(self.col_mask()).await?;
+            for (col, &col_pin) in self.cols.iter().enumerate() {
+                if levels & (1 << col_pin as u8) == 0 {
+                    keys |= 1 << (row * COLS + col);
+                }
+            }
+        }
+        tca.drive_directions(0, row_mask).await?;
+        Ok(keys)
+    }
+
+    /// Scans the matrix and processes the image at `now_ms`, if a scan is due (see
+    /// [`Keypad::with_scan_interval_ms`]); otherwise makes no transfer.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the debounced key image, or an `Error` if an I2C bus operation fails.
+    pub async fn poll<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        now_ms: u32,
+    ) -> Result<u64, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        if self.is_scan_due(now_ms) {
+            let keys = self.scan(tca).await?;
+            self.last_scan_ms = Some(now_ms);
+            self.process(keys, now_ms);
+        }
+        Ok(self.keys)
+    }
+}
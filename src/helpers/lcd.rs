@@ -0,0 +1,573 @@
+//! HD44780 character LCD in 4-bit mode on expander pins.
+
+use crate::errors::{ConfigError, Error};
+use crate::{BusAccess, DelayNs, IoExpander, Pin, Tca6424};
+
+/// Execution time of most instructions, in microseconds.
+const INSTRUCTION_US: u32 = 37;
+/// Execution time of Clear Display and Return Home, in microseconds.
+const LONG_INSTRUCTION_US: u32 = 1520;
+/// Busy-flag reads before [`Hd44780`] gives up on the display.
+const MAX_BUSY_POLLS: u32 = 100;
+/// DDRAM address of the first column of each line (20x4 layout; 16x2 uses the first two).
+const LINE_ADDRESSES: [u8; 4] = [0x00, 0x40, 0x14, 0x54];
+
+/// The expander pins wired to an HD44780 display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LcdPins {
+    /// Register select (low = instruction, high = data).
+    pub rs: Pin,
+    /// Enable strobe.
+    pub enable: Pin,
+    /// Read/write select, or `None` if RW is tied low (write-only wiring).
+    pub rw: Option<Pin>,
+    /// The D4 to D7 data lines.
+    pub data: [Pin; 4],
+}
+
+/// An HD44780-compatible character display driven in 4-bit mode.
+///
+/// Every nibble costs three register writes (data and RS, E high, E low) through the
+/// driver's register cache. After each instruction the adapter waits for the display to
+/// finish: by default with a delay covering the worst-case execution time (37 µs, or 1.52 ms
+/// for Clear Display and Return Home), using the driver's delay source.
+///
+/// With the RW pin wired, [`Hd44780::with_busy_flag`] polls the busy flag instead: the data
+/// pins are turned into inputs, D7 is read with the display selected for reading, and the
+/// pins are switched back to outputs once the display is ready. Over I2C the first poll
+/// usually finds the display ready already, which saves the long fixed waits.
+///
+/// The adapter can also own a backlight pin ([`Hd44780::with_backlight`]) and a power or
+/// contrast-supply enable pin ([`Hd44780::with_power_enable`]). Their levels are folded into
+/// the first register write of every nibble, so while they share a port with the data lines a
+/// backlight change costs no transaction of its own; [`Hd44780::poll`] applies pending levels
+/// when the display is idle and switches the backlight off after the dim timeout.
+///
+/// The pins are claimed from the driver on construction. Call [`Hd44780::init`] once after
+/// power-up.
+#[derive(Debug)]
+pub struct Hd44780 {
+    pins: LcdPins,
+    busy_flag: bool,
+    backlight: Option<ControlPin>,
+    power: Option<ControlPin>,
+    backlight_on: bool,
+    power_on: bool,
+    dim_after_ms: Option<u32>,
+    last_activity_ms: Option<u32>,
+}
+
+/// An on/off pin owned by an [`Hd44780`].
+#[derive(Debug, Clone, Copy)]
+struct ControlPin {
+    pin: Pin,
+    active_low: bool,
+}
+
+impl Hd44780 {
+    /// Creates a display adapter on `pins`, claiming them from `tca`.
+    ///
+    /// No bus transfer is made; call [`Hd44780::init`] to configure the pins and the display.
+    ///
+    /// # Returns
+    ///
+    /// Returns the helper, `ConfigError::InvalidRegisterOrPin` if two of the pins are the same, or
+    /// `ConfigError::PinAlreadyClaimed` if any pin is already owned (in which case nothing is
+    /// claimed).
+    pub fn new<I2C: embedded_hal::i2c::ErrorType, D>(
+        tca: &mut Tca6424<I2C, D>,
+        pins: LcdPins,
+    ) -> Result<Self, ConfigError> {
+        let lcd = Self {
+            pins,
+            busy_flag: false,
+            backlight: None,
+            power: None,
+            backlight_on: true,
+            power_on: true,
+            dim_after_ms: None,
+            last_activity_ms: None,
+        };
+        let count = 6 + u32::from(pins.rw.is_some());
+        if lcd.mask().count_ones() != count {
+            return Err(ConfigError::InvalidRegisterOrPin);
+        }
+        tca.claim_bits(lcd.mask())?;
+        Ok(lcd)
+    }
+
+    /// Waits for instructions by polling the busy flag instead of fixed delays.
+    ///
+    /// Has no effect without an RW pin (see [`Hd44780::uses_busy_flag`]).
+    pub fn with_busy_flag(mut self) -> Self {
+        self.busy_flag = true;
+        self
+    }
+
+    /// Drives the backlight from `pin`, claiming it from `tca`; `active_low` lights it with a
+    /// low level. The backlight starts on.
+    ///
+    /// # Returns
+    ///
+    /// Returns the helper, or `ConfigError::PinAlreadyClaimed` if `pin` is already owned, in which
+    /// case the pins of the display are released as well.
+    pub fn with_backlight<I2C: embedded_hal::i2c::ErrorType, D>(
+        mut self,
+        tca: &mut Tca6424<I2C, D>,
+        pin: Pin,
+        active_low: bool,
+    ) -> Result<Self, ConfigError> {
+        let control = self.claim_control(tca, pin, active_low)?;
+        if let Some(previous) = self.backlight.replace(control) {
+            tca.release_mask(self.bit(previous.pin));
+        }
+        Ok(self)
+    }
+
+    /// Switches the display or contrast supply from `pin`, claiming it from `tca`;
+    /// `active_low` enables it with a low level. The supply is enabled by
+    /// [`Hd44780::init`].
+    ///
+    /// # Returns
+    ///
+    /// Returns the helper, or `ConfigError::PinAlreadyClaimed` if `pin` is already owned, in which
+    /// case the pins of the display are released as well.
+    pub fn with_power_enable<I2C: embedded_hal::i2c::ErrorType, D>(
+        mut self,
+        tca: &mut Tca6424<I2C, D>,
+        pin: Pin,
+        active_low: bool,
+    ) -> Result<Self, ConfigError> {
+        let control = self.claim_control(tca, pin, active_low)?;
+        if let Some(previous) = self.power.replace(control) {
+            tca.release_mask(self.bit(previous.pin));
+        }
+        Ok(self)
+    }
+
+    /// Switches the backlight off once [`Hd44780::poll`] sees no activity for `timeout_ms`;
+    /// `None` (the default) keeps it as set.
+    pub fn with_dim_after_ms(mut self, timeout_ms: Option<u32>) -> Self {
+        self.dim_after_ms = timeout_ms;
+        self
+    }
+
+    /// Returns the backlight pin, if any.
+    pub fn backlight_pin(&self) -> Option<Pin> {
+        self.backlight.map(|control| control.pin)
+    }
+
+    /// Returns the power-enable pin, if any.
+    pub fn power_enable_pin(&self) -> Option<Pin> {
+        self.power.map(|control| control.pin)
+    }
+
+    /// Requests the backlight on or off. The level is written with the next nibble or
+    /// [`Hd44780::poll`].
+    pub fn set_backlight(&mut self, on: bool) {
+        self.backlight_on = on;
+    }
+
+    /// Returns the requested backlight state.
+    pub fn is_backlight_on(&self) -> bool {
+        self.backlight_on
+    }
+
+    /// Records user activity at `now_ms`: restarts the dim timeout and requests the backlight
+    /// on.
+    pub fn wake(&mut self, now_ms: u32) {
+        self.last_activity_ms = Some(now_ms);
+        self.backlight_on = true;
+    }
+
+    /// Processes the dim timeout at `now_ms`, returning the requested backlight state.
+    ///
+    /// The timeout starts with the first call, or the last [`Hd44780::wake`].
+    pub fn process(&mut self, now_ms: u32) -> bool {
+        let since = *self.last_activity_ms.get_or_insert(now_ms);
+        if let Some(timeout_ms) = self.dim_after_ms
+            && now_ms.wrapping_sub(since) >= timeout_ms
+        {
+            self.backlight_on = false;
+        }
+        self.backlight_on
+    }
+
+    /// Returns `true` if instructions are completed by polling the busy flag.
+    pub fn uses_busy_flag(&self) -> bool {
+        self.busy_flag && self.pins.rw.is_some()
+    }
+
+    /// Returns the display pins.
+    pub fn pins(&self) -> LcdPins {
+        self.pins
+    }
+
+    /// Returns the pins to the pool of `tca`.
+    pub fn release<I2C: embedded_hal::i2c::ErrorType, D>(self, tca: &mut Tca6424<I2C, D>) {
+        tca.release_mask(self.mask());
+    }
+
+    fn mask(&self) -> u32 {
+        self.data_mask()
+            | self.bit(self.pins.rs)
+            | self.bit(self.pins.enable)
+            | self.rw_mask()
+            | self.control_mask()
+    }
+
+    fn control_mask(&self) -> u32 {
+        [self.backlight, self.power]
+            .iter()
+            .flatten()
+            .fold(0, |mask, control| mask | self.bit(control.pin))
+    }
+
+    /// Returns the control pins to drive high and low for the requested states.
+    fn control_levels(&self) -> (u32, u32) {
+        let mut levels = (0, 0);
+        for (control, on) in [
+            (self.backlight, self.backlight_on),
+            (self.power, self.power_on),
+        ] {
+            if let Some(control) = control {
+                if on != control.active_low {
+                    levels.0 |= self.bit(control.pin);
+                } else {
+                    levels.1 |= self.bit(control.pin);
+                }
+            }
+        }
+        levels
+    }
+
+    fn claim_control<I2C: embedded_hal::i2c::ErrorType, D>(
+        &self,
+        tca: &mut Tca6424<I2C, D>,
+        pin: Pin,
+        active_low: bool,
+    ) -> Result<ControlPin, ConfigError> {
+        match tca.claim_bits(self.bit(pin)) {
+            Ok(()) => Ok(ControlPin { pin, active_low }),
+            Err(error) => {
+                tca.release_mask(self.mask());
+                Err(error)
+            }
+        }
+    }
+
+    fn data_mask(&self) -> u32 {
+        self.pins
+            .data
+            .iter()
+            .fold(0, |mask, &pin| mask | self.bit(pin))
+    }
+
+    fn rw_mask(&self) -> u32 {
+        self.pins.rw.map_or(0, |pin| self.bit(pin))
+    }
+
+    fn bit(&self, pin: Pin) -> u32 {
+        1 << pin as u8
+    }
+
+    /// Returns the data pins to drive high for the low four bits of `nibble`.
+    fn nibble_mask(&self, nibble: u8) -> u32 {
+        self.pins
+            .data
+            .iter()
+            .enumerate()
+            .filter(|&(bit, _)| nibble & (1 << bit) != 0)
+            .fold(0, |mask, (_, &pin)| mask | self.bit(pin))
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Hd44780",),
+    async(feature = "async", keep_self)
+)]
+impl Hd44780 {
+    /// Drives every pin low, makes them outputs and runs the 4-bit initialisation sequence:
+    /// two-line mode, display on without cursor, cleared, cursor moving right.
+    ///
+    /// The power-enable pin is switched on and the backlight set as requested in the same
+    /// writes. Waits 50 ms first for the display's power-on reset.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn init<I2C, D>(&mut self, tca: &mut Tca6424<I2C, D>) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        self.power_on = true;
+        let (on, off) = self.control_levels();
+        tca.drive_pins(on, (self.mask() | off) & !on).await?;
+        tca.drive_directions(self.mask(), 0).await?;
+        tca.delay().delay_ms(50).await;
+        // The busy flag cannot be read until the interface is in 4-bit mode.
+        for (nibble, wait_us) in [(0x3, 4100), (0x3, 100), (0x3, 100), (0x2, 100)] {
+            self.write_nibble(tca, false, nibble).await?;
+            tca.delay().delay_us(wait_us).await;
+        }
+        for instruction in [0x28, 0x0C, 0x01, 0x06] {
+            self.command(tca, instruction).await?;
+        }
+        Ok(())
+    }
+
+    /// Sends an instruction byte and waits until the display has executed it.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, `Error::Timeout` if the busy flag is still set after 100
+    /// polls, or an `Error` if an I2C bus operation fails.
+    pub async fn command<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        instruction: u8,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        self.send(tca, false, instruction).await?;
+        let execution_us = match instruction {
+            0x01..=0x03 => LONG_INSTRUCTION_US,
+            _ => INSTRUCTION_US,
+        };
+        self.wait_ready(tca, execution_us).await
+    }
+
+    /// Writes a byte to the display RAM at the cursor.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, `Error::Timeout` if the busy flag is still set after 100
+    /// polls, or an `Error` if an I2C bus operation fails.
+    pub async fn write_byte<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        byte: u8,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        self.send(tca, true, byte).await?;
+        self.wait_ready(tca, INSTRUCTION_US).await
+    }
+
+    /// Writes the bytes of `text` at the cursor. Characters outside ASCII are written as
+    /// their UTF-8 bytes, which the display shows from its own character ROM.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` as [`Hd44780::write_byte`].
+    pub async fn write_str<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        text: &str,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        for &byte in text.as_bytes() {
+            self.write_byte(tca, byte).await?;
+        }
+        Ok(())
+    }
+
+    /// Clears the display and returns the cursor home.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` as [`Hd44780::command`].
+    pub async fn clear<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        self.command(tca, 0x01).await
+    }
+
+    /// Moves the cursor to `col` on `line` (0 to 3).
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, `Error::InvalidRegisterOrPin` for a line above 3 (no
+    /// transfer is made), or an `Error` as [`Hd44780::command`].
+    pub async fn set_cursor<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        col: u8,
+        line: u8,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let base = *LINE_ADDRESSES
+            .get(usize::from(line))
+            .ok_or(Error::InvalidRegisterOrPin)?;
+        self.command(tca, 0x80 | (base.wrapping_add(col) & 0x7F))
+            .await
+    }
+
+    /// Processes the dim timeout at `now_ms` (see [`Hd44780::process`]) and writes the
+    /// requested backlight and power levels if they changed.
+    ///
+    /// Costs no transfer while nothing changed. This method is `async` when the `async`
+    /// feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the requested backlight state, or an `Error` if an I2C bus operation fails.
+    pub async fn poll<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        now_ms: u32,
+    ) -> Result<bool, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let backlight_on = self.process(now_ms);
+        let (on, off) = self.control_levels();
+        tca.drive_pins(on, off).await?;
+        Ok(backlight_on)
+    }
+
+    /// Switches the supply of the display off, or back on.
+    ///
+    /// Before switching off, every other display pin and the backlight are driven low so the
+    /// display is not powered through its inputs; the backlight stays off until requested
+    /// again. After switching on, run [`Hd44780::init`] again. Without a power-enable pin
+    /// only the backlight is affected.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn set_power<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        on: bool,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        if !on {
+            self.backlight_on = false;
+            let (control_on, control_off) = self.control_levels();
+            let signals = self.mask() & !self.control_mask();
+            tca.drive_pins(control_on, signals | control_off).await?;
+        }
+        self.power_on = on;
+        let (control_on, control_off) = self.control_levels();
+        tca.drive_pins(control_on, control_off).await
+    }
+
+    /// Sends `byte` as two nibbles, high nibble first.
+    async fn send<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        rs: bool,
+        byte: u8,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        self.write_nibble(tca, rs, byte >> 4).await?;
+        self.write_nibble(tca, rs, byte & 0x0F).await
+    }
+
+    /// Presents a nibble and RS with E low, then strobes E.
+    async fn write_nibble<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        rs: bool,
+        nibble: u8,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let (rs_pin, enable) = (self.bit(self.pins.rs), self.bit(self.pins.enable));
+        let (control_on, control_off) = self.control_levels();
+        let high = self.nibble_mask(nibble) | if rs { rs_pin } else { 0 } | control_on;
+        let low = (self.data_mask() | rs_pin | enable | control_off) & !high;
+        tca.drive_pins(high, low).await?;
+        tca.drive_pins(enable, 0).await?;
+        tca.drive_pins(0, enable).await
+    }
+
+    /// Waits until the display is ready for the next instruction: polls the busy flag, or
+    /// waits `delay_us`.
+    async fn wait_ready<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        delay_us: u32,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        if !self.uses_busy_flag() {
+            tca.delay().delay_us(delay_us).await;
+            return Ok(());
+        }
+        let (rw, enable) = (self.rw_mask(), self.bit(self.pins.enable));
+        let busy_pin = self.bit(self.pins.data[3]);
+        tca.drive_directions(0, self.data_mask()).await?;
+        tca.drive_pins(rw, self.bit(self.pins.rs)).await?;
+        let mut ready = false;
+        for _ in 0..MAX_BUSY_POLLS {
+            // The busy flag comes with the high nibble; the low nibble is clocked out unread.
+            tca.drive_pins(enable, 0).await?;
+            let busy = tca.read(busy_pin).await? != 0;
+            tca.drive_pins(0, enable).await?;
+            tca.drive_pins(enable, 0).await?;
+            tca.drive_pins(0, enable).await?;
+            if !busy {
+                ready = true;
+                break;
+            }
+        }
+        tca.drive_pins(0, rw).await?;
+        tca.drive_directions(self.data_mask(), 0).await?;
+        if ready { Ok(()) } else { Err(Error::Timeout) }
+    }
+}
@@ -0,0 +1,291 @@
+//! Illuminated push-button: a debounced input pin paired with an LED output pin.
+
+use super::{ButtonGesture, Debounce, Debouncer, GestureConfig, GestureDetector};
+use crate::errors::{ConfigError, Error};
+use crate::{BusAccess, DelayNs, Pin, PinClaim, PinDirection, PinState, Tca6424};
+
+/// How the LED of a [`LitButton`] reacts to the button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LedFeedback {
+    /// The LED is lit while the button is held.
+    Follow,
+    /// Every press toggles a latched state, shown by the LED.
+    ToggleLatch,
+    /// The LED is lit while the button is held and starts blinking once it has been held for
+    /// `hold_ms`, toggling every `period_ms / 2`.
+    BlinkOnHold {
+        /// Hold time before blinking starts.
+        hold_ms: u32,
+        /// Blink period.
+        period_ms: u32,
+    },
+}
+
+/// Configuration of a [`LitButton`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LitButtonConfig {
+    /// LED behaviour.
+    pub feedback: LedFeedback,
+    /// Debounce settle time of the button.
+    pub debounce_ms: u32,
+    /// `true` if the button pulls the input low when pressed (the usual pull-up wiring).
+    pub button_active_low: bool,
+    /// `true` if the LED is lit by driving the output low.
+    pub led_active_low: bool,
+}
+
+impl Default for LitButtonConfig {
+    fn default() -> Self {
+        Self {
+            feedback: LedFeedback::Follow,
+            debounce_ms: 20,
+            button_active_low: true,
+            led_active_low: false,
+        }
+    }
+}
+
+/// A debounced button event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ButtonEvent {
+    /// The button was pressed.
+    Pressed,
+    /// The button was released.
+    Released,
+}
+
+/// An illuminated front-panel button: one input pin (debounced) and one LED output pin.
+///
+/// Both pins are claimed from the driver on construction. Call [`LitButton::poll`]
+/// periodically (every few milliseconds); it reads the button, reports press and release
+/// events and keeps the LED in line with the configured [`LedFeedback`].
+///
+/// The button is debounced with a [`Debouncer`] using `debounce_ms` of the configuration;
+/// [`LitButton::with_debouncer`] swaps in any other [`Debounce`] algorithm.
+///
+/// [`LitButton::with_gestures`] additionally classifies presses into clicks, double clicks
+/// and long presses, drained with [`LitButton::next_gesture`].
+#[derive(Debug)]
+pub struct LitButton<B = Debouncer> {
+    button: PinClaim,
+    led: PinClaim,
+    config: LitButtonConfig,
+    debouncer: B,
+    pressed_at_ms: u32,
+    latched: bool,
+    led_on: bool,
+    /// LED level last written to the device; `None` until the first write.
+    led_written: Option<bool>,
+    gestures: Option<GestureDetector>,
+}
+
+impl LitButton {
+    /// Creates a lit button, claiming `button` and `led` from `tca`.
+    ///
+    /// No bus transfer is made; call [`LitButton::init`] to configure the pins.
+    ///
+    /// # Returns
+    ///
+    /// Returns the helper, or `ConfigError::PinAlreadyClaimed` if either pin is already owned.
+    pub fn new<I2C: embedded_hal::i2c::ErrorType, D>(
+        tca: &mut Tca6424<I2C, D>,
+        button: Pin,
+        led: Pin,
+        config: LitButtonConfig,
+    ) -> Result<Self, ConfigError> {
+        let button = tca.claim_pin(button)?;
+        let led = match tca.claim_pin(led) {
+            Ok(led) => led,
+            Err(error) => {
+                let _ = tca.release_pin(button);
+                return Err(error);
+            }
+        };
+        Ok(Self {
+            button,
+            led,
+            config,
+            debouncer: Debouncer::new(false, config.debounce_ms),
+            pressed_at_ms: 0,
+            latched: false,
+            led_on: false,
+            led_written: None,
+            gestures: None,
+        })
+    }
+}
+
+impl<B: Debounce> LitButton<B> {
+    /// Debounces the button with `debouncer` instead, which should start released (low).
+    ///
+    /// `debounce_ms` of the configuration is then unused.
+    pub fn with_debouncer<B2: Debounce>(self, debouncer: B2) -> LitButton<B2> {
+        LitButton {
+            button: self.button,
+            led: self.led,
+            config: self.config,
+            debouncer,
+            pressed_at_ms: self.pressed_at_ms,
+            latched: self.latched,
+            led_on: self.led_on,
+            led_written: self.led_written,
+            gestures: self.gestures,
+        }
+    }
+
+    /// Recognises clicks, double clicks and long presses with the given timing.
+    ///
+    /// The gestures are derived from the debounced button on every [`LitButton::process`] or
+    /// [`LitButton::poll`], so keep polling while the button is idle: single clicks are only
+    /// reported once the double-click window has passed.
+    pub fn with_gestures(mut self, config: GestureConfig) -> Self {
+        self.gestures = Some(GestureDetector::new(config));
+        self
+    }
+
+    /// Takes the oldest recognised gesture; always `None` without
+    /// [`LitButton::with_gestures`].
+    pub fn next_gesture(&mut self) -> Option<ButtonGesture> {
+        self.gestures.as_mut()?.next_gesture()
+    }
+
+    /// Processes one raw sample of the button input (pin level) taken at `now_ms`.
+    ///
+    /// Updates the LED state (see [`LitButton::led_on`]) and returns the debounced event, if any.
+    pub fn process(&mut self, level: PinState, now_ms: u32) -> Option<ButtonEvent> {
+        let pressed = (level == PinState::High) != self.config.button_active_low;
+        let event = match self.debouncer.update(pressed, now_ms) {
+            Some(true) => {
+                self.pressed_at_ms = now_ms;
+                if self.config.feedback == LedFeedback::ToggleLatch {
+                    self.latched = !self.latched;
+                }
+                Some(ButtonEvent::Pressed)
+            }
+            Some(false) => Some(ButtonEvent::Released),
+            None => None,
+        };
+
+        let held = self.debouncer.is_high();
+        if let Some(gestures) = &mut self.gestures {
+            gestures.update(held, now_ms);
+        }
+        self.led_on = match self.config.feedback {
+            LedFeedback::Follow => held,
+            LedFeedback::ToggleLatch => self.latched,
+            LedFeedback::BlinkOnHold { hold_ms, period_ms } => {
+                let held_for = now_ms.wrapping_sub(self.pressed_at_ms);
+                if !held {
+                    false
+                } else if held_for < hold_ms || period_ms == 0 {
+                    true
+                } else {
+                    // Start the blink with the LED off so the transition is visible.
+                    ((held_for - hold_ms) / (period_ms / 2).max(1)) % 2 == 1
+                }
+            }
+        };
+        event
+    }
+
+    /// Returns `true` while the debounced button is pressed.
+    pub fn is_pressed(&self) -> bool {
+        self.debouncer.is_high()
+    }
+
+    /// Returns the latched state of a [`LedFeedback::ToggleLatch`] button.
+    pub fn is_latched(&self) -> bool {
+        self.latched
+    }
+
+    /// Returns `true` if the LED should currently be lit.
+    pub fn led_on(&self) -> bool {
+        self.led_on
+    }
+
+    /// Returns the button pin.
+    pub fn button_pin(&self) -> Pin {
+        self.button.pin()
+    }
+
+    /// Returns the LED pin.
+    pub fn led_pin(&self) -> Pin {
+        self.led.pin()
+    }
+
+    /// Returns both pins to the pool of `tca`; pins claimed from
+    /// another driver stay claimed there.
+    pub fn release<I2C: embedded_hal::i2c::ErrorType, D>(self, tca: &mut Tca6424<I2C, D>) {
+        let _ = tca.release_pin(self.button);
+        let _ = tca.release_pin(self.led);
+    }
+
+    fn led_level(&self, on: bool) -> PinState {
+        if on != self.config.led_active_low {
+            PinState::High
+        } else {
+            PinState::Low
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "LitButton",),
+    async(feature = "async", keep_self)
+)]
+impl<B: Debounce> LitButton<B> {
+    /// Configures the button pin as an input and the LED pin as an output, LED off.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn init<I2C, D>(&mut self, tca: &mut Tca6424<I2C, D>) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        tca.set_pin_direction(self.button.pin(), PinDirection::Input)
+            .await?;
+        tca.set_pin_output(self.led.pin(), self.led_level(false))
+            .await?;
+        self.led_written = Some(false);
+        tca.set_pin_direction(self.led.pin(), PinDirection::Output)
+            .await
+    }
+
+    /// Reads the button, updates the LED if its state changed, and returns the debounced event.
+    ///
+    /// `now_ms` is a free-running millisecond timestamp. Each call costs one register read,
+    /// plus a read-modify-write of the output register when the LED changes.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the event, if any, or an `Error` if an I2C bus operation fails.
+    pub async fn poll<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        now_ms: u32,
+    ) -> Result<Option<ButtonEvent>, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let level = tca.get_pin_input_state(self.button.pin()).await?;
+        let event = self.process(level, now_ms);
+        if self.led_written != Some(self.led_on) {
+            tca.set_pin_output(self.led.pin(), self.led_level(self.led_on))
+                .await?;
+            self.led_written = Some(self.led_on);
+        }
+        Ok(event)
+    }
+}
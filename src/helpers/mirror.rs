@@ -0,0 +1,283 @@
+//! Input-to-output pin mirroring driven by input events.
+
+use heapless::Vec;
+
+use crate::errors::Error;
+use crate::{BusAccess, DelayNs, InputChanges, Pin, PinState, Tca6424};
+
+/// One mirroring rule of a [`PinMirror`]: `output` follows the level of `input`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MirrorRule {
+    input: Pin,
+    output: Pin,
+    inverted: bool,
+    delay_ms: u32,
+}
+
+impl MirrorRule {
+    /// Creates a rule driving `output` to the level of `input`, without delay.
+    pub const fn new(input: Pin, output: Pin) -> Self {
+        Self {
+            input,
+            output,
+            inverted: false,
+            delay_ms: 0,
+        }
+    }
+
+    /// Drives the output to the opposite level of the input when `inverted`.
+    pub const fn with_inverted(mut self, inverted: bool) -> Self {
+        self.inverted = inverted;
+        self
+    }
+
+    /// Forwards a change only once the input has kept its new level for `delay_ms`.
+    pub const fn with_delay_ms(mut self, delay_ms: u32) -> Self {
+        self.delay_ms = delay_ms;
+        self
+    }
+
+    /// Returns the input pin.
+    pub const fn input(&self) -> Pin {
+        self.input
+    }
+
+    /// Returns the output pin.
+    pub const fn output(&self) -> Pin {
+        self.output
+    }
+
+    /// Returns `true` if the output is driven to the opposite level of the input.
+    pub const fn is_inverted(&self) -> bool {
+        self.inverted
+    }
+
+    /// Returns the forwarding delay.
+    pub const fn delay_ms(&self) -> u32 {
+        self.delay_ms
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    rule: MirrorRule,
+    /// Output level waiting for the delay, and when the input changed.
+    pending: Option<(bool, u32)>,
+}
+
+/// Reflects input pins onto output pins, for pass-through indicators and simple signal
+/// forwarding without application code.
+///
+/// Up to `N` rules each drive one output to the level of one input, optionally inverted
+/// and delayed; several rules may share an input. Feed the mirror the changes of an
+/// [`InputWatcher`](crate::InputWatcher) or [`InputMonitor`](crate::InputMonitor) with
+/// [`PinMirror::apply`], and call [`PinMirror::tick`] when [`PinMirror::wait_ms`] runs
+/// out so delayed changes are written without a new event. A
+/// [full resync](InputChanges::is_resync) reports every watched input, so the first one
+/// brings every output in line.
+///
+/// A delay is inertial: a change is forwarded once the input has kept its level for the
+/// delay, and a pulse shorter than the delay is not forwarded at all. Timestamps are a
+/// free-running millisecond counter and may wrap around.
+///
+/// The mirror neither claims nor configures its output pins; set them up as outputs first,
+/// for example with [`board!`](crate::board).
+///
+/// ```
+/// use tca6424::{MirrorRule, Pin, PinMirror};
+///
+/// let mut mirror: PinMirror<2> = PinMirror::new();
+/// mirror.mirror(Pin::P00, Pin::P10).unwrap();
+/// mirror
+///     .add(MirrorRule::new(Pin::P01, Pin::P11).with_inverted(true).with_delay_ms(50))
+///     .unwrap();
+/// assert_eq!(mirror.outputs(), tca6424::pins!(P10, P11));
+/// ```
+#[derive(Debug, Clone)]
+pub struct PinMirror<const N: usize> {
+    slots: Vec<Slot, N>,
+}
+
+impl<const N: usize> PinMirror<N> {
+    /// Creates a mirror without rules.
+    pub const fn new() -> Self {
+        Self { slots: Vec::new() }
+    }
+
+    /// Adds a rule driving `output` to the level of `input`, without inversion or delay.
+    ///
+    /// # Returns
+    ///
+    /// Returns the index of the rule, or `None` as [`PinMirror::add`] does.
+    pub fn mirror(&mut self, input: Pin, output: Pin) -> Option<usize> {
+        self.add(MirrorRule::new(input, output))
+    }
+
+    /// Adds `rule`.
+    ///
+    /// # Returns
+    ///
+    /// Returns the index of the rule, or `None` if `N` rules are already set, the rule
+    /// mirrors a pin onto itself or its output is already driven by another rule.
+    pub fn add(&mut self, rule: MirrorRule) -> Option<usize> {
+        if rule.input == rule.output || self.outputs() & (1 << rule.output as u8) != 0 {
+            return None;
+        }
+        let index = self.slots.len();
+        self.slots
+            .push(Slot {
+                rule,
+                pending: None,
+            })
+            .ok()?;
+        Some(index)
+    }
+
+    /// Returns the rules, in the order they were added.
+    pub fn rules(&self) -> impl Iterator<Item = MirrorRule> + '_ {
+        self.slots.iter().map(|slot| slot.rule)
+    }
+
+    /// Returns the mask of the mirrored inputs (bit `n` = pin index `n`).
+    pub fn inputs(&self) -> u32 {
+        self.rules()
+            .fold(0, |mask, rule| mask | 1 << rule.input as u8)
+    }
+
+    /// Returns the mask of the driven outputs.
+    pub fn outputs(&self) -> u32 {
+        self.rules()
+            .fold(0, |mask, rule| mask | 1 << rule.output as u8)
+    }
+
+    /// Processes the input changes reported at `now_ms` and returns the outputs to drive
+    /// high and low, as `(high, low)` masks: those of undelayed rules whose input changed,
+    /// and those whose delay ran out (see [`PinMirror::expire`]).
+    pub fn process_changes(&mut self, changes: &InputChanges, now_ms: u32) -> (u32, u32) {
+        for event in changes.iter() {
+            let high = event.state == PinState::High;
+            for slot in self
+                .slots
+                .iter_mut()
+                .filter(|slot| slot.rule.input == event.pin)
+            {
+                slot.pending = Some((high != slot.rule.inverted, now_ms));
+            }
+        }
+        self.expire(now_ms)
+    }
+
+    /// Ends the delays that ran out at `now_ms` and returns the outputs to drive high and
+    /// low, as `(high, low)` masks.
+    pub fn expire(&mut self, now_ms: u32) -> (u32, u32) {
+        let (mut high, mut low) = (0, 0);
+        for slot in self.slots.iter_mut() {
+            let Some((level, changed_ms)) = slot.pending else {
+                continue;
+            };
+            if now_ms.wrapping_sub(changed_ms) < slot.rule.delay_ms {
+                continue;
+            }
+            slot.pending = None;
+            let bit = 1 << slot.rule.output as u8;
+            if level {
+                high |= bit;
+            } else {
+                low |= bit;
+            }
+        }
+        (high, low)
+    }
+
+    /// Returns the number of milliseconds until the next delayed change is due, or `None` if
+    /// none is waiting. Useful to arm a timer instead of polling.
+    pub fn wait_ms(&self, now_ms: u32) -> Option<u32> {
+        self.slots
+            .iter()
+            .filter_map(|slot| {
+                let (_, changed_ms) = slot.pending?;
+                Some(
+                    slot.rule
+                        .delay_ms
+                        .saturating_sub(now_ms.wrapping_sub(changed_ms)),
+                )
+            })
+            .min()
+    }
+}
+
+impl<const N: usize> Default for PinMirror<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "PinMirror",),
+    async(feature = "async", keep_self)
+)]
+impl<const N: usize> PinMirror<N> {
+    /// Processes the input changes reported at `now_ms` and drives the outputs that follow
+    /// them, merging the levels into the cached Output registers when they are known.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(true)` if outputs were driven, `Ok(false)` if none had to be, or an
+    /// `Error` if an I2C bus operation fails.
+    pub async fn apply<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        changes: &InputChanges,
+        now_ms: u32,
+    ) -> Result<bool, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let (high, low) = self.process_changes(changes, now_ms);
+        Self::drive(tca, high, low).await
+    }
+
+    /// Drives the outputs whose delay ran out at `now_ms`.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(true)` if outputs were driven, `Ok(false)` if none were due, or an `Error`
+    /// if an I2C bus operation fails.
+    pub async fn tick<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        now_ms: u32,
+    ) -> Result<bool, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let (high, low) = self.expire(now_ms);
+        Self::drive(tca, high, low).await
+    }
+
+    async fn drive<I2C, D>(
+        tca: &mut Tca6424<I2C, D>,
+        high: u32,
+        low: u32,
+    ) -> Result<bool, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        if high | low == 0 {
+            return Ok(false);
+        }
+        tca.drive_pins(high, low).await?;
+        Ok(true)
+    }
+}
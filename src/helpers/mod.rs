@@ -0,0 +1,117 @@
+//! Higher-level helpers built on top of the driver.
+//!
+//! Helpers are split into a pure state machine, fed with pin levels and a millisecond
+//! timestamp, and a thin `poll()` that performs the bus transfers. The state machines can
+//! therefore be unit-tested, or driven from an input cache, without any I2C traffic.
+
+use crate::errors::Error;
+use crate::registers::Register;
+use crate::{BusAccess, DelayNs, Port, RegisterKind, Tca6424};
+
+mod board;
+mod clocked;
+mod debounce;
+mod encoder;
+mod fan_bank;
+mod gesture;
+mod heartbeat;
+mod input_watchdog;
+mod keypad;
+mod lcd;
+mod lit_button;
+mod mirror;
+#[cfg(feature = "auto-increment")]
+mod output_frames;
+mod parallel_bus;
+mod pulse_stretch;
+mod relay_bank;
+mod scheduler;
+mod shift_register;
+mod single_wire;
+mod soft_pwm;
+mod stepper;
+mod straps;
+mod waveform;
+
+#[doc(hidden)]
+pub use board::__private;
+pub use board::{BoardConfig, InitReport, InputLine, OutputLine, RegisterWrite};
+pub use debounce::{
+    Debounce, Debouncer, IntegratorDebouncer, LockoutDebouncer, StableSamplesDebouncer,
+};
+pub use encoder::{EncoderConfig, EncoderEvent, RotaryEncoder};
+pub use fan_bank::{FanBank, FanBankConfig, FanEvent, FanPins, FanStatus};
+pub use gesture::{ButtonGesture, GestureConfig, GestureDetector};
+pub use heartbeat::Heartbeat;
+pub use input_watchdog::{InputWatchdog, WatchdogEvent};
+pub use keypad::{KeyEvent, Keypad, KeypadEvent};
+pub use lcd::{Hd44780, LcdPins};
+pub use lit_button::{ButtonEvent, LedFeedback, LitButton, LitButtonConfig};
+pub use mirror::{MirrorRule, PinMirror};
+#[cfg(feature = "auto-increment")]
+pub use output_frames::OutputFrames;
+pub use parallel_bus::{ByteOrder, ParallelBus};
+pub use pulse_stretch::PulseStretcher;
+pub use relay_bank::{DwellPolicy, RelayBank, RelayBankConfig, RelaySwitch};
+pub use scheduler::PinScheduler;
+pub use shift_register::ShiftRegister;
+pub use single_wire::SingleWire;
+pub use soft_pwm::SoftPwm;
+pub use stepper::{StepRamp, Stepper};
+pub use straps::{BitOrder, StrapDecoder, ValueEncoding};
+pub use waveform::{PlayMode, StepControl, WaveformPlayer, WaveformStep};
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca6424",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> Tca6424<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Drives the pins of `on` high and those of `off` low (24-bit masks), merging them into
+    /// the cached Output registers when known and falling back to a read-modify-write
+    /// otherwise. Ports whose value does not change are not written.
+    pub(crate) async fn drive_pins(&mut self, on: u32, off: u32) -> Result<(), Error<I2C::Error>> {
+        self.merge_family(RegisterKind::Output, on, off).await
+    }
+
+    /// Makes the pins of `outputs` outputs and those of `inputs` inputs (24-bit masks), with
+    /// the same cache use as [`Tca6424::drive_pins`].
+    pub(crate) async fn drive_directions(
+        &mut self,
+        outputs: u32,
+        inputs: u32,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.merge_family(RegisterKind::Configuration, inputs, outputs)
+            .await
+    }
+
+    /// Sets the bits of `set` and clears those of `clear` in every port of a register family.
+    async fn merge_family(
+        &mut self,
+        kind: RegisterKind,
+        set: u32,
+        clear: u32,
+    ) -> Result<(), Error<I2C::Error>> {
+        for port in Port::ALL {
+            let shift = port as u8 * 8;
+            let (set, clear) = ((set >> shift) as u8, (clear >> shift) as u8);
+            if set | clear == 0 {
+                continue;
+            }
+            match self.cache.get(kind, port) {
+                Some(cached) => {
+                    let value = cached & !clear | set;
+                    if value != cached {
+                        self.write_register(Register::of(kind, port), value).await?;
+                    }
+                }
+                None => self.write_masked(kind, port, set | clear, set).await?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+//! Double-buffered output frames for blink-free display updates.
+
+use crate::errors::Error;
+use crate::{BusAccess, DelayNs, Port, Tca6424};
+
+/// Two output frames: the visible one on the device and the next one being drawn.
+///
+/// Scanning displays (multiplexed LED matrices, 7-segment digits) change several pins per
+/// step, on more than one port. Written pin by pin or port by port, the outputs pass through
+/// mixed states that show up as ghosting. Here the application draws the next frame with
+/// [`OutputFrames::stage`] while the device keeps showing the current one, then
+/// [`OutputFrames::swap`] sends all three Output Port registers in one auto-increment write
+/// and rotates the buffers.
+///
+/// The device latches each register as its byte is acknowledged, so between the bytes of
+/// that write the ports can differ for a few bus clocks; no other transfer can come in
+/// between, and after a successful swap the outputs always match one complete frame.
+///
+/// The frames own all 24 output latches (bit `n` = pin index `n`); bits of pins configured
+/// as inputs only set their latch.
+///
+/// ```ignore
+/// let mut frames = OutputFrames::new(0);
+/// for (step, row) in ROWS.iter().enumerate() {
+///     frames.stage(column_bits(step) | row);
+///     frames.swap(&mut tca).await?;
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct OutputFrames {
+    /// The frame last written to the device.
+    front: u32,
+    /// The frame being drawn.
+    back: u32,
+}
+
+impl OutputFrames {
+    /// Creates the buffers with both frames set to `frame`.
+    ///
+    /// No bus transfer is made; `frame` should match the Output Port registers, for example
+    /// the levels written while configuring the pins as outputs.
+    pub const fn new(frame: u32) -> Self {
+        let frame = frame & 0x00FF_FFFF;
+        Self {
+            front: frame,
+            back: frame,
+        }
+    }
+
+    /// Draws `frame` into the back buffer; the device keeps showing the front frame.
+    pub fn stage(&mut self, frame: u32) -> &mut Self {
+        self.back = frame & 0x00FF_FFFF;
+        self
+    }
+
+    /// Returns the frame drawn into the back buffer, to be shown by the next swap.
+    pub const fn staged(&self) -> u32 {
+        self.back
+    }
+
+    /// Returns the frame last written to the device.
+    pub const fn visible(&self) -> u32 {
+        self.front
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "OutputFrames",),
+    async(feature = "async", keep_self)
+)]
+impl OutputFrames {
+    /// Writes the back frame to the three Output Port registers in one auto-increment write,
+    /// then rotates the buffers: the back frame becomes visible and the back buffer holds
+    /// the frame that was visible before.
+    ///
+    /// Applications that draw only the pins that change should [`OutputFrames::stage`] from
+    /// [`OutputFrames::visible`] after the swap.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if the I2C bus operation fails. The buffers
+    /// are not rotated then; the device may hold part of the new frame, and calling `swap`
+    /// again sends the whole frame.
+    pub async fn swap<I2C, D>(&mut self, tca: &mut Tca6424<I2C, D>) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let bytes = Port::ALL.map(|port| port.byte_of(self.back));
+        tca.set_ports_output_ai(Port::Port0, &bytes).await?;
+        core::mem::swap(&mut self.front, &mut self.back);
+        Ok(())
+    }
+}
@@ -0,0 +1,341 @@
+//! Byte-wide parallel data bus on whole expander ports.
+
+use crate::errors::{ConfigError, Error};
+use crate::{
+    BitOrder, BusAccess, DelayNs, IoExpander, Pin, PinClaim, PinDirection, PinState, Port, Tca6424,
+};
+
+/// The order in which the bytes of a [`ParallelBus`] value map onto its ports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ByteOrder {
+    /// The least significant byte goes to the first port.
+    #[default]
+    LittleEndian,
+    /// The most significant byte goes to the first port.
+    BigEndian,
+}
+
+/// A parallel data bus of one to three whole ports, written and read as one value.
+///
+/// Byte `i` of a value is presented on port `ports[i]`, bit `n` of each byte on pin `Pxn`.
+/// When the PCB routes the data lines differently, fix it in configuration rather than in
+/// application code: [`ParallelBus::with_bit_order`] with [`BitOrder::MsbFirst`] puts bit 7
+/// of each byte on `Px0`, and [`ParallelBus::with_byte_order`] with [`ByteOrder::BigEndian`]
+/// puts the most significant byte on the first port.
+///
+/// ```
+/// use tca6424::{BitOrder, ByteOrder, ParallelBus, Port};
+///
+/// let mut i2c = embedded_hal_mock::eh1::i2c::Mock::new(&[]);
+/// let mut tca = tca6424::Tca6424::new(&mut i2c, 0x22).unwrap();
+/// let bus = ParallelBus::new(&mut tca, [Port::Port0, Port::Port1])
+///     .unwrap()
+///     .with_bit_order(BitOrder::MsbFirst)
+///     .with_byte_order(ByteOrder::BigEndian);
+/// // 0x1280: 0x12 on Port 0 and 0x80 on Port 1, both bit-reversed
+/// assert_eq!(bus.encode(0x1280), 0x01_48);
+/// assert_eq!(bus.decode(0x01_48), 0x1280);
+/// # bus.release(&mut tca);
+/// # i2c.done();
+/// ```
+///
+/// Every pin of the ports is claimed from the driver on construction.
+///
+/// # External transceivers
+///
+/// When the bus leaves the board through a bidirectional level shifter or transceiver (such
+/// as a 74LVC245), register its direction-control pin with
+/// [`ParallelBus::with_direction_pin`]. [`ParallelBus::set_direction`] then switches it in
+/// the order that avoids contention on the expander side: before the ports become outputs,
+/// the transceiver is turned to drive away from the expander; after they become inputs, it
+/// is turned to drive towards it. An optional settle time covers the transceiver's turnaround.
+#[derive(Debug)]
+pub struct ParallelBus<const N: usize> {
+    ports: [Port; N],
+    bit_order: BitOrder,
+    byte_order: ByteOrder,
+    direction_pin: Option<DirectionPin>,
+}
+
+/// Direction-control pin of an external transceiver.
+#[derive(Debug)]
+struct DirectionPin {
+    claim: PinClaim,
+    /// Level making the transceiver pass data from the expander outwards.
+    outward: PinState,
+    settle_us: u32,
+}
+
+impl<const N: usize> ParallelBus<N> {
+    /// Creates a bus on `ports`, claiming every pin of them from `tca`. Bytes are least
+    /// significant bit first and little-endian.
+    ///
+    /// No bus transfer is made; call [`ParallelBus::set_direction`] to configure the pins.
+    ///
+    /// `N` must be 1 to 3; other sizes fail to compile.
+    ///
+    /// # Returns
+    ///
+    /// Returns the helper, or `ConfigError::PinAlreadyClaimed` if a pin is already owned or a port
+    /// is used twice.
+    pub fn new<I2C: embedded_hal::i2c::ErrorType, D>(
+        tca: &mut Tca6424<I2C, D>,
+        ports: [Port; N],
+    ) -> Result<Self, ConfigError> {
+        const { assert!(N >= 1 && N <= 3, "a parallel bus has one to three ports") };
+        let mut mask = 0u32;
+        for port in ports {
+            if mask & port_mask(port) != 0 {
+                return Err(ConfigError::PinAlreadyClaimed(first_pin(port)));
+            }
+            mask |= port_mask(port);
+        }
+        tca.claim_bits(mask)?;
+        Ok(Self {
+            ports,
+            bit_order: BitOrder::LsbFirst,
+            byte_order: ByteOrder::LittleEndian,
+            direction_pin: None,
+        })
+    }
+
+    /// Coordinates `pin`, the direction control of an external transceiver, with
+    /// [`ParallelBus::set_direction`], claiming it from `tca`.
+    ///
+    /// `outward` is the level at which the transceiver passes data from the expander to the
+    /// far side. After switching the pin, [`ParallelBus::set_direction`] waits `settle_us`
+    /// microseconds with the driver's delay source.
+    ///
+    /// # Returns
+    ///
+    /// Returns the helper, or `ConfigError::PinAlreadyClaimed` if `pin` is already owned, in which
+    /// case the pins of the bus are released as well.
+    pub fn with_direction_pin<I2C: embedded_hal::i2c::ErrorType, D>(
+        mut self,
+        tca: &mut Tca6424<I2C, D>,
+        pin: Pin,
+        outward: PinState,
+        settle_us: u32,
+    ) -> Result<Self, ConfigError> {
+        let claim = match tca.claim_pin(pin) {
+            Ok(claim) => claim,
+            Err(error) => {
+                self.release(tca);
+                return Err(error);
+            }
+        };
+        if let Some(previous) = self.direction_pin.take() {
+            let _ = tca.release_pin(previous.claim);
+        }
+        self.direction_pin = Some(DirectionPin {
+            claim,
+            outward,
+            settle_us,
+        });
+        Ok(self)
+    }
+
+    /// Returns the direction-control pin of the external transceiver, if any.
+    pub fn direction_pin(&self) -> Option<Pin> {
+        self.direction_pin
+            .as_ref()
+            .map(|control| control.claim.pin())
+    }
+
+    /// Maps the bits of each byte in `order`: [`BitOrder::MsbFirst`] puts bit 7 on `Px0`.
+    pub fn with_bit_order(mut self, order: BitOrder) -> Self {
+        self.bit_order = order;
+        self
+    }
+
+    /// Maps the bytes of a value onto the ports in `order`.
+    pub fn with_byte_order(mut self, order: ByteOrder) -> Self {
+        self.byte_order = order;
+        self
+    }
+
+    /// Returns the bit order of each byte.
+    pub fn bit_order(&self) -> BitOrder {
+        self.bit_order
+    }
+
+    /// Returns the order of the bytes across the ports.
+    pub fn byte_order(&self) -> ByteOrder {
+        self.byte_order
+    }
+
+    /// Returns the ports of the bus.
+    pub fn ports(&self) -> &[Port; N] {
+        &self.ports
+    }
+
+    /// Returns the pins of the bus as a 24-bit mask.
+    pub fn mask(&self) -> u32 {
+        self.ports
+            .iter()
+            .fold(0, |mask, &port| mask | port_mask(port))
+    }
+
+    /// Returns the pin levels (bit `n` = pin index `n`) presenting `value` on the bus. Bits of
+    /// `value` beyond the width of the bus are ignored.
+    pub fn encode(&self, value: u32) -> u32 {
+        let mut levels = 0;
+        for (lane, &port) in self.ports.iter().enumerate() {
+            let byte = self.lane_byte((value >> (self.byte_index(lane) * 8)) as u8);
+            levels |= (byte as u32) << (port as u8 * 8);
+        }
+        levels
+    }
+
+    /// Returns the value presented by the pin levels `levels` (bit `n` = pin index `n`).
+    pub fn decode(&self, levels: u32) -> u32 {
+        let mut value = 0;
+        for (lane, &port) in self.ports.iter().enumerate() {
+            let byte = self.lane_byte((levels >> (port as u8 * 8)) as u8);
+            value |= (byte as u32) << (self.byte_index(lane) * 8);
+        }
+        value
+    }
+
+    /// Returns the pins, including the direction-control pin, to the pool of `tca`.
+    pub fn release<I2C: embedded_hal::i2c::ErrorType, D>(self, tca: &mut Tca6424<I2C, D>) {
+        tca.release_mask(self.mask());
+        if let Some(control) = self.direction_pin {
+            let _ = tca.release_pin(control.claim);
+        }
+    }
+
+    /// Returns the byte of the value carried by the `lane`-th port.
+    fn byte_index(&self, lane: usize) -> usize {
+        match self.byte_order {
+            ByteOrder::LittleEndian => lane,
+            ByteOrder::BigEndian => N - 1 - lane,
+        }
+    }
+
+    /// Converts between a value byte and a port byte; the mapping is its own inverse.
+    fn lane_byte(&self, byte: u8) -> u8 {
+        match self.bit_order {
+            BitOrder::LsbFirst => byte,
+            BitOrder::MsbFirst => byte.reverse_bits(),
+        }
+    }
+}
+
+fn port_mask(port: Port) -> u32 {
+    0xFF << (port as u8 * 8)
+}
+
+fn first_pin(port: Port) -> Pin {
+    match port {
+        Port::Port0 => Pin::P00,
+        Port::Port1 => Pin::P10,
+        Port::Port2 => Pin::P20,
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "ParallelBus",),
+    async(feature = "async", keep_self)
+)]
+impl<const N: usize> ParallelBus<N> {
+    /// Makes every pin of the bus an input or an output.
+    ///
+    /// With a [direction pin](ParallelBus::with_direction_pin), the transceiver is switched
+    /// outwards, and the settle time waited, before the ports become outputs; when they become
+    /// inputs, it is switched inwards afterwards. The direction pin is made an output on every
+    /// call, so no separate initialization is needed.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn set_direction<I2C, D>(
+        &self,
+        tca: &mut Tca6424<I2C, D>,
+        direction: PinDirection,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        if direction == PinDirection::Output {
+            self.switch_transceiver(tca, true).await?;
+        }
+        tca.set_direction(self.mask(), direction).await?;
+        if direction == PinDirection::Input {
+            self.switch_transceiver(tca, false).await?;
+        }
+        Ok(())
+    }
+
+    /// Drives the direction-control pin, if any, outwards or inwards and waits the settle time.
+    async fn switch_transceiver<I2C, D>(
+        &self,
+        tca: &mut Tca6424<I2C, D>,
+        outward: bool,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let Some(control) = &self.direction_pin else {
+            return Ok(());
+        };
+        let mask = 1 << control.claim.pin() as u8;
+        if (control.outward == PinState::High) == outward {
+            tca.drive_pins(mask, 0).await?;
+        } else {
+            tca.drive_pins(0, mask).await?;
+        }
+        tca.set_direction(mask, PinDirection::Output).await?;
+        if control.settle_us > 0 {
+            tca.delay().delay_us(control.settle_us).await;
+        }
+        Ok(())
+    }
+
+    /// Presents `value` on the bus.
+    ///
+    /// Ports whose cached Output register already holds the byte are not written.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn write<I2C, D>(
+        &self,
+        tca: &mut Tca6424<I2C, D>,
+        value: u32,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let levels = self.encode(value);
+        tca.drive_pins(levels, self.mask() & !levels).await
+    }
+
+    /// Reads the value on the bus from the Input Port registers.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the value, or an `Error` if an I2C bus operation fails.
+    pub async fn read<I2C, D>(&self, tca: &mut Tca6424<I2C, D>) -> Result<u32, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let levels = tca.read(self.mask()).await?;
+        Ok(self.decode(levels))
+    }
+}
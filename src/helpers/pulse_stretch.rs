@@ -0,0 +1,145 @@
+//! Minimum reported width for short input pulses.
+
+use crate::{InputChanges, Pin, PinState, pins_from_mask};
+
+/// Reports input levels that last at least a minimum width, so a pulse shorter than the
+/// consumer's loop still shows up as a level it can act on.
+///
+/// An interrupt handler reading the inputs on every INT edge (see
+/// [`Tca6424::service_interrupt`](crate::Tca6424::service_interrupt)) can see a 1 ms pulse
+/// as two images a millisecond apart. Fed to the stretcher, the pulse becomes a high level
+/// of at least `min_width_ms`: every logical level change of a watched pin is held for that
+/// long before the next one is reported, whichever way it goes. Later raw changes during
+/// the hold are not queued; when the hold ends the logical level takes the latest raw
+/// level, so a pulse that ended during the hold ends there, and a glitch that came back
+/// within it adds nothing.
+///
+/// Feed it 24-bit input images with [`PulseStretcher::process`], or the changes of an
+/// [`InputWatcher`](crate::InputWatcher) with [`PulseStretcher::process_changes`], and call
+/// [`PulseStretcher::expire`] when [`PulseStretcher::wait_ms`] runs out to end holds
+/// without a new image. Combined with a
+/// [latching watcher](crate::InputWatcher::with_latching), a slow consumer learns from the
+/// latched flags which pins pulsed and reads their stretched levels here. Timestamps are a
+/// free-running millisecond counter and may wrap around.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PulseStretcher {
+    pins: u32,
+    min_width_ms: u32,
+    raw: u32,
+    logical: u32,
+    held: u32,
+    changed_ms: [u32; 24],
+    started: bool,
+}
+
+impl PulseStretcher {
+    /// Creates a stretcher for the pins of `pins` (bit `n` = pin index `n`), holding every
+    /// reported level for at least `min_width_ms`.
+    pub const fn new(pins: u32, min_width_ms: u32) -> Self {
+        Self {
+            pins: pins & 0x00FF_FFFF,
+            min_width_ms,
+            raw: 0,
+            logical: 0,
+            held: 0,
+            changed_ms: [0; 24],
+            started: false,
+        }
+    }
+
+    /// Returns the watched pins.
+    pub const fn pins(&self) -> u32 {
+        self.pins
+    }
+
+    /// Returns the minimum width of a reported level.
+    pub const fn min_width_ms(&self) -> u32 {
+        self.min_width_ms
+    }
+
+    /// Processes a 24-bit input image read at `now_ms` and returns the watched pins whose
+    /// reported level changed.
+    ///
+    /// The first image sets the levels and reports nothing.
+    pub fn process(&mut self, inputs: u32, now_ms: u32) -> u32 {
+        self.raw = inputs & self.pins;
+        if !self.started {
+            self.started = true;
+            self.logical = self.raw;
+            return 0;
+        }
+        self.expire(now_ms)
+    }
+
+    /// Processes the changes reported by an [`InputWatcher`](crate::InputWatcher) at `now_ms`
+    /// and returns the watched pins whose reported level changed.
+    ///
+    /// Pins without an event keep their level, low until one is reported. A
+    /// [full resync](InputChanges::is_resync) takes over its levels at once and ends every
+    /// hold, so the first wait of an [`InputMonitor`](crate::InputMonitor) sets the levels.
+    pub fn process_changes(&mut self, changes: &InputChanges, now_ms: u32) -> u32 {
+        for event in changes.iter() {
+            let bit = 1 << event.pin as u8;
+            if event.state == PinState::High {
+                self.raw |= bit & self.pins;
+            } else {
+                self.raw &= !bit;
+            }
+        }
+        self.started = true;
+        if changes.is_resync() {
+            self.held = 0;
+            let changed = self.logical ^ self.raw;
+            self.logical = self.raw;
+            return changed;
+        }
+        self.expire(now_ms)
+    }
+
+    /// Ends the holds that lasted `min_width_ms` at `now_ms`, reports the latest raw level of
+    /// every watched pin that is not held, and returns the pins whose reported level
+    /// changed.
+    pub fn expire(&mut self, now_ms: u32) -> u32 {
+        for pin in pins_from_mask(self.held) {
+            if now_ms.wrapping_sub(*pin.entry(&self.changed_ms)) >= self.min_width_ms {
+                self.held &= !(1 << pin as u8);
+            }
+        }
+        let changed = (self.logical ^ self.raw) & !self.held;
+        self.logical ^= changed;
+        if self.min_width_ms > 0 {
+            self.held |= changed;
+        }
+        for pin in pins_from_mask(changed) {
+            *pin.entry_mut(&mut self.changed_ms) = now_ms;
+        }
+        changed
+    }
+
+    /// Returns the number of milliseconds until a held pin whose raw level differs can be
+    /// reported, or `None` if no change is waiting. Useful to arm a timer instead of polling.
+    pub fn wait_ms(&self, now_ms: u32) -> Option<u32> {
+        pins_from_mask((self.logical ^ self.raw) & self.held)
+            .map(|pin| {
+                self.min_width_ms
+                    .saturating_sub(now_ms.wrapping_sub(*pin.entry(&self.changed_ms)))
+            })
+            .min()
+    }
+
+    /// Returns the reported levels of the watched pins.
+    pub const fn levels(&self) -> u32 {
+        self.logical
+    }
+
+    /// Returns the reported level of `pin`.
+    pub const fn is_high(&self, pin: Pin) -> bool {
+        self.logical & (1 << pin as u8) != 0
+    }
+
+    /// Returns the pins whose reported level is still within its minimum width.
+    pub const fn held_mask(&self) -> u32 {
+        self.held
+    }
+}
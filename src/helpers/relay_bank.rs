@@ -0,0 +1,292 @@
+//! Bank of relays or contactors with operation counters and minimum dwell times.
+
+use crate::errors::{ConfigError, Error};
+use crate::{BusAccess, DelayNs, IoExpander, Pin, PinDirection, Tca6424};
+
+/// What a [`RelayBank`] does with a switch request that comes before the minimum dwell time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DwellPolicy {
+    /// Remember the request and carry it out from [`RelayBank::poll`] once the dwell time
+    /// has passed. A later request for the current state cancels it.
+    #[default]
+    Defer,
+    /// Refuse the request.
+    Reject,
+}
+
+/// Configuration of a [`RelayBank`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RelayBankConfig {
+    /// `true` if a relay is energised by driving its pin low.
+    pub active_low: bool,
+    /// Minimum time a relay stays on before it may switch off.
+    pub min_on_ms: u32,
+    /// Minimum time a relay stays off before it may switch on again.
+    pub min_off_ms: u32,
+    /// Handling of requests within the dwell time.
+    pub policy: DwellPolicy,
+}
+
+/// The outcome of [`RelayBank::set_relay`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RelaySwitch {
+    /// The relay was switched.
+    Switched,
+    /// The relay already was in the requested state.
+    Unchanged,
+    /// The dwell time has not passed; the request is carried out by [`RelayBank::poll`].
+    Deferred,
+    /// The dwell time has not passed and the request was refused.
+    Rejected,
+}
+
+/// Up to 24 relays or contactors, each switched by an output pin.
+///
+/// Every relay counts its operations (off-to-on and on-to-off switches) for wear estimates;
+/// restore persisted counts with [`RelayBank::set_operations`]. The bank also enforces a
+/// minimum on and off time per relay, so a control loop toggling too fast cannot chatter a
+/// contactor: early requests are deferred or rejected according to the [`DwellPolicy`].
+/// Call [`RelayBank::poll`] periodically to carry out deferred requests.
+///
+/// The pins are claimed from the driver on construction. After [`RelayBank::init`] every
+/// relay is off with no dwell history, so the first switch of each is always allowed.
+#[derive(Debug)]
+pub struct RelayBank<const N: usize> {
+    pins: [Pin; N],
+    config: RelayBankConfig,
+    on: u32,
+    pending: u32,
+    changed_ms: [Option<u32>; N],
+    operations: [u32; N],
+}
+
+impl<const N: usize> RelayBank<N> {
+    /// Creates a relay bank on `pins`, claiming them from `tca`.
+    ///
+    /// No bus transfer is made; call [`RelayBank::init`] to configure the pins.
+    ///
+    /// # Returns
+    ///
+    /// Returns the helper, or `ConfigError::PinAlreadyClaimed` if a pin is already owned or used
+    /// twice.
+    pub fn new<I2C: embedded_hal::i2c::ErrorType, D>(
+        tca: &mut Tca6424<I2C, D>,
+        pins: [Pin; N],
+        config: RelayBankConfig,
+    ) -> Result<Self, ConfigError> {
+        let mut mask = 0u32;
+        for pin in pins {
+            if mask & (1 << pin as u8) != 0 {
+                return Err(ConfigError::PinAlreadyClaimed(pin));
+            }
+            mask |= 1 << pin as u8;
+        }
+        tca.claim_bits(mask)?;
+        Ok(Self {
+            pins,
+            config,
+            on: 0,
+            pending: 0,
+            changed_ms: [None; N],
+            operations: [0; N],
+        })
+    }
+
+    /// Returns the configuration.
+    pub fn config(&self) -> RelayBankConfig {
+        self.config
+    }
+
+    /// Returns `true` if relay `index` is on.
+    pub fn is_on(&self, index: usize) -> bool {
+        index < N && self.on & (1 << index) != 0
+    }
+
+    /// Returns the relays that are on (bit `n` = relay `n`).
+    pub fn on_mask(&self) -> u32 {
+        self.on
+    }
+
+    /// Returns the relays with a deferred request (bit `n` = relay `n`).
+    pub fn pending_mask(&self) -> u32 {
+        self.pending
+    }
+
+    /// Returns the number of operations of relay `index`, or 0 if there is no such relay.
+    pub fn operations(&self, index: usize) -> u32 {
+        self.operations.get(index).copied().unwrap_or(0)
+    }
+
+    /// Sets the operation count of relay `index`, for example from non-volatile storage.
+    /// Ignored if there is no such relay.
+    pub fn set_operations(&mut self, index: usize, count: u32) {
+        if let Some(operations) = self.operations.get_mut(index) {
+            *operations = count;
+        }
+    }
+
+    /// Returns `true` if relay `index` has been in its current state for its minimum dwell
+    /// time at `now_ms`.
+    pub fn can_switch(&self, index: usize, now_ms: u32) -> bool {
+        let dwell_ms = if self.is_on(index) {
+            self.config.min_on_ms
+        } else {
+            self.config.min_off_ms
+        };
+        self.changed_ms
+            .get(index)
+            .copied()
+            .flatten()
+            .is_none_or(|changed| now_ms.wrapping_sub(changed) >= dwell_ms)
+    }
+
+    /// Returns the pins of every relay.
+    pub fn pins(&self) -> &[Pin; N] {
+        &self.pins
+    }
+
+    /// Returns every pin to the pool of `tca`.
+    pub fn release<I2C: embedded_hal::i2c::ErrorType, D>(self, tca: &mut Tca6424<I2C, D>) {
+        tca.release_mask(self.pin_mask(u32::MAX));
+    }
+
+    /// Returns the pins of the relays in `relays` (bit `n` = relay `n`).
+    fn pin_mask(&self, relays: u32) -> u32 {
+        self.pins
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| relays & (1 << index) != 0)
+            .fold(0, |mask, (_, &pin)| mask | 1 << pin as u8)
+    }
+
+    /// Returns `(mask_high, mask_low)` driving the relays in `relays` to the `on` state.
+    fn levels(&self, relays: u32, on: u32) -> (u32, u32) {
+        let energised = self.pin_mask(relays & on);
+        let released = self.pin_mask(relays & !on);
+        if self.config.active_low {
+            (released, energised)
+        } else {
+            (energised, released)
+        }
+    }
+
+    /// Records the relays in `relays` as toggled at `now_ms`.
+    fn record_toggle(&mut self, relays: u32, now_ms: u32) {
+        let relays_state = self.changed_ms.iter_mut().zip(self.operations.iter_mut());
+        for (index, (changed_ms, operations)) in relays_state.enumerate() {
+            if relays & (1 << index) != 0 {
+                self.on ^= 1 << index;
+                *changed_ms = Some(now_ms);
+                *operations = operations.wrapping_add(1);
+            }
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "RelayBank",),
+    async(feature = "async", keep_self)
+)]
+impl<const N: usize> RelayBank<N> {
+    /// Switches every relay off, then configures the pins as outputs. Forgets deferred
+    /// requests and the dwell history; operation counts are kept.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn init<I2C, D>(&mut self, tca: &mut Tca6424<I2C, D>) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let (high, low) = self.levels(u32::MAX, 0);
+        tca.drive_pins(high, low).await?;
+        self.on = 0;
+        self.pending = 0;
+        self.changed_ms = [None; N];
+        tca.set_direction(high | low, PinDirection::Output).await
+    }
+
+    /// Requests relay `index` on or off at `now_ms`, honouring the minimum dwell time.
+    ///
+    /// A deferred request for the relay is replaced.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns what happened, `Error::InvalidRegisterOrPin` if there is no relay `index`, or
+    /// an `Error` if an I2C bus operation fails.
+    pub async fn set_relay<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        index: usize,
+        on: bool,
+        now_ms: u32,
+    ) -> Result<RelaySwitch, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        if index >= N {
+            return Err(Error::InvalidRegisterOrPin);
+        }
+        let bit = 1 << index;
+        self.pending &= !bit;
+        if self.is_on(index) == on {
+            return Ok(RelaySwitch::Unchanged);
+        }
+        if !self.can_switch(index, now_ms) {
+            return Ok(match self.config.policy {
+                DwellPolicy::Defer => {
+                    self.pending |= bit;
+                    RelaySwitch::Deferred
+                }
+                DwellPolicy::Reject => RelaySwitch::Rejected,
+            });
+        }
+        let (high, low) = self.levels(bit, !self.on);
+        tca.drive_pins(high, low).await?;
+        self.record_toggle(bit, now_ms);
+        Ok(RelaySwitch::Switched)
+    }
+
+    /// Carries out the deferred requests whose dwell time has passed at `now_ms`, all in one
+    /// write per port.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the relays switched (bit `n` = relay `n`), or an `Error` if an I2C bus
+    /// operation fails (the requests stay pending).
+    pub async fn poll<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        now_ms: u32,
+    ) -> Result<u32, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let due = (0..N)
+            .filter(|&index| self.pending & (1 << index) != 0 && self.can_switch(index, now_ms))
+            .fold(0u32, |mask, index| mask | 1 << index);
+        if due == 0 {
+            return Ok(0);
+        }
+        let (high, low) = self.levels(due, !self.on);
+        tca.drive_pins(high, low).await?;
+        self.pending &= !due;
+        self.record_toggle(due, now_ms);
+        Ok(due)
+    }
+}
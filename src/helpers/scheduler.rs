@@ -0,0 +1,218 @@
+//! One-shot output changes scheduled for later, such as releasing a reset line.
+
+use crate::errors::Error;
+use crate::{BusAccess, DelayNs, Pin, PinState, Port, Tca6424, TimeSource, pins_from_mask};
+
+/// Drives output pins to a level once a delay has passed, for delayed de-assertions such as
+/// releasing a reset line 10 ms after power-up, without the application owning a timer per
+/// pin.
+///
+/// Holds at most one pending change per pin: scheduling a pin again replaces its change.
+/// Call [`PinScheduler::run`] from a periodic tick, or when [`PinScheduler::wait_ms`] runs
+/// out, to write the changes that are due; start-up code with nothing else to do can wait
+/// for all of them with [`PinScheduler::finish`]. Timestamps are a free-running millisecond
+/// counter and may wrap around; delays up to `u32::MAX / 2` are supported.
+///
+/// ```
+/// use tca6424::{Pin, PinScheduler, PinState};
+///
+/// let mut scheduler = PinScheduler::new();
+/// scheduler.set_pin_after(Pin::P00, PinState::High, 10, 0);
+/// assert_eq!(scheduler.wait_ms(4), Some(6));
+/// assert_eq!(scheduler.due(10), tca6424::pins!(P00));
+/// ```
+#[derive(Debug, Clone)]
+pub struct PinScheduler {
+    pending: u32,
+    levels: u32,
+    /// When each pending change was scheduled, and its delay.
+    timers: [(u32, u32); 24],
+}
+
+impl PinScheduler {
+    /// Creates a scheduler with no pending change.
+    pub const fn new() -> Self {
+        Self {
+            pending: 0,
+            levels: 0,
+            timers: [(0, 0); 24],
+        }
+    }
+
+    /// Schedules `pin` to be driven to `state` once `delay_ms` have passed since `now_ms`,
+    /// replacing a change already scheduled for it. No transfer is made.
+    pub fn set_pin_after(&mut self, pin: Pin, state: PinState, delay_ms: u32, now_ms: u32) {
+        let bit = 1 << pin as u8;
+        self.pending |= bit;
+        match state {
+            PinState::High => self.levels |= bit,
+            PinState::Low => self.levels &= !bit,
+        }
+        *pin.entry_mut(&mut self.timers) = (now_ms, delay_ms);
+    }
+
+    /// [`PinScheduler::set_pin_after`] at the time of `clock`.
+    pub fn set_pin_after_from<T: TimeSource + ?Sized>(
+        &mut self,
+        pin: Pin,
+        state: PinState,
+        delay_ms: u32,
+        clock: &T,
+    ) {
+        self.set_pin_after(pin, state, delay_ms, clock.now_ms());
+    }
+
+    /// Drops the change scheduled for `pin`. Returns `false` if none was.
+    pub fn cancel(&mut self, pin: Pin) -> bool {
+        let bit = 1 << pin as u8;
+        let scheduled = self.pending & bit != 0;
+        self.pending &= !bit;
+        scheduled
+    }
+
+    /// Returns the pins with a scheduled change (bit `n` = pin index `n`).
+    pub fn scheduled_mask(&self) -> u32 {
+        self.pending
+    }
+
+    /// Returns the pins whose scheduled change is due at `now_ms`.
+    pub fn due(&self, now_ms: u32) -> u32 {
+        pins_from_mask(self.pending)
+            .filter(|&pin| self.remaining_ms(pin, now_ms) == 0)
+            .fold(0, |mask, pin| mask | 1 << pin as u8)
+    }
+
+    /// Returns the number of milliseconds until the next scheduled change is due at `now_ms`,
+    /// `Some(0)` if one already is, or `None` if none is scheduled. Useful to arm a timer
+    /// instead of polling.
+    pub fn wait_ms(&self, now_ms: u32) -> Option<u32> {
+        pins_from_mask(self.pending)
+            .map(|pin| self.remaining_ms(pin, now_ms))
+            .min()
+    }
+
+    /// Milliseconds until the change of `pin` is due at `now_ms`; `0` once it is.
+    fn remaining_ms(&self, pin: Pin, now_ms: u32) -> u32 {
+        let (scheduled_ms, delay_ms) = *pin.entry(&self.timers);
+        delay_ms.saturating_sub(now_ms.wrapping_sub(scheduled_ms))
+    }
+}
+
+impl Default for PinScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "PinScheduler",),
+    async(feature = "async", keep_self)
+)]
+impl PinScheduler {
+    /// Writes the scheduled changes that are due at `now_ms`, with one read-modify-write of
+    /// the Output Port register per port involved (see [`Tca6424::write_pins_masked`]).
+    ///
+    /// A change whose write fails stays scheduled and is retried by the next call.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the pins written (`0` if no change was due), or an `Error` if an I2C bus
+    /// operation fails.
+    pub async fn run<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        now_ms: u32,
+    ) -> Result<u32, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let due = self.due(now_ms);
+        for port in Port::ALL {
+            self.write_port(tca, port, due).await?;
+        }
+        Ok(due)
+    }
+
+    /// [`PinScheduler::run`] at the time of `clock`.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    pub async fn run_from<I2C, D, T>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        clock: &T,
+    ) -> Result<u32, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+        T: TimeSource + ?Sized,
+    {
+        self.run(tca, clock.now_ms()).await
+    }
+
+    /// Waits with `delay` until every scheduled change has been written, starting at
+    /// `now_ms`.
+    ///
+    /// For start-up sequences that have nothing else to do in the meantime, such as holding
+    /// a reset line for 10 ms before releasing it. The time is advanced by the delays waited,
+    /// not by the bus transfers, so no change is written early. The delay is a parameter
+    /// rather than the driver's delay source, so a driver without one cannot release a held
+    /// line at once.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the time reached, by the delays waited, once nothing is scheduled, or an
+    /// `Error` if an I2C bus operation fails; the changes not yet written stay scheduled.
+    pub async fn finish<I2C, D, DL>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        delay: &mut DL,
+        now_ms: u32,
+    ) -> Result<u32, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+        DL: DelayNs,
+    {
+        let mut now_ms = now_ms;
+        while let Some(wait_ms) = self.wait_ms(now_ms) {
+            if wait_ms > 0 {
+                delay.delay_ms(wait_ms).await;
+                now_ms = now_ms.wrapping_add(wait_ms);
+            }
+            self.run(tca, now_ms).await?;
+        }
+        Ok(now_ms)
+    }
+
+    /// Writes the changes of `due` (a mask from [`PinScheduler::due`]) on `port`, if any,
+    /// and unschedules them once written.
+    pub(crate) async fn write_port<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        port: Port,
+        due: u32,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let shift = port as u8 * 8;
+        let mask = (due >> shift) as u8;
+        if mask == 0 {
+            return Ok(());
+        }
+        tca.write_pins_masked(port, mask, (self.levels >> shift) as u8)
+            .await?;
+        self.pending &= !(u32::from(mask) << shift);
+        Ok(())
+    }
+}
@@ -0,0 +1,228 @@
+//! 74HC595-style serial output bit-banged on three expander pins.
+
+use crate::errors::{ConfigError, Error};
+use crate::{BitOrder, BusAccess, DelayNs, Pin, PinClaim, Port, Tca6424};
+
+/// A shift-register output stream on a data, clock and latch pin triple.
+///
+/// Existing code written for a chain of 74HC595 (or similar) shift registers can be pointed
+/// at spare expander pins: [`ShiftRegister::write_bits`], [`ShiftRegister::write_byte`] and
+/// [`ShiftRegister::write_bytes`] shift the bits out on the data pin, one rising clock edge
+/// per bit, and then pulse the latch pin so the chain presents them at once.
+///
+/// Every edge is an I2C write through the driver's register cache, so a byte costs 18 single
+/// register writes (two per bit and two for the latch pulse). That is fine for status LEDs
+/// and relay drivers, but not for fast displays.
+///
+/// The pins are claimed from the driver on construction. Call [`ShiftRegister::init`] once to
+/// make them outputs, all low.
+#[derive(Debug)]
+pub struct ShiftRegister {
+    data: PinClaim,
+    clock: PinClaim,
+    latch: PinClaim,
+    order: BitOrder,
+}
+
+impl ShiftRegister {
+    /// Creates a shift-register output on `data`, `clock` and `latch`, claiming the pins from
+    /// `tca`. Bytes are shifted out most significant bit first.
+    ///
+    /// No bus transfer is made; call [`ShiftRegister::init`] to configure the pins.
+    ///
+    /// # Returns
+    ///
+    /// Returns the helper, `ConfigError::InvalidRegisterOrPin` if two of the pins are the same, or
+    /// `ConfigError::PinAlreadyClaimed` if any pin is already owned (in which case nothing is
+    /// claimed).
+    pub fn new<I2C: embedded_hal::i2c::ErrorType, D>(
+        tca: &mut Tca6424<I2C, D>,
+        data: Pin,
+        clock: Pin,
+        latch: Pin,
+    ) -> Result<Self, ConfigError> {
+        let mask: u32 = (1 << data as u8) | (1 << clock as u8) | (1 << latch as u8);
+        if mask.count_ones() != 3 {
+            return Err(ConfigError::InvalidRegisterOrPin);
+        }
+        tca.claim_bits(mask)?;
+        Ok(Self {
+            data: tca.pin_claim(data),
+            clock: tca.pin_claim(clock),
+            latch: tca.pin_claim(latch),
+            order: BitOrder::MsbFirst,
+        })
+    }
+
+    /// Shifts bytes out in `order` instead of most significant bit first.
+    pub fn with_bit_order(mut self, order: BitOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Returns the order in which the bits of a byte are shifted out.
+    pub fn bit_order(&self) -> BitOrder {
+        self.order
+    }
+
+    /// Returns the data, clock and latch pins.
+    pub fn pins(&self) -> (Pin, Pin, Pin) {
+        (self.data.pin(), self.clock.pin(), self.latch.pin())
+    }
+
+    /// Returns the pins to the pool of `tca`.
+    pub fn release<I2C: embedded_hal::i2c::ErrorType, D>(self, tca: &mut Tca6424<I2C, D>) {
+        tca.release_mask(self.mask());
+    }
+
+    fn mask(&self) -> u32 {
+        self.bit(&self.data) | self.bit(&self.clock) | self.bit(&self.latch)
+    }
+
+    fn bit(&self, claim: &PinClaim) -> u32 {
+        1 << claim.pin() as u8
+    }
+
+    /// Returns the bit of `byte` shifted out at position `index` (0 = first).
+    fn byte_bit(&self, byte: u8, index: u8) -> bool {
+        match self.order {
+            BitOrder::MsbFirst => byte & (0x80 >> index) != 0,
+            BitOrder::LsbFirst => byte & (1 << index) != 0,
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "ShiftRegister",),
+    async(feature = "async", keep_self)
+)]
+impl ShiftRegister {
+    /// Drives the data, clock and latch pins low, then configures them as outputs.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn init<I2C, D>(&mut self, tca: &mut Tca6424<I2C, D>) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let mask = self.mask();
+        tca.drive_pins(0, mask).await?;
+        for port in Port::ALL {
+            let port_mask = (mask >> (port as u8 * 8)) as u8;
+            tca.write_directions_masked(port, port_mask, 0).await?;
+        }
+        Ok(())
+    }
+
+    /// Shifts `bits` out in slice order, then latches them.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails. The outputs
+    /// of the chain are not latched after a failure.
+    pub async fn write_bits<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        bits: &[bool],
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        for &bit in bits {
+            self.shift(tca, bit).await?;
+        }
+        self.pulse_latch(tca).await
+    }
+
+    /// Shifts the 8 bits of `byte` out in [`ShiftRegister::bit_order`], then latches them.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn write_byte<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        byte: u8,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        self.write_bytes(tca, &[byte]).await
+    }
+
+    /// Shifts `bytes` out in slice order, each in [`ShiftRegister::bit_order`], then latches
+    /// them with a single pulse. Use it to update a chain of registers at once: the first
+    /// byte ends up in the register furthest from the expander.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails. The outputs
+    /// of the chain are not latched after a failure.
+    pub async fn write_bytes<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        bytes: &[u8],
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        for &byte in bytes {
+            for index in 0..8 {
+                self.shift(tca, self.byte_bit(byte, index)).await?;
+            }
+        }
+        self.pulse_latch(tca).await
+    }
+
+    /// Presents `bit` on the data pin with the clock low, then raises the clock.
+    async fn shift<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        bit: bool,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let (data, clock) = (self.bit(&self.data), self.bit(&self.clock));
+        if bit {
+            tca.drive_pins(data, clock).await?;
+        } else {
+            tca.drive_pins(0, data | clock).await?;
+        }
+        tca.drive_pins(clock, 0).await
+    }
+
+    /// Raises the latch pin with the clock low, then lowers it again.
+    async fn pulse_latch<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let latch = self.bit(&self.latch);
+        tca.drive_pins(latch, self.bit(&self.clock)).await?;
+        tca.drive_pins(0, latch).await
+    }
+}
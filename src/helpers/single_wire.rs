@@ -0,0 +1,220 @@
+//! Open-drain single-wire signalling for reset, presence and strobe handshakes.
+
+use crate::errors::{ConfigError, Error};
+use crate::registers::Register;
+use crate::{BusAccess, DelayNs, Pin, PinClaim, PinState, RegisterKind, Tca6424};
+
+/// A single open-drain signal line on one expander pin, pulled up externally.
+///
+/// The TCA6424 has push-pull outputs only, so open drain is emulated: the output latch of
+/// the pin stays low and the line is pulled low by making the pin an output, and released by
+/// making it an input again. This is enough for slow handshakes with companion chips: reset
+/// pulses, presence detection after a reset, and strobes.
+///
+/// # Timing accuracy
+///
+/// Every edge is an I2C write, so a pulse lasts the requested time *plus* about one register
+/// write (roughly 100 µs at 400 kHz, 300 µs at 100 kHz), and [`SingleWire::reset_presence`]
+/// samples the line one read later than requested. Bus contention and task scheduling add
+/// to that without bound. Use it for signals specified in hundreds of microseconds or more
+/// with generous tolerances; real 1-Wire data slots (a few microseconds) are out of reach.
+///
+/// The pin is claimed from the driver on construction. Call [`SingleWire::init`] once to
+/// clear its output latch and release the line.
+#[derive(Debug)]
+pub struct SingleWire {
+    pin: PinClaim,
+}
+
+impl SingleWire {
+    /// Creates a single-wire line on `pin`, claiming it from `tca`.
+    ///
+    /// No bus transfer is made; call [`SingleWire::init`] to configure the pin.
+    ///
+    /// # Returns
+    ///
+    /// Returns the helper, or `ConfigError::PinAlreadyClaimed` if the pin is already owned.
+    pub fn new<I2C: embedded_hal::i2c::ErrorType, D>(
+        tca: &mut Tca6424<I2C, D>,
+        pin: Pin,
+    ) -> Result<Self, ConfigError> {
+        Ok(Self {
+            pin: tca.claim_pin(pin)?,
+        })
+    }
+
+    /// Returns the line's pin.
+    pub fn pin(&self) -> Pin {
+        self.pin.pin()
+    }
+
+    /// Returns the pin to the pool of `tca`; pins claimed from
+    /// another driver stay claimed there.
+    pub fn release<I2C: embedded_hal::i2c::ErrorType, D>(self, tca: &mut Tca6424<I2C, D>) {
+        let _ = tca.release_pin(self.pin);
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "SingleWire",),
+    async(feature = "async", keep_self)
+)]
+impl SingleWire {
+    /// Clears the output latch of the pin, then releases the line.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn init<I2C, D>(&mut self, tca: &mut Tca6424<I2C, D>) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let pin = self.pin.pin();
+        tca.write_pins_masked(pin.port(), 1 << pin.bit(), 0).await?;
+        tca.write_directions_masked(pin.port(), 1 << pin.bit(), 0xFF)
+            .await
+    }
+
+    /// Pulls the line low.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn drive_low<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        self.set_released(tca, false).await
+    }
+
+    /// Releases the line, letting the external pull-up (or another device) set its level.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn float<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        self.set_released(tca, true).await
+    }
+
+    /// Reads the level of the line.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(true)` if the line is high, `Ok(false)` if it is low, or an `Error` if the
+    /// I2C bus operation fails.
+    pub async fn is_high<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+    ) -> Result<bool, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let state = tca.get_pin_input_state(self.pin.pin()).await?;
+        Ok(state == PinState::High)
+    }
+
+    /// Pulls the line low for at least `duration_us`, waiting with the driver's delay source
+    /// (see [`Tca6424::with_delay`]), then releases it.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails. The line is
+    /// left pulled low if releasing it fails.
+    pub async fn pulse_low<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        duration_us: u32,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        self.drive_low(tca).await?;
+        tca.delay().delay_us(duration_us).await;
+        self.float(tca).await
+    }
+
+    /// Sends a reset pulse of `reset_us` and checks for a presence response: a companion chip
+    /// answering by holding the line low `sample_after_us` after it was released.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(true)` if the line was low when sampled, `Ok(false)` if it was high, or an
+    /// `Error` if an I2C bus operation fails.
+    pub async fn reset_presence<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        reset_us: u32,
+        sample_after_us: u32,
+    ) -> Result<bool, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        self.pulse_low(tca, reset_us).await?;
+        tca.delay().delay_us(sample_after_us).await;
+        Ok(!self.is_high(tca).await?)
+    }
+
+    /// Makes the pin an input (`released`) or an output, with a single register write once
+    /// the Configuration register of its port is cached.
+    async fn set_released<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        released: bool,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let pin = self.pin.pin();
+        let bit = 1 << pin.bit();
+        match tca.cache().get(RegisterKind::Configuration, pin.port()) {
+            Some(cached) => {
+                let value = if released {
+                    cached | bit
+                } else {
+                    cached & !bit
+                };
+                tca.write_register(Register::of(RegisterKind::Configuration, pin.port()), value)
+                    .await
+            }
+            None => {
+                let inputs = if released { bit } else { 0 };
+                tca.write_directions_masked(pin.port(), bit, inputs).await
+            }
+        }
+    }
+}
@@ -0,0 +1,248 @@
+//! Software PWM on expander outputs, with timed fades.
+
+use crate::errors::Error;
+use crate::{BusAccess, DelayNs, Port, Tca6424, pins_from_mask};
+
+/// Mask of the 24 pin bits.
+const PIN_BITS: u32 = 0x00FF_FFFF;
+
+/// A duty-cycle ramp of one pin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct Ramp {
+    from: u8,
+    to: u8,
+    /// Set by the first [`SoftPwm::process`] after the ramp was requested.
+    start_ms: Option<u32>,
+    duration_ms: u32,
+}
+
+impl Ramp {
+    const fn hold(duty: u8) -> Self {
+        Self {
+            from: duty,
+            to: duty,
+            start_ms: None,
+            duration_ms: 0,
+        }
+    }
+
+    fn duty(&self, now_ms: Option<u32>) -> u8 {
+        let (Some(start), Some(now)) = (self.start_ms, now_ms) else {
+            return self.from;
+        };
+        let elapsed = now.wrapping_sub(start);
+        if elapsed >= self.duration_ms {
+            return self.to;
+        }
+        let span = i64::from(self.to) - i64::from(self.from);
+        let duty = i64::from(self.from) + span * i64::from(elapsed) / i64::from(self.duration_ms);
+        duty as u8
+    }
+
+    fn is_done(&self, now_ms: Option<u32>) -> bool {
+        self.from == self.to
+            || matches!(
+                (self.start_ms, now_ms),
+                (Some(start), Some(now)) if now.wrapping_sub(start) >= self.duration_ms
+            )
+    }
+}
+
+/// Software PWM for LED banks and other slow loads on the expander outputs.
+///
+/// Every pin has a duty cycle from `0` (off) to `255` (fully on) within a common period. Set
+/// it directly with [`SoftPwm::set_duty`], or ramp it linearly with [`SoftPwm::fade_in`],
+/// [`SoftPwm::fade_out`] and [`SoftPwm::fade_to`]; ramps start at the next tick. Drive the
+/// engine from a fast periodic timer with [`SoftPwm::tick`]: each tick computes the level of
+/// every pin and writes only the ports whose level changed, so a bank at a constant 0 or 255
+/// costs no bus traffic.
+///
+/// Each level change is one I2C write, so the achievable resolution depends on the tick rate
+/// and bus speed: at 400 kHz a 10 ms period with 1 ms ticks gives ten brightness steps
+/// without flicker on LEDs.
+///
+/// ```
+/// use tca6424::{SoftPwm, pins};
+///
+/// const BANK: u32 = pins!(P00, P01, P02);
+/// let mut pwm = SoftPwm::new(10);
+/// pwm.fade_in(BANK, 1_000);
+/// assert_eq!(pwm.process(0), Some(0)); // fade starts at 0%
+/// assert_eq!(pwm.process(500), Some(BANK)); // 50% at mid-fade, high in the first half
+/// assert_eq!(pwm.process(506), Some(0));
+/// assert!(pwm.is_ramping(BANK));
+/// pwm.process(1_000);
+/// assert_eq!(pwm.duty(tca6424::Pin::P01), 255);
+/// ```
+#[derive(Debug)]
+pub struct SoftPwm {
+    period_ms: u32,
+    pins: u32,
+    ramps: [Ramp; 24],
+    frame_start_ms: Option<u32>,
+    now_ms: Option<u32>,
+    /// Levels last reported by [`SoftPwm::process`], over [`SoftPwm::pins`].
+    written: Option<u32>,
+}
+
+impl SoftPwm {
+    /// Creates an engine with a PWM period of `period_ms` and no pins. Periods below 2 ms are
+    /// raised to 2 ms.
+    pub const fn new(period_ms: u32) -> Self {
+        Self {
+            period_ms: if period_ms < 2 { 2 } else { period_ms },
+            pins: 0,
+            ramps: [Ramp::hold(0); 24],
+            frame_start_ms: None,
+            now_ms: None,
+            written: None,
+        }
+    }
+
+    /// Returns the PWM period.
+    pub const fn period_ms(&self) -> u32 {
+        self.period_ms
+    }
+
+    /// Returns the pins driven by the engine, as a 24-bit mask.
+    pub const fn pins(&self) -> u32 {
+        self.pins
+    }
+
+    /// Sets the duty cycle of the pins of `pins` at once, cancelling their ramps. The pins
+    /// are added to the engine.
+    pub fn set_duty(&mut self, pins: u32, duty: u8) {
+        self.fade_to(pins, duty, 0);
+    }
+
+    /// Ramps the pins of `pins` linearly from their current duty cycle to `duty` over
+    /// `duration_ms`, starting at the next tick. The pins are added to the engine.
+    pub fn fade_to(&mut self, pins: u32, duty: u8, duration_ms: u32) {
+        let pins = pins & PIN_BITS;
+        for pin in pins_from_mask(pins) {
+            let ramp = pin.entry_mut(&mut self.ramps);
+            *ramp = Ramp {
+                from: ramp.duty(self.now_ms),
+                to: duty,
+                start_ms: None,
+                duration_ms,
+            };
+            if duration_ms == 0 {
+                *ramp = Ramp::hold(duty);
+            }
+        }
+        self.pins |= pins;
+    }
+
+    /// Ramps the pins of `pins` up to fully on over `duration_ms`.
+    pub fn fade_in(&mut self, pins: u32, duration_ms: u32) {
+        self.fade_to(pins, u8::MAX, duration_ms);
+    }
+
+    /// Ramps the pins of `pins` down to off over `duration_ms`.
+    pub fn fade_out(&mut self, pins: u32, duration_ms: u32) {
+        self.fade_to(pins, 0, duration_ms);
+    }
+
+    /// Stops driving the pins of `pins`; their outputs keep their current level.
+    pub fn release(&mut self, pins: u32) {
+        self.pins &= !pins;
+        if let Some(written) = &mut self.written {
+            *written &= self.pins;
+        }
+    }
+
+    /// Returns the duty cycle of `pin` at the last tick.
+    pub fn duty(&self, pin: crate::Pin) -> u8 {
+        pin.entry(&self.ramps).duty(self.now_ms)
+    }
+
+    /// Returns `true` while any pin of `pins` is still ramping.
+    pub fn is_ramping(&self, pins: u32) -> bool {
+        pins_from_mask(pins & self.pins).any(|pin| !pin.entry(&self.ramps).is_done(self.now_ms))
+    }
+
+    /// Advances to `now_ms`, a free-running millisecond timestamp.
+    ///
+    /// # Returns
+    ///
+    /// Returns the levels of [`SoftPwm::pins`] (bit set = high) if any differs from the levels
+    /// returned before, or `None` if nothing has to be written.
+    pub fn process(&mut self, now_ms: u32) -> Option<u32> {
+        self.now_ms = Some(now_ms);
+        let frame_start = *self.frame_start_ms.get_or_insert(now_ms);
+        let phase = now_ms.wrapping_sub(frame_start) % self.period_ms;
+        let mut levels = 0;
+        for pin in pins_from_mask(self.pins) {
+            let ramp = pin.entry_mut(&mut self.ramps);
+            ramp.start_ms.get_or_insert(now_ms);
+            let on_ms = self.period_ms * u32::from(ramp.duty(Some(now_ms))) / u32::from(u8::MAX);
+            if phase < on_ms {
+                levels |= 1 << pin as u8;
+            }
+        }
+        if self.written == Some(levels) {
+            return None;
+        }
+        self.written = Some(levels);
+        Some(levels)
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "SoftPwm",),
+    async(feature = "async", keep_self)
+)]
+impl SoftPwm {
+    /// Drives the engine's pins low, then configures them as outputs.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn init<I2C, D>(&mut self, tca: &mut Tca6424<I2C, D>) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        tca.drive_pins(0, self.pins).await?;
+        self.written = None;
+        for port in Port::ALL {
+            let mask = (self.pins >> (port as u8 * 8)) as u8;
+            tca.write_directions_masked(port, mask, 0).await?;
+        }
+        Ok(())
+    }
+
+    /// Updates the outputs for `now_ms`, a free-running millisecond timestamp.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(true)` if outputs were written, `Ok(false)` if no level changed, or an
+    /// `Error` if an I2C bus operation fails.
+    pub async fn tick<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        now_ms: u32,
+    ) -> Result<bool, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        match self.process(now_ms) {
+            Some(levels) => {
+                if let Err(error) = tca.drive_pins(levels, self.pins & !levels).await {
+                    self.written = None;
+                    return Err(error);
+                }
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
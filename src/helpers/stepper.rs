@@ -0,0 +1,246 @@
+//! Step/direction stepper motor driver with trapezoidal speed ramping.
+
+use crate::errors::{ConfigError, Error};
+use crate::{BusAccess, DelayNs, Pin, PinClaim, Port, Tca6424};
+
+/// A trapezoidal speed profile: moves start at `start_rate`, accelerate by `acceleration`
+/// up to `max_rate`, and decelerate symmetrically so the last step is taken at `start_rate`
+/// again. Short moves that cannot reach `max_rate` form a triangle.
+///
+/// Rates are in steps per second, the acceleration in steps per second squared.
+///
+/// ```
+/// use tca6424::StepRamp;
+///
+/// const RAMP: StepRamp = StepRamp::new(100, 400, 2_000);
+/// assert_eq!(RAMP.rate(0, 1_000), 100);
+/// assert_eq!(RAMP.rate(500, 1_000), 400);
+/// assert_eq!(RAMP.rate(999, 1_000), 100);
+/// assert_eq!(RAMP.interval_us(500, 1_000), 2_500);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct StepRamp {
+    start_rate: u32,
+    max_rate: u32,
+    acceleration: u32,
+}
+
+impl StepRamp {
+    /// Creates a profile. A zero `start_rate` is raised to 1 step/s, and `max_rate` is raised
+    /// to `start_rate` if lower.
+    pub const fn new(start_rate: u32, max_rate: u32, acceleration: u32) -> Self {
+        let start_rate = if start_rate == 0 { 1 } else { start_rate };
+        Self {
+            start_rate,
+            max_rate: if max_rate < start_rate {
+                start_rate
+            } else {
+                max_rate
+            },
+            acceleration,
+        }
+    }
+
+    /// Creates a profile running every step at `rate`, without ramping.
+    pub const fn constant(rate: u32) -> Self {
+        Self::new(rate, rate, 0)
+    }
+
+    /// Returns the start and stop rate.
+    pub const fn start_rate(&self) -> u32 {
+        self.start_rate
+    }
+
+    /// Returns the cruise rate.
+    pub const fn max_rate(&self) -> u32 {
+        self.max_rate
+    }
+
+    /// Returns the acceleration.
+    pub const fn acceleration(&self) -> u32 {
+        self.acceleration
+    }
+
+    /// Returns the rate of step `index` (0-based) of a move of `total` steps.
+    ///
+    /// Follows `v² = v0² + 2·a·n`, counting `n` from the nearer end of the move.
+    pub const fn rate(&self, index: u32, total: u32) -> u32 {
+        let from_start = index;
+        let from_end = total.saturating_sub(index + 1);
+        let steps = if from_start < from_end {
+            from_start
+        } else {
+            from_end
+        };
+        let start = self.start_rate as u64;
+        let squared = start * start + 2 * self.acceleration as u64 * steps as u64;
+        let rate = squared.isqrt();
+        if rate > self.max_rate as u64 {
+            self.max_rate
+        } else {
+            rate as u32
+        }
+    }
+
+    /// Returns the time from step `index` to the next one of a move of `total` steps, in
+    /// microseconds.
+    pub const fn interval_us(&self, index: u32, total: u32) -> u32 {
+        1_000_000 / self.rate(index, total)
+    }
+}
+
+/// A stepper motor driver (A4988, DRV8825, TMC2208 and similar) on a STEP and a DIR pin.
+///
+/// [`Stepper::move_steps`] sets the direction and pulses STEP once per step, spacing the steps
+/// along the [`StepRamp`] with the driver's delay source, so mechanisms start and stop at a
+/// rate they can follow and do not stall or lose steps.
+///
+/// Every step costs two register writes, and the bus time adds to each interval: at 400 kHz
+/// a step takes roughly 0.2 ms on the bus, so rates beyond a few hundred steps per second are
+/// not reachable and the real rate is somewhat below the profile. Use microstepping on the
+/// driver for smoother motion rather than higher step rates.
+///
+/// The pins are claimed from the driver on construction. Call [`Stepper::init`] once to make
+/// them outputs, both low.
+#[derive(Debug)]
+pub struct Stepper {
+    step: PinClaim,
+    dir: PinClaim,
+    ramp: StepRamp,
+    position: i32,
+}
+
+impl Stepper {
+    /// Creates a stepper on `step` and `dir` moving along `ramp`, claiming the pins from
+    /// `tca`. DIR high moves in the positive direction.
+    ///
+    /// No bus transfer is made; call [`Stepper::init`] to configure the pins.
+    ///
+    /// # Returns
+    ///
+    /// Returns the helper, `ConfigError::InvalidRegisterOrPin` if both pins are the same, or
+    /// `ConfigError::PinAlreadyClaimed` if either pin is already owned (in which case nothing is
+    /// claimed).
+    pub fn new<I2C: embedded_hal::i2c::ErrorType, D>(
+        tca: &mut Tca6424<I2C, D>,
+        step: Pin,
+        dir: Pin,
+        ramp: StepRamp,
+    ) -> Result<Self, ConfigError> {
+        if step == dir {
+            return Err(ConfigError::InvalidRegisterOrPin);
+        }
+        tca.claim_bits((1 << step as u8) | (1 << dir as u8))?;
+        Ok(Self {
+            step: tca.pin_claim(step),
+            dir: tca.pin_claim(dir),
+            ramp,
+            position: 0,
+        })
+    }
+
+    /// Moves along `ramp` from now on.
+    pub fn set_ramp(&mut self, ramp: StepRamp) {
+        self.ramp = ramp;
+    }
+
+    /// Returns the speed profile.
+    pub fn ramp(&self) -> StepRamp {
+        self.ramp
+    }
+
+    /// Returns the position in steps, counted from construction or the last
+    /// [`Stepper::set_position`].
+    pub fn position(&self) -> i32 {
+        self.position
+    }
+
+    /// Redefines the current position, for example after homing.
+    pub fn set_position(&mut self, position: i32) {
+        self.position = position;
+    }
+
+    /// Returns the STEP and DIR pins.
+    pub fn pins(&self) -> (Pin, Pin) {
+        (self.step.pin(), self.dir.pin())
+    }
+
+    /// Returns the pins to the pool of `tca`; pins claimed from
+    /// another driver stay claimed there.
+    pub fn release<I2C: embedded_hal::i2c::ErrorType, D>(self, tca: &mut Tca6424<I2C, D>) {
+        let _ = tca.release_pin(self.step);
+        let _ = tca.release_pin(self.dir);
+    }
+
+    fn bit(claim: &PinClaim) -> u32 {
+        1 << claim.pin() as u8
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Stepper",),
+    async(feature = "async", keep_self)
+)]
+impl Stepper {
+    /// Drives STEP and DIR low, then configures them as outputs.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn init<I2C, D>(&mut self, tca: &mut Tca6424<I2C, D>) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let mask = Self::bit(&self.step) | Self::bit(&self.dir);
+        tca.drive_pins(0, mask).await?;
+        for port in Port::ALL {
+            let port_mask = (mask >> (port as u8 * 8)) as u8;
+            tca.write_directions_masked(port, port_mask, 0).await?;
+        }
+        Ok(())
+    }
+
+    /// Moves `steps` steps (negative = backwards) along the speed profile, returning when the
+    /// last step has been taken.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails. The position
+    /// counts the steps taken before the failure.
+    pub async fn move_steps<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        steps: i32,
+    ) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let (step, dir) = (Self::bit(&self.step), Self::bit(&self.dir));
+        if steps >= 0 {
+            tca.drive_pins(dir, 0).await?;
+        } else {
+            tca.drive_pins(0, dir).await?;
+        }
+        let total = steps.unsigned_abs();
+        for index in 0..total {
+            tca.drive_pins(step, 0).await?;
+            tca.drive_pins(0, step).await?;
+            self.position = self.position.wrapping_add(steps.signum());
+            if index + 1 < total {
+                tca.delay()
+                    .delay_us(self.ramp.interval_us(index, total))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+}
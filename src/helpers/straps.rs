@@ -0,0 +1,211 @@
+//! Boot-time decoding of board-revision strap pins.
+
+use super::BoardConfig;
+use crate::errors::Error;
+use crate::registers::Register;
+use crate::{BusAccess, DelayNs, Pin, Tca6424};
+
+/// Order in which strap pins map to the bits of the decoded value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BitOrder {
+    /// The first pin is the most significant bit.
+    MsbFirst,
+    /// The first pin is the least significant bit.
+    LsbFirst,
+}
+
+/// How the bits read from a group of pins encode the decoded number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ValueEncoding {
+    /// Plain binary.
+    #[default]
+    Binary,
+    /// Reflected Gray code, as output by absolute rotary switches and encoders.
+    Gray,
+    /// Binary-coded decimal, four bits per digit, as output by thumbwheel switches. A digit
+    /// above 9, which such switches never produce, counts with its binary value.
+    Bcd,
+}
+
+impl ValueEncoding {
+    /// Returns the number encoded by `raw`.
+    pub const fn decode(self, raw: u32) -> u32 {
+        match self {
+            ValueEncoding::Binary => raw,
+            ValueEncoding::Gray => {
+                let mut value = raw;
+                let mut shift = raw >> 1;
+                while shift != 0 {
+                    value ^= shift;
+                    shift >>= 1;
+                }
+                value
+            }
+            ValueEncoding::Bcd => {
+                let (mut value, mut weight, mut digits) = (0, 1, raw);
+                while digits != 0 {
+                    value += (digits & 0xF) * weight;
+                    weight *= 10;
+                    digits >>= 4;
+                }
+                value
+            }
+        }
+    }
+}
+
+/// Reads a board revision (or any other strapped value) from a set of pins.
+///
+/// Strap pins are tied high or low through resistors and read once at boot. The pins are made
+/// inputs, sampled after an optional settle time, and then handed back to their runtime roles,
+/// so they can be reused as outputs afterwards.
+///
+/// Rotary switches and thumbwheels wired to several pins are read the same way: choose their
+/// [`ValueEncoding`] with [`StrapDecoder::with_encoding`] and the decoder returns the switch
+/// position directly.
+///
+/// ```
+/// use tca6424::{BitOrder, Pin, StrapDecoder, ValueEncoding};
+///
+/// const REVISION_PINS: [Pin; 3] = [Pin::P25, Pin::P26, Pin::P27];
+/// const REVISION: StrapDecoder =
+///     StrapDecoder::new(&REVISION_PINS, BitOrder::MsbFirst).with_settle_us(100);
+///
+/// // P25 high, P26 low, P27 high
+/// assert_eq!(REVISION.decode(0b1010_0000 << 16), 0b101);
+///
+/// // A 4-bit Gray-coded rotary switch on P00-P03, least significant bit on P00
+/// const SWITCH_PINS: [Pin; 4] = [Pin::P00, Pin::P01, Pin::P02, Pin::P03];
+/// const SWITCH: StrapDecoder =
+///     StrapDecoder::new(&SWITCH_PINS, BitOrder::LsbFirst).with_encoding(ValueEncoding::Gray);
+/// assert_eq!(SWITCH.decode(0b1101), 9);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct StrapDecoder<'p> {
+    pins: &'p [Pin],
+    order: BitOrder,
+    encoding: ValueEncoding,
+    settle_us: u32,
+}
+
+impl<'p> StrapDecoder<'p> {
+    /// Creates a decoder for `pins`, read in `order` as plain binary, without a settle time.
+    pub const fn new(pins: &'p [Pin], order: BitOrder) -> Self {
+        Self {
+            pins,
+            order,
+            encoding: ValueEncoding::Binary,
+            settle_us: 0,
+        }
+    }
+
+    /// Decodes the bits read from the pins with `encoding` instead of as plain binary.
+    pub const fn with_encoding(mut self, encoding: ValueEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Returns the encoding of the decoded value.
+    pub const fn encoding(&self) -> ValueEncoding {
+        self.encoding
+    }
+
+    /// Waits `settle_us` microseconds, using the driver's delay source, between making the
+    /// pins inputs and sampling them.
+    pub const fn with_settle_us(mut self, settle_us: u32) -> Self {
+        self.settle_us = settle_us;
+        self
+    }
+
+    /// Returns the mask of the strap pins (bit `n` = pin index `n`).
+    pub fn mask(&self) -> u32 {
+        self.pins.iter().fold(0, |mask, &pin| mask | 1 << pin as u8)
+    }
+
+    /// Decodes the strap value from a 24-bit input image (bit `n` = pin index `n`).
+    pub fn decode(&self, inputs: u32) -> u32 {
+        let bit = |pin: &Pin| (inputs >> *pin as u8) & 1;
+        let raw = match self.order {
+            BitOrder::MsbFirst => self.pins.iter().fold(0, |value, pin| value << 1 | bit(pin)),
+            BitOrder::LsbFirst => self
+                .pins
+                .iter()
+                .rev()
+                .fold(0, |value, pin| value << 1 | bit(pin)),
+        };
+        self.encoding.decode(raw)
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "StrapDecoder",),
+    async(feature = "async", keep_self)
+)]
+impl StrapDecoder<'_> {
+    /// Makes the strap pins inputs, samples and decodes them, then restores their previous
+    /// directions.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the decoded value, or an `Error` if an I2C bus operation fails.
+    pub async fn read<I2C, D>(&self, tca: &mut Tca6424<I2C, D>) -> Result<u32, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let mut configuration = [0u8; 4];
+        tca.read_registers_ai(Register::ConfigurationPort0, &mut configuration[..3])
+            .await?;
+        let previous = u32::from_le_bytes(configuration);
+        let strapped = (previous | self.mask()).to_le_bytes();
+        if strapped != configuration {
+            tca.write_registers_ai(Register::ConfigurationPort0, &strapped[..3])
+                .await?;
+        }
+
+        if self.settle_us > 0 {
+            tca.delay().delay_us(self.settle_us).await;
+        }
+        let mut inputs = [0u8; 4];
+        let sampled = tca
+            .read_registers_ai(Register::InputPort0, &mut inputs[..3])
+            .await;
+
+        if strapped != configuration {
+            tca.write_registers_ai(Register::ConfigurationPort0, &configuration[..3])
+                .await?;
+        }
+        sampled?;
+        Ok(self.decode(u32::from_le_bytes(inputs)))
+    }
+
+    /// Reads the straps like [`StrapDecoder::read`], then applies `runtime`, the directions
+    /// and output levels the pins have once the board is running.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the decoded value, or an `Error` if an I2C bus operation fails or a pin of
+    /// `runtime` is already claimed.
+    pub async fn read_and_apply<I2C, D>(
+        &self,
+        tca: &mut Tca6424<I2C, D>,
+        runtime: &BoardConfig,
+    ) -> Result<u32, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let value = self.read(tca).await?;
+        tca.apply_board_config(runtime).await?;
+        Ok(value)
+    }
+}
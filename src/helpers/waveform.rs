@@ -0,0 +1,425 @@
+//! Waveform player: output patterns stepped through from a const table.
+
+use crate::errors::Error;
+use crate::{BusAccess, DelayNs, Port, Tca6424};
+
+/// One entry of a waveform table: pins to drive high, pins to drive low, and how long the
+/// step lasts.
+///
+/// Masks are 24-bit, bit `n` = pin index `n` (see [`pins!`](crate::pins)). Pins in neither
+/// mask keep their level, so a step only has to name the pins it changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WaveformStep {
+    /// Pins driven high at the start of the step.
+    pub on: u32,
+    /// Pins driven low at the start of the step.
+    pub off: u32,
+    /// Time until the next step, in milliseconds.
+    pub duration_ms: u32,
+}
+
+impl WaveformStep {
+    /// Creates a step. A pin in both masks is driven high.
+    pub const fn new(on: u32, off: u32, duration_ms: u32) -> Self {
+        Self {
+            on: on & PIN_BITS,
+            off: off & !on & PIN_BITS,
+            duration_ms,
+        }
+    }
+}
+
+/// Whether a [`WaveformPlayer`] stops after the last step or starts over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PlayMode {
+    /// Play the table once; the levels of the last step stay applied.
+    #[default]
+    Once,
+    /// Start over from the first step after the last one.
+    Loop,
+}
+
+/// The decision of a step gate passed to [`WaveformPlayer::process_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum StepControl {
+    /// Start the step.
+    Proceed,
+    /// Keep the current levels and ask again on the next tick. Once released, the step is
+    /// timed from that tick rather than from the table's grid.
+    Hold,
+    /// Stop the table, as [`WaveformPlayer::abort`].
+    Abort,
+}
+
+/// Mask of the 24 pin bits.
+const PIN_BITS: u32 = 0x00FF_FFFF;
+
+/// Plays a table of [`WaveformStep`]s on the output pins: blink codes, buzzer patterns,
+/// production test stimuli.
+///
+/// Drive it from a periodic timer with [`WaveformPlayer::tick`], or play it with the
+/// driver's delay source using [`WaveformPlayer::run`]. Ticks follow the table's timing and
+/// not the tick rate: when a tick comes late, every step that elapsed in between is merged
+/// into one write, and the next step is timed from where the table says it starts. Writes
+/// go through the register cache, as for [`Heartbeat`](crate::Heartbeat).
+///
+/// ```
+/// use tca6424::{pins, PlayMode, WaveformPlayer, WaveformStep};
+///
+/// // Error code 3 on P00: three short blinks, then a pause.
+/// const BLINK: u32 = pins!(P00);
+/// static CODE_3: [WaveformStep; 6] = [
+///     WaveformStep::new(BLINK, 0, 200),
+///     WaveformStep::new(0, BLINK, 200),
+///     WaveformStep::new(BLINK, 0, 200),
+///     WaveformStep::new(0, BLINK, 200),
+///     WaveformStep::new(BLINK, 0, 200),
+///     WaveformStep::new(0, BLINK, 1_000),
+/// ];
+///
+/// let mut player = WaveformPlayer::new(&CODE_3, PlayMode::Loop);
+/// assert_eq!(player.process(0), Some((BLINK, 0)));
+/// assert_eq!(player.process(100), None);
+/// assert_eq!(player.process(200), Some((0, BLINK)));
+/// assert_eq!(player.pins(), BLINK);
+/// ```
+///
+/// Supervisory code can interlock a table with external conditions: [`WaveformPlayer::pause`]
+/// and [`WaveformPlayer::resume`] stop and restart the clock, [`WaveformPlayer::abort`] ends
+/// the table, and [`WaveformPlayer::tick_with`] asks a gate before every step, for example to
+/// hold the next rail of a power-up sequence until the previous rail reports power good.
+#[derive(Debug)]
+pub struct WaveformPlayer {
+    steps: &'static [WaveformStep],
+    mode: PlayMode,
+    index: usize,
+    step_started_ms: Option<u32>,
+    finished: bool,
+    aborted: bool,
+    paused_at_ms: Option<u32>,
+    /// The gate held the next step on the last tick.
+    holding: bool,
+}
+
+impl WaveformPlayer {
+    /// Creates a player for `steps`, starting at the first step on the first tick.
+    ///
+    /// No bus transfer is made; call [`WaveformPlayer::init`] to configure the pins.
+    pub const fn new(steps: &'static [WaveformStep], mode: PlayMode) -> Self {
+        Self {
+            steps,
+            mode,
+            index: 0,
+            step_started_ms: None,
+            finished: steps.is_empty(),
+            aborted: false,
+            paused_at_ms: None,
+            holding: false,
+        }
+    }
+
+    /// Rewinds to the first step; the next tick starts it. Clears a pause or abort.
+    pub fn restart(&mut self) {
+        self.index = 0;
+        self.step_started_ms = None;
+        self.finished = self.steps.is_empty();
+        self.aborted = false;
+        self.paused_at_ms = None;
+        self.holding = false;
+    }
+
+    /// Returns `true` once a single-shot table has played its last step, or after
+    /// [`WaveformPlayer::abort`].
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Stops the table at the current step: the levels stay as they are and no further step
+    /// starts until [`WaveformPlayer::restart`].
+    pub fn abort(&mut self) {
+        self.finished = true;
+        self.aborted = true;
+    }
+
+    /// Returns `true` if the table was aborted.
+    pub fn is_aborted(&self) -> bool {
+        self.aborted
+    }
+
+    /// Freezes the table at `now_ms`; ticks change nothing until [`WaveformPlayer::resume`].
+    /// Pausing again keeps the first pause time.
+    pub fn pause(&mut self, now_ms: u32) {
+        self.paused_at_ms.get_or_insert(now_ms);
+    }
+
+    /// Continues a paused table at `now_ms`, with the current step lasting as long as it had
+    /// left when paused.
+    pub fn resume(&mut self, now_ms: u32) {
+        if let Some(paused_at) = self.paused_at_ms.take()
+            && let Some(started) = self.step_started_ms.as_mut()
+        {
+            *started = started.wrapping_add(now_ms.wrapping_sub(paused_at));
+        }
+    }
+
+    /// Returns `true` while the table is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused_at_ms.is_some()
+    }
+
+    /// Returns the index of the current step.
+    pub fn step_index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the play mode.
+    pub fn mode(&self) -> PlayMode {
+        self.mode
+    }
+
+    /// Returns every pin the table drives, as a 24-bit mask.
+    pub fn pins(&self) -> u32 {
+        self.steps
+            .iter()
+            .fold(0, |pins, step| pins | step.on | step.off)
+    }
+
+    /// Returns the current step. The index stays below `steps.len()` while playing; out of
+    /// range it reads as a step that drives nothing and never ends.
+    fn step(&self) -> WaveformStep {
+        self.steps
+            .get(self.index)
+            .copied()
+            .unwrap_or(WaveformStep::new(0, 0, u32::MAX))
+    }
+
+    /// Advances to `now_ms`, a free-running millisecond timestamp. The first call starts the
+    /// first step.
+    ///
+    /// # Returns
+    ///
+    /// Returns the `(high, low)` pin masks to apply if a step started since the last call
+    /// (merged over every step that started), or `None` if the levels stay as they are.
+    pub fn process(&mut self, now_ms: u32) -> Option<(u32, u32)> {
+        self.process_with(now_ms, |_| StepControl::Proceed)
+    }
+
+    /// Advances to `now_ms` as [`WaveformPlayer::process`], asking `gate` with the index of
+    /// every step before starting it.
+    ///
+    /// # Returns
+    ///
+    /// Returns the pin masks to apply, as [`WaveformPlayer::process`].
+    pub fn process_with<F>(&mut self, now_ms: u32, mut gate: F) -> Option<(u32, u32)>
+    where
+        F: FnMut(usize) -> StepControl,
+    {
+        if self.finished || self.paused_at_ms.is_some() {
+            return None;
+        }
+        let Some(started) = self.step_started_ms else {
+            if !self.admit(0, &mut gate) {
+                return None;
+            }
+            self.step_started_ms = Some(now_ms);
+            let step = self.step();
+            return Some((step.on, step.off));
+        };
+        let mut started = started;
+        let mut elapsed = now_ms.wrapping_sub(started);
+        let mut levels: Option<(u32, u32)> = None;
+        let mut advanced = 0;
+        while elapsed >= self.step().duration_ms {
+            let next = if self.index + 1 == self.steps.len() {
+                if self.mode == PlayMode::Once {
+                    self.finished = true;
+                    break;
+                }
+                0
+            } else {
+                self.index + 1
+            };
+            let held = self.holding;
+            if !self.admit(next, &mut gate) {
+                break;
+            }
+            if held {
+                // Released after a hold: the step starts now.
+                started = now_ms;
+                elapsed = 0;
+            } else {
+                let duration = self.step().duration_ms;
+                elapsed -= duration;
+                started = started.wrapping_add(duration);
+            }
+            self.index = next;
+            advanced += 1;
+            if advanced > self.steps.len() {
+                // A whole cycle is merged already and replaying it changes nothing: skip the
+                // remaining full cycles.
+                let cycle: u32 = self.steps.iter().map(|step| step.duration_ms).sum();
+                if cycle == 0 {
+                    break;
+                }
+                let skipped = elapsed - elapsed % cycle;
+                elapsed -= skipped;
+                started = started.wrapping_add(skipped);
+                advanced = 0;
+            }
+            let step = self.step();
+            let (on, off) = levels.unwrap_or_default();
+            levels = Some((on & !step.off | step.on, off & !step.on | step.off));
+        }
+        self.step_started_ms = Some(started);
+        levels
+    }
+
+    /// Asks `gate` whether step `index` may start, recording a hold or abort.
+    fn admit<F>(&mut self, index: usize, gate: &mut F) -> bool
+    where
+        F: FnMut(usize) -> StepControl,
+    {
+        match gate(index) {
+            StepControl::Proceed => {
+                self.holding = false;
+                true
+            }
+            StepControl::Hold => {
+                self.holding = true;
+                false
+            }
+            StepControl::Abort => {
+                self.abort();
+                false
+            }
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "WaveformPlayer",),
+    async(feature = "async", keep_self)
+)]
+impl WaveformPlayer {
+    /// Drives every pin the table uses low, then configures them as outputs.
+    ///
+    /// The Output registers are written before the Configuration registers, so the pins never
+    /// drive the power-on-high latch on their way to becoming outputs.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn init<I2C, D>(&mut self, tca: &mut Tca6424<I2C, D>) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        let pins = self.pins();
+        for port in Port::ALL {
+            let mask = (pins >> (port as u8 * 8)) as u8;
+            tca.write_pins_masked(port, mask, 0).await?;
+        }
+        for port in Port::ALL {
+            let mask = (pins >> (port as u8 * 8)) as u8;
+            tca.write_directions_masked(port, mask, 0).await?;
+        }
+        Ok(())
+    }
+
+    /// Applies the steps that started by `now_ms`, a free-running millisecond timestamp.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(true)` if outputs were written, `Ok(false)` if no step started, or an
+    /// `Error` if an I2C bus operation fails.
+    pub async fn tick<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        now_ms: u32,
+    ) -> Result<bool, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        self.tick_with(tca, now_ms, |_| StepControl::Proceed).await
+    }
+
+    /// Applies the steps that started by `now_ms`, asking `gate` before each one (see
+    /// [`WaveformPlayer::process_with`]).
+    ///
+    /// Read the interlock inputs before the call and decide from them in the gate:
+    ///
+    /// ```ignore
+    /// let pgood = tca.is_high(Pin::P20)?;
+    /// player.tick_with(&mut tca, now_ms, |step| match step {
+    ///     RAIL_2 if !pgood => StepControl::Hold,
+    ///     _ => StepControl::Proceed,
+    /// })?;
+    /// ```
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(true)` if outputs were written, `Ok(false)` if no step started, or an
+    /// `Error` if an I2C bus operation fails.
+    pub async fn tick_with<I2C, D, F>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        now_ms: u32,
+        gate: F,
+    ) -> Result<bool, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+        F: FnMut(usize) -> StepControl,
+    {
+        match self.process_with(now_ms, gate) {
+            Some((on, off)) => {
+                tca.drive_pins(on, off).await?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Plays the table from the first step, waiting out each step with the driver's delay
+    /// source (see [`Tca6424::with_delay`]).
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` after the last step of a [`PlayMode::Once`] table (a looping table
+    /// returns only on error), or an `Error` if an I2C bus operation fails.
+    pub async fn run<I2C, D>(&mut self, tca: &mut Tca6424<I2C, D>) -> Result<(), Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        self.restart();
+        while !self.finished {
+            let step = self.step();
+            tca.drive_pins(step.on, step.off).await?;
+            tca.delay().delay_ms(step.duration_ms).await;
+            if self.index + 1 == self.steps.len() {
+                self.finished = self.mode == PlayMode::Once;
+                self.index = 0;
+            } else {
+                self.index += 1;
+            }
+        }
+        Ok(())
+    }
+}
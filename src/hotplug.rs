@@ -0,0 +1,264 @@
+//! Hot-plug tolerance: offline/online tracking for removable expanders.
+
+use embedded_hal::i2c::ErrorKind;
+
+use crate::errors::Error;
+use crate::recovery::RESTORE_ORDER;
+use crate::registers::{self, Register};
+use crate::{BusAccess, Chip, DelayNs, Port, Tca64xx};
+
+/// Whether the device is considered reachable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LinkState {
+    /// The device is reachable, or hot-plug tracking is disabled.
+    Online,
+    /// The device stopped acknowledging; bus calls fail fast with [`Error::DeviceOffline`].
+    Offline,
+}
+
+/// Settings and counters of automatic re-initialisation.
+#[derive(Debug, Clone, Copy)]
+struct AutoReinit {
+    nack_threshold: u8,
+    budget: u8,
+    remaining: u8,
+    completed: u32,
+}
+
+/// Counts consecutive NACKs and decides when the device goes offline or is re-initialised.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LinkMonitor {
+    /// `None` while hot-plug tracking is disabled.
+    nack_threshold: Option<u8>,
+    consecutive_nacks: u8,
+    offline: bool,
+    /// `None` while automatic re-initialisation is disabled.
+    reinit: Option<AutoReinit>,
+}
+
+impl LinkMonitor {
+    pub(crate) const fn new() -> Self {
+        Self {
+            nack_threshold: None,
+            consecutive_nacks: 0,
+            offline: false,
+            reinit: None,
+        }
+    }
+
+    /// Forgets the NACK streak and the offline state, keeping the settings.
+    pub(crate) fn reset(&mut self) {
+        self.consecutive_nacks = 0;
+        self.offline = false;
+        if let Some(reinit) = &mut self.reinit {
+            reinit.remaining = reinit.budget;
+        }
+    }
+
+    /// Fails fast while the device is offline.
+    pub(crate) fn check<E: core::fmt::Debug>(&self) -> Result<(), Error<E>> {
+        if self.offline {
+            Err(Error::DeviceOffline)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Updates the NACK counter from the outcome of one bus transfer.
+    pub(crate) fn record<T, E: embedded_hal::i2c::Error>(
+        &mut self,
+        result: Result<T, E>,
+    ) -> Result<T, E> {
+        if self.nack_threshold.is_none() && self.reinit.is_none() {
+            return result;
+        }
+        match &result {
+            Ok(_) => {
+                self.consecutive_nacks = 0;
+                if let Some(reinit) = &mut self.reinit {
+                    reinit.remaining = reinit.budget;
+                }
+            }
+            Err(error) if matches!(error.kind(), ErrorKind::NoAcknowledge(_)) => {
+                self.consecutive_nacks = self.consecutive_nacks.saturating_add(1);
+                if matches!(self.nack_threshold, Some(threshold) if self.consecutive_nacks >= threshold)
+                {
+                    self.offline = true;
+                }
+            }
+            Err(_) => {}
+        }
+        result
+    }
+
+    /// Returns `true`, and spends one attempt of the budget, if `error` calls for an
+    /// automatic re-initialisation.
+    fn take_reinit<E: embedded_hal::i2c::Error>(&mut self, error: &Error<E>) -> bool {
+        let Some(reinit) = &mut self.reinit else {
+            return false;
+        };
+        let nack =
+            matches!(error, Error::I2c(e) if matches!(e.kind(), ErrorKind::NoAcknowledge(_)));
+        if !nack || self.consecutive_nacks < reinit.nack_threshold || reinit.remaining == 0 {
+            return false;
+        }
+        reinit.remaining -= 1;
+        true
+    }
+
+    fn state(&self) -> LinkState {
+        if self.offline {
+            LinkState::Offline
+        } else {
+            LinkState::Online
+        }
+    }
+}
+
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C> {
+    /// Enables hot-plug tracking.
+    ///
+    /// After `nack_threshold` consecutive transfers are not acknowledged (at least one), the
+    /// device is marked [`LinkState::Offline`] and every further call fails immediately with
+    /// [`Error::DeviceOffline`] instead of touching the bus, until
+    /// [`Tca64xx::try_reattach`] succeeds. Other bus errors do not count towards the threshold.
+    pub fn enable_hot_plug(&mut self, nack_threshold: u8) {
+        self.link.nack_threshold = Some(nack_threshold.max(1));
+        self.link.consecutive_nacks = 0;
+    }
+
+    /// Disables hot-plug tracking and brings the device back online.
+    pub fn disable_hot_plug(&mut self) {
+        self.link = LinkMonitor {
+            reinit: self.link.reinit,
+            ..LinkMonitor::new()
+        };
+    }
+
+    /// Enables automatic re-initialisation after repeated NACKs.
+    ///
+    /// Once `nack_threshold` consecutive transfers are not acknowledged (at least one), the
+    /// failing call probes the device and writes every cached register back to it before
+    /// returning its error, as [`Tca64xx::try_reattach`] would. An expander that lost power
+    /// briefly is thus configured again without the application noticing more than the one
+    /// failed call. Each attempt spends one of `retry_budget` attempts; a successful transfer
+    /// refills the budget, so a device that stays absent is not probed forever.
+    ///
+    /// With hot-plug tracking enabled, keep `nack_threshold` no higher than the hot-plug
+    /// threshold: an offline device fails fast without NACKs, and only
+    /// [`Tca64xx::try_reattach`] brings it back.
+    pub fn enable_auto_reinit(&mut self, nack_threshold: u8, retry_budget: u8) {
+        self.link.reinit = Some(AutoReinit {
+            nack_threshold: nack_threshold.max(1),
+            budget: retry_budget,
+            remaining: retry_budget,
+            completed: 0,
+        });
+        self.link.consecutive_nacks = 0;
+    }
+
+    /// Disables automatic re-initialisation.
+    pub fn disable_auto_reinit(&mut self) {
+        self.link.reinit = None;
+    }
+
+    /// Returns the number of successful automatic re-initialisations, or `None` if automatic
+    /// re-initialisation is disabled.
+    pub fn auto_reinit_count(&self) -> Option<u32> {
+        self.link.reinit.map(|reinit| reinit.completed)
+    }
+
+    /// Returns the number of automatic re-initialisation attempts left before a transfer
+    /// succeeds again, or `None` if automatic re-initialisation is disabled.
+    pub fn auto_reinit_budget(&self) -> Option<u8> {
+        self.link.reinit.map(|reinit| reinit.remaining)
+    }
+
+    /// Returns the current [`LinkState`].
+    pub fn link_state(&self) -> LinkState {
+        self.link.state()
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca64xx",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Probes an offline device and, if it answers, re-applies the cached configuration.
+    ///
+    /// The probe reads the Configuration registers. A module that was unplugged and
+    /// reinserted comes back at its power-on defaults, so every cached register is then
+    /// written back with [`Tca64xx::restore_from_cache`] and the device goes
+    /// [`LinkState::Online`]. If the probe or the restore fails, the device stays offline.
+    ///
+    /// Calling this on an online device simply re-applies the cache.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` once the device is back online, or an `Error` if an I2C bus
+    /// operation fails.
+    pub async fn try_reattach(&mut self) -> Result<(), Error<I2C::Error>> {
+        let was_offline = self.link.offline;
+        self.link.offline = false;
+        self.link.consecutive_nacks = 0;
+
+        let result = match self.read_configuration().await {
+            Ok(_) => self.restore_from_cache().await,
+            Err(error) => Err(error),
+        };
+        if result.is_err() {
+            self.link.offline |= was_offline;
+        }
+        result
+    }
+    /// Re-initialises the device if `error` completes the NACK streak configured with
+    /// [`Tca64xx::enable_auto_reinit`] and the retry budget allows it.
+    ///
+    /// Runs on the raw transfer primitives, register by register, so it can be called from
+    /// the error path of any bus access without recursing into it.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    pub(crate) async fn auto_reinit(&mut self, error: &Error<I2C::Error>) {
+        if !self.link.take_reinit(error) {
+            return;
+        }
+        let was_offline = self.link.offline;
+        self.link.offline = false;
+
+        let mut configuration = [0u8; 3];
+        let probe_len = self.probe_len();
+        let probe = registers::command_byte(Register::ConfigurationPort0, probe_len > 1);
+        let mut restored = match configuration.get_mut(..probe_len) {
+            Some(buffer) => self.fetch(probe, buffer).await.is_ok(),
+            None => false,
+        };
+        'restore: for kind in RESTORE_ORDER {
+            for port in Port::ALL.into_iter().take(C::PORTS) {
+                if !restored {
+                    break 'restore;
+                }
+                let register = Register::of(kind, port);
+                if let Some(value) = self.cache.get(kind, port)
+                    && C::has(register)
+                {
+                    let command_byte = registers::command_byte(register, false);
+                    restored = self.send(command_byte, &[value]).await.is_ok();
+                }
+            }
+        }
+
+        match (&mut self.link.reinit, restored) {
+            (Some(reinit), true) => reinit.completed = reinit.completed.wrapping_add(1),
+            _ => self.link.offline |= was_offline,
+        }
+    }
+}
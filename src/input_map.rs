@@ -0,0 +1,219 @@
+//! Software mapping from raw input levels to logical input values.
+
+use crate::errors::Error;
+use crate::registers::Register;
+use crate::{BusAccess, DelayNs, Pin, Tca6424};
+
+/// Maps the raw Input Port levels to logical input bits, entirely in software.
+///
+/// Each logical bit `n` takes the level of a source pin (pin `n` unless rerouted with
+/// [`InputMap::route`]) and is optionally inverted. Unlike the Polarity Inversion registers,
+/// this leaves the device configuration untouched, so it works on boards where the polarity
+/// registers are reserved for other purposes or must stay at their defaults.
+///
+/// ```
+/// use tca6424::{InputMap, Pin};
+///
+/// // Both buttons are active-low; the second one moved to P21 on this board revision.
+/// const MAP: InputMap = InputMap::new().invert(0).route(1, Pin::P21).invert(1);
+///
+/// // Raw: P00 low (pressed), P21 low (pressed), everything else high.
+/// let raw = 0x00FF_FFFF & !(1 << 0) & !(1 << 17);
+/// assert_eq!(MAP.apply(raw) & 0b11, 0b11);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InputMap {
+    sources: [u8; 24],
+    invert: u32,
+}
+
+impl InputMap {
+    /// Creates the identity mapping: logical bit `n` is the level of pin `n`.
+    pub const fn new() -> Self {
+        let mut sources = [0u8; 24];
+        let mut rest: &mut [u8] = &mut sources;
+        let mut index = 0;
+        while let [source, tail @ ..] = rest {
+            *source = index;
+            index += 1;
+            rest = tail;
+        }
+        Self { sources, invert: 0 }
+    }
+
+    /// Returns the map with logical bit `logical` taken from `source`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `logical` is 24 or more.
+    pub const fn route(mut self, logical: u8, source: Pin) -> Self {
+        assert!(logical < 24, "logical input out of range");
+        if let Some((_, [slot, ..])) = self.sources.split_at_mut_checked(logical as usize) {
+            *slot = source as u8;
+        }
+        self
+    }
+
+    /// Returns the map with logical bit `logical` inverted (active-low).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `logical` is 24 or more.
+    pub const fn invert(mut self, logical: u8) -> Self {
+        assert!(logical < 24, "logical input out of range");
+        self.invert |= 1 << logical;
+        self
+    }
+
+    /// Returns the map with the inversion mask replaced by `mask` (bit `n` = logical bit `n`).
+    pub const fn with_inverted_mask(mut self, mask: u32) -> Self {
+        self.invert = mask & 0x00FF_FFFF;
+        self
+    }
+
+    /// Returns the inversion mask (bit `n` = logical bit `n`).
+    pub const fn inverted_mask(&self) -> u32 {
+        self.invert
+    }
+
+    /// Returns the source pin of logical bit `logical`, or `None` if `logical` is out of range.
+    pub const fn source(&self, logical: u8) -> Option<Pin> {
+        match self.sources.split_at_checked(logical as usize) {
+            Some((_, [source, ..])) => Pin::from_index(*source),
+            _ => None,
+        }
+    }
+
+    /// Applies the map to raw input levels (bit `n` = pin index `n`).
+    pub const fn apply(&self, raw: u32) -> u32 {
+        let mut logical = 0;
+        let mut sources: &[u8] = &self.sources;
+        let mut index = 0;
+        while let [source, rest @ ..] = sources {
+            logical |= ((raw >> *source) & 1) << index;
+            index += 1;
+            sources = rest;
+        }
+        logical ^ self.invert
+    }
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Logical input bits with asserted/deasserted semantics, returned by
+/// [`Tca6424::read_logical_inputs`].
+///
+/// Bit `n` is logical input `n` of the [`InputMap`]: `1` = asserted, whatever the electrical
+/// polarity of the signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LogicalInputs(u32);
+
+impl LogicalInputs {
+    /// Wraps a logical input word; bits above 23 are ignored.
+    pub const fn new(bits: u32) -> Self {
+        Self(bits & 0x00FF_FFFF)
+    }
+
+    /// Returns `true` if logical input `logical` is asserted.
+    pub const fn is_asserted(&self, logical: u8) -> bool {
+        logical < 24 && self.0 & (1 << logical) != 0
+    }
+
+    /// Returns `true` if logical input `logical` is deasserted.
+    pub const fn is_deasserted(&self, logical: u8) -> bool {
+        logical < 24 && self.0 & (1 << logical) == 0
+    }
+
+    /// Returns the asserted inputs as a 24-bit mask.
+    pub const fn asserted_mask(&self) -> u32 {
+        self.0
+    }
+
+    /// Returns the deasserted inputs as a 24-bit mask.
+    pub const fn deasserted_mask(&self) -> u32 {
+        !self.0 & 0x00FF_FFFF
+    }
+}
+
+impl From<LogicalInputs> for u32 {
+    fn from(inputs: LogicalInputs) -> Self {
+        inputs.0
+    }
+}
+
+impl<I2C, D> Tca6424<I2C, D> {
+    /// Sets the mapping applied by [`Tca6424::get_logical_inputs`].
+    pub fn set_input_map(&mut self, map: InputMap) {
+        self.input_map = map;
+    }
+
+    /// Returns the mapping applied by [`Tca6424::get_logical_inputs`].
+    pub fn input_map(&self) -> &InputMap {
+        &self.input_map
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca6424",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> Tca6424<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Reads all three Input Port registers and returns them through the [`InputMap`].
+    ///
+    /// The read is a single auto-increment transaction. The raw levels still include the
+    /// hardware Polarity Inversion registers; the software map is applied on top.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(u32)` with logical bit `n` in bit `n`, or an `Error` if the I2C bus
+    /// operation fails.
+    pub async fn get_logical_inputs(&mut self) -> Result<u32, Error<I2C::Error>> {
+        let mut raw = [0u8; 4];
+        self.read_registers_ai(Register::InputPort0, &mut raw[..3])
+            .await?;
+        Ok(self.input_map.apply(u32::from_le_bytes(raw)))
+    }
+
+    /// Reads every input and returns whether each logical signal is asserted.
+    ///
+    /// The three Input Port registers are read in one auto-increment transaction. With
+    /// `compensate_inversion`, the hardware Polarity Inversion is undone first, using the
+    /// values in the register cache (see [`Tca6424::inverted_inputs`]; ports whose Polarity
+    /// Inversion register was never written or read through this driver are taken at the
+    /// power-on default, not inverted), so the active
+    /// levels in the [`InputMap`] describe the signals on the pins whatever the device
+    /// configuration. Without it, the map applies on top of the hardware inversion, as for
+    /// [`Tca6424::get_logical_inputs`]. No transfer is made besides the input read.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the [`LogicalInputs`] on success, or an `Error` if the I2C bus operation fails.
+    pub async fn read_logical_inputs(
+        &mut self,
+        compensate_inversion: bool,
+    ) -> Result<LogicalInputs, Error<I2C::Error>> {
+        let mut raw = [0u8; 4];
+        self.read_registers_ai(Register::InputPort0, &mut raw[..3])
+            .await?;
+        let mut raw = u32::from_le_bytes(raw);
+        if compensate_inversion {
+            raw ^= self.inverted_inputs();
+        }
+        Ok(LogicalInputs::new(self.input_map.apply(raw)))
+    }
+}
@@ -0,0 +1,178 @@
+//! Rate limiting of interrupt-triggered input reads, and interrupt source detection.
+
+use crate::errors::Error;
+use crate::registers::Register;
+use crate::{
+    Activity, BusAccess, DelayNs, InputChanges, InputWatcher, Port, PortActivity, Tca6424,
+};
+
+/// Watcher behind [`Tca6424::get_interrupt_sources`]: every pin, no baseline yet.
+pub(crate) const INTERRUPT_SOURCES: InputWatcher = InputWatcher::new()
+    .with_port(Port::Port0)
+    .with_port(Port::Port1)
+    .with_port(Port::Port2);
+
+/// Coalesces bursts of INT assertions into input reads spaced at least `min_interval_ms` apart.
+///
+/// Call [`InterruptThrottle::notify`] from the INT edge (an ISR or a `wait_for_falling_edge`
+/// loop) and service it with [`Tca6424::service_interrupt`]. Any number of interrupts arriving
+/// within one interval result in a single read, so a chattering input cannot saturate the bus
+/// or starve other devices on it. Because the TCA6424 keeps INT asserted until the inputs are
+/// read, no change is lost: the pending flag stays set until the deferred read happens.
+///
+/// Timestamps are a free-running millisecond counter and may wrap around.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InterruptThrottle {
+    min_interval_ms: u32,
+    last_read_ms: Option<u32>,
+    pending: bool,
+}
+
+impl InterruptThrottle {
+    /// Creates a throttle allowing at most one read per `min_interval_ms` milliseconds.
+    pub const fn new(min_interval_ms: u32) -> Self {
+        Self {
+            min_interval_ms,
+            last_read_ms: None,
+            pending: false,
+        }
+    }
+
+    /// Records an interrupt. Interrupts arriving before the pending read coalesce into it.
+    pub fn notify(&mut self) {
+        self.pending = true;
+    }
+
+    /// Returns `true` if an interrupt is waiting for its read.
+    pub const fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    /// Returns the configured minimum interval between reads.
+    pub const fn min_interval_ms(&self) -> u32 {
+        self.min_interval_ms
+    }
+
+    /// Returns the number of milliseconds until the pending read is allowed, or `None` if no
+    /// interrupt is pending. Useful to arm a timer instead of polling.
+    pub fn wait_ms(&self, now_ms: u32) -> Option<u32> {
+        if !self.pending {
+            return None;
+        }
+        match self.last_read_ms {
+            Some(last) => Some(
+                self.min_interval_ms
+                    .saturating_sub(now_ms.wrapping_sub(last)),
+            ),
+            None => Some(0),
+        }
+    }
+
+    /// Returns `true`, and starts a new interval, if a read is due at `now_ms`.
+    pub fn poll(&mut self, now_ms: u32) -> bool {
+        if self.wait_ms(now_ms) != Some(0) {
+            return false;
+        }
+        self.pending = false;
+        self.last_read_ms = Some(now_ms);
+        true
+    }
+}
+
+impl<I2C, D> Tca6424<I2C, D> {
+    /// Returns the rising and falling transitions [`Tca6424::get_interrupt_sources`] has
+    /// reported on `port`, for quick "is anything happening on this connector" checks.
+    ///
+    /// The counters cover pins with their interrupt enabled and reset with
+    /// [`Tca6424::take_port_activity`] or [`Tca6424::set_address`]. Watchers fed by
+    /// [`Tca6424::poll_input_events`] keep their own counters
+    /// ([`InputWatcher::port_activity`]).
+    pub fn port_activity(&self, port: Port) -> PortActivity {
+        self.interrupt_inputs.port_activity(port)
+    }
+
+    /// Returns the transitions counted on `port` (see [`Tca6424::port_activity`]) and resets
+    /// its counters.
+    pub fn take_port_activity(&mut self, port: Port) -> PortActivity {
+        self.interrupt_inputs.take_port_activity(port)
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca6424",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> Tca6424<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Reads the inputs if `throttle` has a pending interrupt whose interval has elapsed.
+    ///
+    /// The three Input Port registers are read in one auto-increment transaction, which also
+    /// releases the INT line.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Some(inputs))` with the 24 pin levels (bit `n` = pin index `n`) if a read
+    /// was performed, `Ok(None)` if no read was due, or an `Error` if the I2C bus operation
+    /// fails. A failed read leaves the interrupt pending.
+    pub async fn service_interrupt(
+        &mut self,
+        throttle: &mut InterruptThrottle,
+        now_ms: u32,
+    ) -> Result<Option<u32>, Error<I2C::Error>> {
+        if !throttle.poll(now_ms) {
+            return Ok(None);
+        }
+        let mut values = [0u8; 4];
+        let scope = self.enter_activity(Activity::ServiceInterrupt);
+        let result = self
+            .read_registers_ai(Register::InputPort0, &mut values[..3])
+            .await;
+        if let Err(error) = self.leave_activity(scope, result) {
+            throttle.notify();
+            return Err(error);
+        }
+        Ok(Some(u32::from_le_bytes(values)))
+    }
+
+    /// Reads the inputs and returns the pins that likely asserted INT.
+    ///
+    /// The TCA6424 has no interrupt status register: INT only says that some input differs
+    /// from its value at the last read. This method reads the three Input Port registers in
+    /// one auto-increment transaction (releasing INT), compares them with the image taken by
+    /// its previous call, and keeps the changed pins whose interrupt is enabled according to
+    /// [`Tca6424::enabled_interrupts`]. Pins whose cached Polarity Inversion changed since
+    /// the previous call flipped without asserting INT and are reported as a full resync (see
+    /// [`InputWatcher::update_with_inversion`]). The first call, and the first call after
+    /// [`Tca6424::set_address`], only takes the image and reports nothing. Other input reads
+    /// also release INT but do not update the image, so a change seen by them is still
+    /// reported here.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the likely interrupt sources with their new levels, or an `Error` if the I2C
+    /// bus operation fails (the image is kept).
+    pub async fn get_interrupt_sources(&mut self) -> Result<InputChanges, Error<I2C::Error>> {
+        let mut values = [0u8; 4];
+        let scope = self.enter_activity(Activity::ServiceInterrupt);
+        let result = self
+            .read_registers_ai(Register::InputPort0, &mut values[..3])
+            .await;
+        self.leave_activity(scope, result)?;
+        let enabled = self.enabled_interrupts();
+        let inversion = self.inverted_inputs();
+        Ok(self.interrupt_inputs.update_with_inversion(
+            u32::from_le_bytes(values),
+            enabled,
+            inversion,
+        ))
+    }
+}
@@ -0,0 +1,154 @@
+//! Device-independent I/O expander interface.
+
+use crate::errors::Error;
+use crate::registers::Register;
+use crate::{BusAccess, DelayNs, PinDirection, Port, RegisterKind, Tca6424};
+
+/// A mask-based interface to a GPIO expander, in the style of `port-expander`'s `PortDriver`.
+///
+/// Pins are addressed by bit masks where bit `n` is pin index `n`, so a single call can touch
+/// any set of pins. Generic keypad scanners, LED drivers and similar code can be written
+/// against this trait and run on any expander implementing it.
+#[cfg(not(feature = "async"))]
+pub trait IoExpander {
+    /// Error returned by bus operations.
+    type Error;
+
+    /// Number of pins provided by the device.
+    const PIN_COUNT: u8;
+
+    /// Sets the direction of every pin in `mask`.
+    fn set_direction(&mut self, mask: u32, direction: PinDirection) -> Result<(), Self::Error>;
+
+    /// Drives the pins in `mask_high` high and the pins in `mask_low` low. Other pins keep
+    /// their output level.
+    fn write(&mut self, mask_high: u32, mask_low: u32) -> Result<(), Self::Error>;
+
+    /// Returns the input levels of the pins in `mask` (other bits are zero).
+    fn read(&mut self, mask: u32) -> Result<u32, Self::Error>;
+
+    /// Returns the output latch levels of the pins in `mask` (other bits are zero).
+    fn read_outputs(&mut self, mask: u32) -> Result<u32, Self::Error>;
+}
+
+/// A mask-based interface to a GPIO expander, in the style of `port-expander`'s `PortDriver`.
+///
+/// Pins are addressed by bit masks where bit `n` is pin index `n`, so a single call can touch
+/// any set of pins. Generic keypad scanners, LED drivers and similar code can be written
+/// against this trait and run on any expander implementing it.
+#[cfg(feature = "async")]
+#[allow(async_fn_in_trait)]
+pub trait IoExpander {
+    /// Error returned by bus operations.
+    type Error;
+
+    /// Number of pins provided by the device.
+    const PIN_COUNT: u8;
+
+    /// Sets the direction of every pin in `mask`.
+    async fn set_direction(
+        &mut self,
+        mask: u32,
+        direction: PinDirection,
+    ) -> Result<(), Self::Error>;
+
+    /// Drives the pins in `mask_high` high and the pins in `mask_low` low. Other pins keep
+    /// their output level.
+    async fn write(&mut self, mask_high: u32, mask_low: u32) -> Result<(), Self::Error>;
+
+    /// Returns the input levels of the pins in `mask` (other bits are zero).
+    async fn read(&mut self, mask: u32) -> Result<u32, Self::Error>;
+
+    /// Returns the output latch levels of the pins in `mask` (other bits are zero).
+    async fn read_outputs(&mut self, mask: u32) -> Result<u32, Self::Error>;
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca6424",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> Tca6424<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Replaces the bits selected by `mask` in every port of a register family, touching only
+    /// the ports that have selected bits (one read-modify-write each).
+    async fn modify_family(
+        &mut self,
+        kind: RegisterKind,
+        mask: u32,
+        value: u32,
+    ) -> Result<(), Error<I2C::Error>> {
+        for port in Port::ALL {
+            let port_mask = port.byte_of(mask);
+            if port_mask == 0 {
+                continue;
+            }
+            self.write_masked(kind, port, port_mask, port.byte_of(value))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Reads the ports of a register family that have bits in `mask`, returning only those bits.
+    async fn read_family(
+        &mut self,
+        kind: RegisterKind,
+        mask: u32,
+    ) -> Result<u32, Error<I2C::Error>> {
+        let mut values = 0u32;
+        for port in Port::ALL {
+            if port.byte_of(mask) != 0 {
+                let value = match kind {
+                    RegisterKind::Output => self.read_output_register(port).await?,
+                    _ => self.read_register(Register::of(kind, port)).await?,
+                };
+                values |= u32::from(value) << (port as u32 * 8);
+            }
+        }
+        Ok(values & mask)
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca6424",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> IoExpander for Tca6424<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    type Error = Error<I2C::Error>;
+
+    const PIN_COUNT: u8 = 24;
+
+    async fn set_direction(
+        &mut self,
+        mask: u32,
+        direction: PinDirection,
+    ) -> Result<(), Self::Error> {
+        let value = match direction {
+            PinDirection::Input => mask,
+            PinDirection::Output => 0,
+        };
+        self.modify_family(RegisterKind::Configuration, mask, value)
+            .await
+    }
+
+    async fn write(&mut self, mask_high: u32, mask_low: u32) -> Result<(), Self::Error> {
+        self.modify_family(RegisterKind::Output, mask_high | mask_low, mask_high)
+            .await
+    }
+
+    async fn read(&mut self, mask: u32) -> Result<u32, Self::Error> {
+        self.read_family(RegisterKind::Input, mask).await
+    }
+
+    async fn read_outputs(&mut self, mask: u32) -> Result<u32, Self::Error> {
+        self.read_family(RegisterKind::Output, mask).await
+    }
+}
@@ -0,0 +1,136 @@
+//! Human-readable pin labels for diagnostics.
+
+use core::fmt;
+
+use crate::{Pin, Tca6424};
+
+/// A table of `&'static str` labels, one optional label per pin.
+///
+/// Labels are purely diagnostic: they replace pin names such as `P12` in `Display` output,
+/// so logs read `RELAY_MAIN: 0->1` instead of `P12: 0->1`. The table can be built in a
+/// `const` or `static` and attached to a driver with [`Tca6424::set_pin_labels`](crate::Tca6424::set_pin_labels).
+///
+/// ```
+/// use tca6424::{Pin, PinLabels};
+///
+/// static LABELS: PinLabels = PinLabels::new()
+///     .with(Pin::P00, "STATUS_LED")
+///     .with(Pin::P12, "RELAY_MAIN");
+///
+/// assert_eq!(LABELS.get(Pin::P12), Some("RELAY_MAIN"));
+/// assert_eq!(LABELS.find("STATUS_LED"), Some(Pin::P00));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PinLabels {
+    labels: [Option<&'static str>; 24],
+}
+
+impl PinLabels {
+    /// Creates a table with no labels.
+    pub const fn new() -> Self {
+        Self { labels: [None; 24] }
+    }
+
+    /// Returns the table with `pin` labelled `label`.
+    pub const fn with(mut self, pin: Pin, label: &'static str) -> Self {
+        *pin.entry_mut(&mut self.labels) = Some(label);
+        self
+    }
+
+    /// Sets or replaces the label of `pin`.
+    pub fn set(&mut self, pin: Pin, label: &'static str) {
+        *pin.entry_mut(&mut self.labels) = Some(label);
+    }
+
+    /// Removes the label of `pin`.
+    pub fn clear(&mut self, pin: Pin) {
+        *pin.entry_mut(&mut self.labels) = None;
+    }
+
+    /// Returns the label of `pin`, if any.
+    pub const fn get(&self, pin: Pin) -> Option<&'static str> {
+        *pin.entry(&self.labels)
+    }
+
+    /// Returns the first pin labelled `label`.
+    pub fn find(&self, label: &str) -> Option<Pin> {
+        Pin::ALL
+            .into_iter()
+            .find(|&pin| *pin.entry(&self.labels) == Some(label))
+    }
+
+    /// Returns a `Display`able name for `pin`: its label, or its pin name if unlabelled.
+    pub const fn name(&self, pin: Pin) -> PinName {
+        PinName {
+            pin,
+            label: self.get(pin),
+        }
+    }
+}
+
+impl Default for PinLabels {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The name of a pin for diagnostic output: its label if it has one, `P00`-`P27` otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PinName {
+    pin: Pin,
+    label: Option<&'static str>,
+}
+
+impl PinName {
+    /// Returns the name of `pin` without a label.
+    pub const fn unlabelled(pin: Pin) -> Self {
+        Self { pin, label: None }
+    }
+
+    /// Returns the named pin.
+    pub const fn pin(&self) -> Pin {
+        self.pin
+    }
+
+    /// Returns the label, if the pin has one.
+    pub const fn label(&self) -> Option<&'static str> {
+        self.label
+    }
+}
+
+impl fmt::Display for PinName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.label {
+            Some(label) => f.write_str(label),
+            None => write!(f, "{}", self.pin),
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for PinName {
+    fn format(&self, f: defmt::Formatter) {
+        match self.label {
+            Some(label) => defmt::write!(f, "{=str}", label),
+            None => defmt::write!(f, "{}", self.pin),
+        }
+    }
+}
+
+impl<I2C, D> Tca6424<I2C, D> {
+    /// Attaches a label table used by [`Tca6424::pin_name`] and diagnostic output.
+    pub fn set_pin_labels(&mut self, labels: &'static PinLabels) {
+        self.labels = Some(labels);
+    }
+
+    /// Returns the attached label table, if any.
+    pub fn pin_labels(&self) -> Option<&'static PinLabels> {
+        self.labels
+    }
+
+    /// Returns the diagnostic name of `pin`, using the attached label table if there is one.
+    pub fn pin_name(&self, pin: Pin) -> PinName {
+        self.labels
+            .map_or(PinName::unlabelled(pin), |labels| labels.name(pin))
+    }
+}
@@ -7,10 +7,72 @@
 //!
 //! ## Features
 //!
-//! - `default`: Enables the `std` feature.
+//! - `default`: Enables `polarity`, `interrupt-mask`, `auto-increment`, `diagnostics`,
+//!   `operations`, `bank`, `helpers`, `fail-safe`, `hot-plug`, `retry`, `fallback` and
+//!   `error-context`.
 //! - `std`: Enables standard library support (for `std::error::Error` implementation).
+//!   Implies `alloc`.
+//! - `alloc`: Enables conveniences that allocate, such as `EventDispatcher` (boxed event
+//!   callbacks) and `InputChanges::to_vec`. Without it the crate never allocates: every
+//!   buffer is a fixed-size array or a `heapless` collection.
 //! - `async`: Enables asynchronous support using `embedded-hal-async`.
 //! - `defmt`: Enables `defmt::Format` implementations for data types and errors.
+//! - `portable-atomic`: Enables `AtomicOutputs`, an ISR-safe deferred output image, and
+//!   `AtomicInputs`, a background-refreshed input image readable without the bus.
+//! - `nb`: Enables `NbTca6424`, an `nb`-style non-blocking front-end (sync mode only).
+//! - `tca9548`: Enables `MuxChannel`, for expanders behind a TCA9548 I2C multiplexer.
+//! - `tca6416`: Enables `Tca6416`, the driver with the register map of the 16-bit TCA6416A
+//!   (`Tca6416Chip`).
+//! - `port-expander`: Enables the `port_expander` module, a pin API following the
+//!   `port-expander` crate conventions (sync mode only).
+//! - `shared-bus`: Enables the `shared_bus` module, an adapter for `embedded-hal` 0.2 buses
+//!   such as `shared-bus` proxies (sync mode only).
+//! - `shared`: Enables `SharedTca6424`, a driver shared between tasks behind an
+//!   `embassy-sync` mutex of the user's choice, and its `Outputs`/`Inputs` role handles.
+//! - `embassy`: Enables `SharedI2cBus`, `SharedI2cDevice` and `Tca6424::new_on_shared_bus`,
+//!   for buses shared with `embassy-embedded-hal`.
+//! - `single-task`: Asserts that the driver is only used from one task: enabling it together
+//!   with `shared`, `embassy` or `shared-bus` (for example through feature unification in a
+//!   workspace) fails to compile. See [Single-task use](crate#single-task-use).
+//! - `polarity`: Enables the Polarity Inversion register methods.
+//! - `interrupt-mask`: Enables the Interrupt Mask register methods.
+//! - `auto-increment`: Enables the public `*_ai` multi-port methods.
+//! - `diagnostics`: Enables register snapshots, health checks, the bus scan and the capture
+//!   of the state a device was found in at startup (`Tca6424::new_checked_with_capture`).
+//! - `capture`: Records the last N bus transfers into a user-provided ring buffer
+//!   (`Tca6424::enable_capture`, `Tca6424::trace`), for attaching bus history to bug reports.
+//! - `dry-run`: Records the transfers operations would issue instead of sending them
+//!   (`Tca6424::begin_dry_run`, `Tca6424::finish_dry_run`), for validating board
+//!   configurations in host tests and code review.
+//! - `stats`: Counts output toggles and input changes per pin (`Tca6424::pin_stats`), for
+//!   relay wear estimates and chatty-input detection.
+//! - `tracing`: Emits a `tracing` event for every register transfer (register, port, bytes,
+//!   latency), for hosted users correlating expander activity with other telemetry.
+//!   Implies `std`.
+//! - `embassy-time`: Adds `*_now` helper variants reading `embassy_time::Instant`,
+//!   `EmbassyClock` and `Tca6424::with_embassy_delay`. Implies `helpers`.
+//! - `config-files`: Adds TOML and JSON import/export of `BoardConfig` with pin names
+//!   (`BoardConfig::to_toml`, `BoardConfig::from_json`, ...) and `serde` support for it, so
+//!   host tools and test rigs share board files with firmware built from `board!`. Implies
+//!   `std` and `helpers`.
+//! - `cli`: Builds `tca6424-cli`, an interactive bring-up shell for Linux I2C buses
+//!   (`dir P04 out`, `set P04 high`, `watch port2`, `dump`), and the `cli` module behind it
+//!   (sync mode only).
+//! - `testing`: Enables the `testing` module: `RegisterModel`, a simulated device, and
+//!   invariant checks over it for property tests. Implies `diagnostics`.
+//! - `operations`: Enables `Operation`, stepwise execution and `OpQueue`.
+//! - `bank`: Enables `ExpanderBank` for driving several expanders together, `BankPin` for
+//!   addressing their pins as one set, and `ScanScheduler` for polling their inputs under a
+//!   per-cycle bus budget.
+//! - `helpers`: Enables higher-level helpers such as `LitButton` and `Debouncer`.
+//! - `fail-safe`: Enables the fail-safe latch (`Tca6424::set_fail_safe`), which drives a safe
+//!   output frame after a hazardous error.
+//! - `hot-plug`: Enables offline tracking (`Tca6424::enable_hot_plug`,
+//!   `Tca6424::try_reattach`) and automatic re-initialisation after repeated NACKs.
+//! - `retry`: Enables retries of transfers that fail on a busy bus (`RetryPolicy`).
+//! - `fallback`: Enables fallback values for failed input reads (`InputFallback`).
+//! - `error-context`: Records what the driver was doing when an error occurred
+//!   (`Tca6424::last_error_context`).
 //!
 //! ## Usage
 //!
@@ -73,6 +135,30 @@
 //!
 //! For a real hardware example, see `examples/stm32g4`.
 //!
+//! ### Bus access
+//!
+//! The driver owns whatever it is given as `I2C`, which can be anything implementing
+//! [`BusAccess`], that is any `I2c` implementor:
+//!
+//! - `&mut bus`, borrowing a bus for the lifetime of the driver,
+//! - the bus itself, moved into the driver and returned by [`Tca6424::into_inner`],
+//! - a bus-sharing device such as `embedded_hal_bus::i2c::RefCellDevice` or
+//!   `embassy_embedded_hal::shared_bus::asynch::i2c::I2cDevice`, to share the bus with other
+//!   drivers.
+//!
+//! ### Single-task use
+//!
+//! A [`Tca6424`] owned by one task is the fast path: every method takes `&mut self`, so calls
+//! run one after the other in program order, and the register cache is a plain field read
+//! and written without locks. The sharing layers (`SharedTca6424` and its role handles,
+//! `SharedI2cBus`, the `shared_bus` adapter) are separate types behind their own features;
+//! they wrap the driver and add nothing to it when unused.
+//!
+//! To make the single-owner design a checked property of a firmware build, enable the
+//! `single-task` feature: it refuses to compile together with the `shared`, `embassy` and
+//! `shared-bus` features, so no mutex can end up in the build through another crate of the
+//! dependency graph.
+//!
 //! ## License
 //!
 //! This project is licensed under either of
@@ -101,20 +187,216 @@
 //! See [PLAN.md](PLAN.md)
 
 #![cfg_attr(not(feature = "std"), no_std)]
+// The driver runs in control paths where a panic takes down the firmware: library code
+// indexes and slices only through checked accessors, so every failure is an `Err`.
+#![deny(
+    clippy::indexing_slicing,
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::unreachable,
+    clippy::todo,
+    clippy::unimplemented
+)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(all(
+    feature = "single-task",
+    any(feature = "shared", feature = "embassy", feature = "shared-bus")
+))]
+compile_error!(
+    "the `single-task` feature excludes the sharing layers: disable `shared`, `embassy` and `shared-bus`"
+);
+
+use core::marker::PhantomData;
 
 #[cfg(not(feature = "async"))]
-use embedded_hal::i2c::I2c;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::Operation as I2cOperation;
 #[cfg(feature = "async")]
-use embedded_hal_async::i2c::I2c;
+use embedded_hal_async::delay::DelayNs;
 
+mod address;
+#[cfg(feature = "portable-atomic")]
+mod atomic_inputs;
+#[cfg(feature = "portable-atomic")]
+mod atomic_outputs;
+#[cfg(feature = "bank")]
+mod bank;
+mod bounds;
+mod bus;
+mod cache;
+#[cfg(feature = "capture")]
+mod capture;
+mod channels;
+mod chip;
+mod claims;
+mod clock;
+mod context;
+#[cfg(all(feature = "cli", not(feature = "async")))]
+pub mod cli;
+#[cfg(feature = "config-files")]
+mod config_file;
 mod data_types;
+mod direction_hook;
+#[cfg(feature = "dry-run")]
+mod dry_run;
+#[cfg(feature = "embassy")]
+mod embassy;
 pub mod errors;
+#[cfg(feature = "examples-support")]
+pub mod examples_support;
+mod events;
+mod expander;
+#[cfg(feature = "fail-safe")]
+mod failsafe;
+mod fallback;
+mod fanout;
+#[cfg(feature = "diagnostics")]
+mod health;
+#[cfg(feature = "helpers")]
+mod helpers;
+#[cfg(feature = "hot-plug")]
+mod hotplug;
+mod input_map;
+mod interrupt;
+mod io_expander;
+mod labels;
+mod monitor;
+#[cfg(feature = "tca9548")]
+mod mux;
+#[cfg(all(feature = "nb", not(feature = "async")))]
+mod nonblocking;
+#[cfg(feature = "operations")]
+mod operation;
+mod output_cache;
+mod plan;
+mod polarity;
+#[cfg(feature = "operations")]
+mod queue;
+#[cfg(all(feature = "port-expander", not(feature = "async")))]
+pub mod port_expander;
+mod recovery;
 mod registers;
+mod replay;
+mod retry;
+#[cfg(feature = "shared")]
+mod roles;
+#[cfg(feature = "diagnostics")]
+mod scan;
+mod service;
+#[cfg(feature = "shared")]
+mod shared;
+#[cfg(all(feature = "shared-bus", not(feature = "async")))]
+pub mod shared_bus;
+#[cfg(feature = "diagnostics")]
+mod snapshot;
+mod sleep;
+#[cfg(feature = "diagnostics")]
+mod startup;
+#[cfg(feature = "stats")]
+mod stats;
+mod stagger;
+mod strategy;
+mod telemetry;
+mod transaction;
+#[cfg(feature = "tca6416")]
+mod tca6416;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "embassy-time")]
+mod timed;
+mod timeout;
+mod verified;
+mod write_only;
+mod yielding;
 
 use crate::errors::Error;
+pub use address::is_reserved_address;
+#[cfg(feature = "portable-atomic")]
+pub use atomic_inputs::AtomicInputs;
+#[cfg(feature = "portable-atomic")]
+pub use atomic_outputs::{AtomicOutputs, CoalesceWindow, Coalescer};
+#[cfg(feature = "bank")]
+pub use bank::{BankInputs, BankPin, ExpanderBank, ScanScheduler};
+pub use bounds::{CacheMode, TransferBound};
+pub use bus::BusAccess;
+pub use cache::RegisterCache;
+#[cfg(feature = "capture")]
+pub use capture::{CAPTURED_BYTES, TransferDirection, TransferOutcome, TransferRecord};
+pub use channels::ChannelMap;
+#[cfg(feature = "tca6416")]
+pub use chip::Tca6416Chip;
+pub use chip::{Chip, Tca6424Chip};
+pub use claims::{MaskClaim, PinClaim};
+pub use clock::{ManualClock, TimeSource};
+pub use context::Activity;
+#[cfg(feature = "error-context")]
+pub use context::ErrorContext;
+#[cfg(feature = "config-files")]
+pub use config_file::ConfigFileError;
 pub use data_types::*;
+#[cfg(feature = "dry-run")]
+pub use dry_run::{DRY_RUN_CAPACITY, DryRunPlan, PlannedAccess, PlannedTransfer};
+#[cfg(feature = "embassy")]
+pub use embassy::{SharedI2cBus, SharedI2cDevice};
+#[cfg(feature = "alloc")]
+pub use events::EventDispatcher;
+pub use events::{InputChanges, InputEvent, InputWatcher, PortActivity};
+pub use expander::Expander;
+#[cfg(feature = "fail-safe")]
+pub use failsafe::{FailSafeFault, FailSafePolicy, FailSafeTriggers};
+#[cfg(feature = "fallback")]
+pub use fallback::InputFallback;
+pub use fanout::{EventFanout, SubscriberId};
+#[cfg(feature = "diagnostics")]
+pub use health::HealthStatus;
+#[cfg(feature = "helpers")]
+pub use helpers::*;
+#[cfg(feature = "hot-plug")]
+pub use hotplug::LinkState;
+pub use input_map::{InputMap, LogicalInputs};
+pub use interrupt::InterruptThrottle;
+pub use io_expander::IoExpander;
+pub use labels::{PinLabels, PinName};
+pub use monitor::{InputMonitor, NoInt};
+#[cfg(feature = "tca9548")]
+pub use mux::{MuxChannel, SelectChannel, TCA9548_DEFAULT_ADDRESS, Tca9548};
+#[cfg(all(feature = "nb", not(feature = "async")))]
+pub use nonblocking::NbTca6424;
+#[cfg(feature = "operations")]
+pub use operation::{InFlight, Operation, StepResult};
+pub use plan::{BusCost, PlannedWrite, estimated_transaction_time};
+#[cfg(feature = "operations")]
+pub use queue::{OpQueue, Priority, ServiceResult};
+pub use registers::{AUTO_INCREMENT, Register, command_byte, register_for};
+pub use replay::{InputReplay, ReplayStep};
+#[cfg(feature = "retry")]
+pub use retry::RetryPolicy;
+#[cfg(feature = "shared")]
+pub use roles::{Inputs, Outputs};
+#[cfg(feature = "diagnostics")]
+pub use scan::{AddressStatus, ScanReport, TCA6424_ADDRESSES, scan};
+pub use service::{EventScan, ServiceTask, TransactionBudget};
+#[cfg(feature = "shared")]
+pub use shared::SharedTca6424;
+pub use sleep::{SleepAction, SleepProfile};
+#[cfg(feature = "diagnostics")]
+pub use snapshot::*;
+#[cfg(feature = "stats")]
+pub use stats::PinStats;
+pub use strategy::{ReadSequence, TransferStrategy};
+#[cfg(feature = "tca6416")]
+pub use tca6416::{TCA6416_DEFAULT_ADDRESS, TCA6416_PORTS, Tca6416};
+#[cfg(feature = "embassy-time")]
+pub use timed::{EmbassyClock, now_ms};
+pub use timeout::NoDelay;
+pub use transaction::ConfigTransaction;
+#[cfg(feature = "async")]
+pub use yielding::yield_now;
 
-/// Default I2C address for the TCA6424 (when ADDR pins are tied low).
 /// Default I2C address for the TCA6424 (when ADDR pins are tied low).
 /// According to PLAN.md and datasheet Table 3 (ADDR=L).
 pub const DEFAULT_ADDRESS: u8 = 0x22;
@@ -124,37 +406,299 @@ pub const DEFAULT_ADDRESS: u8 = 0x22;
 /// This struct provides methods to interact with the TCA6424 via an I2C bus,
 /// allowing control over pin direction, output state, input state, and polarity inversion.
 ///
-/// It is generic over the I2C bus implementation, supporting both synchronous
-/// and asynchronous `embedded-hal` traits via `maybe-async-cfg`.
-pub struct Tca6424<'a, I2C> {
-    i2c: &'a mut I2C,
+/// It is generic over the I2C bus implementation (see [Bus access](crate#bus-access)),
+/// supporting both synchronous and asynchronous `embedded-hal` traits via `maybe-async-cfg`,
+/// and over an optional stored `DelayNs` source `D` (see [`Tca6424::with_delay`]).
+pub type Tca6424<I2C, D = NoDelay> = Tca64xx<I2C, D, Tca6424Chip>;
+
+/// Driver for an expander of the TCA64xx family, whose register map is selected by the
+/// [`Chip`] `C`; use it through its aliases [`Tca6424`] and, with the `tca6416` feature,
+/// `Tca6416`.
+///
+/// The pin and port access, the register cache and the transfer machinery are shared by
+/// every part. The methods spanning all three ports of the TCA6424, such as the
+/// auto-increment and Interrupt Mask methods and the helpers built on them, are implemented
+/// for [`Tca6424`] only.
+pub struct Tca64xx<I2C, D = NoDelay, C = Tca6424Chip> {
+    i2c: I2C,
+    delay: D,
+    #[cfg(feature = "async")]
+    timeout_us: Option<u32>,
+    transfer_gap_us: u32,
     address: u8,
+    cache: RegisterCache,
+    #[cfg(feature = "hot-plug")]
+    link: hotplug::LinkMonitor,
+    labels: Option<&'static PinLabels>,
+    claims: claims::ClaimRegistry,
+    input_map: InputMap,
+    channels: Option<ChannelMap>,
+    #[cfg(feature = "fail-safe")]
+    fail_safe: failsafe::FailSafe,
+    sleep: Option<sleep::SleepState>,
+    interrupt_inputs: InputWatcher,
+    #[cfg(feature = "capture")]
+    capture: capture::Capture,
+    #[cfg(feature = "stats")]
+    stats: stats::Stats,
+    #[cfg(feature = "dry-run")]
+    dry_run: Option<dry_run::DryRun>,
+    #[cfg(feature = "diagnostics")]
+    startup: Option<RegisterSnapshot>,
+    #[cfg(feature = "fallback")]
+    fallback: fallback::FallbackState,
+    write_only: bool,
+    cached_output_reads: bool,
+    transfer: strategy::TransferState,
+    #[cfg(feature = "retry")]
+    retry: retry::RetryState,
+    #[cfg(not(feature = "async"))]
+    yield_fn: Option<fn()>,
+    #[cfg(feature = "async")]
+    yield_between_transfers: bool,
+    direction_hook: Option<fn(Pin, PinDirection)>,
+    #[cfg(feature = "error-context")]
+    context: context::ContextState,
+    transactions: u32,
+    chip: PhantomData<C>,
+}
+
+impl<I2C: embedded_hal::i2c::ErrorType> Tca6424<I2C> {
+    /// Creates a new TCA6424 driver instance.
+    ///
+    /// This function is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `i2c` - The I2C bus, implementing `embedded-hal::i2c::I2c` (sync) or
+    ///   `embedded-hal-async::i2c::I2c` (async): a `&mut` reference, an owned bus or a
+    ///   bus-sharing device.
+    /// * `address` - The I2C slave address of the TCA6424 device.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Self)` on success, or an `Error` if the I2C bus operation fails.
+    pub fn new(i2c: I2C, address: u8) -> Result<Self, Error<I2C::Error>> {
+        Ok(Self::for_chip(i2c, address))
+    }
+}
+
+impl<I2C, C: Chip> Tca64xx<I2C, NoDelay, C> {
+    /// Creates a driver for the part `C` at `address`. No bus transfer is made.
+    fn for_chip(i2c: I2C, address: u8) -> Self {
+        Self {
+            i2c,
+            address,
+            cache: RegisterCache::new(),
+            #[cfg(feature = "hot-plug")]
+            link: hotplug::LinkMonitor::new(),
+            labels: None,
+            claims: claims::ClaimRegistry::new(),
+            input_map: InputMap::new(),
+            channels: None,
+            #[cfg(feature = "fail-safe")]
+            fail_safe: failsafe::FailSafe::new(),
+            sleep: None,
+            interrupt_inputs: interrupt::INTERRUPT_SOURCES,
+            #[cfg(feature = "capture")]
+            capture: capture::Capture::new(),
+            #[cfg(feature = "stats")]
+            stats: stats::Stats::new(),
+            #[cfg(feature = "dry-run")]
+            dry_run: None,
+            #[cfg(feature = "diagnostics")]
+            startup: None,
+            #[cfg(feature = "fallback")]
+            fallback: fallback::FallbackState::default(),
+            write_only: false,
+            cached_output_reads: false,
+            transfer: strategy::TransferState::default(),
+            #[cfg(feature = "retry")]
+            retry: retry::RetryState::default(),
+            #[cfg(not(feature = "async"))]
+            yield_fn: None,
+            #[cfg(feature = "async")]
+            yield_between_transfers: false,
+            direction_hook: None,
+            #[cfg(feature = "error-context")]
+            context: context::ContextState::default(),
+            transactions: 0,
+            chip: PhantomData,
+            delay: NoDelay,
+            #[cfg(feature = "async")]
+            timeout_us: None,
+            transfer_gap_us: 0,
+        }
+    }
+}
+
+impl<I2C, D, C> Tca64xx<I2C, D, C> {
+    /// Consumes the driver and returns the I2C bus it was created with.
+    pub fn into_inner(self) -> I2C {
+        self.i2c
+    }
+
+    /// Returns the shadow cache of the register values written by this driver.
+    ///
+    /// See [`RegisterCache`] for the exact semantics.
+    pub fn cache(&self) -> &RegisterCache {
+        &self.cache
+    }
+
+    /// Returns the I2C address the driver talks to.
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// Points the driver at the device at `address`.
+    ///
+    /// The register cache, the hot-plug NACK streak and any saved sleep state describe the
+    /// previous device, so they are cleared. Labels, claims, input map, channels and fail-safe
+    /// policy are kept. No transfer is made; see [`Tca6424::retarget`] to probe the new device
+    /// and give it the previous configuration.
+    pub fn set_address(&mut self, address: u8) {
+        self.forget_device(address);
+        self.cache.clear();
+    }
+
+    /// Points the driver at `address` and clears the state describing the previous device:
+    /// the hot-plug NACK streak, the saved sleep state, the interrupt image and the statistics
+    /// baselines. The register cache is left to the caller.
+    fn forget_device(&mut self, address: u8) {
+        self.address = address;
+        #[cfg(feature = "hot-plug")]
+        self.link.reset();
+        self.sleep = None;
+        self.interrupt_inputs = interrupt::INTERRUPT_SOURCES;
+        #[cfg(feature = "stats")]
+        self.stats.forget();
+    }
 }
 
 #[maybe_async_cfg::maybe(
-    sync(cfg(not(feature = "async")), self = "Tca6424",),
+    sync(cfg(not(feature = "async")), self = "Tca64xx",),
     async(feature = "async", keep_self)
 )]
-impl<'a, I2C> Tca6424<'a, I2C>
+impl<I2C, D, C> Tca64xx<I2C, D, C>
 where
-    I2C: I2c,
+    I2C: BusAccess,
     I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+    C: Chip,
 {
-    /// Creates a new TCA6424 driver instance.
+    /// Sends a command byte followed by a payload in a single I2C write.
     ///
-    /// This function is `async` when the `async` feature is enabled, and synchronous otherwise.
+    /// This is the low-level write primitive used by every register write. The command byte
+    /// and `payload` are passed as two write operations of one `I2c::transaction`, so no
+    /// intermediate buffer is assembled and the payload length is not limited by the driver.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
     ///
     /// # Arguments
     ///
-    /// * `i2c` - A mutable reference to the I2C bus instance, implementing
-    ///           `embedded-hal::i2c::I2c` (sync) or `embedded-hal-async::i2c::I2c` (async).
-    /// * `address` - The I2C slave address of the TCA6424 device.
+    /// * `command_byte` - The command byte (register address and AI bit).
+    /// * `payload` - The data bytes to send after the command byte.
     ///
     /// # Returns
     ///
-    /// Returns `Ok(Self)` on success, or an `Error` if the I2C bus operation fails.
-    pub fn new(i2c: &'a mut I2C, address: u8) -> Result<Self, Error<I2C::Error>> {
-        Ok(Self { i2c, address })
+    /// Returns `Ok(())` on success, or an `Error` if the I2C bus operation fails.
+    async fn write_command(
+        &mut self,
+        command_byte: u8,
+        payload: &[u8],
+    ) -> Result<(), Error<I2C::Error>> {
+        #[cfg(feature = "fail-safe")]
+        self.fail_safe.check()?;
+        #[cfg(feature = "hot-plug")]
+        self.link.check()?;
+        let result = self.send_retrying(command_byte, payload).await;
+        self.guard(result).await
+    }
+
+    /// Runs automatic re-initialisation and engages the fail-safe latch if `result` failed
+    /// with an error that calls for them, then hands `result` back unchanged.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    async fn guard<T>(
+        &mut self,
+        result: Result<T, Error<I2C::Error>>,
+    ) -> Result<T, Error<I2C::Error>> {
+        #[cfg(any(feature = "hot-plug", feature = "fail-safe"))]
+        if let Err(error) = &result {
+            #[cfg(feature = "hot-plug")]
+            {
+                self.auto_reinit(error).await;
+            }
+            #[cfg(feature = "fail-safe")]
+            {
+                self.trip_fail_safe_on(error).await;
+            }
+        }
+        result
+    }
+
+    /// Yields (see [`Tca6424::set_yield_fn`]) and waits the configured gap between register
+    /// transfers, if any.
+    async fn transfer_gap(&mut self) {
+        #[cfg(not(feature = "async"))]
+        if let Some(yield_fn) = self.yield_fn {
+            yield_fn();
+        }
+        #[cfg(feature = "async")]
+        if self.yield_between_transfers {
+            yielding::yield_now().await;
+        }
+        if self.transfer_gap_us > 0 {
+            self.delay.delay_us(self.transfer_gap_us).await;
+        }
+    }
+
+    /// Sends a command byte and payload in one transaction, bypassing the offline and
+    /// fail-safe checks.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    async fn send(&mut self, command_byte: u8, payload: &[u8]) -> Result<(), Error<I2C::Error>> {
+        #[cfg(feature = "dry-run")]
+        if let Some(dry_run) = self.dry_run.as_mut() {
+            dry_run.record_write(command_byte, payload);
+            return Ok(());
+        }
+        let command = [C::command(command_byte)];
+        let mut operations = [I2cOperation::Write(&command), I2cOperation::Write(payload)];
+        self.transfer_gap().await;
+        self.transactions = self.transactions.wrapping_add(1);
+        let stopwatch = telemetry::Stopwatch::start();
+        #[cfg(not(feature = "async"))]
+        let result = Some(self.i2c.transaction(self.address, &mut operations));
+        #[cfg(feature = "async")]
+        let result = timeout::with_timeout(
+            &mut self.delay,
+            self.timeout_us,
+            self.i2c.transaction(self.address, &mut operations),
+        )
+        .await;
+        stopwatch.finish(
+            "write",
+            self.address,
+            command_byte,
+            payload,
+            result.as_ref(),
+        );
+        #[cfg(feature = "capture")]
+        self.capture.record(
+            capture::TransferDirection::Write,
+            command_byte,
+            payload,
+            result.as_ref(),
+        );
+        let result = result.ok_or(Error::Timeout)?;
+        #[cfg(feature = "stats")]
+        if result.is_ok() {
+            self.stats.record_write(command_byte, payload);
+        }
+        #[cfg(feature = "hot-plug")]
+        let result = self.link.record(result);
+        result.map_err(Error::I2c)
     }
 
     /// Writes a single byte to the specified register.
@@ -177,10 +721,21 @@ where
         register: registers::Register,
         value: u8,
     ) -> Result<(), Error<I2C::Error>> {
+        if !C::has(register) {
+            return Err(Error::InvalidRegisterOrPin);
+        }
         // Command byte: AI=0 (Bit 7), Register address (Bit 0-6)
-        let command_byte = register as u8; // AI=0 by default from enum value
-        let buffer = [command_byte, value];
-        self.i2c.write(self.address, &buffer).await.map_err(Error::I2c)
+        let command_byte = registers::command_byte(register, false);
+        let (kind, port) = (register.kind(), register.port());
+        let previous = self.cache.get(kind, port);
+        self.cache.begin_write(kind, port);
+        let result = self.write_command(command_byte, &[value]).await;
+        match result {
+            Ok(()) => self.record_write(kind, port, previous, value),
+            Err(Error::Timeout) => {}
+            Err(_) => self.cache.abort_write(kind, port),
+        }
+        self.note_access(register, true, result)
     }
 
     /// Reads a single byte from the specified register.
@@ -201,13 +756,15 @@ where
         &mut self,
         register: registers::Register,
     ) -> Result<u8, Error<I2C::Error>> {
+        if !C::has(register) {
+            return Err(Error::InvalidRegisterOrPin);
+        }
         // Command byte: AI=0 (Bit 7), Register address (Bit 0-6)
-        let command_byte = register as u8; // AI=0 by default from enum value
+        let command_byte = registers::command_byte(register, false);
         let mut read_buffer = [0u8];
-        // Send command byte (write mode), then repeated start and read data (read mode)
-        self.i2c
-            .write_read(self.address, &[command_byte], &mut read_buffer).await
-            .map_err(Error::I2c)?;
+        let result = self.fetch_retrying(command_byte, &mut read_buffer).await;
+        let result = self.guard(result).await;
+        self.note_access(register, false, result)?;
         Ok(read_buffer[0])
     }
 
@@ -216,14 +773,17 @@ where
     /// This is a low-level internal method. It sets the auto-increment bit in the command byte.
     /// The TCA6424 automatically increments the register address after each byte transfer.
     ///
+    /// `values` is sent straight from the caller's slice, without being copied. Several
+    /// values are written one register at a time instead when the [`TransferStrategy`] says
+    /// so.
+    ///
     /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
     ///
     /// # Arguments
     ///
     /// * `start_register` - The starting register address.
-    /// * `values` - A slice of bytes to write. The number of bytes written will be
-    ///              limited to the number of registers available from `start_register`
-    ///              to the end of the register map (max 3 for a port group).
+    /// * `values` - A slice of bytes to write. The register address wraps around within the
+    ///              register family, so bytes beyond the third overwrite earlier ports.
     ///
     /// # Returns
     ///
@@ -233,23 +793,44 @@ where
         start_register: registers::Register,
         values: &[u8],
     ) -> Result<(), Error<I2C::Error>> {
+        if values.is_empty() {
+            return Ok(());
+        }
+        let multiple = values.len() > 1;
+        if multiple && self.transfer.splits() {
+            return self.write_split(start_register, values).await;
+        }
         // Command byte: AI=1 (Bit 7), Register address (Bit 0-6)
-        let command_byte = (start_register as u8) | 0x80; // Set AI bit
-        let mut buffer = [0u8; 1 + 3]; // Max 3 bytes for a port group + 1 command byte
-        buffer[0] = command_byte;
-        let len = core::cmp::min(values.len(), 3); // TCA6424 has 3 registers per group
-        buffer[1..len + 1].copy_from_slice(&values[..len]);
-
-        self.i2c
-            .write(self.address, &buffer[..len + 1]).await
-            .map_err(Error::I2c)
+        let command_byte = registers::command_byte(start_register, true);
+        // The two low address bits wrap around within a register family.
+        let kind = start_register.kind();
+        let ports = (0..values.len())
+            .map(|offset| Port::wrapping(start_register.port() as usize + offset));
+        let previous = Port::ALL.map(|port| self.cache.get(kind, port));
+        for port in ports.clone() {
+            self.cache.begin_write(kind, port);
+        }
+        let result = self.write_command(command_byte, values).await;
+        for (port, value) in ports.zip(values) {
+            match result {
+                Ok(()) => self.record_write(kind, port, *port.entry(&previous), *value),
+                Err(Error::Timeout) => {}
+                Err(_) => self.cache.abort_write(kind, port),
+            }
+        }
+        if multiple && self.transfer.retries(&result) {
+            let split = self.write_split(start_register, values).await;
+            return self.retry_split(split, result);
+        }
+        self.note_access(start_register, true, result)
     }
 
     /// Reads multiple consecutive bytes starting from the specified register, enabling auto-increment.
     ///
     /// This is a low-level internal method. It sets the auto-increment bit in the command byte
     /// and handles the repeated start condition. The TCA6424 automatically increments the
-    /// register address after each byte transfer.
+    /// register address after each byte transfer. Several bytes are read one register at a
+    /// time instead when the [`TransferStrategy`] says so.
     ///
     /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
     ///
@@ -267,12 +848,83 @@ where
         start_register: registers::Register,
         buffer: &mut [u8],
     ) -> Result<(), Error<I2C::Error>> {
+        let multiple = buffer.len() > 1;
+        if multiple && self.transfer.splits() {
+            return self.read_split(start_register, buffer).await;
+        }
         // Command byte: AI=1 (Bit 7), Register address (Bit 0-6)
-        let command_byte = (start_register as u8) | 0x80; // Set AI bit
+        let command_byte = registers::command_byte(start_register, true);
+        let result = self.fetch_retrying(command_byte, buffer).await;
+        let result = self.guard(result).await;
+        if multiple && self.transfer.retries(&result) {
+            let split = self.read_split(start_register, buffer).await;
+            return self.retry_split(split, result);
+        }
+        self.note_access(start_register, false, result)
+    }
+
+    /// Sends a command byte, then reads `buffer.len()` bytes after a repeated start, or in a
+    /// separate transaction with [`ReadSequence::WriteThenRead`].
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    async fn fetch(&mut self, command_byte: u8, buffer: &mut [u8]) -> Result<(), Error<I2C::Error>> {
+        #[cfg(feature = "hot-plug")]
+        self.link.check()?;
+        #[cfg(feature = "dry-run")]
+        if let Some(dry_run) = self.dry_run.as_mut() {
+            dry_run.answer_read(command_byte, &self.cache, buffer);
+            return Ok(());
+        }
+        if self.write_only {
+            return self.read_cached(command_byte, buffer);
+        }
         // Send command byte (write mode), then repeated start and read data (read mode)
-        self.i2c
-            .write_read(self.address, &[command_byte], buffer).await
-            .map_err(Error::I2c)
+        self.transfer_gap().await;
+        let sequence = self.read_sequence();
+        let command = [C::command(command_byte)];
+        self.transactions = self.transactions.wrapping_add(match sequence {
+            ReadSequence::RepeatedStart => 1,
+            ReadSequence::WriteThenRead => 2,
+        });
+        let stopwatch = telemetry::Stopwatch::start();
+        #[cfg(not(feature = "async"))]
+        let result = Some(match sequence {
+            ReadSequence::RepeatedStart => self.i2c.write_read(self.address, &command, buffer),
+            ReadSequence::WriteThenRead => self
+                .i2c
+                .write(self.address, &command)
+                .and_then(|()| self.i2c.read(self.address, buffer)),
+        });
+        #[cfg(feature = "async")]
+        let result = {
+            let (i2c, address, read) = (&mut self.i2c, self.address, &mut *buffer);
+            timeout::with_timeout(&mut self.delay, self.timeout_us, async move {
+                match sequence {
+                    ReadSequence::RepeatedStart => i2c.write_read(address, &command, read).await,
+                    ReadSequence::WriteThenRead => {
+                        i2c.write(address, &command).await?;
+                        i2c.read(address, read).await
+                    }
+                }
+            })
+            .await
+        };
+        stopwatch.finish("read", self.address, command_byte, buffer, result.as_ref());
+        #[cfg(feature = "capture")]
+        self.capture.record(
+            capture::TransferDirection::Read,
+            command_byte,
+            buffer,
+            result.as_ref(),
+        );
+        let result = result.ok_or(Error::Timeout)?;
+        #[cfg(feature = "stats")]
+        if result.is_ok() {
+            self.stats.record_read(command_byte, buffer);
+        }
+        #[cfg(feature = "hot-plug")]
+        let result = self.link.record(result);
+        result.map_err(Error::I2c)
     }
 
     /// Sets the direction of a single pin (Input or Output).
@@ -289,22 +941,14 @@ where
     ///
     /// # Returns
     ///
-    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails or
-    /// if an invalid pin is provided (though the `Pin` enum should prevent this).
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
     pub async fn set_pin_direction(
         &mut self,
         pin: Pin,
         direction: PinDirection,
     ) -> Result<(), Error<I2C::Error>> {
-        let pin_index = pin as u8;
-        let port_index = pin_index / 8;
-        let bit_index = pin_index % 8;
-        let config_register = match port_index {
-            0 => registers::Register::ConfigurationPort0,
-            1 => registers::Register::ConfigurationPort1,
-            2 => registers::Register::ConfigurationPort2,
-            _ => return Err(Error::InvalidRegisterOrPin), // Should not happen with valid Pin enum
-        };
+        let bit_index = pin.bit();
+        let config_register = registers::Register::of(RegisterKind::Configuration, pin.port());
         let mut config_value = self.read_register(config_register).await?;
         match direction {
             PinDirection::Input => {
@@ -330,18 +974,10 @@ where
     ///
     /// # Returns
     ///
-    /// Returns `Ok(PinDirection)` on success, or an `Error` if an I2C bus operation fails or
-    /// if an invalid pin is provided.
+    /// Returns `Ok(PinDirection)` on success, or an `Error` if an I2C bus operation fails.
     pub async fn get_pin_direction(&mut self, pin: Pin) -> Result<PinDirection, Error<I2C::Error>> {
-        let pin_index = pin as u8;
-        let port_index = pin_index / 8;
-        let bit_index = pin_index % 8;
-        let config_register = match port_index {
-            0 => registers::Register::ConfigurationPort0,
-            1 => registers::Register::ConfigurationPort1,
-            2 => registers::Register::ConfigurationPort2,
-            _ => return Err(Error::InvalidRegisterOrPin), // Should not happen with valid Pin enum
-        };
+        let bit_index = pin.bit();
+        let config_register = registers::Register::of(RegisterKind::Configuration, pin.port());
         let config_value = self.read_register(config_register).await?;
         if (config_value >> bit_index) & 1 == 1 {
             Ok(PinDirection::Input)
@@ -362,28 +998,21 @@ where
     /// # Arguments
     ///
     /// * `pin` - The target pin (P00-P27).
-    /// * `state` - The desired pin state (`PinState::High` or `PinState::Low`).
+    /// * `state` - The desired pin state: a [`PinState`], an `embedded_hal::digital::PinState`
+    ///   or a `bool` (`true` = high).
     ///
     /// # Returns
     ///
-    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails or
-    /// if an invalid pin is provided.
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
     pub async fn set_pin_output(
         &mut self,
         pin: Pin,
-        state: PinState,
+        state: impl Into<PinState>,
     ) -> Result<(), Error<I2C::Error>> {
-        let pin_index = pin as u8;
-        let port_index = pin_index / 8;
-        let bit_index = pin_index % 8;
-        let output_register = match port_index {
-            0 => registers::Register::OutputPort0,
-            1 => registers::Register::OutputPort1,
-            2 => registers::Register::OutputPort2,
-            _ => return Err(Error::InvalidRegisterOrPin), // Should not happen with valid Pin enum
-        };
+        let bit_index = pin.bit();
+        let output_register = registers::Register::of(RegisterKind::Output, pin.port());
         let mut output_value = self.read_register(output_register).await?;
-        match state {
+        match state.into() {
             PinState::High => {
                 output_value |= 1 << bit_index; // Set bit to 1 (High)
             }
@@ -401,6 +1030,8 @@ where
     ///
     /// Note: This method reads the register value, not the actual physical pin state.
     /// The register value reflects the actual pin state only when the pin is configured as an output.
+    /// With cached output reads enabled the value comes from the register cache (see
+    /// [`Tca6424::set_cached_output_reads`]).
     ///
     /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
     ///
@@ -410,19 +1041,10 @@ where
     ///
     /// # Returns
     ///
-    /// Returns `Ok(PinState)` on success, or an `Error` if an I2C bus operation fails or
-    /// if an invalid pin is provided.
+    /// Returns `Ok(PinState)` on success, or an `Error` if an I2C bus operation fails.
     pub async fn get_pin_output_state(&mut self, pin: Pin) -> Result<PinState, Error<I2C::Error>> {
-        let pin_index = pin as u8;
-        let port_index = pin_index / 8;
-        let bit_index = pin_index % 8;
-        let output_register = match port_index {
-            0 => registers::Register::OutputPort0,
-            1 => registers::Register::OutputPort1,
-            2 => registers::Register::OutputPort2,
-            _ => return Err(Error::InvalidRegisterOrPin), // Should not happen with valid Pin enum
-        };
-        let output_value = self.read_register(output_register).await?;
+        let bit_index = pin.bit();
+        let output_value = self.read_output_register(pin.port()).await?;
         if (output_value >> bit_index) & 1 == 1 {
             Ok(PinState::High)
         } else {
@@ -444,21 +1066,15 @@ where
     ///
     /// * `pin` - The target pin (P00-P27).
     ///
+    /// With an input fallback set (see [`Tca6424::set_input_fallback`]), a failed read is
+    /// retried and then answered with the fallback values instead of an error.
+    ///
     /// # Returns
     ///
-    /// Returns `Ok(PinState)` on success, or an `Error` if an I2C bus operation fails or
-    /// if an invalid pin is provided.
+    /// Returns `Ok(PinState)` on success, or an `Error` if an I2C bus operation fails.
     pub async fn get_pin_input_state(&mut self, pin: Pin) -> Result<PinState, Error<I2C::Error>> {
-        let pin_index = pin as u8;
-        let port_index = pin_index / 8;
-        let bit_index = pin_index % 8;
-        let input_register = match port_index {
-            0 => registers::Register::InputPort0,
-            1 => registers::Register::InputPort1,
-            2 => registers::Register::InputPort2,
-            _ => return Err(Error::InvalidRegisterOrPin), // Should not happen with valid Pin enum
-        };
-        let input_value = self.read_register(input_register).await?;
+        let bit_index = pin.bit();
+        let input_value = self.read_input_register(pin.port()).await?;
         if (input_value >> bit_index) & 1 == 1 {
             Ok(PinState::High)
         } else {
@@ -466,6 +1082,45 @@ where
         }
     }
 
+    /// Drives an output pin high (`true`) or low (`false`).
+    ///
+    /// Shorthand for [`Tca6424::set_pin_output`].
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn set_pin(&mut self, pin: Pin, high: bool) -> Result<(), Error<I2C::Error>> {
+        self.set_pin_output(pin, PinState::from(high)).await
+    }
+
+    /// Returns `true` if the physical level of `pin` is high.
+    ///
+    /// Shorthand for [`Tca6424::get_pin_input_state`].
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(bool)` on success, or an `Error` if the I2C bus operation fails.
+    pub async fn is_high(&mut self, pin: Pin) -> Result<bool, Error<I2C::Error>> {
+        Ok(self.get_pin_input_state(pin).await? == PinState::High)
+    }
+
+    /// Returns `true` if the physical level of `pin` is low.
+    ///
+    /// Shorthand for [`Tca6424::get_pin_input_state`].
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(bool)` on success, or an `Error` if the I2C bus operation fails.
+    pub async fn is_low(&mut self, pin: Pin) -> Result<bool, Error<I2C::Error>> {
+        Ok(self.get_pin_input_state(pin).await? == PinState::Low)
+    }
+
     /// Sets the polarity inversion state for a single pin.
     ///
     /// This method reads the current polarity inversion register for the pin's port,
@@ -483,22 +1138,15 @@ where
     ///
     /// # Returns
     ///
-    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails or
-    /// if an invalid pin is provided.
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    #[cfg(feature = "polarity")]
     pub async fn set_pin_polarity_inversion(
         &mut self,
         pin: Pin,
         invert: bool,
     ) -> Result<(), Error<I2C::Error>> {
-        let pin_index = pin as u8;
-        let port_index = pin_index / 8;
-        let bit_index = pin_index % 8;
-        let polarity_register = match port_index {
-            0 => registers::Register::PolarityInversionPort0,
-            1 => registers::Register::PolarityInversionPort1,
-            2 => registers::Register::PolarityInversionPort2,
-            _ => return Err(Error::InvalidRegisterOrPin), // Should not happen with valid Pin enum
-        };
+        let bit_index = pin.bit();
+        let polarity_register = registers::Register::of(RegisterKind::PolarityInversion, pin.port());
         let mut polarity_value = self.read_register(polarity_register).await?;
         if invert {
             polarity_value |= 1 << bit_index; // Set bit to 1 (Invert)
@@ -522,24 +1170,42 @@ where
     /// # Returns
     ///
     /// Returns `Ok(bool)` where `true` indicates inversion is enabled, `false` otherwise,
-    /// or an `Error` if an I2C bus operation fails or if an invalid pin is provided.
+    /// or an `Error` if an I2C bus operation fails.
+    #[cfg(feature = "polarity")]
     pub async fn get_pin_polarity_inversion(
         &mut self,
         pin: Pin,
     ) -> Result<bool, Error<I2C::Error>> {
-        let pin_index = pin as u8;
-        let port_index = pin_index / 8;
-        let bit_index = pin_index % 8;
-        let polarity_register = match port_index {
-            0 => registers::Register::PolarityInversionPort0,
-            1 => registers::Register::PolarityInversionPort1,
-            2 => registers::Register::PolarityInversionPort2,
-            _ => return Err(Error::InvalidRegisterOrPin), // Should not happen with valid Pin enum
-        };
-        let polarity_value = self.read_register(polarity_register).await?;
+        let bit_index = pin.bit();
+        let polarity_value = self.read_polarity_register(pin.port()).await?;
         Ok(((polarity_value >> bit_index) & 1) == 1)
     }
 
+    /// Gets the physical level of a single pin, undoing its Polarity Inversion setting.
+    ///
+    /// [`Tca6424::get_pin_input_state`] returns the Input Port register bit, which the device
+    /// inverts for pins with polarity inversion enabled. This method XORs that bit with the
+    /// pin's Polarity Inversion bit, taken from the register cache or, when unknown, read
+    /// from the device, so it reports the level on the pin whatever the inversion setting.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(PinState)` with the physical level on success, or an `Error` if an I2C bus
+    /// operation fails.
+    #[cfg(feature = "polarity")]
+    pub async fn get_pin_input_raw(&mut self, pin: Pin) -> Result<PinState, Error<I2C::Error>> {
+        let inversion = match self.cache.get(RegisterKind::PolarityInversion, pin.port()) {
+            Some(value) => value,
+            None => self.read_polarity_register(pin.port()).await?,
+        };
+        let input = self
+            .read_register(registers::Register::of(RegisterKind::Input, pin.port()))
+            .await?;
+        Ok(PinState::from((input ^ inversion) & (1 << pin.bit()) != 0))
+    }
+
     /// Sets the direction of all 8 pins on a specific port simultaneously.
     ///
     /// This method writes directly to the configuration register for the specified port.
@@ -561,11 +1227,7 @@ where
         port: Port,
         direction_mask: u8,
     ) -> Result<(), Error<I2C::Error>> {
-        let config_register = match port {
-            Port::Port0 => registers::Register::ConfigurationPort0,
-            Port::Port1 => registers::Register::ConfigurationPort1,
-            Port::Port2 => registers::Register::ConfigurationPort2,
-        };
+        let config_register = registers::Register::of(RegisterKind::Configuration, port);
         self.write_register(config_register, direction_mask).await
     }
 
@@ -585,11 +1247,7 @@ where
     /// to a pin on the port (`1` = Input, `0` = Output), or an `Error` if the I2C
     /// bus operation fails.
     pub async fn get_port_direction(&mut self, port: Port) -> Result<u8, Error<I2C::Error>> {
-        let config_register = match port {
-            Port::Port0 => registers::Register::ConfigurationPort0,
-            Port::Port1 => registers::Register::ConfigurationPort1,
-            Port::Port2 => registers::Register::ConfigurationPort2,
-        };
+        let config_register = registers::Register::of(RegisterKind::Configuration, port);
         self.read_register(config_register).await
     }
 
@@ -617,11 +1275,7 @@ where
         port: Port,
         output_mask: u8,
     ) -> Result<(), Error<I2C::Error>> {
-        let output_register = match port {
-            Port::Port0 => registers::Register::OutputPort0,
-            Port::Port1 => registers::Register::OutputPort1,
-            Port::Port2 => registers::Register::OutputPort2,
-        };
+        let output_register = registers::Register::of(RegisterKind::Output, port);
         self.write_register(output_register, output_mask).await
     }
 
@@ -631,6 +1285,8 @@ where
     ///
     /// Note: This reads the register value, not the actual physical pin state.
     /// The register value reflects the actual pin state only when the pin is configured as an output.
+    /// With cached output reads enabled the value comes from the register cache (see
+    /// [`Tca6424::set_cached_output_reads`]).
     ///
     /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
     ///
@@ -644,12 +1300,7 @@ where
     /// to a pin on the port (`1` = High, `0` = Low), or an `Error` if the I2C
     /// bus operation fails.
     pub async fn get_port_output_state(&mut self, port: Port) -> Result<u8, Error<I2C::Error>> {
-        let output_register = match port {
-            Port::Port0 => registers::Register::OutputPort0,
-            Port::Port1 => registers::Register::OutputPort1,
-            Port::Port2 => registers::Register::OutputPort2,
-        };
-        self.read_register(output_register).await
+        self.read_output_register(port).await
     }
 
     /// Gets the current physical state mask for all 8 pins on a specific port.
@@ -665,18 +1316,181 @@ where
     ///
     /// * `port` - The target port (`Port::Port0`, `Port::Port1`, or `Port::Port2`).
     ///
+    /// With an input fallback set (see [`Tca6424::set_input_fallback`]), a failed read is
+    /// retried and then answered with the fallback values instead of an error.
+    ///
     /// # Returns
     ///
     /// Returns `Ok(u8)` containing an 8-bit mask on success, where each bit corresponds
     /// to a pin on the port (`1` = High, `0` = Low), or an `Error` if the I2C
     /// bus operation fails.
     pub async fn get_port_input_state(&mut self, port: Port) -> Result<u8, Error<I2C::Error>> {
-        let input_register = match port {
-            Port::Port0 => registers::Register::InputPort0,
-            Port::Port1 => registers::Register::InputPort1,
-            Port::Port2 => registers::Register::InputPort2,
-        };
-        self.read_register(input_register).await
+        self.read_input_register(port).await
+    }
+
+    /// Reads the Input Port register of `port` as a typed [`PortState`].
+    ///
+    /// Same transfer as [`Tca6424::get_port_input_state`].
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(PortState)` on success, or an `Error` if the I2C bus operation fails.
+    pub async fn read_port_inputs(&mut self, port: Port) -> Result<PortState, Error<I2C::Error>> {
+        let value = self.get_port_input_state(port).await?;
+        Ok(PortState::new(port, value))
+    }
+
+    /// Reads the Output Port register of `port` as a typed [`PortState`].
+    ///
+    /// Same transfer as [`Tca6424::get_port_output_state`].
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(PortState)` on success, or an `Error` if the I2C bus operation fails.
+    pub async fn read_port_outputs(&mut self, port: Port) -> Result<PortState, Error<I2C::Error>> {
+        let value = self.get_port_output_state(port).await?;
+        Ok(PortState::new(port, value))
+    }
+
+    /// Drives the output latches of the pins selected by `mask` to the matching bits of
+    /// `values`, leaving the other pins of `port` unchanged.
+    ///
+    /// This is the partial-write primitive for parallel buses, keypads and similar helpers
+    /// that own only some pins of a port: one read-modify-write of the Output Port register.
+    /// An empty `mask` makes no transfer and a full mask skips the read.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn write_pins_masked(
+        &mut self,
+        port: Port,
+        mask: u8,
+        values: u8,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.write_masked(RegisterKind::Output, port, mask, values)
+            .await
+    }
+
+    /// Sets the directions of the pins selected by `mask` from the matching bits of `inputs`
+    /// (`1` = input, `0` = output), leaving the other pins of `port` unchanged.
+    ///
+    /// The Configuration Port counterpart of [`Tca6424::write_pins_masked`].
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn write_directions_masked(
+        &mut self,
+        port: Port,
+        mask: u8,
+        inputs: u8,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.write_masked(RegisterKind::Configuration, port, mask, inputs)
+            .await
+    }
+
+    /// Merges `values` into a register under `mask` with one read-modify-write.
+    async fn write_masked(
+        &mut self,
+        kind: RegisterKind,
+        port: Port,
+        mask: u8,
+        values: u8,
+    ) -> Result<(), Error<I2C::Error>> {
+        match mask {
+            0x00 => Ok(()),
+            0xFF => self.set_register(kind, port, values).await,
+            _ => {
+                self.modify(kind, port, |current| (current & !mask) | (values & mask))
+                    .await?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Reads the register of the `kind` family that belongs to `port`.
+    ///
+    /// The generic counterpart of the `get_port_*` methods, for code that selects the
+    /// register family at run time.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(u8)` on success, or an `Error` if the I2C bus operation fails.
+    pub async fn get_register(
+        &mut self,
+        kind: RegisterKind,
+        port: Port,
+    ) -> Result<u8, Error<I2C::Error>> {
+        self.read_register(registers::Register::of(kind, port))
+            .await
+    }
+
+    /// Writes `value` to the register of the `kind` family that belongs to `port`.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, `Error::InvalidRegisterOrPin` for the read-only Input
+    /// family, or an `Error` if the I2C bus operation fails.
+    pub async fn set_register(
+        &mut self,
+        kind: RegisterKind,
+        port: Port,
+        value: u8,
+    ) -> Result<(), Error<I2C::Error>> {
+        if kind == RegisterKind::Input {
+            return Err(Error::InvalidRegisterOrPin);
+        }
+        self.write_register(registers::Register::of(kind, port), value)
+            .await
+    }
+
+    /// Read-modify-writes the register of the `kind` family that belongs to `port`.
+    ///
+    /// `f` receives the value read from the device and returns the value to write. Every
+    /// single-register update of the driver follows this read-then-write pattern; `modify`
+    /// exposes it for any family and any bit manipulation.
+    ///
+    /// ```ignore
+    /// // Invert the polarity of P20 and P21 without touching the rest of Port 2
+    /// tca.modify(RegisterKind::PolarityInversion, Port::Port2, |value| value ^ 0b11)?;
+    /// ```
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(u8)` with the value written, `Error::InvalidRegisterOrPin` for the
+    /// read-only Input family (no transfer is made), or an `Error` if an I2C bus operation
+    /// fails.
+    pub async fn modify<F>(
+        &mut self,
+        kind: RegisterKind,
+        port: Port,
+        f: F,
+    ) -> Result<u8, Error<I2C::Error>>
+    where
+        F: FnOnce(u8) -> u8,
+    {
+        if kind == RegisterKind::Input {
+            return Err(Error::InvalidRegisterOrPin);
+        }
+        let register = registers::Register::of(kind, port);
+        let value = f(self.read_register(register).await?);
+        self.write_register(register, value).await?;
+        Ok(value)
     }
 
     /// Sets the polarity inversion state for all 8 pins on a specific port simultaneously.
@@ -698,16 +1512,13 @@ where
     /// # Returns
     ///
     /// Returns `Ok(())` on success, or an `Error` if the I2C bus operation fails.
+    #[cfg(feature = "polarity")]
     pub async fn set_port_polarity_inversion(
         &mut self,
         port: Port,
         inversion_mask: u8,
     ) -> Result<(), Error<I2C::Error>> {
-        let polarity_register = match port {
-            Port::Port0 => registers::Register::PolarityInversionPort0,
-            Port::Port1 => registers::Register::PolarityInversionPort1,
-            Port::Port2 => registers::Register::PolarityInversionPort2,
-        };
+        let polarity_register = registers::Register::of(RegisterKind::PolarityInversion, port);
         self.write_register(polarity_register, inversion_mask).await
     }
 
@@ -726,16 +1537,45 @@ where
     /// Returns `Ok(u8)` containing an 8-bit mask on success, where each bit corresponds
     /// to a pin on the port (`1` = Inverted, `0` = Original), or an `Error` if the I2C
     /// bus operation fails.
+    #[cfg(feature = "polarity")]
     pub async fn get_port_polarity_inversion(
         &mut self,
         port: Port,
     ) -> Result<u8, Error<I2C::Error>> {
-        let polarity_register = match port {
-            Port::Port0 => registers::Register::PolarityInversionPort0,
-            Port::Port1 => registers::Register::PolarityInversionPort1,
-            Port::Port2 => registers::Register::PolarityInversionPort2,
-        };
-        self.read_register(polarity_register).await
+        self.read_polarity_register(port).await
+    }
+
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca6424",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> Tca6424<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Reads the Input Port registers of all three ports as typed [`PortState`]s.
+    ///
+    /// The three registers are read in one auto-increment transaction.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok([port0, port1, port2])` on success, or an `Error` if the I2C bus operation
+    /// fails.
+    pub async fn read_ports(&mut self) -> Result<[PortState; 3], Error<I2C::Error>> {
+        let mut values = [0u8; 3];
+        self.read_registers_ai(registers::Register::InputPort0, &mut values)
+            .await?;
+        Ok([
+            PortState::new(Port::Port0, values[0]),
+            PortState::new(Port::Port1, values[1]),
+            PortState::new(Port::Port2, values[2]),
+        ])
     }
 
     // --- Auto-Increment Methods ---
@@ -758,16 +1598,13 @@ where
     /// # Returns
     ///
     /// Returns `Ok(())` on success, or an `Error` if the I2C bus operation fails.
+    #[cfg(feature = "auto-increment")]
     pub async fn set_ports_direction_ai(
         &mut self,
         start_port: Port,
         direction_masks: &[u8],
     ) -> Result<(), Error<I2C::Error>> {
-        let start_register = match start_port {
-            Port::Port0 => registers::Register::ConfigurationPort0,
-            Port::Port1 => registers::Register::ConfigurationPort1,
-            Port::Port2 => registers::Register::ConfigurationPort2,
-        };
+        let start_register = registers::Register::of(RegisterKind::Configuration, start_port);
         self.write_registers_ai(start_register, direction_masks)
             .await
     }
@@ -790,19 +1627,35 @@ where
     /// # Returns
     ///
     /// Returns `Ok(())` on success, or an `Error` if the I2C bus operation fails.
+    #[cfg(feature = "auto-increment")]
     pub async fn get_ports_direction_ai(
         &mut self,
         start_port: Port,
         buffer: &mut [u8],
     ) -> Result<(), Error<I2C::Error>> {
-        let start_register = match start_port {
-            Port::Port0 => registers::Register::ConfigurationPort0,
-            Port::Port1 => registers::Register::ConfigurationPort1,
-            Port::Port2 => registers::Register::ConfigurationPort2,
-        };
+        let start_register = registers::Register::of(RegisterKind::Configuration, start_port);
         self.read_registers_ai(start_register, buffer).await
     }
 
+    /// Reads the direction masks of all three ports in one auto-increment transaction.
+    ///
+    /// Equivalent to [`Tca6424::get_ports_direction_ai`] starting from Port 0 with a 3-byte buffer, without
+    /// the caller having to declare the buffer.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok([port0, port1, port2])` on success, or an `Error` if the I2C bus operation
+    /// fails. A bit value of `1` indicates an input, and `0` an output.
+    #[cfg(feature = "auto-increment")]
+    pub async fn get_ports_direction(&mut self) -> Result<[u8; 3], Error<I2C::Error>> {
+        let mut values = [0u8; 3];
+        self.read_registers_ai(registers::Register::ConfigurationPort0, &mut values)
+            .await?;
+        Ok(values)
+    }
+
     /// Sets the output state of multiple consecutive ports using the auto-increment feature.
     ///
     /// This method writes to the output registers for the specified ports,
@@ -823,16 +1676,13 @@ where
     /// # Returns
     ///
     /// Returns `Ok(())` on success, or an `Error` if the I2C bus operation fails.
+    #[cfg(feature = "auto-increment")]
     pub async fn set_ports_output_ai(
         &mut self,
         start_port: Port,
         output_masks: &[u8],
     ) -> Result<(), Error<I2C::Error>> {
-        let start_register = match start_port {
-            Port::Port0 => registers::Register::OutputPort0,
-            Port::Port1 => registers::Register::OutputPort1,
-            Port::Port2 => registers::Register::OutputPort2,
-        };
+        let start_register = registers::Register::of(RegisterKind::Output, start_port);
         self.write_registers_ai(start_register, output_masks).await
     }
 
@@ -857,19 +1707,43 @@ where
     /// # Returns
     ///
     /// Returns `Ok(())` on success, or an `Error` if the I2C bus operation fails.
+    #[cfg(feature = "auto-increment")]
     pub async fn get_ports_output_state_ai(
         &mut self,
         start_port: Port,
         buffer: &mut [u8],
     ) -> Result<(), Error<I2C::Error>> {
-        let start_register = match start_port {
-            Port::Port0 => registers::Register::OutputPort0,
-            Port::Port1 => registers::Register::OutputPort1,
-            Port::Port2 => registers::Register::OutputPort2,
-        };
+        let start_register = registers::Register::of(RegisterKind::Output, start_port);
         self.read_registers_ai(start_register, buffer).await
     }
 
+    /// Reads the Output Port register values of all three ports in one auto-increment transaction.
+    ///
+    /// Equivalent to [`Tca6424::get_ports_output_state_ai`] starting from Port 0 with a 3-byte buffer, without
+    /// the caller having to declare the buffer.
+    /// With cached output reads enabled the values come from the register cache, or from
+    /// [`Tca6424::refresh_output_cache`] while any of them is unknown.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok([port0, port1, port2])` on success, or an `Error` if the I2C bus operation
+    /// fails. A bit value of `1` indicates the output is High.
+    #[cfg(feature = "auto-increment")]
+    pub async fn get_ports_output_state(&mut self) -> Result<[u8; 3], Error<I2C::Error>> {
+        if self.cached_output_reads {
+            return match self.cache.family(RegisterKind::Output) {
+                Some(values) => Ok(values),
+                None => self.refresh_output_cache().await,
+            };
+        }
+        let mut values = [0u8; 3];
+        self.read_registers_ai(registers::Register::OutputPort0, &mut values)
+            .await?;
+        Ok(values)
+    }
+
     /// Gets the current physical state masks for multiple consecutive ports using the auto-increment feature.
     ///
     /// This method reads from the input registers for the specified ports,
@@ -891,19 +1765,35 @@ where
     /// # Returns
     ///
     /// Returns `Ok(())` on success, or an `Error` if the I2C bus operation fails.
+    #[cfg(feature = "auto-increment")]
     pub async fn get_ports_input_state_ai(
         &mut self,
         start_port: Port,
         buffer: &mut [u8],
     ) -> Result<(), Error<I2C::Error>> {
-        let start_register = match start_port {
-            Port::Port0 => registers::Register::InputPort0,
-            Port::Port1 => registers::Register::InputPort1,
-            Port::Port2 => registers::Register::InputPort2,
-        };
+        let start_register = registers::Register::of(RegisterKind::Input, start_port);
         self.read_registers_ai(start_register, buffer).await
     }
 
+    /// Reads the Input Port register values (physical pin states) of all three ports in one auto-increment transaction.
+    ///
+    /// Equivalent to [`Tca6424::get_ports_input_state_ai`] starting from Port 0 with a 3-byte buffer, without
+    /// the caller having to declare the buffer.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok([port0, port1, port2])` on success, or an `Error` if the I2C bus operation
+    /// fails. A bit value of `1` indicates the pin is High.
+    #[cfg(feature = "auto-increment")]
+    pub async fn get_ports_input_state(&mut self) -> Result<[u8; 3], Error<I2C::Error>> {
+        let mut values = [0u8; 3];
+        self.read_registers_ai(registers::Register::InputPort0, &mut values)
+            .await?;
+        Ok(values)
+    }
+
     /// Sets the polarity inversion state for multiple consecutive ports using the auto-increment feature.
     ///
     /// This method writes to the polarity inversion registers for the specified ports,
@@ -925,16 +1815,13 @@ where
     /// # Returns
     ///
     /// Returns `Ok(())` on success, or an `Error` if the I2C bus operation fails.
+    #[cfg(all(feature = "polarity", feature = "auto-increment"))]
     pub async fn set_ports_polarity_inversion_ai(
         &mut self,
         start_port: Port,
         inversion_masks: &[u8],
     ) -> Result<(), Error<I2C::Error>> {
-        let start_register = match start_port {
-            Port::Port0 => registers::Register::PolarityInversionPort0,
-            Port::Port1 => registers::Register::PolarityInversionPort1,
-            Port::Port2 => registers::Register::PolarityInversionPort2,
-        };
+        let start_register = registers::Register::of(RegisterKind::PolarityInversion, start_port);
         self.write_registers_ai(start_register, inversion_masks)
             .await
     }
@@ -957,18 +1844,34 @@ where
     /// # Returns
     ///
     /// Returns `Ok(())` on success, or an `Error` if the I2C bus operation fails.
+    #[cfg(all(feature = "polarity", feature = "auto-increment"))]
     pub async fn get_ports_polarity_inversion_ai(
         &mut self,
         start_port: Port,
         buffer: &mut [u8],
     ) -> Result<(), Error<I2C::Error>> {
-        let start_register = match start_port {
-            Port::Port0 => registers::Register::PolarityInversionPort0,
-            Port::Port1 => registers::Register::PolarityInversionPort1,
-            Port::Port2 => registers::Register::PolarityInversionPort2,
-        };
-        self.read_registers_ai(start_register, buffer).await
+        let start_register = registers::Register::of(RegisterKind::PolarityInversion, start_port);
+        self.read_registers_ai(start_register, buffer).await?;
+        self.record_polarity(start_port, buffer);
+        Ok(())
     }
+
+    /// Reads the polarity inversion masks of all three ports in one auto-increment transaction.
+    ///
+    /// Equivalent to [`Tca6424::get_ports_polarity_inversion_ai`] starting from Port 0 with a 3-byte buffer, without
+    /// the caller having to declare the buffer.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok([port0, port1, port2])` on success, or an `Error` if the I2C bus operation
+    /// fails. A bit value of `1` indicates inversion is enabled.
+    #[cfg(all(feature = "polarity", feature = "auto-increment"))]
+    pub async fn get_ports_polarity_inversion(&mut self) -> Result<[u8; 3], Error<I2C::Error>> {
+        self.refresh_polarity_cache().await
+    }
+
     /// Sets the interrupt mask state for a single pin.
     ///
     /// When a pin is configured as an input, its corresponding interrupt mask bit
@@ -986,22 +1889,15 @@ where
     ///
     /// # Returns
     ///
-    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails or
-    /// if an invalid pin is provided.
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    #[cfg(feature = "interrupt-mask")]
     pub async fn set_pin_interrupt_mask(
         &mut self,
         pin: Pin,
         mask: bool,
     ) -> Result<(), Error<I2C::Error>> {
-        let pin_index = pin as u8;
-        let port_index = pin_index / 8;
-        let bit_index = pin_index % 8;
-        let mask_register = match port_index {
-            0 => registers::Register::InterruptMaskPort0,
-            1 => registers::Register::InterruptMaskPort1,
-            2 => registers::Register::InterruptMaskPort2,
-            _ => return Err(Error::InvalidRegisterOrPin), // Should not happen with valid Pin enum
-        };
+        let bit_index = pin.bit();
+        let mask_register = registers::Register::of(RegisterKind::InterruptMask, pin.port());
         let mut mask_value = self.read_register(mask_register).await?;
         if mask {
             mask_value |= 1 << bit_index; // Set bit to 1 (Mask/Disable Interrupt)
@@ -1025,17 +1921,11 @@ where
     /// # Returns
     ///
     /// Returns `Ok(bool)` where `true` indicates the interrupt is masked (disabled), `false` otherwise,
-    /// or an `Error` if an I2C bus operation fails or if an invalid pin is provided.
+    /// or an `Error` if an I2C bus operation fails.
+    #[cfg(feature = "interrupt-mask")]
     pub async fn get_pin_interrupt_mask(&mut self, pin: Pin) -> Result<bool, Error<I2C::Error>> {
-        let pin_index = pin as u8;
-        let port_index = pin_index / 8;
-        let bit_index = pin_index % 8;
-        let mask_register = match port_index {
-            0 => registers::Register::InterruptMaskPort0,
-            1 => registers::Register::InterruptMaskPort1,
-            2 => registers::Register::InterruptMaskPort2,
-            _ => return Err(Error::InvalidRegisterOrPin), // Should not happen with valid Pin enum
-        };
+        let bit_index = pin.bit();
+        let mask_register = registers::Register::of(RegisterKind::InterruptMask, pin.port());
         let mask_value = self.read_register(mask_register).await?;
         Ok(((mask_value >> bit_index) & 1) == 1)
     }
@@ -1056,16 +1946,13 @@ where
     /// # Returns
     ///
     /// Returns `Ok(())` on success, or an `Error` if the I2C bus operation fails.
+    #[cfg(feature = "interrupt-mask")]
     pub async fn set_port_interrupt_mask(
         &mut self,
         port: Port,
         mask_value: u8,
     ) -> Result<(), Error<I2C::Error>> {
-        let mask_register = match port {
-            Port::Port0 => registers::Register::InterruptMaskPort0,
-            Port::Port1 => registers::Register::InterruptMaskPort1,
-            Port::Port2 => registers::Register::InterruptMaskPort2,
-        };
+        let mask_register = registers::Register::of(RegisterKind::InterruptMask, port);
         self.write_register(mask_register, mask_value).await
     }
 
@@ -1084,12 +1971,9 @@ where
     /// Returns `Ok(u8)` containing an 8-bit mask on success, where each bit corresponds
     /// to a pin on the port (`1` = Masked/Disabled, `0` = Enabled), or an `Error` if the I2C
     /// bus operation fails.
+    #[cfg(feature = "interrupt-mask")]
     pub async fn get_port_interrupt_mask(&mut self, port: Port) -> Result<u8, Error<I2C::Error>> {
-        let mask_register = match port {
-            Port::Port0 => registers::Register::InterruptMaskPort0,
-            Port::Port1 => registers::Register::InterruptMaskPort1,
-            Port::Port2 => registers::Register::InterruptMaskPort2,
-        };
+        let mask_register = registers::Register::of(RegisterKind::InterruptMask, port);
         self.read_register(mask_register).await
     }
 
@@ -1111,16 +1995,13 @@ where
     /// # Returns
     ///
     /// Returns `Ok(())` on success, or an `Error` if the I2C bus operation fails.
+    #[cfg(all(feature = "interrupt-mask", feature = "auto-increment"))]
     pub async fn set_ports_interrupt_mask_ai(
         &mut self,
         start_port: Port,
         mask_masks: &[u8],
     ) -> Result<(), Error<I2C::Error>> {
-        let start_register = match start_port {
-            Port::Port0 => registers::Register::InterruptMaskPort0,
-            Port::Port1 => registers::Register::InterruptMaskPort1,
-            Port::Port2 => registers::Register::InterruptMaskPort2,
-        };
+        let start_register = registers::Register::of(RegisterKind::InterruptMask, start_port);
         self.write_registers_ai(start_register, mask_masks).await
     }
 
@@ -1142,18 +2023,34 @@ where
     /// # Returns
     ///
     /// Returns `Ok(())` on success, or an `Error` if the I2C bus operation fails.
+    #[cfg(all(feature = "interrupt-mask", feature = "auto-increment"))]
     pub async fn get_ports_interrupt_mask_ai(
         &mut self,
         start_port: Port,
         buffer: &mut [u8],
     ) -> Result<(), Error<I2C::Error>> {
-        let start_register = match start_port {
-            Port::Port0 => registers::Register::InterruptMaskPort0,
-            Port::Port1 => registers::Register::InterruptMaskPort1,
-            Port::Port2 => registers::Register::InterruptMaskPort2,
-        };
+        let start_register = registers::Register::of(RegisterKind::InterruptMask, start_port);
         self.read_registers_ai(start_register, buffer).await
     }
+
+    /// Reads the interrupt masks of all three ports in one auto-increment transaction.
+    ///
+    /// Equivalent to [`Tca6424::get_ports_interrupt_mask_ai`] starting from Port 0 with a 3-byte buffer, without
+    /// the caller having to declare the buffer.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok([port0, port1, port2])` on success, or an `Error` if the I2C bus operation
+    /// fails. A bit value of `1` indicates the interrupt is masked.
+    #[cfg(all(feature = "interrupt-mask", feature = "auto-increment"))]
+    pub async fn get_ports_interrupt_mask(&mut self) -> Result<[u8; 3], Error<I2C::Error>> {
+        let mut values = [0u8; 3];
+        self.read_registers_ai(registers::Register::InterruptMaskPort0, &mut values)
+            .await?;
+        Ok(values)
+    }
     /// Sets the initial output state for all three ports (Port0, Port1, Port2).
     ///
     /// This method writes the provided masks to the Output Port Registers (0x04, 0x05, 0x06)
@@ -1182,6 +2079,55 @@ where
         self.write_registers_ai(registers::Register::OutputPort0, &masks)
             .await
     }
+
+    /// Reads every register of the device into a [`RegisterSnapshot`].
+    ///
+    /// Each register family is read with a single auto-increment transaction, so a
+    /// snapshot costs five I2C transactions. Two snapshots can be compared with
+    /// [`RegisterSnapshot::diff`] to see exactly which registers and bits changed, and
+    /// written back with [`Tca6424::restore`]. [`Tca6424::snapshot_families`] reads only
+    /// some families.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(RegisterSnapshot)` on success, or an `Error` if an I2C bus operation fails.
+    #[cfg(feature = "diagnostics")]
+    pub async fn snapshot(&mut self) -> Result<RegisterSnapshot, Error<I2C::Error>> {
+        self.snapshot_families(RegisterFamilies::all()).await
+    }
+
+    /// Reads the functional state of the device into a [`DeviceState`].
+    ///
+    /// The Input, Output and Configuration families are read with one auto-increment
+    /// transaction each. Two states can be compared with [`DeviceState::diff`].
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(DeviceState)` on success, or an `Error` if an I2C bus operation fails.
+    #[cfg(feature = "diagnostics")]
+    pub async fn device_state(&mut self) -> Result<DeviceState, Error<I2C::Error>> {
+        let mut words = [[0u8; 4]; 3];
+        let families = [
+            RegisterKind::Input,
+            RegisterKind::Output,
+            RegisterKind::Configuration,
+        ];
+        for (kind, word) in families.into_iter().zip(words.iter_mut()) {
+            let start_register = registers::Register::of(kind, Port::Port0);
+            self.read_registers_ai(start_register, &mut word[..3])
+                .await?;
+        }
+        let [inputs, outputs, directions] = words.map(u32::from_le_bytes);
+        Ok(DeviceState {
+            inputs,
+            outputs,
+            directions,
+        })
+    }
 }
 
 // TODO: Add mock-based tests using embedded-hal-mock (in tests/integration_test.rs)
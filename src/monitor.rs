@@ -0,0 +1,229 @@
+//! Input monitoring driven by the INT line, with a polling fallback.
+
+use core::convert::Infallible;
+
+use embedded_hal::digital::{ErrorType, InputPin};
+#[cfg(feature = "async")]
+use embedded_hal_async::digital::Wait;
+
+use crate::errors::Error;
+use crate::{BusAccess, DelayNs, InputChanges, InputWatcher, Tca6424};
+
+/// Waits for input changes on the INT line of the expander, reading the inputs anyway when
+/// INT stays quiet.
+///
+/// INT is active low and stays asserted until the Input Port registers are read. On boards
+/// where INT is unreliable, or wired-OR with other devices, waiting on it alone can miss or
+/// misattribute changes; [`InputMonitor::wait_for_change_or_timeout`] therefore always ends
+/// with an input read, whether INT fired or the timeout elapsed.
+///
+/// `INT` implements `embedded_hal::digital::InputPin` in sync mode and
+/// `embedded_hal_async::digital::Wait` with the `async` feature. On boards without the INT
+/// trace routed, [`InputMonitor::polling`] builds a monitor on [`NoInt`] instead: every wait
+/// then lasts the full timeout and ends with a read, so the same event code runs on both
+/// kinds of board with the timeout as the polling interval.
+///
+/// A change from before boot can leave INT asserted when the monitor is created, and the
+/// watcher's first image would silently absorb it into the baseline. The first wait of a
+/// monitor therefore does not wait: it reads the inputs at once, releasing INT, and reports
+/// every subscribed pin with its level as a [full resync](InputWatcher::request_full_resync),
+/// so the application starts from the actual state.
+#[derive(Debug)]
+pub struct InputMonitor<INT> {
+    int: INT,
+    watcher: InputWatcher,
+    fallback_polls: u32,
+    primed: bool,
+}
+
+impl<INT> InputMonitor<INT> {
+    /// Creates a monitor on the `int` line, reporting the changes `watcher` subscribes to.
+    pub const fn new(int: INT, watcher: InputWatcher) -> Self {
+        Self {
+            int,
+            watcher,
+            fallback_polls: 0,
+            primed: false,
+        }
+    }
+
+    /// Returns the watcher, for example to change subscriptions.
+    pub fn watcher(&mut self) -> &mut InputWatcher {
+        &mut self.watcher
+    }
+
+    /// Returns how many waits ended on the timeout (or an INT pin error) rather than INT.
+    ///
+    /// A count that grows while inputs are changing points at an unreliable INT line. With
+    /// [`NoInt`] every wait counts.
+    pub const fn fallback_polls(&self) -> u32 {
+        self.fallback_polls
+    }
+
+    /// Returns `true` once the first wait has taken the baseline read.
+    pub const fn is_primed(&self) -> bool {
+        self.primed
+    }
+
+    /// Schedules the baseline read and full resync of the first wait.
+    fn prime(&mut self) {
+        if !self.primed {
+            self.watcher.request_full_resync();
+            self.primed = true;
+        }
+    }
+
+    /// Releases the INT pin.
+    pub fn into_inner(self) -> INT {
+        self.int
+    }
+}
+
+impl InputMonitor<NoInt> {
+    /// Creates a monitor for a board without an INT line, reading the inputs once per wait.
+    pub const fn polling(watcher: InputWatcher) -> Self {
+        Self::new(NoInt, watcher)
+    }
+}
+
+impl<INT> InputMonitor<INT> {
+    /// Waits until INT goes low or `timeout_ms` elapses, then reads the inputs.
+    ///
+    /// The wait uses the driver's stored delay source for the timeout. Either way the three
+    /// Input Port registers are read in one auto-increment transaction (which also releases
+    /// INT) and fed to the watcher, so a missed or shared interrupt costs at most one timeout.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    /// In sync mode INT is sampled once per millisecond.
+    ///
+    /// The first wait of the monitor, and any wait while the watcher has a
+    /// [full resync](InputWatcher::request_full_resync) pending, reads the inputs at once
+    /// without waiting and reports every subscribed pin.
+    ///
+    /// # Returns
+    ///
+    /// Returns the subscribed changes (possibly none, if INT belonged to another device or
+    /// nothing changed before the timeout), or an `Error` if the I2C bus operation fails.
+    #[cfg(feature = "async")]
+    pub async fn wait_for_change_or_timeout<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        timeout_ms: u32,
+    ) -> Result<InputChanges, Error<I2C::Error>>
+    where
+        INT: Wait,
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        self.prime();
+        if !self.watcher.is_resync_pending() {
+            let timeout_us = timeout_ms.saturating_mul(1000);
+            let woken = crate::timeout::with_timeout(
+                &mut tca.delay,
+                Some(timeout_us),
+                self.int.wait_for_low(),
+            )
+            .await;
+            if !matches!(woken, Some(Ok(()))) {
+                self.fallback_polls = self.fallback_polls.wrapping_add(1);
+            }
+        }
+        tca.poll_input_events(&mut self.watcher).await
+    }
+
+    /// Waits until INT goes low or `timeout_ms` elapses, then reads the inputs.
+    ///
+    /// The wait uses the driver's stored delay source for the timeout. Either way the three
+    /// Input Port registers are read in one auto-increment transaction (which also releases
+    /// INT) and fed to the watcher, so a missed or shared interrupt costs at most one timeout.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    /// In sync mode INT is sampled once per millisecond.
+    ///
+    /// The first wait of the monitor, and any wait while the watcher has a
+    /// [full resync](InputWatcher::request_full_resync) pending, reads the inputs at once
+    /// without waiting and reports every subscribed pin.
+    ///
+    /// # Returns
+    ///
+    /// Returns the subscribed changes (possibly none, if INT belonged to another device or
+    /// nothing changed before the timeout), or an `Error` if the I2C bus operation fails.
+    #[cfg(not(feature = "async"))]
+    pub fn wait_for_change_or_timeout<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        timeout_ms: u32,
+    ) -> Result<InputChanges, Error<I2C::Error>>
+    where
+        INT: InputPin,
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        self.prime();
+        if self.watcher.is_resync_pending() {
+            return tca.poll_input_events(&mut self.watcher);
+        }
+        let mut woken = false;
+        for elapsed_ms in 0..=timeout_ms {
+            match self.int.is_low() {
+                Ok(true) => {
+                    woken = true;
+                    break;
+                }
+                Ok(false) if elapsed_ms < timeout_ms => tca.delay.delay_ms(1),
+                _ => break,
+            }
+        }
+        if !woken {
+            self.fallback_polls = self.fallback_polls.wrapping_add(1);
+        }
+        tca.poll_input_events(&mut self.watcher)
+    }
+}
+
+/// Placeholder for an INT line that is not wired, used by [`InputMonitor::polling`].
+///
+/// It reads as permanently deasserted (high), and with the `async` feature its falling-edge
+/// and low-level waits never complete, so every wait runs into its timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NoInt;
+
+impl ErrorType for NoInt {
+    type Error = Infallible;
+}
+
+impl InputPin for NoInt {
+    fn is_high(&mut self) -> Result<bool, Infallible> {
+        Ok(true)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Infallible> {
+        Ok(false)
+    }
+}
+
+#[cfg(feature = "async")]
+impl Wait for NoInt {
+    async fn wait_for_high(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Infallible> {
+        core::future::pending().await
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Infallible> {
+        core::future::pending().await
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Infallible> {
+        core::future::pending().await
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Infallible> {
+        core::future::pending().await
+    }
+}
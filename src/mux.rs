@@ -0,0 +1,156 @@
+//! Access to expanders behind a TCA9548 I2C multiplexer.
+
+#[cfg(not(feature = "async"))]
+use embedded_hal::i2c::I2c;
+use embedded_hal::i2c::{ErrorType, Operation};
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c;
+
+use crate::BusAccess;
+
+/// Default I2C address of the TCA9548 (A0-A2 tied low).
+pub const TCA9548_DEFAULT_ADDRESS: u8 = 0x70;
+
+/// Selects a downstream channel of an I2C multiplexer.
+///
+/// Implemented by [`Tca9548`] and, in sync mode, by any
+/// `FnMut(&mut I2C) -> Result<(), I2C::Error>` closure, so other multiplexers (or a
+/// GPIO-controlled analog switch) can be plugged in.
+#[cfg(not(feature = "async"))]
+pub trait SelectChannel<I2C: ErrorType> {
+    /// Routes the bus to the channel of the device behind this selector.
+    fn select(&mut self, i2c: &mut I2C) -> Result<(), I2C::Error>;
+}
+
+/// Selects a downstream channel of an I2C multiplexer.
+///
+/// Implemented by [`Tca9548`], so other multiplexers can be plugged in.
+#[cfg(feature = "async")]
+#[allow(async_fn_in_trait)]
+pub trait SelectChannel<I2C: ErrorType> {
+    /// Routes the bus to the channel of the device behind this selector.
+    async fn select(&mut self, i2c: &mut I2C) -> Result<(), I2C::Error>;
+}
+
+#[cfg(not(feature = "async"))]
+impl<I2C, F> SelectChannel<I2C> for F
+where
+    I2C: ErrorType,
+    F: FnMut(&mut I2C) -> Result<(), I2C::Error>,
+{
+    fn select(&mut self, i2c: &mut I2C) -> Result<(), I2C::Error> {
+        self(i2c)
+    }
+}
+
+/// Channel selection for a TCA9548 8-channel I2C multiplexer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Tca9548 {
+    address: u8,
+    channel: u8,
+}
+
+impl Tca9548 {
+    /// Selects `channel` (0-7) of the multiplexer at `address`.
+    ///
+    /// Returns `None` if `channel` is out of range.
+    pub const fn new(address: u8, channel: u8) -> Option<Self> {
+        if channel < 8 {
+            Some(Self { address, channel })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the multiplexer address.
+    pub const fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// Returns the selected channel.
+    pub const fn channel(&self) -> u8 {
+        self.channel
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca9548",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C: BusAccess> SelectChannel<I2C> for Tca9548 {
+    async fn select(&mut self, i2c: &mut I2C) -> Result<(), I2C::Error> {
+        // The control register holds one enable bit per channel.
+        i2c.write(self.address, &[1 << self.channel]).await
+    }
+}
+
+/// An I2C bus that routes a multiplexer channel before every transfer.
+///
+/// Pass a `&mut MuxChannel` to [`Tca6424::new`](crate::Tca6424::new) to place an expander
+/// behind a multiplexer. Because each expander gets its own channel adapter, any number of
+/// expanders with the same address can share one bus; `I2C` is then typically a bus-sharing
+/// device such as `embedded-hal-bus`'s `RefCellDevice`.
+///
+/// The channel is selected before each transfer, not cached, so adapters for different
+/// channels can be interleaved freely. Requires the `tca9548` feature.
+#[derive(Debug)]
+pub struct MuxChannel<I2C, S = Tca9548> {
+    i2c: I2C,
+    selector: S,
+}
+
+impl<I2C, S> MuxChannel<I2C, S> {
+    /// Creates an adapter that calls `selector` before every transfer on `i2c`.
+    pub fn new(i2c: I2C, selector: S) -> Self {
+        Self { i2c, selector }
+    }
+
+    /// Releases the bus and the selector.
+    pub fn into_inner(self) -> (I2C, S) {
+        (self.i2c, self.selector)
+    }
+}
+
+impl<I2C: ErrorType, S> ErrorType for MuxChannel<I2C, S> {
+    type Error = I2C::Error;
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "MuxChannel",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, S> I2c for MuxChannel<I2C, S>
+where
+    I2C: BusAccess,
+    S: SelectChannel<I2C>,
+{
+    async fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.selector.select(&mut self.i2c).await?;
+        self.i2c.read(address, read).await
+    }
+
+    async fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        self.selector.select(&mut self.i2c).await?;
+        self.i2c.write(address, write).await
+    }
+
+    async fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.selector.select(&mut self.i2c).await?;
+        self.i2c.write_read(address, write, read).await
+    }
+
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.selector.select(&mut self.i2c).await?;
+        self.i2c.transaction(address, operations).await
+    }
+}
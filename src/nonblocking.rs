@@ -0,0 +1,103 @@
+//! `nb`-style non-blocking interface for superloop firmware.
+
+use crate::errors::Error;
+use crate::operation::{InFlight, Operation, StepResult};
+use crate::{BusAccess, DelayNs, NoDelay, Tca6424};
+
+/// Non-blocking front-end over a [`Tca6424`] driver.
+///
+/// An operation is started with [`NbTca6424::start`] and driven to completion by calling
+/// [`NbTca6424::poll`] from the main loop. Every `poll()` issues at most one I2C transaction
+/// and returns `Err(nb::Error::WouldBlock)` while the operation still needs more, so the time
+/// spent in the driver per loop iteration is bounded by a single bus transfer.
+///
+/// Only one operation can be in flight at a time. Requires the `nb` feature and is only
+/// available without the `async` feature.
+///
+/// ```ignore
+/// let mut expander = NbTca6424::new(tca);
+/// expander.start(Operation::set_pin_output(Pin::P03, PinState::High))?;
+/// loop {
+///     match expander.poll() {
+///         Ok(_) => break,
+///         Err(nb::Error::WouldBlock) => do_other_work(),
+///         Err(nb::Error::Other(e)) => return Err(e),
+///     }
+/// }
+/// ```
+pub struct NbTca6424<I2C, D = NoDelay> {
+    tca: Tca6424<I2C, D>,
+    in_flight: Option<InFlight>,
+}
+
+impl<I2C, D> NbTca6424<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Wraps a driver instance.
+    pub fn new(tca: Tca6424<I2C, D>) -> Self {
+        Self {
+            tca,
+            in_flight: None,
+        }
+    }
+
+    /// Starts a new operation without issuing any I2C transaction.
+    ///
+    /// Returns `Err(nb::Error::WouldBlock)` if another operation is still in flight.
+    pub fn start(&mut self, operation: Operation) -> nb::Result<(), Error<I2C::Error>> {
+        if self.in_flight.is_some() {
+            return Err(nb::Error::WouldBlock);
+        }
+        self.in_flight = Some(InFlight::new(operation));
+        Ok(())
+    }
+
+    /// Advances the in-flight operation by one I2C transaction.
+    ///
+    /// Returns `Ok(value)` when the operation completes (`value` is the register value for
+    /// [`Operation::Read`], `None` otherwise), `Err(nb::Error::WouldBlock)` while more
+    /// transactions are needed, and `Err(nb::Error::Other(_))` if a transaction fails. After an
+    /// error the operation stays in flight and the failed step is retried by the next `poll()`;
+    /// use [`NbTca6424::cancel`] to give up instead.
+    ///
+    /// Polling with no operation in flight returns `Ok(None)` without touching the bus.
+    pub fn poll(&mut self) -> nb::Result<Option<u8>, Error<I2C::Error>> {
+        let Some(in_flight) = self.in_flight.as_mut() else {
+            return Ok(None);
+        };
+        match self.tca.step(in_flight).map_err(nb::Error::Other)? {
+            StepResult::Pending => Err(nb::Error::WouldBlock),
+            StepResult::Done(value) => {
+                self.in_flight = None;
+                Ok(value)
+            }
+        }
+    }
+
+    /// Returns `true` if an operation is in flight.
+    pub fn is_busy(&self) -> bool {
+        self.in_flight.is_some()
+    }
+
+    /// Abandons the in-flight operation, returning it if there was one.
+    ///
+    /// A cancelled read-modify-write may already have performed its read but never its write.
+    pub fn cancel(&mut self) -> Option<Operation> {
+        self.in_flight
+            .take()
+            .map(|in_flight| *in_flight.operation())
+    }
+
+    /// Returns a mutable reference to the wrapped driver for blocking calls.
+    pub fn inner(&mut self) -> &mut Tca6424<I2C, D> {
+        &mut self.tca
+    }
+
+    /// Releases the wrapped driver.
+    pub fn into_inner(self) -> Tca6424<I2C, D> {
+        self.tca
+    }
+}
@@ -0,0 +1,229 @@
+//! Driver operations that can be executed one I2C transaction at a time.
+
+use crate::errors::Error;
+use crate::registers::Register;
+use crate::{BusAccess, DelayNs, Pin, PinDirection, PinState, Port, RegisterKind, Tca6424};
+
+/// A self-contained driver operation.
+///
+/// Operations are plain data so they can be stored, queued and executed later in small
+/// steps, each step issuing exactly one I2C transaction. Read-modify-write operations take
+/// two steps (read, then write); plain writes and reads take one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Operation {
+    /// Replace the bits selected by `mask` in one register with the bits of `value`,
+    /// leaving the other bits untouched (read-modify-write).
+    Modify {
+        /// Register family.
+        kind: RegisterKind,
+        /// Target port.
+        port: Port,
+        /// Bits to change.
+        mask: u8,
+        /// New values for the bits selected by `mask`.
+        value: u8,
+    },
+    /// Write a whole register.
+    Write {
+        /// Register family.
+        kind: RegisterKind,
+        /// Target port.
+        port: Port,
+        /// Value to write.
+        value: u8,
+    },
+    /// Read a whole register.
+    Read {
+        /// Register family.
+        kind: RegisterKind,
+        /// Target port.
+        port: Port,
+    },
+}
+
+impl Operation {
+    /// Sets a single bit of a register family (read-modify-write).
+    pub const fn set_bit(kind: RegisterKind, pin: Pin, value: bool) -> Self {
+        Operation::Modify {
+            kind,
+            port: pin.port(),
+            mask: 1 << pin.bit(),
+            value: if value { 1 << pin.bit() } else { 0 },
+        }
+    }
+
+    /// Equivalent of [`Tca6424::set_pin_direction`].
+    pub const fn set_pin_direction(pin: Pin, direction: PinDirection) -> Self {
+        Self::set_bit(
+            RegisterKind::Configuration,
+            pin,
+            matches!(direction, PinDirection::Input),
+        )
+    }
+
+    /// Equivalent of [`Tca6424::set_pin_output`].
+    pub const fn set_pin_output(pin: Pin, state: PinState) -> Self {
+        Self::set_bit(RegisterKind::Output, pin, matches!(state, PinState::High))
+    }
+
+    /// Equivalent of [`Tca6424::set_pin_polarity_inversion`].
+    #[cfg(feature = "polarity")]
+    pub const fn set_pin_polarity_inversion(pin: Pin, invert: bool) -> Self {
+        Self::set_bit(RegisterKind::PolarityInversion, pin, invert)
+    }
+
+    /// Equivalent of [`Tca6424::set_pin_interrupt_mask`].
+    #[cfg(feature = "interrupt-mask")]
+    pub const fn set_pin_interrupt_mask(pin: Pin, mask: bool) -> Self {
+        Self::set_bit(RegisterKind::InterruptMask, pin, mask)
+    }
+
+    /// Equivalent of [`Tca6424::set_port_output`].
+    pub const fn set_port_output(port: Port, value: u8) -> Self {
+        Operation::Write {
+            kind: RegisterKind::Output,
+            port,
+            value,
+        }
+    }
+
+    /// Equivalent of [`Tca6424::set_port_direction`].
+    pub const fn set_port_direction(port: Port, value: u8) -> Self {
+        Operation::Write {
+            kind: RegisterKind::Configuration,
+            port,
+            value,
+        }
+    }
+
+    /// Equivalent of [`Tca6424::get_port_input_state`].
+    pub const fn read_port_input(port: Port) -> Self {
+        Operation::Read {
+            kind: RegisterKind::Input,
+            port,
+        }
+    }
+
+    /// Returns the number of I2C transactions needed to complete this operation.
+    pub const fn transaction_count(&self) -> usize {
+        match self {
+            Operation::Modify { .. } => 2,
+            Operation::Write { .. } | Operation::Read { .. } => 1,
+        }
+    }
+}
+
+/// Progress of an [`Operation`] that is being executed step by step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InFlight {
+    operation: Operation,
+    /// Value read by the first step of a read-modify-write.
+    read_value: Option<u8>,
+}
+
+impl InFlight {
+    /// Starts tracking `operation`; no transaction has been issued yet.
+    pub const fn new(operation: Operation) -> Self {
+        Self {
+            operation,
+            read_value: None,
+        }
+    }
+
+    /// Returns the operation being executed.
+    pub const fn operation(&self) -> &Operation {
+        &self.operation
+    }
+
+    /// Returns the number of transactions still needed to finish the operation.
+    pub const fn remaining_transactions(&self) -> usize {
+        match (self.operation, self.read_value) {
+            (Operation::Modify { .. }, Some(_)) => 1,
+            _ => self.operation.transaction_count(),
+        }
+    }
+}
+
+/// Result of executing one step of an [`InFlight`] operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum StepResult {
+    /// More transactions are needed.
+    Pending,
+    /// The operation completed. Holds the register value for [`Operation::Read`].
+    Done(Option<u8>),
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca6424",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> Tca6424<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Executes the next step of an in-flight operation, issuing exactly one I2C transaction.
+    ///
+    /// On error the in-flight state is left unchanged, so the same step can be retried.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(StepResult::Pending)` if more steps are needed, `Ok(StepResult::Done(_))`
+    /// when the operation has completed, or an `Error` if the I2C bus operation fails.
+    pub async fn step(
+        &mut self,
+        in_flight: &mut InFlight,
+    ) -> Result<StepResult, Error<I2C::Error>> {
+        match in_flight.operation {
+            Operation::Modify {
+                kind,
+                port,
+                mask,
+                value,
+            } => match in_flight.read_value {
+                None => {
+                    let current = self.read_register(Register::of(kind, port)).await?;
+                    in_flight.read_value = Some(current);
+                    Ok(StepResult::Pending)
+                }
+                Some(current) => {
+                    let merged = (current & !mask) | (value & mask);
+                    self.write_register(Register::of(kind, port), merged)
+                        .await?;
+                    Ok(StepResult::Done(None))
+                }
+            },
+            Operation::Write { kind, port, value } => {
+                self.write_register(Register::of(kind, port), value).await?;
+                Ok(StepResult::Done(None))
+            }
+            Operation::Read { kind, port } => {
+                let value = self.read_register(Register::of(kind, port)).await?;
+                Ok(StepResult::Done(Some(value)))
+            }
+        }
+    }
+
+    /// Executes an operation to completion.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the register value for [`Operation::Read`], `None` otherwise, or an `Error`
+    /// if an I2C bus operation fails.
+    pub async fn execute(&mut self, operation: Operation) -> Result<Option<u8>, Error<I2C::Error>> {
+        let mut in_flight = InFlight::new(operation);
+        loop {
+            if let StepResult::Done(value) = self.step(&mut in_flight).await? {
+                return Ok(value);
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! Output latch getters served from the register cache.
+
+use crate::errors::Error;
+use crate::registers::Register;
+use crate::{BusAccess, Chip, DelayNs, Port, RegisterKind, Tca64xx};
+
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C> {
+    /// Enables or disables cached output reads.
+    ///
+    /// When enabled, [`Tca64xx::get_pin_output_state`], [`Tca64xx::get_port_output_state`],
+    /// [`Tca64xx::read_port_outputs`], [`Tca64xx::get_ports_output_state`] and
+    /// `IoExpander::read_outputs` answer from the register cache whenever it holds the Output
+    /// Port value, without a bus transfer; an unknown value is read once and then cached.
+    /// These getters then report the *commanded* state: the last value the driver wrote or
+    /// read. A device reset or a write by another bus master is not seen until
+    /// [`Tca64xx::refresh_output_cache`] reads the latches again.
+    ///
+    /// Disabled by default, so every getter reads the device. Enable it for hot paths that
+    /// only query what the firmware itself commanded.
+    pub fn set_cached_output_reads(&mut self, enabled: bool) {
+        self.cached_output_reads = enabled;
+    }
+
+    /// Returns `true` if cached output reads are enabled.
+    pub fn cached_output_reads(&self) -> bool {
+        self.cached_output_reads
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca64xx",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Reads the three Output Port registers in one auto-increment transaction and records
+    /// them in the register cache, replacing the cached values.
+    ///
+    /// With cached output reads enabled (see [`Tca64xx::set_cached_output_reads`]), call it
+    /// when the bus truth matters, for example after a suspected reset or before a
+    /// [`Tca64xx::snapshot`] comparison.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok([port0, port1, port2])` with the values read, or an `Error` if the I2C bus
+    /// operation fails; the cache is left unchanged in that case.
+    pub async fn refresh_output_cache(&mut self) -> Result<[u8; 3], Error<I2C::Error>> {
+        let mut values = [0u8; 3];
+        self.read_registers_ai(Register::OutputPort0, &mut values)
+            .await?;
+        for (port, value) in Port::ALL.into_iter().zip(values) {
+            self.cache.set(RegisterKind::Output, port, value);
+        }
+        Ok(values)
+    }
+
+    /// Reads the Output Port register of `port`, from the cache when cached output reads are
+    /// enabled and the value is known.
+    pub(crate) async fn read_output_register(
+        &mut self,
+        port: Port,
+    ) -> Result<u8, Error<I2C::Error>> {
+        if !self.cached_output_reads {
+            return self
+                .read_register(Register::of(RegisterKind::Output, port))
+                .await;
+        }
+        if let Some(value) = self.cache.get(RegisterKind::Output, port) {
+            return Ok(value);
+        }
+        let value = self
+            .read_register(Register::of(RegisterKind::Output, port))
+            .await?;
+        self.cache.set(RegisterKind::Output, port, value);
+        Ok(value)
+    }
+}
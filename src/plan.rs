@@ -0,0 +1,324 @@
+//! Transaction plans: the register writes an apply operation will issue, computed up front,
+//! and the bus time they take.
+
+use core::fmt;
+use core::ops::{Add, AddAssign};
+use core::time::Duration;
+
+use crate::registers::Register;
+use crate::{Port, RegisterKind};
+
+/// One register write of a plan: a single-register write, or an auto-increment write when
+/// the payload spans several ports.
+///
+/// Returned by the `plan()` methods of [`BoardConfig`](crate::BoardConfig),
+/// [`ConfigTransaction`](crate::ConfigTransaction) and
+/// [`ExpanderBank`](crate::ExpanderBank), so tests can assert on the bus traffic of an
+/// operation without listing every transfer of a mock:
+///
+/// ```
+/// use tca6424::{ConfigTransaction, PlannedWrite, Port, Register, RegisterCache};
+///
+/// let mut txn = ConfigTransaction::new();
+/// txn.port_output(Port::Port0, 0x0F).port_direction(Port::Port0, 0xF0);
+/// let writes: Vec<_> = txn.plan(&RegisterCache::new()).collect();
+/// assert_eq!(
+///     writes,
+///     [
+///         PlannedWrite::new(Register::OutputPort0, &[0x0F]).unwrap(),
+///         PlannedWrite::new(Register::ConfigurationPort0, &[0xF0]).unwrap(),
+///     ]
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PlannedWrite {
+    register: Register,
+    len: u8,
+    bytes: [u8; 3],
+}
+
+impl PlannedWrite {
+    /// Creates a write of `payload` starting at `register`; a payload of more than one byte
+    /// is written with auto-increment.
+    ///
+    /// # Returns
+    ///
+    /// Returns the write, or `None` if `payload` is empty or longer than three bytes.
+    pub fn new(register: Register, payload: &[u8]) -> Option<Self> {
+        if !(1..=3).contains(&payload.len()) {
+            return None;
+        }
+        let mut bytes = [0u8; 3];
+        for (byte, value) in bytes.iter_mut().zip(payload) {
+            *byte = *value;
+        }
+        Some(Self {
+            register,
+            len: payload.len() as u8,
+            bytes,
+        })
+    }
+
+    /// Returns the first register written.
+    pub fn register(&self) -> Register {
+        self.register
+    }
+
+    /// Returns the bytes written, one per port from [`PlannedWrite::register`] on.
+    pub fn payload(&self) -> &[u8] {
+        self.bytes
+            .get(..usize::from(self.len))
+            .unwrap_or(&self.bytes)
+    }
+
+    /// Returns `true` if the write uses auto-increment.
+    pub fn is_auto_increment(&self) -> bool {
+        self.len > 1
+    }
+
+    /// Returns the command byte of the write.
+    pub fn command(&self) -> u8 {
+        crate::command_byte(self.register, self.is_auto_increment())
+    }
+
+    /// Returns the bus cost of the write.
+    pub fn cost(&self) -> BusCost {
+        BusCost::write(self.len)
+    }
+}
+
+/// Returns the time one I2C transaction of `bytes` bytes takes at a clock of `bus_hz`:
+/// nine clock cycles per byte (eight data bits and the acknowledge) plus one each for the
+/// START and STOP conditions, rounded up to whole nanoseconds.
+///
+/// `bytes` counts everything on the wire, the address byte included. This is the time the
+/// bus is busy at the nominal clock; clock stretching, slow rise times and the gaps the I2C
+/// controller and its driver add between transfers come on top, so budget a margin when
+/// using it for worst-case loop times. A `bus_hz` of zero yields [`Duration::MAX`].
+///
+/// ```
+/// use core::time::Duration;
+/// use tca6424::estimated_transaction_time;
+///
+/// // Address, command and one data byte at 400 kHz: 29 clocks
+/// assert_eq!(
+///     estimated_transaction_time(3, 400_000),
+///     Duration::from_nanos(72_500)
+/// );
+/// ```
+pub const fn estimated_transaction_time(bytes: u32, bus_hz: u32) -> Duration {
+    clocks_to_time(bytes as u64 * 9 + 2, bus_hz)
+}
+
+const fn clocks_to_time(clocks: u64, bus_hz: u32) -> Duration {
+    if bus_hz == 0 {
+        return Duration::MAX;
+    }
+    Duration::from_nanos((clocks * 1_000_000_000).div_ceil(bus_hz as u64))
+}
+
+/// The bus traffic of an operation: transactions, bytes on the wire and clock cycles.
+///
+/// Costs add up, so the cost of a control loop's expander access is the sum of its
+/// operations; [`BusCost::estimated_time`] turns it into bus time for a loop budget. The
+/// associated constants give the cost of the common driver methods, and plans report the
+/// cost of their writes ([`PlannedWrite::cost`]).
+///
+/// ```
+/// use tca6424::BusCost;
+///
+/// // Read all inputs, then update one output pin by read-modify-write
+/// let cost = BusCost::INPUTS_READ + BusCost::PIN_WRITE;
+/// assert_eq!(cost.transactions(), 3);
+/// assert_eq!(cost.bytes(), 13);
+/// assert_eq!(cost.estimated_time(400_000).as_micros(), 312);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BusCost {
+    transactions: u32,
+    bytes: u32,
+    clocks: u32,
+}
+
+impl BusCost {
+    /// No bus traffic.
+    pub const NONE: BusCost = BusCost {
+        transactions: 0,
+        bytes: 0,
+        clocks: 0,
+    };
+
+    /// One register write, as issued by `set_port_output` and the other whole-port setters.
+    pub const PORT_WRITE: BusCost = BusCost::write(1);
+
+    /// One register read, as issued by `get_port_input_state` and the other port getters.
+    pub const PORT_READ: BusCost = BusCost::read(1);
+
+    /// A pin-level read-modify-write, as issued by `set_pin_output` and
+    /// `set_pin_direction`.
+    pub const PIN_WRITE: BusCost = BusCost::read(1).plus(BusCost::write(1));
+
+    /// An auto-increment write of all three ports of a register family.
+    pub const FAMILY_WRITE: BusCost = BusCost::write(3);
+
+    /// An auto-increment read of the three Input Port registers.
+    pub const INPUTS_READ: BusCost = BusCost::read(3);
+
+    /// Returns the cost of a write transaction carrying `data` register bytes: address,
+    /// command byte and data.
+    pub const fn write(data: u8) -> Self {
+        let bytes = 2 + data as u32;
+        Self {
+            transactions: 1,
+            bytes,
+            clocks: bytes * 9 + 2,
+        }
+    }
+
+    /// Returns the cost of a read transaction returning `data` register bytes: address and
+    /// command byte, a repeated START, the address again and the data.
+    pub const fn read(data: u8) -> Self {
+        let bytes = 3 + data as u32;
+        Self {
+            transactions: 1,
+            bytes,
+            clocks: bytes * 9 + 3,
+        }
+    }
+
+    /// Returns the combined cost of `self` and `other`.
+    pub const fn plus(self, other: BusCost) -> Self {
+        Self {
+            transactions: self.transactions + other.transactions,
+            bytes: self.bytes + other.bytes,
+            clocks: self.clocks + other.clocks,
+        }
+    }
+
+    /// Returns the cost of performing `self` `count` times.
+    pub const fn times(self, count: u32) -> Self {
+        Self {
+            transactions: self.transactions * count,
+            bytes: self.bytes * count,
+            clocks: self.clocks * count,
+        }
+    }
+
+    /// Returns the number of I2C transactions.
+    pub const fn transactions(&self) -> u32 {
+        self.transactions
+    }
+
+    /// Returns the number of bytes on the wire, address bytes included.
+    pub const fn bytes(&self) -> u32 {
+        self.bytes
+    }
+
+    /// Returns the number of bus clock cycles, START, repeated START and STOP conditions
+    /// included.
+    pub const fn bus_clocks(&self) -> u32 {
+        self.clocks
+    }
+
+    /// Returns the bus time at a clock of `bus_hz`, with the same caveats as
+    /// [`estimated_transaction_time`].
+    pub const fn estimated_time(&self, bus_hz: u32) -> Duration {
+        clocks_to_time(self.clocks as u64, bus_hz)
+    }
+}
+
+impl Add for BusCost {
+    type Output = BusCost;
+
+    fn add(self, other: BusCost) -> BusCost {
+        self.plus(other)
+    }
+}
+
+impl AddAssign for BusCost {
+    fn add_assign(&mut self, other: BusCost) {
+        *self = self.plus(other);
+    }
+}
+
+impl core::iter::Sum for BusCost {
+    fn sum<I: Iterator<Item = BusCost>>(iter: I) -> BusCost {
+        iter.fold(BusCost::NONE, BusCost::plus)
+    }
+}
+
+impl fmt::Display for PlannedWrite {
+    /// Prints `Output Port 0 (AI) <- 0x01 0x04 0x00`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} Port {}",
+            self.register.kind().name(),
+            self.register.port() as u8
+        )?;
+        if self.is_auto_increment() {
+            f.write_str(" (AI)")?;
+        }
+        f.write_str(" <-")?;
+        for byte in self.payload() {
+            write!(f, " {:#04x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the value a register holds after power-on or reset, taking inputs as low.
+pub(crate) const fn power_on_value(kind: RegisterKind) -> u8 {
+    match kind {
+        RegisterKind::Input | RegisterKind::PolarityInversion => 0x00,
+        RegisterKind::Output | RegisterKind::Configuration | RegisterKind::InterruptMask => 0xFF,
+    }
+}
+
+/// Plans the writes bringing the ports of a register family from `current` to `target`.
+///
+/// Ports without a target, or whose target equals the current value, are skipped. Several
+/// changed ports share one auto-increment write when every port between the first and the
+/// last has a target value; otherwise each is written alone.
+pub(crate) fn family_writes(
+    kind: RegisterKind,
+    current: [Option<u8>; 3],
+    target: [Option<u8>; 3],
+) -> heapless::Vec<PlannedWrite, 3> {
+    let mut writes = heapless::Vec::new();
+    let changed = Port::ALL.map(|port| {
+        let port_target = *port.entry(&target);
+        port_target.is_some() && port_target != *port.entry(&current)
+    });
+    let (Some(first), Some(last)) = (
+        changed.iter().position(|&c| c),
+        changed.iter().rposition(|&c| c),
+    ) else {
+        return writes;
+    };
+    let span = target.get(first..=last).unwrap_or_default();
+    if first != last && span.iter().all(Option::is_some) {
+        let mut values = [0u8; 3];
+        for (value, port_target) in values.iter_mut().zip(span) {
+            *value = port_target.unwrap_or_default();
+        }
+        // At most three writes are planned, matching the capacity.
+        if let Some(write) = PlannedWrite::new(
+            Register::of(kind, Port::wrapping(first)),
+            values.get(..span.len()).unwrap_or_default(),
+        ) {
+            let _ = writes.push(write);
+        }
+        return writes;
+    }
+    for port in Port::ALL {
+        if let (true, Some(value)) = (*port.entry(&changed), *port.entry(&target))
+            && let Some(write) = PlannedWrite::new(Register::of(kind, port), &[value])
+        {
+            let _ = writes.push(write);
+        }
+    }
+    writes
+}
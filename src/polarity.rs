@@ -0,0 +1,84 @@
+//! Polarity Inversion settings tracked in the register cache.
+
+#[cfg(feature = "polarity")]
+use crate::errors::Error;
+#[cfg(feature = "polarity")]
+use crate::registers::Register;
+#[cfg(feature = "polarity")]
+use crate::{BusAccess, DelayNs};
+use crate::{Chip, Port, RegisterKind, Tca64xx};
+
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C> {
+    /// Returns the pins whose input is inverted by the device according to the register
+    /// cache (bit `n` = pin index `n`).
+    ///
+    /// The cache follows every Polarity Inversion write and read made through the driver.
+    /// Ports whose register is not in the cache are taken at the power-on default, not
+    /// inverted; call [`Tca64xx::refresh_polarity_cache`] after adopting a device configured
+    /// by someone else. Input change detection ([`Tca64xx::poll_input_events`],
+    /// [`Tca64xx::get_interrupt_sources`]) and the compensated logical reads
+    /// ([`Tca64xx::read_logical_inputs`]) rely on this word. No transfer is made.
+    pub fn inverted_inputs(&self) -> u32 {
+        Port::ALL.iter().fold(0, |inverted, &port| {
+            let mask = self
+                .cache
+                .get(RegisterKind::PolarityInversion, port)
+                .unwrap_or(0);
+            inverted | u32::from(mask) << (port as u8 * 8)
+        })
+    }
+}
+
+#[cfg(feature = "polarity")]
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca64xx",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Reads the three Polarity Inversion registers in one auto-increment transaction and
+    /// records them in the register cache, replacing the cached values.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok([port0, port1, port2])` with the values read, or an `Error` if the I2C bus
+    /// operation fails; the cache is left unchanged in that case.
+    pub async fn refresh_polarity_cache(&mut self) -> Result<[u8; 3], Error<I2C::Error>> {
+        let mut values = [0u8; 3];
+        self.read_registers_ai(Register::PolarityInversionPort0, &mut values)
+            .await?;
+        self.record_polarity(Port::Port0, &values);
+        Ok(values)
+    }
+
+    /// Reads the Polarity Inversion register of `port` and records it in the cache.
+    pub(crate) async fn read_polarity_register(
+        &mut self,
+        port: Port,
+    ) -> Result<u8, Error<I2C::Error>> {
+        let value = self
+            .read_register(Register::of(RegisterKind::PolarityInversion, port))
+            .await?;
+        self.cache.set(RegisterKind::PolarityInversion, port, value);
+        Ok(value)
+    }
+}
+
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C> {
+    /// Records Polarity Inversion `values` read from consecutive ports starting at
+    /// `start_port`; the address wraps within the family like an auto-increment read.
+    #[cfg(feature = "polarity")]
+    pub(crate) fn record_polarity(&mut self, start_port: Port, values: &[u8]) {
+        for (offset, value) in values.iter().enumerate() {
+            let port = Port::wrapping(start_port as usize + offset);
+            self.cache
+                .set(RegisterKind::PolarityInversion, port, *value);
+        }
+    }
+}
@@ -0,0 +1,366 @@
+//! Pin API following the conventions of the `port-expander` crate.
+//!
+//! Code written against `port-expander` drivers such as `Pca9555` or `Pcf8574` usually looks
+//! like this:
+//!
+//! ```ignore
+//! let mut pca = port_expander::Pca9555::new(i2c, false, false, false);
+//! let pins = pca.split();
+//! let mut led = pins.io0_0.into_output()?;
+//! led.set_high()?;
+//! ```
+//!
+//! [`PortExpander`] provides the same shape on top of a [`Tca6424`], so migrating mostly means
+//! changing the constructor and the pin names:
+//!
+//! ```ignore
+//! let mut tca = PortExpander::new(Tca6424::new(&mut i2c, 0x22)?);
+//! let pins = tca.split();
+//! let mut led = pins.p00.into_output()?;
+//! led.set_high()?;
+//! ```
+//!
+//! Pins share the driver through a [`RefCell`], implement the `embedded-hal` digital traits
+//! and are `!Send`, like `port-expander` pins using its default mutex. Requires the
+//! `port-expander` feature and is only available without the `async` feature.
+
+use core::cell::{RefCell, RefMut};
+use core::marker::PhantomData;
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital;
+
+use crate::errors::{Error, ErrorKind};
+use crate::{BusAccess, IoExpander, NoDelay, PinDirection, Tca6424};
+
+/// Pin modes.
+pub mod mode {
+    /// Pin configured as an input.
+    pub struct Input;
+    /// Pin configured as an output.
+    pub struct Output;
+}
+
+/// A [`Tca6424`] shared between the pins handed out by [`PortExpander::split`].
+pub struct PortExpander<I2C, D = NoDelay> {
+    tca: RefCell<Tca6424<I2C, D>>,
+}
+
+impl<I2C, D> PortExpander<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Wraps a driver instance.
+    ///
+    /// The pins returned by [`PortExpander::split`] start out as inputs, which is the power-on
+    /// state of the device. Do not change pin directions through the driver before wrapping it.
+    pub fn new(tca: Tca6424<I2C, D>) -> Self {
+        Self {
+            tca: RefCell::new(tca),
+        }
+    }
+
+    /// Splits the expander into its 24 pins.
+    pub fn split(&mut self) -> Parts<'_, I2C, D> {
+        Parts::new(&self.tca)
+    }
+
+    /// Returns the wrapped driver.
+    pub fn into_inner(self) -> Tca6424<I2C, D> {
+        self.tca.into_inner()
+    }
+}
+
+macro_rules! parts {
+    ($($field:ident => $index:literal),* $(,)?) => {
+        /// The pins of a [`PortExpander`], named after [`crate::Pin`].
+        pub struct Parts<'b, I2C, D = NoDelay> {
+            $(
+                #[allow(missing_docs)]
+                pub $field: Pin<'b, mode::Input, I2C, D>,
+            )*
+        }
+
+        impl<'b, I2C, D> Parts<'b, I2C, D> {
+            fn new(tca: &'b RefCell<Tca6424<I2C, D>>) -> Self {
+                Self {
+                    $($field: Pin::new($index, tca),)*
+                }
+            }
+        }
+    };
+}
+
+parts! {
+    p00 => 0, p01 => 1, p02 => 2, p03 => 3, p04 => 4, p05 => 5, p06 => 6, p07 => 7,
+    p10 => 8, p11 => 9, p12 => 10, p13 => 11, p14 => 12, p15 => 13, p16 => 14, p17 => 15,
+    p20 => 16, p21 => 17, p22 => 18, p23 => 19, p24 => 20, p25 => 21, p26 => 22, p27 => 23,
+}
+
+/// A single expander pin in mode `MODE`.
+pub struct Pin<'b, MODE, I2C, D = NoDelay> {
+    pin_mask: u32,
+    tca: &'b RefCell<Tca6424<I2C, D>>,
+    _mode: PhantomData<MODE>,
+}
+
+/// Error returned by [`Pin`] operations; implements [`embedded_hal::digital::Error`].
+///
+/// `embedded_hal::digital::ErrorKind` only has an `Other` kind, so the detailed classification
+/// is available from [`PinError::kind`], and the underlying I2C error from
+/// [`PinError::i2c_error`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinError<E: core::fmt::Debug> {
+    driver_error: Error<E>,
+}
+
+impl<E: core::fmt::Debug> PinError<E> {
+    /// Returns the underlying driver error.
+    pub fn driver_error(&self) -> &Error<E> {
+        &self.driver_error
+    }
+
+    /// Returns the underlying driver error by value.
+    pub fn into_driver_error(self) -> Error<E> {
+        self.driver_error
+    }
+
+    /// Returns the I2C error, if the operation failed on the bus.
+    pub fn i2c_error(&self) -> Option<&E> {
+        self.driver_error.i2c_error()
+    }
+}
+
+impl<E: embedded_hal::i2c::Error> PinError<E> {
+    /// Returns the classification of the underlying driver error.
+    pub fn kind(&self) -> ErrorKind {
+        self.driver_error.kind()
+    }
+}
+
+impl<E: core::fmt::Debug> From<Error<E>> for PinError<E> {
+    fn from(driver_error: Error<E>) -> Self {
+        Self { driver_error }
+    }
+}
+
+impl<E: core::fmt::Debug> digital::Error for PinError<E> {
+    fn kind(&self) -> digital::ErrorKind {
+        digital::ErrorKind::Other
+    }
+}
+
+impl<'b, MODE, I2C, D> Pin<'b, MODE, I2C, D> {
+    /// Borrows the shared driver; `Error::Busy` if a pin of the same expander already holds
+    /// it, from inside [`Pin::access_port_driver`].
+    fn driver<E: core::fmt::Debug>(&self) -> Result<RefMut<'b, Tca6424<I2C, D>>, Error<E>> {
+        self.tca.try_borrow_mut().map_err(|_| Error::Busy)
+    }
+
+    fn new(index: u8, tca: &'b RefCell<Tca6424<I2C, D>>) -> Self {
+        Self {
+            pin_mask: 1 << index,
+            tca,
+            _mode: PhantomData,
+        }
+    }
+
+    fn into_mode<NEW>(self) -> Pin<'b, NEW, I2C, D> {
+        Pin {
+            pin_mask: self.pin_mask,
+            tca: self.tca,
+            _mode: PhantomData,
+        }
+    }
+}
+
+impl<'b, MODE, I2C, D> Pin<'b, MODE, I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Returns the mask of this pin (bit `n` = pin index `n`).
+    pub fn pin_mask(&self) -> u32 {
+        self.pin_mask
+    }
+
+    /// Runs `f` with exclusive access to the shared driver.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `f`, or `Error::Busy` without running `f` if called from within
+    /// another `access_port_driver` closure.
+    pub fn access_port_driver<F, R>(&self, f: F) -> Result<R, PinError<I2C::Error>>
+    where
+        F: FnOnce(&mut Tca6424<I2C, D>) -> Result<R, Error<I2C::Error>>,
+    {
+        Ok(f(&mut *self.driver()?)?)
+    }
+
+    /// Switches the pin to an input.
+    pub fn into_input(self) -> Result<Pin<'b, mode::Input, I2C, D>, PinError<I2C::Error>> {
+        self.driver()?
+            .set_direction(self.pin_mask, PinDirection::Input)?;
+        Ok(self.into_mode())
+    }
+
+    /// Switches the pin to an output, driving it low.
+    pub fn into_output(self) -> Result<Pin<'b, mode::Output, I2C, D>, PinError<I2C::Error>> {
+        self.into_output_with(false)
+    }
+
+    /// Switches the pin to an output, driving it high.
+    pub fn into_output_high(self) -> Result<Pin<'b, mode::Output, I2C, D>, PinError<I2C::Error>> {
+        self.into_output_with(true)
+    }
+
+    /// Latches the output level before changing the direction, so the pin never glitches.
+    fn into_output_with(
+        self,
+        high: bool,
+    ) -> Result<Pin<'b, mode::Output, I2C, D>, PinError<I2C::Error>> {
+        {
+            let mut tca = self.driver()?;
+            if high {
+                tca.write(self.pin_mask, 0)?;
+            } else {
+                tca.write(0, self.pin_mask)?;
+            }
+            tca.set_direction(self.pin_mask, PinDirection::Output)?;
+        }
+        Ok(self.into_mode())
+    }
+}
+
+impl<'b, I2C, D> Pin<'b, mode::Input, I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Returns `true` if the input is high.
+    pub fn is_high(&self) -> Result<bool, PinError<I2C::Error>> {
+        Ok(self.driver()?.read(self.pin_mask)? != 0)
+    }
+
+    /// Returns `true` if the input is low.
+    pub fn is_low(&self) -> Result<bool, PinError<I2C::Error>> {
+        Ok(!self.is_high()?)
+    }
+
+    /// Returns the pin with its input polarity inverted.
+    #[cfg(feature = "polarity")]
+    pub fn into_inverted(mut self) -> Result<Self, PinError<I2C::Error>> {
+        self.set_inverted(true)?;
+        Ok(self)
+    }
+
+    /// Sets whether the input polarity of the pin is inverted.
+    #[cfg(feature = "polarity")]
+    pub fn set_inverted(&mut self, inverted: bool) -> Result<(), PinError<I2C::Error>> {
+        let pin = crate::Pin::from_index(self.pin_mask.trailing_zeros() as u8)
+            .ok_or(Error::InvalidRegisterOrPin)?;
+        self.driver()?
+            .set_pin_polarity_inversion(pin, inverted)?;
+        Ok(())
+    }
+}
+
+impl<'b, I2C, D> Pin<'b, mode::Output, I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Drives the pin high.
+    pub fn set_high(&mut self) -> Result<(), PinError<I2C::Error>> {
+        self.driver()?.write(self.pin_mask, 0)?;
+        Ok(())
+    }
+
+    /// Drives the pin low.
+    pub fn set_low(&mut self) -> Result<(), PinError<I2C::Error>> {
+        self.driver()?.write(0, self.pin_mask)?;
+        Ok(())
+    }
+
+    /// Returns `true` if the output latch is high.
+    pub fn is_set_high(&self) -> Result<bool, PinError<I2C::Error>> {
+        Ok(self.driver()?.read_outputs(self.pin_mask)? != 0)
+    }
+
+    /// Returns `true` if the output latch is low.
+    pub fn is_set_low(&self) -> Result<bool, PinError<I2C::Error>> {
+        Ok(!self.is_set_high()?)
+    }
+
+    /// Inverts the output level.
+    pub fn toggle(&mut self) -> Result<(), PinError<I2C::Error>> {
+        if self.is_set_high()? {
+            self.set_low()
+        } else {
+            self.set_high()
+        }
+    }
+}
+
+impl<MODE, I2C, D> digital::ErrorType for Pin<'_, MODE, I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    type Error = PinError<I2C::Error>;
+}
+
+impl<I2C, D> digital::InputPin for Pin<'_, mode::Input, I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Pin::is_high(self)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Pin::is_low(self)
+    }
+}
+
+impl<I2C, D> digital::OutputPin for Pin<'_, mode::Output, I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Pin::set_low(self)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Pin::set_high(self)
+    }
+}
+
+impl<I2C, D> digital::StatefulOutputPin for Pin<'_, mode::Output, I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+        Pin::is_set_high(self)
+    }
+
+    fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+        Pin::is_set_low(self)
+    }
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        Pin::toggle(self)
+    }
+}
@@ -0,0 +1,176 @@
+//! Poll-driven operation queue for cooperative schedulers.
+
+use heapless::Deque;
+
+use crate::errors::Error;
+use crate::operation::{InFlight, Operation, StepResult};
+use crate::{BusAccess, DelayNs, Tca6424};
+
+/// Outcome of one [`OpQueue::service`] call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ServiceResult {
+    /// The queue was empty; no transaction was issued.
+    Idle,
+    /// One transaction was issued and the current operation needs more.
+    Progressed,
+    /// One transaction was issued and it completed `operation`. `value` holds the register
+    /// value for [`Operation::Read`].
+    Completed {
+        /// The completed operation.
+        operation: Operation,
+        /// The value read, for read operations.
+        value: Option<u8>,
+    },
+}
+
+/// Priority class of a queued [`Operation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Priority {
+    /// Serviced before any normal operation, for example safety outputs.
+    High,
+    /// Serviced when no high-priority operation is pending, for example cosmetic LEDs.
+    #[default]
+    Normal,
+}
+
+/// A fixed-capacity queue of [`Operation`]s serviced one I2C transaction at a time.
+///
+/// Application code enqueues pin and port operations from anywhere in the main loop; a single
+/// [`OpQueue::service`] call per loop iteration then issues at most one transaction, bounding
+/// the time spent in the expander driver.
+///
+/// Operations have a [`Priority`]: high-priority operations always start before normal ones,
+/// so when the bus cannot keep up the normal operations wait. An operation already in flight
+/// is finished first, so a read-modify-write is never interleaved with another operation.
+/// Within a class, operations complete in FIFO order. Each class holds up to `N` operations.
+#[derive(Debug, Default)]
+pub struct OpQueue<const N: usize> {
+    urgent: Deque<Operation, N>,
+    pending: Deque<Operation, N>,
+    in_flight: Option<InFlight>,
+}
+
+impl<const N: usize> OpQueue<N> {
+    /// Creates an empty queue.
+    pub const fn new() -> Self {
+        Self {
+            urgent: Deque::new(),
+            pending: Deque::new(),
+            in_flight: None,
+        }
+    }
+
+    /// Appends an operation with [`Priority::Normal`]. Returns it back as `Err` if the queue is
+    /// full.
+    pub fn enqueue(&mut self, operation: Operation) -> Result<(), Operation> {
+        self.enqueue_with_priority(operation, Priority::Normal)
+    }
+
+    /// Appends an operation to the `priority` class. Returns it back as `Err` if that class is
+    /// full.
+    pub fn enqueue_with_priority(
+        &mut self,
+        operation: Operation,
+        priority: Priority,
+    ) -> Result<(), Operation> {
+        self.class_mut(priority).push_back(operation)
+    }
+
+    /// Returns the number of operations not yet completed, including the one in flight.
+    pub fn len(&self) -> usize {
+        self.urgent.len() + self.pending.len() + usize::from(self.in_flight.is_some())
+    }
+
+    /// Returns `true` if there is no pending work.
+    pub fn is_empty(&self) -> bool {
+        self.in_flight.is_none() && self.urgent.is_empty() && self.pending.is_empty()
+    }
+
+    /// Returns `true` if no more [`Priority::Normal`] operations can be enqueued.
+    pub fn is_full(&self) -> bool {
+        self.is_full_for(Priority::Normal)
+    }
+
+    /// Returns `true` if no more operations of the `priority` class can be enqueued.
+    pub fn is_full_for(&self, priority: Priority) -> bool {
+        match priority {
+            Priority::High => self.urgent.is_full(),
+            Priority::Normal => self.pending.is_full(),
+        }
+    }
+
+    /// Returns the number of I2C transactions needed to drain the queue.
+    pub fn pending_transactions(&self) -> usize {
+        self.in_flight
+            .as_ref()
+            .map_or(0, InFlight::remaining_transactions)
+            + self
+                .urgent
+                .iter()
+                .chain(self.pending.iter())
+                .map(Operation::transaction_count)
+                .sum::<usize>()
+    }
+
+    /// Drops every pending operation of both classes, including a partially executed one.
+    pub fn clear(&mut self) {
+        self.urgent.clear();
+        self.pending.clear();
+        self.in_flight = None;
+    }
+
+    fn class_mut(&mut self, priority: Priority) -> &mut Deque<Operation, N> {
+        match priority {
+            Priority::High => &mut self.urgent,
+            Priority::Normal => &mut self.pending,
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "OpQueue",),
+    async(feature = "async", keep_self)
+)]
+impl<const N: usize> OpQueue<N> {
+    /// Issues at most one I2C transaction towards the operation in flight, or else towards
+    /// the oldest high-priority operation, or else the oldest normal one.
+    ///
+    /// If the transaction fails the operation stays at the head of the queue and the same
+    /// step is retried by the next call.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the [`ServiceResult`], or an `Error` if the I2C bus operation fails.
+    pub async fn service<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+    ) -> Result<ServiceResult, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        if self.in_flight.is_none() {
+            self.in_flight = self
+                .urgent
+                .pop_front()
+                .or_else(|| self.pending.pop_front())
+                .map(InFlight::new);
+        }
+        let Some(in_flight) = self.in_flight.as_mut() else {
+            return Ok(ServiceResult::Idle);
+        };
+        match tca.step(in_flight).await? {
+            StepResult::Pending => Ok(ServiceResult::Progressed),
+            StepResult::Done(value) => {
+                let operation = *in_flight.operation();
+                self.in_flight = None;
+                Ok(ServiceResult::Completed { operation, value })
+            }
+        }
+    }
+}
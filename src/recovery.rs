@@ -0,0 +1,168 @@
+//! Detection of, and recovery from, unexpected device resets.
+
+use crate::errors::Error;
+use crate::registers::Register;
+use crate::{Activity, BusAccess, Chip, DelayNs, Port, RegisterKind, Tca64xx};
+
+/// Power-on default of a Configuration register (all pins are inputs).
+const CONFIGURATION_DEFAULT: u8 = 0xFF;
+
+/// Order in which cached register families are written back to the device.
+///
+/// Outputs go first so that pins being turned into outputs immediately drive the
+/// intended level; the Configuration registers go last.
+pub(crate) const RESTORE_ORDER: [RegisterKind; 4] = [
+    RegisterKind::Output,
+    RegisterKind::PolarityInversion,
+    RegisterKind::InterruptMask,
+    RegisterKind::Configuration,
+];
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca64xx",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Detects whether the device has been reset and, if so, re-applies the cached configuration.
+    ///
+    /// A reset (brown-out, hot-plug, supply dip) returns every Configuration register to its
+    /// power-on default of `0xFF`. This method reads the Configuration registers and treats the
+    /// device as reset when all three read back as `0xFF` while the [`RegisterCache`](crate::RegisterCache)
+    /// holds a different value for at least one port. In that case every cached register is
+    /// written back using [`Tca64xx::restore_from_cache`].
+    ///
+    /// If nothing has been configured through this driver yet, a reset cannot be told apart
+    /// from the initial state and `Ok(false)` is returned.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(true)` if a reset was detected and the configuration re-applied, `Ok(false)`
+    /// if the device looks configured, or an `Error` if an I2C bus operation fails.
+    pub async fn check_and_recover(&mut self) -> Result<bool, Error<I2C::Error>> {
+        let configuration = self.read_configuration().await?;
+
+        let at_defaults = configuration
+            .iter()
+            .all(|&value| value == CONFIGURATION_DEFAULT);
+        let cache_differs = Port::ALL.iter().any(|&port| {
+            matches!(
+                self.cache.get(RegisterKind::Configuration, port),
+                Some(value) if value != CONFIGURATION_DEFAULT
+            )
+        });
+
+        if at_defaults && cache_differs {
+            self.restore_from_cache().await?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Writes every cached register value back to the device.
+    ///
+    /// Families are written in the order Output, Polarity Inversion, Interrupt Mask and
+    /// Configuration, so outputs never glitch to the power-on level when their direction is
+    /// restored. A family whose three ports are all cached is written with one auto-increment
+    /// transaction; otherwise only the known ports are written individually.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn restore_from_cache(&mut self) -> Result<(), Error<I2C::Error>> {
+        let scope = self.enter_activity(Activity::Restore);
+        let result = self.write_cached_registers().await;
+        self.leave_activity(scope, result)
+    }
+
+    async fn write_cached_registers(&mut self) -> Result<(), Error<I2C::Error>> {
+        for kind in RESTORE_ORDER {
+            if let Some(values) = self.cache.family(kind) {
+                self.write_registers_ai(Register::of(kind, Port::Port0), &values)
+                    .await?;
+            } else {
+                for port in Port::ALL {
+                    if let Some(value) = self.cache.get(kind, port) {
+                        self.write_register(Register::of(kind, port), value).await?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Points the driver at the device at `address` and gives it the current configuration.
+    ///
+    /// The register cache acts as a configuration template: the new device is probed by
+    /// reading its Configuration registers, then every cached register is written to it with
+    /// [`Tca64xx::restore_from_cache`]. Configure the first device, then call `retarget` for
+    /// each following one, for example on a production programming fixture. The hot-plug
+    /// NACK streak, any saved sleep state, the interrupt image and the statistics baselines
+    /// are reset, as with [`Tca64xx::set_address`], but the cache is kept.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` once the new device is configured, or an `Error` if an I2C bus
+    /// operation fails. The driver keeps the new address, so the call can be retried.
+    pub async fn retarget(&mut self, address: u8) -> Result<(), Error<I2C::Error>> {
+        self.forget_device(address);
+        self.read_configuration().await?;
+        self.restore_from_cache().await
+    }
+
+    /// Reads the Configuration registers of the part in one transfer. Ports the part does not
+    /// have read as the power-on default.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    pub(crate) async fn read_configuration(&mut self) -> Result<[u8; 3], Error<I2C::Error>> {
+        let mut configuration = [CONFIGURATION_DEFAULT; 3];
+        if let Some(ports) = configuration.get_mut(..C::PORTS) {
+            self.read_registers_ai(Register::ConfigurationPort0, ports)
+                .await?;
+        }
+        Ok(configuration)
+    }
+
+    /// Resolves registers left dirty by an interrupted write.
+    ///
+    /// Dropping a driver future mid-transfer, or an async transfer timeout, leaves the written
+    /// registers [dirty](crate::RegisterCache::is_dirty): whether the device latched the new
+    /// value is unknown, so the cache reports those registers as unknown. This method reads each
+    /// dirty register back and records the device's value in the cache, so later
+    /// read-modify-write operations and [`Tca64xx::restore_from_cache`] work from what the
+    /// device actually holds. It is the one place where the cache is updated from a read.
+    ///
+    /// Call it after cancelling a driver future (for example when a `select!` branch loses).
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(true)` if any register was dirty, `Ok(false)` if there was nothing to do,
+    /// or an `Error` if an I2C bus operation fails (registers not read yet stay dirty).
+    pub async fn sync_after_cancel(&mut self) -> Result<bool, Error<I2C::Error>> {
+        if !self.cache.is_dirty() {
+            return Ok(false);
+        }
+        for kind in RESTORE_ORDER {
+            for port in Port::ALL {
+                if self.cache.is_register_dirty(kind, port) {
+                    let value = self.read_register(Register::of(kind, port)).await?;
+                    self.cache.set(kind, port, value);
+                }
+            }
+        }
+        Ok(true)
+    }
+}
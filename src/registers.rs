@@ -2,9 +2,84 @@
 
 use bitflags::bitflags;
 
-/// TCA6424 寄存器地址
-#[allow(dead_code)] // 允许在未使用时保留定义
+use crate::{Pin, Port, RegisterKind};
+
+/// Auto-increment bit of the command byte.
+///
+/// When set, the register address advances after every byte, wrapping around within the
+/// three registers of a family.
+pub const AUTO_INCREMENT: u8 = 0x80;
+
+/// Returns the command byte that selects `register`, optionally with auto-increment.
+///
+/// The command byte is the first byte written after the device address. Use it to build
+/// custom `I2c::transaction` sequences, for example to access the expander and another
+/// device under one bus lock:
+///
+/// ```
+/// use tca6424::{Register, command_byte};
+///
+/// assert_eq!(command_byte(Register::OutputPort1, false), 0x05);
+/// assert_eq!(command_byte(Register::ConfigurationPort0, true), 0x8C);
+/// ```
+pub const fn command_byte(register: Register, auto_increment: bool) -> u8 {
+    if auto_increment {
+        register as u8 | AUTO_INCREMENT
+    } else {
+        register as u8
+    }
+}
+
+/// Returns the register of the `kind` family that holds `pin`, and the pin's bit in it.
+///
+/// Being a `const fn`, it lets board support crates and macros compute register and bit
+/// pairs at compile time, for documentation tables or static assertions on a pin map:
+///
+/// ```
+/// use tca6424::{Pin, Register, RegisterKind, register_for};
+///
+/// const LED_DIRECTION: (Register, u8) = register_for(RegisterKind::Configuration, Pin::P13);
+/// const _: () = assert!(LED_DIRECTION.1 == 3);
+/// assert_eq!(LED_DIRECTION, (Register::ConfigurationPort1, 3));
+/// ```
+pub const fn register_for(kind: RegisterKind, pin: Pin) -> (Register, u8) {
+    (Register::of(kind, pin.port()), pin.bit())
+}
+
+/// Returns the register addressed by `command`, ignoring the auto-increment bit.
+pub(crate) fn register_of(command: u8) -> Register {
+    let address = command & !AUTO_INCREMENT;
+    let kind = match address >> 2 {
+        0 => RegisterKind::Input,
+        1 => RegisterKind::Output,
+        2 => RegisterKind::PolarityInversion,
+        3 => RegisterKind::Configuration,
+        _ => RegisterKind::InterruptMask,
+    };
+    let port = match address & 0x03 {
+        0 => Port::Port0,
+        1 => Port::Port1,
+        _ => Port::Port2,
+    };
+    Register::of(kind, port)
+}
+
+/// Returns the registers a transfer of `len` bytes with `command` accesses, in order.
+pub(crate) fn command_registers(command: u8, len: usize) -> impl Iterator<Item = Register> {
+    let start = register_of(command);
+    let auto_increment = command & AUTO_INCREMENT != 0;
+    (0..len).map(move |offset| {
+        if auto_increment {
+            Register::of(start.kind(), Port::wrapping(start.port() as usize + offset))
+        } else {
+            start
+        }
+    })
+}
+
+/// TCA6424 register addresses; each value is the register address of the command byte.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum Register {
     /// Input Port 0
@@ -39,6 +114,55 @@ pub enum Register {
     InterruptMaskPort2 = 0x12,
 }
 
+/// The register address map: the registers of each family, in [`RegisterKind::ALL`] order,
+/// by port.
+const REGISTER_MAP: [[Register; 3]; 5] = [
+    [Register::InputPort0, Register::InputPort1, Register::InputPort2],
+    [Register::OutputPort0, Register::OutputPort1, Register::OutputPort2],
+    [
+        Register::PolarityInversionPort0,
+        Register::PolarityInversionPort1,
+        Register::PolarityInversionPort2,
+    ],
+    [
+        Register::ConfigurationPort0,
+        Register::ConfigurationPort1,
+        Register::ConfigurationPort2,
+    ],
+    [
+        Register::InterruptMaskPort0,
+        Register::InterruptMaskPort1,
+        Register::InterruptMaskPort2,
+    ],
+];
+
+impl Register {
+    /// Returns the register of the `kind` family that belongs to `port`.
+    pub const fn of(kind: RegisterKind, port: Port) -> Register {
+        *port.entry(kind.entry(&REGISTER_MAP))
+    }
+
+    /// Returns the register family this register belongs to.
+    pub const fn kind(self) -> RegisterKind {
+        match self as u8 >> 2 {
+            0 => RegisterKind::Input,
+            1 => RegisterKind::Output,
+            2 => RegisterKind::PolarityInversion,
+            3 => RegisterKind::Configuration,
+            _ => RegisterKind::InterruptMask,
+        }
+    }
+
+    /// Returns the port this register belongs to.
+    pub const fn port(self) -> Port {
+        match self as u8 & 0x03 {
+            0 => Port::Port0,
+            1 => Port::Port1,
+            _ => Port::Port2,
+        }
+    }
+}
+
 bitflags! {
     /// Configuration register bits (Input=1, Output=0)
     #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
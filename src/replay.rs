@@ -0,0 +1,191 @@
+//! Synthetic and recorded input sequences for testing applications without hardware.
+
+use crate::{InputChanges, InputEvent, InputWatcher, Pin, PinState};
+#[cfg(feature = "capture")]
+use crate::{TransferDirection, TransferOutcome, TransferRecord};
+
+/// Mask of the 24 pin bits in an input image.
+const PIN_BITS: u32 = 0x00FF_FFFF;
+
+/// One input change of an [`InputReplay`], `at_ms` after the replay started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ReplayStep {
+    /// Time of the change, in milliseconds from the start of the replay.
+    pub at_ms: u32,
+    /// The pin and its new level. The sequence number is ignored: the watcher numbers the
+    /// events it reports.
+    pub event: InputEvent,
+}
+
+impl ReplayStep {
+    /// Creates a step changing `pin` to `state` at `at_ms`.
+    pub const fn new(at_ms: u32, pin: Pin, state: PinState) -> Self {
+        Self {
+            at_ms,
+            event: InputEvent { pin, state, seq: 0 },
+        }
+    }
+}
+
+/// Plays a sequence of input changes into an [`InputWatcher`] in place of the expander.
+///
+/// Application code driven by input events usually calls [`Tca6424::poll_input_events`]
+/// and handles the returned [`InputChanges`]. In a unit test, an `InputReplay` produces the
+/// same `InputChanges` from a scripted sequence of [`ReplayStep`]s, so the state machine
+/// under test sees exactly the events the hardware would report, deterministically:
+///
+/// - [`InputReplay::poll`] applies every step due at a timestamp, for code paced by a clock;
+/// - [`InputReplay::next_changes`] applies the next group of simultaneous steps, ignoring
+///   time, for tests that walk through a sequence event by event.
+///
+/// With the `capture` feature, `InputReplay::feed_record` replays the Input Port reads of a
+/// trace recorded on real hardware (see `Tca6424::trace`) instead.
+///
+/// The watcher's subscriptions apply as usual. It is primed with the initial image on
+/// construction, so the first poll already reports changes.
+///
+/// ```
+/// use tca6424::{InputReplay, InputWatcher, Pin, PinState, Port, ReplayStep};
+///
+/// static PRESS: [ReplayStep; 2] = [
+///     ReplayStep::new(10, Pin::P20, PinState::Low),
+///     ReplayStep::new(60, Pin::P20, PinState::High),
+/// ];
+/// let watcher = InputWatcher::new().with_port(Port::Port2);
+/// let mut replay = InputReplay::new(watcher, 0xFF_FFFF, &PRESS);
+///
+/// assert!(replay.poll(0).is_empty());
+/// let changes = replay.poll(10);
+/// assert_eq!(changes.changed_mask(), tca6424::pins!(P20));
+/// assert_eq!(replay.next_changes().unwrap().changed_mask(), tca6424::pins!(P20));
+/// assert!(replay.is_finished());
+/// ```
+///
+/// [`Tca6424::poll_input_events`]: crate::Tca6424::poll_input_events
+#[derive(Debug, Clone)]
+pub struct InputReplay<'a> {
+    watcher: InputWatcher,
+    inputs: u32,
+    steps: &'a [ReplayStep],
+    next: usize,
+    started_ms: Option<u32>,
+}
+
+impl<'a> InputReplay<'a> {
+    /// Creates a replay of `steps`, sorted by time, starting from the input image `initial`
+    /// (bit `n` = pin index `n`).
+    pub fn new(mut watcher: InputWatcher, initial: u32, steps: &'a [ReplayStep]) -> Self {
+        let inputs = initial & PIN_BITS;
+        watcher.update(inputs);
+        Self {
+            watcher,
+            inputs,
+            steps,
+            next: 0,
+            started_ms: None,
+        }
+    }
+
+    /// Returns the current synthetic input image.
+    pub fn inputs(&self) -> u32 {
+        self.inputs
+    }
+
+    /// Returns the watcher receiving the images.
+    pub fn watcher(&self) -> &InputWatcher {
+        &self.watcher
+    }
+
+    /// Returns the watcher receiving the images, for changing its subscriptions.
+    pub fn watcher_mut(&mut self) -> &mut InputWatcher {
+        &mut self.watcher
+    }
+
+    /// Returns `true` once every step has been applied.
+    pub fn is_finished(&self) -> bool {
+        self.next == self.steps.len()
+    }
+
+    /// Applies every step due at `now_ms`, a free-running millisecond timestamp, and returns
+    /// the subscribed changes. The first call starts the replay.
+    pub fn poll(&mut self, now_ms: u32) -> InputChanges {
+        let started = *self.started_ms.get_or_insert(now_ms);
+        let elapsed = now_ms.wrapping_sub(started);
+        while let Some(step) = self.steps.get(self.next) {
+            if step.at_ms > elapsed {
+                break;
+            }
+            self.apply(step.event);
+            self.next += 1;
+        }
+        self.watcher.update(self.inputs)
+    }
+
+    /// Applies the next step, together with the following steps at the same time, and
+    /// returns the subscribed changes.
+    ///
+    /// # Returns
+    ///
+    /// Returns the changes, which are empty if the steps only touched unsubscribed pins, or
+    /// `None` once the replay is finished.
+    pub fn next_changes(&mut self) -> Option<InputChanges> {
+        let at_ms = self.steps.get(self.next)?.at_ms;
+        while let Some(step) = self.steps.get(self.next) {
+            if step.at_ms != at_ms {
+                break;
+            }
+            self.apply(step.event);
+            self.next += 1;
+        }
+        Some(self.watcher.update(self.inputs))
+    }
+
+    /// Rewinds to the start of the sequence with `initial` as the input image.
+    pub fn restart(&mut self, initial: u32) {
+        self.inputs = initial & PIN_BITS;
+        self.watcher.update(self.inputs);
+        self.next = 0;
+        self.started_ms = None;
+    }
+
+    fn apply(&mut self, event: InputEvent) {
+        let bit = 1 << event.pin as u8;
+        match event.state {
+            PinState::High => self.inputs |= bit,
+            PinState::Low => self.inputs &= !bit,
+        }
+    }
+}
+
+#[cfg(feature = "capture")]
+impl InputReplay<'_> {
+    /// Replays one captured transfer: a successful read of Input Port registers updates the
+    /// image with the ports it read and reports the subscribed changes. Any other record is
+    /// ignored.
+    ///
+    /// # Returns
+    ///
+    /// Returns the changes, or `None` if the record is not a successful Input Port read.
+    pub fn feed_record(&mut self, record: &TransferRecord) -> Option<InputChanges> {
+        let register = record.command & 0x7F;
+        if record.direction != TransferDirection::Read
+            || record.outcome != TransferOutcome::Ok
+            || register > 0x02
+        {
+            return None;
+        }
+        let data = record.data();
+        // Without auto-increment every byte comes from the same register.
+        let count = if record.command & 0x80 != 0 {
+            data.len().min(3 - usize::from(register))
+        } else {
+            data.len().min(1)
+        };
+        for (offset, &value) in data.iter().take(count).enumerate() {
+            let shift = (usize::from(register) + offset) * 8;
+            self.inputs = self.inputs & !(0xFF << shift) | u32::from(value) << shift;
+        }
+        Some(self.watcher.update(self.inputs))
+    }
+}
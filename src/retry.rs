@@ -0,0 +1,223 @@
+//! Retries of register transfers that collided with other bus traffic, with randomized
+//! backoff. Without the `retry` feature every transfer is tried once.
+
+#[cfg(feature = "retry")]
+use embedded_hal::i2c::ErrorKind;
+
+use crate::errors::Error;
+use crate::{BusAccess, Chip, DelayNs, Tca64xx};
+
+/// Retry policy for register transfers that fail because the bus was busy.
+///
+/// With a policy set (see [`Tca64xx::set_retry_policy`]), a transfer that fails with
+/// arbitration loss or a bus error, the errors another master or task on the same bus
+/// causes, is retried up to [`RetryPolicy::retries`] times. Other errors, such as a NACK
+/// from a missing device, are returned at once.
+///
+/// Before retry `n` (counted from 0) the driver waits `base_us * 2^n`, capped at `max_us`,
+/// with its delay source (see [`Tca64xx::with_delay`]). Two drivers that failed together
+/// would retry together and collide again; [`RetryPolicy::with_jitter`] draws each wait
+/// uniformly from its upper half instead, from a pseudo-random sequence seeded per driver.
+///
+/// ```
+/// use tca6424::RetryPolicy;
+///
+/// // Up to 3 retries after 100, 200 and 400 µs, each shortened by up to half at random
+/// let policy = RetryPolicy::new(3)
+///     .with_backoff_us(100, 1_000)
+///     .with_jitter(0x2A);
+/// assert_eq!(policy.retries(), 3);
+/// ```
+#[cfg(feature = "retry")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RetryPolicy {
+    retries: u8,
+    base_us: u32,
+    max_us: u32,
+    seed: Option<u32>,
+}
+
+#[cfg(feature = "retry")]
+impl RetryPolicy {
+    /// Creates a policy retrying up to `retries` times, immediately and without jitter.
+    pub const fn new(retries: u8) -> Self {
+        Self {
+            retries,
+            base_us: 0,
+            max_us: 0,
+            seed: None,
+        }
+    }
+
+    /// Waits `base_us`, doubling with every retry up to `max_us`, before retrying.
+    pub const fn with_backoff_us(mut self, base_us: u32, max_us: u32) -> Self {
+        self.base_us = base_us;
+        self.max_us = if max_us > base_us { max_us } else { base_us };
+        self
+    }
+
+    /// Randomizes every wait within its upper half, from a sequence seeded with `seed`.
+    ///
+    /// Give every driver or task sharing the bus a different seed, for example from a unique
+    /// device ID, so their retries drift apart.
+    pub const fn with_jitter(mut self, seed: u32) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Returns the maximum number of retries of a transfer.
+    pub const fn retries(&self) -> u8 {
+        self.retries
+    }
+
+    /// Returns the wait before the first retry and the cap on the doubled waits.
+    pub const fn backoff_us(&self) -> (u32, u32) {
+        (self.base_us, self.max_us)
+    }
+
+    /// Returns the jitter seed, if jitter is enabled.
+    pub const fn jitter_seed(&self) -> Option<u32> {
+        self.seed
+    }
+
+    /// Returns the wait before retry `attempt` without jitter.
+    const fn backoff(&self, attempt: u8) -> u32 {
+        let doubled = match 1u32.checked_shl(attempt as u32) {
+            Some(factor) => self.base_us.saturating_mul(factor),
+            None => u32::MAX,
+        };
+        if doubled < self.max_us {
+            doubled
+        } else {
+            self.max_us
+        }
+    }
+}
+
+/// The retry policy of a driver and the state of its jitter sequence.
+#[cfg(feature = "retry")]
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct RetryState {
+    policy: Option<RetryPolicy>,
+    rng: u32,
+}
+
+#[cfg(feature = "retry")]
+impl RetryState {
+    /// Returns the wait before retrying a transfer that ended with `result` after `attempt`
+    /// retries, or `None` if it must not be retried.
+    fn next_wait<T, E>(&mut self, result: &Result<T, Error<E>>, attempt: u8) -> Option<u32>
+    where
+        E: embedded_hal::i2c::Error,
+    {
+        let policy = self.policy?;
+        let busy = match result {
+            Err(Error::I2c(error)) => {
+                matches!(error.kind(), ErrorKind::ArbitrationLoss | ErrorKind::Bus)
+            }
+            _ => false,
+        };
+        if !busy || attempt >= policy.retries {
+            return None;
+        }
+        let wait = policy.backoff(attempt);
+        if policy.seed.is_none() || wait < 2 {
+            return Some(wait);
+        }
+        // xorshift32: cheap, and plenty to decorrelate a few bus users
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 17;
+        self.rng ^= self.rng << 5;
+        let half = wait / 2;
+        Some(half + self.rng % (wait - half + 1))
+    }
+}
+
+#[cfg(feature = "retry")]
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C> {
+    /// Retries register transfers that fail on a busy bus according to `policy` (see
+    /// [`RetryPolicy`]), restarting its jitter sequence from the seed.
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry = RetryState {
+            policy: Some(policy),
+            // xorshift32 never leaves 0
+            rng: policy.seed.map_or(1, |seed| seed.max(1)),
+        };
+    }
+
+    /// Stops retrying failed transfers.
+    pub fn clear_retry_policy(&mut self) {
+        self.retry = RetryState::default();
+    }
+
+    /// Returns the policy set with [`Tca64xx::set_retry_policy`], if any.
+    pub fn retry_policy(&self) -> Option<RetryPolicy> {
+        self.retry.policy
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca64xx",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// [`Tca64xx::send`], retried on a busy bus according to the retry policy.
+    #[cfg(feature = "retry")]
+    pub(crate) async fn send_retrying(
+        &mut self,
+        command_byte: u8,
+        payload: &[u8],
+    ) -> Result<(), Error<I2C::Error>> {
+        let mut result = self.send(command_byte, payload).await;
+        let mut attempt = 0;
+        while let Some(wait_us) = self.retry.next_wait(&result, attempt) {
+            self.delay.delay_us(wait_us).await;
+            result = self.send(command_byte, payload).await;
+            attempt += 1;
+        }
+        result
+    }
+
+    /// [`Tca64xx::fetch`], retried on a busy bus according to the retry policy.
+    #[cfg(feature = "retry")]
+    pub(crate) async fn fetch_retrying(
+        &mut self,
+        command_byte: u8,
+        buffer: &mut [u8],
+    ) -> Result<(), Error<I2C::Error>> {
+        let mut result = self.fetch(command_byte, buffer).await;
+        let mut attempt = 0;
+        while let Some(wait_us) = self.retry.next_wait(&result, attempt) {
+            self.delay.delay_us(wait_us).await;
+            result = self.fetch(command_byte, buffer).await;
+            attempt += 1;
+        }
+        result
+    }
+
+    /// [`Tca64xx::send`], tried once.
+    #[cfg(not(feature = "retry"))]
+    pub(crate) async fn send_retrying(
+        &mut self,
+        command_byte: u8,
+        payload: &[u8],
+    ) -> Result<(), Error<I2C::Error>> {
+        self.send(command_byte, payload).await
+    }
+
+    /// [`Tca64xx::fetch`], tried once.
+    #[cfg(not(feature = "retry"))]
+    pub(crate) async fn fetch_retrying(
+        &mut self,
+        command_byte: u8,
+        buffer: &mut [u8],
+    ) -> Result<(), Error<I2C::Error>> {
+        self.fetch(command_byte, buffer).await
+    }
+}
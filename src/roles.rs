@@ -0,0 +1,133 @@
+//! Input-side and output-side handles over one shared driver.
+//!
+//! [`SharedTca6424::split_roles`] hands out an [`Outputs`] handle, reaching only the Output
+//! and Configuration registers, and an [`Inputs`] handle, reaching only the Input and
+//! Interrupt Mask registers. An output-driving task and an input-scanning task each own one
+//! handle; every call locks the shared driver for the duration of one method, so the two
+//! tasks interleave at method granularity without sharing a `&mut` driver.
+
+use embassy_sync::blocking_mutex::raw::RawMutex;
+
+use crate::errors::Error;
+use crate::{
+    BusAccess, DelayNs, InterruptThrottle, NoDelay, Pin, PinDirection, PinState, Port, PortState,
+    SharedTca6424,
+};
+
+/// Forwards a driver method through the shared mutex, in the sync or async flavour.
+macro_rules! forward {
+    ($(#[$meta:meta])* $name:ident($($arg:ident: $ty:ty),*) -> $ret:ty) => {
+        $(#[$meta])*
+        ///
+        /// This method is `async` when the `async` feature is enabled, and synchronous
+        /// otherwise.
+        #[cfg(feature = "async")]
+        pub async fn $name(&self, $($arg: $ty),*) -> Result<$ret, Error<I2C::Error>> {
+            self.shared.lock().await.$name($($arg),*).await
+        }
+
+        $(#[$meta])*
+        ///
+        /// This method is `async` when the `async` feature is enabled, and synchronous
+        /// otherwise.
+        #[cfg(not(feature = "async"))]
+        pub fn $name(&self, $($arg: $ty),*) -> Result<$ret, Error<I2C::Error>> {
+            self.shared.lock(|tca| tca.$name($($arg),*))
+        }
+    };
+}
+
+/// The output side of a [`SharedTca6424`]: Output and Configuration registers.
+pub struct Outputs<'a, M: RawMutex, I2C, D = NoDelay> {
+    shared: &'a SharedTca6424<M, I2C, D>,
+}
+
+/// The input side of a [`SharedTca6424`]: Input and Interrupt Mask registers.
+pub struct Inputs<'a, M: RawMutex, I2C, D = NoDelay> {
+    shared: &'a SharedTca6424<M, I2C, D>,
+}
+
+impl<M: RawMutex, I2C, D> SharedTca6424<M, I2C, D> {
+    /// Splits access into an [`Outputs`] and an [`Inputs`] handle, one per task.
+    pub fn split_roles(&self) -> (Outputs<'_, M, I2C, D>, Inputs<'_, M, I2C, D>) {
+        (Outputs { shared: self }, Inputs { shared: self })
+    }
+}
+
+impl<M: RawMutex, I2C, D> Outputs<'_, M, I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    forward! {
+        /// See [`Tca6424::set_pin_direction`](crate::Tca6424::set_pin_direction).
+        set_pin_direction(pin: Pin, direction: PinDirection) -> ()
+    }
+    forward! {
+        /// See [`Tca6424::get_pin_direction`](crate::Tca6424::get_pin_direction).
+        get_pin_direction(pin: Pin) -> PinDirection
+    }
+    forward! {
+        /// See [`Tca6424::set_pin_output`](crate::Tca6424::set_pin_output).
+        set_pin_output(pin: Pin, state: PinState) -> ()
+    }
+    forward! {
+        /// See [`Tca6424::get_pin_output_state`](crate::Tca6424::get_pin_output_state).
+        get_pin_output_state(pin: Pin) -> PinState
+    }
+    forward! {
+        /// See [`Tca6424::set_port_direction`](crate::Tca6424::set_port_direction).
+        set_port_direction(port: Port, direction_mask: u8) -> ()
+    }
+    forward! {
+        /// See [`Tca6424::set_port_output`](crate::Tca6424::set_port_output).
+        set_port_output(port: Port, output_mask: u8) -> ()
+    }
+    forward! {
+        /// See [`Tca6424::read_port_outputs`](crate::Tca6424::read_port_outputs).
+        read_port_outputs(port: Port) -> PortState
+    }
+    forward! {
+        /// See [`Tca6424::write_pins_masked`](crate::Tca6424::write_pins_masked).
+        write_pins_masked(port: Port, mask: u8, values: u8) -> ()
+    }
+    forward! {
+        /// See [`Tca6424::write_directions_masked`](crate::Tca6424::write_directions_masked).
+        write_directions_masked(port: Port, mask: u8, inputs: u8) -> ()
+    }
+}
+
+impl<M: RawMutex, I2C, D> Inputs<'_, M, I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    forward! {
+        /// See [`Tca6424::get_pin_input_state`](crate::Tca6424::get_pin_input_state).
+        get_pin_input_state(pin: Pin) -> PinState
+    }
+    forward! {
+        /// See [`Tca6424::read_port_inputs`](crate::Tca6424::read_port_inputs).
+        read_port_inputs(port: Port) -> PortState
+    }
+    forward! {
+        /// See [`Tca6424::read_ports`](crate::Tca6424::read_ports).
+        read_ports() -> [PortState; 3]
+    }
+    forward! {
+        /// See [`Tca6424::service_interrupt`](crate::Tca6424::service_interrupt).
+        service_interrupt(throttle: &mut InterruptThrottle, now_ms: u32) -> Option<u32>
+    }
+    forward! {
+        /// See [`Tca6424::set_pin_interrupt_mask`](crate::Tca6424::set_pin_interrupt_mask).
+        #[cfg(feature = "interrupt-mask")]
+        set_pin_interrupt_mask(pin: Pin, mask: bool) -> ()
+    }
+    forward! {
+        /// See [`Tca6424::set_port_interrupt_mask`](crate::Tca6424::set_port_interrupt_mask).
+        #[cfg(feature = "interrupt-mask")]
+        set_port_interrupt_mask(port: Port, mask: u8) -> ()
+    }
+}
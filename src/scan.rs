@@ -0,0 +1,175 @@
+//! Bus scan with address conflict and aliasing diagnosis.
+
+use crate::BusAccess;
+use crate::registers::Register;
+
+/// The two addresses a TCA6424 can respond to (ADDR low, ADDR high).
+pub const TCA6424_ADDRESSES: [u8; 2] = [0x22, 0x23];
+
+/// Diagnosis of one scanned address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AddressStatus {
+    /// Nothing acknowledged the address.
+    Absent,
+    /// A device acknowledged and behaves like a TCA6424 register file.
+    Present,
+    /// Something acknowledged, but a value written to the Polarity Inversion Port 0 register
+    /// did not read back: not a TCA6424, or a ghost produced by bus problems.
+    Ghost {
+        /// Value written.
+        wrote: u8,
+        /// Value read back, or `None` if the read-back was not acknowledged.
+        read: Option<u8>,
+    },
+    /// A device acknowledged the register read but not the write of its complement: something
+    /// answers at this address, but it cannot be written like a TCA6424.
+    WriteRejected {
+        /// Value read before the write.
+        read: u8,
+    },
+    /// A write through this address changed the register file seen through `address`:
+    /// both addresses reach the same chip (ADDR pin floating, or strapping mistake).
+    AliasOf {
+        /// The earlier scanned address that showed the same register file.
+        address: u8,
+    },
+}
+
+impl AddressStatus {
+    /// Returns `true` for [`AddressStatus::Present`].
+    pub fn is_present(&self) -> bool {
+        matches!(self, AddressStatus::Present)
+    }
+}
+
+/// Result of [`scan`]: one [`AddressStatus`] per expected address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ScanReport<const N: usize> {
+    addresses: [u8; N],
+    statuses: [AddressStatus; N],
+}
+
+impl<const N: usize> ScanReport<N> {
+    /// Returns the status of `address`, or `None` if it was not scanned.
+    pub fn status(&self, address: u8) -> Option<AddressStatus> {
+        self.addresses
+            .iter()
+            .zip(self.statuses)
+            .find_map(|(&a, status)| (a == address).then_some(status))
+    }
+
+    /// Returns `true` if every expected address holds a distinct, working expander.
+    pub fn all_present(&self) -> bool {
+        self.statuses.iter().all(AddressStatus::is_present)
+    }
+
+    /// Iterates over `(address, status)` pairs in scan order.
+    pub fn iter(&self) -> impl Iterator<Item = (u8, AddressStatus)> + '_ {
+        self.addresses
+            .iter()
+            .copied()
+            .zip(self.statuses.iter().copied())
+    }
+}
+
+/// Reads one register; `None` if the transfer fails.
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), keep_self),
+    async(feature = "async", keep_self)
+)]
+async fn probe_read<I2C: BusAccess>(i2c: &mut I2C, address: u8) -> Option<u8> {
+    let mut value = [0u8];
+    i2c.write_read(
+        address,
+        &[Register::PolarityInversionPort0 as u8],
+        &mut value,
+    )
+    .await
+    .ok()
+    .map(|()| value[0])
+}
+
+/// Scans the expected expander addresses and diagnoses conflicts before any driver is created.
+///
+/// Every address is checked in order with the Polarity Inversion Port 0 register, which
+/// only affects how inputs are reported:
+///
+/// 1. The register is read; no acknowledge means [`AddressStatus::Absent`].
+/// 2. Its complement is written; a failed write means [`AddressStatus::WriteRejected`].
+///    Otherwise the register is read back, and a mismatch or a failed read-back means
+///    [`AddressStatus::Ghost`].
+/// 3. Every earlier address found present is read again; if it now shows the complement, the
+///    two addresses reach the same chip and the later one is reported as
+///    [`AddressStatus::AliasOf`].
+///
+/// The original register value is written back afterwards. Use the report to refuse to
+/// configure anything rather than silently driving the wrong chip.
+///
+/// This function is `async` when the `async` feature is enabled, and synchronous otherwise.
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), keep_self),
+    async(feature = "async", keep_self)
+)]
+pub async fn scan<I2C: BusAccess, const N: usize>(
+    i2c: &mut I2C,
+    addresses: [u8; N],
+) -> ScanReport<N> {
+    let mut statuses = [AddressStatus::Absent; N];
+    let mut originals = [0u8; N];
+    let command = Register::PolarityInversionPort0 as u8;
+
+    for (index, address) in addresses.into_iter().enumerate() {
+        let original = match probe_read(i2c, address).await {
+            Some(original) => original,
+            None => continue,
+        };
+        if let Some(slot) = originals.get_mut(index) {
+            *slot = original;
+        }
+
+        let pattern = !original;
+        if i2c.write(address, &[command, pattern]).await.is_err() {
+            if let Some(slot) = statuses.get_mut(index) {
+                *slot = AddressStatus::WriteRejected { read: original };
+            }
+            continue;
+        }
+        let read = probe_read(i2c, address).await;
+
+        let status = if read != Some(pattern) {
+            AddressStatus::Ghost {
+                wrote: pattern,
+                read,
+            }
+        } else {
+            let mut status = AddressStatus::Present;
+            let earlier = statuses.iter().zip(addresses).zip(originals).take(index);
+            for ((earlier_status, earlier_address), earlier_original) in earlier {
+                if !earlier_status.is_present() {
+                    continue;
+                }
+                let seen = probe_read(i2c, earlier_address).await;
+                if seen == Some(pattern) && pattern != earlier_original {
+                    status = AddressStatus::AliasOf {
+                        address: earlier_address,
+                    };
+                    break;
+                }
+            }
+            status
+        };
+        if let Some(slot) = statuses.get_mut(index) {
+            *slot = status;
+        }
+
+        // Best effort: a failure here already shows up in the status.
+        let _ = i2c.write(address, &[command, original]).await;
+    }
+
+    ScanReport {
+        addresses,
+        statuses,
+    }
+}
@@ -0,0 +1,512 @@
+//! One budgeted entry point advancing the pending work of every subsystem.
+
+use core::fmt::Debug;
+
+#[cfg(feature = "portable-atomic")]
+use crate::AtomicOutputs;
+#[cfg(feature = "operations")]
+use crate::OpQueue;
+use crate::errors::Error;
+use crate::{
+    BusAccess, DelayNs, EventFanout, InputWatcher, InterruptThrottle, Tca6424, TransferBound,
+};
+#[cfg(feature = "helpers")]
+use crate::{PinMirror, PinScheduler, Port, RegisterKind, SoftPwm, WaveformPlayer};
+
+/// The number of I2C transactions a [`Tca6424::service`] call may issue.
+///
+/// [`ServiceTask`]s check the worst case of each step against
+/// [`TransactionBudget::allows`] before making it, and deduct the transactions it actually
+/// issued with [`TransactionBudget::spend`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TransactionBudget {
+    remaining: u32,
+}
+
+impl TransactionBudget {
+    /// Creates a budget of `transactions`.
+    pub const fn new(transactions: u32) -> Self {
+        Self {
+            remaining: transactions,
+        }
+    }
+
+    /// Returns the number of transactions left.
+    pub const fn remaining(&self) -> u32 {
+        self.remaining
+    }
+
+    /// Returns `true` once no transaction is left.
+    pub const fn is_exhausted(&self) -> bool {
+        self.remaining == 0
+    }
+
+    /// Returns `true` if `transactions` more fit in the budget.
+    pub const fn allows(&self, transactions: u32) -> bool {
+        transactions <= self.remaining
+    }
+
+    /// Deducts `transactions`, stopping at zero.
+    pub fn spend(&mut self, transactions: u32) {
+        self.remaining = self.remaining.saturating_sub(transactions);
+    }
+
+    /// Deducts the transactions `tca` issued since [`Tca6424::transaction_count`] was
+    /// `start`.
+    fn charge<I2C, D>(&mut self, tca: &Tca6424<I2C, D>, start: u32) {
+        self.spend(tca.transaction_count().wrapping_sub(start));
+    }
+}
+
+impl<I2C, D> Tca6424<I2C, D> {
+    /// Returns the number of I2C transactions the driver has issued, wrapping around at
+    /// `u32::MAX`. Reads answered from the register cache and transfers recorded by a dry run
+    /// are not transactions.
+    pub fn transaction_count(&self) -> u32 {
+        self.transactions
+    }
+
+    /// Returns the worst case of a [`Tca6424::drive_pins`] call touching the pins of `pins`:
+    /// one write per port whose Output Port register is cached, a read-modify-write per
+    /// other port.
+    #[cfg(feature = "helpers")]
+    fn drive_bound(&self, pins: u32) -> u32 {
+        let masked = u32::from(TransferBound::WRITE_PINS_MASKED.max(self.cache_mode()));
+        Port::ALL
+            .into_iter()
+            .filter(|&port| port.byte_of(pins) != 0)
+            .map(|port| match self.cache.get(RegisterKind::Output, port) {
+                Some(_) => 1,
+                None => masked,
+            })
+            .sum()
+    }
+}
+
+/// Pending work advanced by [`Tca6424::service`] within a [`TransactionBudget`].
+///
+/// Implemented by [`PinScheduler`](crate::PinScheduler), [`OpQueue`](crate::OpQueue),
+/// [`EventScan`], [`SoftPwm`](crate::SoftPwm), [`WaveformPlayer`](crate::WaveformPlayer),
+/// [`PinMirror`](crate::PinMirror) and
+/// `&AtomicOutputs`, by `&mut` references to tasks, by `()`, and by tuples of up to eight
+/// tasks, which are advanced in order. Implement it for application subsystems to advance
+/// them from the same call.
+#[cfg(not(feature = "async"))]
+pub trait ServiceTask<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: Debug,
+    D: DelayNs,
+{
+    /// Advances the task at `now_ms`, a free-running millisecond timestamp, making only the
+    /// steps whose worst case fits in `budget` and deducting the transactions they issued.
+    /// Steps that do not fit wait for the next call.
+    fn service_within(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        budget: &mut TransactionBudget,
+        now_ms: u32,
+    ) -> Result<(), Error<I2C::Error>>;
+}
+
+/// Pending work advanced by [`Tca6424::service`] within a [`TransactionBudget`].
+///
+/// Implemented by [`PinScheduler`](crate::PinScheduler), [`OpQueue`](crate::OpQueue),
+/// [`EventScan`], [`SoftPwm`](crate::SoftPwm), [`WaveformPlayer`](crate::WaveformPlayer),
+/// [`PinMirror`](crate::PinMirror) and
+/// `&AtomicOutputs`, by `&mut` references to tasks, by `()`, and by tuples of up to eight
+/// tasks, which are advanced in order. Implement it for application subsystems to advance
+/// them from the same call.
+#[cfg(feature = "async")]
+#[allow(async_fn_in_trait)]
+pub trait ServiceTask<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: Debug,
+    D: DelayNs,
+{
+    /// Advances the task at `now_ms`, a free-running millisecond timestamp, making only the
+    /// steps whose worst case fits in `budget` and deducting the transactions they issued.
+    /// Steps that do not fit wait for the next call.
+    async fn service_within(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        budget: &mut TransactionBudget,
+        now_ms: u32,
+    ) -> Result<(), Error<I2C::Error>>;
+}
+
+/// Scans the inputs for [`Tca6424::service`] when an interrupt is pending, and publishes the
+/// changes to every subscriber of a fan-out.
+///
+/// The scan is gated by an [`InterruptThrottle`]: call [`InterruptThrottle::notify`] from the
+/// INT edge, or on every loop iteration to poll at the throttle's interval on boards without
+/// INT. A scan that does not fit in the budget, or fails, stays pending.
+#[derive(Debug)]
+pub struct EventScan<'a, const N: usize, const S: usize> {
+    watcher: &'a mut InputWatcher,
+    throttle: &'a mut InterruptThrottle,
+    fanout: &'a mut EventFanout<N, S>,
+}
+
+impl<'a, const N: usize, const S: usize> EventScan<'a, N, S> {
+    /// Creates a scan feeding the changes `watcher` is subscribed to into `fanout`, when
+    /// `throttle` lets a read through.
+    pub fn new(
+        watcher: &'a mut InputWatcher,
+        throttle: &'a mut InterruptThrottle,
+        fanout: &'a mut EventFanout<N, S>,
+    ) -> Self {
+        Self {
+            watcher,
+            throttle,
+            fanout,
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca6424",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> Tca6424<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: Debug,
+    D: DelayNs,
+{
+    /// Advances the pending work of `tasks`, in order, without issuing more than `budget` I2C
+    /// transactions. One call per superloop iteration integrates every subsystem:
+    ///
+    /// ```ignore
+    /// loop {
+    ///     if int.is_low()? {
+    ///         throttle.notify();
+    ///     }
+    ///     let scan = EventScan::new(&mut watcher, &mut throttle, &mut fanout);
+    ///     let mut tasks = (&mut scheduler, &mut queue, scan, &mut pwm);
+    ///     tca.service(TransactionBudget::new(4), now_ms(), &mut tasks)?;
+    ///     while let Some(event) = fanout.next(control) {
+    ///         // ...
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// A step is only made if its worst case fits in what is left of the budget; the work
+    /// that does not fit waits for the next call, while later tasks with smaller steps may
+    /// still run. Put the time-critical tasks first. Like a [`TransferBound`], the budget
+    /// holds with the transfer options at their defaults; retries, split transfers, separate
+    /// read transactions and fail-safe writes can exceed it.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of transactions issued, or the first `Error` an I2C bus operation
+    /// returned; the tasks after the failing one are not advanced, and the failed work stays
+    /// pending.
+    pub async fn service<T>(
+        &mut self,
+        budget: TransactionBudget,
+        now_ms: u32,
+        tasks: &mut T,
+    ) -> Result<u32, Error<I2C::Error>>
+    where
+        T: ServiceTask<I2C, D> + ?Sized,
+    {
+        let start = self.transaction_count();
+        let mut budget = budget;
+        tasks.service_within(self, &mut budget, now_ms).await?;
+        Ok(self.transaction_count().wrapping_sub(start))
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), keep_self),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> ServiceTask<I2C, D> for ()
+where
+    I2C: BusAccess,
+    I2C::Error: Debug,
+    D: DelayNs,
+{
+    async fn service_within(
+        &mut self,
+        _tca: &mut Tca6424<I2C, D>,
+        _budget: &mut TransactionBudget,
+        _now_ms: u32,
+    ) -> Result<(), Error<I2C::Error>> {
+        Ok(())
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), keep_self),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D, T> ServiceTask<I2C, D> for &mut T
+where
+    I2C: BusAccess,
+    I2C::Error: Debug,
+    D: DelayNs,
+    T: ServiceTask<I2C, D> + ?Sized,
+{
+    async fn service_within(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        budget: &mut TransactionBudget,
+        now_ms: u32,
+    ) -> Result<(), Error<I2C::Error>> {
+        (**self).service_within(tca, budget, now_ms).await
+    }
+}
+
+macro_rules! tuple_tasks {
+    ($($task:ident),+) => {
+        #[maybe_async_cfg::maybe(
+            sync(cfg(not(feature = "async")), keep_self),
+            async(feature = "async", keep_self)
+        )]
+        impl<I2C, D, $($task),+> ServiceTask<I2C, D> for ($($task,)+)
+        where
+            I2C: BusAccess,
+            I2C::Error: Debug,
+            D: DelayNs,
+            $($task: ServiceTask<I2C, D>,)+
+        {
+            #[allow(non_snake_case)]
+            async fn service_within(
+                &mut self,
+                tca: &mut Tca6424<I2C, D>,
+                budget: &mut TransactionBudget,
+                now_ms: u32,
+            ) -> Result<(), Error<I2C::Error>> {
+                let ($($task,)+) = self;
+                $($task.service_within(tca, budget, now_ms).await?;)+
+                Ok(())
+            }
+        }
+    };
+}
+
+tuple_tasks!(A);
+tuple_tasks!(A, B);
+tuple_tasks!(A, B, C);
+tuple_tasks!(A, B, C, E);
+tuple_tasks!(A, B, C, E, F);
+tuple_tasks!(A, B, C, E, F, G);
+tuple_tasks!(A, B, C, E, F, G, H);
+tuple_tasks!(A, B, C, E, F, G, H, J);
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), keep_self),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D, const N: usize, const S: usize> ServiceTask<I2C, D> for EventScan<'_, N, S>
+where
+    I2C: BusAccess,
+    I2C::Error: Debug,
+    D: DelayNs,
+{
+    /// Reads the inputs once if the throttle lets a read through (one transaction).
+    async fn service_within(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        budget: &mut TransactionBudget,
+        now_ms: u32,
+    ) -> Result<(), Error<I2C::Error>> {
+        let cost = u32::from(TransferBound::POLL_INPUT_EVENTS.max(tca.cache_mode()));
+        if self.throttle.wait_ms(now_ms) != Some(0) || !budget.allows(cost) {
+            return Ok(());
+        }
+        self.throttle.poll(now_ms);
+        let start = tca.transaction_count();
+        let result = tca.poll_input_events(self.watcher).await;
+        budget.charge(tca, start);
+        match result {
+            Ok(changes) => {
+                self.fanout.publish_changes(&changes);
+                Ok(())
+            }
+            Err(error) => {
+                self.throttle.notify();
+                Err(error)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "operations")]
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), keep_self),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D, const N: usize> ServiceTask<I2C, D> for OpQueue<N>
+where
+    I2C: BusAccess,
+    I2C::Error: Debug,
+    D: DelayNs,
+{
+    /// Services the queue (one transaction per step) until it is empty or the budget is.
+    async fn service_within(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        budget: &mut TransactionBudget,
+        _now_ms: u32,
+    ) -> Result<(), Error<I2C::Error>> {
+        while !self.is_empty() && budget.allows(1) {
+            let start = tca.transaction_count();
+            let result = self.service(tca).await;
+            budget.charge(tca, start);
+            result?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "helpers")]
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), keep_self),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> ServiceTask<I2C, D> for PinScheduler
+where
+    I2C: BusAccess,
+    I2C::Error: Debug,
+    D: DelayNs,
+{
+    /// Writes the due changes port by port, while a read-modify-write fits in the budget.
+    async fn service_within(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        budget: &mut TransactionBudget,
+        now_ms: u32,
+    ) -> Result<(), Error<I2C::Error>> {
+        let due = self.due(now_ms);
+        let cost = u32::from(TransferBound::WRITE_PINS_MASKED.max(tca.cache_mode()));
+        for port in Port::ALL {
+            if port.byte_of(due) == 0 || !budget.allows(cost) {
+                continue;
+            }
+            let start = tca.transaction_count();
+            let result = self.write_port(tca, port, due).await;
+            budget.charge(tca, start);
+            result?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "helpers")]
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), keep_self),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> ServiceTask<I2C, D> for SoftPwm
+where
+    I2C: BusAccess,
+    I2C::Error: Debug,
+    D: DelayNs,
+{
+    /// Ticks the engine if driving all its pins fits in the budget.
+    async fn service_within(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        budget: &mut TransactionBudget,
+        now_ms: u32,
+    ) -> Result<(), Error<I2C::Error>> {
+        if !budget.allows(tca.drive_bound(self.pins())) {
+            return Ok(());
+        }
+        let start = tca.transaction_count();
+        let result = self.tick(tca, now_ms).await;
+        budget.charge(tca, start);
+        result.map(drop)
+    }
+}
+
+#[cfg(feature = "helpers")]
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), keep_self),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> ServiceTask<I2C, D> for WaveformPlayer
+where
+    I2C: BusAccess,
+    I2C::Error: Debug,
+    D: DelayNs,
+{
+    /// Ticks the player if driving every pin of its table fits in the budget.
+    async fn service_within(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        budget: &mut TransactionBudget,
+        now_ms: u32,
+    ) -> Result<(), Error<I2C::Error>> {
+        if !budget.allows(tca.drive_bound(self.pins())) {
+            return Ok(());
+        }
+        let start = tca.transaction_count();
+        let result = self.tick(tca, now_ms).await;
+        budget.charge(tca, start);
+        result.map(drop)
+    }
+}
+
+#[cfg(feature = "helpers")]
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), keep_self),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D, const N: usize> ServiceTask<I2C, D> for PinMirror<N>
+where
+    I2C: BusAccess,
+    I2C::Error: Debug,
+    D: DelayNs,
+{
+    /// Drives the delayed changes that are due, if driving every output fits in the budget.
+    async fn service_within(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        budget: &mut TransactionBudget,
+        now_ms: u32,
+    ) -> Result<(), Error<I2C::Error>> {
+        if self.wait_ms(now_ms) != Some(0) || !budget.allows(tca.drive_bound(self.outputs())) {
+            return Ok(());
+        }
+        let start = tca.transaction_count();
+        let result = self.tick(tca, now_ms).await;
+        budget.charge(tca, start);
+        result.map(drop)
+    }
+}
+
+#[cfg(feature = "portable-atomic")]
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), keep_self),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> ServiceTask<I2C, D> for &AtomicOutputs
+where
+    I2C: BusAccess,
+    I2C::Error: Debug,
+    D: DelayNs,
+{
+    /// Flushes the image if it is dirty (one transaction).
+    async fn service_within(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+        budget: &mut TransactionBudget,
+        _now_ms: u32,
+    ) -> Result<(), Error<I2C::Error>> {
+        if !self.is_dirty() || !budget.allows(1) {
+            return Ok(());
+        }
+        let start = tca.transaction_count();
+        let result = self.flush(tca).await;
+        budget.charge(tca, start);
+        result.map(drop)
+    }
+}
@@ -0,0 +1,115 @@
+//! Sharing one driver between tasks behind an `embassy-sync` mutex.
+
+use embassy_sync::blocking_mutex::raw::RawMutex;
+
+#[cfg(not(feature = "async"))]
+use crate::errors::Error;
+use crate::{NoDelay, Tca6424};
+
+/// A driver shared between tasks, generic over the `embassy-sync` [`RawMutex`].
+///
+/// The mutex type picks the synchronisation cost: `NoopRawMutex` for tasks of one executor,
+/// `ThreadModeRawMutex` for thread-mode code on a single core, and `CriticalSectionRawMutex`
+/// when the driver is also used from interrupts or other cores.
+///
+/// With the `async` feature the driver is locked with [`SharedTca6424::lock`], which waits
+/// for the current holder; in sync mode [`SharedTca6424::lock`] runs a closure inside the
+/// critical section of the mutex.
+///
+/// ```ignore
+/// use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+///
+/// static TCA: StaticCell<SharedTca6424<CriticalSectionRawMutex, I2c>> = StaticCell::new();
+/// let tca = TCA.init(SharedTca6424::new(Tca6424::new(i2c, DEFAULT_ADDRESS)?));
+///
+/// tca.lock().await.set_pin_output(Pin::P00, true).await?;
+/// ```
+///
+/// Futures that each lock the driver can be awaited together with `join`: each one waits
+/// for the lock, so a sequence of operations under one guard is never interleaved with
+/// another's. Keep guards short-lived, so the other futures, and other devices on the same
+/// bus, get their turn while one of them waits:
+///
+/// ```ignore
+/// let (leds, buttons) = embassy_futures::join::join(
+///     async { tca.lock().await.set_port_output(Port::Port0, 0xFF).await },
+///     async { tca.lock().await.get_port_input_state(Port::Port1).await },
+/// )
+/// .await;
+/// ```
+pub struct SharedTca6424<M: RawMutex, I2C, D = NoDelay> {
+    #[cfg(feature = "async")]
+    inner: embassy_sync::mutex::Mutex<M, Tca6424<I2C, D>>,
+    #[cfg(not(feature = "async"))]
+    inner: embassy_sync::blocking_mutex::Mutex<M, core::cell::RefCell<Tca6424<I2C, D>>>,
+}
+
+impl<M: RawMutex, I2C, D> SharedTca6424<M, I2C, D> {
+    /// Wraps a driver for sharing.
+    pub const fn new(tca: Tca6424<I2C, D>) -> Self {
+        Self {
+            #[cfg(feature = "async")]
+            inner: embassy_sync::mutex::Mutex::new(tca),
+            #[cfg(not(feature = "async"))]
+            inner: embassy_sync::blocking_mutex::Mutex::new(core::cell::RefCell::new(tca)),
+        }
+    }
+
+    /// Returns the driver without locking; `&mut self` proves no other user holds it.
+    pub fn get_mut(&mut self) -> &mut Tca6424<I2C, D> {
+        #[cfg(feature = "async")]
+        {
+            self.inner.get_mut()
+        }
+        #[cfg(not(feature = "async"))]
+        {
+            self.inner.get_mut().get_mut()
+        }
+    }
+
+    /// Unwraps the driver.
+    pub fn into_inner(self) -> Tca6424<I2C, D> {
+        #[cfg(feature = "async")]
+        {
+            self.inner.into_inner()
+        }
+        #[cfg(not(feature = "async"))]
+        {
+            self.inner.into_inner().into_inner()
+        }
+    }
+
+    /// Locks the driver, waiting until the current holder releases it.
+    #[cfg(feature = "async")]
+    pub async fn lock(&self) -> embassy_sync::mutex::MutexGuard<'_, M, Tca6424<I2C, D>> {
+        self.inner.lock().await
+    }
+
+    /// Locks the driver if no one else holds it.
+    #[cfg(feature = "async")]
+    pub fn try_lock(
+        &self,
+    ) -> Result<
+        embassy_sync::mutex::MutexGuard<'_, M, Tca6424<I2C, D>>,
+        embassy_sync::mutex::TryLockError,
+    > {
+        self.inner.try_lock()
+    }
+
+    /// Runs `f` with exclusive access to the driver.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `f`, or `Error::Busy` without running `f` if called again from
+    /// inside `f`.
+    #[cfg(not(feature = "async"))]
+    pub fn lock<R, E: core::fmt::Debug>(
+        &self,
+        f: impl FnOnce(&mut Tca6424<I2C, D>) -> Result<R, Error<E>>,
+    ) -> Result<R, Error<E>> {
+        self.inner.lock(|tca| {
+            let mut tca = tca.try_borrow_mut().map_err(|_| Error::Busy)?;
+            f(&mut tca)
+        })
+    }
+}
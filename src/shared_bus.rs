@@ -0,0 +1,106 @@
+//! Adapter for `embedded-hal` 0.2 buses such as `shared-bus` proxies.
+//!
+//! `shared-bus` hands out proxies implementing the `embedded-hal` 0.2 blocking I2C traits,
+//! while this driver is written against `embedded-hal` 1.0. Wrapping a proxy in [`Eh02I2c`]
+//! bridges the two:
+//!
+//! ```ignore
+//! // std
+//! let bus: &'static _ = shared_bus::new_std!(I2cdev = i2c).unwrap();
+//! // cortex-m
+//! let bus: &'static _ = shared_bus::new_cortexm!(I2c1 = i2c).unwrap();
+//!
+//! let mut tca = Tca6424::new(Eh02I2c::new(bus.acquire_i2c()), DEFAULT_ADDRESS)?;
+//! let mut sensor = OtherSensor::new(bus.acquire_i2c());
+//! ```
+//!
+//! The `new_std!`/`new_cortexm!` managers are `'static`, so the driver owns its proxy and no
+//! lifetime has to be threaded through the application.
+
+use core::fmt::Debug;
+
+use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, Operation, SevenBitAddress};
+use embedded_hal_02::blocking::i2c as eh02;
+
+/// Longest run of consecutive write operations that [`Eh02I2c`] can merge into one transfer.
+pub const MAX_WRITE_LEN: usize = 32;
+
+/// Error of an [`Eh02I2c`] transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Eh02Error<E> {
+    /// The wrapped bus reported an error.
+    Bus(E),
+    /// Consecutive write operations exceeded [`MAX_WRITE_LEN`] bytes.
+    WriteTooLong,
+}
+
+impl<E: Debug> embedded_hal::i2c::Error for Eh02Error<E> {
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Other
+    }
+}
+
+/// Implements the `embedded-hal` 1.0 `I2c` trait on top of an `embedded-hal` 0.2 bus.
+///
+/// Consecutive write operations of a transaction are merged into one write, and a read that
+/// follows writes is issued as a write-read with a repeated start. `embedded-hal` 0.2 has no
+/// way to continue a read, so consecutive reads are issued as separate transfers.
+#[derive(Debug)]
+pub struct Eh02I2c<T> {
+    bus: T,
+}
+
+impl<T> Eh02I2c<T> {
+    /// Wraps an `embedded-hal` 0.2 bus, for example a `shared-bus` proxy.
+    pub const fn new(bus: T) -> Self {
+        Self { bus }
+    }
+
+    /// Returns the wrapped bus.
+    pub fn into_inner(self) -> T {
+        self.bus
+    }
+}
+
+impl<T, E> ErrorType for Eh02I2c<T>
+where
+    T: eh02::Write<Error = E> + eh02::WriteRead<Error = E> + eh02::Read<Error = E>,
+    E: Debug,
+{
+    type Error = Eh02Error<E>;
+}
+
+impl<T, E> I2c for Eh02I2c<T>
+where
+    T: eh02::Write<Error = E> + eh02::WriteRead<Error = E> + eh02::Read<Error = E>,
+    E: Debug,
+{
+    fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let mut pending: heapless::Vec<u8, MAX_WRITE_LEN> = heapless::Vec::new();
+        for operation in operations {
+            match operation {
+                Operation::Write(bytes) => pending
+                    .extend_from_slice(bytes)
+                    .map_err(|_| Eh02Error::WriteTooLong)?,
+                Operation::Read(buffer) if pending.is_empty() => {
+                    self.bus.read(address, buffer).map_err(Eh02Error::Bus)?
+                }
+                Operation::Read(buffer) => {
+                    self.bus
+                        .write_read(address, &pending, buffer)
+                        .map_err(Eh02Error::Bus)?;
+                    pending.clear();
+                }
+            }
+        }
+        if !pending.is_empty() {
+            self.bus.write(address, &pending).map_err(Eh02Error::Bus)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,286 @@
+//! Low-power sleep preparation and wake restoration.
+
+use crate::errors::Error;
+use crate::plan;
+use crate::registers::Register;
+use crate::{BusAccess, DelayNs, Pin, Port, RegisterKind, Tca6424};
+
+/// What a pin does while the system sleeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SleepAction {
+    /// Keep the current direction and level.
+    #[default]
+    Retain,
+    /// Become a high-impedance input.
+    Float,
+    /// Become an output driving low.
+    DriveLow,
+    /// Become an output driving high.
+    DriveHigh,
+}
+
+/// The sleep state of every pin, applied with [`Tca6424::prepare_for_sleep`].
+///
+/// Pins default to [`SleepAction::Retain`]. Choose the state that leaks least for the
+/// circuit on each pin: outputs into pulled-up nets drive high, inputs without a pull become
+/// outputs so they do not float, and so on. The profile is built at compile time and stored
+/// as three 24-bit masks (bit `n` = pin index `n`), from which the sleep and wake methods
+/// derive the register writes.
+///
+/// ```
+/// use tca6424::{Pin, SleepAction, SleepProfile};
+///
+/// const SLEEP: SleepProfile = SleepProfile::new()
+///     .drive_low(0x00_00FF)
+///     .float(0xFF_0000)
+///     .with_pin(Pin::P00, SleepAction::DriveHigh);
+/// assert_eq!(SLEEP.action(Pin::P00), SleepAction::DriveHigh);
+/// assert_eq!(SLEEP.action(Pin::P17), SleepAction::Retain);
+/// assert_eq!(SLEEP.pins(SleepAction::DriveLow), 0x00_00FE);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SleepProfile {
+    float: u32,
+    low: u32,
+    high: u32,
+}
+
+/// Mask of the 24 pin bits.
+const PIN_BITS: u32 = 0x00FF_FFFF;
+
+impl SleepProfile {
+    /// Creates a profile retaining every pin.
+    pub const fn new() -> Self {
+        Self {
+            float: 0,
+            low: 0,
+            high: 0,
+        }
+    }
+
+    /// Returns the profile with `action` chosen for every pin whose bit is `1` in `mask`.
+    pub const fn with_pins(mut self, mask: u32, action: SleepAction) -> Self {
+        let mask = mask & PIN_BITS;
+        self.float &= !mask;
+        self.low &= !mask;
+        self.high &= !mask;
+        match action {
+            SleepAction::Retain => {}
+            SleepAction::Float => self.float |= mask,
+            SleepAction::DriveLow => self.low |= mask,
+            SleepAction::DriveHigh => self.high |= mask,
+        }
+        self
+    }
+
+    /// Returns the profile with `action` chosen for `pin`.
+    pub const fn with_pin(self, pin: Pin, action: SleepAction) -> Self {
+        self.with_pins(1 << pin as u8, action)
+    }
+
+    /// Returns the profile retaining the pins of `mask`.
+    pub const fn retain(self, mask: u32) -> Self {
+        self.with_pins(mask, SleepAction::Retain)
+    }
+
+    /// Returns the profile floating the pins of `mask`.
+    pub const fn float(self, mask: u32) -> Self {
+        self.with_pins(mask, SleepAction::Float)
+    }
+
+    /// Returns the profile driving the pins of `mask` low.
+    pub const fn drive_low(self, mask: u32) -> Self {
+        self.with_pins(mask, SleepAction::DriveLow)
+    }
+
+    /// Returns the profile driving the pins of `mask` high.
+    pub const fn drive_high(self, mask: u32) -> Self {
+        self.with_pins(mask, SleepAction::DriveHigh)
+    }
+
+    /// Chooses `action` for `pin`.
+    pub fn set_pin(&mut self, pin: Pin, action: SleepAction) {
+        *self = self.with_pin(pin, action);
+    }
+
+    /// Returns the action chosen for `pin`.
+    pub const fn action(&self, pin: Pin) -> SleepAction {
+        let bit = 1 << pin as u8;
+        if self.float & bit != 0 {
+            SleepAction::Float
+        } else if self.low & bit != 0 {
+            SleepAction::DriveLow
+        } else if self.high & bit != 0 {
+            SleepAction::DriveHigh
+        } else {
+            SleepAction::Retain
+        }
+    }
+
+    /// Returns the pins using `action`, as a 24-bit mask.
+    pub const fn pins(&self, action: SleepAction) -> u32 {
+        match action {
+            SleepAction::Retain => !(self.float | self.low | self.high) & PIN_BITS,
+            SleepAction::Float => self.float,
+            SleepAction::DriveLow => self.low,
+            SleepAction::DriveHigh => self.high,
+        }
+    }
+
+    /// Returns the Output and Configuration values of `port` in sleep, given the current ones.
+    fn apply(&self, port: Port, output: u8, configuration: u8) -> (u8, u8) {
+        let byte = |mask: u32| (mask >> (port as u8 * 8)) as u8;
+        let driven = byte(self.low | self.high);
+        (
+            (output & !driven) | byte(self.high),
+            (configuration & !driven) | byte(self.float),
+        )
+    }
+}
+
+impl Default for SleepProfile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Output and Configuration values of the ports changed by [`Tca6424::prepare_for_sleep`].
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct SleepState {
+    outputs: [Option<u8>; 3],
+    configuration: [Option<u8>; 3],
+}
+
+impl<I2C, D> Tca6424<I2C, D> {
+    /// Returns `true` between [`Tca6424::prepare_for_sleep`] and
+    /// [`Tca6424::restore_after_wake`].
+    pub fn is_prepared_for_sleep(&self) -> bool {
+        self.sleep.is_some()
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca6424",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> Tca6424<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Puts every pin into the state chosen by `profile`, remembering the configuration it
+    /// replaces.
+    ///
+    /// Only registers whose value changes are written, adjacent ports of a family in one
+    /// auto-increment transaction. Ports where every pin is [`SleepAction::Retain`] are not
+    /// touched. The Output registers are written before the Configuration registers, so pins
+    /// becoming outputs drive their sleep level from the first moment. Current values come
+    /// from the register cache, or are read from the device when unknown. Calling it again
+    /// before waking keeps the configuration saved by the first call.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails. Registers
+    /// changed before the failure stay in their sleep state and are restored by
+    /// [`Tca6424::restore_after_wake`].
+    pub async fn prepare_for_sleep(
+        &mut self,
+        profile: &SleepProfile,
+    ) -> Result<(), Error<I2C::Error>> {
+        let mut saved = self.sleep.unwrap_or_default();
+        let affected = !profile.pins(SleepAction::Retain) & PIN_BITS;
+        let mut current = SleepState::default();
+        let mut target = SleepState::default();
+        for port in Port::ALL {
+            if (affected >> (port as u8 * 8)) as u8 == 0 {
+                continue;
+            }
+            let output = self.current_value(RegisterKind::Output, port).await?;
+            let configuration = self
+                .current_value(RegisterKind::Configuration, port)
+                .await?;
+            port.entry_mut(&mut saved.outputs).get_or_insert(output);
+            port.entry_mut(&mut saved.configuration)
+                .get_or_insert(configuration);
+            *port.entry_mut(&mut current.outputs) = Some(output);
+            *port.entry_mut(&mut current.configuration) = Some(configuration);
+            let (sleep_output, sleep_configuration) = profile.apply(port, output, configuration);
+            *port.entry_mut(&mut target.outputs) = Some(sleep_output);
+            *port.entry_mut(&mut target.configuration) = Some(sleep_configuration);
+        }
+        self.sleep = Some(saved);
+
+        self.write_changed(RegisterKind::Output, current.outputs, target.outputs)
+            .await?;
+        self.write_changed(
+            RegisterKind::Configuration,
+            current.configuration,
+            target.configuration,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Re-applies the configuration saved by [`Tca6424::prepare_for_sleep`].
+    ///
+    /// The saved Output registers are written before the Configuration registers, so pins
+    /// returning to output mode drive their previous level immediately.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(true)` if a saved configuration was restored, `Ok(false)` if the driver was
+    /// not prepared for sleep, or an `Error` if an I2C bus operation fails (the saved
+    /// configuration is kept, so the call can be retried).
+    pub async fn restore_after_wake(&mut self) -> Result<bool, Error<I2C::Error>> {
+        let Some(saved) = self.sleep else {
+            return Ok(false);
+        };
+        // The device may have lost its state while asleep: write every saved register.
+        self.write_changed(RegisterKind::Output, [None; 3], saved.outputs)
+            .await?;
+        self.write_changed(RegisterKind::Configuration, [None; 3], saved.configuration)
+            .await?;
+        self.sleep = None;
+        Ok(true)
+    }
+
+    /// Writes the ports of a register family whose `target` value differs from `current`.
+    ///
+    /// Several changed ports are written with one auto-increment transaction when every port
+    /// between the first and the last has a target value; otherwise each is written alone.
+    /// Returns the number of transactions issued.
+    pub(crate) async fn write_changed(
+        &mut self,
+        kind: RegisterKind,
+        current: [Option<u8>; 3],
+        target: [Option<u8>; 3],
+    ) -> Result<u32, Error<I2C::Error>> {
+        let writes = plan::family_writes(kind, current, target);
+        for write in &writes {
+            match write.payload() {
+                [value] => self.write_register(write.register(), *value).await?,
+                payload => self.write_registers_ai(write.register(), payload).await?,
+            }
+        }
+        Ok(writes.len() as u32)
+    }
+
+    /// Returns the cached value of a register, reading the device when it is unknown.
+    async fn current_value(
+        &mut self,
+        kind: RegisterKind,
+        port: Port,
+    ) -> Result<u8, Error<I2C::Error>> {
+        match self.cache.get(kind, port) {
+            Some(value) => Ok(value),
+            None => self.read_register(Register::of(kind, port)).await,
+        }
+    }
+}
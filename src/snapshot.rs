@@ -0,0 +1,531 @@
+//! Register snapshots and snapshot diffs.
+
+use core::fmt;
+
+use bitflags::bitflags;
+
+use crate::errors::Error;
+use crate::recovery::RESTORE_ORDER;
+use crate::registers::Register;
+use crate::{Activity, BusAccess, DelayNs, Pin, PinLabels, PinName, Port, RegisterKind, Tca6424};
+
+/// A copy of every register of the TCA6424, indexed by register family and port.
+///
+/// Snapshots are plain data: they can be captured with [`crate::Tca6424::snapshot`],
+/// stored, and compared later with [`RegisterSnapshot::diff`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RegisterSnapshot {
+    /// Input Port registers (Port0, Port1, Port2).
+    pub inputs: [u8; 3],
+    /// Output Port registers (Port0, Port1, Port2).
+    pub outputs: [u8; 3],
+    /// Polarity Inversion registers (Port0, Port1, Port2).
+    pub polarity_inversion: [u8; 3],
+    /// Configuration registers (Port0, Port1, Port2).
+    pub configuration: [u8; 3],
+    /// Interrupt Mask registers (Port0, Port1, Port2).
+    pub interrupt_mask: [u8; 3],
+}
+
+impl RegisterSnapshot {
+    /// Returns the three port registers of the given family.
+    pub fn family(&self, kind: RegisterKind) -> &[u8; 3] {
+        match kind {
+            RegisterKind::Input => &self.inputs,
+            RegisterKind::Output => &self.outputs,
+            RegisterKind::PolarityInversion => &self.polarity_inversion,
+            RegisterKind::Configuration => &self.configuration,
+            RegisterKind::InterruptMask => &self.interrupt_mask,
+        }
+    }
+
+    /// Returns a mutable reference to the three port registers of the given family.
+    pub fn family_mut(&mut self, kind: RegisterKind) -> &mut [u8; 3] {
+        match kind {
+            RegisterKind::Input => &mut self.inputs,
+            RegisterKind::Output => &mut self.outputs,
+            RegisterKind::PolarityInversion => &mut self.polarity_inversion,
+            RegisterKind::Configuration => &mut self.configuration,
+            RegisterKind::InterruptMask => &mut self.interrupt_mask,
+        }
+    }
+
+    /// Returns the value of a single register.
+    pub fn get(&self, kind: RegisterKind, port: Port) -> u8 {
+        *port.entry(self.family(kind))
+    }
+
+    /// Returns `true` if every register except the inputs holds its power-on value: Output,
+    /// Configuration and Interrupt Mask `0xFF`, Polarity Inversion `0x00`.
+    ///
+    /// A device found in this state has most likely just powered up; anything else points
+    /// to a warm reboot of the controller while the expander kept its configuration.
+    pub fn is_power_on_default(&self) -> bool {
+        RegisterKind::ALL
+            .into_iter()
+            .filter(|&kind| kind != RegisterKind::Input)
+            .all(|kind| {
+                self.family(kind)
+                    .iter()
+                    .all(|&value| value == crate::plan::power_on_value(kind))
+            })
+    }
+
+    /// Compares this snapshot (the "before" state) with `other` (the "after" state)
+    /// and lists every register whose value differs.
+    ///
+    /// Registers are reported in register map order.
+    pub fn diff(&self, other: &RegisterSnapshot) -> SnapshotDiff {
+        let mut changes = heapless::Vec::new();
+        for kind in RegisterKind::ALL {
+            for port in Port::ALL {
+                let before = self.get(kind, port);
+                let after = other.get(kind, port);
+                if before != after {
+                    // At most 15 registers exist, so this never overflows.
+                    let _ = changes.push(RegisterChange {
+                        kind,
+                        port,
+                        before,
+                        after,
+                    });
+                }
+            }
+        }
+        SnapshotDiff { changes }
+    }
+}
+
+bitflags! {
+    /// Register families selected for [`Tca6424::snapshot_families`] and
+    /// [`Tca6424::restore`].
+    ///
+    /// Leave out the families another agent owns, such as Polarity Inversion set up by a
+    /// bootloader or a co-processor, so a partial restore does not clobber them.
+    ///
+    /// ```
+    /// use tca6424::{RegisterFamilies, RegisterKind};
+    ///
+    /// let families = RegisterFamilies::all() - RegisterFamilies::POLARITY_INVERSION;
+    /// assert!(families.includes(RegisterKind::Output));
+    /// assert!(!families.includes(RegisterKind::PolarityInversion));
+    /// ```
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct RegisterFamilies: u8 {
+        /// Input Port registers (read-only; never written by a restore).
+        const INPUT = 0b0_0001;
+        /// Output Port registers.
+        const OUTPUT = 0b0_0010;
+        /// Polarity Inversion registers.
+        const POLARITY_INVERSION = 0b0_0100;
+        /// Configuration registers.
+        const CONFIGURATION = 0b0_1000;
+        /// Interrupt Mask registers.
+        const INTERRUPT_MASK = 0b1_0000;
+    }
+}
+
+impl RegisterFamilies {
+    /// Returns the set holding only `kind`.
+    pub const fn of(kind: RegisterKind) -> Self {
+        match kind {
+            RegisterKind::Input => Self::INPUT,
+            RegisterKind::Output => Self::OUTPUT,
+            RegisterKind::PolarityInversion => Self::POLARITY_INVERSION,
+            RegisterKind::Configuration => Self::CONFIGURATION,
+            RegisterKind::InterruptMask => Self::INTERRUPT_MASK,
+        }
+    }
+
+    /// Returns `true` if the family `kind` is selected.
+    pub const fn includes(&self, kind: RegisterKind) -> bool {
+        self.contains(Self::of(kind))
+    }
+}
+
+/// A single register whose value differs between two snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RegisterChange {
+    /// Register family of the changed register.
+    pub kind: RegisterKind,
+    /// Port of the changed register.
+    pub port: Port,
+    /// Value in the first snapshot.
+    pub before: u8,
+    /// Value in the second snapshot.
+    pub after: u8,
+}
+
+impl RegisterChange {
+    /// Returns a mask of the bits that differ between `before` and `after`.
+    pub fn changed_bits(&self) -> u8 {
+        self.before ^ self.after
+    }
+
+    /// Returns a mask of the bits that went from `0` to `1`.
+    pub fn set_bits(&self) -> u8 {
+        !self.before & self.after
+    }
+
+    /// Returns a mask of the bits that went from `1` to `0`.
+    pub fn cleared_bits(&self) -> u8 {
+        self.before & !self.after
+    }
+
+    /// Returns an iterator over the pins whose bit changed in this register.
+    pub fn changed_pins(&self) -> impl Iterator<Item = Pin> + '_ {
+        crate::pins_from_mask(u32::from(self.changed_bits()) << (self.port as u8 * 8))
+    }
+}
+
+impl RegisterChange {
+    /// Returns a `Display` wrapper that names pins using `labels`.
+    pub fn with_labels<'a>(&'a self, labels: &'a PinLabels) -> Labelled<'a, RegisterChange> {
+        Labelled {
+            value: self,
+            labels: Some(labels),
+        }
+    }
+
+    fn fmt_with(&self, labels: Option<&PinLabels>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} Port {}: {:08b} -> {:08b} [",
+            self.kind.name(),
+            self.port as u8,
+            self.before,
+            self.after
+        )?;
+        for (i, pin) in self.changed_pins().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            let bit = pin.bit();
+            let name = labels.map_or(PinName::unlabelled(pin), |labels| labels.name(pin));
+            write!(
+                f,
+                "{}: {}->{}",
+                name,
+                (self.before >> bit) & 1,
+                (self.after >> bit) & 1
+            )?;
+        }
+        f.write_str("]")
+    }
+}
+
+impl fmt::Display for RegisterChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with(None, f)
+    }
+}
+
+/// The set of registers that differ between two [`RegisterSnapshot`]s.
+///
+/// The `Display` implementation prints one changed register per line, listing the
+/// individual pins whose bits flipped.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SnapshotDiff {
+    changes: heapless::Vec<RegisterChange, 15>,
+}
+
+impl SnapshotDiff {
+    /// Returns `true` if the two snapshots were identical.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns the number of changed registers.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Returns the changed registers in register map order.
+    pub fn changes(&self) -> &[RegisterChange] {
+        &self.changes
+    }
+
+    /// Returns the change recorded for a specific register, if it changed.
+    pub fn get(&self, kind: RegisterKind, port: Port) -> Option<&RegisterChange> {
+        self.changes
+            .iter()
+            .find(|change| change.kind == kind && change.port == port)
+    }
+
+    /// Returns an iterator over the changed registers.
+    pub fn iter(&self) -> core::slice::Iter<'_, RegisterChange> {
+        self.changes.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a SnapshotDiff {
+    type Item = &'a RegisterChange;
+    type IntoIter = core::slice::Iter<'a, RegisterChange>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.changes.iter()
+    }
+}
+
+impl SnapshotDiff {
+    /// Returns a `Display` wrapper that names pins using `labels`.
+    pub fn with_labels<'a>(&'a self, labels: &'a PinLabels) -> Labelled<'a, SnapshotDiff> {
+        Labelled {
+            value: self,
+            labels: Some(labels),
+        }
+    }
+
+    fn fmt_with(&self, labels: Option<&PinLabels>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.changes.is_empty() {
+            return f.write_str("no register changes");
+        }
+        for (i, change) in self.changes.iter().enumerate() {
+            if i > 0 {
+                f.write_str("\n")?;
+            }
+            change.fmt_with(labels, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SnapshotDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with(None, f)
+    }
+}
+
+/// The functional state of the expander: pin levels, output latches and directions as
+/// 24-bit words (bit `n` = pin index `n`).
+///
+/// Captured with [`crate::Tca6424::device_state`] or derived from a [`RegisterSnapshot`], and
+/// compared with [`DeviceState::diff`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DeviceState {
+    /// Pin levels from the Input Port registers.
+    pub inputs: u32,
+    /// Output latches from the Output Port registers.
+    pub outputs: u32,
+    /// Directions from the Configuration registers (`1` = input, `0` = output).
+    pub directions: u32,
+}
+
+impl DeviceState {
+    /// Compares this state (the "before" state) with `other` (the "after" state).
+    pub fn diff(&self, other: &DeviceState) -> DeviceStateDiff {
+        DeviceStateDiff {
+            before: *self,
+            after: *other,
+        }
+    }
+}
+
+impl From<&RegisterSnapshot> for DeviceState {
+    fn from(snapshot: &RegisterSnapshot) -> Self {
+        let word = |[p0, p1, p2]: [u8; 3]| u32::from_le_bytes([p0, p1, p2, 0]);
+        Self {
+            inputs: word(snapshot.inputs),
+            outputs: word(snapshot.outputs),
+            directions: word(snapshot.configuration),
+        }
+    }
+}
+
+/// The differences between two [`DeviceState`]s.
+///
+/// The `Display` implementation prints one line per changed field, listing the pins whose
+/// bits flipped (`outputs: P00: 0->1, P27: 1->0`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DeviceStateDiff {
+    /// The first state.
+    pub before: DeviceState,
+    /// The second state.
+    pub after: DeviceState,
+}
+
+impl DeviceStateDiff {
+    /// Returns the pins whose input level changed.
+    pub fn inputs_changed(&self) -> u32 {
+        self.before.inputs ^ self.after.inputs
+    }
+
+    /// Returns the pins whose output latch changed.
+    pub fn outputs_changed(&self) -> u32 {
+        self.before.outputs ^ self.after.outputs
+    }
+
+    /// Returns the pins whose direction changed.
+    pub fn directions_changed(&self) -> u32 {
+        self.before.directions ^ self.after.directions
+    }
+
+    /// Returns the pins with any change.
+    pub fn changed_mask(&self) -> u32 {
+        self.inputs_changed() | self.outputs_changed() | self.directions_changed()
+    }
+
+    /// Returns `true` if the two states were identical.
+    pub fn is_empty(&self) -> bool {
+        self.changed_mask() == 0
+    }
+
+    /// Iterates over the pins with any change, lowest pin first.
+    pub fn changed_pins(&self) -> crate::PinIter {
+        crate::pins_from_mask(self.changed_mask())
+    }
+}
+
+impl fmt::Display for DeviceStateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("no state changes");
+        }
+        let fields = [
+            ("inputs", self.before.inputs, self.after.inputs),
+            ("outputs", self.before.outputs, self.after.outputs),
+            ("directions", self.before.directions, self.after.directions),
+        ];
+        let mut first_line = true;
+        for (name, before, after) in fields {
+            if before == after {
+                continue;
+            }
+            if !first_line {
+                f.write_str("\n")?;
+            }
+            first_line = false;
+            write!(f, "{name}:")?;
+            for (i, pin) in crate::pins_from_mask(before ^ after).enumerate() {
+                let bit = pin as u8;
+                let separator = if i > 0 { "," } else { "" };
+                write!(
+                    f,
+                    "{separator} {pin}: {}->{}",
+                    (before >> bit) & 1,
+                    (after >> bit) & 1
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A [`RegisterChange`] or [`SnapshotDiff`] whose `Display` output uses pin labels.
+///
+/// Created with `with_labels()`.
+#[derive(Debug, Clone, Copy)]
+pub struct Labelled<'a, T> {
+    value: &'a T,
+    labels: Option<&'a PinLabels>,
+}
+
+impl fmt::Display for Labelled<'_, RegisterChange> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt_with(self.labels, f)
+    }
+}
+
+impl fmt::Display for Labelled<'_, SnapshotDiff> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt_with(self.labels, f)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for SnapshotDiff {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "SnapshotDiff {{ changes: {=[?]} }}",
+            self.changes.as_slice()
+        );
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca6424",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> Tca6424<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Reads the register families in `families` into a [`RegisterSnapshot`].
+    ///
+    /// Each selected family is read with one auto-increment transaction; the families left
+    /// out are not read and stay `0` in the snapshot. [`Tca6424::snapshot`] reads them all.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(RegisterSnapshot)` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn snapshot_families(
+        &mut self,
+        families: RegisterFamilies,
+    ) -> Result<RegisterSnapshot, Error<I2C::Error>> {
+        let scope = self.enter_activity(Activity::Snapshot);
+        let result = self.read_families(families).await;
+        self.leave_activity(scope, result)
+    }
+
+    async fn read_families(
+        &mut self,
+        families: RegisterFamilies,
+    ) -> Result<RegisterSnapshot, Error<I2C::Error>> {
+        let mut snapshot = RegisterSnapshot::default();
+        for kind in RegisterKind::ALL {
+            if families.includes(kind) {
+                let start_register = Register::of(kind, Port::Port0);
+                self.read_registers_ai(start_register, snapshot.family_mut(kind))
+                    .await?;
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// Writes the register families in `families` back from `snapshot`.
+    ///
+    /// Each selected family is written with one auto-increment transaction and recorded in
+    /// the register cache; the others are left untouched on the device. The Input family
+    /// is read-only and skipped. As with [`Tca6424::restore_from_cache`], the outputs are
+    /// written first and the Configuration registers last, so pins turned into outputs
+    /// drive their restored level at once.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails; the families
+    /// written before the failure keep their restored values.
+    pub async fn restore(
+        &mut self,
+        snapshot: &RegisterSnapshot,
+        families: RegisterFamilies,
+    ) -> Result<(), Error<I2C::Error>> {
+        let scope = self.enter_activity(Activity::Restore);
+        let result = self.write_families(snapshot, families).await;
+        self.leave_activity(scope, result)
+    }
+
+    async fn write_families(
+        &mut self,
+        snapshot: &RegisterSnapshot,
+        families: RegisterFamilies,
+    ) -> Result<(), Error<I2C::Error>> {
+        for kind in RESTORE_ORDER {
+            if families.includes(kind) {
+                self.write_registers_ai(Register::of(kind, Port::Port0), snapshot.family(kind))
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+//! Staggered switching of output groups to limit inrush current.
+
+use crate::errors::Error;
+use crate::registers::Register;
+use crate::{BusAccess, DelayNs, Pin, PinState, RegisterKind, Tca6424};
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca6424",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> Tca6424<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Drives `pins` high one at a time, in order, waiting `per_step_delay_us` microseconds
+    /// with `delay` between steps.
+    ///
+    /// Switching load banks or LED strings on together can pull the supply down; staggering
+    /// spreads the inrush current. The Output registers are read once, then every step is a
+    /// single register write. Pins that are already high are skipped without a delay. The
+    /// delay is a parameter rather than the driver's delay source, so a driver without one
+    /// (the default [`NoDelay`](crate::NoDelay)) cannot silently skip the waits. The pins
+    /// must already be configured as outputs.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails. Pins switched
+    /// before the failure stay on.
+    pub async fn enable_group_staggered<DL: DelayNs>(
+        &mut self,
+        pins: &[Pin],
+        per_step_delay_us: u32,
+        delay: &mut DL,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.switch_staggered(
+            pins.iter().copied(),
+            PinState::High,
+            per_step_delay_us,
+            delay,
+        )
+        .await
+    }
+
+    /// Drives `pins` low one at a time, in reverse order, waiting `per_step_delay_us`
+    /// microseconds with `delay` between steps.
+    ///
+    /// The counterpart of [`Tca6424::enable_group_staggered`]: passing the same slice turns
+    /// the group off last-on, first-off.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn disable_group_staggered<DL: DelayNs>(
+        &mut self,
+        pins: &[Pin],
+        per_step_delay_us: u32,
+        delay: &mut DL,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.switch_staggered(
+            pins.iter().rev().copied(),
+            PinState::Low,
+            per_step_delay_us,
+            delay,
+        )
+        .await
+    }
+
+    async fn switch_staggered<DL: DelayNs>(
+        &mut self,
+        pins: impl Iterator<Item = Pin>,
+        state: PinState,
+        per_step_delay_us: u32,
+        delay: &mut DL,
+    ) -> Result<(), Error<I2C::Error>> {
+        let mut outputs = [0u8; 3];
+        self.read_registers_ai(Register::OutputPort0, &mut outputs)
+            .await?;
+
+        let mut first = true;
+        for pin in pins {
+            let value = pin.port().entry_mut(&mut outputs);
+            let switched = match state {
+                PinState::High => *value | 1 << pin.bit(),
+                PinState::Low => *value & !(1 << pin.bit()),
+            };
+            if switched == *value {
+                continue;
+            }
+            *value = switched;
+
+            if !first {
+                delay.delay_us(per_step_delay_us).await;
+            }
+            first = false;
+            self.write_register(Register::of(RegisterKind::Output, pin.port()), switched)
+                .await?;
+        }
+        Ok(())
+    }
+}
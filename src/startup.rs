@@ -0,0 +1,88 @@
+//! Capture of the register state a device was found in before the firmware configured it.
+//!
+//! After a watchdog reset or a firmware update the microcontroller restarts while the
+//! expander keeps power, so its registers still hold what the previous firmware last wrote.
+//! That state is lost the moment the new firmware reconfigures the device. Capturing it
+//! first keeps it available for a crash report or a debug console.
+
+use crate::errors::Error;
+use crate::{BusAccess, DelayNs, RegisterSnapshot, Tca6424, is_reserved_address};
+
+impl<I2C, D> Tca6424<I2C, D> {
+    /// Returns the register state captured before initialisation, or `None` if none was
+    /// captured (see [`Tca6424::capture_startup_state`]).
+    ///
+    /// Compare it with [`RegisterSnapshot::is_power_on_default`] to tell a warm reboot from a
+    /// cold start, or diff it against a later snapshot to see what the firmware changed.
+    pub fn startup_state(&self) -> Option<&RegisterSnapshot> {
+        self.startup.as_ref()
+    }
+
+    /// Removes and returns the captured startup state, for example once it has been logged.
+    pub fn take_startup_state(&mut self) -> Option<RegisterSnapshot> {
+        self.startup.take()
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca6424",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C> Tca6424<I2C>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+{
+    /// Creates a driver like [`Tca6424::new_checked`] and captures the register state the
+    /// device is found in (see [`Tca6424::capture_startup_state`]) before anything is
+    /// written.
+    ///
+    /// This function is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Self)` on success, `Error::ReservedAddress(address)` if the address is
+    /// reserved, or an `Error` if an I2C bus operation fails.
+    pub async fn new_checked_with_capture(
+        i2c: I2C,
+        address: u8,
+    ) -> Result<Self, Error<I2C::Error>> {
+        if is_reserved_address(address) {
+            return Err(Error::ReservedAddress(address));
+        }
+        let mut tca = Self::new(i2c, address)?;
+        tca.capture_startup_state().await?;
+        Ok(tca)
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca6424",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> Tca6424<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Reads every register into a [`RegisterSnapshot`] and keeps it as the startup state,
+    /// replacing any earlier capture.
+    ///
+    /// Call it before board initialisation (such as `apply_board_config`) to preserve the
+    /// state a warm-rebooted expander was in; [`Tca6424::startup_state`] returns it later.
+    /// The read costs five I2C transactions and does not touch the register cache. Reading
+    /// the Input registers clears a pending interrupt.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the captured snapshot, or an `Error` if an I2C bus operation fails; an earlier
+    /// capture is kept in that case.
+    pub async fn capture_startup_state(&mut self) -> Result<RegisterSnapshot, Error<I2C::Error>> {
+        let snapshot = self.snapshot().await?;
+        self.startup = Some(snapshot);
+        Ok(snapshot)
+    }
+}
@@ -0,0 +1,132 @@
+//! Per-pin activity counters for wear estimates and chatty-input detection.
+
+use crate::{Chip, Pin, Port, Tca64xx};
+
+/// Activity counters of one pin, returned by [`Tca64xx::pin_stats`].
+///
+/// Counters saturate at `u32::MAX` instead of wrapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PinStats {
+    /// Level changes written to the pin's Output Port bit.
+    pub output_toggles: u32,
+    /// Level changes seen on the pin's Input Port bit between two reads.
+    pub input_changes: u32,
+}
+
+/// Register families as encoded in bits 2-4 of the register address.
+const INPUT_FAMILY: u8 = 0;
+const OUTPUT_FAMILY: u8 = 1;
+
+/// Counters of every pin, fed with the successful transfers of the driver.
+#[derive(Debug, Default)]
+pub(crate) struct Stats {
+    pins: [PinStats; 24],
+    /// Last Output Port value written or read per port, the baseline for output toggles.
+    outputs: [Option<u8>; 3],
+    /// Last Input Port value read per port, the baseline for input changes.
+    inputs: [Option<u8>; 3],
+}
+
+impl Stats {
+    pub(crate) const fn new() -> Self {
+        Self {
+            pins: [PinStats {
+                output_toggles: 0,
+                input_changes: 0,
+            }; 24],
+            outputs: [None; 3],
+            inputs: [None; 3],
+        }
+    }
+
+    /// Counts the Output Port bits changed by a successful write.
+    pub(crate) fn record_write(&mut self, command_byte: u8, payload: &[u8]) {
+        for (port, value) in ports(command_byte, OUTPUT_FAMILY, payload) {
+            if let Some(previous) = port.entry_mut(&mut self.outputs).replace(value) {
+                Self::count(&mut self.pins, port, previous ^ value, |stats| {
+                    &mut stats.output_toggles
+                });
+            }
+        }
+    }
+
+    /// Counts the Input Port bits changed since the previous read, and takes Output Port
+    /// reads as the new baseline for output toggles.
+    pub(crate) fn record_read(&mut self, command_byte: u8, buffer: &[u8]) {
+        for (port, value) in ports(command_byte, INPUT_FAMILY, buffer) {
+            if let Some(previous) = port.entry_mut(&mut self.inputs).replace(value) {
+                Self::count(&mut self.pins, port, previous ^ value, |stats| {
+                    &mut stats.input_changes
+                });
+            }
+        }
+        for (port, value) in ports(command_byte, OUTPUT_FAMILY, buffer) {
+            *port.entry_mut(&mut self.outputs) = Some(value);
+        }
+    }
+
+    pub(crate) fn get(&self, pin: Pin) -> PinStats {
+        *pin.entry(&self.pins)
+    }
+
+    /// Clears the counters; the baselines are kept.
+    pub(crate) fn reset(&mut self) {
+        self.pins = [PinStats::default(); 24];
+    }
+
+    /// Forgets the baselines, for example when the device behind the driver changes.
+    pub(crate) fn forget(&mut self) {
+        self.outputs = [None; 3];
+        self.inputs = [None; 3];
+    }
+
+    fn count(
+        pins: &mut [PinStats; 24],
+        port: Port,
+        changed: u8,
+        counter: fn(&mut PinStats) -> &mut u32,
+    ) {
+        for bit in 0..8 {
+            if changed & (1 << bit) != 0
+                && let Some(pin) = Pin::from_port_bit(port, bit)
+            {
+                let counter = counter(pin.entry_mut(pins));
+                *counter = counter.saturating_add(1);
+            }
+        }
+    }
+}
+
+/// Returns the `(port, byte)` pairs of a transfer that fall in `family`, following the
+/// auto-increment wrap within the family.
+fn ports(command_byte: u8, family: u8, data: &[u8]) -> impl Iterator<Item = (Port, u8)> + '_ {
+    let address = command_byte & 0x7F;
+    let start = usize::from(address & 0x03);
+    let auto_increment = command_byte & 0x80 != 0;
+    let len = if auto_increment { data.len() } else { 1 };
+    let matches = address >> 2 == family && start < 3;
+    data.iter()
+        .take(if matches { len } else { 0 })
+        .enumerate()
+        .map(move |(offset, &value)| (Port::wrapping(start + offset), value))
+}
+
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C> {
+    /// Returns the output toggle and input change counts of `pin` since the driver was
+    /// created or [`Tca64xx::reset_pin_stats`] was called.
+    ///
+    /// Output toggles are counted from the Output Port values the driver writes, compared
+    /// with the last value it wrote or read; input changes from consecutive Input Port reads,
+    /// so changes that come and go between two reads are not seen. A relay's toggle count
+    /// estimates its contact wear, and an input whose count grows much faster than expected
+    /// points at noise or a failing switch. Requires the `stats` feature.
+    pub fn pin_stats(&self, pin: Pin) -> PinStats {
+        self.stats.get(pin)
+    }
+
+    /// Clears the counters of every pin.
+    pub fn reset_pin_stats(&mut self) {
+        self.stats.reset();
+    }
+}
@@ -0,0 +1,178 @@
+//! Choice between auto-increment and per-register transfers for multi-register operations.
+
+use crate::errors::Error;
+use crate::registers::Register;
+use crate::{BusAccess, Chip, DelayNs, Port, Tca64xx};
+
+/// How the driver transfers several consecutive registers, set with
+/// [`Tca64xx::set_transfer_strategy`].
+///
+/// Every multi-port operation (the `_ai` methods, family writes of configuration
+/// transactions and board bring-up, snapshots, restores) goes through the same two
+/// primitives, so the strategy applies to all of them without changing call sites.
+///
+/// With per-register transfers, an operation documented as one auto-increment transaction
+/// becomes one transaction per register: it takes longer, and other bus traffic can come
+/// in between the ports. Plans and cost estimates ([`PlannedWrite`](crate::PlannedWrite),
+/// [`BusCost`](crate::BusCost)) always describe the auto-increment transfers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TransferStrategy {
+    /// Transfer consecutive registers with one auto-increment transaction (the default).
+    #[default]
+    PreferAutoIncrement,
+    /// Transfer every register in its own transaction, for I2C controllers or DMA setups
+    /// that handle multi-byte transfers poorly.
+    PerRegisterOnly,
+    /// Start with auto-increment. When a multi-register transfer fails with a bus error and
+    /// the same registers then transfer one by one, keep using per-register transfers.
+    Auto,
+}
+
+/// How the driver addresses a register before reading it, set with
+/// [`Tca64xx::set_read_sequence`].
+///
+/// The TCA6424 keeps the register address written by the command byte until the next
+/// command, so both sequences read the same data; they only differ on the bus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ReadSequence {
+    /// Write the command byte and read after a repeated start, with the HAL's `write_read`
+    /// (the default).
+    #[default]
+    RepeatedStart,
+    /// Write the command byte in one transaction and read in a second one, with a stop and
+    /// a new start in between. For HALs whose `write_read` does not work, or which cannot
+    /// issue a repeated start at all. Another master can take the bus between the two
+    /// transactions; its own command byte would then move the register address.
+    WriteThenRead,
+}
+
+/// The configured strategy and whether `Auto` has switched to per-register transfers.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct TransferState {
+    strategy: TransferStrategy,
+    fell_back: bool,
+    read_sequence: ReadSequence,
+}
+
+impl TransferState {
+    /// Returns `true` if multi-register transfers are currently split per register.
+    pub(crate) fn splits(&self) -> bool {
+        match self.strategy {
+            TransferStrategy::PreferAutoIncrement => false,
+            TransferStrategy::PerRegisterOnly => true,
+            TransferStrategy::Auto => self.fell_back,
+        }
+    }
+
+    /// Returns `true` if a multi-register transfer that ended with `result` should be
+    /// retried register by register.
+    pub(crate) fn retries<T, E: core::fmt::Debug>(&self, result: &Result<T, Error<E>>) -> bool {
+        self.strategy == TransferStrategy::Auto
+            && !self.fell_back
+            && matches!(result, Err(Error::I2c(_)))
+    }
+}
+
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C> {
+    /// Selects how multi-register operations transfer consecutive registers (see
+    /// [`TransferStrategy`]). Setting a strategy clears an earlier `Auto` switch.
+    pub fn set_transfer_strategy(&mut self, strategy: TransferStrategy) {
+        self.transfer.strategy = strategy;
+        self.transfer.fell_back = false;
+    }
+
+    /// Returns the strategy set with [`Tca64xx::set_transfer_strategy`].
+    pub fn transfer_strategy(&self) -> TransferStrategy {
+        self.transfer.strategy
+    }
+
+    /// Returns `true` if multi-register transfers are currently issued one register at a
+    /// time: always with [`TransferStrategy::PerRegisterOnly`], and with
+    /// [`TransferStrategy::Auto`] once it has switched.
+    pub fn splits_transfers(&self) -> bool {
+        self.transfer.splits()
+    }
+
+    /// Selects how register reads address the register (see [`ReadSequence`]).
+    pub fn set_read_sequence(&mut self, sequence: ReadSequence) {
+        self.transfer.read_sequence = sequence;
+    }
+
+    /// Returns the sequence set with [`Tca64xx::set_read_sequence`].
+    pub fn read_sequence(&self) -> ReadSequence {
+        self.transfer.read_sequence
+    }
+
+    /// Returns the number of bytes to read when probing the device: the whole Configuration
+    /// family, or only its first register when transfers are split.
+    #[cfg(feature = "hot-plug")]
+    pub(crate) fn probe_len(&self) -> usize {
+        if self.transfer.splits() { 1 } else { C::PORTS }
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca64xx",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Writes `values` one register at a time; the address wraps within the register
+    /// family like an auto-increment write.
+    pub(crate) async fn write_split(
+        &mut self,
+        start_register: Register,
+        values: &[u8],
+    ) -> Result<(), Error<I2C::Error>> {
+        let kind = start_register.kind();
+        for (offset, value) in values.iter().enumerate() {
+            let port = Port::wrapping(start_register.port() as usize + offset);
+            self.write_register(Register::of(kind, port), *value)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Reads `buffer` one register at a time; the address wraps within the register family
+    /// like an auto-increment read.
+    pub(crate) async fn read_split(
+        &mut self,
+        start_register: Register,
+        buffer: &mut [u8],
+    ) -> Result<(), Error<I2C::Error>> {
+        let kind = start_register.kind();
+        for (offset, byte) in buffer.iter_mut().enumerate() {
+            let port = Port::wrapping(start_register.port() as usize + offset);
+            *byte = self.read_register(Register::of(kind, port)).await?;
+        }
+        Ok(())
+    }
+}
+
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C>
+where
+    I2C: embedded_hal::i2c::ErrorType,
+    I2C::Error: core::fmt::Debug,
+{
+    /// Completes an `Auto` retry: switches to per-register transfers if `split` succeeded,
+    /// otherwise reports the error of the `original` auto-increment transfer.
+    pub(crate) fn retry_split(
+        &mut self,
+        split: Result<(), Error<I2C::Error>>,
+        original: Result<(), Error<I2C::Error>>,
+    ) -> Result<(), Error<I2C::Error>> {
+        match split {
+            Ok(()) => {
+                self.transfer.fell_back = true;
+                Ok(())
+            }
+            Err(_) => original,
+        }
+    }
+}
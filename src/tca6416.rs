@@ -0,0 +1,70 @@
+//! The 16-bit TCA6416A sibling of the TCA6424.
+//!
+//! The TCA6416A has the same register families as the TCA6424 minus Interrupt Mask, over two
+//! ports instead of three, and no auto-increment bit: consecutive bytes alternate between the
+//! two registers of a family. [`Tca6416`] is the shared [`Tca64xx`] driver with the
+//! [`Tca6416Chip`] register map, so code handling pins, ports, the cache and errors is shared
+//! between both parts. Pins `P20`-`P27` and [`Port::Port2`] do not exist on the TCA6416A and
+//! are rejected with `Error::InvalidRegisterOrPin`.
+
+use core::fmt::Debug;
+
+use crate::address::is_reserved_address;
+use crate::errors::Error;
+use crate::registers::Register;
+use crate::{BusAccess, Chip, DelayNs, NoDelay, Port, PortState, Tca64xx, Tca6416Chip};
+
+/// Default I2C address of the TCA6416A (ADDR pin tied low).
+pub const TCA6416_DEFAULT_ADDRESS: u8 = 0x20;
+
+/// Number of 8-bit ports of the TCA6416A.
+pub const TCA6416_PORTS: usize = <Tca6416Chip as Chip>::PORTS;
+
+/// Driver for the Texas Instruments TCA6416A 16-bit I2C I/O Expander.
+///
+/// Covers the pin and port access and the input events of [`Tca64xx`]; the methods spanning
+/// three ports are [`Tca6424`](crate::Tca6424) only. Requires the `tca6416` feature.
+pub type Tca6416<I2C, D = NoDelay> = Tca64xx<I2C, D, Tca6416Chip>;
+
+impl<I2C: embedded_hal::i2c::ErrorType> Tca6416<I2C> {
+    /// Creates a driver for the TCA6416A at `address`. No bus transfer is made.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Self)` on success, or `Error::ReservedAddress(address)` if the address is
+    /// reserved (see [`is_reserved_address`]).
+    pub fn new(i2c: I2C, address: u8) -> Result<Self, Error<I2C::Error>> {
+        if is_reserved_address(address) {
+            return Err(Error::ReservedAddress(address));
+        }
+        Ok(Self::for_chip(i2c, address))
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca6416",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> Tca6416<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: Debug,
+    D: DelayNs,
+{
+    /// Reads both Input Port registers in one transaction.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok([port0, port1])` on success, or an `Error` if the I2C bus operation fails.
+    pub async fn read_ports(&mut self) -> Result<[PortState; TCA6416_PORTS], Error<I2C::Error>> {
+        let mut values = [0u8; TCA6416_PORTS];
+        self.read_registers_ai(Register::InputPort0, &mut values)
+            .await?;
+        Ok([
+            PortState::new(Port::Port0, values[0]),
+            PortState::new(Port::Port1, values[1]),
+        ])
+    }
+}
@@ -0,0 +1,107 @@
+//! `tracing` events for every bus transfer.
+//!
+//! With the `tracing` feature each register read or write emits one event under the
+//! `tca6424` target: `TRACE` on success and `WARN` on failure, carrying the device address,
+//! register family, first port, auto-increment flag, data bytes and transfer latency in
+//! microseconds. Without the feature the [`Stopwatch`] is a zero-sized no-op.
+
+#[cfg(feature = "tracing")]
+use crate::{Port, RegisterKind};
+
+/// Measures one bus transfer and reports it when it completes.
+pub(crate) struct Stopwatch {
+    #[cfg(feature = "tracing")]
+    start: std::time::Instant,
+}
+
+impl Stopwatch {
+    /// Starts timing a transfer.
+    #[inline]
+    pub(crate) fn start() -> Self {
+        Self {
+            #[cfg(feature = "tracing")]
+            start: std::time::Instant::now(),
+        }
+    }
+
+    /// Reports a finished transfer; `result` is `None` if it timed out.
+    #[cfg(feature = "tracing")]
+    pub(crate) fn finish<E: core::fmt::Debug>(
+        self,
+        op: &'static str,
+        address: u8,
+        command_byte: u8,
+        bytes: &[u8],
+        result: Option<&Result<(), E>>,
+    ) {
+        let latency_us = self.start.elapsed().as_micros() as u64;
+        let (register, port) = decode(command_byte);
+        let auto_increment = command_byte & crate::registers::AUTO_INCREMENT != 0;
+        match result {
+            Some(Ok(())) => tracing::trace!(
+                target: "tca6424",
+                op,
+                address,
+                ?register,
+                ?port,
+                auto_increment,
+                ?bytes,
+                latency_us,
+                "transfer"
+            ),
+            Some(Err(error)) => tracing::warn!(
+                target: "tca6424",
+                op,
+                address,
+                ?register,
+                ?port,
+                auto_increment,
+                latency_us,
+                ?error,
+                "transfer failed"
+            ),
+            None => tracing::warn!(
+                target: "tca6424",
+                op,
+                address,
+                ?register,
+                ?port,
+                auto_increment,
+                latency_us,
+                "transfer timed out"
+            ),
+        }
+    }
+
+    /// Reports a finished transfer; `result` is `None` if it timed out.
+    #[cfg(not(feature = "tracing"))]
+    #[inline(always)]
+    pub(crate) fn finish<E: core::fmt::Debug>(
+        self,
+        _op: &'static str,
+        _address: u8,
+        _command_byte: u8,
+        _bytes: &[u8],
+        _result: Option<&Result<(), E>>,
+    ) {
+    }
+}
+
+/// Returns the register family and port selected by a command byte.
+#[cfg(feature = "tracing")]
+fn decode(command_byte: u8) -> (RegisterKind, Port) {
+    let address = command_byte & !crate::registers::AUTO_INCREMENT;
+    let kind = match address >> 2 {
+        0 => RegisterKind::Input,
+        1 => RegisterKind::Output,
+        2 => RegisterKind::PolarityInversion,
+        3 => RegisterKind::Configuration,
+        _ => RegisterKind::InterruptMask,
+    };
+    let port = match address & 0x03 {
+        0 => Port::Port0,
+        1 => Port::Port1,
+        _ => Port::Port2,
+    };
+    (kind, port)
+}
@@ -0,0 +1,401 @@
+//! A simulated TCA6424 and invariant checks for property tests.
+//!
+//! [`RegisterModel`] behaves like the device on the bus: it decodes command bytes, honours
+//! auto-increment, and derives the Input registers from the configured directions, the
+//! output latches, the polarity inversion and externally applied pin levels. It implements
+//! the I2C trait the driver uses, so a `Tca6424<RegisterModel>` runs every driver method
+//! without hardware or mock expectations.
+//!
+//! The `check_*` functions state invariants of the driver over that model. Each runs one
+//! operation and returns an [`InvariantViolation`] describing the first broken property, so
+//! they can be called from any property-testing framework with generated inputs:
+//!
+//! ```
+//! use tca6424::testing::{self, PinOp, RegisterModel};
+//! use tca6424::{DEFAULT_ADDRESS, Pin, PinState, Tca6424};
+//!
+//! # #[cfg(not(feature = "async"))]
+//! # {
+//! let mut tca = Tca6424::new(RegisterModel::new(), DEFAULT_ADDRESS).unwrap();
+//! for index in 0..24 {
+//!     let pin = Pin::from_index(index).unwrap();
+//!     testing::check_pin_isolation(&mut tca, pin, PinOp::Output(PinState::Low)).unwrap();
+//! }
+//! testing::check_cache_coherent(&tca).unwrap();
+//! # }
+//! ```
+
+use embedded_hal::i2c::{ErrorKind, ErrorType, NoAcknowledgeSource, Operation, SevenBitAddress};
+
+use crate::errors::Error;
+use crate::registers::Register;
+use crate::{
+    DEFAULT_ADDRESS, DelayNs, Pin, PinDirection, PinState, Port, RegisterChange, RegisterKind,
+    RegisterSnapshot, Tca6424,
+};
+
+/// Register families the driver writes; the Input registers are read-only.
+const WRITABLE_FAMILIES: [RegisterKind; 4] = [
+    RegisterKind::Output,
+    RegisterKind::PolarityInversion,
+    RegisterKind::Configuration,
+    RegisterKind::InterruptMask,
+];
+
+/// A simulated TCA6424 on its own I2C bus.
+///
+/// Transfers to any other address are not acknowledged, and a command byte that selects a
+/// reserved register is rejected with a data NACK. Writes to the Input registers are
+/// ignored, as on the device.
+///
+/// The model also records output glitches: pins turned into outputs while their Output
+/// latch still held the power-on default, because no value was written to it first. See
+/// [`RegisterModel::glitched_pins`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisterModel {
+    address: u8,
+    registers: RegisterSnapshot,
+    pin_levels: u32,
+    pointer: u8,
+    /// Output latches written since reset, per family byte.
+    latched: [bool; 3],
+    glitched: u32,
+}
+
+impl RegisterModel {
+    /// Creates a model at [`DEFAULT_ADDRESS`] with every register at its power-on default.
+    pub const fn new() -> Self {
+        Self {
+            address: DEFAULT_ADDRESS,
+            registers: RegisterSnapshot {
+                inputs: [0; 3],
+                outputs: [0xFF; 3],
+                polarity_inversion: [0; 3],
+                configuration: [0xFF; 3],
+                interrupt_mask: [0xFF; 3],
+            },
+            pin_levels: 0,
+            pointer: 0,
+            latched: [false; 3],
+            glitched: 0,
+        }
+    }
+
+    /// Answers at `address` instead of [`DEFAULT_ADDRESS`].
+    pub const fn with_address(mut self, address: u8) -> Self {
+        self.address = address;
+        self
+    }
+
+    /// Returns the I2C address the model answers at.
+    pub const fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// Puts every register back at its power-on default, as a power cycle would.
+    pub fn reset(&mut self) {
+        *self = Self::new().with_address(self.address);
+    }
+
+    /// Drives the input pins externally (bit `n` = pin index `n`). Output pins ignore it.
+    pub fn set_pin_levels(&mut self, levels: u32) {
+        self.pin_levels = levels & 0x00FF_FFFF;
+    }
+
+    /// Returns the level of every pin: the external level of inputs, the latch of outputs.
+    pub fn pin_levels(&self) -> u32 {
+        let configuration = family_word(&self.registers.configuration);
+        let outputs = family_word(&self.registers.outputs);
+        (self.pin_levels & configuration) | (outputs & !configuration & 0x00FF_FFFF)
+    }
+
+    /// Returns the pins that became outputs before their Output latch was written since the
+    /// last reset (bit `n` = pin index `n`).
+    ///
+    /// Such a pin drives the power-on-high latch for at least one transfer before the
+    /// intended level is written: a glitch on active-high loads. Configuration paths are
+    /// expected to write the Output registers first and leave this mask empty.
+    pub fn glitched_pins(&self) -> u32 {
+        self.glitched
+    }
+
+    /// Returns the value the device would return for `register`.
+    pub fn register(&self, register: Register) -> u8 {
+        self.snapshot().get(register.kind(), register.port())
+    }
+
+    /// Overwrites `register`, bypassing the bus (for example to simulate a glitch).
+    pub fn set_register(&mut self, register: Register, value: u8) {
+        self.store(register.kind(), register.port(), value);
+    }
+
+    /// Returns every register as the device would report it.
+    pub fn snapshot(&self) -> RegisterSnapshot {
+        let inputs = self.pin_levels() ^ family_word(&self.registers.polarity_inversion);
+        let mut snapshot = self.registers;
+        let [port0, port1, port2, _] = inputs.to_le_bytes();
+        snapshot.inputs = [port0, port1, port2];
+        snapshot
+    }
+
+    /// Writes a register, recording output glitches caused by Configuration writes.
+    fn store(&mut self, kind: RegisterKind, port: Port, value: u8) {
+        let latched = port.entry_mut(&mut self.latched);
+        match kind {
+            RegisterKind::Output => *latched = true,
+            RegisterKind::Configuration if !*latched => {
+                let new_outputs = *port.entry(&self.registers.configuration) & !value;
+                self.glitched |= u32::from(new_outputs) << (port as u8 * 8);
+            }
+            _ => {}
+        }
+        *port.entry_mut(self.registers.family_mut(kind)) = value;
+    }
+
+    fn select(&mut self, command_byte: u8) -> Result<(), ErrorKind> {
+        let address = command_byte & !crate::AUTO_INCREMENT;
+        if address > Register::InterruptMaskPort2 as u8 || address & 0x03 == 0x03 {
+            return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data));
+        }
+        self.pointer = command_byte;
+        Ok(())
+    }
+
+    fn current(&self) -> (RegisterKind, Port) {
+        let register = crate::registers::register_of(self.pointer);
+        (register.kind(), register.port())
+    }
+
+    fn advance(&mut self) {
+        if self.pointer & crate::AUTO_INCREMENT != 0 {
+            let port = ((self.pointer & 0x03) + 1) % 3;
+            self.pointer = (self.pointer & !0x03) | port;
+        }
+    }
+
+    fn run(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), ErrorKind> {
+        if address != self.address {
+            return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+        }
+        let mut command_pending = true;
+        for operation in operations {
+            match operation {
+                Operation::Write(bytes) => {
+                    for &byte in bytes.iter() {
+                        if command_pending {
+                            self.select(byte)?;
+                            command_pending = false;
+                            continue;
+                        }
+                        let (kind, port) = self.current();
+                        if kind != RegisterKind::Input {
+                            self.store(kind, port, byte);
+                        }
+                        self.advance();
+                    }
+                }
+                Operation::Read(buffer) => {
+                    for byte in buffer.iter_mut() {
+                        let (kind, port) = self.current();
+                        *byte = self.snapshot().get(kind, port);
+                        self.advance();
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for RegisterModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn family_word(family: &[u8; 3]) -> u32 {
+    u32::from_le_bytes([family[0], family[1], family[2], 0])
+}
+
+impl ErrorType for RegisterModel {
+    type Error = ErrorKind;
+}
+
+#[cfg(not(feature = "async"))]
+impl embedded_hal::i2c::I2c for RegisterModel {
+    fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.run(address, operations)
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::i2c::I2c for RegisterModel {
+    async fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.run(address, operations)
+    }
+}
+
+impl<D> Tca6424<RegisterModel, D> {
+    /// Returns the simulated device.
+    pub fn model(&self) -> &RegisterModel {
+        &self.i2c
+    }
+
+    /// Returns the simulated device, for example to drive its input pins.
+    pub fn model_mut(&mut self) -> &mut RegisterModel {
+        &mut self.i2c
+    }
+}
+
+/// A pin operation exercised by [`check_pin_isolation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PinOp {
+    /// [`Tca6424::set_pin_direction`].
+    Direction(PinDirection),
+    /// [`Tca6424::set_pin_output`].
+    Output(PinState),
+}
+
+/// A broken invariant, as reported by the `check_*` functions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvariantViolation {
+    /// A driver call failed.
+    Bus(Error<ErrorKind>),
+    /// A register read back differs from what was written: `before` holds the written value
+    /// and `after` the value read back.
+    ReadbackMismatch(RegisterChange),
+    /// A pin operation changed a bit it does not own, or did not set its own bit. `before`
+    /// and `after` are the register before and after the operation.
+    OtherPinTouched(RegisterChange),
+    /// The driver's cache disagrees with the device: `before` holds the cached value and
+    /// `after` the device register.
+    CacheIncoherent(RegisterChange),
+}
+
+impl From<Error<ErrorKind>> for InvariantViolation {
+    fn from(error: Error<ErrorKind>) -> Self {
+        InvariantViolation::Bus(error)
+    }
+}
+
+/// Checks that the driver's register cache matches the device wherever it holds a value.
+pub fn check_cache_coherent<D>(tca: &Tca6424<RegisterModel, D>) -> Result<(), InvariantViolation> {
+    let device = tca.model().snapshot();
+    for kind in WRITABLE_FAMILIES {
+        for port in Port::ALL {
+            if let Some(cached) = tca.cache().get(kind, port) {
+                let actual = device.get(kind, port);
+                if cached != actual {
+                    return Err(InvariantViolation::CacheIncoherent(RegisterChange {
+                        kind,
+                        port,
+                        before: cached,
+                        after: actual,
+                    }));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that writing every writable register family of `image` and reading it back
+/// returns `image` unchanged, over the device and through the driver.
+///
+/// The Input registers of `image` are ignored. This function is `async` when the `async`
+/// feature is enabled, and synchronous otherwise.
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), keep_self),
+    async(feature = "async", keep_self)
+)]
+pub async fn check_apply_readback<D: DelayNs>(
+    tca: &mut Tca6424<RegisterModel, D>,
+    image: &RegisterSnapshot,
+) -> Result<(), InvariantViolation> {
+    for kind in WRITABLE_FAMILIES {
+        tca.write_registers_ai(Register::of(kind, Port::Port0), image.family(kind))
+            .await?;
+    }
+    for kind in WRITABLE_FAMILIES {
+        let mut values = [0u8; 3];
+        tca.read_registers_ai(Register::of(kind, Port::Port0), &mut values)
+            .await?;
+        for port in Port::ALL {
+            let written = image.get(kind, port);
+            let read = *port.entry(&values);
+            if written != read {
+                return Err(InvariantViolation::ReadbackMismatch(RegisterChange {
+                    kind,
+                    port,
+                    before: written,
+                    after: read,
+                }));
+            }
+        }
+    }
+    check_cache_coherent(tca)
+}
+
+/// Checks that `op` on `pin` changes at most the bit of `pin`, in the register family the
+/// operation targets, and that the bit ends up at the requested value.
+///
+/// This function is `async` when the `async` feature is enabled, and synchronous otherwise.
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), keep_self),
+    async(feature = "async", keep_self)
+)]
+pub async fn check_pin_isolation<D: DelayNs>(
+    tca: &mut Tca6424<RegisterModel, D>,
+    pin: Pin,
+    op: PinOp,
+) -> Result<(), InvariantViolation> {
+    let before = tca.model().snapshot();
+    let (target, bit_set) = match op {
+        PinOp::Direction(direction) => {
+            tca.set_pin_direction(pin, direction).await?;
+            (
+                RegisterKind::Configuration,
+                direction == PinDirection::Input,
+            )
+        }
+        PinOp::Output(state) => {
+            tca.set_pin_output(pin, state).await?;
+            (RegisterKind::Output, state == PinState::High)
+        }
+    };
+    let after = tca.model().snapshot();
+
+    for kind in WRITABLE_FAMILIES {
+        for port in Port::ALL {
+            let change = RegisterChange {
+                kind,
+                port,
+                before: before.get(kind, port),
+                after: after.get(kind, port),
+            };
+            let owned = if kind == target && port == pin.port() {
+                1 << pin.bit()
+            } else {
+                0
+            };
+            let bit_wrong = owned != 0 && (change.after & owned != 0) != bit_set;
+            if change.changed_bits() & !owned != 0 || bit_wrong {
+                return Err(InvariantViolation::OtherPinTouched(change));
+            }
+        }
+    }
+    check_cache_coherent(tca)
+}
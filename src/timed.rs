@@ -0,0 +1,163 @@
+//! `embassy-time` conveniences: helpers that read the clock themselves.
+//!
+//! The helpers take a free-running millisecond timestamp and the driver takes a `DelayNs`
+//! source, so they work with any time base. With the `embassy-time` feature the `*_now`
+//! variants read `embassy_time::Instant` directly, [`EmbassyClock`] feeds it to the
+//! [`TimeSource`] based `*_from` variants, and [`Tca6424::with_embassy_delay`] stores
+//! `embassy_time::Delay` in the driver, so staggered switching, strap settling and
+//! [`Heartbeat::run`] wait on embassy timers without a hand-written adapter.
+
+use crate::errors::Error;
+use crate::{
+    BusAccess, ButtonEvent, Debounce, Debouncer, DelayNs, EncoderEvent, Heartbeat,
+    InterruptThrottle, LitButton, Pin, PinScheduler, PinState, RotaryEncoder, Tca6424, TimeSource,
+};
+
+/// Returns `embassy_time::Instant::now()` as the wrapping millisecond timestamp the helpers
+/// expect.
+pub fn now_ms() -> u32 {
+    embassy_time::Instant::now().as_millis() as u32
+}
+
+/// The `embassy_time::Instant` clock as a [`TimeSource`], for the `*_from` helper variants.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EmbassyClock;
+
+impl TimeSource for EmbassyClock {
+    fn now_ms(&self) -> u32 {
+        now_ms()
+    }
+}
+
+impl<I2C, D> Tca6424<I2C, D> {
+    /// Returns the driver with `embassy_time::Delay` as its delay source.
+    pub fn with_embassy_delay(self) -> Tca6424<I2C, embassy_time::Delay> {
+        self.with_delay(embassy_time::Delay)
+    }
+}
+
+impl Debouncer {
+    /// Processes one raw sample taken now; see [`Debouncer::update`].
+    pub fn update_now(&mut self, raw: bool) -> Option<bool> {
+        self.update(raw, now_ms())
+    }
+}
+
+impl PinScheduler {
+    /// [`PinScheduler::set_pin_after`] counting from now.
+    pub fn set_pin_after_now(&mut self, pin: Pin, state: PinState, delay_ms: u32) {
+        self.set_pin_after(pin, state, delay_ms, now_ms());
+    }
+}
+
+impl InterruptThrottle {
+    /// Returns `true` if the inputs should be read now; see [`InterruptThrottle::poll`].
+    pub fn poll_now(&mut self) -> bool {
+        self.poll(now_ms())
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "LitButton",),
+    async(feature = "async", keep_self)
+)]
+impl<B: Debounce> LitButton<B> {
+    /// [`LitButton::poll`] with the current time.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    pub async fn poll_now<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+    ) -> Result<Option<ButtonEvent>, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        self.poll(tca, now_ms()).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "RotaryEncoder",),
+    async(feature = "async", keep_self)
+)]
+impl<B: Debounce> RotaryEncoder<B> {
+    /// [`RotaryEncoder::poll`] with the current time.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    pub async fn poll_now<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+    ) -> Result<Option<EncoderEvent>, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        self.poll(tca, now_ms()).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Heartbeat",),
+    async(feature = "async", keep_self)
+)]
+impl Heartbeat {
+    /// [`Heartbeat::tick`] with the current time.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    pub async fn tick_now<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+    ) -> Result<bool, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        self.tick(tca, now_ms()).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "PinScheduler",),
+    async(feature = "async", keep_self)
+)]
+impl PinScheduler {
+    /// [`PinScheduler::run`] with the current time.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    pub async fn run_now<I2C, D>(
+        &mut self,
+        tca: &mut Tca6424<I2C, D>,
+    ) -> Result<u32, Error<I2C::Error>>
+    where
+        I2C: BusAccess,
+        I2C::Error: core::fmt::Debug,
+        D: DelayNs,
+    {
+        self.run(tca, now_ms()).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca6424",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> Tca6424<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// [`Tca6424::service_interrupt`] with the current time.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    pub async fn service_interrupt_now(
+        &mut self,
+        throttle: &mut InterruptThrottle,
+    ) -> Result<Option<u32>, Error<I2C::Error>> {
+        self.service_interrupt(throttle, now_ms()).await
+    }
+}
@@ -0,0 +1,163 @@
+//! Stored delay source and async operation timeouts.
+
+use crate::errors::Error;
+use crate::{Tca64xx, Tca6424};
+
+/// Placeholder delay source of a driver created without one.
+///
+/// Its delays return immediately. Attach a real `DelayNs` implementor with
+/// [`Tca6424::with_delay`] to use features that need time, such as operation timeouts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoDelay;
+
+impl embedded_hal::delay::DelayNs for NoDelay {
+    fn delay_ns(&mut self, _ns: u32) {}
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::delay::DelayNs for NoDelay {
+    async fn delay_ns(&mut self, _ns: u32) {}
+}
+
+impl<I2C: embedded_hal::i2c::ErrorType, D> Tca6424<I2C, D> {
+    /// Creates a driver with `delay` stored as its delay source.
+    ///
+    /// Equivalent to [`Tca6424::new`] followed by [`Tca6424::with_delay`]. The methods that
+    /// wait as a side task (strap settling, health-check loops, helper tasks) use the stored
+    /// source. Those whose waits protect the hardware, such as staggered switching, take the
+    /// delay as an argument instead, so the default [`NoDelay`] cannot skip them.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Self)` on success, or an `Error` if the I2C bus operation fails.
+    pub fn new_with_delay(i2c: I2C, address: u8, delay: D) -> Result<Self, Error<I2C::Error>> {
+        Ok(Tca6424::<I2C, NoDelay>::new(i2c, address)?.with_delay(delay))
+    }
+}
+
+impl<I2C, D, C> Tca64xx<I2C, D, C> {
+    /// Returns the driver with `delay` stored as its delay source.
+    ///
+    /// The delay implements `embedded_hal::delay::DelayNs` in sync mode and
+    /// `embedded_hal_async::delay::DelayNs` with the `async` feature. Every other setting,
+    /// including the register cache and pin claims, carries over.
+    pub fn with_delay<D2>(self, delay: D2) -> Tca64xx<I2C, D2, C> {
+        Tca64xx {
+            i2c: self.i2c,
+            delay,
+            #[cfg(feature = "async")]
+            timeout_us: self.timeout_us,
+            transfer_gap_us: self.transfer_gap_us,
+            address: self.address,
+            cache: self.cache,
+            #[cfg(feature = "hot-plug")]
+            link: self.link,
+            labels: self.labels,
+            claims: self.claims,
+            input_map: self.input_map,
+            channels: self.channels,
+            #[cfg(feature = "fail-safe")]
+            fail_safe: self.fail_safe,
+            sleep: self.sleep,
+            interrupt_inputs: self.interrupt_inputs,
+            #[cfg(feature = "capture")]
+            capture: self.capture,
+            #[cfg(feature = "stats")]
+            stats: self.stats,
+            #[cfg(feature = "dry-run")]
+            dry_run: self.dry_run,
+            #[cfg(feature = "diagnostics")]
+            startup: self.startup,
+            #[cfg(feature = "fallback")]
+            fallback: self.fallback,
+            write_only: self.write_only,
+            cached_output_reads: self.cached_output_reads,
+            transfer: self.transfer,
+            #[cfg(feature = "retry")]
+            retry: self.retry,
+            #[cfg(not(feature = "async"))]
+            yield_fn: self.yield_fn,
+            #[cfg(feature = "async")]
+            yield_between_transfers: self.yield_between_transfers,
+            direction_hook: self.direction_hook,
+            #[cfg(feature = "error-context")]
+            context: self.context,
+            transactions: self.transactions,
+            chip: self.chip,
+        }
+    }
+
+    /// Returns the stored delay source.
+    pub fn delay(&mut self) -> &mut D {
+        &mut self.delay
+    }
+
+    /// Sets a timeout for every I2C transfer, measured with the stored delay source.
+    ///
+    /// A transfer that has not completed within `timeout_us` microseconds is abandoned and
+    /// reported as `Error::Timeout`, so a wedged bus cannot stall the task forever. `None`
+    /// (the default) waits indefinitely. Requires a real delay source: with [`NoDelay`] every
+    /// transfer would time out immediately.
+    ///
+    /// Only available with the `async` feature; a blocking transfer cannot be abandoned.
+    #[cfg(feature = "async")]
+    pub fn set_timeout_us(&mut self, timeout_us: Option<u32>) {
+        self.timeout_us = timeout_us;
+    }
+
+    /// Returns the configured transfer timeout.
+    #[cfg(feature = "async")]
+    pub fn timeout_us(&self) -> Option<u32> {
+        self.timeout_us
+    }
+
+    /// Waits `gap_us` microseconds, with the stored delay source, before every register
+    /// transfer.
+    ///
+    /// Helpers that issue several transactions back to back (auto-increment batches,
+    /// configuration commits, sleep and restore sequences) then leave a gap between them,
+    /// which helps on long cables or heavily loaded buses where an immediate new start
+    /// condition causes errors. `0` (the default) inserts no delay. Requires a real delay
+    /// source: [`NoDelay`] returns at once.
+    pub fn set_transfer_gap_us(&mut self, gap_us: u32) {
+        self.transfer_gap_us = gap_us;
+    }
+
+    /// Returns the configured gap between register transfers.
+    pub fn transfer_gap_us(&self) -> u32 {
+        self.transfer_gap_us
+    }
+}
+
+/// Runs `transfer`, giving up once `delay` has waited `timeout_us`.
+///
+/// Returns `None` on timeout. The transfer future is dropped in that case, which aborts it
+/// at its current await point.
+#[cfg(feature = "async")]
+pub(crate) async fn with_timeout<D, F>(
+    delay: &mut D,
+    timeout_us: Option<u32>,
+    transfer: F,
+) -> Option<F::Output>
+where
+    D: embedded_hal_async::delay::DelayNs,
+    F: core::future::Future,
+{
+    use core::task::Poll;
+
+    let Some(timeout_us) = timeout_us else {
+        return Some(transfer.await);
+    };
+    let mut transfer = core::pin::pin!(transfer);
+    let mut expiry = core::pin::pin!(delay.delay_us(timeout_us));
+    core::future::poll_fn(|cx| {
+        if let Poll::Ready(output) = transfer.as_mut().poll(cx) {
+            return Poll::Ready(Some(output));
+        }
+        if expiry.as_mut().poll(cx).is_ready() {
+            return Poll::Ready(None);
+        }
+        Poll::Pending
+    })
+    .await
+}
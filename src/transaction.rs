@@ -0,0 +1,293 @@
+//! Staged multi-register configuration changes, committed in a fixed order.
+
+use crate::errors::Error;
+use crate::plan::{self, PlannedWrite};
+use crate::recovery::RESTORE_ORDER;
+use crate::registers::Register;
+use crate::{
+    Activity, BusAccess, DelayNs, Pin, PinDirection, PinState, Port, RegisterCache, RegisterKind,
+    Tca6424,
+};
+
+/// Configuration changes staged for one commit, built by [`Tca6424::with_config_transaction`].
+///
+/// The device cannot apply several registers atomically, so a commit is "atomic" only in a
+/// weaker, well-defined sense:
+///
+/// - Register families are written in a fixed order: Output, Polarity Inversion, Interrupt
+///   Mask, then Configuration. Pins becoming outputs therefore drive their staged level from
+///   the first moment, and their polarity and interrupt settings are in place before any
+///   direction changes.
+/// - Each family is written with at most one auto-increment transaction when its changed
+///   ports are adjacent (or all three ports have a known value); otherwise one write per
+///   changed port. Ports whose value would not change are not written.
+/// - Bits not staged keep their value. Partially staged ports take the rest of their bits
+///   from the register cache, or from one auto-increment read of the family when a value is
+///   unknown.
+/// - If a transfer fails, the families written before it stay applied, and the cache records
+///   exactly what reached the device.
+///
+/// Later calls override earlier ones for the same pin and family.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ConfigTransaction {
+    /// Staged bits and their values, per family in [`RESTORE_ORDER`] order.
+    masks: [u32; 4],
+    values: [u32; 4],
+}
+
+/// Mask of the 24 pin bits.
+const PIN_BITS: u32 = 0x00FF_FFFF;
+
+impl ConfigTransaction {
+    /// Creates an empty transaction.
+    pub const fn new() -> Self {
+        Self {
+            masks: [0; 4],
+            values: [0; 4],
+        }
+    }
+
+    /// Returns `true` if nothing is staged.
+    pub const fn is_empty(&self) -> bool {
+        matches!(self.masks, [0, 0, 0, 0])
+    }
+
+    /// Returns the staged `(mask, values)` of `kind` (bit `n` = pin index `n`); the Input
+    /// family is never staged.
+    pub fn staged(&self, kind: RegisterKind) -> (u32, u32) {
+        self.families()
+            .find(|&(family, _, _)| family == kind)
+            .map_or((0, 0), |(_, mask, values)| (mask, values))
+    }
+
+    /// Stages the direction of `pin`.
+    pub fn direction(&mut self, pin: Pin, direction: PinDirection) -> &mut Self {
+        let input = direction == PinDirection::Input;
+        self.stage(
+            RegisterKind::Configuration,
+            1 << pin as u8,
+            u32::from(input) * PIN_BITS,
+        )
+    }
+
+    /// Stages the output level of `pin`.
+    pub fn output(&mut self, pin: Pin, state: impl Into<PinState>) -> &mut Self {
+        let high = state.into() == PinState::High;
+        self.stage(
+            RegisterKind::Output,
+            1 << pin as u8,
+            u32::from(high) * PIN_BITS,
+        )
+    }
+
+    /// Stages the polarity inversion of `pin`.
+    #[cfg(feature = "polarity")]
+    pub fn polarity_inversion(&mut self, pin: Pin, invert: bool) -> &mut Self {
+        self.stage(
+            RegisterKind::PolarityInversion,
+            1 << pin as u8,
+            u32::from(invert) * PIN_BITS,
+        )
+    }
+
+    /// Stages the interrupt mask of `pin` (`true` = masked).
+    #[cfg(feature = "interrupt-mask")]
+    pub fn interrupt_mask(&mut self, pin: Pin, masked: bool) -> &mut Self {
+        self.stage(
+            RegisterKind::InterruptMask,
+            1 << pin as u8,
+            u32::from(masked) * PIN_BITS,
+        )
+    }
+
+    /// Stages the directions of every pin of `port` (`1` = input).
+    pub fn port_direction(&mut self, port: Port, direction_mask: u8) -> &mut Self {
+        self.stage_port(RegisterKind::Configuration, port, direction_mask)
+    }
+
+    /// Stages the output levels of every pin of `port` (`1` = high).
+    pub fn port_output(&mut self, port: Port, output_mask: u8) -> &mut Self {
+        self.stage_port(RegisterKind::Output, port, output_mask)
+    }
+
+    /// Stages the bits of `mask` in the `kind` family to the matching bits of `values`
+    /// (24-bit, bit `n` = pin index `n`). Staging the Input family does nothing.
+    pub fn stage(&mut self, kind: RegisterKind, mask: u32, values: u32) -> &mut Self {
+        let slots = self.masks.iter_mut().zip(self.values.iter_mut());
+        let slot = RESTORE_ORDER
+            .iter()
+            .zip(slots)
+            .find_map(|(&family, slot)| (family == kind).then_some(slot));
+        if let Some((staged, staged_values)) = slot {
+            let mask = mask & PIN_BITS;
+            *staged |= mask;
+            *staged_values = (*staged_values & !mask) | (values & mask);
+        }
+        self
+    }
+
+    /// Returns the writes a commit would issue against a device whose registers hold the
+    /// values of `cache`, in commit order, without touching the bus.
+    ///
+    /// Unknown register values are taken as their power-on defaults. Where a partially staged
+    /// port's value is unknown, the commit reads its family first and writes from the values
+    /// read, so the plan matches the device only if it still holds those defaults; pass
+    /// [`Tca6424::cache`] of an initialised driver for an exact plan.
+    pub fn plan(&self, cache: &RegisterCache) -> impl Iterator<Item = PlannedWrite> + use<> {
+        let mut writes: heapless::Vec<PlannedWrite, 12> = heapless::Vec::new();
+        for (kind, mask, values) in self.families() {
+            if mask == 0 {
+                continue;
+            }
+            let mut current = Port::ALL.map(|port| cache.get(kind, port));
+            if partial_unknown(mask, &current) {
+                current = current.map(|value| Some(value.unwrap_or(plan::power_on_value(kind))));
+            }
+            let target = family_target(mask, values, current);
+            // At most three writes per family, matching the capacity.
+            writes.extend(plan::family_writes(kind, current, target));
+        }
+        writes.into_iter()
+    }
+
+    /// Returns the staged `(kind, mask, values)` of every family, in commit order.
+    fn families(&self) -> impl Iterator<Item = (RegisterKind, u32, u32)> + use<> {
+        RESTORE_ORDER
+            .into_iter()
+            .zip(self.masks)
+            .zip(self.values)
+            .map(|((kind, mask), values)| (kind, mask, values))
+    }
+
+    fn stage_port(&mut self, kind: RegisterKind, port: Port, value: u8) -> &mut Self {
+        let shift = port as u8 * 8;
+        self.stage(kind, 0xFF << shift, u32::from(value) << shift)
+    }
+}
+
+/// Returns `true` if a partially staged port of `mask` has no known value in `current`.
+fn partial_unknown(mask: u32, current: &[Option<u8>; 3]) -> bool {
+    Port::ALL.iter().any(|&port| {
+        let staged = port.byte_of(mask);
+        staged != 0 && staged != 0xFF && port.entry(current).is_none()
+    })
+}
+
+/// Returns the value of every port with staged bits: fully staged ports take the staged
+/// value, partially staged ones merge it into `current` (`None` while that is unknown).
+fn family_target(mask: u32, values: u32, current: [Option<u8>; 3]) -> [Option<u8>; 3] {
+    Port::ALL.map(|port| {
+        let (staged, value) = (port.byte_of(mask), port.byte_of(values));
+        match (staged, *port.entry(&current)) {
+            (0, _) => None,
+            (0xFF, _) => Some(value),
+            (_, known) => known.map(|known| (known & !staged) | (value & staged)),
+        }
+    })
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca6424",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> Tca6424<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Stages configuration changes in `stage`, then commits them with
+    /// [`Tca6424::apply_config_transaction`].
+    ///
+    /// ```ignore
+    /// tca.with_config_transaction(|txn| {
+    ///     txn.output(Pin::P00, PinState::Low)
+    ///         .direction(Pin::P00, PinDirection::Output)
+    ///         .port_direction(Port::Port2, 0xFF);
+    /// })?;
+    /// ```
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails.
+    pub async fn with_config_transaction<F>(&mut self, stage: F) -> Result<(), Error<I2C::Error>>
+    where
+        F: FnOnce(&mut ConfigTransaction),
+    {
+        let mut transaction = ConfigTransaction::new();
+        stage(&mut transaction);
+        self.apply_config_transaction(&transaction).await
+    }
+
+    /// Commits the changes staged in `transaction`, family by family in the order documented
+    /// on [`ConfigTransaction`].
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails; the families
+    /// written before the failure stay applied.
+    pub async fn apply_config_transaction(
+        &mut self,
+        transaction: &ConfigTransaction,
+    ) -> Result<(), Error<I2C::Error>> {
+        let scope = self.enter_activity(Activity::ApplyConfig);
+        let result = self.commit_transaction(transaction).await;
+        self.leave_activity(scope, result)
+    }
+
+    async fn commit_transaction(
+        &mut self,
+        transaction: &ConfigTransaction,
+    ) -> Result<(), Error<I2C::Error>> {
+        for (kind, mask, values) in transaction.families() {
+            if mask == 0 {
+                continue;
+            }
+            let mut current = Port::ALL.map(|port| self.cache.get(kind, port));
+            if partial_unknown(mask, &current) {
+                let mut read = [0u8; 3];
+                self.read_registers_ai(Register::of(kind, Port::Port0), &mut read)
+                    .await?;
+                current = read.map(Some);
+            }
+            let target = family_target(mask, values, current);
+            self.write_changed(kind, current, target).await?;
+        }
+        Ok(())
+    }
+
+    /// Makes the pins of `pins` inputs, with their interrupts enabled when `interrupts` is
+    /// `true` (masked otherwise) and their polarity inverted where `invert` has a bit set;
+    /// masks use bit `n` = pin index `n`, and the bits of `invert` outside `pins` are ignored.
+    ///
+    /// All three register families are written in one [`ConfigTransaction`], so polarity and
+    /// interrupt mask are in place before the pins turn into inputs, each family takes at
+    /// most one auto-increment write, and values already in the cache are not rewritten.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `Error` if an I2C bus operation fails; the families
+    /// written before the failure stay applied.
+    #[cfg(all(feature = "polarity", feature = "interrupt-mask"))]
+    pub async fn configure_inputs(
+        &mut self,
+        pins: u32,
+        interrupts: bool,
+        invert: u32,
+    ) -> Result<(), Error<I2C::Error>> {
+        let masked = if interrupts { 0 } else { PIN_BITS };
+        let mut transaction = ConfigTransaction::new();
+        transaction
+            .stage(RegisterKind::PolarityInversion, pins, invert)
+            .stage(RegisterKind::InterruptMask, pins, masked)
+            .stage(RegisterKind::Configuration, pins, PIN_BITS);
+        self.apply_config_transaction(&transaction).await
+    }
+}
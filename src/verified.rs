@@ -0,0 +1,83 @@
+//! Verified and filtered input reads for safety-critical and asynchronous signals.
+
+use crate::errors::Error;
+use crate::registers::Register;
+use crate::{BusAccess, DelayNs, Pin, PinState, RegisterKind, Tca6424};
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")), self = "Tca6424",),
+    async(feature = "async", keep_self)
+)]
+impl<I2C, D> Tca6424<I2C, D>
+where
+    I2C: BusAccess,
+    I2C::Error: core::fmt::Debug,
+    D: DelayNs,
+{
+    /// Reads the 24 input levels and accepts them only once two consecutive reads agree.
+    ///
+    /// Intended for interlock and E-stop signals, where acting on a corrupted read is worse
+    /// than failing. Each read fetches the three Input Port registers in one auto-increment
+    /// transaction. When two reads differ, another read is taken and compared with the
+    /// previous one, up to `retries` times.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(inputs)` (bit `n` = pin index `n`) when two consecutive reads match,
+    /// `Error::InputsUnstable` if they never did within `retries` extra reads, or an `Error`
+    /// if an I2C bus operation fails.
+    pub async fn read_inputs_verified(&mut self, retries: u8) -> Result<u32, Error<I2C::Error>> {
+        let mut previous = self.read_input_word().await?;
+        for _ in 0..=retries {
+            let current = self.read_input_word().await?;
+            if current == previous {
+                return Ok(current);
+            }
+            previous = current;
+        }
+        Err(Error::InputsUnstable)
+    }
+
+    /// Reads `pin` until two consecutive samples, `interval_us` microseconds apart, agree.
+    ///
+    /// A cheap guard against glitches and metastability on signals that change
+    /// asynchronously to the read, at the cost of at least one extra register read. The
+    /// interval is waited with the driver's delay source (see [`Tca6424::with_delay`]). When
+    /// two samples differ, another is taken and compared with the previous one, up to
+    /// `retries` times.
+    ///
+    /// This method is `async` when the `async` feature is enabled, and synchronous otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Returns the agreed `PinState`, `Error::InputsUnstable` if no two consecutive samples
+    /// agreed, or an `Error` if an I2C bus operation fails.
+    pub async fn get_pin_input_stable(
+        &mut self,
+        pin: Pin,
+        interval_us: u32,
+        retries: u8,
+    ) -> Result<PinState, Error<I2C::Error>> {
+        let register = Register::of(RegisterKind::Input, pin.port());
+        let mut previous = self.read_register(register).await? & (1 << pin.bit());
+        for _ in 0..=retries {
+            self.delay.delay_us(interval_us).await;
+            let current = self.read_register(register).await? & (1 << pin.bit());
+            if current == previous {
+                return Ok(PinState::from(current != 0));
+            }
+            previous = current;
+        }
+        Err(Error::InputsUnstable)
+    }
+
+    /// Reads the three Input Port registers as one 24-bit word.
+    async fn read_input_word(&mut self) -> Result<u32, Error<I2C::Error>> {
+        let mut values = [0u8; 4];
+        self.read_registers_ai(Register::InputPort0, &mut values[..3])
+            .await?;
+        Ok(u32::from_le_bytes(values))
+    }
+}
@@ -0,0 +1,61 @@
+//! Write-only operation for boards whose bus cannot carry reads reliably.
+
+use core::fmt::Debug;
+
+use crate::errors::Error;
+use crate::registers;
+use crate::{Chip, Port, RegisterKind, Tca64xx, plan};
+
+impl<I2C, D, C: Chip> Tca64xx<I2C, D, C> {
+    /// Enables or disables write-only mode.
+    ///
+    /// Some boards can drive the expander but not read it back reliably, for example when
+    /// SDA passes through a unidirectional level shifter or an isolator. In write-only mode
+    /// the driver never issues a `write_read` transfer: reads of Output, Polarity Inversion,
+    /// Configuration and Interrupt Mask registers are answered from the register cache, so
+    /// read-modify-write methods keep working once the registers they touch are known, and
+    /// every other read fails with `Error::WriteOnly`. Input registers are never cached, so
+    /// input reads always fail. [`Tca64xx::ping`] skips its read fallback.
+    ///
+    /// Fill the cache with whole-port or whole-family writes (such as a board configuration)
+    /// before using pin-level methods, or call [`Tca64xx::assume_power_on_state`] right after
+    /// the device was reset.
+    pub fn set_write_only(&mut self, write_only: bool) {
+        self.write_only = write_only;
+    }
+
+    /// Returns `true` if write-only mode is enabled.
+    pub fn is_write_only(&self) -> bool {
+        self.write_only
+    }
+
+    /// Records the power-on values of every writable register in the register cache
+    /// (Output, Configuration and Interrupt Mask `0xFF`, Polarity Inversion `0x00`), without
+    /// any bus transfer.
+    ///
+    /// Only correct right after a power-on or hardware reset of the device; it lets
+    /// write-only mode start from a known state instead of requiring full writes first.
+    pub fn assume_power_on_state(&mut self) {
+        for kind in RegisterKind::ALL {
+            for port in Port::ALL {
+                self.cache.set(kind, port, plan::power_on_value(kind));
+            }
+        }
+    }
+
+    /// Answers a read with `command` from the register cache.
+    pub(crate) fn read_cached<E: Debug>(
+        &self,
+        command: u8,
+        buffer: &mut [u8],
+    ) -> Result<(), Error<E>> {
+        let registers = registers::command_registers(command, buffer.len());
+        for (byte, register) in buffer.iter_mut().zip(registers) {
+            *byte = self
+                .cache
+                .get(register.kind(), register.port())
+                .ok_or(Error::WriteOnly)?;
+        }
+        Ok(())
+    }
+}
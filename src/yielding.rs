@@ -0,0 +1,79 @@
+//! Co-operative yielding between register transfers.
+//!
+//! Batch operations (auto-increment families, configuration commits, snapshots, restores)
+//! issue several transactions back to back. In a superloop that can hold off other
+//! time-critical work for the whole batch; with yielding enabled the driver hands control
+//! back before every transfer: to a user function in the blocking driver, to the executor
+//! in the async one.
+
+use crate::Tca6424;
+
+impl<I2C, D> Tca6424<I2C, D> {
+    /// Calls `yield_fn` before every register transfer, so a batch runs it between its
+    /// transactions. `None` (the default) disables the hook.
+    ///
+    /// The function runs on the caller's stack, while the driver is borrowed; it must not
+    /// use this driver. Keep it short, such as polling a UART or feeding a watchdog.
+    ///
+    /// Only available without the `async` feature; async drivers yield to the executor with
+    /// `Tca6424::set_yield_between_transfers`.
+    #[cfg(not(feature = "async"))]
+    pub fn set_yield_fn(&mut self, yield_fn: Option<fn()>) {
+        self.yield_fn = yield_fn;
+    }
+
+    /// Returns the function set with [`Tca6424::set_yield_fn`], if any.
+    #[cfg(not(feature = "async"))]
+    pub fn yield_fn(&self) -> Option<fn()> {
+        self.yield_fn
+    }
+
+    /// Yields to the executor (see [`yield_now`]) before every register transfer when
+    /// `enabled`, so other tasks run between the transactions of a batch. Disabled by
+    /// default.
+    ///
+    /// Only available with the `async` feature.
+    #[cfg(feature = "async")]
+    pub fn set_yield_between_transfers(&mut self, enabled: bool) {
+        self.yield_between_transfers = enabled;
+    }
+
+    /// Returns `true` if the driver yields before every register transfer.
+    #[cfg(feature = "async")]
+    pub fn yields_between_transfers(&self) -> bool {
+        self.yield_between_transfers
+    }
+}
+
+/// Returns control to the executor once, letting every other ready task run before the
+/// caller continues.
+///
+/// Works with any executor: the future wakes itself and completes on its second poll.
+///
+/// Only available with the `async` feature.
+#[cfg(feature = "async")]
+pub async fn yield_now() {
+    YieldNow { yielded: false }.await
+}
+
+#[cfg(feature = "async")]
+struct YieldNow {
+    yielded: bool,
+}
+
+#[cfg(feature = "async")]
+impl core::future::Future for YieldNow {
+    type Output = ();
+
+    fn poll(
+        mut self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<()> {
+        if self.yielded {
+            return core::task::Poll::Ready(());
+        }
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        core::task::Poll::Pending
+    }
+}
@@ -0,0 +1,57 @@
+use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::errors::Error;
+use tca6424::{Tca6424, is_reserved_address};
+
+#[test]
+fn test_new_checked_rejects_reserved_addresses() {
+    for address in [0x00, 0x01, 0x07, 0x78, 0x7F, 0x80, 0xFF] {
+        assert!(is_reserved_address(address));
+        let mut i2c_mock = I2cMock::new(&[]);
+        assert!(matches!(
+            Tca6424::new_checked(&mut i2c_mock, address),
+            Err(Error::ReservedAddress(a)) if a == address
+        ));
+        i2c_mock.done();
+    }
+
+    let mut i2c_mock = I2cMock::new(&[]);
+    let tca = Tca6424::new_checked(&mut i2c_mock, 0x23).unwrap();
+    assert_eq!(tca.address(), 0x23);
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_ping_sync() {
+    let address = 0x22;
+    let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+    let expectations = [
+        // Acknowledged zero-length write
+        I2cTransaction::write(address, vec![]),
+        // Nothing at the address
+        I2cTransaction::write(address, vec![]).with_error(nack),
+        // The peripheral cannot send an empty write: fall back to a Configuration read
+        I2cTransaction::write(address, vec![]).with_error(ErrorKind::Other),
+        I2cTransaction::write_read(address, vec![0x0C], vec![0xFF]),
+        I2cTransaction::write(address, vec![]).with_error(ErrorKind::Other),
+        I2cTransaction::write_read(address, vec![0x0C], vec![0x00]).with_error(nack),
+        I2cTransaction::write(address, vec![]).with_error(ErrorKind::Other),
+        I2cTransaction::write_read(address, vec![0x0C], vec![0x00])
+            .with_error(ErrorKind::ArbitrationLoss),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    assert!(tca.ping().unwrap());
+    assert!(!tca.ping().unwrap());
+    assert!(tca.ping().unwrap());
+    assert!(!tca.ping().unwrap());
+    assert_eq!(
+        tca.ping().unwrap_err(),
+        Error::I2c(ErrorKind::ArbitrationLoss)
+    );
+
+    i2c_mock.done();
+}
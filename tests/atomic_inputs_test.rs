@@ -0,0 +1,47 @@
+#![cfg(feature = "portable-atomic")]
+
+use embedded_hal::i2c::ErrorKind;
+use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+use embedded_hal_mock::eh1::i2c::Transaction as I2cTransaction;
+use tca6424::{AtomicInputs, InterruptThrottle, Pin, PinState};
+
+static INPUTS: AtomicInputs = AtomicInputs::new();
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_atomic_inputs_refresh_sync() {
+    let address = 0x22;
+
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x80], vec![0x01, 0x00, 0x80]),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x00, 0x10, 0x00]),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x00, 0x00, 0x00])
+            .with_error(ErrorKind::Other),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    // "Reader" side sees nothing before the first refresh
+    assert_eq!(INPUTS.cached_input(Pin::P00), None);
+
+    assert_eq!(INPUTS.refresh(&mut tca).unwrap(), 0x80_0001);
+    assert_eq!(INPUTS.cached_input(Pin::P00), Some(PinState::High));
+    assert_eq!(INPUTS.cached_input(Pin::P27), Some(PinState::High));
+    assert_eq!(INPUTS.cached_input(Pin::P14), Some(PinState::Low));
+
+    let mut throttle = InterruptThrottle::new(10);
+    assert!(!INPUTS.service(&mut tca, &mut throttle, 0).unwrap());
+    throttle.notify();
+    assert!(INPUTS.service(&mut tca, &mut throttle, 0).unwrap());
+    assert_eq!(INPUTS.image(), Some(0x00_1000));
+
+    // A failed refresh empties the image
+    assert!(INPUTS.refresh(&mut tca).is_err());
+    assert_eq!(INPUTS.image(), None);
+
+    INPUTS.store(0xFFFF_FFFF);
+    assert_eq!(INPUTS.image(), Some(0xFF_FFFF));
+
+    i2c_mock.done();
+}
@@ -0,0 +1,130 @@
+#![cfg(feature = "portable-atomic")]
+
+use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+use embedded_hal_mock::eh1::i2c::Transaction as I2cTransaction;
+use tca6424::{AtomicOutputs, Pin, PinState};
+
+static OUTPUTS: AtomicOutputs = AtomicOutputs::new(0);
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_atomic_outputs_flush_sync() {
+    let address = 0x22;
+
+    let expectations = [
+        // First flush writes every port
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x84]),
+        I2cTransaction::write(address, vec![0x01, 0x00, 0x80]),
+        I2cTransaction::transaction_end(address),
+        // Only Port1 changed
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x05]),
+        I2cTransaction::write(address, vec![0x10]),
+        I2cTransaction::transaction_end(address),
+        // Port0 and Port2 changed: one AI write spanning Port0..Port2
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x84]),
+        I2cTransaction::write(address, vec![0x00, 0x10, 0x00]),
+        I2cTransaction::transaction_end(address),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    // "ISR" side
+    OUTPUTS.set_high(Pin::P00);
+    OUTPUTS.set(Pin::P27, PinState::High);
+    assert!(OUTPUTS.is_dirty());
+    assert!(OUTPUTS.flush(&mut tca).unwrap());
+    assert!(!OUTPUTS.is_dirty());
+    assert!(!OUTPUTS.flush(&mut tca).unwrap());
+
+    OUTPUTS.set_high(Pin::P14);
+    assert!(OUTPUTS.flush(&mut tca).unwrap());
+
+    OUTPUTS.toggle(Pin::P00);
+    OUTPUTS.set_low(Pin::P27);
+    assert!(OUTPUTS.flush(&mut tca).unwrap());
+    assert_eq!(OUTPUTS.image(), 0x00_1000);
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_atomic_outputs_flush_coalesced_sync() {
+    use tca6424::{CoalesceWindow, Coalescer};
+
+    let address = 0x22;
+    let expectations = [
+        // Three changes of Port 0 within one window: a single write
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x84]),
+        I2cTransaction::write(address, vec![0x02, 0x00, 0x00]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0x03]),
+        I2cTransaction::transaction_end(address),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    let outputs = AtomicOutputs::new(0);
+
+    // The device state is unknown, so the image starts dirty
+    let mut coalescer = Coalescer::new(CoalesceWindow::Millis(10));
+    outputs.set_high(Pin::P00);
+    // The window opens now; timestamps wrap around
+    assert!(
+        !outputs
+            .flush_coalesced(&mut tca, &mut coalescer, u32::MAX - 4)
+            .unwrap()
+    );
+    assert!(coalescer.is_open());
+    outputs.toggle(Pin::P00);
+    outputs.set_high(Pin::P01);
+    assert!(
+        !outputs
+            .flush_coalesced(&mut tca, &mut coalescer, 4)
+            .unwrap()
+    );
+    assert!(
+        outputs
+            .flush_coalesced(&mut tca, &mut coalescer, 5)
+            .unwrap()
+    );
+    assert!(!coalescer.is_open());
+    assert!(
+        !outputs
+            .flush_coalesced(&mut tca, &mut coalescer, 20)
+            .unwrap()
+    );
+    assert!(!coalescer.is_open(), "a clean image opens no window");
+
+    // Ticks(2): the image is written on the third call that sees it dirty
+    let mut coalescer = Coalescer::new(CoalesceWindow::Ticks(2));
+    outputs.set_high(Pin::P00);
+    assert!(
+        !outputs
+            .flush_coalesced(&mut tca, &mut coalescer, 0)
+            .unwrap()
+    );
+    assert!(
+        !outputs
+            .flush_coalesced(&mut tca, &mut coalescer, 0)
+            .unwrap()
+    );
+    assert!(
+        outputs
+            .flush_coalesced(&mut tca, &mut coalescer, 0)
+            .unwrap()
+    );
+    assert!(
+        !outputs
+            .flush_coalesced(&mut tca, &mut coalescer, 0)
+            .unwrap()
+    );
+
+    i2c_mock.done();
+}
@@ -0,0 +1,296 @@
+#![cfg(feature = "bank")]
+
+use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+use embedded_hal_mock::eh1::i2c::Transaction as I2cTransaction;
+#[cfg(feature = "hot-plug")]
+use tca6424::LinkState;
+use tca6424::{BankPin, ExpanderBank, Pin, Port, RegisterKind, ScanScheduler};
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_apply_all_sync() {
+    let expectations_a = [
+        I2cTransaction::transaction_start(0x22),
+        I2cTransaction::write(0x22, vec![0x84]),
+        I2cTransaction::write(0x22, vec![0x01, 0x02, 0x03]),
+        I2cTransaction::transaction_end(0x22),
+    ];
+    let expectations_b = [
+        I2cTransaction::transaction_start(0x23),
+        I2cTransaction::write(0x23, vec![0x84]),
+        I2cTransaction::write(0x23, vec![0xA1, 0xA2, 0xA3]),
+        I2cTransaction::transaction_end(0x23),
+    ];
+
+    let mut i2c_a = I2cMock::new(&expectations_a);
+    let mut i2c_b = I2cMock::new(&expectations_b);
+    let mut bank = ExpanderBank::new([
+        tca6424::Tca6424::new(&mut i2c_a, 0x22).unwrap(),
+        tca6424::Tca6424::new(&mut i2c_b, 0x23).unwrap(),
+    ]);
+
+    bank.apply_all(&[[0x01, 0x02, 0x03], [0xA1, 0xA2, 0xA3]])
+        .unwrap();
+    assert_eq!(
+        bank.device(1).unwrap().cache().family(RegisterKind::Output),
+        Some([0xA1, 0xA2, 0xA3])
+    );
+    assert!(bank.device(2).is_none());
+
+    i2c_a.done();
+    i2c_b.done();
+}
+
+#[test]
+fn test_scan_scheduler_config() {
+    let scheduler: ScanScheduler<4> = ScanScheduler::new(0);
+    assert_eq!(scheduler.budget(), 1);
+    assert_eq!(scheduler.sweep_cycles(), 4);
+
+    let mut scheduler: ScanScheduler<4> = ScanScheduler::new(2).with_ports(3, 0);
+    assert_eq!(scheduler.sweep_cycles(), 2);
+    scheduler.set_budget(3);
+    assert_eq!(scheduler.sweep_cycles(), 1);
+    assert_eq!(scheduler.inputs(0), None);
+    assert_eq!(scheduler.take_changed(), 0);
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_scan_interleaves_devices_within_budget_sync() {
+    use embedded_hal::i2c::ErrorKind;
+
+    let expectations_a = [
+        I2cTransaction::write_read(0x22, vec![0x80], vec![0x01, 0x02, 0x03]),
+        I2cTransaction::write_read(0x22, vec![0x80], vec![0x01, 0x02, 0x03]),
+        I2cTransaction::write_read(0x22, vec![0x80], vec![0x01, 0x02, 0x03]),
+    ];
+    // Only ports 1 and 2 are polled
+    let expectations_b = [
+        I2cTransaction::write_read(0x23, vec![0x81], vec![0x10, 0x20]),
+        I2cTransaction::write_read(0x23, vec![0x81], vec![0x10, 0x21]),
+    ];
+    // Ports 0 and 2 are polled: the read spans port 1, which is ignored
+    let expectations_c = [
+        I2cTransaction::write_read(0x24, vec![0x80], vec![0xAA, 0xFF, 0x55])
+            .with_error(ErrorKind::Other),
+        I2cTransaction::write_read(0x24, vec![0x80], vec![0xAA, 0xFF, 0x55]),
+    ];
+    let mut i2c_a = I2cMock::new(&expectations_a);
+    let mut i2c_b = I2cMock::new(&expectations_b);
+    let mut i2c_c = I2cMock::new(&expectations_c);
+    let mut bank = ExpanderBank::new([
+        tca6424::Tca6424::new(&mut i2c_a, 0x22).unwrap(),
+        tca6424::Tca6424::new(&mut i2c_b, 0x23).unwrap(),
+        tca6424::Tca6424::new(&mut i2c_c, 0x24).unwrap(),
+    ]);
+    let mut scheduler = ScanScheduler::new(2)
+        .with_ports(1, 0b110)
+        .with_ports(2, 0b101);
+    assert_eq!(scheduler.sweep_cycles(), 2);
+
+    // Cycle 1: devices 0 and 1
+    assert_eq!(bank.scan(&mut scheduler).unwrap(), 0b011);
+    assert_eq!(scheduler.inputs(0), Some(0x03_0201));
+    assert_eq!(scheduler.inputs(1), Some(0x20_1000));
+    // Cycle 2: device 2 fails; the cycle stops there
+    assert!(bank.scan(&mut scheduler).is_err());
+    // Cycle 3 moves on past it: devices 0 and 1; cycle 4 retries device 2, then device 0
+    assert_eq!(bank.scan(&mut scheduler).unwrap(), 0b010);
+    assert_eq!(scheduler.inputs(1), Some(0x21_1000));
+    assert_eq!(bank.scan(&mut scheduler).unwrap(), 0b100);
+    assert_eq!(scheduler.inputs(2), Some(0x55_00AA));
+    assert_eq!(scheduler.take_changed(), 0b111);
+    assert_eq!(scheduler.take_changed(), 0);
+
+    i2c_a.done();
+    i2c_b.done();
+    i2c_c.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_all_inputs_isolates_failing_device_sync() {
+    use embedded_hal::i2c::ErrorKind;
+
+    let expectations_a = [
+        I2cTransaction::write_read(0x22, vec![0x80], vec![0x01, 0x00, 0x00]),
+        I2cTransaction::write_read(0x22, vec![0x80], vec![0x03, 0x00, 0x00]),
+    ];
+    let expectations_b = [
+        I2cTransaction::write_read(0x23, vec![0x80], vec![0x00, 0x10, 0x00]),
+        I2cTransaction::write_read(0x23, vec![0x80], vec![0xFF, 0xFF, 0xFF])
+            .with_error(ErrorKind::Other),
+    ];
+    let mut i2c_a = I2cMock::new(&expectations_a);
+    let mut i2c_b = I2cMock::new(&expectations_b);
+    let mut bank = ExpanderBank::new([
+        tca6424::Tca6424::new(&mut i2c_a, 0x22).unwrap(),
+        tca6424::Tca6424::new(&mut i2c_b, 0x23).unwrap(),
+    ]);
+    assert_eq!(bank.inputs(0), None);
+    bank.watcher_mut(0).unwrap().subscribe_port(Port::Port0);
+    bank.watcher_mut(1).unwrap().subscribe_port(Port::Port1);
+    assert!(bank.watcher_mut(2).is_none());
+
+    assert_eq!(bank.all_inputs(), [0x00_0001, 0x00_1000]);
+    assert_eq!(bank.failed_devices(), 0);
+    // The first image only sets each watcher's baseline
+    assert!(bank.changes(0).is_empty());
+
+    // Device 1 fails: device 0 is read and reports its change, device 1 keeps its image
+    assert_eq!(bank.all_inputs(), [0x00_0003, 0x00_1000]);
+    assert_eq!(bank.failed_devices(), 0b10);
+    assert_eq!(bank.changes(0).changed_mask(), 1 << Pin::P01 as u8);
+    assert!(bank.changes(1).is_empty());
+    assert_eq!(bank.inputs(1), Some(0x00_1000));
+    #[cfg(feature = "hot-plug")]
+    assert_eq!(bank.link_states(), [LinkState::Online; 2]);
+    assert!(bank.changes(5).is_empty());
+
+    i2c_a.done();
+    i2c_b.done();
+}
+
+#[test]
+fn test_bank_pin_flat_index() {
+    assert_eq!(BankPin::new(0, Pin::P00).flat_index(), 0);
+    assert_eq!(BankPin::new(2, Pin::P27).flat_index(), 71);
+    assert_eq!(BankPin::from_flat_index(24), BankPin::new(1, Pin::P00));
+    for index in 0..96 {
+        assert_eq!(BankPin::from_flat_index(index).flat_index(), index);
+    }
+    assert_eq!(BankPin::new(3, Pin::P15).to_string(), "3:P15");
+
+    let pins = [
+        BankPin::new(0, Pin::P01),
+        BankPin::new(1, Pin::P20),
+        BankPin::new(1, Pin::P21),
+        BankPin::new(4, Pin::P00),
+    ];
+    assert_eq!(BankPin::device_masks::<2>(&pins), [0x00_0002, 0x03_0000]);
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_bank_pin_access_sync() {
+    use tca6424::PinState;
+    use tca6424::errors::Error;
+
+    let expectations_a = [
+        // Batch write: read-modify-write of Output Port 0
+        I2cTransaction::write_read(0x22, vec![0x04], vec![0x00]),
+        I2cTransaction::transaction_start(0x22),
+        I2cTransaction::write(0x22, vec![0x04]),
+        I2cTransaction::write(0x22, vec![0x01]),
+        I2cTransaction::transaction_end(0x22),
+        // Batch read of Input Port 0
+        I2cTransaction::write_read(0x22, vec![0x00], vec![0x02]),
+    ];
+    let expectations_b = [
+        // Batch write: both pins share one write of Output Port 1
+        I2cTransaction::write_read(0x23, vec![0x05], vec![0xF0]),
+        I2cTransaction::transaction_start(0x23),
+        I2cTransaction::write(0x23, vec![0x05]),
+        I2cTransaction::write(0x23, vec![0xF8]),
+        I2cTransaction::transaction_end(0x23),
+        // Single pin: read-modify-write of Output Port 1
+        I2cTransaction::write_read(0x23, vec![0x05], vec![0xF8]),
+        I2cTransaction::transaction_start(0x23),
+        I2cTransaction::write(0x23, vec![0x05]),
+        I2cTransaction::write(0x23, vec![0xFC]),
+        I2cTransaction::transaction_end(0x23),
+        // Batch read of Input Port 2
+        I2cTransaction::write_read(0x23, vec![0x02], vec![0x01]),
+    ];
+    let mut i2c_a = I2cMock::new(&expectations_a);
+    let mut i2c_b = I2cMock::new(&expectations_b);
+    let mut bank = ExpanderBank::new([
+        tca6424::Tca6424::new(&mut i2c_a, 0x22).unwrap(),
+        tca6424::Tca6424::new(&mut i2c_b, 0x23).unwrap(),
+    ]);
+
+    bank.set_pin_outputs(&[
+        (BankPin::new(0, Pin::P00), PinState::High),
+        (BankPin::new(1, Pin::P12), PinState::Low),
+        (BankPin::new(1, Pin::P13), PinState::High),
+    ])
+    .unwrap();
+    bank.set_pin_output(BankPin::from_flat_index(34), true)
+        .unwrap();
+    let levels = bank
+        .get_pin_inputs(&[
+            BankPin::new(0, Pin::P01),
+            BankPin::new(1, Pin::P20),
+            BankPin::new(1, Pin::P21),
+        ])
+        .unwrap();
+    assert_eq!(levels.values(), [0x00_0002, 0x01_0000]);
+    assert_eq!(levels.pin(BankPin::new(1, Pin::P20)), Some(PinState::High));
+    assert!(levels.is_complete());
+
+    // A pin beyond the bank fails before any transfer
+    assert_eq!(
+        bank.set_pin_outputs(&[
+            (BankPin::new(0, Pin::P00), PinState::Low),
+            (BankPin::new(2, Pin::P00), PinState::High),
+        ]),
+        Err(Error::InvalidRegisterOrPin)
+    );
+    assert_eq!(
+        bank.get_pin_input_state(BankPin::from_flat_index(48)),
+        Err(Error::InvalidRegisterOrPin)
+    );
+
+    i2c_a.done();
+    i2c_b.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_optional_device_degrades_reads_sync() {
+    use embedded_hal::i2c::ErrorKind;
+    use tca6424::PinState;
+
+    let expectations_a = [
+        I2cTransaction::write_read(0x22, vec![0x80], vec![0x01, 0x02, 0x03]),
+        I2cTransaction::write_read(0x22, vec![0x01], vec![0x80]),
+        I2cTransaction::write_read(0x22, vec![0x80], vec![0x00, 0x00, 0x00])
+            .with_error(ErrorKind::Other),
+    ];
+    let expectations_b = [
+        I2cTransaction::write_read(0x23, vec![0x80], vec![0x00, 0x00, 0x00])
+            .with_error(ErrorKind::Other),
+        I2cTransaction::write_read(0x23, vec![0x00], vec![0x00]).with_error(ErrorKind::Other),
+    ];
+    let mut i2c_a = I2cMock::new(&expectations_a);
+    let mut i2c_b = I2cMock::new(&expectations_b);
+    let mut bank = ExpanderBank::new([
+        tca6424::Tca6424::new(&mut i2c_a, 0x22).unwrap(),
+        tca6424::Tca6424::new(&mut i2c_b, 0x23).unwrap(),
+    ]);
+    bank.set_optional(1, true);
+    bank.set_optional(7, true);
+    assert_eq!(bank.optional_devices(), 0b10);
+
+    // The expansion card is missing: device 0 is still read
+    let inputs = bank.read_inputs().unwrap();
+    assert_eq!(inputs.device(0), Some(0x03_0201));
+    assert_eq!(inputs.device(1), None);
+    assert_eq!(inputs.valid_mask(), 0b01);
+    assert_eq!(inputs.missing_mask(), 0b10);
+    assert!(!inputs.is_complete());
+    assert_eq!(inputs.values(), [0x03_0201, 0]);
+
+    let inputs = bank
+        .get_pin_inputs(&[BankPin::new(0, Pin::P17), BankPin::new(1, Pin::P00)])
+        .unwrap();
+    assert_eq!(inputs.pin(BankPin::new(0, Pin::P17)), Some(PinState::High));
+    assert_eq!(inputs.pin(BankPin::new(1, Pin::P00)), None);
+
+    // A failing required device fails the read
+    assert!(bank.read_inputs().is_err());
+
+    i2c_a.done();
+    i2c_b.done();
+}
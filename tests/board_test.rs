@@ -0,0 +1,217 @@
+#![cfg(feature = "helpers")]
+
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::Pin;
+use tca6424::errors::Error;
+
+tca6424::board! {
+    /// Front panel of the test board.
+    pub struct FrontPanel {
+        pub status_led: output(P00, active_high, on),
+        pub relay_main: output(P12, active_low, off),
+        pub start_button: input(P20, active_low),
+    }
+}
+
+tca6424::board! {
+    /// Door controller with two interrupt-driven inputs.
+    pub struct DoorController {
+        pub lock: output(P03, active_high, off),
+        pub door_switch: event_input(P10, active_low),
+        pub service_key: input(P11, active_high),
+        pub alarm_loop: event_input(P27, active_low),
+    }
+}
+
+#[test]
+fn test_board_config() {
+    let config = FrontPanel::CONFIG;
+    assert_eq!(config.pins(), tca6424::pins!(P00, P12, P20));
+    assert_eq!(config.outputs(), tca6424::pins!(P00, P12));
+    // LED on (high), relay off (active-low, so high)
+    assert_eq!(config.levels(), tca6424::pins!(P00, P12));
+
+    let panel = FrontPanel::new();
+    assert_eq!(panel.start_button.pin(), Pin::P20);
+    assert!(panel.relay_main.is_active_low());
+}
+
+#[test]
+fn test_board_interrupt_mask() {
+    let config = DoorController::CONFIG;
+    assert_eq!(config.events(), tca6424::pins!(P10, P27));
+    assert_eq!(config.pins(), tca6424::pins!(P03, P10, P11, P27));
+    assert_eq!(config.interrupt_mask(), 0x7F_FEFF);
+    // A board without event inputs masks every pin
+    assert_eq!(FrontPanel::CONFIG.interrupt_mask(), 0xFF_FFFF);
+
+    let door = DoorController::new();
+    assert_eq!(door.door_switch.pin(), Pin::P10);
+    assert!(door.door_switch.is_active_low());
+}
+
+#[cfg(all(feature = "interrupt-mask", not(feature = "async")))]
+#[test]
+fn test_apply_board_interrupt_mask_sync() {
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x90]),
+        I2cTransaction::write(address, vec![0xFF, 0xFE, 0x7F]),
+        I2cTransaction::transaction_end(address),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    tca.apply_board_interrupt_mask(&DoorController::CONFIG)
+        .unwrap();
+    assert_eq!(tca.claimed_mask(), 0);
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_board_init_and_use_sync() {
+    let address = 0x22;
+    let expectations = [
+        // Outputs first, then directions, each as one auto-increment write
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x84]),
+        I2cTransaction::write(address, vec![0x01, 0x04, 0x00]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x8C]),
+        I2cTransaction::write(address, vec![0xFE, 0xFB, 0xFF]),
+        I2cTransaction::transaction_end(address),
+        // Button pressed (P20 low) -> activate relay (P12 low)
+        I2cTransaction::write_read(address, vec![0x02], vec![0xFE]),
+        I2cTransaction::write_read(address, vec![0x05], vec![0x04]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x05]),
+        I2cTransaction::write(address, vec![0x00]),
+        I2cTransaction::transaction_end(address),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    let panel = FrontPanel::init(&mut tca).unwrap();
+    assert!(tca.is_claimed(Pin::P12));
+    assert!(matches!(
+        FrontPanel::init(&mut tca),
+        Err(Error::PinAlreadyClaimed(Pin::P00))
+    ));
+
+    if panel.start_button.is_active(&mut tca).unwrap() {
+        panel.relay_main.set_active(&mut tca, true).unwrap();
+    }
+
+    panel.release(&mut tca);
+    assert_eq!(tca.claimed_mask(), 0);
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_board_init_with_report_sync() {
+    use tca6424::{Port, RegisterKind};
+
+    let address = 0x22;
+    let expectations = [
+        // Previous Output and Configuration values, then the two writes
+        I2cTransaction::write_read(address, vec![0x84], vec![0xFF, 0x04, 0xFF]),
+        I2cTransaction::write_read(address, vec![0x8C], vec![0xFF, 0xFF, 0xFF]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x84]),
+        I2cTransaction::write(address, vec![0x01, 0x04, 0x00]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x8C]),
+        I2cTransaction::write(address, vec![0xFE, 0xFB, 0xFF]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x84]),
+        I2cTransaction::write(address, vec![0x01, 0x04, 0x00]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x8C]),
+        I2cTransaction::write(address, vec![0xFE, 0xFB, 0xFF]),
+        I2cTransaction::transaction_end(address),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    let (panel, report) = FrontPanel::init_with_report(&mut tca).unwrap();
+    assert_eq!(report.transactions(), 4);
+    assert_eq!(report.writes().len(), 6);
+    let output1 = report.get(RegisterKind::Output, Port::Port1).unwrap();
+    assert_eq!((output1.before, output1.after), (Some(0x04), 0x04));
+    assert_eq!(output1.changed(), Some(false));
+    let config0 = report
+        .get(RegisterKind::Configuration, Port::Port0)
+        .unwrap();
+    assert_eq!(config0.changed(), Some(true));
+    assert_eq!(
+        config0.to_string(),
+        "Configuration Port 0: 11111111 -> 11111110"
+    );
+    panel.release(&mut tca);
+
+    // Without reads, previous values come from the cache
+    let report = tca
+        .apply_board_config_reported(&FrontPanel::CONFIG, false)
+        .unwrap();
+    assert_eq!(report.transactions(), 2);
+    assert!(
+        report
+            .writes()
+            .iter()
+            .all(|write| write.changed() == Some(false))
+    );
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_board_reconcile_sync() {
+    use tca6424::{Port, RegisterKind};
+
+    let address = 0x22;
+    let expectations = [
+        // Warm restart: only Output Port 0 and Configuration Port 1 differ
+        I2cTransaction::write_read(address, vec![0x84], vec![0x00, 0x04, 0x00]),
+        I2cTransaction::write_read(address, vec![0x8C], vec![0xFE, 0xFF, 0xFF]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0x01]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x0D]),
+        I2cTransaction::write(address, vec![0xFB]),
+        I2cTransaction::transaction_end(address),
+        // Second run: nothing to write
+        I2cTransaction::write_read(address, vec![0x84], vec![0x01, 0x04, 0x00]),
+        I2cTransaction::write_read(address, vec![0x8C], vec![0xFE, 0xFB, 0xFF]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    let (panel, report) = FrontPanel::reconcile(&mut tca).unwrap();
+    assert_eq!(report.transactions(), 4);
+    assert_eq!(report.writes().len(), 2);
+    let write = report
+        .get(RegisterKind::Configuration, Port::Port1)
+        .unwrap();
+    assert_eq!((write.before, write.after), (Some(0xFF), 0xFB));
+    assert!(report.get(RegisterKind::Output, Port::Port1).is_none());
+    assert!(tca.is_claimed(Pin::P20));
+    panel.release(&mut tca);
+
+    let report = tca.reconcile(&FrontPanel::CONFIG).unwrap();
+    assert_eq!(report.transactions(), 2);
+    assert!(report.writes().is_empty());
+
+    i2c_mock.done();
+}
@@ -0,0 +1,235 @@
+#![cfg(all(
+    feature = "polarity",
+    feature = "interrupt-mask",
+    feature = "auto-increment",
+    feature = "diagnostics",
+    not(feature = "async")
+))]
+
+use std::cell::Cell;
+
+use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, Operation, SevenBitAddress};
+use tca6424::{CacheMode, InputWatcher, Pin, PinState, Port, RegisterKind, Tca6424, TransferBound};
+
+/// A bus that answers every read with zeros and counts the transactions.
+struct CountingBus<'a> {
+    transactions: &'a Cell<u32>,
+}
+
+impl ErrorType for CountingBus<'_> {
+    type Error = ErrorKind;
+}
+
+impl I2c for CountingBus<'_> {
+    fn transaction(
+        &mut self,
+        _address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        for operation in operations {
+            if let Operation::Read(buffer) = operation {
+                buffer.fill(0);
+            }
+        }
+        self.transactions.set(self.transactions.get() + 1);
+        Ok(())
+    }
+}
+
+type Driver<'a> = Tca6424<CountingBus<'a>>;
+
+/// Runs `call` on a fresh driver in `mode` and checks it issues exactly the transactions
+/// of `bound`.
+fn check(name: &str, bound: TransferBound, call: impl Fn(&mut Driver<'_>)) {
+    for mode in [
+        CacheMode::Uncached,
+        CacheMode::CachedOutputs,
+        CacheMode::WriteOnly,
+    ] {
+        let transactions = Cell::new(0);
+        let mut tca = Tca6424::new(
+            CountingBus {
+                transactions: &transactions,
+            },
+            0x22,
+        )
+        .unwrap();
+        match mode {
+            CacheMode::Uncached => {}
+            CacheMode::CachedOutputs => {
+                tca.set_cached_output_reads(true);
+                tca.refresh_output_cache().unwrap();
+            }
+            CacheMode::WriteOnly => {
+                tca.set_write_only(true);
+                tca.assume_power_on_state();
+            }
+        }
+        assert_eq!(tca.cache_mode(), mode);
+        transactions.set(0);
+        call(&mut tca);
+        assert_eq!(
+            transactions.get(),
+            u32::from(bound.max(mode)),
+            "{name} in {mode:?}"
+        );
+    }
+}
+
+/// Generates one test per method, checking its transactions against its bound.
+macro_rules! bound_tests {
+    ($($test:ident, $bound:ident: |$tca:ident| $call:expr;)*) => {
+        $(
+            #[test]
+            fn $test() {
+                check(stringify!($bound), TransferBound::$bound, |$tca| {
+                    let _ = $call;
+                });
+            }
+        )*
+    };
+}
+
+const PIN: Pin = Pin::P13;
+const PORT: Port = Port::Port1;
+const VALUES: [u8; 3] = [0x01, 0x02, 0x03];
+
+bound_tests! {
+    test_set_pin_direction_bound, SET_PIN_DIRECTION:
+        |tca| tca.set_pin_direction(PIN, tca6424::PinDirection::Output);
+    test_get_pin_direction_bound, GET_PIN_DIRECTION: |tca| tca.get_pin_direction(PIN);
+    test_set_pin_output_bound, SET_PIN_OUTPUT: |tca| tca.set_pin_output(PIN, PinState::High);
+    test_get_pin_output_state_bound, GET_PIN_OUTPUT_STATE: |tca| tca.get_pin_output_state(PIN);
+    test_get_pin_input_state_bound, GET_PIN_INPUT_STATE: |tca| tca.get_pin_input_state(PIN);
+    test_set_pin_bound, SET_PIN: |tca| tca.set_pin(PIN, true);
+    test_is_high_bound, IS_HIGH: |tca| tca.is_high(PIN);
+    test_is_low_bound, IS_LOW: |tca| tca.is_low(PIN);
+    test_set_pin_polarity_inversion_bound, SET_PIN_POLARITY_INVERSION:
+        |tca| tca.set_pin_polarity_inversion(PIN, true);
+    test_get_pin_polarity_inversion_bound, GET_PIN_POLARITY_INVERSION:
+        |tca| tca.get_pin_polarity_inversion(PIN);
+    test_get_pin_input_raw_bound, GET_PIN_INPUT_RAW: |tca| tca.get_pin_input_raw(PIN);
+    test_set_port_direction_bound, SET_PORT_DIRECTION: |tca| tca.set_port_direction(PORT, 0x0F);
+    test_get_port_direction_bound, GET_PORT_DIRECTION: |tca| tca.get_port_direction(PORT);
+    test_set_port_output_bound, SET_PORT_OUTPUT: |tca| tca.set_port_output(PORT, 0x0F);
+    test_get_port_output_state_bound, GET_PORT_OUTPUT_STATE: |tca| tca.get_port_output_state(PORT);
+    test_get_port_input_state_bound, GET_PORT_INPUT_STATE: |tca| tca.get_port_input_state(PORT);
+    test_read_port_inputs_bound, READ_PORT_INPUTS: |tca| tca.read_port_inputs(PORT);
+    test_read_port_outputs_bound, READ_PORT_OUTPUTS: |tca| tca.read_port_outputs(PORT);
+    test_read_ports_bound, READ_PORTS: |tca| tca.read_ports();
+    test_write_pins_masked_bound, WRITE_PINS_MASKED: |tca| tca.write_pins_masked(PORT, 0x0F, 0x05);
+    test_write_directions_masked_bound, WRITE_DIRECTIONS_MASKED:
+        |tca| tca.write_directions_masked(PORT, 0x0F, 0x05);
+    test_set_port_polarity_inversion_bound, SET_PORT_POLARITY_INVERSION:
+        |tca| tca.set_port_polarity_inversion(PORT, 0x0F);
+    test_get_port_polarity_inversion_bound, GET_PORT_POLARITY_INVERSION:
+        |tca| tca.get_port_polarity_inversion(PORT);
+    test_set_pin_interrupt_mask_bound, SET_PIN_INTERRUPT_MASK:
+        |tca| tca.set_pin_interrupt_mask(PIN, true);
+    test_get_pin_interrupt_mask_bound, GET_PIN_INTERRUPT_MASK:
+        |tca| tca.get_pin_interrupt_mask(PIN);
+    test_set_port_interrupt_mask_bound, SET_PORT_INTERRUPT_MASK:
+        |tca| tca.set_port_interrupt_mask(PORT, 0x0F);
+    test_get_port_interrupt_mask_bound, GET_PORT_INTERRUPT_MASK:
+        |tca| tca.get_port_interrupt_mask(PORT);
+    test_set_ports_direction_ai_bound, SET_PORTS_DIRECTION_AI:
+        |tca| tca.set_ports_direction_ai(Port::Port0, &VALUES);
+    test_get_ports_direction_ai_bound, GET_PORTS_DIRECTION_AI:
+        |tca| tca.get_ports_direction_ai(Port::Port0, &mut [0; 3]);
+    test_get_ports_direction_bound, GET_PORTS_DIRECTION: |tca| tca.get_ports_direction();
+    test_set_ports_output_ai_bound, SET_PORTS_OUTPUT_AI:
+        |tca| tca.set_ports_output_ai(Port::Port0, &VALUES);
+    test_get_ports_output_state_ai_bound, GET_PORTS_OUTPUT_STATE_AI:
+        |tca| tca.get_ports_output_state_ai(Port::Port0, &mut [0; 3]);
+    test_get_ports_output_state_bound, GET_PORTS_OUTPUT_STATE: |tca| tca.get_ports_output_state();
+    test_get_ports_input_state_ai_bound, GET_PORTS_INPUT_STATE_AI:
+        |tca| tca.get_ports_input_state_ai(Port::Port0, &mut [0; 3]);
+    test_get_ports_input_state_bound, GET_PORTS_INPUT_STATE: |tca| tca.get_ports_input_state();
+    test_set_ports_polarity_inversion_ai_bound, SET_PORTS_POLARITY_INVERSION_AI:
+        |tca| tca.set_ports_polarity_inversion_ai(Port::Port0, &VALUES);
+    test_get_ports_polarity_inversion_ai_bound, GET_PORTS_POLARITY_INVERSION_AI:
+        |tca| tca.get_ports_polarity_inversion_ai(Port::Port0, &mut [0; 3]);
+    test_get_ports_polarity_inversion_bound, GET_PORTS_POLARITY_INVERSION:
+        |tca| tca.get_ports_polarity_inversion();
+    test_set_ports_interrupt_mask_ai_bound, SET_PORTS_INTERRUPT_MASK_AI:
+        |tca| tca.set_ports_interrupt_mask_ai(Port::Port0, &VALUES);
+    test_get_ports_interrupt_mask_ai_bound, GET_PORTS_INTERRUPT_MASK_AI:
+        |tca| tca.get_ports_interrupt_mask_ai(Port::Port0, &mut [0; 3]);
+    test_get_ports_interrupt_mask_bound, GET_PORTS_INTERRUPT_MASK:
+        |tca| tca.get_ports_interrupt_mask();
+    test_set_initial_output_state_bound, SET_INITIAL_OUTPUT_STATE:
+        |tca| tca.set_initial_output_state(0x01, 0x02, 0x03);
+    test_snapshot_bound, SNAPSHOT: |tca| tca.snapshot();
+    test_device_state_bound, DEVICE_STATE: |tca| tca.device_state();
+    test_refresh_output_cache_bound, REFRESH_OUTPUT_CACHE: |tca| tca.refresh_output_cache();
+    test_refresh_polarity_cache_bound, REFRESH_POLARITY_CACHE: |tca| tca.refresh_polarity_cache();
+    test_poll_input_events_bound, POLL_INPUT_EVENTS:
+        |tca| tca.poll_input_events(&mut InputWatcher::new());
+    test_get_interrupt_sources_bound, GET_INTERRUPT_SOURCES: |tca| tca.get_interrupt_sources();
+}
+
+#[test]
+fn test_get_register_bound() {
+    for kind in RegisterKind::ALL {
+        check("GET_REGISTER", TransferBound::GET_REGISTER, |tca| {
+            let _ = tca.get_register(kind, Port::Port2);
+        });
+    }
+}
+
+#[test]
+fn test_set_register_bound() {
+    for kind in RegisterKind::ALL
+        .into_iter()
+        .filter(|&kind| kind != RegisterKind::Input)
+    {
+        check("SET_REGISTER", TransferBound::SET_REGISTER, |tca| {
+            let _ = tca.set_register(kind, Port::Port2, 0x0F);
+        });
+    }
+}
+
+#[test]
+fn test_modify_bound() {
+    for kind in RegisterKind::ALL
+        .into_iter()
+        .filter(|&kind| kind != RegisterKind::Input)
+    {
+        check("MODIFY", TransferBound::MODIFY, |tca| {
+            let _ = tca.modify(kind, Port::Port2, |value| !value);
+        });
+    }
+}
+
+#[cfg(feature = "helpers")]
+#[test]
+fn test_pin_scheduler_run_bound() {
+    check(
+        "PIN_SCHEDULER_RUN",
+        TransferBound::PIN_SCHEDULER_RUN,
+        |tca| {
+            let mut scheduler = tca6424::PinScheduler::new();
+            for pin in [Pin::P00, Pin::P10, Pin::P20] {
+                scheduler.set_pin_after(pin, PinState::High, 0, 0);
+            }
+            let _ = scheduler.run(tca, 0);
+        },
+    );
+}
+
+#[test]
+fn test_cache_mode_needs_every_output_register() {
+    let transactions = Cell::new(0);
+    let mut tca = Tca6424::new(
+        CountingBus {
+            transactions: &transactions,
+        },
+        0x22,
+    )
+    .unwrap();
+    tca.set_cached_output_reads(true);
+    tca.set_port_output(Port::Port0, 0x00).unwrap();
+    assert_eq!(tca.cache_mode(), CacheMode::Uncached);
+    tca.set_initial_output_state(0x00, 0x00, 0x00).unwrap();
+    assert_eq!(tca.cache_mode(), CacheMode::CachedOutputs);
+}
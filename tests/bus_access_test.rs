@@ -0,0 +1,60 @@
+#![cfg(not(feature = "async"))]
+
+mod common;
+
+use core::cell::RefCell;
+
+use common::write;
+use embedded_hal_bus::i2c::{AtomicDevice, CriticalSectionDevice, RefCellDevice};
+use embedded_hal_bus::util::AtomicCell;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{BusAccess, Port, Tca6424};
+
+fn accepts<I2C: BusAccess>(_: &I2C) {}
+
+#[test]
+fn test_bus_access_covers_every_way_of_passing_a_bus() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    accepts(&&mut i2c_mock);
+    accepts(&i2c_mock);
+
+    let refcell = RefCell::new(i2c_mock.clone());
+    accepts(&RefCellDevice::new(&refcell));
+    let critical_section = critical_section::Mutex::new(RefCell::new(i2c_mock.clone()));
+    accepts(&CriticalSectionDevice::new(&critical_section));
+    let atomic = AtomicCell::new(i2c_mock.clone());
+    accepts(&AtomicDevice::new(&atomic));
+
+    i2c_mock.done();
+}
+
+#[test]
+fn test_drivers_share_a_bus_through_a_device_sync() {
+    let expectations: Vec<I2cTransaction> = [
+        write(0x22, 0x04, &[0x01]).to_vec(),
+        write(0x23, 0x04, &[0x02]).to_vec(),
+    ]
+    .concat();
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let bus = RefCell::new(i2c_mock.clone());
+
+    let mut first = Tca6424::new(RefCellDevice::new(&bus), 0x22).unwrap();
+    let mut second = Tca6424::new(RefCellDevice::new(&bus), 0x23).unwrap();
+    first.set_port_output(Port::Port0, 0x01).unwrap();
+    second.set_port_output(Port::Port0, 0x02).unwrap();
+
+    i2c_mock.done();
+}
+
+#[cfg(feature = "embassy")]
+#[test]
+fn test_bus_access_covers_embassy_devices() {
+    use embassy_embedded_hal::shared_bus::blocking::i2c::I2cDevice;
+    use embassy_sync::blocking_mutex::NoopMutex;
+
+    let mut i2c_mock = I2cMock::new(&[]);
+    let bus = NoopMutex::new(RefCell::new(i2c_mock.clone()));
+    accepts(&I2cDevice::new(&bus));
+
+    i2c_mock.done();
+}
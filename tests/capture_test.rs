@@ -0,0 +1,54 @@
+#![cfg(feature = "capture")]
+
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{TransferDirection, TransferOutcome, TransferRecord};
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_capture_keeps_last_transfers_sync() {
+    use tca6424::{Pin, Port};
+
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x00], vec![0x5A]),
+        I2cTransaction::write_read(address, vec![0x0D], vec![0xFF]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x0D]),
+        I2cTransaction::write(address, vec![0xFB]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x80], vec![0; 3]).with_error(
+            embedded_hal::i2c::ErrorKind::NoAcknowledge(
+                embedded_hal::i2c::NoAcknowledgeSource::Address,
+            ),
+        ),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    assert_eq!(tca.trace().count(), 0);
+
+    let buffer = Box::leak(Box::new([TransferRecord::default(); 3]));
+    assert!(tca.enable_capture(buffer).is_none());
+
+    tca.get_port_input_state(Port::Port0).unwrap();
+    tca.set_pin_direction(Pin::P12, tca6424::PinDirection::Output)
+        .unwrap();
+    tca.read_ports().unwrap_err();
+
+    // Four transfers into three slots: the first read was overwritten
+    let trace: Vec<TransferRecord> = tca.trace().copied().collect();
+    assert_eq!(trace.len(), 3);
+    assert_eq!(trace[0].command, 0x0D);
+    assert_eq!(trace[0].direction, TransferDirection::Read);
+    assert_eq!(trace[0].data(), [0xFF]);
+    assert_eq!(trace[1].direction, TransferDirection::Write);
+    assert_eq!(trace[1].to_string(), "W 0x0d [0xfb] Ok");
+    assert_eq!(trace[2].command, 0x80);
+    assert_eq!(trace[2].len, 3);
+    assert_eq!(trace[2].outcome, TransferOutcome::Nack);
+
+    tca.clear_trace();
+    assert_eq!(tca.trace().count(), 0);
+    assert_eq!(tca.disable_capture().map(|buffer| buffer.len()), Some(3));
+
+    i2c_mock.done();
+}
@@ -0,0 +1,54 @@
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::errors::Error;
+use tca6424::{ChannelMap, Pin, PinState};
+
+static REV_B: ChannelMap = ChannelMap::new(&[Pin::P10, Pin::P27]);
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_channels_address_mapped_pins_sync() {
+    let address = 0x22;
+    let expectations = [
+        // Channel 1 = P27 high
+        I2cTransaction::write_read(address, vec![0x06], vec![0x00]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x06]),
+        I2cTransaction::write(address, vec![0x80]),
+        I2cTransaction::transaction_end(address),
+        // Channel 0 = P10 input
+        I2cTransaction::write_read(address, vec![0x01], vec![0x01]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    assert!(matches!(
+        tca.set_channel_output(0, true),
+        Err(Error::UnmappedChannel(0))
+    ));
+
+    tca.set_channel_map(REV_B);
+    tca.set_channel_output(1, true).unwrap();
+    assert_eq!(tca.get_channel_input_state(0).unwrap(), PinState::High);
+    assert!(matches!(
+        tca.get_channel_input_state(2),
+        Err(Error::UnmappedChannel(2))
+    ));
+
+    i2c_mock.done();
+}
+
+#[test]
+fn test_channel_map_lookup() {
+    assert_eq!(REV_B.len(), 2);
+    assert_eq!(REV_B.pin(0), Some(Pin::P10));
+    assert_eq!(REV_B.pin(2), None);
+    assert_eq!(REV_B.channel(Pin::P27), Some(1));
+    assert_eq!(REV_B.channel(Pin::P00), None);
+}
+
+#[test]
+#[should_panic(expected = "more than one channel")]
+fn test_channel_map_rejects_duplicate_pins() {
+    let pins: &'static [Pin] = &[Pin::P00, Pin::P00];
+    let _ = ChannelMap::new(pins);
+}
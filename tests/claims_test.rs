@@ -0,0 +1,75 @@
+use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+use tca6424::Pin;
+use tca6424::errors::ConfigError;
+
+#[test]
+fn test_claim_pin_conflicts() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+
+    let claim = tca.claim_pin(Pin::P12).unwrap();
+    assert_eq!(claim.pin(), Pin::P12);
+    assert!(tca.is_claimed(Pin::P12));
+    assert!(matches!(
+        tca.claim_pin(Pin::P12),
+        Err(ConfigError::PinAlreadyClaimed(Pin::P12))
+    ));
+
+    // All or nothing: P00 stays free because P12 conflicts
+    let mask = (1 << Pin::P00 as u8) | (1 << Pin::P12 as u8);
+    assert!(matches!(
+        tca.claim_mask(mask),
+        Err(ConfigError::PinAlreadyClaimed(Pin::P12))
+    ));
+    assert!(!tca.is_claimed(Pin::P00));
+    assert_eq!(tca.claimed_mask(), 1 << Pin::P12 as u8);
+
+    i2c_mock.done();
+}
+
+#[test]
+fn test_release_and_reclaim() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+
+    let led = tca.claim_pin(Pin::P00).unwrap();
+    led.release(&mut tca).unwrap();
+    assert!(!tca.is_claimed(Pin::P00));
+    let test_probe = tca.claim_pin(Pin::P00).unwrap();
+    tca.release_pin(test_probe).unwrap();
+
+    let low = tca.claim_mask(0b011).unwrap();
+    let high = tca.claim_mask(0b100).unwrap();
+    assert_eq!(low.mask(), 0b011);
+    tca.release_mask_claim(low).unwrap();
+    assert_eq!(tca.claimed_mask(), 0b100);
+    high.release(&mut tca).unwrap();
+    assert_eq!(tca.claimed_mask(), 0);
+
+    i2c_mock.done();
+}
+
+#[test]
+fn test_release_rejects_claims_of_another_driver() {
+    let mut first_mock = I2cMock::new(&[]);
+    let mut second_mock = I2cMock::new(&[]);
+    let mut first = tca6424::Tca6424::new(&mut first_mock, 0x22).unwrap();
+    let mut second = tca6424::Tca6424::new(&mut second_mock, 0x23).unwrap();
+
+    let pin = first.claim_pin(Pin::P05).unwrap();
+    let mask = first.claim_mask(0b11).unwrap();
+    let _relay = second.claim_pin(Pin::P05).unwrap();
+    let _bus = second.claim_mask(0b11).unwrap();
+
+    // The claims go back unchanged and the second driver keeps its pins
+    let pin = second.release_pin(pin).unwrap_err();
+    let mask = mask.release(&mut second).unwrap_err();
+    assert_eq!(second.claimed_mask(), (1 << Pin::P05 as u8) | 0b11);
+
+    pin.release(&mut first).unwrap();
+    first.release_mask_claim(mask).unwrap();
+    assert_eq!(first.claimed_mask(), 0);
+
+    first_mock.done();
+    second_mock.done();
+}
@@ -0,0 +1,103 @@
+#![cfg(all(feature = "cli", not(feature = "async")))]
+
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::cli::{self, Command, Flow, ParseError};
+use tca6424::{Pin, PinDirection, PinState, Port};
+
+#[test]
+fn test_cli_parse() {
+    assert_eq!(
+        "dir P04 out".parse(),
+        Ok(Command::Direction(Pin::P04, PinDirection::Output))
+    );
+    assert_eq!(
+        "  SET p27 1 ".parse(),
+        Ok(Command::Set(Pin::P27, PinState::High))
+    );
+    assert_eq!("get P10".parse(), Ok(Command::Get(Pin::P10)));
+    assert_eq!("watch port2".parse(), Ok(Command::Watch(Port::Port2, 10)));
+    assert_eq!("watch 1 3".parse(), Ok(Command::Watch(Port::Port1, 3)));
+    assert_eq!("dump".parse(), Ok(Command::Dump));
+    assert_eq!("quit".parse(), Ok(Command::Quit));
+
+    assert_eq!("".parse::<Command>(), Err(ParseError::Empty));
+    assert_eq!(
+        "set P04".parse::<Command>(),
+        Err(ParseError::MissingArgument("high|low"))
+    );
+    assert_eq!(
+        "set P38 high".parse::<Command>(),
+        Err(ParseError::InvalidArgument("P38".into()))
+    );
+    assert_eq!(
+        "blink P04".parse::<Command>(),
+        Err(ParseError::UnknownCommand("blink".into()))
+    );
+}
+
+#[test]
+fn test_cli_execute() {
+    let address = 0x22;
+    let expectations = [
+        // set P04 high
+        I2cTransaction::write_read(address, vec![0x04], vec![0x00]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0x10]),
+        I2cTransaction::transaction_end(address),
+        // get P21
+        I2cTransaction::write_read(address, vec![0x02], vec![0x02]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    let mut out = Vec::new();
+
+    let set = Command::Set(Pin::P04, PinState::High);
+    assert_eq!(
+        cli::execute(&mut tca, set, &mut out).unwrap(),
+        Flow::Continue
+    );
+    let get = Command::Get(Pin::P21);
+    assert_eq!(
+        cli::execute(&mut tca, get, &mut out).unwrap(),
+        Flow::Continue
+    );
+    assert_eq!(
+        cli::execute(&mut tca, Command::Quit, &mut out).unwrap(),
+        Flow::Quit
+    );
+    assert_eq!(String::from_utf8(out).unwrap(), "P04 High\nP21 High\n");
+
+    i2c_mock.done();
+}
+
+#[test]
+fn test_cli_watch() {
+    let address = 0x22;
+    let polls = 1_000 / cli::WATCH_INTERVAL_MS as usize;
+    let mut expectations = vec![I2cTransaction::write_read(address, vec![0x02], vec![0x01])];
+    for poll in 0..polls {
+        // Port 0 changes are not watched
+        let levels = if poll < 10 {
+            [0x00, 0x00, 0x01]
+        } else {
+            [0xFF, 0x00, 0x03]
+        };
+        expectations.push(I2cTransaction::write_read(
+            address,
+            vec![0x80],
+            levels.to_vec(),
+        ));
+    }
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    let mut out = Vec::new();
+
+    cli::execute(&mut tca, Command::Watch(Port::Port2, 1), &mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "P2: 0b00000001\nP21 High\n"
+    );
+
+    i2c_mock.done();
+}
@@ -0,0 +1,108 @@
+use tca6424::{ManualClock, TimeSource};
+
+#[test]
+fn test_manual_clock_steps() {
+    let clock = ManualClock::default();
+    assert_eq!(clock.now_ms(), 0);
+    clock.advance(40);
+    clock.advance(2);
+    assert_eq!(clock.now_ms(), 42);
+
+    // References are time sources too, and see later steps
+    let shared = &clock;
+    clock.set(u32::MAX - 1);
+    assert_eq!(shared.now_ms(), u32::MAX - 1);
+    clock.advance(3);
+    assert_eq!(TimeSource::now_ms(&shared), 1);
+}
+
+#[cfg(feature = "helpers")]
+mod helpers {
+    use tca6424::{
+        Debounce, Debouncer, InputWatchdog, LockoutDebouncer, ManualClock, Pin, PlayMode, SoftPwm,
+        TimeSource, WatchdogEvent, WaveformPlayer, WaveformStep, pins,
+    };
+
+    static BLINK: [WaveformStep; 2] = [
+        WaveformStep::new(pins!(P00), 0, 100),
+        WaveformStep::new(0, pins!(P00), 100),
+    ];
+
+    #[test]
+    fn test_debouncers_follow_clock() {
+        let clock = ManualClock::new(u32::MAX - 5);
+        let mut debouncer = Debouncer::new(false, 20);
+        assert_eq!(debouncer.update_from(true, &clock), None);
+        clock.advance(19);
+        assert_eq!(debouncer.update_from(true, &clock), None);
+        clock.advance(1);
+        assert_eq!(debouncer.update_from(true, &clock), Some(true));
+
+        // Through a trait object
+        let dyn_clock: &dyn TimeSource = &clock;
+        let mut lockout = LockoutDebouncer::new(false, 50);
+        assert_eq!(lockout.update_from(true, dyn_clock), Some(true));
+        clock.advance(10);
+        assert_eq!(lockout.update_from(false, dyn_clock), None);
+        clock.advance(40);
+        assert_eq!(lockout.update_from(false, dyn_clock), Some(false));
+    }
+
+    #[test]
+    fn test_sequencer_and_pwm_follow_clock() {
+        let clock = ManualClock::new(1_000);
+        let mut player = WaveformPlayer::new(&BLINK, PlayMode::Loop);
+        assert_eq!(player.process_from(&clock), Some((pins!(P00), 0)));
+        clock.advance(99);
+        assert_eq!(player.process_from(&clock), None);
+        clock.advance(1);
+        assert_eq!(player.process_from(&clock), Some((0, pins!(P00))));
+        clock.advance(100);
+        assert_eq!(player.process_from(&clock), Some((pins!(P00), 0)));
+
+        let mut pwm = SoftPwm::new(10);
+        pwm.fade_in(pins!(P01), 100);
+        assert_eq!(pwm.process_from(&clock), Some(0));
+        clock.advance(100);
+        assert_eq!(pwm.process_from(&clock), Some(pins!(P01)));
+        assert_eq!(pwm.duty(Pin::P01), 255);
+    }
+
+    #[test]
+    fn test_watchdog_follows_clock() {
+        let clock = ManualClock::new(0);
+        let mut watchdog = InputWatchdog::new(pins!(P10), 200);
+        watchdog.process_from(0, &clock);
+        clock.advance(199);
+        watchdog.process_from(0, &clock);
+        assert_eq!(watchdog.next_event(), None);
+        clock.advance(1);
+        watchdog.process_from(0, &clock);
+        assert_eq!(
+            watchdog.next_event(),
+            Some(WatchdogEvent::StuckInput(Pin::P10))
+        );
+    }
+
+    #[cfg(not(feature = "async"))]
+    #[test]
+    fn test_watchdog_poll_from_sync() {
+        use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+        let address = 0x22;
+        let expectations = [
+            I2cTransaction::write_read(address, vec![0x00], vec![0x00]),
+            I2cTransaction::write_read(address, vec![0x00], vec![0x00]),
+        ];
+        let mut i2c_mock = I2cMock::new(&expectations);
+        let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+        let clock = ManualClock::new(0);
+        let mut watchdog = InputWatchdog::new(pins!(P03), 50);
+        assert_eq!(watchdog.poll_from(&mut tca, &clock).unwrap(), 0);
+        clock.advance(50);
+        assert_eq!(watchdog.poll_from(&mut tca, &clock).unwrap(), pins!(P03));
+
+        i2c_mock.done();
+    }
+}
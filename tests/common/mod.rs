@@ -0,0 +1,100 @@
+//! Fixtures shared by the integration tests.
+
+#![allow(dead_code)]
+
+use embedded_hal_mock::eh1::i2c::Transaction as I2cTransaction;
+
+#[cfg(feature = "async")]
+pub use self::stalling::{BusMode, StallingBus};
+
+/// The mock transactions of one register write: the command byte and `data` as two write
+/// operations of a single I2C transaction.
+pub fn write(address: u8, command: u8, data: &[u8]) -> [I2cTransaction; 4] {
+    [
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![command]),
+        I2cTransaction::write(address, data.to_vec()),
+        I2cTransaction::transaction_end(address),
+    ]
+}
+
+#[cfg(feature = "async")]
+mod stalling {
+    use std::cell::{Cell, RefCell};
+
+    use embedded_hal::i2c::{ErrorKind, ErrorType, Operation, SevenBitAddress};
+    use embedded_hal_async::i2c::I2c;
+
+    /// How a [`StallingBus`] handles the next transactions.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum BusMode {
+        /// Answer every read with `StallingBus::input` and log every write.
+        Answer,
+        /// Answer reads, but never complete a write.
+        StallWrites,
+        /// Never complete any transaction.
+        Stall,
+        /// Fail every transaction.
+        Fail,
+    }
+
+    /// An async bus whose transactions can stay pending forever, standing in for a wedged
+    /// bus or a transfer that is cut short by a dropped future. Logs `(command, data)` of each
+    /// completed write.
+    pub struct StallingBus {
+        pub mode: Cell<BusMode>,
+        pub input: Cell<u8>,
+        pub writes: RefCell<Vec<(u8, Vec<u8>)>>,
+    }
+
+    impl StallingBus {
+        pub fn new(mode: BusMode) -> Self {
+            Self {
+                mode: Cell::new(mode),
+                input: Cell::new(0x00),
+                writes: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl ErrorType for &StallingBus {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for &StallingBus {
+        async fn transaction(
+            &mut self,
+            _address: SevenBitAddress,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            let reads = operations
+                .iter()
+                .any(|operation| matches!(operation, Operation::Read(_)));
+            match self.mode.get() {
+                BusMode::Stall => core::future::pending().await,
+                BusMode::StallWrites if !reads => core::future::pending().await,
+                BusMode::Fail => return Err(ErrorKind::Other),
+                _ => {}
+            }
+            let mut bytes = Vec::new();
+            for operation in operations {
+                match operation {
+                    Operation::Write(data) => bytes.extend_from_slice(data),
+                    Operation::Read(buffer) => buffer.fill(self.input.get()),
+                }
+            }
+            if !reads && let Some((&command, data)) = bytes.split_first() {
+                self.writes.borrow_mut().push((command, data.to_vec()));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Polls `future` once and drops it, as a losing `select!` branch would.
+#[cfg(feature = "async")]
+pub fn poll_once_and_drop<F: core::future::Future>(future: F) {
+    let mut future = core::pin::pin!(future);
+    let mut context = core::task::Context::from_waker(core::task::Waker::noop());
+    assert!(future.as_mut().poll(&mut context).is_pending());
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "config-files")]
+
+use tca6424::{BoardConfig, ConfigFileError};
+
+tca6424::board! {
+    pub struct FrontPanel {
+        pub status_led: output(P00, active_high, on),
+        pub relay_main: output(P12, active_low, off),
+        pub start_button: input(P20, active_low),
+    }
+}
+
+const TOML: &str = r#"inputs = ["P20"]
+
+[outputs]
+P00 = "high"
+P12 = "high"
+"#;
+
+#[test]
+fn test_board_config_toml_round_trip() {
+    assert_eq!(FrontPanel::CONFIG.to_toml(), TOML);
+    assert_eq!(BoardConfig::from_toml(TOML).unwrap(), FrontPanel::CONFIG);
+    assert_eq!(
+        BoardConfig::from_toml("").unwrap(),
+        BoardConfig::new(),
+        "both sections are optional"
+    );
+}
+
+#[test]
+fn test_board_config_file_event_inputs() {
+    let config = BoardConfig::new()
+        .input(tca6424::Pin::P00, false)
+        .event_input(tca6424::Pin::P01, true);
+    let toml = config.to_toml();
+    assert_eq!(
+        toml,
+        "inputs = [\"P00\"]\nevents = [\"P01\"]\n\n[outputs]\n"
+    );
+    assert_eq!(BoardConfig::from_toml(&toml).unwrap(), config);
+    assert_eq!(BoardConfig::from_json(&config.to_json()).unwrap(), config);
+}
+
+#[test]
+fn test_board_config_json_round_trip() {
+    let json = FrontPanel::CONFIG.to_json();
+    assert_eq!(BoardConfig::from_json(&json).unwrap(), FrontPanel::CONFIG);
+
+    let config =
+        BoardConfig::from_json(r#"{"inputs": ["p01"], "outputs": {"P27": "low"}}"#).unwrap();
+    assert_eq!(config.pins(), tca6424::pins!(P01, P27));
+    assert_eq!(config.outputs(), tca6424::pins!(P27));
+    assert_eq!(config.levels(), 0);
+}
+
+#[test]
+fn test_board_config_file_errors() {
+    let error = BoardConfig::from_toml("inputs = [\"P28\"]").unwrap_err();
+    assert!(matches!(error, ConfigFileError::Toml(_)));
+    assert!(error.to_string().contains("unknown pin `P28`"));
+
+    let error =
+        BoardConfig::from_json(r#"{"inputs": ["P00"], "outputs": {"P00": "low"}}"#).unwrap_err();
+    assert!(matches!(error, ConfigFileError::Json(_)));
+    assert!(
+        error
+            .to_string()
+            .contains("pin P00 used by more than one net")
+    );
+
+    assert!(BoardConfig::from_json(r#"{"outputs": {"P00": "on"}}"#).is_err());
+    assert!(BoardConfig::from_json(r#"{"unknown": []}"#).is_err());
+}
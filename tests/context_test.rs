@@ -0,0 +1,106 @@
+#![cfg(all(feature = "auto-increment", feature = "error-context"))]
+
+mod common;
+
+#[cfg(not(feature = "async"))]
+use embedded_hal::i2c::ErrorKind;
+#[cfg(not(feature = "async"))]
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{Activity, ErrorContext, InterruptThrottle, Port, Register, Tca6424};
+
+const ADDRESS: u8 = 0x22;
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_register_access_errors_are_labelled() {
+    let expectations = [
+        I2cTransaction::write_read(ADDRESS, vec![0x01], vec![0x00]).with_error(ErrorKind::Other),
+        I2cTransaction::write_read(ADDRESS, vec![0x0E], vec![0xFF]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, ADDRESS).unwrap();
+    assert_eq!(tca.last_error_context(), None);
+
+    assert!(tca.get_port_input_state(Port::Port1).is_err());
+    let context = tca.last_error_context().unwrap();
+    assert_eq!(
+        context,
+        ErrorContext {
+            activity: Activity::ReadInput,
+            register: Some(Register::InputPort1),
+        }
+    );
+    assert_eq!(context.to_string(), "reading inputs (Input Port 1)");
+
+    // A successful call keeps the context until it is taken
+    tca.get_port_direction(Port::Port2).unwrap();
+    assert_eq!(tca.take_error_context(), Some(context));
+    assert_eq!(tca.last_error_context(), None);
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_driver_methods_label_their_accesses() {
+    let expectations = [
+        I2cTransaction::write_read(ADDRESS, vec![0x80], vec![0x00, 0x00, 0x00])
+            .with_error(ErrorKind::Other),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, ADDRESS).unwrap();
+
+    let mut throttle = InterruptThrottle::new(0);
+    throttle.notify();
+    assert!(tca.service_interrupt(&mut throttle, 0).is_err());
+    let context = tca.last_error_context().unwrap();
+    assert_eq!(context.activity, Activity::ServiceInterrupt);
+    assert_eq!(context.register, Some(Register::InputPort0));
+    assert_eq!(context.to_string(), "servicing an interrupt (Input Port 0)");
+
+    i2c_mock.done();
+}
+
+#[cfg(all(feature = "helpers", not(feature = "async")))]
+#[test]
+fn test_errors_before_any_access_are_labelled() {
+    tca6424::board! {
+        pub struct Panel {
+            pub led: output(P00, active_high, off),
+        }
+    }
+
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = Tca6424::new(&mut i2c_mock, ADDRESS).unwrap();
+    let _claim = tca.claim_pin(tca6424::Pin::P00).unwrap();
+
+    assert!(Panel::init(&mut tca).is_err());
+    let context = tca.last_error_context().unwrap();
+    assert_eq!(context.activity, Activity::ApplyConfig);
+    assert_eq!(context.register, None);
+    assert_eq!(context.to_string(), "applying a configuration");
+
+    i2c_mock.done();
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_cancelled_method_does_not_label_later_errors() {
+    use common::{BusMode, StallingBus, poll_once_and_drop};
+
+    let bus = StallingBus::new(BusMode::Stall);
+    let mut tca = Tca6424::new(&bus, ADDRESS).unwrap();
+    let mut throttle = InterruptThrottle::new(0);
+    throttle.notify();
+    poll_once_and_drop(tca.service_interrupt(&mut throttle, 0));
+
+    bus.mode.set(BusMode::Fail);
+    assert!(tca.set_port_output(Port::Port0, 0x01).await.is_err());
+    assert_eq!(
+        tca.last_error_context(),
+        Some(ErrorContext {
+            activity: Activity::WriteOutput,
+            register: Some(Register::OutputPort0),
+        })
+    );
+}
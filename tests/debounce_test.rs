@@ -0,0 +1,69 @@
+#![cfg(feature = "helpers")]
+
+use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+use tca6424::{
+    ButtonEvent, Debounce, Debouncer, IntegratorDebouncer, LitButton, LitButtonConfig,
+    LockoutDebouncer, Pin, PinState, StableSamplesDebouncer,
+};
+
+/// Feeds `samples` one millisecond apart and returns the reported changes.
+fn run(debouncer: &mut impl Debounce, samples: &[bool]) -> Vec<(u32, bool)> {
+    samples
+        .iter()
+        .zip(0u32..)
+        .filter_map(|(&raw, now_ms)| debouncer.update(raw, now_ms).map(|level| (now_ms, level)))
+        .collect()
+}
+
+const NOISY: [bool; 10] = [
+    true, false, true, true, false, true, true, true, false, false,
+];
+
+#[test]
+fn test_settle_time_debouncer() {
+    let mut debouncer = Debouncer::new(false, 2);
+    assert_eq!(run(&mut debouncer, &NOISY), [(7, true)]);
+    assert!(Debounce::is_high(&debouncer));
+}
+
+#[test]
+fn test_integrator_debouncer() {
+    let mut debouncer = IntegratorDebouncer::new(false, 3);
+    // Counts 1 0 1 2 1 2 3 (high) 3 2 1
+    assert_eq!(run(&mut debouncer, &NOISY), [(6, true)]);
+    assert_eq!(debouncer.update(false, 10), Some(false));
+    assert!(!debouncer.is_high());
+}
+
+#[test]
+fn test_stable_samples_debouncer() {
+    let mut debouncer = StableSamplesDebouncer::new(false, 3);
+    // The high run 5-7 is accepted; the low run 8-9 needs a third sample
+    assert_eq!(run(&mut debouncer, &NOISY), [(7, true)]);
+    assert_eq!(debouncer.update(false, 10), Some(false));
+}
+
+#[test]
+fn test_lockout_debouncer() {
+    let mut debouncer = LockoutDebouncer::new(false, 4);
+    // Reacts on the first edge; the low level seen when the lockout ends is taken over
+    assert_eq!(run(&mut debouncer, &NOISY), [(0, true), (4, false)]);
+    assert!(!debouncer.is_high());
+}
+
+#[test]
+fn test_lit_button_with_custom_debouncer() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+    let mut button = LitButton::new(&mut tca, Pin::P00, Pin::P10, LitButtonConfig::default())
+        .unwrap()
+        .with_debouncer(StableSamplesDebouncer::new(false, 2));
+
+    // Active-low button, two agreeing samples needed regardless of time
+    assert_eq!(button.process(PinState::Low, 0), None);
+    assert_eq!(button.process(PinState::Low, 1), Some(ButtonEvent::Pressed));
+    assert!(button.is_pressed());
+
+    button.release(&mut tca);
+    i2c_mock.done();
+}
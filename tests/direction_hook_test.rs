@@ -0,0 +1,61 @@
+#![cfg(all(feature = "auto-increment", not(feature = "async")))]
+
+mod common;
+
+use common::write;
+use std::cell::RefCell;
+
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{Pin, PinDirection, Port, Tca6424};
+
+thread_local! {
+    static CHANGES: RefCell<Vec<(Pin, PinDirection)>> = const { RefCell::new(Vec::new()) };
+}
+
+fn record(pin: Pin, direction: PinDirection) {
+    CHANGES.with_borrow_mut(|changes| changes.push((pin, direction)));
+}
+
+fn take_changes() -> Vec<(Pin, PinDirection)> {
+    CHANGES.with_borrow_mut(std::mem::take)
+}
+
+#[test]
+fn test_direction_hook_reports_changed_pins() {
+    let address = 0x22;
+    let mut expectations = Vec::new();
+    expectations.extend(write(address, 0x8C, &[0xFF, 0xFF, 0xFF]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x0D], vec![0xFF]));
+    expectations.extend(write(address, 0x0D, &[0xF7]));
+    expectations.extend(write(address, 0x0D, &[0xF7]));
+    expectations.extend(write(address, 0x05, &[0x00]));
+    expectations.extend(write(address, 0x0E, &[0x00]));
+    expectations.extend(write(address, 0x0E, &[0xFF]));
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, address).unwrap();
+    assert!(tca.direction_hook().is_none());
+
+    // Unknown registers: every pin of the family is reported
+    tca.set_direction_hook(Some(record));
+    tca.set_ports_direction_ai(Port::Port0, &[0xFF; 3]).unwrap();
+    let changes = take_changes();
+    assert_eq!(changes.len(), 24);
+    assert!(changes.iter().all(|&(_, dir)| dir == PinDirection::Input));
+
+    tca.set_pin_direction(Pin::P13, PinDirection::Output)
+        .unwrap();
+    assert_eq!(take_changes(), [(Pin::P13, PinDirection::Output)]);
+
+    // Rewriting the same directions, or writing other registers, reports nothing
+    tca.set_port_direction(Port::Port1, 0xF7).unwrap();
+    tca.set_port_output(Port::Port1, 0x00).unwrap();
+    assert!(take_changes().is_empty());
+
+    tca.set_port_direction(Port::Port2, 0x00).unwrap();
+    assert_eq!(take_changes().len(), 8);
+
+    tca.set_direction_hook(None);
+    tca.set_port_direction(Port::Port2, 0xFF).unwrap();
+    assert!(take_changes().is_empty());
+    i2c_mock.done();
+}
@@ -0,0 +1,124 @@
+#![cfg(all(feature = "dry-run", not(feature = "async")))]
+
+use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+use tca6424::{PinDirection, PinState, PlannedAccess, Port, Register, RegisterKind, Tca6424};
+
+#[cfg(feature = "helpers")]
+tca6424::board! {
+    /// Front panel of the test board.
+    pub struct FrontPanel {
+        pub status_led: output(P00, active_high, on),
+        pub relay_main: output(P12, active_low, off),
+        pub start_button: input(P20, active_low),
+    }
+}
+
+#[cfg(feature = "helpers")]
+#[test]
+fn test_dry_run_board_config() {
+    // No expectations: any bus transfer fails the test
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+
+    tca.begin_dry_run();
+    assert!(tca.is_dry_run());
+    FrontPanel::init(&mut tca).unwrap();
+    assert!(tca.is_claimed(tca6424::Pin::P12));
+    assert_eq!(
+        tca.cache().get(RegisterKind::Output, Port::Port1),
+        Some(0x04)
+    );
+    let plan = tca.finish_dry_run().unwrap();
+
+    assert_eq!(plan.len(), 2);
+    let transfers = plan.transfers();
+    assert_eq!(transfers[0].access, PlannedAccess::Write);
+    assert_eq!(transfers[0].register, Register::OutputPort0);
+    assert!(transfers[0].auto_increment);
+    assert_eq!(transfers[0].command(), 0x84);
+    assert_eq!(transfers[0].data(), &[0x01, 0x04, 0x00]);
+    assert_eq!(transfers[1].data(), &[0xFE, 0xFB, 0xFF]);
+    assert_eq!(
+        plan.to_string(),
+        "W Output Port 0 (AI) [0x01 0x04 0x00]\nW Configuration Port 0 (AI) [0xfe 0xfb 0xff]"
+    );
+
+    // The cache and claims are as before, so the real run can follow
+    assert!(!tca.is_dry_run());
+    assert!(tca.cache().is_empty());
+    assert!(!tca.is_claimed(tca6424::Pin::P12));
+    assert!(tca.finish_dry_run().is_none());
+    i2c_mock.done();
+}
+
+#[test]
+fn test_dry_run_answers_reads_from_cache_and_defaults() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+
+    tca.begin_dry_run();
+    // Unknown Configuration register: read as the power-on 0xFF
+    tca.set_pin_direction(tca6424::Pin::P02, PinDirection::Output)
+        .unwrap();
+    // The Output family is unknown, so the transaction reads it first; the Configuration
+    // port was written during the run and is taken from the cache
+    tca.with_config_transaction(|txn| {
+        txn.output(tca6424::Pin::P01, PinState::Low)
+            .direction(tca6424::Pin::P01, PinDirection::Output);
+    })
+    .unwrap();
+    assert_eq!(tca.get_port_input_state(Port::Port2).unwrap(), 0x00);
+    let plan = tca.finish_dry_run().unwrap();
+
+    let summary: Vec<_> = plan
+        .transfers()
+        .iter()
+        .map(|transfer| {
+            (
+                transfer.access,
+                transfer.command(),
+                transfer.data().to_vec(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (PlannedAccess::Read, 0x0C, vec![0xFF]),
+            (PlannedAccess::Write, 0x0C, vec![0xFB]),
+            (PlannedAccess::Read, 0x84, vec![0xFF, 0xFF, 0xFF]),
+            (PlannedAccess::Write, 0x04, vec![0xFD]),
+            (PlannedAccess::Write, 0x0C, vec![0xF9]),
+            (PlannedAccess::Read, 0x02, vec![0x00]),
+        ]
+    );
+    assert_eq!(plan.writes().count(), 3);
+    assert!(!plan.is_truncated());
+    let cost = plan.cost();
+    assert_eq!(cost.transactions(), 6);
+    assert_eq!(
+        cost,
+        tca6424::BusCost::PORT_READ.times(2)
+            + tca6424::BusCost::INPUTS_READ
+            + tca6424::BusCost::PORT_WRITE.times(3)
+    );
+    i2c_mock.done();
+}
+
+#[test]
+fn test_dry_run_plan_truncates() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+
+    tca.begin_dry_run();
+    for value in 0..40u8 {
+        tca.set_port_output(Port::Port0, value).unwrap();
+    }
+    let plan = tca.finish_dry_run().unwrap();
+    assert_eq!(plan.len(), 40);
+    assert_eq!(plan.transfers().len(), tca6424::DRY_RUN_CAPACITY);
+    assert_eq!(plan.cost(), tca6424::BusCost::PORT_WRITE.times(40));
+    assert!(plan.is_truncated());
+    assert!(plan.to_string().ends_with("\n... 8 more transfers"));
+    i2c_mock.done();
+}
@@ -0,0 +1,31 @@
+#![cfg(all(feature = "embassy", not(feature = "async")))]
+
+use embassy_embedded_hal::shared_bus::blocking::i2c::I2cDevice;
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+use embedded_hal::i2c::I2c;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{Port, SharedI2cBus, Tca6424};
+
+#[test]
+fn test_driver_on_embassy_shared_bus() {
+    let expectations = [
+        I2cTransaction::write_read(0x22, vec![0x00], vec![0x5A]),
+        // Another device on the same bus
+        I2cTransaction::write(0x48, vec![0x01]),
+        I2cTransaction::transaction_start(0x23),
+        I2cTransaction::write(0x23, vec![0x04]),
+        I2cTransaction::write(0x23, vec![0xA5]),
+        I2cTransaction::transaction_end(0x23),
+    ];
+    let bus: SharedI2cBus<NoopRawMutex, _> = SharedI2cBus::new(I2cMock::new(&expectations).into());
+
+    let mut tca = Tca6424::new_on_shared_bus(&bus, 0x22).unwrap();
+    let mut sensor = I2cDevice::new(&bus);
+    let mut second = Tca6424::new(I2cDevice::new(&bus), 0x23).unwrap();
+
+    assert_eq!(tca.get_port_input_state(Port::Port0).unwrap(), 0x5A);
+    sensor.write(0x48, &[0x01]).unwrap();
+    second.set_port_output(Port::Port0, 0xA5).unwrap();
+
+    bus.into_inner().into_inner().done();
+}
@@ -0,0 +1,88 @@
+#![cfg(feature = "helpers")]
+
+use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+use embedded_hal_mock::eh1::i2c::Transaction as I2cTransaction;
+use tca6424::{EncoderConfig, EncoderEvent, Pin, RotaryEncoder};
+
+const A: u32 = 1 << Pin::P00 as u8;
+const B: u32 = 1 << Pin::P01 as u8;
+const SW: u32 = 1 << Pin::P20 as u8;
+
+#[test]
+fn test_encoder_steps_click_and_long_press() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+    let config = EncoderConfig {
+        debounce_ms: 5,
+        long_press_ms: 100,
+        ..EncoderConfig::default()
+    };
+    let mut knob =
+        RotaryEncoder::new(&mut tca, Pin::P00, Pin::P01, Some(Pin::P20), config).unwrap();
+    assert_eq!(tca.claimed_mask(), A | B | SW);
+
+    // Switch idle (high), quadrature at rest in state 00
+    let mut now = 0;
+    let mut feed = |knob: &mut RotaryEncoder, ab: u32, sw: u32| {
+        now += 1;
+        knob.process(ab | sw, now);
+    };
+
+    // One detent clockwise: 00 -> 10 -> 11 -> 01 -> 00
+    for ab in [A, A | B, B, 0] {
+        feed(&mut knob, ab, SW);
+    }
+    assert_eq!(knob.next_event(), Some(EncoderEvent::Step(1)));
+    // One detent counter-clockwise
+    for ab in [B, A | B, A, 0] {
+        feed(&mut knob, ab, SW);
+    }
+    assert_eq!(knob.next_event(), Some(EncoderEvent::Step(-1)));
+    assert_eq!(knob.next_event(), None);
+
+    // Short press
+    for _ in 0..10 {
+        feed(&mut knob, 0, 0);
+    }
+    for _ in 0..10 {
+        feed(&mut knob, 0, SW);
+    }
+    assert_eq!(knob.next_event(), Some(EncoderEvent::Click));
+
+    // Long press: reported while held, no click on release
+    for _ in 0..120 {
+        feed(&mut knob, 0, 0);
+    }
+    assert_eq!(knob.next_event(), Some(EncoderEvent::LongPress));
+    for _ in 0..10 {
+        feed(&mut knob, 0, SW);
+    }
+    assert_eq!(knob.next_event(), None);
+
+    knob.release(&mut tca);
+    assert_eq!(tca.claimed_mask(), 0);
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_encoder_poll_sync() {
+    let address = 0x22;
+
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x80], vec![0x01, 0x00, 0x00]),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x03, 0x00, 0x00]),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x02, 0x00, 0x00]),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x00, 0x00, 0x00]),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    let mut knob =
+        RotaryEncoder::new(&mut tca, Pin::P00, Pin::P01, None, EncoderConfig::default()).unwrap();
+
+    let events: Vec<_> = (0..4).map(|t| knob.poll(&mut tca, t).unwrap()).collect();
+    assert_eq!(events, [None, None, None, Some(EncoderEvent::Step(1))]);
+
+    i2c_mock.done();
+}
@@ -0,0 +1,87 @@
+use embedded_hal::i2c::{ErrorKind as I2cErrorKind, NoAcknowledgeSource};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::errors::{ConfigError, Error, ErrorKind};
+use tca6424::{Pin, Port};
+
+#[test]
+fn test_error_clone_and_eq() {
+    let nack = I2cErrorKind::NoAcknowledge(NoAcknowledgeSource::Data);
+    let error: Error<I2cErrorKind> = Error::I2c(nack);
+    assert_eq!(error.clone(), error);
+    assert_ne!(error, Error::I2c(I2cErrorKind::Bus));
+    assert_ne!(error, Error::Timeout);
+    assert_eq!(error.kind(), ErrorKind::Bus(nack));
+}
+
+#[test]
+fn test_error_i2c_accessors() {
+    fn probe(result: Result<(), I2cErrorKind>) -> Result<(), Error<I2cErrorKind>> {
+        result?;
+        Ok(())
+    }
+
+    let error = probe(Err(I2cErrorKind::Bus)).unwrap_err();
+    assert!(error.is_i2c());
+    assert_eq!(error.i2c_error(), Some(&I2cErrorKind::Bus));
+    assert_eq!(error.into_inner(), Some(I2cErrorKind::Bus));
+
+    let error: Error<I2cErrorKind> = Error::DeviceOffline;
+    assert!(!error.is_i2c());
+    assert_eq!(error.i2c_error(), None);
+    assert_eq!(error.into_inner(), None);
+}
+
+#[test]
+fn test_error_forwards_hal_error_kind() {
+    fn hal_kind<E: embedded_hal::i2c::Error>(error: &E) -> I2cErrorKind {
+        error.kind()
+    }
+
+    let nack = I2cErrorKind::NoAcknowledge(NoAcknowledgeSource::Data);
+    assert_eq!(hal_kind(&Error::I2c(nack)), nack);
+    assert_eq!(
+        hal_kind(&Error::<I2cErrorKind>::DeviceOffline),
+        I2cErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)
+    );
+    assert_eq!(
+        hal_kind(&Error::<I2cErrorKind>::Timeout),
+        I2cErrorKind::Other
+    );
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_assert_eq_on_driver_error_sync() {
+    let address = 0x22;
+    let expectations = [I2cTransaction::write_read(address, vec![0x00], vec![0x00])
+        .with_error(I2cErrorKind::ArbitrationLoss)];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    assert_eq!(
+        tca.get_port_input_state(Port::Port0),
+        Err(Error::I2c(I2cErrorKind::ArbitrationLoss))
+    );
+    i2c_mock.done();
+}
+
+#[test]
+fn test_config_errors_are_separate_from_runtime_errors() {
+    let claimed = ConfigError::PinAlreadyClaimed(Pin::P12);
+    assert_eq!(claimed.kind(), ErrorKind::PinClaimed);
+    assert_eq!(
+        ConfigError::UnmappedChannel(3).kind(),
+        ErrorKind::InvalidArgument
+    );
+
+    // Runtime paths that validate configuration report the same mistake
+    let error: Error<I2cErrorKind> = Error::from_config(claimed);
+    assert_eq!(error, Error::PinAlreadyClaimed(Pin::P12));
+    assert!(error.is_config());
+    assert_eq!(error.config_error(), Some(claimed));
+
+    let error: Error<I2cErrorKind> = Error::I2c(I2cErrorKind::Bus);
+    assert!(!error.is_config());
+    assert_eq!(error.config_error(), None);
+    assert!(!Error::<I2cErrorKind>::Timeout.is_config());
+}
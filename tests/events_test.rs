@@ -0,0 +1,317 @@
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{InputEvent, InputWatcher, Pin, PinState, Port, PortActivity};
+
+#[test]
+fn test_port_subscription_masks_other_ports() {
+    let mut watcher = InputWatcher::new()
+        .with_port(Port::Port2)
+        .with_pin(Pin::P00);
+    assert_eq!(watcher.subscribed_mask(), 0xFF_00_01);
+
+    assert!(watcher.update(0x00_00_00).is_empty());
+    // P01 and P10 are not subscribed
+    let changes = watcher.update(0x81_01_03);
+    assert_eq!(changes.changed_mask(), 0x81_00_01);
+    assert_eq!(changes.port_mask(Port::Port2), 0x81);
+    assert_eq!(changes.port_mask(Port::Port1), 0x00);
+    let events: Vec<InputEvent> = changes.iter().collect();
+    assert_eq!(
+        events,
+        [
+            InputEvent {
+                pin: Pin::P00,
+                state: PinState::High,
+                seq: 0,
+            },
+            InputEvent {
+                pin: Pin::P20,
+                state: PinState::High,
+                seq: 1,
+            },
+            InputEvent {
+                pin: Pin::P27,
+                state: PinState::High,
+                seq: 2,
+            },
+        ]
+    );
+
+    watcher.unsubscribe_pin(Pin::P27);
+    let changes = watcher.update(0x01_01_03);
+    assert!(changes.is_empty());
+    watcher.unsubscribe_port(Port::Port2);
+    assert_eq!(watcher.subscribed_mask(), 0x00_00_01);
+}
+
+#[test]
+fn test_event_sequence_numbers() {
+    let mut watcher = InputWatcher::new().with_port(Port::Port0);
+    watcher.update(0x00);
+    let first = watcher.update(0x03);
+    assert_eq!(first.first_seq(), 0);
+    assert_eq!(watcher.next_seq(), 2);
+    // Unsubscribed and unchanged pins do not consume numbers
+    assert!(watcher.update(0x01_03).is_empty());
+    let second = watcher.update(0x01_07);
+    assert_eq!(second.first_seq(), 2);
+
+    // A consumer that dropped the second event of the first batch sees the gap
+    let kept = first.iter().next().unwrap();
+    let next = second.iter().next().unwrap();
+    assert_eq!(next.seq, 2);
+    assert_eq!(next.missed_since(kept.seq), 1);
+    assert_eq!(next.missed_since(1), 0);
+}
+
+#[test]
+fn test_full_resync_reports_every_subscribed_pin() {
+    let mut watcher = InputWatcher::new().with_port(Port::Port1);
+    watcher.update(0x00_05_00);
+    watcher.request_full_resync();
+    assert!(watcher.is_resync_pending());
+
+    // Nothing changed, yet every subscribed pin is reported with its level
+    let changes = watcher.update(0x00_05_00);
+    assert!(changes.is_resync());
+    assert_eq!(changes.changed_mask(), 0x00_FF_00);
+    let high: Vec<Pin> = changes
+        .iter()
+        .filter(|event| event.state == PinState::High)
+        .map(|event| event.pin)
+        .collect();
+    assert_eq!(high, [Pin::P10, Pin::P12]);
+    assert_eq!(watcher.next_seq(), 8);
+
+    assert!(!watcher.is_resync_pending());
+    assert!(watcher.update(0x00_05_00).is_empty());
+}
+
+#[test]
+fn test_port_activity_counts_reported_transitions() {
+    let mut watcher = InputWatcher::new()
+        .with_port(Port::Port0)
+        .with_pin(Pin::P10);
+    watcher.update(0x00_00_0F);
+    // The baseline is not a transition
+    assert!(watcher.port_activity(Port::Port0).is_idle());
+
+    // P00-P01 fall, P04-P05 rise; P11 is not subscribed
+    watcher.update(0x00_02_3C);
+    watcher.update(0x00_03_3D);
+    assert_eq!(
+        watcher.port_activity(Port::Port0),
+        PortActivity {
+            rising: 3,
+            falling: 2,
+        }
+    );
+    assert_eq!(
+        watcher.port_activity(Port::Port1),
+        PortActivity {
+            rising: 1,
+            falling: 0,
+        }
+    );
+    assert!(watcher.port_activity(Port::Port2).is_idle());
+
+    // Resync events are not transitions
+    watcher.request_full_resync();
+    watcher.update(0x00_03_3D);
+    assert_eq!(watcher.take_port_activity(Port::Port0).total(), 5);
+    assert!(watcher.port_activity(Port::Port0).is_idle());
+    assert_eq!(watcher.port_activity(Port::Port1).rising, 1);
+    watcher.reset_activity();
+    assert!(watcher.port_activity(Port::Port1).is_idle());
+}
+
+#[test]
+fn test_inversion_change_is_not_a_transition() {
+    let mut watcher = InputWatcher::new().with_port(Port::Port0);
+    watcher.update_with_inversion(0x00_00_01, u32::MAX, 0);
+
+    // Inverting P00 flips its reported level although the pin did not move
+    let changes = watcher.update_with_inversion(0x00_00_00, u32::MAX, 0x01);
+    assert!(changes.is_resync());
+    assert_eq!(changes.changed_mask(), 0xFF);
+    assert!(watcher.port_activity(Port::Port0).is_idle());
+
+    // A real edge on the inverted pin is reported with the inverted level
+    let changes = watcher.update_with_inversion(0x00_00_01, u32::MAX, 0x01);
+    assert!(!changes.is_resync());
+    assert_eq!(changes.changed_mask(), 0x01);
+    assert_eq!(watcher.port_activity(Port::Port0).rising, 1);
+
+    // Inversion changes on unsubscribed pins go unnoticed
+    assert!(
+        watcher
+            .update_with_inversion(0x00_00_01, u32::MAX, 0x01_00_01)
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_latched_changes_persist_until_acknowledged() {
+    let mut watcher = InputWatcher::new()
+        .with_port(Port::Port0)
+        .with_latching(true);
+    assert!(watcher.is_latching());
+    watcher.update(0x00_00_00);
+
+    // A pulse on P00 and a press on P07, seen by two reads in quick succession
+    watcher.update(0x00_00_81);
+    watcher.update(0x00_00_80);
+    // Further reads without change keep the flags; P00 latched once for both edges
+    assert!(watcher.update(0x00_00_80).is_empty());
+    assert_eq!(watcher.latched_mask(), 0x00_00_81);
+    assert!(watcher.is_latched(Pin::P00));
+
+    assert!(watcher.acknowledge(Pin::P00));
+    assert!(!watcher.acknowledge(Pin::P00));
+    assert_eq!(watcher.latched_mask(), 0x00_00_80);
+
+    // Resyncs and unsubscribed pins latch nothing
+    watcher.request_full_resync();
+    watcher.update(0x00_01_80);
+    assert_eq!(watcher.acknowledge_mask(0xFF_FF_FF), 0x00_00_80);
+    assert_eq!(watcher.latched_mask(), 0);
+
+    // Without latching nothing is kept
+    let mut watcher = watcher.with_latching(false);
+    watcher.update(0x00_01_81);
+    assert_eq!(watcher.latched_mask(), 0);
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_poll_input_events_sync() {
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x80], vec![0xFF, 0xFF, 0xFF]),
+        I2cTransaction::write_read(address, vec![0x80], vec![0xFE, 0xFF, 0xEF]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    let mut watcher = InputWatcher::new().with_port(Port::Port2);
+
+    assert!(tca.poll_input_events(&mut watcher).unwrap().is_empty());
+    let changes = tca.poll_input_events(&mut watcher).unwrap();
+    assert_eq!(changes.changed_mask(), 1 << Pin::P24 as u8);
+    assert_eq!(
+        changes.iter().next(),
+        Some(InputEvent {
+            pin: Pin::P24,
+            state: PinState::Low,
+            seq: 0,
+        })
+    );
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[cfg(feature = "interrupt-mask")]
+#[test]
+fn test_poll_input_events_follows_interrupt_mask_sync() {
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x80], vec![0x00, 0x00, 0x00]),
+        // P00 masked below: its change only moves the baseline
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x10]),
+        I2cTransaction::write(address, vec![0x01]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x03, 0x00, 0x01]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x10]),
+        I2cTransaction::write(address, vec![0x00]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x03, 0x00, 0x01]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    let mut watcher = InputWatcher::new()
+        .with_port(Port::Port0)
+        .with_port(Port::Port2)
+        .with_interrupt_mask(true);
+    assert!(watcher.follows_interrupt_mask());
+
+    assert!(tca.poll_input_events(&mut watcher).unwrap().is_empty());
+    tca.set_port_interrupt_mask(Port::Port0, 0x01).unwrap();
+    // Port 2 mask is not cached, so its pins count as enabled
+    assert_eq!(tca.enabled_interrupts(), 0xFF_FF_FE);
+    let changes = tca.poll_input_events(&mut watcher).unwrap();
+    assert_eq!(
+        changes.changed_mask(),
+        1 << Pin::P01 as u8 | 1 << Pin::P20 as u8
+    );
+
+    tca.set_port_interrupt_mask(Port::Port0, 0x00).unwrap();
+    assert!(tca.poll_input_events(&mut watcher).unwrap().is_empty());
+
+    i2c_mock.done();
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_event_dispatcher_routes_by_pin_and_port() {
+    use tca6424::EventDispatcher;
+
+    let mut watcher = InputWatcher::new()
+        .with_port(Port::Port1)
+        .with_pin(Pin::P00);
+    let mut seen = Vec::new();
+    let mut port1_changes = 0;
+    {
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher.on_pin(Pin::P00, |event| seen.push(event));
+        dispatcher.on_port(Port::Port1, |_| port1_changes += 1);
+
+        watcher.update(0x00_00_00);
+        let changes = watcher.update(0x00_03_01);
+        assert_eq!(changes.to_vec().len(), 3);
+        dispatcher.dispatch(&changes);
+    }
+    assert_eq!(
+        seen,
+        [InputEvent {
+            pin: Pin::P00,
+            state: PinState::High,
+            seq: 0,
+        }]
+    );
+    assert_eq!(port1_changes, 2);
+}
+
+#[cfg(not(feature = "async"))]
+#[cfg(feature = "polarity")]
+#[test]
+fn test_poll_input_events_follows_polarity_inversion_sync() {
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x80], vec![0x00, 0x00, 0x00]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x08]),
+        I2cTransaction::write(address, vec![0x01]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x01, 0x00, 0x00]),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x01, 0x00, 0x00]),
+        // Reading the inversion records it as well
+        I2cTransaction::write_read(address, vec![0x0A], vec![0x80]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    let mut watcher = InputWatcher::new().with_port(Port::Port0);
+
+    assert!(tca.poll_input_events(&mut watcher).unwrap().is_empty());
+    tca.set_port_polarity_inversion(Port::Port0, 0x01).unwrap();
+    assert_eq!(tca.inverted_inputs(), 0x00_00_01);
+    let changes = tca.poll_input_events(&mut watcher).unwrap();
+    assert!(changes.is_resync());
+    assert!(watcher.port_activity(Port::Port0).is_idle());
+    assert!(tca.poll_input_events(&mut watcher).unwrap().is_empty());
+
+    assert_eq!(tca.get_port_polarity_inversion(Port::Port2).unwrap(), 0x80);
+    assert_eq!(tca.inverted_inputs(), 0x80_00_01);
+
+    i2c_mock.done();
+}
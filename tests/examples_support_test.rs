@@ -0,0 +1,38 @@
+#![cfg(all(feature = "examples-support", not(feature = "async")))]
+
+mod common;
+
+use common::write;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::examples_support;
+
+#[test]
+fn test_init_scan_and_drive() {
+    let address = 0x22;
+    let mut expectations = Vec::new();
+    // Init: output latches low first, then P00-P07 become outputs
+    for (command, data) in [
+        (0x04, 0x00),
+        (0x05, 0x00),
+        (0x06, 0x00),
+        (0x0C, 0x00),
+        (0x0D, 0xFF),
+        (0x0E, 0xFF),
+    ] {
+        expectations.extend(write(address, command, &[data]));
+    }
+    expectations.push(I2cTransaction::write_read(address, vec![0x00], vec![0x12]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x01], vec![0x34]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x02], vec![0x56]));
+    expectations.extend(write(address, 0x04, &[0x34]));
+    expectations.extend(write(address, 0x05, &[0x56]));
+    expectations.extend(write(address, 0x06, &[0x00]));
+    let mut i2c_mock = I2cMock::new(&expectations);
+
+    let mut tca = examples_support::init(&mut i2c_mock, address, 0x00_00FF).unwrap();
+    let levels = examples_support::scan_inputs(&mut tca).unwrap();
+    assert_eq!(levels, 0x56_34_12);
+    examples_support::drive_outputs(&mut tca, levels >> 8).unwrap();
+
+    i2c_mock.done();
+}
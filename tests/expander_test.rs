@@ -0,0 +1,63 @@
+#![cfg(all(feature = "testing", not(feature = "async")))]
+
+use tca6424::errors::Error;
+use tca6424::testing::RegisterModel;
+use tca6424::{DEFAULT_ADDRESS, Expander, PinDirection, PinState, Tca6424};
+
+/// Application logic written only against the trait: mirrors input `from` onto output `to`.
+fn mirror<E: Expander>(expander: &mut E, from: u8, to: u8) -> Result<PinState, E::Error> {
+    expander.set_direction(to, PinDirection::Output)?;
+    let level = expander.input(from)?;
+    expander.set_output(to, level)?;
+    Ok(level)
+}
+
+#[test]
+fn test_expander_on_simulator() {
+    let mut tca = Tca6424::new(RegisterModel::new(), DEFAULT_ADDRESS).unwrap();
+    tca.model_mut().set_pin_levels(1 << 3);
+
+    assert_eq!(mirror(&mut tca, 3, 20), Ok(PinState::High));
+    assert_eq!(tca.direction(20), Ok(PinDirection::Output));
+    assert_eq!(tca.output(20), Ok(PinState::High));
+    assert_eq!(tca.model().pin_levels() & (1 << 20), 1 << 20);
+
+    assert_eq!(
+        tca.set_output(24, PinState::Low),
+        Err(Error::InvalidRegisterOrPin)
+    );
+    assert_eq!(<Tca6424<RegisterModel> as Expander>::PIN_COUNT, 24);
+}
+
+#[cfg(feature = "tca6416")]
+#[test]
+fn test_expander_on_tca6416() {
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+    use tca6424::{TCA6416_DEFAULT_ADDRESS, Tca6416};
+
+    let address = TCA6416_DEFAULT_ADDRESS;
+    let expectations = [
+        // Pin 9 (P11) becomes an output
+        I2cTransaction::write_read(address, vec![0x07], vec![0xFF]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x07]),
+        I2cTransaction::write(address, vec![0xFD]),
+        I2cTransaction::transaction_end(address),
+        // Pin 1 (P01) is high
+        I2cTransaction::write_read(address, vec![0x00], vec![0x02]),
+        // Pin 9 is driven high
+        I2cTransaction::write_read(address, vec![0x03], vec![0x00]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x03]),
+        I2cTransaction::write(address, vec![0x02]),
+        I2cTransaction::transaction_end(address),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6416::new(&mut i2c_mock, address).unwrap();
+
+    assert_eq!(mirror(&mut tca, 1, 9), Ok(PinState::High));
+    // Pin 16 does not exist on the TCA6416A; no transfer is made
+    assert_eq!(tca.input(16), Err(Error::InvalidRegisterOrPin));
+
+    i2c_mock.done();
+}
@@ -0,0 +1,88 @@
+#![cfg(feature = "fail-safe")]
+
+#[cfg(all(not(feature = "async"), feature = "hot-plug"))]
+use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+#[cfg(all(not(feature = "async"), feature = "hot-plug"))]
+use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+#[cfg(all(not(feature = "async"), feature = "hot-plug"))]
+use embedded_hal_mock::eh1::i2c::Transaction as I2cTransaction;
+#[cfg(all(not(feature = "async"), feature = "hot-plug"))]
+use tca6424::errors::Error;
+#[cfg(all(not(feature = "async"), feature = "hot-plug"))]
+use tca6424::{FailSafeFault, Pin, Port};
+use tca6424::{FailSafePolicy, FailSafeTriggers};
+
+#[test]
+fn test_fail_safe_policy_masks() {
+    let policy = FailSafePolicy::new(0x0100_0003, 0x0000_0006, FailSafeTriggers::TIMEOUT);
+    assert_eq!(policy.mask_high(), 0x01);
+    assert_eq!(policy.mask_low(), 0x06);
+    assert_eq!(policy.mask(), 0x07);
+    assert_eq!(policy.triggers(), FailSafeTriggers::TIMEOUT);
+}
+
+#[cfg(all(not(feature = "async"), feature = "hot-plug"))]
+#[test]
+fn test_fail_safe_latch_on_device_offline_sync() {
+    let address = 0x22;
+    let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+
+    let expectations = [
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0x5A]),
+        I2cTransaction::transaction_end(address),
+        // A single NACK is a bus error, which the policy ignores
+        I2cTransaction::write_read(address, vec![0x00], vec![0x00]).with_error(nack),
+        // The second takes the device offline and engages the latch
+        I2cTransaction::write_read(address, vec![0x00], vec![0x00]).with_error(nack),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0x59]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x0C]),
+        I2cTransaction::write(address, vec![0xFC]),
+        I2cTransaction::transaction_end(address),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    tca.enable_hot_plug(2);
+    tca.set_fail_safe(Some(FailSafePolicy::new(
+        1 << Pin::P00 as u8,
+        1 << Pin::P01 as u8,
+        FailSafeTriggers::DEVICE_OFFLINE,
+    )))
+    .unwrap();
+
+    tca.set_port_output(Port::Port0, 0x5A).unwrap();
+    assert!(tca.get_port_input_state(Port::Port0).is_err());
+    assert!(!tca.is_faulted());
+    assert!(tca.get_port_input_state(Port::Port0).is_err());
+    assert!(tca.is_faulted());
+
+    let fault = FailSafeFault {
+        cause: FailSafeTriggers::DEVICE_OFFLINE,
+        applied: true,
+    };
+    assert_eq!(tca.fail_safe_fault(), Some(fault));
+    assert_eq!(
+        tca.cache().get(tca6424::RegisterKind::Output, Port::Port0),
+        Some(0x59)
+    );
+
+    // Writes are refused without touching the bus until the latch is cleared
+    assert_eq!(
+        tca.set_port_output(Port::Port0, 0xFF),
+        Err(Error::FailSafeLatched)
+    );
+    assert_eq!(tca.clear_fault(), Some(fault));
+    assert!(!tca.is_faulted());
+    assert_eq!(
+        tca.set_port_output(Port::Port0, 0xFF),
+        Err(Error::DeviceOffline)
+    );
+
+    i2c_mock.done();
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "fallback")]
+
+use tca6424::{InputFallback, Pin, PinState};
+
+#[test]
+fn test_input_fallback_builder() {
+    let fallback = InputFallback::new(0xFF00_0001)
+        .with_pin(Pin::P00, PinState::Low)
+        .with_pin(Pin::P27, PinState::High);
+    assert_eq!(fallback.values(), 0x80_0000);
+    assert_eq!(fallback.retries(), 0);
+    assert_eq!(fallback.with_retries(3).retries(), 3);
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_failed_reads_use_fallback_sync() {
+    use embedded_hal::i2c::ErrorKind;
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+    use tca6424::{Port, Tca6424};
+
+    let address = 0x22;
+    let expectations = [
+        // Without a fallback the error is returned
+        I2cTransaction::write_read(address, vec![0x01], vec![0x00]).with_error(ErrorKind::Other),
+        // The first attempt fails, the retry succeeds
+        I2cTransaction::write_read(address, vec![0x01], vec![0x00]).with_error(ErrorKind::Other),
+        I2cTransaction::write_read(address, vec![0x01], vec![0x5A]),
+        // Both attempts fail: fallback values
+        I2cTransaction::write_read(address, vec![0x02], vec![0x00]).with_error(ErrorKind::Other),
+        I2cTransaction::write_read(address, vec![0x02], vec![0x00]).with_error(ErrorKind::Other),
+        // Recovery clears degraded mode
+        I2cTransaction::write_read(address, vec![0x02], vec![0x00]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    assert!(tca.get_port_input_state(Port::Port1).is_err());
+    assert!(!tca.is_degraded());
+
+    let fallback = InputFallback::new(0)
+        .with_pin(Pin::P21, PinState::High)
+        .with_retries(1);
+    tca.set_input_fallback(fallback);
+    assert_eq!(tca.input_fallback(), Some(fallback));
+    assert_eq!(tca.get_port_input_state(Port::Port1).unwrap(), 0x5A);
+    assert!(!tca.is_degraded());
+
+    assert_eq!(tca.get_pin_input_state(Pin::P21).unwrap(), PinState::High);
+    assert!(tca.is_degraded());
+    assert_eq!(tca.fallback_count(), 1);
+
+    assert_eq!(tca.get_pin_input_state(Pin::P21).unwrap(), PinState::Low);
+    assert!(!tca.is_degraded());
+
+    tca.clear_input_fallback();
+    assert_eq!(tca.input_fallback(), None);
+    assert_eq!(tca.fallback_count(), 0);
+    i2c_mock.done();
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "helpers")]
+
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::errors::{ConfigError, Error};
+use tca6424::{FanBank, FanBankConfig, FanEvent, FanPins, Pin};
+
+const FANS: [FanPins; 2] = [
+    FanPins {
+        enable: Pin::P00,
+        fault: Pin::P20,
+    },
+    FanPins {
+        enable: Pin::P01,
+        fault: Pin::P21,
+    },
+];
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_fan_bank_sync() {
+    let address = 0x22;
+    let expectations = [
+        // init: fault pins inputs, enables latched low, then outputs
+        I2cTransaction::write_read(address, vec![0x0E], vec![0xFF]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x0E]),
+        I2cTransaction::write(address, vec![0xFF]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x04], vec![0xFF]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0xFC]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x0C], vec![0xFF]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x0C]),
+        I2cTransaction::write(address, vec![0xFC]),
+        I2cTransaction::transaction_end(address),
+        // set_fan(1, true)
+        I2cTransaction::write_read(address, vec![0x04], vec![0xFC]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0xFE]),
+        I2cTransaction::transaction_end(address),
+        // poll: fan 1 faults (P21 low)
+        I2cTransaction::write_read(address, vec![0x02], vec![0xFD]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    let mut fans = FanBank::new(&mut tca, FANS, FanBankConfig::default()).unwrap();
+
+    fans.init(&mut tca).unwrap();
+    fans.set_fan(&mut tca, 1, true).unwrap();
+    assert!(matches!(
+        fans.set_fan(&mut tca, 2, true),
+        Err(Error::InvalidRegisterOrPin)
+    ));
+
+    let status = fans.poll(&mut tca).unwrap();
+    assert_eq!(status.enabled(), 0b10);
+    assert_eq!(status.faulted(), 0b10);
+    assert_eq!(status.bits(), 0x0002_0002);
+    assert_eq!(fans.next_event(), Some(FanEvent::Fault(1)));
+    assert_eq!(fans.next_event(), None);
+
+    fans.release(&mut tca);
+    assert_eq!(tca.claimed_mask(), 0);
+    i2c_mock.done();
+}
+
+#[test]
+fn test_fan_bank_fault_events() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+    let mut fans = FanBank::new(&mut tca, FANS, FanBankConfig::default()).unwrap();
+
+    // Both fault lines low, then fan 0 recovers
+    fans.process(0);
+    fans.process(1 << Pin::P20 as u8);
+    assert_eq!(fans.next_event(), Some(FanEvent::Fault(0)));
+    assert_eq!(fans.next_event(), Some(FanEvent::Fault(1)));
+    assert_eq!(fans.next_event(), Some(FanEvent::Recovered(0)));
+    assert!(!fans.status().is_ok());
+
+    // Pins used twice are rejected
+    let reused = [FanPins {
+        enable: Pin::P10,
+        fault: Pin::P10,
+    }];
+    assert!(matches!(
+        FanBank::new(&mut tca, reused, FanBankConfig::default()),
+        Err(ConfigError::PinAlreadyClaimed(Pin::P10))
+    ));
+
+    fans.release(&mut tca);
+    i2c_mock.done();
+}
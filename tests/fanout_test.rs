@@ -0,0 +1,54 @@
+use tca6424::{EventFanout, InputWatcher, Pin, PinState, Port};
+
+#[test]
+fn test_subscribers_read_independently() {
+    let mut watcher = InputWatcher::new().with_port(Port::Port1);
+    let mut fanout: EventFanout<8, 2> = EventFanout::new();
+    let logger = fanout.subscribe().unwrap();
+    let control = fanout.subscribe().unwrap();
+    assert_eq!(fanout.subscribe(), None);
+    assert_eq!(fanout.subscribers(), 2);
+
+    watcher.update(0);
+    fanout.publish_changes(&watcher.update(0x00_81_00));
+    let event = fanout.next(control).unwrap();
+    assert_eq!((event.pin, event.state), (Pin::P10, PinState::High));
+    assert_eq!(fanout.pending(control), 1);
+    assert_eq!(fanout.pending(logger), 2);
+
+    fanout.publish_changes(&watcher.update(0x00_80_00));
+    let seqs = |fanout: &mut EventFanout<8, 2>, id| {
+        core::iter::from_fn(|| fanout.next(id))
+            .map(|event| event.seq)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(seqs(&mut fanout, logger), [0, 1, 2]);
+    assert_eq!(seqs(&mut fanout, control), [1, 2]);
+    assert_eq!(fanout.next(logger), None);
+}
+
+#[test]
+fn test_lagging_subscriber_skips_overwritten_events() {
+    let mut watcher = InputWatcher::new().with_pin(Pin::P00);
+    let mut fanout: EventFanout<4, 2> = EventFanout::new();
+    let slow = fanout.subscribe().unwrap();
+    watcher.update(0);
+    for step in 1..=6 {
+        fanout.publish_changes(&watcher.update(step & 1));
+    }
+
+    // Events 0 and 1 were overwritten; the gap shows in the sequence numbers
+    assert_eq!(fanout.pending(slow), 4);
+    let first = fanout.next(slow).unwrap();
+    assert_eq!(first.seq, 2);
+    assert_eq!(first.missed_since(0), 1);
+
+    // A late subscriber only sees new events; a freed slot can be reused
+    let late = fanout.subscribe().unwrap();
+    assert_eq!(fanout.next(late), None);
+    assert!(fanout.unsubscribe(late));
+    assert!(!fanout.unsubscribe(late));
+    assert_eq!(fanout.pending(late), 0);
+    assert_eq!(fanout.subscribe(), Some(late));
+    assert_eq!(late.index(), 1);
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "helpers")]
+
+use tca6424::{ButtonGesture, GestureConfig, GestureDetector};
+
+fn drain(detector: &mut GestureDetector) -> Vec<ButtonGesture> {
+    core::iter::from_fn(|| detector.next_gesture()).collect()
+}
+
+#[test]
+fn test_gesture_detector() {
+    let mut detector = GestureDetector::new(GestureConfig {
+        long_press_ms: 500,
+        double_click_ms: 200,
+    });
+
+    // Single click: reported once the double-click window has passed
+    detector.update(true, 0);
+    detector.update(false, 100);
+    detector.update(false, 299);
+    assert_eq!(drain(&mut detector), []);
+    detector.update(false, 300);
+    assert_eq!(drain(&mut detector), [ButtonGesture::Click]);
+
+    // Double click
+    detector.update(true, 1000);
+    detector.update(false, 1050);
+    detector.update(true, 1150);
+    detector.update(false, 1200);
+    detector.update(false, 2000);
+    assert_eq!(drain(&mut detector), [ButtonGesture::DoubleClick]);
+
+    // Long press: reported while held, nothing on release
+    detector.update(true, 3000);
+    detector.update(true, 3499);
+    assert_eq!(drain(&mut detector), []);
+    detector.update(true, 3500);
+    assert_eq!(drain(&mut detector), [ButtonGesture::LongPress]);
+    detector.update(false, 4000);
+    detector.update(false, 5000);
+    assert_eq!(drain(&mut detector), []);
+
+    // Click followed by a long press
+    detector.update(true, 6000);
+    detector.update(false, 6050);
+    detector.update(true, 6100);
+    detector.update(true, 6600);
+    assert_eq!(
+        drain(&mut detector),
+        [ButtonGesture::Click, ButtonGesture::LongPress]
+    );
+}
+
+#[test]
+fn test_gesture_detector_without_double_click() {
+    let mut detector = GestureDetector::new(GestureConfig {
+        long_press_ms: 500,
+        double_click_ms: 0,
+    });
+    detector.update(true, 0);
+    detector.update(false, 50);
+    assert_eq!(detector.next_gesture(), Some(ButtonGesture::Click));
+    detector.update(true, 60);
+    detector.update(false, 100);
+    assert_eq!(detector.next_gesture(), Some(ButtonGesture::Click));
+}
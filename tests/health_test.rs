@@ -0,0 +1,105 @@
+#![cfg(feature = "diagnostics")]
+
+use embedded_hal::i2c::ErrorKind;
+use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+use embedded_hal_mock::eh1::i2c::Transaction as I2cTransaction;
+use tca6424::{HealthStatus, Port, RegisterKind};
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_health_check_healthy_sync() {
+    let address = 0x22;
+
+    let expectations = [
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x0C]),
+        I2cTransaction::write(address, vec![0xF0]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x8C], vec![0xF0, 0xFF, 0xFF]),
+        I2cTransaction::write_read(address, vec![0x84], vec![0xFF, 0xFF, 0xFF]),
+        I2cTransaction::write_read(address, vec![0x88], vec![0x00, 0x00, 0x00]),
+        I2cTransaction::write_read(address, vec![0x90], vec![0xFF, 0xFF, 0xFF]),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    tca.set_port_direction(Port::Port0, 0xF0).unwrap();
+    assert_eq!(tca.health_check(), HealthStatus::Healthy);
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_health_check_reset_and_mismatch_sync() {
+    let address = 0x22;
+
+    let expectations = [
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x0C]),
+        I2cTransaction::write(address, vec![0xF0]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x05]),
+        I2cTransaction::write(address, vec![0x0F]),
+        I2cTransaction::transaction_end(address),
+        // First check: configuration at defaults
+        I2cTransaction::write_read(address, vec![0x8C], vec![0xFF, 0xFF, 0xFF]),
+        // Second check: configuration fine, output latch differs
+        I2cTransaction::write_read(address, vec![0x8C], vec![0xF0, 0xFF, 0xFF]),
+        I2cTransaction::write_read(address, vec![0x84], vec![0xFF, 0x0E, 0xFF]),
+        I2cTransaction::write_read(address, vec![0x88], vec![0x00, 0x00, 0x00]),
+        I2cTransaction::write_read(address, vec![0x90], vec![0xFF, 0xFF, 0xFF]),
+        // Third check: no response
+        I2cTransaction::write_read(address, vec![0x8C], vec![0x00, 0x00, 0x00])
+            .with_error(ErrorKind::Other),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    tca.set_port_direction(Port::Port0, 0xF0).unwrap();
+    tca.set_port_output(Port::Port1, 0x0F).unwrap();
+    assert_eq!(tca.health_check(), HealthStatus::ResetDetected);
+
+    match tca.health_check() {
+        HealthStatus::ConfigMismatch(change) => {
+            assert_eq!(change.kind, RegisterKind::Output);
+            assert_eq!(change.port, Port::Port1);
+            assert_eq!(change.before, 0x0F);
+            assert_eq!(change.after, 0x0E);
+        }
+        other => panic!("unexpected status {:?}", other),
+    }
+
+    assert_eq!(tca.health_check(), HealthStatus::Unresponsive);
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_check_output_readback_sync() {
+    let address = 0x22;
+    let expectations = [
+        // P00-P03 outputs, commanded 0b0101; P02 inverted
+        I2cTransaction::write_read(address, vec![0x8C], vec![0xF0, 0xFF, 0xFF]),
+        I2cTransaction::write_read(address, vec![0x84], vec![0x05, 0x00, 0x00]),
+        I2cTransaction::write_read(address, vec![0x88], vec![0x04, 0x00, 0x00]),
+        // P00 reads high, P01 shorted high, P02 high (inverted to low), P03 low; inputs differ freely
+        I2cTransaction::write_read(address, vec![0x80], vec![0xF3, 0x12, 0x34]),
+        I2cTransaction::write_read(address, vec![0x8C], vec![0xF0, 0xFF, 0xFF]),
+        I2cTransaction::write_read(address, vec![0x84], vec![0x05, 0x00, 0x00]),
+        I2cTransaction::write_read(address, vec![0x88], vec![0x04, 0x00, 0x00]),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x01, 0x00, 0x00]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    // Input ^ inversion = 0b0111 against commanded 0b0101: P01 mismatches
+    assert_eq!(tca.check_output_readback().unwrap(), tca6424::pins!(P01));
+    assert_eq!(tca.check_output_readback().unwrap(), 0);
+
+    i2c_mock.done();
+}
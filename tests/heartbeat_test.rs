@@ -0,0 +1,51 @@
+#![cfg(feature = "helpers")]
+
+mod common;
+
+use common::write;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{Heartbeat, Pin};
+
+#[test]
+fn test_heartbeat_process() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+    let mut heartbeat = Heartbeat::new(&mut tca, Pin::P00, 1_000).unwrap();
+    assert!(Heartbeat::new(&mut tca, Pin::P00, 1_000).is_err());
+
+    assert!(heartbeat.process(u32::MAX - 100));
+    assert!(heartbeat.process(u32::MAX));
+    // Timestamps wrap around
+    assert!(!heartbeat.process(400));
+    assert!(heartbeat.process(900));
+    heartbeat.release(&mut tca);
+    assert!(!tca.is_claimed(Pin::P00));
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_heartbeat_tick_sync() {
+    let address = 0x22;
+    let mut expectations = vec![I2cTransaction::write_read(address, vec![0x04], vec![0x80])];
+    expectations.extend(write(address, 0x04, &[0x80]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x0C], vec![0xFF]));
+    expectations.extend(write(address, 0x0C, &[0xFE]));
+    // Toggles are single writes merged into the cached Output register
+    expectations.extend(write(address, 0x04, &[0x81]));
+    expectations.extend(write(address, 0x04, &[0x80]));
+    expectations.extend(write(address, 0x04, &[0x81]));
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    let mut heartbeat = Heartbeat::new(&mut tca, Pin::P00, 1_000).unwrap();
+
+    heartbeat.init(&mut tca).unwrap();
+    assert!(heartbeat.tick(&mut tca, 0).unwrap());
+    assert!(!heartbeat.tick(&mut tca, 100).unwrap());
+    assert!(heartbeat.tick(&mut tca, 500).unwrap());
+    assert!(!heartbeat.tick(&mut tca, 999).unwrap());
+    assert!(heartbeat.tick(&mut tca, 1_000).unwrap());
+
+    i2c_mock.done();
+}
@@ -0,0 +1,158 @@
+#![cfg(feature = "hot-plug")]
+
+use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+use embedded_hal_mock::eh1::i2c::Transaction as I2cTransaction;
+use tca6424::errors::Error;
+use tca6424::{LinkState, Port};
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_hot_plug_offline_and_reattach_sync() {
+    let address = 0x22;
+    let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+
+    let expectations = [
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0x5A]),
+        I2cTransaction::transaction_end(address),
+        // Module unplugged: two NACKs take the device offline
+        I2cTransaction::write_read(address, vec![0x00], vec![0x00]).with_error(nack),
+        I2cTransaction::write_read(address, vec![0x00], vec![0x00]).with_error(nack),
+        // Reinserted: probe, then restore the cached output
+        I2cTransaction::write_read(address, vec![0x8C], vec![0xFF, 0xFF, 0xFF]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0x5A]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x00], vec![0x81]),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    tca.enable_hot_plug(2);
+
+    tca.set_port_output(Port::Port0, 0x5A).unwrap();
+    assert!(matches!(
+        tca.get_port_input_state(Port::Port0),
+        Err(Error::I2c(_))
+    ));
+    assert_eq!(tca.link_state(), LinkState::Online);
+    assert!(matches!(
+        tca.get_port_input_state(Port::Port0),
+        Err(Error::I2c(_))
+    ));
+    assert_eq!(tca.link_state(), LinkState::Offline);
+
+    // Fails fast without touching the bus
+    assert!(matches!(
+        tca.set_port_output(Port::Port0, 0x00),
+        Err(Error::DeviceOffline)
+    ));
+
+    tca.try_reattach().unwrap();
+    assert_eq!(tca.link_state(), LinkState::Online);
+    assert_eq!(tca.get_port_input_state(Port::Port0).ok(), Some(0x81));
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_hot_plug_failed_reattach_stays_offline_sync() {
+    let address = 0x22;
+    let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x00], vec![0x00]).with_error(nack),
+        I2cTransaction::write_read(address, vec![0x8C], vec![0x00, 0x00, 0x00]).with_error(nack),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    tca.enable_hot_plug(1);
+
+    assert!(tca.get_port_input_state(Port::Port0).is_err());
+    assert_eq!(tca.link_state(), LinkState::Offline);
+    assert!(tca.try_reattach().is_err());
+    assert_eq!(tca.link_state(), LinkState::Offline);
+
+    tca.disable_hot_plug();
+    assert_eq!(tca.link_state(), LinkState::Online);
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_auto_reinit_after_nacks_sync() {
+    let address = 0x22;
+    let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+
+    let expectations = [
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0x5A]),
+        I2cTransaction::transaction_end(address),
+        // Power cycle: the second NACK triggers probe + restore inside the failing call
+        I2cTransaction::write_read(address, vec![0x00], vec![0x00]).with_error(nack),
+        I2cTransaction::write_read(address, vec![0x00], vec![0x00]).with_error(nack),
+        I2cTransaction::write_read(address, vec![0x8C], vec![0xFF, 0xFF, 0xFF]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0x5A]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x00], vec![0x81]),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    assert_eq!(tca.auto_reinit_count(), None);
+    tca.enable_auto_reinit(2, 3);
+
+    tca.set_port_output(Port::Port0, 0x5A).unwrap();
+    assert!(tca.get_port_input_state(Port::Port0).is_err());
+    assert_eq!(tca.auto_reinit_count(), Some(0));
+    assert!(tca.get_port_input_state(Port::Port0).is_err());
+    assert_eq!(tca.auto_reinit_count(), Some(1));
+    assert_eq!(tca.get_port_input_state(Port::Port0).ok(), Some(0x81));
+    assert_eq!(tca.auto_reinit_budget(), Some(3));
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_auto_reinit_budget_sync() {
+    let address = 0x22;
+    let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+
+    let expectations = [
+        // Device absent: two failed probes exhaust the budget
+        I2cTransaction::write_read(address, vec![0x00], vec![0x00]).with_error(nack),
+        I2cTransaction::write_read(address, vec![0x8C], vec![0x00, 0x00, 0x00]).with_error(nack),
+        I2cTransaction::write_read(address, vec![0x00], vec![0x00]).with_error(nack),
+        I2cTransaction::write_read(address, vec![0x8C], vec![0x00, 0x00, 0x00]).with_error(nack),
+        I2cTransaction::write_read(address, vec![0x00], vec![0x00]).with_error(nack),
+        // Back: a successful transfer refills the budget
+        I2cTransaction::write_read(address, vec![0x00], vec![0x42]),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    tca.enable_auto_reinit(1, 2);
+
+    for _ in 0..3 {
+        assert!(tca.get_port_input_state(Port::Port0).is_err());
+    }
+    assert_eq!(tca.auto_reinit_budget(), Some(0));
+    assert_eq!(tca.auto_reinit_count(), Some(0));
+    assert_eq!(tca.get_port_input_state(Port::Port0).ok(), Some(0x42));
+    assert_eq!(tca.auto_reinit_budget(), Some(2));
+
+    tca.disable_auto_reinit();
+    assert_eq!(tca.auto_reinit_budget(), None);
+
+    i2c_mock.done();
+}
@@ -0,0 +1,59 @@
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{InputMap, Pin};
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_get_logical_inputs_applies_map_sync() {
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x80], vec![0x01, 0x00, 0x00]),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x01, 0x00, 0x04]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    // Identity by default
+    assert_eq!(tca.get_logical_inputs().unwrap(), 0x0000_0001);
+
+    // Logical 0 is active-low; logical 5 is rerouted to P22
+    tca.set_input_map(InputMap::new().invert(0).route(5, Pin::P22));
+    assert_eq!(tca.input_map().source(5), Some(Pin::P22));
+    assert_eq!(tca.get_logical_inputs().unwrap(), 0x0004_0020);
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[cfg(feature = "polarity")]
+#[test]
+fn test_read_logical_inputs_compensates_inversion_sync() {
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x08]),
+        I2cTransaction::write(address, vec![0x01]),
+        I2cTransaction::transaction_end(address),
+        // P00 is low on the pin, reported high through the inversion
+        I2cTransaction::write_read(address, vec![0x80], vec![0x01, 0x00, 0x00]),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x01, 0x00, 0x00]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    tca.set_port_polarity_inversion(tca6424::Port::Port0, 0x01)
+        .unwrap();
+    // Logical 0 is an active-low button, logical 1 an active-high sensor on P01
+    tca.set_input_map(InputMap::new().invert(0));
+
+    let inputs = tca.read_logical_inputs(true).unwrap();
+    assert!(inputs.is_asserted(0));
+    assert!(inputs.is_deasserted(1));
+    assert!(!inputs.is_asserted(24));
+    assert_eq!(inputs.asserted_mask(), 0x0000_0001);
+
+    // Without compensation the hardware inversion cancels the software one
+    let inputs = tca.read_logical_inputs(false).unwrap();
+    assert_eq!(inputs, tca6424::LogicalInputs::new(0));
+    assert_eq!(inputs.deasserted_mask(), 0x00FF_FFFF);
+
+    i2c_mock.done();
+}
@@ -0,0 +1,54 @@
+#![cfg(feature = "helpers")]
+
+use tca6424::{InputWatchdog, Pin, WatchdogEvent};
+
+#[test]
+fn test_input_watchdog_process() {
+    let mut watchdog = InputWatchdog::new(tca6424::pins!(P00, P21), 100);
+    watchdog.process(0, 1000);
+    watchdog.process(tca6424::pins!(P21), 1060);
+    assert_eq!(watchdog.next_event(), None);
+
+    // P00 never changed; P21 changed 50 ms ago
+    watchdog.process(tca6424::pins!(P21), 1110);
+    assert_eq!(
+        watchdog.next_event(),
+        Some(WatchdogEvent::StuckInput(Pin::P00))
+    );
+    assert_eq!(watchdog.next_event(), None);
+    assert!(watchdog.is_stuck(Pin::P00));
+
+    // Reported once, then recovered on the next edge; unwatched pins are ignored
+    watchdog.process(tca6424::pins!(P21, P17), 1150);
+    assert_eq!(watchdog.next_event(), None);
+    watchdog.process(tca6424::pins!(P00), 1170);
+    assert_eq!(
+        watchdog.next_event(),
+        Some(WatchdogEvent::Recovered(Pin::P00))
+    );
+    assert_eq!(watchdog.stuck_mask(), 0);
+
+    watchdog.reset();
+    watchdog.process(0, 5000);
+    assert_eq!(watchdog.stuck_mask(), 0);
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_input_watchdog_poll_sync() {
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x02], vec![0x01]),
+        I2cTransaction::write_read(address, vec![0x02], vec![0x01]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    let mut watchdog = InputWatchdog::new(tca6424::pins!(P20), 500);
+    assert_eq!(watchdog.poll(&mut tca, 0).unwrap(), 0);
+    assert_eq!(watchdog.poll(&mut tca, 500).unwrap(), tca6424::pins!(P20));
+
+    i2c_mock.done();
+}
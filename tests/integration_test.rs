@@ -1,3 +1,10 @@
+// The original tests are kept as written; they predate the clippy gate.
+#![allow(
+    clippy::useless_conversion,
+    clippy::bool_assert_comparison,
+    clippy::identity_op
+)]
+
 use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
 use embedded_hal_mock::eh1::i2c::Transaction as I2cTransaction;
 use tca6424::Port;
@@ -43,11 +50,17 @@ fn test_set_pin_direction_sync() {
     let expectations = [
         // Set P00 to Output (clear bit 0 in Config Port 0)
         I2cTransaction::write_read(address, vec![0x0C], vec![initial_config_port0]).into(), // Read Config Port 0
-        I2cTransaction::write(address, vec![0x0C, initial_config_port0 & !(1 << 0)]).into(), // Write Config Port 0 with bit 0 cleared
+        I2cTransaction::transaction_start(address), // Write Config Port 0 with bit 0 cleared
+        I2cTransaction::write(address, vec![0x0C]),
+        I2cTransaction::write(address, vec![initial_config_port0 & !(1 << 0)]),
+        I2cTransaction::transaction_end(address),
 
         // Set P17 to Input (set bit 7 in Config Port 1)
         I2cTransaction::write_read(address, vec![0x0D], vec![initial_config_port1]).into(), // Read Config Port 1
-        I2cTransaction::write(address, vec![0x0D, initial_config_port1 | (1 << 7)]).into(), // Write Config Port 1 with bit 7 set
+        I2cTransaction::transaction_start(address), // Write Config Port 1 with bit 7 set
+        I2cTransaction::write(address, vec![0x0D]),
+        I2cTransaction::write(address, vec![initial_config_port1 | (1 << 7)]),
+        I2cTransaction::transaction_end(address),
     ];
 
     let mut i2c_mock = I2cMock::new(&expectations);
@@ -70,10 +83,16 @@ async fn test_set_pin_direction_async() {
     let expectations = [
         // Set P00 to Output (clear bit 0 in Config Port 0)
         I2cTransaction::write_read(address, vec![0x0C], vec![initial_config_port0]), // Read Config Port 0
-        I2cTransaction::write(address, vec![0x0C, initial_config_port0 & !(1 << 0)]), // Write Config Port 0 with bit 0 cleared
+        I2cTransaction::transaction_start(address), // Write Config Port 0 with bit 0 cleared
+        I2cTransaction::write(address, vec![0x0C]),
+        I2cTransaction::write(address, vec![initial_config_port0 & !(1 << 0)]),
+        I2cTransaction::transaction_end(address),
         // Set P17 to Input (set bit 7 in Config Port 1)
         I2cTransaction::write_read(address, vec![0x0D], vec![initial_config_port1]), // Read Config Port 1
-        I2cTransaction::write(address, vec![0x0D, initial_config_port1 | (1 << 7)]), // Write Config Port 1 with bit 7 set
+        I2cTransaction::transaction_start(address), // Write Config Port 1 with bit 7 set
+        I2cTransaction::write(address, vec![0x0D]),
+        I2cTransaction::write(address, vec![initial_config_port1 | (1 << 7)]),
+        I2cTransaction::transaction_end(address),
     ];
 
     let mut i2c_mock = I2cMock::new(&expectations);
@@ -145,10 +164,16 @@ fn test_set_pin_output_sync() {
     let expectations = [
         // Set P00 to High (set bit 0 in Output Port 0)
         I2cTransaction::write_read(address, vec![0x04], vec![initial_output_port0]).into(), // Read Output Port 0
-        I2cTransaction::write(address, vec![0x04, initial_output_port0 | (1 << 0)]).into(), // Write Output Port 0 with bit 0 set
+        I2cTransaction::transaction_start(address), // Write Output Port 0 with bit 0 set
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![initial_output_port0 | (1 << 0)]),
+        I2cTransaction::transaction_end(address),
         // Set P00 to Low (clear bit 0 in Output Port 0)
         I2cTransaction::write_read(address, vec![0x04], vec![initial_output_port0 | (1 << 0)]).into(), // Read Output Port 0 (after previous write)
-        I2cTransaction::write(address, vec![0x04, initial_output_port0 & !(1 << 0)]).into(), // Write Output Port 0 with bit 0 cleared
+        I2cTransaction::transaction_start(address), // Write Output Port 0 with bit 0 cleared
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![initial_output_port0 & !(1 << 0)]),
+        I2cTransaction::transaction_end(address),
     ];
 
     let mut i2c_mock = I2cMock::new(&expectations);
@@ -162,6 +187,35 @@ fn test_set_pin_output_sync() {
     i2c_mock.done();
 }
 
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_set_pin_output_accepts_hal_states_sync() {
+    let address = 0x22;
+
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x05], vec![0x00]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x05]),
+        I2cTransaction::write(address, vec![0x08]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x05], vec![0x08]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x05]),
+        I2cTransaction::write(address, vec![0x00]),
+        I2cTransaction::transaction_end(address),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    // embedded-hal pin state and plain bool are accepted as-is
+    tca.set_pin_output(tca6424::Pin::P13, embedded_hal::digital::PinState::High)
+        .unwrap();
+    tca.set_pin_output(tca6424::Pin::P13, false).unwrap();
+
+    i2c_mock.done();
+}
+
 #[cfg(feature = "async")]
 #[tokio::test]
 async fn test_set_pin_output_async() {
@@ -171,10 +225,16 @@ async fn test_set_pin_output_async() {
     let expectations = [
         // Set P00 to High (set bit 0 in Output Port 0)
         I2cTransaction::write_read(address, vec![0x04], vec![initial_output_port0]), // Read Output Port 0
-        I2cTransaction::write(address, vec![0x04, initial_output_port0 | (1 << 0)]), // Write Output Port 0 with bit 0 set
+        I2cTransaction::transaction_start(address), // Write Output Port 0 with bit 0 set
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![initial_output_port0 | (1 << 0)]),
+        I2cTransaction::transaction_end(address),
         // Set P00 to Low (clear bit 0 in Output Port 0)
         I2cTransaction::write_read(address, vec![0x04], vec![initial_output_port0 | (1 << 0)]), // Read Output Port 0 (after previous write)
-        I2cTransaction::write(address, vec![0x04, initial_output_port0 & !(1 << 0)]), // Write Output Port 0 with bit 0 cleared
+        I2cTransaction::transaction_start(address), // Write Output Port 0 with bit 0 cleared
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![initial_output_port0 & !(1 << 0)]),
+        I2cTransaction::transaction_end(address),
     ];
 
     let mut i2c_mock = I2cMock::new(&expectations);
@@ -283,6 +343,7 @@ async fn test_get_pin_input_state_async() {
 }
 
 #[cfg(not(feature = "async"))]
+#[cfg(feature = "polarity")]
 #[test]
 fn test_set_pin_polarity_inversion_sync() {
     let address = 0x22;
@@ -291,10 +352,16 @@ fn test_set_pin_polarity_inversion_sync() {
     let expectations = [
         // Set P00 to invert (set bit 0 in Polarity Inversion Port 0)
         I2cTransaction::write_read(address, vec![0x08], vec![initial_polarity_port0]).into(), // Read Polarity Port 0
-        I2cTransaction::write(address, vec![0x08, initial_polarity_port0 | (1 << 0)]).into(), // Write Polarity Port 0 with bit 0 set
+        I2cTransaction::transaction_start(address), // Write Polarity Port 0 with bit 0 set
+        I2cTransaction::write(address, vec![0x08]),
+        I2cTransaction::write(address, vec![initial_polarity_port0 | (1 << 0)]),
+        I2cTransaction::transaction_end(address),
         // Set P00 to not invert (clear bit 0 in Polarity Inversion Port 0)
         I2cTransaction::write_read(address, vec![0x08], vec![initial_polarity_port0 | (1 << 0)]).into(), // Read Polarity Port 0 (after previous write)
-        I2cTransaction::write(address, vec![0x08, initial_polarity_port0 & !(1 << 0)]).into(), // Write Polarity Port 0 with bit 0 cleared
+        I2cTransaction::transaction_start(address), // Write Polarity Port 0 with bit 0 cleared
+        I2cTransaction::write(address, vec![0x08]),
+        I2cTransaction::write(address, vec![initial_polarity_port0 & !(1 << 0)]),
+        I2cTransaction::transaction_end(address),
     ];
 
     let mut i2c_mock = I2cMock::new(&expectations);
@@ -309,6 +376,7 @@ fn test_set_pin_polarity_inversion_sync() {
 }
 
 #[cfg(feature = "async")]
+#[cfg(feature = "polarity")]
 #[tokio::test]
 async fn test_set_pin_polarity_inversion_async() {
     let address = 0x22;
@@ -317,10 +385,16 @@ async fn test_set_pin_polarity_inversion_async() {
     let expectations = [
         // Set P00 to invert (set bit 0 in Polarity Inversion Port 0)
         I2cTransaction::write_read(address, vec![0x08], vec![initial_polarity_port0]), // Read Polarity Port 0
-        I2cTransaction::write(address, vec![0x08, initial_polarity_port0 | (1 << 0)]), // Write Polarity Port 0 with bit 0 set
+        I2cTransaction::transaction_start(address), // Write Polarity Port 0 with bit 0 set
+        I2cTransaction::write(address, vec![0x08]),
+        I2cTransaction::write(address, vec![initial_polarity_port0 | (1 << 0)]),
+        I2cTransaction::transaction_end(address),
         // Set P00 to not invert (clear bit 0 in Polarity Inversion Port 0)
         I2cTransaction::write_read(address, vec![0x08], vec![initial_polarity_port0 | (1 << 0)]), // Read Polarity Port 0 (after previous write)
-        I2cTransaction::write(address, vec![0x08, initial_polarity_port0 & !(1 << 0)]), // Write Polarity Port 0 with bit 0 cleared
+        I2cTransaction::transaction_start(address), // Write Polarity Port 0 with bit 0 cleared
+        I2cTransaction::write(address, vec![0x08]),
+        I2cTransaction::write(address, vec![initial_polarity_port0 & !(1 << 0)]),
+        I2cTransaction::transaction_end(address),
     ];
 
     let mut i2c_mock = I2cMock::new(&expectations);
@@ -331,6 +405,7 @@ async fn test_set_pin_polarity_inversion_async() {
 }
 
 #[cfg(not(feature = "async"))]
+#[cfg(feature = "polarity")]
 #[test]
 fn test_get_pin_polarity_inversion_sync() {
     let address = 0x22;
@@ -359,6 +434,7 @@ fn test_get_pin_polarity_inversion_sync() {
 }
 
 #[cfg(feature = "async")]
+#[cfg(feature = "polarity")]
 #[tokio::test]
 async fn test_get_pin_polarity_inversion_async() {
     let address = 0x22;
@@ -379,6 +455,41 @@ async fn test_get_pin_polarity_inversion_async() {
     i2c_mock.done();
 }
 
+#[cfg(not(feature = "async"))]
+#[cfg(feature = "polarity")]
+#[test]
+fn test_get_pin_input_raw_sync() {
+    let address = 0x22;
+    let expectations = [
+        // Inversion unknown: read it, then the input
+        I2cTransaction::write_read(address, vec![0x09], vec![0x04]),
+        I2cTransaction::write_read(address, vec![0x01], vec![0x00]),
+        // Inversion cached after a write
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x09]),
+        I2cTransaction::write(address, vec![0x00]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x01], vec![0x00]),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    // P12 reads low through the inversion: the pin is high
+    assert_eq!(
+        tca.get_pin_input_raw(tca6424::Pin::P12).unwrap(),
+        tca6424::PinState::High
+    );
+    tca.set_port_polarity_inversion(tca6424::Port::Port1, 0x00)
+        .unwrap();
+    assert_eq!(
+        tca.get_pin_input_raw(tca6424::Pin::P12).unwrap(),
+        tca6424::PinState::Low
+    );
+
+    i2c_mock.done();
+}
+
 #[test]
 fn simple_sync_test() {
     assert_eq!(1 + 1, 2);
@@ -395,7 +506,10 @@ fn test_set_port_direction_sync() {
 
     let expectations = [
         // Set Port0 direction
-        I2cTransaction::write(address, vec![0x0C, new_direction_mask]).into(), // Write Config Port 0
+        I2cTransaction::transaction_start(address), // Write Config Port 0
+        I2cTransaction::write(address, vec![0x0C]),
+        I2cTransaction::write(address, vec![new_direction_mask]),
+        I2cTransaction::transaction_end(address),
     ];
 
     let mut i2c_mock = I2cMock::new(&expectations);
@@ -415,7 +529,10 @@ async fn test_set_port_direction_async() {
 
     let expectations = [
         // Set Port0 direction
-        I2cTransaction::write(address, vec![0x0C, new_direction_mask]), // Write Config Port 0
+        I2cTransaction::transaction_start(address), // Write Config Port 0
+        I2cTransaction::write(address, vec![0x0C]),
+        I2cTransaction::write(address, vec![new_direction_mask]),
+        I2cTransaction::transaction_end(address),
     ];
 
     let mut i2c_mock = I2cMock::new(&expectations);
@@ -474,7 +591,10 @@ fn test_set_port_output_sync() {
 
     let expectations = [
         // Set Port0 output
-        I2cTransaction::write(address, vec![0x04, new_output_mask]).into(), // Write Output Port 0
+        I2cTransaction::transaction_start(address), // Write Output Port 0
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![new_output_mask]),
+        I2cTransaction::transaction_end(address),
     ];
 
     let mut i2c_mock = I2cMock::new(&expectations);
@@ -493,7 +613,10 @@ async fn test_set_port_output_async() {
 
     let expectations = [
         // Set Port0 output
-        I2cTransaction::write(address, vec![0x04, new_output_mask]), // Write Output Port 0
+        I2cTransaction::transaction_start(address), // Write Output Port 0
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![new_output_mask]),
+        I2cTransaction::transaction_end(address),
     ];
 
     let mut i2c_mock = I2cMock::new(&expectations);
@@ -585,6 +708,7 @@ async fn test_get_port_input_state_async() {
 }
 
 #[cfg(not(feature = "async"))]
+#[cfg(feature = "polarity")]
 #[test]
 fn test_set_port_polarity_inversion_sync() {
     let address = 0x22;
@@ -592,7 +716,10 @@ fn test_set_port_polarity_inversion_sync() {
 
     let expectations = [
         // Set Port0 polarity inversion
-        I2cTransaction::write(address, vec![0x08, new_polarity_mask]).into(), // Write Polarity Inversion Port 0
+        I2cTransaction::transaction_start(address), // Write Polarity Inversion Port 0
+        I2cTransaction::write(address, vec![0x08]),
+        I2cTransaction::write(address, vec![new_polarity_mask]),
+        I2cTransaction::transaction_end(address),
     ];
 
     let mut i2c_mock = I2cMock::new(&expectations);
@@ -604,6 +731,7 @@ fn test_set_port_polarity_inversion_sync() {
 }
 
 #[cfg(feature = "async")]
+#[cfg(feature = "polarity")]
 #[tokio::test]
 async fn test_set_port_polarity_inversion_async() {
     let address = 0x22;
@@ -611,7 +739,10 @@ async fn test_set_port_polarity_inversion_async() {
 
     let expectations = [
         // Set Port0 polarity inversion
-        I2cTransaction::write(address, vec![0x08, new_polarity_mask]), // Write Polarity Inversion Port 0
+        I2cTransaction::transaction_start(address), // Write Polarity Inversion Port 0
+        I2cTransaction::write(address, vec![0x08]),
+        I2cTransaction::write(address, vec![new_polarity_mask]),
+        I2cTransaction::transaction_end(address),
     ];
 
     let mut i2c_mock = I2cMock::new(&expectations);
@@ -623,6 +754,7 @@ async fn test_set_port_polarity_inversion_async() {
 }
 
 #[cfg(not(feature = "async"))]
+#[cfg(feature = "polarity")]
 #[test]
 fn test_get_port_polarity_inversion_sync() {
     let address = 0x22;
@@ -643,6 +775,7 @@ fn test_get_port_polarity_inversion_sync() {
 }
 
 #[cfg(feature = "async")]
+#[cfg(feature = "polarity")]
 #[tokio::test]
 async fn test_get_port_polarity_inversion_async() {
     let address = 0x22;
@@ -665,6 +798,7 @@ async fn test_get_port_polarity_inversion_async() {
 // --- Auto-Increment Tests ---
 
 #[cfg(not(feature = "async"))]
+#[cfg(feature = "auto-increment")]
 #[test]
 fn test_set_ports_direction_ai_sync() {
     let address = 0x22;
@@ -672,7 +806,10 @@ fn test_set_ports_direction_ai_sync() {
 
     let expectations = [
         // Set Port0, Port1, Port2 directions using AI (Config Port 0 is 0x0C, AI bit is 0x80)
-        I2cTransaction::write(address, vec![0x0C | 0x80, 0xAA, 0x55, 0xCC]).into(),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x0C | 0x80]),
+        I2cTransaction::write(address, vec![0xAA, 0x55, 0xCC]),
+        I2cTransaction::transaction_end(address),
     ];
 
     let mut i2c_mock = I2cMock::new(&expectations);
@@ -684,6 +821,7 @@ fn test_set_ports_direction_ai_sync() {
 }
 
 #[cfg(feature = "async")]
+#[cfg(feature = "auto-increment")]
 #[tokio::test]
 async fn test_set_ports_direction_ai_async() {
     let address = 0x22;
@@ -691,7 +829,10 @@ async fn test_set_ports_direction_ai_async() {
 
     let expectations = [
         // Set Port0, Port1, Port2 directions using AI (Config Port 0 is 0x0C, AI bit is 0x80)
-        I2cTransaction::write(address, vec![0x0C | 0x80, 0xAA, 0x55, 0xCC]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x0C | 0x80]),
+        I2cTransaction::write(address, vec![0xAA, 0x55, 0xCC]),
+        I2cTransaction::transaction_end(address),
     ];
 
     let mut i2c_mock = I2cMock::new(&expectations);
@@ -703,6 +844,7 @@ async fn test_set_ports_direction_ai_async() {
 }
 
 #[cfg(not(feature = "async"))]
+#[cfg(feature = "auto-increment")]
 #[test]
 fn test_get_ports_direction_ai_sync() {
     let address = 0x22;
@@ -724,6 +866,7 @@ fn test_get_ports_direction_ai_sync() {
 }
 
 #[cfg(feature = "async")]
+#[cfg(feature = "auto-increment")]
 #[tokio::test]
 async fn test_get_ports_direction_ai_async() {
     let address = 0x22;
@@ -745,6 +888,7 @@ async fn test_get_ports_direction_ai_async() {
 }
 
 #[cfg(not(feature = "async"))]
+#[cfg(feature = "auto-increment")]
 #[test]
 fn test_set_ports_output_ai_sync() {
     let address = 0x22;
@@ -752,7 +896,10 @@ fn test_set_ports_output_ai_sync() {
 
     let expectations = [
         // Set Port0, Port1, Port2 outputs using AI (Output Port 0 is 0x04, AI bit is 0x80)
-        I2cTransaction::write(address, vec![0x04 | 0x80, 0x11, 0x22, 0x33]).into(),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04 | 0x80]),
+        I2cTransaction::write(address, vec![0x11, 0x22, 0x33]),
+        I2cTransaction::transaction_end(address),
     ];
 
     let mut i2c_mock = I2cMock::new(&expectations);
@@ -763,7 +910,34 @@ fn test_set_ports_output_ai_sync() {
     i2c_mock.done();
 }
 
+#[cfg(not(feature = "async"))]
+#[cfg(feature = "auto-increment")]
+#[test]
+fn test_set_ports_output_ai_wraps_sync() {
+    let address = 0x22;
+
+    let expectations = [
+        // Four bytes from Output Port 1: the address wraps back to Port 0, then Port 1
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x05 | 0x80]),
+        I2cTransaction::write(address, vec![0x01, 0x02, 0x03, 0x04]),
+        I2cTransaction::transaction_end(address),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    tca.set_ports_output_ai(Port::Port1, &[0x01, 0x02, 0x03, 0x04]).unwrap();
+    assert_eq!(
+        tca.cache().family(tca6424::RegisterKind::Output),
+        Some([0x03, 0x04, 0x02])
+    );
+
+    i2c_mock.done();
+}
+
 #[cfg(feature = "async")]
+#[cfg(feature = "auto-increment")]
 #[tokio::test]
 async fn test_set_ports_output_ai_async() {
     let address = 0x22;
@@ -771,7 +945,10 @@ async fn test_set_ports_output_ai_async() {
 
     let expectations = [
         // Set Port0, Port1, Port2 outputs using AI (Output Port 0 is 0x04, AI bit is 0x80)
-        I2cTransaction::write(address, vec![0x04 | 0x80, 0x11, 0x22, 0x33]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04 | 0x80]),
+        I2cTransaction::write(address, vec![0x11, 0x22, 0x33]),
+        I2cTransaction::transaction_end(address),
     ];
 
     let mut i2c_mock = I2cMock::new(&expectations);
@@ -783,6 +960,7 @@ async fn test_set_ports_output_ai_async() {
 }
 
 #[cfg(not(feature = "async"))]
+#[cfg(feature = "auto-increment")]
 #[test]
 fn test_get_ports_output_state_ai_sync() {
     let address = 0x22;
@@ -804,6 +982,7 @@ fn test_get_ports_output_state_ai_sync() {
 }
 
 #[cfg(feature = "async")]
+#[cfg(feature = "auto-increment")]
 #[tokio::test]
 async fn test_get_ports_output_state_ai_async() {
     let address = 0x22;
@@ -825,6 +1004,7 @@ async fn test_get_ports_output_state_ai_async() {
 }
 
 #[cfg(not(feature = "async"))]
+#[cfg(feature = "auto-increment")]
 #[test]
 fn test_get_ports_input_state_ai_sync() {
     let address = 0x22;
@@ -845,7 +1025,203 @@ fn test_get_ports_input_state_ai_sync() {
     i2c_mock.done();
 }
 
+#[cfg(not(feature = "async"))]
+#[cfg(feature = "auto-increment")]
+#[test]
+fn test_get_ports_by_value_sync() {
+    let address = 0x22;
+
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x80], vec![0xDD, 0xEE, 0xFF]),
+        I2cTransaction::write_read(address, vec![0x8C], vec![0x0F, 0xF0, 0xFF]),
+        I2cTransaction::write_read(address, vec![0x84], vec![0x01, 0x02, 0x03]),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    assert_eq!(tca.get_ports_input_state().unwrap(), [0xDD, 0xEE, 0xFF]);
+    assert_eq!(tca.get_ports_direction().unwrap(), [0x0F, 0xF0, 0xFF]);
+    assert_eq!(tca.get_ports_output_state().unwrap(), [0x01, 0x02, 0x03]);
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_read_ports_sync() {
+    let address = 0x22;
+
+    let expectations = [I2cTransaction::write_read(
+        address,
+        vec![0x80],
+        vec![0x00, 0x2C, 0xFF],
+    )];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    let [port0, port1, port2] = tca.read_ports().unwrap();
+    assert_eq!(port0.pins_high().count(), 0);
+    assert!(port1.is_high(2) && port1.is_low(0));
+    assert_eq!(port1.pin(tca6424::Pin::P13), Some(tca6424::PinState::High));
+    assert_eq!(port1.pin(tca6424::Pin::P03), None);
+    let high: Vec<_> = port1.pins_high().collect();
+    assert_eq!(high, [tca6424::Pin::P12, tca6424::Pin::P13, tca6424::Pin::P15]);
+    assert_eq!(u8::from(port2), 0xFF);
+    assert_eq!(format!("{port1}"), "P1: 0b00101100");
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_read_port_inputs_outputs_sync() {
+    let address = 0x22;
+
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x01], vec![0x2C]),
+        I2cTransaction::write_read(address, vec![0x06], vec![0x81]),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    let inputs = tca.read_port_inputs(Port::Port1).unwrap();
+    assert_eq!(inputs.port(), Port::Port1);
+    assert_eq!(inputs.get(2), tca6424::PinState::High);
+    assert_eq!(inputs.get(8), tca6424::PinState::Low);
+    assert_eq!(format!("{inputs:08b}"), "00101100");
+
+    let outputs = tca.read_port_outputs(Port::Port2).unwrap();
+    let high: Vec<_> = outputs.pins_high().collect();
+    assert_eq!(high, [tca6424::Pin::P20, tca6424::Pin::P27]);
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_bool_pin_api_sync() {
+    use tca6424::Pin;
+
+    let address = 0x22;
+
+    let expectations = [
+        // set_pin(P05, true)
+        I2cTransaction::write_read(address, vec![0x04], vec![0x00]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0x20]),
+        I2cTransaction::transaction_end(address),
+        // is_high(P21), is_low(P21)
+        I2cTransaction::write_read(address, vec![0x02], vec![0x02]),
+        I2cTransaction::write_read(address, vec![0x02], vec![0x02]),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    tca.set_pin(Pin::P05, true).unwrap();
+    assert_eq!(tca.is_high(Pin::P21), Ok(true));
+    assert_eq!(tca.is_low(Pin::P21), Ok(false));
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_write_pins_masked_sync() {
+    let address = 0x22;
+
+    let expectations = [
+        // Bits 4..7 of Output Port 1 take the high nibble of 0xA5
+        I2cTransaction::write_read(address, vec![0x05], vec![0x0F]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x05]),
+        I2cTransaction::write(address, vec![0xAF]),
+        I2cTransaction::transaction_end(address),
+        // A full mask writes without reading
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x0E]),
+        I2cTransaction::write(address, vec![0x3C]),
+        I2cTransaction::transaction_end(address),
+        // Pins 0 and 1 of Port 0 become an output and an input
+        I2cTransaction::write_read(address, vec![0x0C], vec![0xFF]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x0C]),
+        I2cTransaction::write(address, vec![0xFE]),
+        I2cTransaction::transaction_end(address),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    tca.write_pins_masked(Port::Port1, 0xF0, 0xA5).unwrap();
+    tca.write_pins_masked(Port::Port0, 0x00, 0xFF).unwrap();
+    tca.write_directions_masked(Port::Port2, 0xFF, 0x3C).unwrap();
+    tca.write_directions_masked(Port::Port0, 0x03, 0x02).unwrap();
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_generic_register_access_sync() {
+    use tca6424::RegisterKind;
+    use tca6424::errors::Error;
+
+    let address = 0x22;
+
+    let expectations = [
+        // modify: Polarity Inversion Port 2 (0x0A)
+        I2cTransaction::write_read(address, vec![0x0A], vec![0x81]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x0A]),
+        I2cTransaction::write(address, vec![0x82]),
+        I2cTransaction::transaction_end(address),
+        // get_register: Interrupt Mask Port 1 (0x11)
+        I2cTransaction::write_read(address, vec![0x11], vec![0xF0]),
+        // set_register: Configuration Port 0 (0x0C)
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x0C]),
+        I2cTransaction::write(address, vec![0x0F]),
+        I2cTransaction::transaction_end(address),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    let written = tca
+        .modify(RegisterKind::PolarityInversion, Port::Port2, |value| value ^ 0b11)
+        .unwrap();
+    assert_eq!(written, 0x82);
+    assert_eq!(
+        tca.get_register(RegisterKind::InterruptMask, Port::Port1),
+        Ok(0xF0)
+    );
+    tca.set_register(RegisterKind::Configuration, Port::Port0, 0x0F)
+        .unwrap();
+    assert_eq!(
+        tca.cache().get(RegisterKind::Configuration, Port::Port0),
+        Some(0x0F)
+    );
+
+    // The Input family is read-only: no transfer is made
+    assert_eq!(
+        tca.modify(RegisterKind::Input, Port::Port0, |value| value),
+        Err(Error::InvalidRegisterOrPin)
+    );
+    assert_eq!(
+        tca.set_register(RegisterKind::Input, Port::Port0, 0),
+        Err(Error::InvalidRegisterOrPin)
+    );
+
+    i2c_mock.done();
+}
+
 #[cfg(feature = "async")]
+#[cfg(feature = "auto-increment")]
 #[tokio::test]
 async fn test_get_ports_input_state_ai_async() {
     let address = 0x22;
@@ -867,6 +1243,7 @@ async fn test_get_ports_input_state_ai_async() {
 }
 
 #[cfg(not(feature = "async"))]
+#[cfg(all(feature = "polarity", feature = "auto-increment"))]
 #[test]
 fn test_set_ports_polarity_inversion_ai_sync() {
     let address = 0x22;
@@ -874,7 +1251,10 @@ fn test_set_ports_polarity_inversion_ai_sync() {
 
     let expectations = [
         // Set Port0, Port1, Port2 polarity inversions using AI (Polarity Inversion Port 0 is 0x08, AI bit is 0x80)
-        I2cTransaction::write(address, vec![0x08 | 0x80, 0x0F, 0xF0, 0xAA]).into(),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x08 | 0x80]),
+        I2cTransaction::write(address, vec![0x0F, 0xF0, 0xAA]),
+        I2cTransaction::transaction_end(address),
     ];
 
     let mut i2c_mock = I2cMock::new(&expectations);
@@ -886,6 +1266,7 @@ fn test_set_ports_polarity_inversion_ai_sync() {
 }
 
 #[cfg(feature = "async")]
+#[cfg(all(feature = "polarity", feature = "auto-increment"))]
 #[tokio::test]
 async fn test_set_ports_polarity_inversion_ai_async() {
     let address = 0x22;
@@ -893,7 +1274,10 @@ async fn test_set_ports_polarity_inversion_ai_async() {
 
     let expectations = [
         // Set Port0, Port1, Port2 polarity inversions using AI (Polarity Inversion Port 0 is 0x08, AI bit is 0x80)
-        I2cTransaction::write(address, vec![0x08 | 0x80, 0x0F, 0xF0, 0xAA]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x08 | 0x80]),
+        I2cTransaction::write(address, vec![0x0F, 0xF0, 0xAA]),
+        I2cTransaction::transaction_end(address),
     ];
 
     let mut i2c_mock = I2cMock::new(&expectations);
@@ -905,6 +1289,7 @@ async fn test_set_ports_polarity_inversion_ai_async() {
 }
 
 #[cfg(not(feature = "async"))]
+#[cfg(all(feature = "polarity", feature = "auto-increment"))]
 #[test]
 fn test_get_ports_polarity_inversion_ai_sync() {
     let address = 0x22;
@@ -926,6 +1311,7 @@ fn test_get_ports_polarity_inversion_ai_sync() {
 }
 
 #[cfg(feature = "async")]
+#[cfg(all(feature = "polarity", feature = "auto-increment"))]
 #[tokio::test]
 async fn test_get_ports_polarity_inversion_ai_async() {
     let address = 0x22;
@@ -945,3 +1331,258 @@ async fn test_get_ports_polarity_inversion_ai_async() {
 
     i2c_mock.done();
 }
+
+// --- Snapshot tests ---
+
+#[cfg(not(feature = "async"))]
+#[cfg(feature = "diagnostics")]
+#[test]
+fn test_snapshot_sync() {
+    let address = 0x22;
+
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x80], vec![0x01, 0x02, 0x03]),
+        I2cTransaction::write_read(address, vec![0x84], vec![0xFF, 0x00, 0x0F]),
+        I2cTransaction::write_read(address, vec![0x88], vec![0x00, 0x00, 0x00]),
+        I2cTransaction::write_read(address, vec![0x8C], vec![0xF0, 0xFF, 0xFF]),
+        I2cTransaction::write_read(address, vec![0x90], vec![0xFF, 0xFF, 0xFF]),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    let snapshot = tca.snapshot().unwrap();
+    assert_eq!(snapshot.inputs, [0x01, 0x02, 0x03]);
+    assert_eq!(snapshot.outputs, [0xFF, 0x00, 0x0F]);
+    assert_eq!(snapshot.polarity_inversion, [0x00, 0x00, 0x00]);
+    assert_eq!(snapshot.configuration, [0xF0, 0xFF, 0xFF]);
+    assert_eq!(snapshot.interrupt_mask, [0xFF, 0xFF, 0xFF]);
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[cfg(feature = "diagnostics")]
+#[test]
+fn test_partial_snapshot_and_restore_sync() {
+    use tca6424::{RegisterFamilies, RegisterKind};
+
+    let address = 0x22;
+    let write = |command: u8, values: &[u8]| {
+        [
+            I2cTransaction::transaction_start(address),
+            I2cTransaction::write(address, vec![command]),
+            I2cTransaction::write(address, values.to_vec()),
+            I2cTransaction::transaction_end(address),
+        ]
+    };
+    let mut expectations = vec![
+        I2cTransaction::write_read(address, vec![0x84], vec![0xFF, 0x00, 0x0F]),
+        I2cTransaction::write_read(address, vec![0x8C], vec![0xF0, 0xFF, 0xFF]),
+    ];
+    // Outputs first, Configuration last; Polarity Inversion is left alone
+    expectations.extend(write(0x84, &[0xFF, 0x00, 0x0F]));
+    expectations.extend(write(0x8C, &[0xF0, 0xFF, 0xFF]));
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    let families = RegisterFamilies::OUTPUT | RegisterFamilies::CONFIGURATION;
+    let mut snapshot = tca.snapshot_families(families).unwrap();
+    assert_eq!(snapshot.outputs, [0xFF, 0x00, 0x0F]);
+    assert_eq!(snapshot.configuration, [0xF0, 0xFF, 0xFF]);
+    assert_eq!(snapshot.inputs, [0x00; 3]);
+
+    snapshot.polarity_inversion = [0xFF; 3];
+    tca.restore(&snapshot, families | RegisterFamilies::INPUT).unwrap();
+    assert_eq!(
+        tca.cache().get(RegisterKind::Configuration, Port::Port0),
+        Some(0xF0)
+    );
+    assert_eq!(
+        tca.cache().get(RegisterKind::PolarityInversion, Port::Port0),
+        None
+    );
+
+    i2c_mock.done();
+}
+
+#[cfg(feature = "diagnostics")]
+#[test]
+fn test_snapshot_diff() {
+    use tca6424::{Pin, RegisterKind, RegisterSnapshot};
+
+    let before = RegisterSnapshot {
+        configuration: [0xFF, 0xFF, 0xFF],
+        outputs: [0x00, 0x00, 0x00],
+        ..Default::default()
+    };
+    let mut after = before;
+    after.configuration[1] = 0xFE;
+    after.outputs[0] = 0x81;
+
+    assert!(before.diff(&before).is_empty());
+
+    let diff = before.diff(&after);
+    assert_eq!(diff.len(), 2);
+
+    // Changes are reported in register map order: Output before Configuration.
+    let output = &diff.changes()[0];
+    assert_eq!(output.kind, RegisterKind::Output);
+    assert_eq!(output.port, Port::Port0);
+    assert_eq!(output.set_bits(), 0x81);
+    assert_eq!(output.changed_pins().collect::<Vec<_>>(), [Pin::P00, Pin::P07]);
+
+    let config = diff.get(RegisterKind::Configuration, Port::Port1).unwrap();
+    assert_eq!(config.cleared_bits(), 0x01);
+
+    assert_eq!(
+        diff.to_string(),
+        "Output Port 0: 00000000 -> 10000001 [P00: 0->1, P07: 0->1]\n\
+         Configuration Port 1: 11111111 -> 11111110 [P10: 1->0]"
+    );
+}
+
+#[cfg(all(feature = "diagnostics", not(feature = "async")))]
+#[test]
+fn test_device_state_diff_sync() {
+    use tca6424::{DeviceState, Pin};
+
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x80], vec![0x01, 0x00, 0x80]),
+        I2cTransaction::write_read(address, vec![0x84], vec![0x00, 0x00, 0x00]),
+        I2cTransaction::write_read(address, vec![0x8C], vec![0xFE, 0xFF, 0xFF]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    let before = tca.device_state().unwrap();
+    assert_eq!(
+        before,
+        DeviceState {
+            inputs: 0x80_0001,
+            outputs: 0,
+            directions: 0xFF_FFFE,
+        }
+    );
+    assert!(before.diff(&before).is_empty());
+    assert_eq!(before.diff(&before).to_string(), "no state changes");
+
+    let after = DeviceState {
+        inputs: 0x00_0001,
+        outputs: 0x00_0101,
+        ..before
+    };
+    let diff = before.diff(&after);
+    assert_eq!(diff.inputs_changed(), 0x80_0000);
+    assert_eq!(diff.outputs_changed(), 0x00_0101);
+    assert_eq!(diff.directions_changed(), 0);
+    assert_eq!(
+        diff.changed_pins().collect::<Vec<_>>(),
+        [Pin::P00, Pin::P10, Pin::P27]
+    );
+    assert_eq!(
+        diff.to_string(),
+        "inputs: P27: 1->0\n\
+         outputs: P00: 0->1, P10: 0->1"
+    );
+
+    i2c_mock.done();
+}
+
+#[cfg(feature = "diagnostics")]
+#[test]
+fn test_device_state_from_snapshot() {
+    use tca6424::{DeviceState, RegisterSnapshot};
+
+    let snapshot = RegisterSnapshot {
+        inputs: [0x01, 0x02, 0x03],
+        outputs: [0x10, 0x20, 0x30],
+        configuration: [0xFF, 0x0F, 0xF0],
+        ..Default::default()
+    };
+    let state = DeviceState::from(&snapshot);
+    assert_eq!(state.inputs, 0x03_0201);
+    assert_eq!(state.outputs, 0x30_2010);
+    assert_eq!(state.directions, 0xF0_0FFF);
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_driver_owns_bus_sync() {
+    let address = 0x22;
+    let expectations = [I2cTransaction::write_read(address, vec![0x00], vec![0x5A])];
+    let mut tca = tca6424::Tca6424::new(I2cMock::new(&expectations), address).unwrap();
+
+    assert_eq!(tca.get_port_input_state(Port::Port0).unwrap(), 0x5A);
+
+    let mut i2c_mock = tca.into_inner();
+    i2c_mock.done();
+}
+
+#[test]
+fn test_pins_from_mask() {
+    use tca6424::{Pin, pins_from_mask};
+
+    assert_eq!(pins_from_mask(0).count(), 0);
+    let pins = pins_from_mask(0xFF80_0101);
+    assert_eq!(pins.len(), 3);
+    assert_eq!(pins.clone().collect::<Vec<_>>(), [Pin::P00, Pin::P10, Pin::P27]);
+    assert_eq!(pins.rev().collect::<Vec<_>>(), [Pin::P27, Pin::P10, Pin::P00]);
+    assert_eq!(pins_from_mask(0x00FF_FFFF).collect::<Vec<_>>(), Pin::ALL);
+}
+
+#[test]
+fn test_const_mask_builders() {
+    use tca6424::{PortMask, mask_range, pins};
+
+    const DIRECTIONS: u8 = PortMask::of(&[1, 2]).union(PortMask::range(6, 7)).bits();
+    assert_eq!(DIRECTIONS, 0b1100_0110);
+    assert_eq!(PortMask::of(&[]), PortMask::EMPTY);
+    assert_eq!(PortMask::range(0, 7), PortMask::ALL);
+    assert_eq!(u8::from(PortMask::ALL.invert()), 0);
+
+    const BANK: u32 = mask_range(4, 11);
+    assert_eq!(BANK, pins!(P04, P05, P06, P07, P10, P11, P12, P13));
+    assert_eq!(mask_range(23, 23), 1 << 23);
+}
+
+#[test]
+fn test_pins_and_ports_as_map_keys() {
+    use heapless::FnvIndexMap;
+    use std::collections::BTreeMap;
+    use tca6424::Pin;
+
+    let mut labels: FnvIndexMap<Pin, &str, 4> = FnvIndexMap::new();
+    labels.insert(Pin::P12, "RELAY").unwrap();
+    labels.insert(Pin::P00, "LED").unwrap();
+    assert_eq!(labels.get(&Pin::P12), Some(&"RELAY"));
+
+    let mut counts = BTreeMap::new();
+    for pin in [Pin::P27, Pin::P03, Pin::P10, Pin::P21] {
+        *counts.entry(pin.port()).or_insert(0) += 1;
+    }
+    assert_eq!(
+        counts.into_iter().collect::<Vec<_>>(),
+        [(Port::Port0, 1), (Port::Port1, 1), (Port::Port2, 2)]
+    );
+    assert!(Pin::P07 < Pin::P10);
+}
+
+#[test]
+fn test_register_address_map() {
+    use tca6424::{Register, RegisterKind};
+
+    for (family, kind) in RegisterKind::ALL.into_iter().enumerate() {
+        for port in Port::ALL {
+            let register = Register::of(kind, port);
+            assert_eq!(register as u8, (family as u8) << 2 | port as u8);
+            assert_eq!((register.kind(), register.port()), (kind, port));
+        }
+    }
+    assert_eq!(
+        Register::of(RegisterKind::InterruptMask, Port::Port2),
+        Register::InterruptMaskPort2
+    );
+}
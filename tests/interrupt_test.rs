@@ -0,0 +1,108 @@
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::InterruptThrottle;
+
+#[test]
+fn test_interrupt_throttle_coalesces_bursts() {
+    let mut throttle = InterruptThrottle::new(10);
+    assert!(!throttle.poll(0));
+
+    // The first interrupt is read immediately
+    throttle.notify();
+    assert!(throttle.poll(0));
+
+    // A burst within the interval is deferred and read once
+    throttle.notify();
+    throttle.notify();
+    assert_eq!(throttle.wait_ms(4), Some(6));
+    assert!(!throttle.poll(4));
+    assert!(throttle.is_pending());
+    assert!(throttle.poll(10));
+    assert!(!throttle.poll(25));
+
+    // Timestamps wrap around
+    let mut throttle = InterruptThrottle::new(10);
+    throttle.notify();
+    assert!(throttle.poll(u32::MAX - 2));
+    throttle.notify();
+    assert!(!throttle.poll(3));
+    assert!(throttle.poll(7));
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_service_interrupt_sync() {
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x80], vec![0x01, 0x02, 0x04]),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x00, 0x02, 0x04])
+            .with_error(embedded_hal::i2c::ErrorKind::Other),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x00, 0x02, 0x04]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    let mut throttle = InterruptThrottle::new(5);
+
+    assert_eq!(tca.service_interrupt(&mut throttle, 0).unwrap(), None);
+    throttle.notify();
+    assert_eq!(
+        tca.service_interrupt(&mut throttle, 0).unwrap(),
+        Some(0x04_02_01)
+    );
+
+    // A failed read keeps the interrupt pending for the next interval
+    throttle.notify();
+    assert_eq!(tca.service_interrupt(&mut throttle, 2).unwrap(), None);
+    assert!(tca.service_interrupt(&mut throttle, 5).is_err());
+    assert!(throttle.is_pending());
+    assert_eq!(
+        tca.service_interrupt(&mut throttle, 10).unwrap(),
+        Some(0x04_02_00)
+    );
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[cfg(feature = "interrupt-mask")]
+#[test]
+fn test_get_interrupt_sources_sync() {
+    use tca6424::{Pin, Port};
+
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x90]),
+        I2cTransaction::write(address, vec![0xFF, 0x00, 0xFF]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x00, 0x00, 0x00]),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x10, 0x81, 0x00]),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x10, 0x80, 0x00]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    // Only Port 1 interrupts are enabled
+    tca.set_ports_interrupt_mask_ai(Port::Port0, &[0xFF, 0x00, 0xFF])
+        .unwrap();
+
+    // The first call takes the baseline
+    assert!(tca.get_interrupt_sources().unwrap().is_empty());
+    // P04 changed too, but its interrupt is masked
+    let sources = tca.get_interrupt_sources().unwrap();
+    assert_eq!(
+        sources.changed_mask(),
+        1 << Pin::P10 as u8 | 1 << Pin::P17 as u8
+    );
+    let sources = tca.get_interrupt_sources().unwrap();
+    assert_eq!(
+        sources.iter().map(|event| event.pin).collect::<Vec<_>>(),
+        [Pin::P10]
+    );
+    // P10 and P17 rose, P10 fell again; the masked P04 is not counted
+    let activity = tca.port_activity(Port::Port1);
+    assert_eq!((activity.rising, activity.falling), (2, 1));
+    assert!(tca.port_activity(Port::Port0).is_idle());
+    assert_eq!(tca.take_port_activity(Port::Port1).total(), 3);
+    assert!(tca.port_activity(Port::Port1).is_idle());
+
+    i2c_mock.done();
+}
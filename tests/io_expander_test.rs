@@ -0,0 +1,62 @@
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{IoExpander, PinDirection};
+
+/// Generic code written only against the trait.
+#[cfg(not(feature = "async"))]
+fn light_row<E: IoExpander>(expander: &mut E, row: u32, all: u32) -> Result<(), E::Error> {
+    expander.set_direction(all, PinDirection::Output)?;
+    expander.write(row, all & !row)
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_io_expander_touches_only_masked_ports_sync() {
+    let address = 0x22;
+    let expectations = [
+        // set_direction: bits 0..1 of Port 0 and bit 0 of Port 2 become outputs
+        I2cTransaction::write_read(address, vec![0x0C], vec![0xFF]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x0C]),
+        I2cTransaction::write(address, vec![0xFC]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x0E], vec![0xFF]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x0E]),
+        I2cTransaction::write(address, vec![0xFE]),
+        I2cTransaction::transaction_end(address),
+        // write: bit 0 high, bits 1 and 16 low; Port 1 is skipped
+        I2cTransaction::write_read(address, vec![0x04], vec![0x80]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0x81]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x06], vec![0x03]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x06]),
+        I2cTransaction::write(address, vec![0x02]),
+        I2cTransaction::transaction_end(address),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    assert_eq!(<tca6424::Tca6424<I2cMock> as IoExpander>::PIN_COUNT, 24);
+    light_row(&mut tca, 0x0000_0001, 0x0001_0003).unwrap();
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_io_expander_read_masks_result_sync() {
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x01], vec![0xFF]),
+        I2cTransaction::write_read(address, vec![0x02], vec![0x5A]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    assert_eq!(tca.read(0x00F0_0100).unwrap(), 0x0050_0100);
+
+    i2c_mock.done();
+}
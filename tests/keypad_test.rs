@@ -0,0 +1,209 @@
+#![cfg(feature = "helpers")]
+
+use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+use tca6424::errors::ConfigError;
+use tca6424::{KeyEvent, Keypad, KeypadEvent, Pin};
+
+type Keypad3x3 = Keypad<3, 3>;
+
+const ROWS: [Pin; 3] = [Pin::P00, Pin::P01, Pin::P02];
+const COLS: [Pin; 3] = [Pin::P10, Pin::P11, Pin::P12];
+
+fn drain(keypad: &mut Keypad3x3) -> Vec<KeypadEvent> {
+    core::iter::from_fn(|| keypad.next_event()).collect()
+}
+
+#[test]
+fn test_keypad_chords() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+    let mut keypad = Keypad3x3::new(&mut tca, ROWS, COLS, 10)
+        .unwrap()
+        .with_chord_window_ms(100);
+    assert!(matches!(
+        Keypad::new(&mut tca, [Pin::P20], [Pin::P12], 10),
+        Err(ConfigError::PinAlreadyClaimed(Pin::P12))
+    ));
+    assert!(!tca.is_claimed(Pin::P20));
+
+    let (a, b) = (Keypad3x3::key(0, 0), Keypad3x3::key(2, 1));
+    let service = keypad.add_chord((1 << a) | (1 << b)).unwrap();
+    assert_eq!(keypad.add_chord(1 << a), None);
+
+    // Both keys within the window: the key events, then the chord once
+    keypad.process(1 << a, 0);
+    keypad.process(1 << a, 10);
+    keypad.process((1 << a) | (1 << b), 50);
+    keypad.process((1 << a) | (1 << b), 60);
+    keypad.process((1 << a) | (1 << b), 500);
+    assert_eq!(
+        drain(&mut keypad),
+        [
+            KeypadEvent::Pressed(a),
+            KeypadEvent::Pressed(b),
+            KeypadEvent::Chord(service)
+        ]
+    );
+
+    // Releasing one key and pressing it again does not restart the window
+    keypad.process(1 << a, 600);
+    keypad.process(1 << a, 610);
+    keypad.process((1 << a) | (1 << b), 620);
+    keypad.process((1 << a) | (1 << b), 630);
+    assert_eq!(
+        drain(&mut keypad),
+        [KeypadEvent::Released(b), KeypadEvent::Pressed(b)]
+    );
+
+    // Too slow after a full release
+    keypad.process(0, 700);
+    keypad.process(0, 710);
+    keypad.process(1 << b, 800);
+    keypad.process(1 << b, 810);
+    keypad.process((1 << a) | (1 << b), 950);
+    keypad.process((1 << a) | (1 << b), 960);
+    assert!(!drain(&mut keypad).contains(&KeypadEvent::Chord(service)));
+
+    keypad.release(&mut tca);
+    assert_eq!(tca.claimed_mask(), 0);
+    i2c_mock.done();
+}
+
+#[test]
+fn test_keypad_ghosting() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+    let mut keypad = Keypad3x3::new(&mut tca, ROWS, COLS, 0).unwrap();
+    let corners = [(0, 0), (0, 1), (1, 0), (1, 1)].map(|(row, col)| Keypad3x3::key(row, col));
+    let chord = keypad
+        .add_chord((1 << corners[0]) | (1 << corners[3]))
+        .unwrap();
+
+    // Three corners of a rectangle also read the fourth: no chord from the ambiguous image
+    keypad.process(corners.iter().fold(0, |keys, key| keys | 1 << key), 0);
+    assert!(keypad.is_ambiguous());
+    assert!(!drain(&mut keypad).contains(&KeypadEvent::Chord(chord)));
+
+    keypad.process((1 << corners[0]) | (1 << corners[3]), 10);
+    assert!(!keypad.is_ambiguous());
+    assert_eq!(
+        drain(&mut keypad),
+        [
+            KeypadEvent::Released(corners[1]),
+            KeypadEvent::Released(corners[2]),
+            KeypadEvent::Chord(chord)
+        ]
+    );
+
+    keypad.release(&mut tca);
+    i2c_mock.done();
+}
+
+#[test]
+fn test_keypad_layout() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+    let mut keypad = Keypad3x3::new(&mut tca, ROWS, COLS, 0)
+        .unwrap()
+        .with_layout([['1', '2', '3'], ['4', '5', '6'], ['7', '8', '9']]);
+    let chord = keypad
+        .add_chord((1 << Keypad3x3::key(0, 0)) | (1 << Keypad3x3::key(2, 2)))
+        .unwrap();
+    assert_eq!(keypad.key_code(Keypad3x3::key(1, 2)), Some('6'));
+    assert_eq!(keypad.key_code(9), None);
+
+    keypad.process(1 << Keypad3x3::key(2, 1), 0);
+    keypad.process(0, 10);
+    keypad.process(
+        (1 << Keypad3x3::key(0, 0)) | (1 << Keypad3x3::key(2, 2)),
+        20,
+    );
+    assert_eq!(
+        core::iter::from_fn(|| keypad.next_key_event()).collect::<Vec<_>>(),
+        [
+            KeyEvent::Pressed('8'),
+            KeyEvent::Released('8'),
+            KeyEvent::Pressed('1'),
+            KeyEvent::Pressed('9'),
+            KeyEvent::Chord(chord),
+        ]
+    );
+
+    keypad.release(&mut tca);
+    i2c_mock.done();
+}
+
+#[test]
+fn test_keypad_event_buffer() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+    let mut keypad = Keypad3x3::new(&mut tca, ROWS, COLS, 0)
+        .unwrap()
+        .with_event_buffer::<4>();
+
+    // Five keys pressed between two scans are all seen, but only four fit the buffer
+    keypad.process(0b1_1111, 0);
+    assert_eq!(keypad.keys(), 0b1_1111);
+    assert_eq!(keypad.pending_events(), 4);
+    assert_eq!(keypad.dropped_events(), 1);
+    assert_eq!(keypad.next_event(), Some(KeypadEvent::Pressed(1)));
+
+    // Shrinking the buffer keeps the newest events
+    let mut keypad = keypad.with_event_buffer::<2>();
+    assert_eq!(keypad.dropped_events(), 2);
+    assert_eq!(keypad.next_event(), Some(KeypadEvent::Pressed(3)));
+    assert_eq!(keypad.next_event(), Some(KeypadEvent::Pressed(4)));
+    assert_eq!(keypad.next_event(), None);
+
+    keypad.release(&mut tca);
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_keypad_scan_sync() {
+    use embedded_hal_mock::eh1::i2c::Transaction as I2cTransaction;
+
+    let address = 0x22;
+    let write = |cmd: u8, value: u8| {
+        [
+            I2cTransaction::transaction_start(address),
+            I2cTransaction::write(address, vec![cmd]),
+            I2cTransaction::write(address, vec![value]),
+            I2cTransaction::transaction_end(address),
+        ]
+    };
+    let mut expectations = vec![I2cTransaction::write_read(address, vec![0x04], vec![0xFF])];
+    // init: row latches low, every pin an input
+    expectations.extend(write(0x04, 0xFC));
+    expectations.push(I2cTransaction::write_read(address, vec![0x0C], vec![0xFF]));
+    expectations.extend(write(0x0C, 0xFF));
+    expectations.push(I2cTransaction::write_read(address, vec![0x0D], vec![0xFF]));
+    expectations.extend(write(0x0D, 0xFF));
+    // Row P00 strobed: column P11 reads low
+    expectations.extend(write(0x0C, 0xFE));
+    expectations.push(I2cTransaction::write_read(address, vec![0x01], vec![0xFD]));
+    // Row P01 strobed: nothing pressed
+    expectations.extend(write(0x0C, 0xFD));
+    expectations.push(I2cTransaction::write_read(address, vec![0x01], vec![0xFF]));
+    // Rows back to inputs
+    expectations.extend(write(0x0C, 0xFF));
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    let mut keypad = Keypad::<2, 2>::new(&mut tca, [Pin::P00, Pin::P01], [Pin::P10, Pin::P11], 0)
+        .unwrap()
+        .with_scan_interval_ms(5);
+    keypad.init(&mut tca).unwrap();
+    assert_eq!(
+        keypad.poll(&mut tca, 0).unwrap(),
+        1 << Keypad::<2, 2>::key(0, 1)
+    );
+    assert_eq!(keypad.next_event(), Some(KeypadEvent::Pressed(1)));
+
+    // Not due yet: no transfer
+    assert!(!keypad.is_scan_due(4));
+    assert_eq!(keypad.poll(&mut tca, 4).unwrap(), 1 << 1);
+
+    i2c_mock.done();
+}
@@ -0,0 +1,40 @@
+use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+use tca6424::{Pin, PinLabels};
+
+static LABELS: PinLabels = PinLabels::new()
+    .with(Pin::P10, "DOOR_OPEN")
+    .with(Pin::P12, "RELAY_MAIN");
+
+#[cfg(feature = "diagnostics")]
+#[test]
+fn test_register_change_with_labels() {
+    let change = tca6424::RegisterChange {
+        kind: tca6424::RegisterKind::Output,
+        port: tca6424::Port::Port1,
+        before: 0b0000_0001,
+        after: 0b0000_0110,
+    };
+
+    assert_eq!(
+        change.to_string(),
+        "Output Port 1: 00000001 -> 00000110 [P10: 1->0, P11: 0->1, P12: 0->1]"
+    );
+    assert_eq!(
+        change.with_labels(&LABELS).to_string(),
+        "Output Port 1: 00000001 -> 00000110 [DOOR_OPEN: 1->0, P11: 0->1, RELAY_MAIN: 0->1]"
+    );
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_driver_pin_names() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+
+    assert_eq!(tca.pin_name(Pin::P12).to_string(), "P12");
+    tca.set_pin_labels(&LABELS);
+    assert_eq!(tca.pin_name(Pin::P12).to_string(), "RELAY_MAIN");
+    assert_eq!(tca.pin_name(Pin::P27).to_string(), "P27");
+
+    i2c_mock.done();
+}
@@ -0,0 +1,171 @@
+#![cfg(feature = "helpers")]
+
+mod common;
+
+use common::write;
+use embedded_hal::delay::DelayNs;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::errors::ConfigError;
+use tca6424::{Hd44780, LcdPins, Pin};
+
+#[derive(Default)]
+struct CountingDelay {
+    calls: u32,
+    total_us: u32,
+}
+
+impl DelayNs for CountingDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.calls += 1;
+        self.total_us += ns / 1_000;
+    }
+}
+
+/// D4-D7 on P00-P03, RS on P04, E on P05 and RW on P06.
+fn pins(rw: Option<Pin>) -> LcdPins {
+    LcdPins {
+        rs: Pin::P04,
+        enable: Pin::P05,
+        rw,
+        data: [Pin::P00, Pin::P01, Pin::P02, Pin::P03],
+    }
+}
+
+#[test]
+fn test_lcd_claims_pins() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+
+    assert!(matches!(
+        Hd44780::new(&mut tca, pins(Some(Pin::P04))),
+        Err(ConfigError::InvalidRegisterOrPin)
+    ));
+    let lcd = Hd44780::new(&mut tca, pins(None)).unwrap().with_busy_flag();
+    assert_eq!(tca.claimed_mask(), 0x3F);
+    // Without an RW pin the busy flag cannot be read
+    assert!(!lcd.uses_busy_flag());
+    lcd.release(&mut tca);
+    assert_eq!(tca.claimed_mask(), 0);
+
+    // A control pin clashing with a display pin releases everything
+    let lcd = Hd44780::new(&mut tca, pins(None))
+        .unwrap()
+        .with_backlight(&mut tca, Pin::P07, false)
+        .unwrap();
+    assert_eq!(lcd.backlight_pin(), Some(Pin::P07));
+    assert!(matches!(
+        lcd.with_power_enable(&mut tca, Pin::P04, false),
+        Err(ConfigError::PinAlreadyClaimed(Pin::P04))
+    ));
+    assert_eq!(tca.claimed_mask(), 0);
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_lcd_write_byte_with_delay_sync() {
+    let address = 0x22;
+    let mut expectations = Vec::new();
+    expectations.extend(write(address, 0x04, &[0x00]));
+    // 'A' = 0x41: high nibble with RS high, strobed, then the low nibble
+    for value in [0x14, 0x34, 0x14, 0x11, 0x31, 0x11] {
+        expectations.extend(write(address, 0x04, &[value]));
+    }
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address)
+        .unwrap()
+        .with_delay(CountingDelay::default());
+    tca.set_port_output(tca6424::Port::Port0, 0x00).unwrap();
+    let mut lcd = Hd44780::new(&mut tca, pins(None)).unwrap();
+
+    lcd.write_byte(&mut tca, b'A').unwrap();
+    assert_eq!(tca.delay().calls, 1);
+    assert_eq!(tca.delay().total_us, 37);
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_lcd_write_byte_with_busy_flag_sync() {
+    let address = 0x22;
+    let mut expectations = Vec::new();
+    expectations.extend(write(address, 0x04, &[0x00]));
+    expectations.extend(write(address, 0x0C, &[0x80]));
+    for value in [0x14, 0x34, 0x14, 0x11, 0x31, 0x11] {
+        expectations.extend(write(address, 0x04, &[value]));
+    }
+    // Data pins become inputs, RS low and RW high
+    expectations.extend(write(address, 0x0C, &[0x8F]));
+    expectations.extend(write(address, 0x04, &[0x41]));
+    // Busy on the first read, ready on the second; each read clocks out both nibbles
+    for busy in [0x08, 0x00] {
+        expectations.extend(write(address, 0x04, &[0x61]));
+        expectations.push(I2cTransaction::write_read(address, vec![0x00], vec![busy]));
+        for value in [0x41, 0x61, 0x41] {
+            expectations.extend(write(address, 0x04, &[value]));
+        }
+    }
+    // RW low, data pins back to outputs
+    expectations.extend(write(address, 0x04, &[0x01]));
+    expectations.extend(write(address, 0x0C, &[0x80]));
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address)
+        .unwrap()
+        .with_delay(CountingDelay::default());
+    tca.set_port_output(tca6424::Port::Port0, 0x00).unwrap();
+    tca.set_port_direction(tca6424::Port::Port0, 0x80).unwrap();
+    let mut lcd = Hd44780::new(&mut tca, pins(Some(Pin::P06)))
+        .unwrap()
+        .with_busy_flag();
+    assert!(lcd.uses_busy_flag());
+
+    lcd.write_byte(&mut tca, b'A').unwrap();
+    assert_eq!(tca.delay().calls, 0);
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_lcd_backlight_sync() {
+    let address = 0x22;
+    let mut expectations = Vec::new();
+    expectations.extend(write(address, 0x04, &[0x80]));
+    // The backlight on P07 rides along with the nibble writes
+    for value in [0x94, 0xB4, 0x94, 0x91, 0xB1, 0x91] {
+        expectations.extend(write(address, 0x04, &[value]));
+    }
+    // Dimmed after a second without activity
+    expectations.extend(write(address, 0x04, &[0x11]));
+    // Woken: switched back on by the first nibble write
+    for value in [0x94, 0xB4, 0x94, 0x91, 0xB1, 0x91] {
+        expectations.extend(write(address, 0x04, &[value]));
+    }
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address)
+        .unwrap()
+        .with_delay(CountingDelay::default());
+    tca.set_port_output(tca6424::Port::Port0, 0x80).unwrap();
+    let mut lcd = Hd44780::new(&mut tca, pins(None))
+        .unwrap()
+        .with_backlight(&mut tca, Pin::P07, false)
+        .unwrap()
+        .with_dim_after_ms(Some(1000));
+
+    assert!(lcd.poll(&mut tca, 0).unwrap());
+    lcd.write_byte(&mut tca, b'A').unwrap();
+    assert!(lcd.poll(&mut tca, 999).unwrap());
+    assert!(!lcd.poll(&mut tca, 1000).unwrap());
+    assert!(!lcd.poll(&mut tca, 1200).unwrap());
+
+    lcd.wake(1500);
+    lcd.write_byte(&mut tca, b'A').unwrap();
+    assert!(lcd.poll(&mut tca, 2000).unwrap());
+
+    i2c_mock.done();
+}
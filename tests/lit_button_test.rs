@@ -0,0 +1,164 @@
+#![cfg(feature = "helpers")]
+
+use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+use embedded_hal_mock::eh1::i2c::Transaction as I2cTransaction;
+use tca6424::errors::ConfigError;
+use tca6424::{
+    ButtonEvent, ButtonGesture, GestureConfig, LedFeedback, LitButton, LitButtonConfig, Pin,
+    PinState,
+};
+
+fn config(feedback: LedFeedback) -> LitButtonConfig {
+    LitButtonConfig {
+        feedback,
+        debounce_ms: 10,
+        ..LitButtonConfig::default()
+    }
+}
+
+#[test]
+fn test_lit_button_toggle_latch() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+    let mut button = LitButton::new(
+        &mut tca,
+        Pin::P00,
+        Pin::P10,
+        config(LedFeedback::ToggleLatch),
+    )
+    .unwrap();
+    assert!(matches!(
+        LitButton::new(&mut tca, Pin::P01, Pin::P10, LitButtonConfig::default()),
+        Err(ConfigError::PinAlreadyClaimed(Pin::P10))
+    ));
+    assert!(!tca.is_claimed(Pin::P01));
+
+    // Active-low button: a bounce shorter than the settle time is ignored
+    assert_eq!(button.process(PinState::Low, 0), None);
+    assert_eq!(button.process(PinState::High, 5), None);
+    assert_eq!(button.process(PinState::Low, 6), None);
+    assert_eq!(
+        button.process(PinState::Low, 16),
+        Some(ButtonEvent::Pressed)
+    );
+    assert!(button.is_latched() && button.led_on());
+    assert_eq!(button.process(PinState::High, 20), None);
+    assert_eq!(
+        button.process(PinState::High, 30),
+        Some(ButtonEvent::Released)
+    );
+    assert!(button.led_on());
+    button.process(PinState::Low, 40);
+    assert_eq!(
+        button.process(PinState::Low, 50),
+        Some(ButtonEvent::Pressed)
+    );
+    assert!(!button.is_latched() && !button.led_on());
+
+    button.release(&mut tca);
+    assert_eq!(tca.claimed_mask(), 0);
+    i2c_mock.done();
+}
+
+#[test]
+fn test_lit_button_blink_on_hold() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+    let feedback = LedFeedback::BlinkOnHold {
+        hold_ms: 100,
+        period_ms: 40,
+    };
+    let mut button = LitButton::new(&mut tca, Pin::P00, Pin::P10, config(feedback)).unwrap();
+
+    button.process(PinState::Low, 0);
+    button.process(PinState::Low, 10);
+    assert!(button.led_on());
+    button.process(PinState::Low, 109);
+    assert!(button.led_on());
+    button.process(PinState::Low, 110);
+    assert!(!button.led_on());
+    button.process(PinState::Low, 130);
+    assert!(button.led_on());
+
+    i2c_mock.done();
+}
+
+#[test]
+fn test_lit_button_gestures() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+    let mut button = LitButton::new(&mut tca, Pin::P00, Pin::P10, config(LedFeedback::Follow))
+        .unwrap()
+        .with_gestures(GestureConfig {
+            long_press_ms: 400,
+            double_click_ms: 150,
+        });
+
+    // Two debounced presses in quick succession
+    for (level, now) in [
+        (PinState::Low, 0),
+        (PinState::Low, 10),
+        (PinState::High, 60),
+    ] {
+        button.process(level, now);
+    }
+    assert_eq!(
+        button.process(PinState::High, 70),
+        Some(ButtonEvent::Released)
+    );
+    button.process(PinState::Low, 100);
+    button.process(PinState::Low, 110);
+    button.process(PinState::High, 150);
+    button.process(PinState::High, 160);
+    assert_eq!(button.next_gesture(), Some(ButtonGesture::DoubleClick));
+    assert_eq!(button.next_gesture(), None);
+
+    // Gestures are derived from the debounced level: the press counts from 210
+    button.process(PinState::Low, 200);
+    button.process(PinState::Low, 210);
+    button.process(PinState::Low, 609);
+    assert_eq!(button.next_gesture(), None);
+    button.process(PinState::Low, 610);
+    assert_eq!(button.next_gesture(), Some(ButtonGesture::LongPress));
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_lit_button_poll_sync() {
+    let address = 0x22;
+
+    let expectations = [
+        // Pressed (P00 low): LED on P10 is switched on with a read-modify-write
+        I2cTransaction::write_read(address, vec![0x00], vec![0xFE]),
+        I2cTransaction::write_read(address, vec![0x05], vec![0x00]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x05]),
+        I2cTransaction::write(address, vec![0x01]),
+        I2cTransaction::transaction_end(address),
+        // Still pressed: no LED write
+        I2cTransaction::write_read(address, vec![0x00], vec![0xFE]),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    let mut button = LitButton::new(
+        &mut tca,
+        Pin::P00,
+        Pin::P10,
+        LitButtonConfig {
+            debounce_ms: 0,
+            ..LitButtonConfig::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        button.poll(&mut tca, 0).unwrap(),
+        Some(ButtonEvent::Pressed)
+    );
+    assert_eq!(button.poll(&mut tca, 5).unwrap(), None);
+
+    i2c_mock.done();
+}
@@ -0,0 +1,107 @@
+#![cfg(feature = "helpers")]
+
+use tca6424::{InputWatcher, MirrorRule, Pin, PinMirror, Port};
+
+#[test]
+fn test_mirror_rules() {
+    let mut mirror: PinMirror<2> = PinMirror::new();
+    assert_eq!(mirror.mirror(Pin::P00, Pin::P10), Some(0));
+    // A pin cannot mirror itself, and an output follows one input only
+    assert_eq!(mirror.mirror(Pin::P01, Pin::P01), None);
+    assert_eq!(mirror.mirror(Pin::P01, Pin::P10), None);
+    let rule = MirrorRule::new(Pin::P00, Pin::P11)
+        .with_inverted(true)
+        .with_delay_ms(50);
+    assert_eq!(mirror.add(rule), Some(1));
+    assert_eq!(mirror.mirror(Pin::P02, Pin::P12), None, "all rules are set");
+
+    assert_eq!(mirror.inputs(), tca6424::pins!(P00));
+    assert_eq!(mirror.outputs(), tca6424::pins!(P10, P11));
+    assert_eq!(mirror.rules().nth(1), Some(rule));
+    assert!(rule.is_inverted());
+    assert_eq!(rule.delay_ms(), 50);
+}
+
+#[test]
+fn test_mirror_follows_watcher() {
+    let mut watcher = InputWatcher::new().with_port(Port::Port0);
+    let mut mirror: PinMirror<2> = PinMirror::new();
+    mirror.mirror(Pin::P00, Pin::P10).unwrap();
+    mirror
+        .add(
+            MirrorRule::new(Pin::P01, Pin::P11)
+                .with_inverted(true)
+                .with_delay_ms(50),
+        )
+        .unwrap();
+
+    // The first resync brings every output in line; the delayed one after its delay
+    watcher.request_full_resync();
+    let changes = watcher.update(0x01);
+    assert_eq!(
+        mirror.process_changes(&changes, 1000),
+        (tca6424::pins!(P10), 0)
+    );
+    assert_eq!(mirror.wait_ms(1020), Some(30));
+    assert_eq!(mirror.expire(1049), (0, 0));
+    assert_eq!(mirror.expire(1050), (tca6424::pins!(P11), 0));
+    assert_eq!(mirror.wait_ms(1050), None);
+
+    // A pulse shorter than the delay is not forwarded
+    let changes = watcher.update(0x03);
+    assert_eq!(mirror.process_changes(&changes, 2000), (0, 0));
+    let changes = watcher.update(0x01);
+    assert_eq!(mirror.process_changes(&changes, 2010), (0, 0));
+    assert_eq!(mirror.expire(2060), (tca6424::pins!(P11), 0));
+
+    let changes = watcher.update(0x02);
+    assert_eq!(
+        mirror.process_changes(&changes, 3000),
+        (0, tca6424::pins!(P10))
+    );
+    assert_eq!(mirror.expire(3050), (0, tca6424::pins!(P11)));
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_mirror_drives_outputs_sync() {
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+    let address = 0x22;
+    let write = |value: u8| {
+        [
+            I2cTransaction::transaction_start(address),
+            I2cTransaction::write(address, vec![0x05]),
+            I2cTransaction::write(address, vec![value]),
+            I2cTransaction::transaction_end(address),
+        ]
+    };
+    let expectations: Vec<_> = [write(0x00), write(0x01), write(0x03), write(0x02)]
+        .into_iter()
+        .flatten()
+        .collect();
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    tca.set_port_output(Port::Port1, 0x00).unwrap();
+
+    let mut watcher = InputWatcher::new().with_port(Port::Port0);
+    let mut mirror: PinMirror<2> = PinMirror::new();
+    mirror.mirror(Pin::P00, Pin::P10).unwrap();
+    mirror
+        .add(
+            MirrorRule::new(Pin::P01, Pin::P11)
+                .with_inverted(true)
+                .with_delay_ms(50),
+        )
+        .unwrap();
+
+    watcher.request_full_resync();
+    let changes = watcher.update(0x01);
+    assert!(mirror.apply(&mut tca, &changes, 0).unwrap());
+    assert!(!mirror.tick(&mut tca, 10).unwrap());
+    assert!(mirror.tick(&mut tca, 50).unwrap());
+    let changes = watcher.update(0x00);
+    assert!(mirror.apply(&mut tca, &changes, 100).unwrap());
+
+    i2c_mock.done();
+}
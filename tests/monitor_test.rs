@@ -0,0 +1,146 @@
+#![cfg(not(feature = "async"))]
+
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use embedded_hal_mock::eh1::pin::{Mock as PinMock, State, Transaction as PinTransaction};
+use tca6424::{InputMonitor, InputWatcher, NoInt, Pin, Port, Tca6424};
+
+#[test]
+fn test_wait_for_change_reads_inputs_on_int() {
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x80], vec![0xFF, 0xFF, 0xFF]),
+        I2cTransaction::write_read(address, vec![0x80], vec![0xFF, 0xFE, 0xFF]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, address).unwrap();
+    let int = PinMock::new(&[
+        PinTransaction::get(State::High),
+        PinTransaction::get(State::Low),
+    ]);
+    let mut monitor = InputMonitor::new(int, InputWatcher::new().with_port(Port::Port1));
+
+    // The first wait takes the baseline without sampling INT
+    let changes = monitor.wait_for_change_or_timeout(&mut tca, 10).unwrap();
+    assert!(changes.is_resync());
+    assert!(monitor.is_primed());
+    let changes = monitor.wait_for_change_or_timeout(&mut tca, 10).unwrap();
+    assert_eq!(changes.changed_mask(), 1 << Pin::P10 as u8);
+    assert_eq!(monitor.fallback_polls(), 0);
+
+    monitor.into_inner().done();
+    i2c_mock.done();
+}
+
+#[test]
+fn test_wait_for_change_polls_after_timeout() {
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x80], vec![0x00, 0x00, 0x00]),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x04, 0x00, 0x00]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, address).unwrap();
+    // INT never asserts: two samples per 1 ms timeout, then the inputs are read anyway
+    let int = PinMock::new(&[
+        PinTransaction::get(State::High),
+        PinTransaction::get(State::High),
+    ]);
+    let mut monitor = InputMonitor::new(int, InputWatcher::new().with_pin(Pin::P02));
+
+    assert!(
+        monitor
+            .wait_for_change_or_timeout(&mut tca, 1)
+            .unwrap()
+            .is_resync()
+    );
+    let changes = monitor.wait_for_change_or_timeout(&mut tca, 1).unwrap();
+    assert_eq!(changes.changed_mask(), 1 << Pin::P02 as u8);
+    assert_eq!(monitor.fallback_polls(), 1);
+
+    monitor.into_inner().done();
+    i2c_mock.done();
+}
+
+#[test]
+fn test_polling_monitor_without_int() {
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x80], vec![0x00, 0x00, 0x00]),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x00, 0x00, 0x80]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, address).unwrap();
+    let mut monitor = InputMonitor::polling(InputWatcher::new().with_port(Port::Port2));
+
+    assert!(
+        monitor
+            .wait_for_change_or_timeout(&mut tca, 5)
+            .unwrap()
+            .is_resync()
+    );
+    let changes = monitor.wait_for_change_or_timeout(&mut tca, 5).unwrap();
+    assert_eq!(changes.changed_mask(), 1 << Pin::P27 as u8);
+    assert_eq!(monitor.fallback_polls(), 1);
+    assert_eq!(monitor.into_inner(), NoInt);
+
+    i2c_mock.done();
+}
+
+#[test]
+fn test_full_resync_reads_without_waiting() {
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x80], vec![0x00, 0x00, 0x00]),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x00, 0x00, 0x81]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, address).unwrap();
+    // INT is never sampled while a resync is pending
+    let int = PinMock::new(&[PinTransaction::get(State::Low)]);
+    let mut monitor = InputMonitor::new(int, InputWatcher::new().with_port(Port::Port2));
+
+    monitor.watcher().request_full_resync();
+    let changes = monitor.wait_for_change_or_timeout(&mut tca, 10).unwrap();
+    assert!(changes.is_resync());
+    assert_eq!(changes.changed_mask(), 0xFF_00_00);
+    assert!(!monitor.watcher().is_resync_pending());
+
+    let changes = monitor.wait_for_change_or_timeout(&mut tca, 10).unwrap();
+    assert!(!changes.is_resync());
+    assert_eq!(changes.changed_mask(), 0x81_00_00);
+    assert_eq!(monitor.fallback_polls(), 0);
+
+    monitor.into_inner().done();
+    i2c_mock.done();
+}
+
+#[test]
+fn test_int_asserted_at_construction_is_not_missed() {
+    use tca6424::PinState;
+
+    let address = 0x22;
+    let expectations = [
+        // P12 went low before boot; INT has been asserted ever since
+        I2cTransaction::write_read(address, vec![0x80], vec![0xFF, 0xFB, 0xFF]),
+        I2cTransaction::write_read(address, vec![0x80], vec![0xFF, 0xFF, 0xFF]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, address).unwrap();
+    let int = PinMock::new(&[PinTransaction::get(State::Low)]);
+    let mut monitor = InputMonitor::new(int, InputWatcher::new().with_pin(Pin::P12));
+    assert!(!monitor.is_primed());
+
+    // The clearing read reports the pending state as the baseline
+    let changes = monitor.wait_for_change_or_timeout(&mut tca, 10).unwrap();
+    assert!(changes.is_resync());
+    let event = changes.iter().next().unwrap();
+    assert_eq!((event.pin, event.state), (Pin::P12, PinState::Low));
+
+    // The next edge is a regular change
+    let changes = monitor.wait_for_change_or_timeout(&mut tca, 10).unwrap();
+    assert!(!changes.is_resync());
+    assert_eq!(changes.changed_mask(), 1 << Pin::P12 as u8);
+
+    monitor.into_inner().done();
+    i2c_mock.done();
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "tca9548")]
+
+use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+use embedded_hal_mock::eh1::i2c::Transaction as I2cTransaction;
+use tca6424::{MuxChannel, Port, TCA9548_DEFAULT_ADDRESS, Tca9548};
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_mux_channel_selects_before_each_transfer_sync() {
+    let address = 0x22;
+    let mux = TCA9548_DEFAULT_ADDRESS;
+
+    let expectations = [
+        // Select channel 3, then write Output Port 0
+        I2cTransaction::write(mux, vec![0x08]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0x5A]),
+        I2cTransaction::transaction_end(address),
+        // Select channel 3 again, then read Input Port 1
+        I2cTransaction::write(mux, vec![0x08]),
+        I2cTransaction::write_read(address, vec![0x01], vec![0xC3]),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut channel = MuxChannel::new(&mut i2c_mock, Tca9548::new(mux, 3).unwrap());
+    let mut tca = tca6424::Tca6424::new(&mut channel, address).unwrap();
+
+    tca.set_port_output(Port::Port0, 0x5A).unwrap();
+    assert_eq!(tca.get_port_input_state(Port::Port1).unwrap(), 0xC3);
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_mux_channel_closure_selector_sync() {
+    let address = 0x22;
+    let mut selections = 0;
+
+    let expectations = [I2cTransaction::write_read(address, vec![0x00], vec![0x01])];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut channel = MuxChannel::new(&mut i2c_mock, |_: &mut &mut I2cMock| {
+        selections += 1;
+        Ok(())
+    });
+    let mut tca = tca6424::Tca6424::new(&mut channel, address).unwrap();
+
+    assert_eq!(tca.get_port_input_state(Port::Port0).unwrap(), 0x01);
+    assert!(Tca9548::new(TCA9548_DEFAULT_ADDRESS, 8).is_none());
+
+    i2c_mock.done();
+    assert_eq!(selections, 1);
+}
@@ -0,0 +1,198 @@
+#![cfg(all(feature = "testing", not(feature = "async")))]
+
+//! The driver must turn every failure into an `Err`: arbitrary arguments, a failing bus and
+//! a device whose registers change behind the driver's back may produce errors, but never a
+//! panic.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, Operation, SevenBitAddress};
+use proptest::prelude::*;
+use tca6424::testing::RegisterModel;
+use tca6424::{
+    BankPin, ChannelMap, ConfigTransaction, DEFAULT_ADDRESS, InputFallback, InputMap, Pin,
+    PinDirection, PinState, Port, Register, RegisterCache, RegisterKind, Tca6424,
+};
+
+/// A simulated device whose transactions fail where the bit of `failures` is set. The test
+/// keeps a handle on the model to change registers behind the driver's back.
+struct FlakyModel {
+    model: Rc<RefCell<RegisterModel>>,
+    failures: u64,
+    count: u32,
+}
+
+impl ErrorType for FlakyModel {
+    type Error = ErrorKind;
+}
+
+impl I2c for FlakyModel {
+    fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let fails = self.failures & (1 << (self.count % 64)) != 0;
+        self.count = self.count.wrapping_add(1);
+        if fails {
+            return Err(ErrorKind::Other);
+        }
+        self.model.borrow_mut().transaction(address, operations)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Op {
+    PinDirection(Pin, bool),
+    PinOutput(Pin, bool),
+    PinInput(Pin),
+    PinPolarity(Pin, bool),
+    PortOutput(Port, u8),
+    PortsOutput(Port, Vec<u8>),
+    PortsInput(Port, usize),
+    Register(RegisterKind, Port, u8),
+    Modify(RegisterKind, Port, u8),
+    Transaction(Vec<(Pin, bool, bool)>),
+    Snapshot,
+    Glitch(Register, u8),
+    Fallback(u32, u8),
+}
+
+fn pin() -> impl Strategy<Value = Pin> {
+    (0u8..24).prop_map(|index| Pin::from_index(index).unwrap())
+}
+
+fn port() -> impl Strategy<Value = Port> {
+    prop::sample::select(Port::ALL.to_vec())
+}
+
+fn kind() -> impl Strategy<Value = RegisterKind> {
+    prop::sample::select(RegisterKind::ALL.to_vec())
+}
+
+fn register() -> impl Strategy<Value = Register> {
+    (kind(), port()).prop_map(|(kind, port)| Register::of(kind, port))
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        (pin(), any::<bool>()).prop_map(|(pin, input)| Op::PinDirection(pin, input)),
+        (pin(), any::<bool>()).prop_map(|(pin, high)| Op::PinOutput(pin, high)),
+        pin().prop_map(Op::PinInput),
+        (pin(), any::<bool>()).prop_map(|(pin, invert)| Op::PinPolarity(pin, invert)),
+        (port(), any::<u8>()).prop_map(|(port, value)| Op::PortOutput(port, value)),
+        (port(), prop::collection::vec(any::<u8>(), 0..8))
+            .prop_map(|(port, values)| Op::PortsOutput(port, values)),
+        (port(), 0usize..8).prop_map(|(port, len)| Op::PortsInput(port, len)),
+        (kind(), port(), any::<u8>())
+            .prop_map(|(kind, port, value)| Op::Register(kind, port, value)),
+        (kind(), port(), any::<u8>()).prop_map(|(kind, port, bits)| Op::Modify(kind, port, bits)),
+        prop::collection::vec((pin(), any::<bool>(), any::<bool>()), 0..8)
+            .prop_map(Op::Transaction),
+        Just(Op::Snapshot),
+        (register(), any::<u8>()).prop_map(|(register, value)| Op::Glitch(register, value)),
+        (any::<u32>(), 0u8..3).prop_map(|(values, retries)| Op::Fallback(values, retries)),
+    ]
+}
+
+fn run(tca: &mut Tca6424<FlakyModel>, model: &RefCell<RegisterModel>, op: Op) {
+    // Errors are expected on a failing bus; only a panic fails the test.
+    let _ = match op {
+        Op::PinDirection(pin, input) => {
+            let direction = if input {
+                PinDirection::Input
+            } else {
+                PinDirection::Output
+            };
+            tca.set_pin_direction(pin, direction)
+        }
+        Op::PinOutput(pin, high) => tca.set_pin_output(pin, PinState::from(high)),
+        Op::PinInput(pin) => tca.get_pin_input_state(pin).map(|_| ()),
+        Op::PinPolarity(pin, invert) => tca.set_pin_polarity_inversion(pin, invert),
+        Op::PortOutput(port, value) => tca.set_port_output(port, value),
+        Op::PortsOutput(port, values) => tca.set_ports_output_ai(port, &values),
+        Op::PortsInput(port, len) => tca.get_ports_input_state_ai(port, &mut vec![0; len]),
+        Op::Register(kind, port, value) => tca.set_register(kind, port, value),
+        Op::Modify(kind, port, bits) => tca.modify(kind, port, |value| value ^ bits).map(|_| ()),
+        Op::Transaction(stages) => {
+            let mut txn = ConfigTransaction::new();
+            for (pin, output, high) in stages {
+                if output {
+                    txn.direction(pin, PinDirection::Output);
+                }
+                txn.output(pin, PinState::from(high));
+            }
+            let _ = txn.plan(tca.cache()).count();
+            tca.apply_config_transaction(&txn)
+        }
+        Op::Snapshot => tca.snapshot().map(|_| ()),
+        Op::Glitch(register, value) => {
+            model.borrow_mut().set_register(register, value);
+            Ok(())
+        }
+        Op::Fallback(values, retries) => {
+            tca.set_input_fallback(InputFallback::new(values).with_retries(retries));
+            Ok(())
+        }
+    };
+}
+
+proptest! {
+    #[test]
+    fn prop_driver_never_panics(
+        failures in any::<u64>(),
+        levels in any::<u32>(),
+        ops in prop::collection::vec(op(), 1..48),
+    ) {
+        let model = Rc::new(RefCell::new(RegisterModel::new()));
+        model.borrow_mut().set_pin_levels(levels);
+        let i2c = FlakyModel {
+            model: model.clone(),
+            failures,
+            count: 0,
+        };
+        let mut tca = Tca6424::new(i2c, DEFAULT_ADDRESS).unwrap();
+        for op in ops {
+            run(&mut tca, &model, op);
+        }
+    }
+
+    #[test]
+    fn prop_lookups_never_panic(index in any::<u8>(), flat in any::<usize>(), raw in any::<u32>()) {
+        static PINS: [Pin; 3] = [Pin::P00, Pin::P13, Pin::P27];
+
+        let _ = Pin::from_index(index);
+        for port in Port::ALL {
+            let _ = Pin::from_port_bit(port, index);
+        }
+        let pin = BankPin::from_flat_index(flat);
+        prop_assert_eq!(pin.device_index, flat / 24);
+        let map = InputMap::new().invert(index % 24);
+        let _ = map.source(index);
+        let _ = map.apply(raw);
+        let _ = ChannelMap::new(&PINS).pin(index);
+        let _ = RegisterCache::new().get(RegisterKind::Output, Port::Port2);
+    }
+}
+
+#[cfg(feature = "capture")]
+proptest! {
+    #[test]
+    fn prop_replay_decodes_any_record(
+        command in any::<u8>(),
+        len in any::<u8>(),
+        bytes in any::<[u8; tca6424::CAPTURED_BYTES]>(),
+    ) {
+        let record = tca6424::TransferRecord {
+            command,
+            direction: tca6424::TransferDirection::Read,
+            len,
+            bytes,
+            outcome: tca6424::TransferOutcome::Ok,
+        };
+        prop_assert!(record.data().len() <= tca6424::CAPTURED_BYTES);
+        let watcher = tca6424::InputWatcher::new().with_port(Port::Port0);
+        let _ = tca6424::InputReplay::new(watcher, 0, &[]).feed_record(&record);
+    }
+}
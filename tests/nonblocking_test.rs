@@ -0,0 +1,68 @@
+#![cfg(all(feature = "nb", not(feature = "async")))]
+
+use embedded_hal::i2c::ErrorKind;
+use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+use embedded_hal_mock::eh1::i2c::Transaction as I2cTransaction;
+use tca6424::{NbTca6424, Operation, Pin, PinState, Port};
+
+#[test]
+fn test_nb_read_modify_write() {
+    let address = 0x22;
+
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x05], vec![0xF0]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x05]),
+        I2cTransaction::write(address, vec![0xF8]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x02], vec![0x5A]),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    let mut nb_tca = NbTca6424::new(tca);
+
+    // Nothing in flight: polling is a no-op
+    assert_eq!(nb_tca.poll().ok(), Some(None));
+
+    nb_tca
+        .start(Operation::set_pin_output(Pin::P13, PinState::High))
+        .unwrap();
+    assert!(matches!(
+        nb_tca.start(Operation::read_port_input(Port::Port2)),
+        Err(nb::Error::WouldBlock)
+    ));
+    assert!(matches!(nb_tca.poll(), Err(nb::Error::WouldBlock)));
+    assert_eq!(nb_tca.poll().ok(), Some(None));
+    assert!(!nb_tca.is_busy());
+
+    nb_tca
+        .start(Operation::read_port_input(Port::Port2))
+        .unwrap();
+    assert_eq!(nb_tca.poll().ok(), Some(Some(0x5A)));
+
+    i2c_mock.done();
+}
+
+#[test]
+fn test_nb_retries_failed_step() {
+    let address = 0x22;
+
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x00], vec![0x00]).with_error(ErrorKind::Other),
+        I2cTransaction::write_read(address, vec![0x00], vec![0x3C]),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    let mut nb_tca = NbTca6424::new(tca);
+
+    nb_tca
+        .start(Operation::read_port_input(Port::Port0))
+        .unwrap();
+    assert!(matches!(nb_tca.poll(), Err(nb::Error::Other(_))));
+    assert!(nb_tca.is_busy());
+    assert_eq!(nb_tca.poll().ok(), Some(Some(0x3C)));
+
+    i2c_mock.done();
+}
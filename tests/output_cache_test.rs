@@ -0,0 +1,63 @@
+#![cfg(not(feature = "async"))]
+
+mod common;
+
+use common::write;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{IoExpander, Pin, PinState, Port, RegisterKind, Tca6424};
+
+#[test]
+fn test_cached_output_reads() {
+    let address = 0x22;
+    let mut expectations: Vec<_> = write(address, 0x04, &[0x0F]).into();
+    // Port 1 is unknown: read once, then cached
+    expectations.push(I2cTransaction::write_read(address, vec![0x05], vec![0xA0]));
+    // Refresh reads every latch, picking up a change made behind the driver's back
+    expectations.push(I2cTransaction::write_read(
+        address,
+        vec![0x84],
+        vec![0x0E, 0xA0, 0x33],
+    ));
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, address).unwrap();
+    assert!(!tca.cached_output_reads());
+    tca.set_cached_output_reads(true);
+
+    // Written values are answered without a bus transfer
+    tca.set_port_output(Port::Port0, 0x0F).unwrap();
+    assert_eq!(tca.get_pin_output_state(Pin::P03).unwrap(), PinState::High);
+    assert_eq!(tca.get_port_output_state(Port::Port0).unwrap(), 0x0F);
+
+    assert_eq!(tca.get_pin_output_state(Pin::P17).unwrap(), PinState::High);
+    assert_eq!(tca.read_outputs(0x00_FF_00).unwrap(), 0x00_A0_00);
+
+    assert_eq!(tca.refresh_output_cache().unwrap(), [0x0E, 0xA0, 0x33]);
+    assert_eq!(
+        tca.cache().get(RegisterKind::Output, Port::Port2),
+        Some(0x33)
+    );
+    #[cfg(feature = "auto-increment")]
+    assert_eq!(tca.get_ports_output_state().unwrap(), [0x0E, 0xA0, 0x33]);
+    assert_eq!(tca.get_pin_output_state(Pin::P00).unwrap(), PinState::Low);
+
+    i2c_mock.done();
+}
+
+#[test]
+fn test_output_reads_hit_the_bus_by_default() {
+    let address = 0x22;
+    let mut expectations: Vec<_> = write(address, 0x04, &[0x0F]).into();
+    expectations.push(I2cTransaction::write_read(address, vec![0x04], vec![0x0E]));
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    tca.set_port_output(Port::Port0, 0x0F).unwrap();
+    assert_eq!(tca.get_pin_output_state(Pin::P00).unwrap(), PinState::Low);
+    // The read does not replace the written value in the cache
+    assert_eq!(
+        tca.cache().get(RegisterKind::Output, Port::Port0),
+        Some(0x0F)
+    );
+
+    i2c_mock.done();
+}
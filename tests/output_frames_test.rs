@@ -0,0 +1,37 @@
+#![cfg(all(
+    feature = "helpers",
+    feature = "auto-increment",
+    not(feature = "async")
+))]
+
+mod common;
+
+use common::write;
+use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+use tca6424::{OutputFrames, Tca6424};
+
+#[test]
+fn test_output_frames_swap() {
+    let address = 0x22;
+    let mut expectations: Vec<_> = write(address, 0x84, &[0x01, 0x80, 0xF0]).into();
+    expectations.extend(write(address, 0x84, &[0x02, 0x40, 0x0F]));
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    let mut frames = OutputFrames::new(0xFF00_0000);
+    assert_eq!(frames.visible(), 0);
+    frames.stage(0xF0_8001);
+    // Staging does not touch the bus
+    assert_eq!(frames.visible(), 0);
+    frames.swap(&mut tca).unwrap();
+    assert_eq!(frames.visible(), 0xF0_8001);
+    // The previously visible frame is now the back buffer
+    assert_eq!(frames.staged(), 0);
+
+    frames.stage(0x0F_4002);
+    assert_eq!(frames.staged(), 0x0F_4002);
+    frames.swap(&mut tca).unwrap();
+    assert_eq!(frames.visible(), 0x0F_4002);
+
+    i2c_mock.done();
+}
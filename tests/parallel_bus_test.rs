@@ -0,0 +1,123 @@
+#![cfg(feature = "helpers")]
+
+mod common;
+
+use common::write;
+use embedded_hal::delay::DelayNs;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::errors::ConfigError;
+use tca6424::{BitOrder, ByteOrder, ParallelBus, Pin, PinDirection, PinState, Port};
+
+#[derive(Default)]
+struct CountingDelay {
+    calls: u32,
+    total_us: u32,
+}
+
+impl DelayNs for CountingDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.calls += 1;
+        self.total_us += ns / 1_000;
+    }
+}
+
+#[test]
+fn test_parallel_bus_encoding() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+
+    let bus = ParallelBus::new(&mut tca, [Port::Port2, Port::Port0]).unwrap();
+    assert_eq!(bus.mask(), 0xFF_00FF);
+    assert_eq!(bus.encode(0xAB_CD12), 0x12_00CD);
+    assert_eq!(bus.decode(0x12_00CD), 0xCD12);
+    assert!(matches!(
+        ParallelBus::new(&mut tca, [Port::Port0]),
+        Err(ConfigError::PinAlreadyClaimed(Pin::P00))
+    ));
+    bus.release(&mut tca);
+
+    assert!(matches!(
+        ParallelBus::new(&mut tca, [Port::Port1, Port::Port1]),
+        Err(ConfigError::PinAlreadyClaimed(Pin::P10))
+    ));
+    let bus = ParallelBus::new(&mut tca, [Port::Port0, Port::Port1, Port::Port2])
+        .unwrap()
+        .with_bit_order(BitOrder::MsbFirst)
+        .with_byte_order(ByteOrder::BigEndian);
+    assert_eq!(bus.encode(0x01_0203), 0xC0_4080);
+    assert_eq!(bus.decode(0xC0_4080), 0x01_0203);
+    bus.release(&mut tca);
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_parallel_bus_write_read_sync() {
+    let address = 0x22;
+    let expectations = [
+        // A whole port needs no read-modify-write; the unchanged second write is skipped
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x05]),
+        I2cTransaction::write(address, vec![0x81]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x01], vec![0x01]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    let bus = ParallelBus::new(&mut tca, [Port::Port1])
+        .unwrap()
+        .with_bit_order(BitOrder::MsbFirst);
+    bus.write(&mut tca, 0x81).unwrap();
+    bus.write(&mut tca, 0x81).unwrap();
+    assert_eq!(bus.read(&mut tca).unwrap(), 0x80);
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_parallel_bus_direction_pin_ordering_sync() {
+    let address = 0x22;
+    let mut expectations = Vec::new();
+    // Output: DIR (P00) high and made an output, settle, then the bus pins
+    expectations.push(I2cTransaction::write_read(address, vec![0x04], vec![0x00]));
+    expectations.extend(write(address, 0x04, &[0x01]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x0C], vec![0xFF]));
+    expectations.extend(write(address, 0x0C, &[0xFE]));
+    expectations.extend(write(address, 0x0D, &[0x00]));
+    // Input: the bus pins first, then DIR low
+    expectations.extend(write(address, 0x0D, &[0xFF]));
+    expectations.extend(write(address, 0x04, &[0x00]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x0C], vec![0xFE]));
+    expectations.extend(write(address, 0x0C, &[0xFE]));
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca =
+        tca6424::Tca6424::new_with_delay(&mut i2c_mock, address, CountingDelay::default()).unwrap();
+
+    let bus = ParallelBus::new(&mut tca, [Port::Port1])
+        .unwrap()
+        .with_direction_pin(&mut tca, Pin::P00, PinState::High, 5)
+        .unwrap();
+    assert_eq!(bus.direction_pin(), Some(Pin::P00));
+    bus.set_direction(&mut tca, PinDirection::Output).unwrap();
+    assert_eq!(tca.delay().total_us, 5);
+    bus.set_direction(&mut tca, PinDirection::Input).unwrap();
+    assert_eq!(tca.delay().calls, 2);
+
+    // A claimed direction pin releases the bus pins too
+    let claim = tca.claim_pin(Pin::P01).unwrap();
+    bus.release(&mut tca);
+    let result = ParallelBus::new(&mut tca, [Port::Port1])
+        .unwrap()
+        .with_direction_pin(&mut tca, Pin::P01, PinState::Low, 0);
+    assert!(matches!(
+        result,
+        Err(ConfigError::PinAlreadyClaimed(Pin::P01))
+    ));
+    claim.release(&mut tca).unwrap();
+    assert_eq!(tca.claimed_mask(), 0);
+
+    i2c_mock.done();
+}
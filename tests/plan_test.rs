@@ -0,0 +1,205 @@
+use core::time::Duration;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+use tca6424::{
+    BusCost, ConfigTransaction, Pin, PinDirection, PinState, PlannedWrite, Port, Register,
+    RegisterCache, RegisterKind, estimated_transaction_time,
+};
+
+/// Turns planned writes into mock expectations.
+fn expect(address: u8, writes: impl Iterator<Item = PlannedWrite>) -> Vec<I2cTransaction> {
+    writes
+        .flat_map(|write| {
+            [
+                I2cTransaction::transaction_start(address),
+                I2cTransaction::write(address, vec![write.command()]),
+                I2cTransaction::write(address, write.payload().to_vec()),
+                I2cTransaction::transaction_end(address),
+            ]
+        })
+        .collect()
+}
+
+#[test]
+fn test_planned_write() {
+    let write = PlannedWrite::new(Register::OutputPort1, &[0xAA, 0x55]).unwrap();
+    assert_eq!(write.register(), Register::OutputPort1);
+    assert_eq!(write.payload(), &[0xAA, 0x55]);
+    assert!(write.is_auto_increment());
+    assert_eq!(write.command(), 0x85);
+    assert_eq!(write.to_string(), "Output Port 1 (AI) <- 0xaa 0x55");
+
+    let write = PlannedWrite::new(Register::ConfigurationPort2, &[0xF0]).unwrap();
+    assert!(!write.is_auto_increment());
+    assert_eq!(write.to_string(), "Configuration Port 2 <- 0xf0");
+}
+
+#[test]
+fn test_bus_cost() {
+    assert_eq!(BusCost::PORT_WRITE.bytes(), 3);
+    assert_eq!(BusCost::PORT_READ.bytes(), 4);
+    assert_eq!(BusCost::PORT_READ.bus_clocks(), 39);
+    assert_eq!(BusCost::FAMILY_WRITE.bus_clocks(), 47);
+    assert_eq!(BusCost::PIN_WRITE, BusCost::PORT_READ + BusCost::PORT_WRITE);
+    assert_eq!(BusCost::PORT_WRITE.times(3).transactions(), 3);
+    assert_eq!(BusCost::default(), BusCost::NONE);
+
+    // 100 kHz: 10 us per clock
+    assert_eq!(
+        BusCost::INPUTS_READ.estimated_time(100_000),
+        Duration::from_micros(570)
+    );
+    assert_eq!(
+        estimated_transaction_time(3, 1_000_000),
+        Duration::from_micros(29)
+    );
+    assert_eq!(estimated_transaction_time(3, 0), Duration::MAX);
+
+    // A plan reports the cost of its writes
+    let mut txn = ConfigTransaction::new();
+    txn.port_output(Port::Port0, 0x0F)
+        .port_output(Port::Port1, 0xF0)
+        .port_direction(Port::Port2, 0x00);
+    let cost: BusCost = txn
+        .plan(&RegisterCache::new())
+        .map(|write| write.cost())
+        .sum();
+    assert_eq!(cost, BusCost::write(2) + BusCost::PORT_WRITE);
+}
+
+#[test]
+fn test_planned_write_rejects_empty_payload() {
+    assert_eq!(PlannedWrite::new(Register::OutputPort0, &[]), None);
+    assert_eq!(PlannedWrite::new(Register::OutputPort0, &[0; 4]), None);
+}
+
+#[test]
+fn test_config_transaction_plan() {
+    let mut txn = ConfigTransaction::new();
+    txn.direction(Pin::P00, PinDirection::Output)
+        .port_direction(Port::Port2, 0xF0)
+        .output(Pin::P00, PinState::Low)
+        .port_output(Port::Port1, 0xAA);
+
+    // Unknown registers are planned from their power-on defaults
+    let writes: Vec<_> = txn.plan(&RegisterCache::new()).collect();
+    assert_eq!(
+        writes,
+        [
+            PlannedWrite::new(Register::OutputPort0, &[0xFE, 0xAA]).unwrap(),
+            PlannedWrite::new(Register::ConfigurationPort0, &[0xFE]).unwrap(),
+            PlannedWrite::new(Register::ConfigurationPort2, &[0xF0]).unwrap(),
+        ]
+    );
+
+    // Known values that already match are not written
+    let mut cache = RegisterCache::new();
+    cache.set(RegisterKind::Output, Port::Port0, 0x00);
+    cache.set(RegisterKind::Output, Port::Port1, 0xAA);
+    cache.set(RegisterKind::Configuration, Port::Port0, 0xFE);
+    let writes: Vec<_> = txn.plan(&cache).collect();
+    assert_eq!(
+        writes,
+        [PlannedWrite::new(Register::ConfigurationPort2, &[0xF0]).unwrap()]
+    );
+    assert_eq!(ConfigTransaction::new().plan(&cache).count(), 0);
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_config_transaction_plan_matches_commit() {
+    let address = 0x22;
+    let mut setup = ConfigTransaction::new();
+    setup
+        .port_output(Port::Port0, 0x00)
+        .port_output(Port::Port1, 0x00)
+        .port_output(Port::Port2, 0x00)
+        .port_direction(Port::Port0, 0xFF)
+        .port_direction(Port::Port1, 0xFF)
+        .port_direction(Port::Port2, 0xFF);
+    let mut txn = ConfigTransaction::new();
+    txn.output(Pin::P10, PinState::High)
+        .output(Pin::P27, PinState::High)
+        .direction(Pin::P10, PinDirection::Output)
+        .direction(Pin::P11, PinDirection::Output);
+
+    // Every setup port is fully staged, so the plan does not depend on the cache
+    let mut expectations = expect(address, setup.plan(&RegisterCache::new()));
+    // Plan the change against the cache the setup leaves behind
+    let mut planned = RegisterCache::new();
+    for port in Port::ALL {
+        planned.set(RegisterKind::Output, port, 0x00);
+        planned.set(RegisterKind::Configuration, port, 0xFF);
+    }
+    let writes: Vec<_> = txn.plan(&planned).collect();
+    assert_eq!(
+        writes,
+        [
+            PlannedWrite::new(Register::OutputPort1, &[0x01, 0x80]).unwrap(),
+            PlannedWrite::new(Register::ConfigurationPort1, &[0xFC]).unwrap(),
+        ]
+    );
+    expectations.extend(expect(address, writes.into_iter()));
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    tca.apply_config_transaction(&setup).unwrap();
+    assert_eq!(tca.cache(), &planned);
+    tca.apply_config_transaction(&txn).unwrap();
+    i2c_mock.done();
+}
+
+#[cfg(feature = "helpers")]
+#[test]
+fn test_board_config_plan() {
+    let config = tca6424::BoardConfig::new()
+        .output(Pin::P00, false, true)
+        .output(Pin::P12, true, false)
+        .input(Pin::P20, true);
+    let writes: Vec<_> = config.plan().collect();
+    assert_eq!(
+        writes,
+        [
+            PlannedWrite::new(Register::OutputPort0, &[0x01, 0x04, 0x00]).unwrap(),
+            PlannedWrite::new(Register::ConfigurationPort0, &[0xFE, 0xFB, 0xFF]).unwrap(),
+        ]
+    );
+
+    #[cfg(not(feature = "async"))]
+    {
+        let expectations = expect(0x22, config.plan());
+        let mut i2c_mock = I2cMock::new(&expectations);
+        let mut tca = tca6424::Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+        tca.apply_board_config(&config).unwrap();
+        i2c_mock.done();
+    }
+}
+
+#[cfg(feature = "bank")]
+#[test]
+fn test_expander_bank_plan() {
+    let mut i2c_a = I2cMock::new(&[]);
+    let mut i2c_b = I2cMock::new(&[]);
+    let bank = tca6424::ExpanderBank::new([
+        tca6424::Tca6424::new(&mut i2c_a, 0x22).unwrap(),
+        tca6424::Tca6424::new(&mut i2c_b, 0x23).unwrap(),
+    ]);
+    let writes: Vec<_> = bank
+        .plan(&[[0x01, 0x02, 0x03], [0xA1, 0xA2, 0xA3]])
+        .collect();
+    assert_eq!(
+        writes,
+        [
+            (
+                0,
+                PlannedWrite::new(Register::OutputPort0, &[0x01, 0x02, 0x03]).unwrap()
+            ),
+            (
+                1,
+                PlannedWrite::new(Register::OutputPort0, &[0xA1, 0xA2, 0xA3]).unwrap()
+            ),
+        ]
+    );
+    i2c_a.done();
+    i2c_b.done();
+}
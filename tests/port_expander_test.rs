@@ -0,0 +1,109 @@
+#![cfg(all(feature = "port-expander", not(feature = "async")))]
+
+use embedded_hal::digital::{InputPin, OutputPin, StatefulOutputPin};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::errors::ErrorKind;
+use tca6424::port_expander::PortExpander;
+
+#[test]
+fn test_port_expander_output_pin_sync() {
+    let address = 0x22;
+    let expectations = [
+        // into_output_high: latch P12 high, then make it an output
+        I2cTransaction::write_read(address, vec![0x05], vec![0x00]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x05]),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x0D], vec![0xFF]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x0D]),
+        I2cTransaction::write(address, vec![0xFB]),
+        I2cTransaction::transaction_end(address),
+        // toggle: read latch, drive low
+        I2cTransaction::write_read(address, vec![0x05], vec![0x04]),
+        I2cTransaction::write_read(address, vec![0x05], vec![0x04]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x05]),
+        I2cTransaction::write(address, vec![0x00]),
+        I2cTransaction::transaction_end(address),
+        // set_high through embedded-hal
+        I2cTransaction::write_read(address, vec![0x05], vec![0x00]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x05]),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::transaction_end(address),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = PortExpander::new(tca6424::Tca6424::new(&mut i2c_mock, address).unwrap());
+
+    {
+        let pins = tca.split();
+        let mut led = pins.p12.into_output_high().unwrap();
+        assert_eq!(led.pin_mask(), 1 << 10);
+        StatefulOutputPin::toggle(&mut led).unwrap();
+        OutputPin::set_high(&mut led).unwrap();
+    }
+
+    let _ = tca.into_inner();
+    i2c_mock.done();
+}
+
+#[test]
+fn test_port_expander_input_pin_sync() {
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x02], vec![0x01]),
+        I2cTransaction::write_read(address, vec![0x02], vec![0xFE]),
+        I2cTransaction::write_read(address, vec![0x00], vec![0x00]).with_error(
+            embedded_hal::i2c::ErrorKind::NoAcknowledge(
+                embedded_hal::i2c::NoAcknowledgeSource::Address,
+            ),
+        ),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = PortExpander::new(tca6424::Tca6424::new(&mut i2c_mock, address).unwrap());
+
+    {
+        let mut pins = tca.split();
+        assert!(InputPin::is_high(&mut pins.p20).unwrap());
+        assert!(pins.p20.is_low().unwrap());
+        let error = pins.p00.is_high().unwrap_err();
+        assert!(matches!(
+            error.driver_error(),
+            tca6424::errors::Error::I2c(_)
+        ));
+        assert_eq!(
+            error.kind(),
+            ErrorKind::Bus(embedded_hal::i2c::ErrorKind::NoAcknowledge(
+                embedded_hal::i2c::NoAcknowledgeSource::Address
+            ))
+        );
+        assert!(error.i2c_error().is_some());
+    }
+
+    let _ = tca.into_inner();
+    i2c_mock.done();
+}
+
+#[test]
+fn test_port_expander_reentrant_access_is_busy_sync() {
+    let address = 0x22;
+    let expectations = [I2cTransaction::write_read(address, vec![0x00], vec![0x01])];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = PortExpander::new(tca6424::Tca6424::new(&mut i2c_mock, address).unwrap());
+
+    {
+        let pins = tca.split();
+        let button = pins.p00;
+        let inner = button
+            .access_port_driver(|_| Ok(pins.p01.is_high()))
+            .unwrap();
+        // The driver was already borrowed: no transfer, no panic
+        assert_eq!(inner.unwrap_err().kind(), ErrorKind::Busy);
+        assert_eq!(button.is_high(), Ok(true));
+    }
+
+    let _ = tca.into_inner();
+    i2c_mock.done();
+}
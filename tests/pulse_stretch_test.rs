@@ -0,0 +1,52 @@
+#![cfg(feature = "helpers")]
+
+use tca6424::{InputWatcher, Pin, Port, PulseStretcher};
+
+#[test]
+fn test_short_pulse_is_stretched() {
+    let mut stretcher = PulseStretcher::new(tca6424::pins!(P00, P01), 20);
+    assert_eq!(stretcher.process(0, 1000), 0);
+
+    // A 1 ms pulse on P00, caught by two interrupt reads; P10 is not watched
+    assert_eq!(stretcher.process(tca6424::pins!(P00, P10), 1000), 0x01);
+    assert_eq!(stretcher.process(0, 1001), 0);
+    assert!(stretcher.is_high(Pin::P00));
+    assert_eq!(stretcher.held_mask(), 0x01);
+    assert_eq!(stretcher.wait_ms(1005), Some(15));
+
+    // The end of the pulse is reported once the minimum width has passed
+    assert_eq!(stretcher.expire(1019), 0);
+    assert_eq!(stretcher.expire(1020), 0x01);
+    assert_eq!(stretcher.levels(), 0);
+    assert_eq!(stretcher.wait_ms(1020), None);
+
+    // A long pulse is reported unchanged; a glitch back within the hold adds nothing
+    assert_eq!(stretcher.process(tca6424::pins!(P01), 1100), 0x02);
+    assert_eq!(stretcher.process(0, 1105), 0);
+    assert_eq!(stretcher.process(tca6424::pins!(P01), 1110), 0);
+    assert_eq!(stretcher.process(0, 1150), 0x02);
+    assert_eq!(stretcher.min_width_ms(), 20);
+}
+
+#[test]
+fn test_stretcher_fed_by_latching_watcher() {
+    let mut watcher = InputWatcher::new()
+        .with_port(Port::Port0)
+        .with_latching(true);
+    let mut stretcher = PulseStretcher::new(0xFF, 10);
+    watcher.request_full_resync();
+    assert_eq!(stretcher.process_changes(&watcher.update(0x80), 0), 0x80);
+    assert_eq!(stretcher.held_mask(), 0);
+
+    let changes = watcher.update(0x81);
+    assert_eq!(stretcher.process_changes(&changes, 5), 0x01);
+    let changes = watcher.update(0x80);
+    assert_eq!(stretcher.process_changes(&changes, 6), 0);
+
+    // The consumer comes around later: the pin is latched and its level was held
+    assert_eq!(watcher.latched_mask(), 0x01);
+    assert!(stretcher.is_high(Pin::P00));
+    assert!(watcher.acknowledge(Pin::P00));
+    assert_eq!(stretcher.expire(15), 0x01);
+    assert_eq!(stretcher.levels(), 0x80);
+}
@@ -0,0 +1,162 @@
+#![cfg(feature = "operations")]
+
+use embedded_hal::i2c::ErrorKind;
+use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+use embedded_hal_mock::eh1::i2c::Transaction as I2cTransaction;
+use tca6424::{OpQueue, Operation, Pin, PinDirection, Port, Priority, ServiceResult};
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_op_queue_one_transaction_per_service_sync() {
+    let address = 0x22;
+
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x0C], vec![0xFF]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x0C]),
+        I2cTransaction::write(address, vec![0xFB]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x06]),
+        I2cTransaction::write(address, vec![0x55]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x01], vec![0x3C]),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    let mut queue: OpQueue<4> = OpQueue::new();
+    assert_eq!(queue.service(&mut tca).unwrap(), ServiceResult::Idle);
+
+    let direction = Operation::set_pin_direction(Pin::P02, PinDirection::Output);
+    queue.enqueue(direction).unwrap();
+    queue
+        .enqueue(Operation::set_port_output(Port::Port2, 0x55))
+        .unwrap();
+    queue
+        .enqueue(Operation::read_port_input(Port::Port1))
+        .unwrap();
+    assert_eq!(queue.len(), 3);
+    assert_eq!(queue.pending_transactions(), 4);
+
+    assert_eq!(queue.service(&mut tca).unwrap(), ServiceResult::Progressed);
+    assert_eq!(queue.pending_transactions(), 3);
+    assert_eq!(
+        queue.service(&mut tca).unwrap(),
+        ServiceResult::Completed {
+            operation: direction,
+            value: None
+        }
+    );
+    assert!(matches!(
+        queue.service(&mut tca).unwrap(),
+        ServiceResult::Completed { value: None, .. }
+    ));
+    assert!(matches!(
+        queue.service(&mut tca).unwrap(),
+        ServiceResult::Completed {
+            value: Some(0x3C),
+            ..
+        }
+    ));
+    assert!(queue.is_empty());
+    assert_eq!(queue.service(&mut tca).unwrap(), ServiceResult::Idle);
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_op_queue_full_and_retry_sync() {
+    let address = 0x22;
+
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x01], vec![0x00]).with_error(ErrorKind::Other),
+        I2cTransaction::write_read(address, vec![0x01], vec![0xA5]),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    let mut queue: OpQueue<1> = OpQueue::new();
+    let op = Operation::read_port_input(Port::Port1);
+    queue.enqueue(op).unwrap();
+    assert!(queue.is_full());
+    assert_eq!(queue.enqueue(op), Err(op));
+
+    assert!(queue.service(&mut tca).is_err());
+    assert_eq!(queue.len(), 1);
+    assert_eq!(
+        queue.service(&mut tca).unwrap(),
+        ServiceResult::Completed {
+            operation: op,
+            value: Some(0xA5)
+        }
+    );
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_op_queue_high_priority_first_sync() {
+    let address = 0x22;
+
+    let expectations = [
+        // The cosmetic read-modify-write already in flight is finished first
+        I2cTransaction::write_read(address, vec![0x04], vec![0x00]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0x01]),
+        I2cTransaction::transaction_end(address),
+        // Then the safety output overtakes the older normal operation
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x06]),
+        I2cTransaction::write(address, vec![0x00]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x01], vec![0x3C]),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    let mut queue: OpQueue<1> = OpQueue::new();
+    let led = Operation::set_pin_output(Pin::P00, tca6424::PinState::High);
+    let read = Operation::read_port_input(Port::Port1);
+    let safe = Operation::set_port_output(Port::Port2, 0x00);
+    queue.enqueue(led).unwrap();
+    assert_eq!(queue.service(&mut tca).unwrap(), ServiceResult::Progressed);
+    queue.enqueue(read).unwrap();
+    assert!(queue.is_full());
+    assert!(!queue.is_full_for(Priority::High));
+    queue.enqueue_with_priority(safe, Priority::High).unwrap();
+    assert_eq!(queue.enqueue_with_priority(safe, Priority::High), Err(safe));
+    assert_eq!(queue.len(), 3);
+    assert_eq!(queue.pending_transactions(), 3);
+
+    assert_eq!(
+        queue.service(&mut tca).unwrap(),
+        ServiceResult::Completed {
+            operation: led,
+            value: None
+        }
+    );
+    assert_eq!(
+        queue.service(&mut tca).unwrap(),
+        ServiceResult::Completed {
+            operation: safe,
+            value: None
+        }
+    );
+    assert_eq!(
+        queue.service(&mut tca).unwrap(),
+        ServiceResult::Completed {
+            operation: read,
+            value: Some(0x3C)
+        }
+    );
+    assert!(queue.is_empty());
+
+    i2c_mock.done();
+}
@@ -0,0 +1,40 @@
+#![cfg(all(feature = "auto-increment", not(feature = "async")))]
+
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{Port, ReadSequence, Tca6424};
+
+#[test]
+fn test_repeated_start_by_default() {
+    let address = 0x22;
+    // Command byte, repeated start, data: one `write_read`
+    let expectations = [I2cTransaction::write_read(address, vec![0x01], vec![0x5A])];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, address).unwrap();
+    assert_eq!(tca.read_sequence(), ReadSequence::RepeatedStart);
+
+    assert_eq!(tca.get_port_input_state(Port::Port1).unwrap(), 0x5A);
+    i2c_mock.done();
+}
+
+#[test]
+fn test_write_then_read() {
+    let address = 0x22;
+    // The command byte in its own transaction, then a plain read from the same register
+    let expectations = [
+        I2cTransaction::write(address, vec![0x01]),
+        I2cTransaction::read(address, vec![0x5A]),
+        I2cTransaction::write(address, vec![0x80]),
+        I2cTransaction::read(address, vec![0x01, 0x02, 0x03]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, address).unwrap();
+    tca.set_read_sequence(ReadSequence::WriteThenRead);
+    assert_eq!(tca.read_sequence(), ReadSequence::WriteThenRead);
+
+    assert_eq!(tca.get_port_input_state(Port::Port1).unwrap(), 0x5A);
+    let mut inputs = [0u8; 3];
+    tca.get_ports_input_state_ai(Port::Port0, &mut inputs)
+        .unwrap();
+    assert_eq!(inputs, [0x01, 0x02, 0x03]);
+    i2c_mock.done();
+}
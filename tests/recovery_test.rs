@@ -0,0 +1,313 @@
+mod common;
+
+#[cfg(not(feature = "async"))]
+use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+#[cfg(not(feature = "async"))]
+use embedded_hal_mock::eh1::i2c::Transaction as I2cTransaction;
+use tca6424::{Port, RegisterKind};
+
+#[cfg(not(feature = "async"))]
+#[cfg(feature = "auto-increment")]
+#[test]
+fn test_check_and_recover_detects_reset_sync() {
+    let address = 0x22;
+
+    let expectations = [
+        // Configure outputs, then directions
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x84]),
+        I2cTransaction::write(address, vec![0x01, 0x02, 0x03]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x8C]),
+        I2cTransaction::write(address, vec![0xF0, 0x0F, 0xFF]),
+        I2cTransaction::transaction_end(address),
+        // Configuration reads back at power-on defaults
+        I2cTransaction::write_read(address, vec![0x8C], vec![0xFF, 0xFF, 0xFF]),
+        // Outputs are restored before directions
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x84]),
+        I2cTransaction::write(address, vec![0x01, 0x02, 0x03]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x8C]),
+        I2cTransaction::write(address, vec![0xF0, 0x0F, 0xFF]),
+        I2cTransaction::transaction_end(address),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    tca.set_ports_output_ai(Port::Port0, &[0x01, 0x02, 0x03])
+        .unwrap();
+    tca.set_ports_direction_ai(Port::Port0, &[0xF0, 0x0F, 0xFF])
+        .unwrap();
+    assert!(tca.check_and_recover().unwrap());
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_check_and_recover_no_reset_sync() {
+    let address = 0x22;
+
+    let expectations = [
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x0D]),
+        I2cTransaction::write(address, vec![0x00]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0xAA]),
+        I2cTransaction::transaction_end(address),
+        // Configuration still matches, nothing is rewritten
+        I2cTransaction::write_read(address, vec![0x8C], vec![0xFF, 0x00, 0xFF]),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    tca.set_port_direction(Port::Port1, 0x00).unwrap();
+    tca.set_port_output(Port::Port0, 0xAA).unwrap();
+    assert!(!tca.check_and_recover().unwrap());
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_restore_from_cache_partial_sync() {
+    let address = 0x22;
+
+    let expectations = [
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x0D]),
+        I2cTransaction::write(address, vec![0x00]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0xAA]),
+        I2cTransaction::transaction_end(address),
+        // Partially cached families are restored one register at a time
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0xAA]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x0D]),
+        I2cTransaction::write(address, vec![0x00]),
+        I2cTransaction::transaction_end(address),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    tca.set_port_direction(Port::Port1, 0x00).unwrap();
+    tca.set_port_output(Port::Port0, 0xAA).unwrap();
+    assert_eq!(
+        tca.cache().get(RegisterKind::Configuration, Port::Port1),
+        Some(0x00)
+    );
+    assert_eq!(
+        tca.cache().get(RegisterKind::Configuration, Port::Port0),
+        None
+    );
+    tca.restore_from_cache().unwrap();
+
+    i2c_mock.done();
+}
+
+/// Bus whose write transactions never complete while `stalled` is set; the panic stands in for
+/// the driver future being dropped mid-transfer.
+#[cfg(not(feature = "async"))]
+struct StallingBus<'a> {
+    inner: I2cMock,
+    stalled: &'a core::cell::Cell<bool>,
+}
+
+#[cfg(not(feature = "async"))]
+impl embedded_hal::i2c::ErrorType for StallingBus<'_> {
+    type Error = embedded_hal::i2c::ErrorKind;
+}
+
+#[cfg(not(feature = "async"))]
+impl embedded_hal::i2c::I2c for StallingBus<'_> {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [embedded_hal::i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        assert!(!self.stalled.get(), "bus stalled");
+        self.inner.transaction(address, operations)
+    }
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.inner.write_read(address, write, read)
+    }
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_sync_after_cancel_resolves_interrupted_write_sync() {
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+    use tca6424::{Pin, PinState};
+
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x04], vec![0x00]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0x01]),
+        I2cTransaction::transaction_end(address),
+        // Read-modify-write whose write stalls
+        I2cTransaction::write_read(address, vec![0x04], vec![0x01]),
+        // sync_after_cancel reads the dirty register back
+        I2cTransaction::write_read(address, vec![0x04], vec![0x03]),
+    ];
+    let stalled = core::cell::Cell::new(false);
+    let mut bus = StallingBus {
+        inner: I2cMock::new(&expectations),
+        stalled: &stalled,
+    };
+    let mut tca = tca6424::Tca6424::new(&mut bus, address).unwrap();
+
+    tca.set_pin_output(Pin::P00, PinState::High).unwrap();
+    assert!(!tca.cache().is_dirty());
+
+    stalled.set(true);
+    let interrupted = catch_unwind(AssertUnwindSafe(|| {
+        let _ = tca.set_pin_output(Pin::P01, PinState::High);
+    }));
+    assert!(interrupted.is_err());
+    stalled.set(false);
+
+    // The cache no longer claims a value for the interrupted register
+    assert!(
+        tca.cache()
+            .is_register_dirty(RegisterKind::Output, Port::Port0)
+    );
+    assert_eq!(tca.cache().get(RegisterKind::Output, Port::Port0), None);
+
+    assert!(tca.sync_after_cancel().unwrap());
+    assert!(!tca.cache().is_dirty());
+    assert_eq!(
+        tca.cache().get(RegisterKind::Output, Port::Port0),
+        Some(0x03)
+    );
+    assert!(!tca.sync_after_cancel().unwrap());
+
+    bus.inner.done();
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_sync_after_cancel_resolves_dropped_write() {
+    use common::{BusMode, StallingBus, poll_once_and_drop};
+    use tca6424::{Pin, PinState};
+
+    let bus = StallingBus::new(BusMode::Answer);
+    let mut tca = tca6424::Tca6424::new(&bus, 0x22).unwrap();
+
+    tca.set_pin_output(Pin::P00, PinState::High).await.unwrap();
+    assert!(!tca.cache().is_dirty());
+
+    // The read-modify-write reads Output Port 0, then its write never completes
+    bus.input.set(0x01);
+    bus.mode.set(BusMode::StallWrites);
+    poll_once_and_drop(tca.set_pin_output(Pin::P01, PinState::High));
+    bus.mode.set(BusMode::Answer);
+
+    // The cache no longer claims a value for the interrupted register
+    assert!(
+        tca.cache()
+            .is_register_dirty(RegisterKind::Output, Port::Port0)
+    );
+    assert_eq!(tca.cache().get(RegisterKind::Output, Port::Port0), None);
+
+    // sync_after_cancel reads the dirty register back
+    bus.input.set(0x03);
+    assert!(tca.sync_after_cancel().await.unwrap());
+    assert!(!tca.cache().is_dirty());
+    assert_eq!(
+        tca.cache().get(RegisterKind::Output, Port::Port0),
+        Some(0x03)
+    );
+    assert!(!tca.sync_after_cancel().await.unwrap());
+    assert_eq!(*bus.writes.borrow(), [(0x04, vec![0x01])]);
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_retarget_and_set_address_sync() {
+    let expectations = [
+        // Configure the device at 0x22
+        I2cTransaction::transaction_start(0x22),
+        I2cTransaction::write(0x22, vec![0x04]),
+        I2cTransaction::write(0x22, vec![0x5A]),
+        I2cTransaction::transaction_end(0x22),
+        I2cTransaction::transaction_start(0x22),
+        I2cTransaction::write(0x22, vec![0x0C]),
+        I2cTransaction::write(0x22, vec![0x00]),
+        I2cTransaction::transaction_end(0x22),
+        // retarget(0x23): probe, then the template, outputs first
+        I2cTransaction::write_read(0x23, vec![0x8C], vec![0xFF, 0xFF, 0xFF]),
+        I2cTransaction::transaction_start(0x23),
+        I2cTransaction::write(0x23, vec![0x04]),
+        I2cTransaction::write(0x23, vec![0x5A]),
+        I2cTransaction::transaction_end(0x23),
+        I2cTransaction::transaction_start(0x23),
+        I2cTransaction::write(0x23, vec![0x0C]),
+        I2cTransaction::write(0x23, vec![0x00]),
+        I2cTransaction::transaction_end(0x23),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+    assert_eq!(tca.address(), 0x22);
+
+    tca.set_port_output(Port::Port0, 0x5A).unwrap();
+    tca.set_port_direction(Port::Port0, 0x00).unwrap();
+
+    tca.retarget(0x23).unwrap();
+    assert_eq!(tca.address(), 0x23);
+    assert_eq!(
+        tca.cache().get(RegisterKind::Output, Port::Port0),
+        Some(0x5A)
+    );
+
+    // set_address forgets the previous device without touching the bus
+    tca.set_address(0x24);
+    assert_eq!(tca.address(), 0x24);
+    assert!(tca.cache().is_empty());
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_retarget_forgets_the_previous_inputs_sync() {
+    let expectations = [
+        // Interrupt image of the device at 0x22
+        I2cTransaction::write_read(0x22, vec![0x80], vec![0x00, 0x00, 0x00]),
+        // retarget(0x23) with an empty template: probe only
+        I2cTransaction::write_read(0x23, vec![0x8C], vec![0xFF, 0xFF, 0xFF]),
+        // The new device differs from the old image, but its first read is a baseline
+        I2cTransaction::write_read(0x23, vec![0x80], vec![0xFF, 0x00, 0x00]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+
+    assert!(tca.get_interrupt_sources().unwrap().is_empty());
+    tca.retarget(0x23).unwrap();
+    assert!(tca.get_interrupt_sources().unwrap().is_empty());
+    assert_eq!(tca.port_activity(Port::Port0), Default::default());
+    #[cfg(feature = "stats")]
+    assert_eq!(tca.pin_stats(tca6424::Pin::P00).input_changes, 0);
+
+    i2c_mock.done();
+}
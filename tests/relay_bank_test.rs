@@ -0,0 +1,114 @@
+#![cfg(feature = "helpers")]
+
+mod common;
+
+use common::write;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::errors::{ConfigError, Error};
+use tca6424::{DwellPolicy, Pin, RelayBank, RelayBankConfig, RelaySwitch};
+
+#[test]
+fn test_relay_bank_claims_pins() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+
+    assert!(matches!(
+        RelayBank::new(&mut tca, [Pin::P00, Pin::P00], RelayBankConfig::default()),
+        Err(ConfigError::PinAlreadyClaimed(Pin::P00))
+    ));
+    let mut bank =
+        RelayBank::new(&mut tca, [Pin::P00, Pin::P10], RelayBankConfig::default()).unwrap();
+    assert_eq!(tca.claimed_mask(), 0x0101);
+    bank.set_operations(1, 12_000);
+    assert_eq!(bank.operations(1), 12_000);
+    assert_eq!(bank.operations(2), 0);
+    bank.release(&mut tca);
+    assert_eq!(tca.claimed_mask(), 0);
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_relay_bank_dwell_sync() {
+    let address = 0x22;
+    let mut expectations = Vec::new();
+    // init: both relays off, then outputs
+    expectations.push(I2cTransaction::write_read(address, vec![0x04], vec![0x00]));
+    expectations.extend(write(address, 0x04, &[0x00]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x0C], vec![0xFF]));
+    expectations.extend(write(address, 0x0C, &[0xFC]));
+    // Relay 0 on, then off once its minimum on time has passed
+    expectations.extend(write(address, 0x04, &[0x01]));
+    expectations.extend(write(address, 0x04, &[0x00]));
+    // Active-low bank on P02: off, outputs, on
+    expectations.extend(write(address, 0x04, &[0x04]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x0C], vec![0xFC]));
+    expectations.extend(write(address, 0x0C, &[0xF8]));
+    expectations.extend(write(address, 0x04, &[0x00]));
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    let config = RelayBankConfig {
+        min_on_ms: 100,
+        min_off_ms: 200,
+        ..RelayBankConfig::default()
+    };
+    let mut bank = RelayBank::new(&mut tca, [Pin::P00, Pin::P01], config).unwrap();
+    bank.init(&mut tca).unwrap();
+    assert!(matches!(
+        bank.set_relay(&mut tca, 2, true, 0),
+        Err(Error::InvalidRegisterOrPin)
+    ));
+
+    assert_eq!(
+        bank.set_relay(&mut tca, 0, true, 0).unwrap(),
+        RelaySwitch::Switched
+    );
+    assert_eq!(
+        bank.set_relay(&mut tca, 0, false, 50).unwrap(),
+        RelaySwitch::Deferred
+    );
+    assert_eq!(bank.pending_mask(), 0b01);
+    assert_eq!(bank.poll(&mut tca, 99).unwrap(), 0);
+    assert_eq!(bank.poll(&mut tca, 100).unwrap(), 0b01);
+    assert!(!bank.is_on(0));
+    assert_eq!(bank.operations(0), 2);
+
+    // Too soon after switching off; asking for the current state cancels the request
+    assert_eq!(
+        bank.set_relay(&mut tca, 0, true, 150).unwrap(),
+        RelaySwitch::Deferred
+    );
+    assert_eq!(
+        bank.set_relay(&mut tca, 0, false, 160).unwrap(),
+        RelaySwitch::Unchanged
+    );
+    assert_eq!(bank.poll(&mut tca, 400).unwrap(), 0);
+    assert_eq!(bank.operations(0), 2);
+
+    let mut rejecting = RelayBank::new(
+        &mut tca,
+        [Pin::P02],
+        RelayBankConfig {
+            active_low: true,
+            min_on_ms: 100,
+            policy: DwellPolicy::Reject,
+            ..RelayBankConfig::default()
+        },
+    )
+    .unwrap();
+    rejecting.init(&mut tca).unwrap();
+    assert_eq!(
+        rejecting.set_relay(&mut tca, 0, true, 0).unwrap(),
+        RelaySwitch::Switched
+    );
+    assert_eq!(
+        rejecting.set_relay(&mut tca, 0, false, 10).unwrap(),
+        RelaySwitch::Rejected
+    );
+    assert_eq!(rejecting.pending_mask(), 0);
+    assert!(rejecting.is_on(0));
+
+    i2c_mock.done();
+}
@@ -0,0 +1,87 @@
+use tca6424::{InputReplay, InputWatcher, Pin, PinState, Port, ReplayStep, pins};
+
+static SEQUENCE: [ReplayStep; 4] = [
+    ReplayStep::new(5, Pin::P00, PinState::High),
+    ReplayStep::new(5, Pin::P10, PinState::High),
+    ReplayStep::new(20, Pin::P01, PinState::High),
+    ReplayStep::new(40, Pin::P00, PinState::Low),
+];
+
+#[test]
+fn test_replay_poll_applies_due_steps() {
+    let watcher = InputWatcher::new().with_port(Port::Port0);
+    let mut replay = InputReplay::new(watcher, 0, &SEQUENCE);
+
+    assert!(replay.poll(1_000).is_empty());
+    // Both steps at 5 ms; only the Port 0 one is subscribed
+    let changes = replay.poll(1_005);
+    assert_eq!(changes.changed_mask(), pins!(P00));
+    assert_eq!(replay.inputs(), pins!(P00, P10));
+    // A late poll reports everything that happened since the previous one
+    let changes = replay.poll(1_100);
+    assert_eq!(changes.changed_mask(), pins!(P00, P01));
+    let events: Vec<_> = changes.iter().collect();
+    assert_eq!(events[0].state, PinState::Low);
+    assert_eq!(events[1].state, PinState::High);
+    assert!(replay.is_finished());
+    assert!(replay.poll(1_200).is_empty());
+}
+
+#[test]
+fn test_replay_next_changes_steps_through_sequence() {
+    let mut replay = InputReplay::new(InputWatcher::new().with_port(Port::Port1), 0, &SEQUENCE);
+
+    assert_eq!(replay.next_changes().unwrap().changed_mask(), pins!(P10));
+    // Steps on unsubscribed pins still advance the replay
+    assert!(replay.next_changes().unwrap().is_empty());
+    assert!(replay.next_changes().unwrap().is_empty());
+    assert_eq!(replay.next_changes(), None);
+
+    replay.restart(pins!(P10));
+    assert_eq!(replay.inputs(), pins!(P10));
+    replay.watcher_mut().subscribe_pin(Pin::P00);
+    assert_eq!(replay.next_changes().unwrap().changed_mask(), pins!(P00));
+    assert!(!replay.is_finished());
+}
+
+#[cfg(feature = "capture")]
+#[test]
+fn test_replay_feed_record() {
+    use tca6424::{TransferDirection, TransferOutcome, TransferRecord};
+
+    let read = |command, data: &[u8]| {
+        let mut bytes = [0; tca6424::CAPTURED_BYTES];
+        bytes[..data.len()].copy_from_slice(data);
+        TransferRecord {
+            command,
+            direction: TransferDirection::Read,
+            len: data.len() as u8,
+            bytes,
+            outcome: TransferOutcome::Ok,
+        }
+    };
+    let watcher = InputWatcher::new()
+        .with_port(Port::Port0)
+        .with_port(Port::Port1)
+        .with_port(Port::Port2);
+    let mut replay = InputReplay::new(watcher, 0, &[]);
+
+    // Auto-increment read of all three ports
+    let changes = replay
+        .feed_record(&read(0x80, &[0x01, 0x02, 0x04]))
+        .unwrap();
+    assert_eq!(changes.changed_mask(), 0x04_02_01);
+    // Single-port read of Port 1
+    let changes = replay.feed_record(&read(0x01, &[0x00])).unwrap();
+    assert_eq!(changes.changed_mask(), 0x00_02_00);
+    assert_eq!(replay.inputs(), 0x04_00_01);
+    // Writes, failed reads and other registers are ignored
+    let mut write = read(0x04, &[0xFF]);
+    write.direction = TransferDirection::Write;
+    assert_eq!(replay.feed_record(&write), None);
+    let mut failed = read(0x80, &[0, 0, 0]);
+    failed.outcome = TransferOutcome::Nack;
+    assert_eq!(replay.feed_record(&failed), None);
+    assert_eq!(replay.feed_record(&read(0x0C, &[0xFF])), None);
+    assert_eq!(replay.inputs(), 0x04_00_01);
+}
@@ -0,0 +1,116 @@
+#![cfg(all(not(feature = "async"), feature = "retry"))]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{Port, RetryPolicy, Tca6424};
+
+/// Records every wait, so the test keeps a handle after the driver takes the delay.
+#[derive(Default, Clone)]
+struct RecordingDelay {
+    waits_us: Rc<RefCell<Vec<u32>>>,
+}
+
+impl DelayNs for RecordingDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.waits_us.borrow_mut().push(ns / 1_000);
+    }
+}
+
+fn failing_read(address: u8, error: ErrorKind) -> I2cTransaction {
+    I2cTransaction::write_read(address, vec![0x00], vec![0x00]).with_error(error)
+}
+
+/// Reads Port 0 with the bus answering `failures` failed reads and then, if `then_ok`, the
+/// value; returns whether the read succeeded and the waits between the attempts.
+fn read_with(policy: RetryPolicy, failures: &[ErrorKind], then_ok: bool) -> (bool, Vec<u32>) {
+    let address = 0x22;
+    let mut expectations: Vec<_> = failures
+        .iter()
+        .map(|&error| failing_read(address, error))
+        .collect();
+    if then_ok {
+        expectations.push(I2cTransaction::write_read(address, vec![0x00], vec![0x5A]));
+    }
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let delay = RecordingDelay::default();
+    let mut tca = Tca6424::new(&mut i2c_mock, address)
+        .unwrap()
+        .with_delay(delay.clone());
+    tca.set_retry_policy(policy);
+    let result = tca.get_port_input_state(Port::Port0);
+    if let Ok(value) = result {
+        assert_eq!(value, 0x5A);
+    }
+    i2c_mock.done();
+    let waits = delay.waits_us.borrow().clone();
+    (result.is_ok(), waits)
+}
+
+#[test]
+fn test_retry_backoff_doubles_up_to_the_cap() {
+    let policy = RetryPolicy::new(3).with_backoff_us(100, 250);
+    assert_eq!(policy.backoff_us(), (100, 250));
+    assert_eq!(policy.jitter_seed(), None);
+
+    let (ok, waits) = read_with(policy, &[ErrorKind::ArbitrationLoss; 3], true);
+    assert!(ok);
+    assert_eq!(waits, [100, 200, 250]);
+
+    // Retries exhausted: the last error is returned
+    let (ok, waits) = read_with(policy, &[ErrorKind::Bus; 4], false);
+    assert!(!ok);
+    assert_eq!(waits, [100, 200, 250]);
+}
+
+#[test]
+fn test_only_busy_bus_errors_are_retried() {
+    let policy = RetryPolicy::new(3).with_backoff_us(100, 1_000);
+    let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+    let (ok, waits) = read_with(policy, &[nack], false);
+    assert!(!ok);
+    assert!(waits.is_empty());
+}
+
+#[test]
+fn test_retry_jitter_is_seeded() {
+    let policy = RetryPolicy::new(4).with_backoff_us(1_000, 8_000);
+    let (_, first) = read_with(
+        policy.with_jitter(1),
+        &[ErrorKind::ArbitrationLoss; 5],
+        false,
+    );
+    let (_, again) = read_with(
+        policy.with_jitter(1),
+        &[ErrorKind::ArbitrationLoss; 5],
+        false,
+    );
+    let (_, other) = read_with(
+        policy.with_jitter(2),
+        &[ErrorKind::ArbitrationLoss; 5],
+        false,
+    );
+
+    // Each wait lies in the upper half of its backoff step
+    for (wait, step) in first.iter().zip([1_000, 2_000, 4_000, 8_000]) {
+        assert!((step / 2..=step).contains(wait), "{wait} outside {step}");
+    }
+    assert_eq!(first, again);
+    assert_ne!(first, other);
+}
+
+#[test]
+fn test_without_policy_failures_return_at_once() {
+    let address = 0x22;
+    let mut i2c_mock = I2cMock::new(&[failing_read(address, ErrorKind::ArbitrationLoss)]);
+    let mut tca = Tca6424::new(&mut i2c_mock, address).unwrap();
+    assert_eq!(tca.retry_policy(), None);
+    assert!(tca.get_port_input_state(Port::Port0).is_err());
+    tca.set_retry_policy(RetryPolicy::new(1));
+    tca.clear_retry_policy();
+    assert_eq!(tca.retry_policy(), None);
+    i2c_mock.done();
+}
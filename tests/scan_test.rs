@@ -0,0 +1,105 @@
+#![cfg(feature = "diagnostics")]
+
+use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+use embedded_hal_mock::eh1::i2c::Transaction as I2cTransaction;
+use tca6424::{AddressStatus, TCA6424_ADDRESSES};
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_scan_detects_alias_sync() {
+    let expectations = [
+        // 0x22: read, write complement, read back, restore
+        I2cTransaction::write_read(0x22, vec![0x08], vec![0x00]),
+        I2cTransaction::write(0x22, vec![0x08, 0xFF]),
+        I2cTransaction::write_read(0x22, vec![0x08], vec![0xFF]),
+        I2cTransaction::write(0x22, vec![0x08, 0x00]),
+        // 0x23 reaches the same chip: the write shows up at 0x22 too
+        I2cTransaction::write_read(0x23, vec![0x08], vec![0x00]),
+        I2cTransaction::write(0x23, vec![0x08, 0xFF]),
+        I2cTransaction::write_read(0x23, vec![0x08], vec![0xFF]),
+        I2cTransaction::write_read(0x22, vec![0x08], vec![0xFF]),
+        I2cTransaction::write(0x23, vec![0x08, 0x00]),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let report = tca6424::scan(&mut i2c_mock, TCA6424_ADDRESSES);
+
+    assert_eq!(report.status(0x22), Some(AddressStatus::Present));
+    assert_eq!(
+        report.status(0x23),
+        Some(AddressStatus::AliasOf { address: 0x22 })
+    );
+    assert!(!report.all_present());
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_scan_ghost_and_absent_sync() {
+    let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+
+    let expectations = [
+        // 0x22 acknowledges but does not hold the written value
+        I2cTransaction::write_read(0x22, vec![0x08], vec![0x12]),
+        I2cTransaction::write(0x22, vec![0x08, 0xED]),
+        I2cTransaction::write_read(0x22, vec![0x08], vec![0x12]),
+        I2cTransaction::write(0x22, vec![0x08, 0x12]),
+        // 0x23 does not acknowledge
+        I2cTransaction::write_read(0x23, vec![0x08], vec![0x00]).with_error(nack),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let report = tca6424::scan(&mut i2c_mock, TCA6424_ADDRESSES);
+
+    assert_eq!(
+        report.iter().collect::<Vec<_>>(),
+        [
+            (
+                0x22,
+                AddressStatus::Ghost {
+                    wrote: 0xED,
+                    read: Some(0x12)
+                }
+            ),
+            (0x23, AddressStatus::Absent),
+        ]
+    );
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_scan_write_rejected_and_failed_read_back_sync() {
+    let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data);
+
+    let expectations = [
+        // 0x22 answers the read but refuses the write: not absent
+        I2cTransaction::write_read(0x22, vec![0x08], vec![0x5A]),
+        I2cTransaction::write(0x22, vec![0x08, 0xA5]).with_error(nack),
+        // 0x23 takes the write but does not answer the read-back
+        I2cTransaction::write_read(0x23, vec![0x08], vec![0x00]),
+        I2cTransaction::write(0x23, vec![0x08, 0xFF]),
+        I2cTransaction::write_read(0x23, vec![0x08], vec![0x00]).with_error(nack),
+        I2cTransaction::write(0x23, vec![0x08, 0x00]),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let report = tca6424::scan(&mut i2c_mock, TCA6424_ADDRESSES);
+
+    assert_eq!(
+        report.status(0x22),
+        Some(AddressStatus::WriteRejected { read: 0x5A })
+    );
+    assert_eq!(
+        report.status(0x23),
+        Some(AddressStatus::Ghost {
+            wrote: 0xFF,
+            read: None
+        })
+    );
+
+    i2c_mock.done();
+}
@@ -0,0 +1,113 @@
+#![cfg(all(not(feature = "async"), feature = "helpers"))]
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{ManualClock, Pin, PinScheduler, PinState, Tca6424, TimeSource};
+
+const ADDRESS: u8 = 0x22;
+
+#[derive(Default)]
+struct CountingDelay {
+    calls: u32,
+    total_us: u32,
+}
+
+impl DelayNs for CountingDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.calls += 1;
+        self.total_us += ns / 1_000;
+    }
+}
+
+/// Read-modify-write of an Output Port register.
+fn modify(command: u8, read: u8, written: u8) -> [I2cTransaction; 5] {
+    [
+        I2cTransaction::write_read(ADDRESS, vec![command], vec![read]),
+        I2cTransaction::transaction_start(ADDRESS),
+        I2cTransaction::write(ADDRESS, vec![command]),
+        I2cTransaction::write(ADDRESS, vec![written]),
+        I2cTransaction::transaction_end(ADDRESS),
+    ]
+}
+
+#[test]
+fn test_scheduled_changes_are_written_when_due() {
+    let expectations: Vec<_> = [modify(0x04, 0x80, 0x81), modify(0x05, 0xFF, 0xFB)]
+        .into_iter()
+        .flatten()
+        .collect();
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, ADDRESS).unwrap();
+    let mut scheduler = PinScheduler::new();
+
+    // Release a reset line 10 ms from now; the timestamps wrap around in between
+    let now = u32::MAX - 4;
+    scheduler.set_pin_after(Pin::P00, PinState::High, 10, now);
+    scheduler.set_pin_after(Pin::P12, PinState::High, 10, now);
+    // A second change of a pin replaces the first
+    scheduler.set_pin_after(Pin::P12, PinState::Low, 20, now);
+    scheduler.set_pin_after(Pin::P27, PinState::High, 30, now);
+    assert!(scheduler.cancel(Pin::P27));
+    assert!(!scheduler.cancel(Pin::P27));
+    assert_eq!(scheduler.scheduled_mask(), tca6424::pins!(P00, P12));
+    assert_eq!(scheduler.wait_ms(now), Some(10));
+
+    assert_eq!(scheduler.run(&mut tca, now.wrapping_add(9)).unwrap(), 0);
+    assert_eq!(scheduler.run(&mut tca, now.wrapping_add(10)).unwrap(), 0x01);
+    assert_eq!(scheduler.wait_ms(now.wrapping_add(10)), Some(10));
+
+    let clock = ManualClock::new(now.wrapping_add(25));
+    assert_eq!(scheduler.wait_ms(clock.now_ms()), Some(0));
+    assert_eq!(
+        scheduler.run_from(&mut tca, &clock).unwrap(),
+        tca6424::pins!(P12)
+    );
+    assert_eq!(scheduler.scheduled_mask(), 0);
+    assert_eq!(scheduler.wait_ms(clock.now_ms()), None);
+
+    i2c_mock.done();
+}
+
+#[test]
+fn test_due_changes_of_a_port_share_one_write() {
+    let expectations = modify(0x04, 0x0F, 0x0A);
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, ADDRESS).unwrap();
+    let mut scheduler = PinScheduler::new();
+    let clock = ManualClock::new(0);
+
+    scheduler.set_pin_after_from(Pin::P00, PinState::Low, 5, &clock);
+    scheduler.set_pin_after_from(Pin::P02, PinState::Low, 3, &clock);
+    scheduler.set_pin_after_from(Pin::P03, PinState::High, 5, &clock);
+    clock.advance(5);
+    assert_eq!(
+        scheduler.run_from(&mut tca, &clock).unwrap(),
+        tca6424::pins!(P00, P02, P03)
+    );
+
+    i2c_mock.done();
+}
+
+#[test]
+fn test_finish_waits_for_every_change() {
+    let expectations: Vec<_> = [modify(0x04, 0x00, 0x01), modify(0x06, 0x00, 0x80)]
+        .into_iter()
+        .flatten()
+        .collect();
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, ADDRESS).unwrap();
+    let mut scheduler = PinScheduler::new();
+
+    scheduler.set_pin_after(Pin::P00, PinState::High, 10, 1000);
+    scheduler.set_pin_after(Pin::P27, PinState::High, 25, 1000);
+    let mut delay = CountingDelay::default();
+    assert_eq!(scheduler.finish(&mut tca, &mut delay, 1000).unwrap(), 1025);
+    assert_eq!(scheduler.scheduled_mask(), 0);
+    // Waited 10 ms for the first change, then 15 ms for the second
+    assert_eq!((delay.calls, delay.total_us), (2, 25_000));
+    // Nothing scheduled: no wait and no transfer
+    assert_eq!(scheduler.finish(&mut tca, &mut delay, 2000).unwrap(), 2000);
+    assert_eq!(delay.calls, 2);
+
+    i2c_mock.done();
+}
@@ -0,0 +1,178 @@
+#![cfg(all(not(feature = "async"), feature = "operations", feature = "helpers"))]
+
+mod common;
+
+use common::write;
+use embedded_hal::i2c::ErrorKind;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{
+    EventFanout, EventScan, InputWatcher, InterruptThrottle, OpQueue, Operation, Pin, PinScheduler,
+    PinState, Port, SoftPwm, Tca6424, TransactionBudget,
+};
+
+const ADDRESS: u8 = 0x22;
+
+fn read(command: u8, value: u8) -> I2cTransaction {
+    I2cTransaction::write_read(ADDRESS, vec![command], vec![value])
+}
+
+#[test]
+fn test_transaction_budget() {
+    let mut budget = TransactionBudget::new(3);
+    assert!(budget.allows(3));
+    assert!(!budget.allows(4));
+    budget.spend(2);
+    assert_eq!(budget.remaining(), 1);
+    assert!(!budget.is_exhausted());
+    budget.spend(5);
+    assert_eq!(budget.remaining(), 0);
+    assert!(budget.is_exhausted());
+}
+
+#[test]
+fn test_service_spreads_work_over_calls_within_the_budget() {
+    let expectations: Vec<_> = [
+        // First call: the due scheduled change, then the read of the first queued operation
+        vec![read(0x04, 0x00)],
+        write(ADDRESS, 0x04, &[0x01]).to_vec(),
+        vec![read(0x05, 0x00)],
+        // Second call: the rest of the queue
+        write(ADDRESS, 0x05, &[0x01]).to_vec(),
+        vec![read(0x06, 0x00)],
+        write(ADDRESS, 0x06, &[0x01]).to_vec(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, ADDRESS).unwrap();
+    let mut queue: OpQueue<4> = OpQueue::new();
+    queue
+        .enqueue(Operation::set_pin_output(Pin::P10, PinState::High))
+        .unwrap();
+    queue
+        .enqueue(Operation::set_pin_output(Pin::P20, PinState::High))
+        .unwrap();
+    let mut scheduler = PinScheduler::new();
+    scheduler.set_pin_after(Pin::P00, PinState::High, 5, 0);
+
+    // Nothing fits in an empty budget
+    assert_eq!(
+        tca.service(
+            TransactionBudget::new(0),
+            5,
+            &mut (&mut scheduler, &mut queue)
+        )
+        .unwrap(),
+        0
+    );
+    assert_eq!(scheduler.scheduled_mask(), tca6424::pins!(P00));
+
+    assert_eq!(
+        tca.service(
+            TransactionBudget::new(3),
+            5,
+            &mut (&mut scheduler, &mut queue)
+        )
+        .unwrap(),
+        3
+    );
+    assert_eq!(scheduler.scheduled_mask(), 0);
+    assert_eq!(queue.len(), 2);
+    assert_eq!(
+        tca.service(
+            TransactionBudget::new(3),
+            6,
+            &mut (&mut scheduler, &mut queue)
+        )
+        .unwrap(),
+        3
+    );
+    assert!(queue.is_empty());
+    assert_eq!(tca.transaction_count(), 6);
+    assert_eq!(
+        tca.service(
+            TransactionBudget::new(3),
+            7,
+            &mut (&mut scheduler, &mut queue)
+        )
+        .unwrap(),
+        0
+    );
+
+    i2c_mock.done();
+}
+
+#[test]
+fn test_steps_that_do_not_fit_wait_while_smaller_ones_run() {
+    let expectations: Vec<_> = [
+        vec![I2cTransaction::write_read(
+            ADDRESS,
+            vec![0x80],
+            vec![0x00, 0x00, 0x00],
+        )],
+        vec![I2cTransaction::write_read(
+            ADDRESS,
+            vec![0x80],
+            vec![0x01, 0x00, 0x00],
+        )],
+        // The PWM write once the budget allows its read-modify-write
+        vec![read(0x04, 0x00)],
+        write(ADDRESS, 0x04, &[0x01]).to_vec(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, ADDRESS).unwrap();
+    let mut pwm = SoftPwm::new(10);
+    pwm.set_duty(tca6424::pins!(P00), u8::MAX);
+    let mut watcher = InputWatcher::new().with_port(Port::Port0);
+    let mut throttle = InterruptThrottle::new(0);
+    let mut fanout: EventFanout<4, 1> = EventFanout::new();
+    let subscriber = fanout.subscribe().unwrap();
+
+    for now in [0, 1] {
+        throttle.notify();
+        let scan = EventScan::new(&mut watcher, &mut throttle, &mut fanout);
+        let used = tca
+            .service(TransactionBudget::new(1), now, &mut (&mut pwm, scan))
+            .unwrap();
+        assert_eq!(used, 1);
+    }
+    let event = fanout.next(subscriber).unwrap();
+    assert_eq!((event.pin, event.state), (Pin::P00, PinState::High));
+    assert_eq!(fanout.next(subscriber), None);
+
+    // No interrupt pending: the whole budget goes to the PWM
+    let scan = EventScan::new(&mut watcher, &mut throttle, &mut fanout);
+    let used = tca
+        .service(TransactionBudget::new(2), 2, &mut (&mut pwm, scan))
+        .unwrap();
+    assert_eq!(used, 2);
+
+    i2c_mock.done();
+}
+
+#[test]
+fn test_failed_scan_stays_pending() {
+    let expectations = [
+        I2cTransaction::write_read(ADDRESS, vec![0x80], vec![0x00, 0x00, 0x00])
+            .with_error(ErrorKind::Other),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, ADDRESS).unwrap();
+    let mut watcher = InputWatcher::new().with_port(Port::Port0);
+    let mut throttle = InterruptThrottle::new(0);
+    let mut fanout: EventFanout<4, 1> = EventFanout::new();
+
+    throttle.notify();
+    let mut scan = EventScan::new(&mut watcher, &mut throttle, &mut fanout);
+    assert!(
+        tca.service(TransactionBudget::new(4), 0, &mut scan)
+            .is_err()
+    );
+    assert!(throttle.is_pending());
+
+    i2c_mock.done();
+}
@@ -0,0 +1,35 @@
+#![cfg(all(feature = "shared-bus", not(feature = "async")))]
+
+use embedded_hal_mock::eh0::i2c::{Mock as Eh02Mock, Transaction as Eh02Transaction};
+use tca6424::shared_bus::{Eh02Error, Eh02I2c};
+use tca6424::{Pin, PinState, Port};
+
+#[test]
+fn test_driver_on_eh02_bus() {
+    let address = 0x22;
+    let expectations = [
+        // set_pin_output: read-modify-write, command and payload merged into one write
+        Eh02Transaction::write_read(address, vec![0x04], vec![0x00]),
+        Eh02Transaction::write(address, vec![0x04, 0x01]),
+        Eh02Transaction::write(address, vec![0x0D, 0x0F]),
+        Eh02Transaction::write_read(address, vec![0x02], vec![0xA5]),
+    ];
+    let mut tca =
+        tca6424::Tca6424::new(Eh02I2c::new(Eh02Mock::new(&expectations)), address).unwrap();
+
+    tca.set_pin_output(Pin::P00, PinState::High).unwrap();
+    tca.set_port_direction(Port::Port1, 0x0F).unwrap();
+    assert_eq!(tca.get_port_input_state(Port::Port2).unwrap(), 0xA5);
+
+    tca.into_inner().into_inner().done();
+}
+
+#[test]
+fn test_eh02_bus_rejects_long_writes() {
+    use embedded_hal::i2c::I2c;
+
+    let mut bus = Eh02I2c::new(Eh02Mock::new(&[]));
+    let payload = [0u8; 40];
+    assert_eq!(bus.write(0x22, &payload), Err(Eh02Error::WriteTooLong));
+    bus.into_inner().done();
+}
@@ -0,0 +1,81 @@
+#![cfg(feature = "shared")]
+
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{Pin, PinState, SharedTca6424};
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_shared_driver_sync() {
+    use tca6424::errors::Error;
+
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x04], vec![0x00]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0x01]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x00], vec![0x01]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let shared: SharedTca6424<NoopRawMutex, _> =
+        SharedTca6424::new(tca6424::Tca6424::new(&mut i2c_mock, address).unwrap());
+
+    shared
+        .lock(|tca| tca.set_pin_output(Pin::P00, PinState::High))
+        .unwrap();
+    let level = shared
+        .lock(|tca| tca.get_pin_input_state(Pin::P00))
+        .unwrap();
+    assert_eq!(level, PinState::High);
+
+    // Locking again from inside the closure reports Busy instead of panicking
+    let nested = shared
+        .lock(|_| Ok::<_, Error<()>>(shared.lock(|tca| tca.get_pin_input_state(Pin::P00))))
+        .unwrap();
+    assert_eq!(nested, Err(Error::Busy));
+
+    let _ = shared.into_inner();
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_split_roles_sync() {
+    use tca6424::Port;
+
+    let address = 0x22;
+    let expectations = [
+        // Output side: drive P10 high
+        I2cTransaction::write_read(address, vec![0x05], vec![0x00]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x05]),
+        I2cTransaction::write(address, vec![0x01]),
+        I2cTransaction::transaction_end(address),
+        // Input side: read Port 2
+        I2cTransaction::write_read(address, vec![0x02], vec![0x80]),
+        // Output side: low nibble of Port 0 becomes outputs
+        I2cTransaction::write_read(address, vec![0x0C], vec![0xFF]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x0C]),
+        I2cTransaction::write(address, vec![0xF0]),
+        I2cTransaction::transaction_end(address),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let shared: SharedTca6424<NoopRawMutex, _> =
+        SharedTca6424::new(tca6424::Tca6424::new(&mut i2c_mock, address).unwrap());
+
+    {
+        let (outputs, inputs) = shared.split_roles();
+        outputs.set_pin_output(Pin::P10, PinState::High).unwrap();
+        let port2 = inputs.read_port_inputs(Port::Port2).unwrap();
+        assert_eq!(port2.pin(Pin::P27), Some(PinState::High));
+        outputs
+            .write_directions_masked(Port::Port0, 0x0F, 0x00)
+            .unwrap();
+    }
+
+    let _ = shared.into_inner();
+    i2c_mock.done();
+}
@@ -0,0 +1,102 @@
+#![cfg(feature = "helpers")]
+
+mod common;
+
+use common::write;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::errors::ConfigError;
+use tca6424::{BitOrder, Pin, ShiftRegister};
+
+#[test]
+fn test_shift_register_claims_pins() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+
+    assert!(matches!(
+        ShiftRegister::new(&mut tca, Pin::P00, Pin::P00, Pin::P01),
+        Err(ConfigError::InvalidRegisterOrPin)
+    ));
+    let shift = ShiftRegister::new(&mut tca, Pin::P00, Pin::P01, Pin::P02).unwrap();
+    assert_eq!(tca.claimed_mask(), 0b111);
+    assert_eq!(shift.pins(), (Pin::P00, Pin::P01, Pin::P02));
+    assert_eq!(shift.bit_order(), BitOrder::MsbFirst);
+    assert!(matches!(
+        ShiftRegister::new(&mut tca, Pin::P02, Pin::P03, Pin::P04),
+        Err(ConfigError::PinAlreadyClaimed(Pin::P02))
+    ));
+    shift.release(&mut tca);
+    assert_eq!(tca.claimed_mask(), 0);
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_shift_register_write_bits_sync() {
+    let address = 0x22;
+    let mut expectations = Vec::new();
+    // init: data, clock and latch are driven low, then become outputs
+    expectations.push(I2cTransaction::write_read(address, vec![0x04], vec![0xFF]));
+    expectations.extend(write(address, 0x04, &[0xF8]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x0C], vec![0xFF]));
+    expectations.extend(write(address, 0x0C, &[0xF8]));
+    // 1: data high with clock low, then clock high
+    expectations.extend(write(address, 0x04, &[0xF9]));
+    expectations.extend(write(address, 0x04, &[0xFB]));
+    // 0
+    expectations.extend(write(address, 0x04, &[0xF8]));
+    expectations.extend(write(address, 0x04, &[0xFA]));
+    // Latch pulse
+    expectations.extend(write(address, 0x04, &[0xFC]));
+    expectations.extend(write(address, 0x04, &[0xF8]));
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    let mut shift = ShiftRegister::new(&mut tca, Pin::P00, Pin::P01, Pin::P02).unwrap();
+
+    shift.init(&mut tca).unwrap();
+    shift.write_bits(&mut tca, &[true, false]).unwrap();
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_shift_register_write_byte_lsb_first_sync() {
+    let address = 0x22;
+    let mut expectations = Vec::new();
+    // init: clock P00 and latch P01 on Port 0, data P10 on Port 1
+    expectations.push(I2cTransaction::write_read(address, vec![0x04], vec![0xFF]));
+    expectations.extend(write(address, 0x04, &[0xFC]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x05], vec![0xFF]));
+    expectations.extend(write(address, 0x05, &[0xFE]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x0C], vec![0xFF]));
+    expectations.extend(write(address, 0x0C, &[0xFC]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x0D], vec![0xFF]));
+    expectations.extend(write(address, 0x0D, &[0xFE]));
+    // Bit 0 first: the clock is already low, only the data port changes
+    expectations.extend(write(address, 0x05, &[0xFF]));
+    expectations.extend(write(address, 0x04, &[0xFD]));
+    // Bit 1 drops the clock and the data line
+    expectations.extend(write(address, 0x04, &[0xFC]));
+    expectations.extend(write(address, 0x05, &[0xFE]));
+    expectations.extend(write(address, 0x04, &[0xFD]));
+    // Bits 2-7 leave the data line low
+    for _ in 2..8 {
+        expectations.extend(write(address, 0x04, &[0xFC]));
+        expectations.extend(write(address, 0x04, &[0xFD]));
+    }
+    expectations.extend(write(address, 0x04, &[0xFE]));
+    expectations.extend(write(address, 0x04, &[0xFC]));
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    let mut shift = ShiftRegister::new(&mut tca, Pin::P10, Pin::P00, Pin::P01)
+        .unwrap()
+        .with_bit_order(BitOrder::LsbFirst);
+
+    shift.init(&mut tca).unwrap();
+    shift.write_byte(&mut tca, 0x01).unwrap();
+
+    i2c_mock.done();
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "helpers")]
+
+mod common;
+
+use common::write;
+use embedded_hal::delay::DelayNs;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{Pin, SingleWire};
+
+#[derive(Default)]
+struct CountingDelay {
+    calls: u32,
+    total_us: u32,
+}
+
+impl DelayNs for CountingDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.calls += 1;
+        self.total_us += ns / 1_000;
+    }
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_single_wire_reset_presence_sync() {
+    let address = 0x22;
+    let mut expectations = Vec::new();
+    // init: the output latch is cleared, then the line is released
+    expectations.push(I2cTransaction::write_read(address, vec![0x05], vec![0xFF]));
+    expectations.extend(write(address, 0x05, &[0xFB]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x0D], vec![0x00]));
+    expectations.extend(write(address, 0x0D, &[0x04]));
+    // Reset pulse: output, then input again, with the cached Configuration register
+    expectations.extend(write(address, 0x0D, &[0x00]));
+    expectations.extend(write(address, 0x0D, &[0x04]));
+    // The companion chip holds the line low
+    expectations.push(I2cTransaction::write_read(address, vec![0x01], vec![0x00]));
+    // Second reset: nobody answers
+    expectations.extend(write(address, 0x0D, &[0x00]));
+    expectations.extend(write(address, 0x0D, &[0x04]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x01], vec![0x04]));
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address)
+        .unwrap()
+        .with_delay(CountingDelay::default());
+    let mut line = SingleWire::new(&mut tca, Pin::P12).unwrap();
+    assert!(tca.is_claimed(Pin::P12));
+
+    line.init(&mut tca).unwrap();
+    assert!(line.reset_presence(&mut tca, 480, 70).unwrap());
+    assert_eq!(tca.delay().calls, 2);
+    assert_eq!(tca.delay().total_us, 550);
+    assert!(!line.reset_presence(&mut tca, 480, 70).unwrap());
+
+    line.release(&mut tca);
+    assert!(!tca.is_claimed(Pin::P12));
+    i2c_mock.done();
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use common::write;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{Pin, PinDirection, PinState, SleepAction, SleepProfile};
+
+#[test]
+fn test_sleep_profile() {
+    let mut profile = SleepProfile::new().with_pin(Pin::P00, SleepAction::DriveHigh);
+    profile.set_pin(Pin::P21, SleepAction::Float);
+    assert_eq!(profile.action(Pin::P00), SleepAction::DriveHigh);
+    assert_eq!(profile.action(Pin::P21), SleepAction::Float);
+    assert_eq!(profile.action(Pin::P10), SleepAction::Retain);
+    assert_eq!(SleepProfile::default(), SleepProfile::new());
+
+    let profile = SleepProfile::new()
+        .drive_low(0x00_FFFF)
+        .drive_high(0x00_0F00)
+        .retain(0x00_0001);
+    assert_eq!(profile.pins(SleepAction::DriveLow), 0x00_F0FE);
+    assert_eq!(profile.pins(SleepAction::DriveHigh), 0x00_0F00);
+    assert_eq!(profile.pins(SleepAction::Retain), 0xFF_0001);
+    assert_eq!(profile.pins(SleepAction::Float), 0);
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_sleep_merges_adjacent_ports_sync() {
+    let address = 0x22;
+    let mut expectations = vec![
+        I2cTransaction::write_read(address, vec![0x04], vec![0xFF]),
+        I2cTransaction::write_read(address, vec![0x0C], vec![0xFF]),
+        I2cTransaction::write_read(address, vec![0x05], vec![0xFF]),
+        I2cTransaction::write_read(address, vec![0x0D], vec![0xFF]),
+    ];
+    // Both ports change: one auto-increment write per family
+    expectations.extend([
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x84]),
+        I2cTransaction::write(address, vec![0x00, 0x00]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x8C]),
+        I2cTransaction::write(address, vec![0x00, 0x00]),
+        I2cTransaction::transaction_end(address),
+    ]);
+    // Wake restores both ports the same way
+    expectations.extend([
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x84]),
+        I2cTransaction::write(address, vec![0xFF, 0xFF]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x8C]),
+        I2cTransaction::write(address, vec![0xFF, 0xFF]),
+        I2cTransaction::transaction_end(address),
+    ]);
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    tca.prepare_for_sleep(&SleepProfile::new().drive_low(0x00_FFFF))
+        .unwrap();
+    assert!(tca.restore_after_wake().unwrap());
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_prepare_for_sleep_and_restore_sync() {
+    let address = 0x22;
+    let mut expectations = Vec::new();
+    // Configuration: P00 and P01 outputs, P00 high
+    expectations.push(I2cTransaction::write_read(address, vec![0x04], vec![0x00]));
+    expectations.extend(write(address, 0x04, &[0x01]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x0C], vec![0xFF]));
+    expectations.extend(write(address, 0x0C, &[0xFE]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x0C], vec![0xFE]));
+    expectations.extend(write(address, 0x0C, &[0xFC]));
+    // Sleep: Port0 from the cache, Port2 read from the device; P20 is already low
+    expectations.push(I2cTransaction::write_read(address, vec![0x06], vec![0x00]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x0E], vec![0xFF]));
+    expectations.extend(write(address, 0x04, &[0x80]));
+    expectations.extend(write(address, 0x0C, &[0x7E]));
+    expectations.extend(write(address, 0x0E, &[0xFE]));
+    // Wake
+    expectations.extend(write(address, 0x04, &[0x01]));
+    expectations.extend(write(address, 0x06, &[0x00]));
+    expectations.extend(write(address, 0x0C, &[0xFC]));
+    expectations.extend(write(address, 0x0E, &[0xFF]));
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    tca.set_pin_output(Pin::P00, PinState::High).unwrap();
+    tca.set_pin_direction(Pin::P00, PinDirection::Output)
+        .unwrap();
+    tca.set_pin_direction(Pin::P01, PinDirection::Output)
+        .unwrap();
+
+    let profile = SleepProfile::new()
+        .with_pin(Pin::P00, SleepAction::DriveLow)
+        .with_pin(Pin::P01, SleepAction::Float)
+        .with_pin(Pin::P07, SleepAction::DriveHigh)
+        .with_pin(Pin::P20, SleepAction::DriveLow);
+    assert!(!tca.is_prepared_for_sleep());
+    tca.prepare_for_sleep(&profile).unwrap();
+    assert!(tca.is_prepared_for_sleep());
+
+    assert!(tca.restore_after_wake().unwrap());
+    assert!(!tca.is_prepared_for_sleep());
+    assert!(!tca.restore_after_wake().unwrap());
+
+    i2c_mock.done();
+}
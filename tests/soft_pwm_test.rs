@@ -0,0 +1,72 @@
+#![cfg(feature = "helpers")]
+
+mod common;
+
+use common::write;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{Pin, SoftPwm, pins};
+
+#[test]
+fn test_soft_pwm_process_ramps() {
+    let mut pwm = SoftPwm::new(10);
+    pwm.set_duty(pins!(P00), 255);
+    pwm.fade_in(pins!(P01), 100);
+    assert_eq!(pwm.pins(), pins!(P00, P01));
+    assert!(pwm.is_ramping(pins!(P01)));
+    assert!(!pwm.is_ramping(pins!(P00)));
+
+    // The first tick reports every level and starts the fade
+    assert_eq!(pwm.process(0), Some(pins!(P00)));
+    assert_eq!(pwm.process(5), None);
+    // Halfway through the fade P01 is high for the first 4 ms of each period
+    assert_eq!(pwm.process(50), Some(pins!(P00, P01)));
+    assert_eq!(pwm.duty(Pin::P01), 127);
+    assert_eq!(pwm.process(55), Some(pins!(P00)));
+
+    // Fades start from the current duty cycle
+    pwm.fade_out(pins!(P00), 100);
+    assert_eq!(pwm.duty(Pin::P00), 255);
+    assert_eq!(pwm.process(60), Some(pins!(P00, P01)));
+    assert_eq!(pwm.process(160), Some(pins!(P01)));
+    assert_eq!(pwm.duty(Pin::P00), 0);
+    assert_eq!(pwm.duty(Pin::P01), 255);
+    assert!(!pwm.is_ramping(pins!(P00, P01)));
+    assert_eq!(pwm.process(165), None);
+
+    pwm.release(pins!(P01));
+    assert_eq!(pwm.pins(), pins!(P00));
+    // P00 is still low: nothing to write
+    assert_eq!(pwm.process(170), None);
+    assert_eq!(SoftPwm::new(0).period_ms(), 2);
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_soft_pwm_tick_sync() {
+    let address = 0x22;
+    let mut expectations = Vec::new();
+    // init: the pins are driven low, then become outputs
+    expectations.push(I2cTransaction::write_read(address, vec![0x04], vec![0xFF]));
+    expectations.extend(write(address, 0x04, &[0xFC]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x0C], vec![0xFF]));
+    expectations.extend(write(address, 0x0C, &[0xFC]));
+    // Level changes merge into the cached Output register
+    expectations.extend(write(address, 0x04, &[0xFF]));
+    expectations.extend(write(address, 0x04, &[0xFD]));
+    expectations.extend(write(address, 0x04, &[0xFF]));
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    let mut pwm = SoftPwm::new(10);
+    pwm.set_duty(pins!(P00), 255);
+    pwm.set_duty(pins!(P01), 128);
+
+    pwm.init(&mut tca).unwrap();
+    assert!(pwm.tick(&mut tca, 0).unwrap());
+    assert!(!pwm.tick(&mut tca, 3).unwrap());
+    // P01 is high for the first 5 ms of each period
+    assert!(pwm.tick(&mut tca, 5).unwrap());
+    assert!(pwm.tick(&mut tca, 10).unwrap());
+
+    i2c_mock.done();
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::write;
+use embedded_hal::delay::DelayNs;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::Pin;
+
+#[derive(Default)]
+struct CountingDelay {
+    calls: u32,
+    total_us: u32,
+}
+
+impl DelayNs for CountingDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.calls += 1;
+        self.total_us += ns / 1_000;
+    }
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_group_staggered_sync() {
+    let address = 0x22;
+    let group = [Pin::P00, Pin::P01, Pin::P10, Pin::P02];
+
+    let mut expectations = vec![I2cTransaction::write_read(
+        address,
+        vec![0x84],
+        vec![0x02, 0x00, 0x00],
+    )];
+    // P01 is already on and is skipped
+    expectations.extend(write(address, 0x04, &[0x03]));
+    expectations.extend(write(address, 0x05, &[0x01]));
+    expectations.extend(write(address, 0x04, &[0x07]));
+    // Off in reverse order
+    expectations.push(I2cTransaction::write_read(
+        address,
+        vec![0x84],
+        vec![0x07, 0x01, 0x00],
+    ));
+    expectations.extend(write(address, 0x04, &[0x03]));
+    expectations.extend(write(address, 0x05, &[0x00]));
+    expectations.extend(write(address, 0x04, &[0x01]));
+    expectations.extend(write(address, 0x04, &[0x00]));
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    let mut delay = CountingDelay::default();
+
+    tca.enable_group_staggered(&group, 500, &mut delay).unwrap();
+    assert_eq!(delay.calls, 2);
+    assert_eq!(delay.total_us, 1_000);
+
+    tca.disable_group_staggered(&group, 200, &mut delay)
+        .unwrap();
+    assert_eq!(delay.calls, 5);
+    assert_eq!(delay.total_us, 1_600);
+
+    i2c_mock.done();
+}
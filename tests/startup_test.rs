@@ -0,0 +1,59 @@
+#![cfg(feature = "diagnostics")]
+
+use tca6424::RegisterSnapshot;
+
+#[test]
+fn test_power_on_default_snapshot() {
+    let mut snapshot = RegisterSnapshot {
+        inputs: [0x12, 0x34, 0x56],
+        outputs: [0xFF; 3],
+        polarity_inversion: [0x00; 3],
+        configuration: [0xFF; 3],
+        interrupt_mask: [0xFF; 3],
+    };
+    // Input levels do not matter
+    assert!(snapshot.is_power_on_default());
+    snapshot.configuration[1] = 0xF0;
+    assert!(!snapshot.is_power_on_default());
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_new_checked_with_capture_sync() {
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+    use tca6424::errors::Error;
+    use tca6424::{Pin, PinState, Tca6424};
+
+    let address = 0x22;
+    let expectations = [
+        // Warm reboot: the previous firmware left P00 driven high as an output
+        I2cTransaction::write_read(address, vec![0x80], vec![0x01, 0x00, 0x00]),
+        I2cTransaction::write_read(address, vec![0x84], vec![0x01, 0x00, 0x00]),
+        I2cTransaction::write_read(address, vec![0x88], vec![0x00, 0x00, 0x00]),
+        I2cTransaction::write_read(address, vec![0x8C], vec![0xFE, 0xFF, 0xFF]),
+        I2cTransaction::write_read(address, vec![0x90], vec![0xFF, 0xFF, 0xFF]),
+        // Initialisation
+        I2cTransaction::write_read(address, vec![0x04], vec![0x01]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0x00]),
+        I2cTransaction::transaction_end(address),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+
+    assert!(matches!(
+        Tca6424::new_checked_with_capture(&mut i2c_mock, 0x00),
+        Err(Error::ReservedAddress(0x00))
+    ));
+    let mut tca = Tca6424::new_checked_with_capture(&mut i2c_mock, address).unwrap();
+    tca.set_pin_output(Pin::P00, PinState::Low).unwrap();
+
+    let startup = *tca.startup_state().unwrap();
+    assert!(!startup.is_power_on_default());
+    assert_eq!(startup.outputs, [0x01, 0x00, 0x00]);
+    assert_eq!(startup.configuration, [0xFE, 0xFF, 0xFF]);
+    assert_eq!(tca.take_startup_state(), Some(startup));
+    assert!(tca.startup_state().is_none());
+
+    i2c_mock.done();
+}
@@ -0,0 +1,91 @@
+#![cfg(all(feature = "stats", not(feature = "async")))]
+
+mod common;
+
+use common::write;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{Pin, PinDirection, PinStats, Port, Tca6424};
+
+#[test]
+fn test_output_toggles_counted_from_writes() {
+    let address = 0x22;
+    let mut expectations = Vec::new();
+    // The first write only sets the baseline
+    expectations.extend(write(address, 0x04, &[0x00]));
+    expectations.extend(write(address, 0x04, &[0x03]));
+    expectations.extend(write(address, 0x04, &[0x01]));
+    // Auto-increment writes wrap within the Output family: ports 2, 0, 1
+    expectations.extend(write(address, 0x86, &[0x80, 0x02, 0x00]));
+    expectations.extend(write(address, 0x86, &[0x00, 0x03, 0x01]));
+    // Other families are not counted
+    expectations.push(I2cTransaction::write_read(address, vec![0x0C], vec![0xFF]));
+    expectations.extend(write(address, 0x0C, &[0xFE]));
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, address).unwrap();
+    tca.set_port_output(Port::Port0, 0x00).unwrap();
+    tca.set_port_output(Port::Port0, 0x03).unwrap();
+    tca.set_port_output(Port::Port0, 0x01).unwrap();
+    tca.set_ports_output_ai(Port::Port2, &[0x80, 0x02, 0x00])
+        .unwrap();
+    tca.set_ports_output_ai(Port::Port2, &[0x00, 0x03, 0x01])
+        .unwrap();
+    tca.set_pin_direction(Pin::P00, PinDirection::Output)
+        .unwrap();
+
+    assert_eq!(
+        tca.pin_stats(Pin::P00),
+        PinStats {
+            output_toggles: 3,
+            input_changes: 0
+        }
+    );
+    assert_eq!(tca.pin_stats(Pin::P01).output_toggles, 3);
+    assert_eq!(tca.pin_stats(Pin::P02).output_toggles, 0);
+    assert_eq!(tca.pin_stats(Pin::P10).output_toggles, 1);
+    assert_eq!(tca.pin_stats(Pin::P27).output_toggles, 1);
+
+    tca.reset_pin_stats();
+    assert_eq!(tca.pin_stats(Pin::P00), PinStats::default());
+
+    i2c_mock.done();
+}
+
+#[test]
+fn test_input_changes_counted_between_reads() {
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x80], vec![0x00, 0xFF, 0x00]),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x01, 0xFF, 0x00]),
+        I2cTransaction::write_read(address, vec![0x00], vec![0x00]),
+        I2cTransaction::write_read(address, vec![0x01], vec![0x7F]),
+        // An Output Port read sets the output baseline
+        I2cTransaction::write_read(address, vec![0x05], vec![0x0F]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x05]),
+        I2cTransaction::write(address, vec![0x0E]),
+        I2cTransaction::transaction_end(address),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, address).unwrap();
+    tca.read_ports().unwrap();
+    tca.read_ports().unwrap();
+    tca.get_port_input_state(Port::Port0).unwrap();
+    tca.get_port_input_state(Port::Port1).unwrap();
+    tca.get_port_output_state(Port::Port1).unwrap();
+    tca.set_port_output(Port::Port1, 0x0E).unwrap();
+
+    assert_eq!(tca.pin_stats(Pin::P00).input_changes, 2);
+    assert_eq!(
+        tca.pin_stats(Pin::P17),
+        PinStats {
+            output_toggles: 0,
+            input_changes: 1
+        }
+    );
+    assert_eq!(tca.pin_stats(Pin::P10).output_toggles, 1);
+    assert_eq!(tca.pin_stats(Pin::P20), PinStats::default());
+
+    i2c_mock.done();
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "helpers")]
+
+mod common;
+
+use common::write;
+use embedded_hal::delay::DelayNs;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::errors::ConfigError;
+use tca6424::{Pin, StepRamp, Stepper};
+
+#[derive(Default)]
+struct CountingDelay {
+    calls: u32,
+    total_us: u32,
+}
+
+impl DelayNs for CountingDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.calls += 1;
+        self.total_us += ns / 1_000;
+    }
+}
+
+#[test]
+fn test_step_ramp_profile() {
+    let ramp = StepRamp::new(100, 400, 2_000);
+    let rates: Vec<u32> = (0..10).map(|index| ramp.rate(index, 10)).collect();
+    // Short move: a symmetric triangle that never reaches the cruise rate
+    assert_eq!(rates, [100, 118, 134, 148, 161, 161, 148, 134, 118, 100]);
+    assert_eq!(ramp.interval_us(0, 10), 10_000);
+
+    // Long move: accelerates, cruises and decelerates
+    assert_eq!(ramp.rate(37, 200), 397);
+    assert_eq!(ramp.rate(38, 200), 400);
+    assert_eq!(ramp.rate(100, 200), 400);
+    assert_eq!(ramp.rate(199, 200), 100);
+
+    assert_eq!(StepRamp::constant(250).rate(5, 10), 250);
+    assert_eq!(StepRamp::new(0, 0, 10).start_rate(), 1);
+    assert_eq!(StepRamp::new(50, 10, 10).max_rate(), 50);
+}
+
+#[test]
+fn test_stepper_claims_pins() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, 0x22).unwrap();
+    let ramp = StepRamp::constant(100);
+
+    assert!(matches!(
+        Stepper::new(&mut tca, Pin::P00, Pin::P00, ramp),
+        Err(ConfigError::InvalidRegisterOrPin)
+    ));
+    let stepper = Stepper::new(&mut tca, Pin::P00, Pin::P01, ramp).unwrap();
+    assert!(matches!(
+        Stepper::new(&mut tca, Pin::P02, Pin::P01, ramp),
+        Err(ConfigError::PinAlreadyClaimed(Pin::P01))
+    ));
+    assert_eq!(tca.claimed_mask(), 0b11);
+    stepper.release(&mut tca);
+    assert_eq!(tca.claimed_mask(), 0);
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_stepper_move_sync() {
+    let address = 0x22;
+    let mut expectations = Vec::new();
+    // init: STEP (P00) and DIR (P01) low, then outputs
+    expectations.push(I2cTransaction::write_read(address, vec![0x04], vec![0x00]));
+    expectations.extend(write(address, 0x04, &[0x00]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x0C], vec![0xFF]));
+    expectations.extend(write(address, 0x0C, &[0xFC]));
+    // Three steps backwards: DIR is already low
+    for _ in 0..3 {
+        expectations.extend(write(address, 0x04, &[0x01]));
+        expectations.extend(write(address, 0x04, &[0x00]));
+    }
+    // Two steps forwards
+    expectations.extend(write(address, 0x04, &[0x02]));
+    for _ in 0..2 {
+        expectations.extend(write(address, 0x04, &[0x03]));
+        expectations.extend(write(address, 0x04, &[0x02]));
+    }
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address)
+        .unwrap()
+        .with_delay(CountingDelay::default());
+    let mut stepper =
+        Stepper::new(&mut tca, Pin::P00, Pin::P01, StepRamp::constant(1_000)).unwrap();
+    stepper.init(&mut tca).unwrap();
+
+    stepper.move_steps(&mut tca, -3).unwrap();
+    assert_eq!(stepper.position(), -3);
+    assert_eq!(tca.delay().calls, 2);
+    assert_eq!(tca.delay().total_us, 2_000);
+
+    stepper.set_ramp(StepRamp::new(100, 400, 2_000));
+    stepper.move_steps(&mut tca, 2).unwrap();
+    assert_eq!(stepper.position(), -1);
+    assert_eq!(tca.delay().total_us, 12_000);
+
+    i2c_mock.done();
+}
@@ -0,0 +1,104 @@
+#![cfg(feature = "helpers")]
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{BitOrder, BoardConfig, Pin, StrapDecoder, ValueEncoding};
+
+static REVISION_PINS: [Pin; 3] = [Pin::P25, Pin::P26, Pin::P27];
+
+#[derive(Default)]
+struct RecordingDelay {
+    total_us: u32,
+}
+
+impl DelayNs for RecordingDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.total_us += ns / 1_000;
+    }
+}
+
+#[test]
+fn test_strap_decode_bit_order() {
+    let msb = StrapDecoder::new(&REVISION_PINS, BitOrder::MsbFirst);
+    let lsb = StrapDecoder::new(&REVISION_PINS, BitOrder::LsbFirst);
+    // P25 high, P26 high, P27 low
+    let inputs = 0b0110_0000 << 16;
+    assert_eq!(msb.decode(inputs), 0b110);
+    assert_eq!(lsb.decode(inputs), 0b011);
+    assert_eq!(msb.mask(), 0xE0_00_00);
+}
+
+#[test]
+fn test_strap_decode_encodings() {
+    static WHEEL_PINS: [Pin; 8] = [
+        Pin::P10,
+        Pin::P11,
+        Pin::P12,
+        Pin::P13,
+        Pin::P14,
+        Pin::P15,
+        Pin::P16,
+        Pin::P17,
+    ];
+    let bcd = StrapDecoder::new(&WHEEL_PINS, BitOrder::LsbFirst).with_encoding(ValueEncoding::Bcd);
+    assert_eq!(bcd.encoding(), ValueEncoding::Bcd);
+    // Two thumbwheel digits: 4 (high) and 7 (low)
+    assert_eq!(bcd.decode(0x47 << 8), 47);
+
+    let gray =
+        StrapDecoder::new(&REVISION_PINS, BitOrder::MsbFirst).with_encoding(ValueEncoding::Gray);
+    // Gray 0b110 is position 4
+    assert_eq!(gray.decode(0b0110_0000 << 16), 4);
+    for position in 0..16u32 {
+        assert_eq!(
+            ValueEncoding::Gray.decode(position ^ (position >> 1)),
+            position
+        );
+    }
+    assert_eq!(ValueEncoding::Binary.decode(0b101), 0b101);
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_strap_read_restores_directions_sync() {
+    let address = 0x22;
+    let expectations = [
+        // P27 is an output; the straps are made inputs for the sample
+        I2cTransaction::write_read(address, vec![0x8C], vec![0xFF, 0xFF, 0x7F]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x8C]),
+        I2cTransaction::write(address, vec![0xFF, 0xFF, 0xFF]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x00, 0x00, 0xA0]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x8C]),
+        I2cTransaction::write(address, vec![0xFF, 0xFF, 0x7F]),
+        I2cTransaction::transaction_end(address),
+        // Straps already inputs: no direction writes
+        I2cTransaction::write_read(address, vec![0x8C], vec![0xFF, 0xFF, 0xFF]),
+        I2cTransaction::write_read(address, vec![0x80], vec![0x00, 0x00, 0x40]),
+        // Runtime roles: P27 output, driven low
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x84]),
+        I2cTransaction::write(address, vec![0x00, 0x00, 0x00]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x8C]),
+        I2cTransaction::write(address, vec![0xFF, 0xFF, 0x7F]),
+        I2cTransaction::transaction_end(address),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address)
+        .unwrap()
+        .with_delay(RecordingDelay::default());
+    let straps = StrapDecoder::new(&REVISION_PINS, BitOrder::MsbFirst).with_settle_us(50);
+
+    assert_eq!(straps.read(&mut tca).unwrap(), 0b101);
+    assert_eq!(tca.delay().total_us, 50);
+
+    let runtime = BoardConfig::new().output(Pin::P27, false, false);
+    assert_eq!(straps.read_and_apply(&mut tca, &runtime).unwrap(), 0b010);
+    assert!(tca.is_claimed(Pin::P27));
+
+    i2c_mock.done();
+}
@@ -0,0 +1,101 @@
+#![cfg(all(feature = "auto-increment", not(feature = "async")))]
+
+mod common;
+
+use common::write;
+use embedded_hal::i2c::ErrorKind;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{Port, Tca6424, TransferStrategy};
+
+#[test]
+fn test_per_register_only() {
+    let address = 0x22;
+    let mut expectations = Vec::new();
+    expectations.extend(write(address, 0x04, &[0x01]));
+    expectations.extend(write(address, 0x05, &[0x02]));
+    expectations.extend(write(address, 0x06, &[0x03]));
+    // The address wraps within the family, like an auto-increment read
+    expectations.push(I2cTransaction::write_read(address, vec![0x02], vec![0xAA]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x00], vec![0xBB]));
+    // A single register is transferred as before
+    expectations.extend(write(address, 0x8C, &[0x0F]));
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, address).unwrap();
+    assert_eq!(
+        tca.transfer_strategy(),
+        TransferStrategy::PreferAutoIncrement
+    );
+    tca.set_transfer_strategy(TransferStrategy::PerRegisterOnly);
+    assert!(tca.splits_transfers());
+
+    tca.set_ports_output_ai(Port::Port0, &[0x01, 0x02, 0x03])
+        .unwrap();
+    let mut inputs = [0u8; 2];
+    tca.get_ports_input_state_ai(Port::Port2, &mut inputs)
+        .unwrap();
+    assert_eq!(inputs, [0xAA, 0xBB]);
+    tca.set_ports_direction_ai(Port::Port0, &[0x0F]).unwrap();
+    assert_eq!(
+        tca.cache().get(tca6424::RegisterKind::Output, Port::Port1),
+        Some(0x02)
+    );
+
+    i2c_mock.done();
+}
+
+#[test]
+fn test_auto_switches_after_split_retry_succeeds() {
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x80], vec![0x00, 0x00, 0x00])
+            .with_error(ErrorKind::Other),
+        I2cTransaction::write_read(address, vec![0x00], vec![0x11]),
+        I2cTransaction::write_read(address, vec![0x01], vec![0x22]),
+        I2cTransaction::write_read(address, vec![0x02], vec![0x33]),
+        // From now on every multi-register transfer is split
+        I2cTransaction::write_read(address, vec![0x0C], vec![0xFF]),
+        I2cTransaction::write_read(address, vec![0x0D], vec![0xF0]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, address).unwrap();
+    tca.set_transfer_strategy(TransferStrategy::Auto);
+    assert!(!tca.splits_transfers());
+
+    let mut inputs = [0u8; 3];
+    tca.get_ports_input_state_ai(Port::Port0, &mut inputs)
+        .unwrap();
+    assert_eq!(inputs, [0x11, 0x22, 0x33]);
+    assert!(tca.splits_transfers());
+    let mut directions = [0u8; 2];
+    tca.get_ports_direction_ai(Port::Port0, &mut directions)
+        .unwrap();
+    assert_eq!(directions, [0xFF, 0xF0]);
+
+    // Setting the strategy again starts over with auto-increment
+    tca.set_transfer_strategy(TransferStrategy::Auto);
+    assert!(!tca.splits_transfers());
+
+    i2c_mock.done();
+}
+
+#[test]
+fn test_auto_keeps_auto_increment_when_split_retry_fails() {
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x80], vec![0x00, 0x00, 0x00])
+            .with_error(ErrorKind::Other),
+        I2cTransaction::write_read(address, vec![0x00], vec![0x00]).with_error(ErrorKind::Other),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, address).unwrap();
+    tca.set_transfer_strategy(TransferStrategy::Auto);
+
+    let mut inputs = [0u8; 3];
+    assert!(
+        tca.get_ports_input_state_ai(Port::Port0, &mut inputs)
+            .is_err()
+    );
+    assert!(!tca.splits_transfers());
+
+    i2c_mock.done();
+}
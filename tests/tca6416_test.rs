@@ -0,0 +1,184 @@
+#![cfg(feature = "tca6416")]
+
+mod common;
+
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::errors::Error;
+use tca6424::{Pin, PinDirection, PinState, Port, RegisterKind, TCA6416_DEFAULT_ADDRESS, Tca6416};
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_tca6416_pins_and_ports_sync() {
+    let address = TCA6416_DEFAULT_ADDRESS;
+    let expectations = [
+        // P13 becomes an output: read-modify-write of Configuration Port 1 (0x07)
+        I2cTransaction::write_read(address, vec![0x07], vec![0xFF]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x07]),
+        I2cTransaction::write(address, vec![0xF7]),
+        I2cTransaction::transaction_end(address),
+        // P13 driven high: Output Port 1 (0x03)
+        I2cTransaction::write_read(address, vec![0x03], vec![0x00]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x03]),
+        I2cTransaction::write(address, vec![0x08]),
+        I2cTransaction::transaction_end(address),
+        // Polarity Port 0 (0x04)
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0x0F]),
+        I2cTransaction::transaction_end(address),
+        // P01 input level from Input Port 0 (0x00)
+        I2cTransaction::write_read(address, vec![0x00], vec![0x02]),
+        // Both input ports in one read
+        I2cTransaction::write_read(address, vec![0x00], vec![0x12, 0x34]),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6416::new(&mut i2c_mock, address).unwrap();
+
+    tca.set_pin_direction(Pin::P13, PinDirection::Output)
+        .unwrap();
+    tca.set_pin_output(Pin::P13, PinState::High).unwrap();
+    tca.set_port_polarity_inversion(Port::Port0, 0x0F).unwrap();
+    assert_eq!(tca.get_pin_input_state(Pin::P01), Ok(PinState::High));
+    let ports = tca.read_ports().unwrap();
+    assert_eq!(ports[0].value(), 0x12);
+    assert_eq!(ports[1].value(), 0x34);
+
+    assert_eq!(
+        tca.cache().get(RegisterKind::Configuration, Port::Port1),
+        Some(0xF7)
+    );
+    assert_eq!(
+        tca.cache().get(RegisterKind::Output, Port::Port1),
+        Some(0x08)
+    );
+
+    // Port 2 does not exist on the TCA6416A; no transfer is made
+    assert_eq!(
+        tca.set_pin_output(Pin::P20, PinState::High),
+        Err(Error::InvalidRegisterOrPin)
+    );
+    assert_eq!(
+        tca.get_port_input_state(Port::Port2),
+        Err(Error::InvalidRegisterOrPin)
+    );
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_tca6416_shares_the_driver_sync() {
+    let address = TCA6416_DEFAULT_ADDRESS;
+    let expectations = [
+        // Read-modify-write of Output Port 1 (0x03)
+        I2cTransaction::write_read(address, vec![0x03], vec![0xF0]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x03]),
+        I2cTransaction::write(address, vec![0xF5]),
+        I2cTransaction::transaction_end(address),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6416::new(&mut i2c_mock, address).unwrap();
+
+    tca.write_pins_masked(Port::Port1, 0x0F, 0x05).unwrap();
+    tca.set_cached_output_reads(true);
+    assert_eq!(tca.get_port_output_state(Port::Port1), Ok(0xF5));
+
+    // The TCA6416A has no Interrupt Mask registers
+    assert_eq!(
+        tca.get_register(RegisterKind::InterruptMask, Port::Port0),
+        Err(Error::InvalidRegisterOrPin)
+    );
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_tca6416_input_events_sync() {
+    use tca6424::InputWatcher;
+
+    let address = TCA6416_DEFAULT_ADDRESS;
+    let expectations = [
+        // Both Input Port registers (0x00, 0x01) in one read
+        I2cTransaction::write_read(address, vec![0x00], vec![0xFF, 0xFF]),
+        I2cTransaction::write_read(address, vec![0x00], vec![0xFF, 0x7F]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6416::new(&mut i2c_mock, address).unwrap();
+    // No Interrupt Mask on the TCA6416A: every pin of its two ports is enabled
+    assert_eq!(tca.enabled_interrupts(), 0xFFFF);
+
+    let mut watcher = InputWatcher::new()
+        .with_port(Port::Port1)
+        .with_interrupt_mask(true);
+    assert!(tca.poll_input_events(&mut watcher).unwrap().is_empty());
+    let changes = tca.poll_input_events(&mut watcher).unwrap();
+    assert_eq!(changes.changed_mask(), 1 << Pin::P17 as u8);
+
+    i2c_mock.done();
+}
+
+#[test]
+fn test_tca6416_rejects_reserved_address() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    assert!(matches!(
+        Tca6416::new(&mut i2c_mock, 0x78),
+        Err(Error::ReservedAddress(0x78))
+    ));
+    i2c_mock.done();
+}
+
+#[cfg(all(feature = "fail-safe", not(feature = "async")))]
+#[test]
+fn test_tca6416_fail_safe_frame_stays_on_its_ports_sync() {
+    use common::write;
+    use embedded_hal::i2c::ErrorKind;
+    use tca6424::errors::ConfigError;
+    use tca6424::{FailSafePolicy, FailSafeTriggers};
+
+    let address = TCA6416_DEFAULT_ADDRESS;
+    let expectations: Vec<_> = [
+        vec![
+            I2cTransaction::write_read(address, vec![0x00], vec![0x00])
+                .with_error(ErrorKind::Other),
+        ],
+        // P00 high and P10 low: Output then Configuration of Port 0 (0x02, 0x06) and Port 1
+        // (0x03, 0x07), nothing else
+        write(address, 0x02, &[0xFF]).to_vec(),
+        write(address, 0x06, &[0xFE]).to_vec(),
+        write(address, 0x03, &[0xFE]).to_vec(),
+        write(address, 0x07, &[0xFE]).to_vec(),
+    ]
+    .concat();
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6416::new(&mut i2c_mock, address).unwrap();
+
+    // P20 does not exist: the command byte of Output Port 2 would reach Polarity Port 0
+    let port2 = FailSafePolicy::new(1 << Pin::P20 as u8, 0, FailSafeTriggers::BUS_ERROR);
+    assert_eq!(
+        tca.set_fail_safe(Some(port2)),
+        Err(ConfigError::InvalidRegisterOrPin)
+    );
+    assert_eq!(tca.fail_safe(), None);
+
+    let policy = FailSafePolicy::new(
+        1 << Pin::P00 as u8,
+        1 << Pin::P10 as u8,
+        FailSafeTriggers::BUS_ERROR,
+    );
+    tca.set_fail_safe(Some(policy)).unwrap();
+    assert!(tca.get_port_input_state(Port::Port0).is_err());
+    assert!(tca.is_faulted());
+    assert_eq!(
+        tca.cache().get(RegisterKind::Configuration, Port::Port1),
+        Some(0xFE)
+    );
+    assert_eq!(tca.cache().get(RegisterKind::Output, Port::Port2), None);
+
+    i2c_mock.done();
+}
@@ -0,0 +1,143 @@
+#![cfg(all(feature = "testing", not(feature = "async")))]
+
+use proptest::prelude::*;
+use tca6424::testing::{self, InvariantViolation, PinOp, RegisterModel};
+use tca6424::{
+    DEFAULT_ADDRESS, Pin, PinDirection, PinState, Port, Register, RegisterKind, RegisterSnapshot,
+    Tca6424,
+};
+
+fn pin() -> impl Strategy<Value = Pin> {
+    (0u8..24).prop_map(|index| Pin::from_index(index).unwrap())
+}
+
+fn pin_op() -> impl Strategy<Value = PinOp> {
+    prop_oneof![
+        any::<bool>().prop_map(|input| PinOp::Direction(if input {
+            PinDirection::Input
+        } else {
+            PinDirection::Output
+        })),
+        any::<bool>().prop_map(|high| PinOp::Output(PinState::from(high))),
+    ]
+}
+
+fn image() -> impl Strategy<Value = RegisterSnapshot> {
+    any::<[[u8; 3]; 4]>().prop_map(
+        |[outputs, polarity_inversion, configuration, interrupt_mask]| RegisterSnapshot {
+            inputs: [0; 3],
+            outputs,
+            polarity_inversion,
+            configuration,
+            interrupt_mask,
+        },
+    )
+}
+
+proptest! {
+    #[test]
+    fn prop_apply_readback_is_identity(images in prop::collection::vec(image(), 1..4)) {
+        let mut tca = Tca6424::new(RegisterModel::new(), DEFAULT_ADDRESS).unwrap();
+        for image in &images {
+            prop_assert_eq!(testing::check_apply_readback(&mut tca, image), Ok(()));
+        }
+    }
+
+    #[test]
+    fn prop_pin_ops_stay_in_their_bit(
+        start in image(),
+        ops in prop::collection::vec((pin(), pin_op()), 1..32),
+    ) {
+        let mut tca = Tca6424::new(RegisterModel::new(), DEFAULT_ADDRESS).unwrap();
+        testing::check_apply_readback(&mut tca, &start).unwrap();
+        for (pin, op) in ops {
+            prop_assert_eq!(testing::check_pin_isolation(&mut tca, pin, op), Ok(()));
+        }
+    }
+}
+
+#[test]
+fn test_register_model_inputs_follow_pins() {
+    let mut tca = Tca6424::new(RegisterModel::new(), DEFAULT_ADDRESS).unwrap();
+    tca.model_mut().set_pin_levels(0x00_00_0F);
+    tca.set_port_direction(Port::Port0, 0xF0).unwrap();
+    tca.set_port_output(Port::Port0, 0x05).unwrap();
+    tca.set_port_polarity_inversion(Port::Port0, 0x80).unwrap();
+
+    // Outputs reflect the latch, inputs the external level, inverted where requested
+    assert_eq!(tca.get_port_input_state(Port::Port0).unwrap(), 0x85);
+    assert_eq!(tca.model().pin_levels() & 0xFF, 0x05);
+    assert_eq!(tca.model().register(Register::InputPort0), 0x85);
+}
+
+#[test]
+fn test_cache_incoherence_is_reported() {
+    let mut tca = Tca6424::new(RegisterModel::new(), DEFAULT_ADDRESS).unwrap();
+    tca.set_port_output(Port::Port1, 0x0F).unwrap();
+    testing::check_cache_coherent(&tca).unwrap();
+
+    // Simulate a reset behind the driver's back
+    tca.model_mut().reset();
+    match testing::check_cache_coherent(&tca) {
+        Err(InvariantViolation::CacheIncoherent(change)) => {
+            assert_eq!(change.kind, RegisterKind::Output);
+            assert_eq!(change.port, Port::Port1);
+            assert_eq!((change.before, change.after), (0x0F, 0xFF));
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn test_register_model_reports_output_glitches() {
+    let mut tca = Tca6424::new(RegisterModel::new(), DEFAULT_ADDRESS).unwrap();
+    // Direction first: P00 drives the power-on-high latch until the output is written
+    tca.set_pin_direction(Pin::P00, PinDirection::Output)
+        .unwrap();
+    tca.set_pin_output(Pin::P00, PinState::Low).unwrap();
+    assert_eq!(tca.model().glitched_pins(), 1 << Pin::P00 as u8);
+
+    // Output first: no glitch
+    tca.set_pin_output(Pin::P10, PinState::Low).unwrap();
+    tca.set_pin_direction(Pin::P10, PinDirection::Output)
+        .unwrap();
+    assert_eq!(tca.model().glitched_pins(), 1 << Pin::P00 as u8);
+
+    tca.model_mut().reset();
+    assert_eq!(tca.model().glitched_pins(), 0);
+}
+
+#[cfg(feature = "helpers")]
+#[test]
+fn test_configuration_paths_write_outputs_first() {
+    use tca6424::{BoardConfig, PlayMode, WaveformPlayer, WaveformStep};
+
+    const BOARD: BoardConfig = BoardConfig::new()
+        .output(Pin::P00, false, true)
+        .output(Pin::P13, true, false)
+        .output(Pin::P27, false, false)
+        .input(Pin::P20, true);
+    let mut tca = Tca6424::new(RegisterModel::new(), DEFAULT_ADDRESS).unwrap();
+    tca.apply_board_config(&BOARD).unwrap();
+    assert_eq!(tca.model().glitched_pins(), 0);
+    assert_eq!(
+        tca.model().pin_levels() & BOARD.outputs(),
+        BOARD.levels() & BOARD.outputs()
+    );
+
+    static STEPS: [WaveformStep; 1] = [WaveformStep::new(0x00_00_10, 0x01_00_00, 10)];
+    let mut tca = Tca6424::new(RegisterModel::new(), DEFAULT_ADDRESS).unwrap();
+    WaveformPlayer::new(&STEPS, PlayMode::Once)
+        .init(&mut tca)
+        .unwrap();
+    assert_eq!(tca.model().glitched_pins(), 0);
+    assert_eq!(tca.model().pin_levels() & 0x01_00_10, 0);
+
+    // Restoring the cache onto a reset device writes the Output registers first as well
+    let mut tca = Tca6424::new(RegisterModel::new(), DEFAULT_ADDRESS).unwrap();
+    tca.set_port_output(Port::Port1, 0x00).unwrap();
+    tca.set_port_direction(Port::Port1, 0x00).unwrap();
+    tca.model_mut().reset();
+    tca.restore_from_cache().unwrap();
+    assert_eq!(tca.model().glitched_pins(), 0);
+}
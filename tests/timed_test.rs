@@ -0,0 +1,40 @@
+#![cfg(all(feature = "embassy-time", not(feature = "async")))]
+
+mod common;
+
+use common::write;
+use embassy_time::{Duration, MockDriver};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{Debouncer, EmbassyClock, Heartbeat, Pin, TimeSource};
+
+// The mock driver is global, so every clock-dependent step lives in one test
+#[test]
+fn test_now_helpers_follow_embassy_clock() {
+    let driver = MockDriver::get();
+    driver.reset();
+    assert_eq!(tca6424::now_ms(), 0);
+    driver.advance(Duration::from_millis(1_500));
+    assert_eq!(tca6424::now_ms(), 1_500);
+    assert_eq!(EmbassyClock.now_ms(), 1_500);
+
+    let mut debouncer = Debouncer::new(false, 20);
+    assert_eq!(debouncer.update_now(true), None);
+    driver.advance(Duration::from_millis(25));
+    assert_eq!(debouncer.update_now(true), Some(true));
+
+    let address = 0x22;
+    let mut expectations = vec![I2cTransaction::write_read(address, vec![0x04], vec![0x00])];
+    expectations.extend(write(address, 0x04, &[0x01]));
+    expectations.extend(write(address, 0x04, &[0x00]));
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address)
+        .unwrap()
+        .with_embassy_delay();
+    let mut heartbeat = Heartbeat::new(&mut tca, Pin::P00, 100).unwrap();
+    assert!(heartbeat.tick_now(&mut tca).unwrap());
+    driver.advance(Duration::from_millis(10));
+    assert!(!heartbeat.tick_now(&mut tca).unwrap());
+    driver.advance(Duration::from_millis(50));
+    assert!(heartbeat.tick_now(&mut tca).unwrap());
+    i2c_mock.done();
+}
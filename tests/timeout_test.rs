@@ -0,0 +1,126 @@
+mod common;
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+#[cfg(not(feature = "async"))]
+use embedded_hal_mock::eh1::i2c::Transaction as I2cTransaction;
+use tca6424::{Pin, PinState};
+
+#[derive(Default)]
+struct CountingDelay {
+    total_ns: u64,
+}
+
+impl DelayNs for CountingDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.total_ns += u64::from(ns);
+    }
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_with_delay_keeps_driver_state_sync() {
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x04], vec![0x00]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0x01]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x04], vec![0x01]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x04]),
+        I2cTransaction::write(address, vec![0x03]),
+        I2cTransaction::transaction_end(address),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    tca.set_pin_output(Pin::P00, PinState::High).unwrap();
+    let _claim = tca.claim_pin(Pin::P05).unwrap();
+
+    let mut tca = tca.with_delay(CountingDelay::default());
+    assert!(tca.is_claimed(Pin::P05));
+    tca.set_pin_output(Pin::P01, PinState::High).unwrap();
+    tca.delay().delay_us(3);
+    assert_eq!(tca.delay().total_ns, 3_000);
+
+    i2c_mock.done();
+}
+
+#[test]
+fn test_new_with_delay_stores_delay() {
+    let mut i2c_mock = I2cMock::new(&[]);
+    let mut tca =
+        tca6424::Tca6424::new_with_delay(&mut i2c_mock, 0x22, CountingDelay::default()).unwrap();
+    embedded_hal::delay::DelayNs::delay_ms(tca.delay(), 2);
+    assert_eq!(tca.delay().total_ns, 2_000_000);
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[cfg(feature = "auto-increment")]
+#[test]
+fn test_transfer_gap_sync() {
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x84], vec![0x00, 0x00, 0x00]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x84]),
+        I2cTransaction::write(address, vec![0x01, 0x02, 0x03]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x00], vec![0x00]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca =
+        tca6424::Tca6424::new_with_delay(&mut i2c_mock, address, CountingDelay::default()).unwrap();
+    assert_eq!(tca.transfer_gap_us(), 0);
+
+    tca.set_transfer_gap_us(20);
+    let mut outputs = [0u8; 3];
+    tca.get_ports_output_state_ai(tca6424::Port::Port0, &mut outputs)
+        .unwrap();
+    tca.set_ports_output_ai(tca6424::Port::Port0, &[0x01, 0x02, 0x03])
+        .unwrap();
+    assert_eq!(tca.delay().total_ns, 40_000);
+
+    // The gap carries over to a new delay source
+    let mut tca = tca.with_delay(CountingDelay::default());
+    tca.get_pin_input_state(Pin::P00).unwrap();
+    assert_eq!(tca.delay().total_ns, 20_000);
+
+    i2c_mock.done();
+}
+
+/// A delay that has always already elapsed, so any timeout expires at its first poll.
+#[cfg(feature = "async")]
+struct ElapsedDelay;
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::delay::DelayNs for ElapsedDelay {
+    async fn delay_ns(&mut self, _ns: u32) {}
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_timeout_abandons_a_wedged_write() {
+    use common::{BusMode, StallingBus};
+    use tca6424::errors::Error;
+    use tca6424::{Port, RegisterKind};
+
+    let bus = StallingBus::new(BusMode::StallWrites);
+    let mut tca = tca6424::Tca6424::new_with_delay(&bus, 0x22, ElapsedDelay).unwrap();
+    tca.set_timeout_us(Some(1_000));
+
+    // The read of the read-modify-write completes; the write never does
+    assert_eq!(
+        tca.set_pin_output(Pin::P00, PinState::High).await,
+        Err(Error::Timeout)
+    );
+    assert!(
+        tca.cache()
+            .is_register_dirty(RegisterKind::Output, Port::Port0)
+    );
+    assert_eq!(tca.cache().get(RegisterKind::Output, Port::Port0), None);
+    assert!(bus.writes.borrow().is_empty());
+}
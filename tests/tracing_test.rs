@@ -0,0 +1,77 @@
+#![cfg(all(feature = "tracing", not(feature = "async")))]
+
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{Pin, PinDirection};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+/// Records every event as its level followed by its `name=value` fields.
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<String>>>);
+
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.push_str(&format!(" {}={:?}", field.name(), value));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields(format!("{}", event.metadata().level()));
+        event.record(&mut fields);
+        self.0.lock().unwrap().push(fields.0);
+    }
+    fn enter(&self, _: &Id) {}
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn test_transfers_emit_events() {
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x0D], vec![0xFF]),
+        I2cTransaction::transaction_start(address),
+        I2cTransaction::write(address, vec![0x0D]),
+        I2cTransaction::write(address, vec![0xFB]),
+        I2cTransaction::transaction_end(address),
+        I2cTransaction::write_read(address, vec![0x80], vec![0; 3])
+            .with_error(embedded_hal::i2c::ErrorKind::Other),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let recorder = Recorder::default();
+
+    tracing::subscriber::with_default(recorder.clone(), || {
+        let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+        tca.set_pin_direction(Pin::P12, PinDirection::Output)
+            .unwrap();
+        tca.read_ports().unwrap_err();
+    });
+
+    let events = recorder.0.lock().unwrap();
+    assert_eq!(events.len(), 3);
+    assert!(events[0].starts_with(&Level::TRACE.to_string()));
+    assert!(events[0].contains("op=\"read\""));
+    assert!(events[0].contains("register=Configuration port=Port1"));
+    assert!(events[1].contains("op=\"write\""));
+    assert!(events[1].contains("bytes=[251]"));
+    assert!(events[1].contains("latency_us="));
+    assert!(events[2].starts_with(&Level::WARN.to_string()));
+    assert!(events[2].contains("auto_increment=true"));
+    assert!(events[2].contains("error=Other"));
+
+    i2c_mock.done();
+}
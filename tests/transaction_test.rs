@@ -0,0 +1,116 @@
+#![cfg(not(feature = "async"))]
+
+mod common;
+
+use common::write;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{ConfigTransaction, Pin, PinDirection, PinState, Port, RegisterKind, Tca6424};
+
+#[test]
+fn test_config_transaction_staging() {
+    let mut txn = ConfigTransaction::new();
+    assert!(txn.is_empty());
+    txn.output(Pin::P00, PinState::High)
+        .output(Pin::P00, PinState::Low)
+        .port_output(Port::Port1, 0xAA)
+        .direction(Pin::P27, PinDirection::Input);
+    assert!(!txn.is_empty());
+    assert_eq!(txn.staged(RegisterKind::Output), (0x00_FF01, 0x00_AA00));
+    assert_eq!(
+        txn.staged(RegisterKind::Configuration),
+        (0x80_0000, 0x80_0000)
+    );
+    txn.stage(RegisterKind::Input, 0xFF, 0xFF);
+    assert_eq!(txn.staged(RegisterKind::Input), (0, 0));
+}
+
+#[test]
+fn test_with_config_transaction_orders_and_merges_writes() {
+    let address = 0x22;
+    let mut expectations = Vec::new();
+    // Outputs first: P00 is a partial port with an unknown value, so the family is read once
+    expectations.push(I2cTransaction::write_read(
+        address,
+        vec![0x84],
+        vec![0xFF, 0x00, 0x12],
+    ));
+    // Adjacent changed ports share one auto-increment write
+    expectations.extend(write(address, 0x84, &[0xFE, 0xAA]));
+    // Directions last; ports 0 and 2 are not adjacent, so they are written separately
+    expectations.push(I2cTransaction::write_read(
+        address,
+        vec![0x8C],
+        vec![0xFF, 0xFF, 0xFF],
+    ));
+    expectations.extend(write(address, 0x0C, &[0xFE]));
+    expectations.extend(write(address, 0x0E, &[0xF0]));
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, address).unwrap();
+    let stage = |txn: &mut ConfigTransaction| {
+        txn.direction(Pin::P00, PinDirection::Output)
+            .port_direction(Port::Port2, 0xF0)
+            .output(Pin::P00, PinState::Low)
+            .port_output(Port::Port1, 0xAA);
+    };
+    tca.with_config_transaction(stage).unwrap();
+    // Everything is cached and unchanged now: no transfer at all
+    tca.with_config_transaction(stage).unwrap();
+    tca.with_config_transaction(|_| {}).unwrap();
+
+    i2c_mock.done();
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn test_config_transaction_does_not_glitch() {
+    use tca6424::DEFAULT_ADDRESS;
+    use tca6424::testing::RegisterModel;
+
+    let mut tca = Tca6424::new(RegisterModel::new(), DEFAULT_ADDRESS).unwrap();
+    // Staged in the "wrong" order on purpose
+    tca.with_config_transaction(|txn| {
+        txn.port_direction(Port::Port0, 0x00)
+            .port_output(Port::Port0, 0x0F);
+    })
+    .unwrap();
+    assert_eq!(tca.model().glitched_pins(), 0);
+    assert_eq!(tca.model().pin_levels() & 0xFF, 0x0F);
+}
+
+#[cfg(all(feature = "polarity", feature = "interrupt-mask"))]
+#[test]
+fn test_configure_inputs() {
+    let address = 0x22;
+    let mut expectations = Vec::new();
+    // Port 0 is fully staged; the partial Port 1 needs each family read once
+    expectations.push(I2cTransaction::write_read(
+        address,
+        vec![0x88],
+        vec![0x00, 0x00, 0x00],
+    ));
+    expectations.extend(write(address, 0x88, &[0x0F, 0x01]));
+    expectations.push(I2cTransaction::write_read(
+        address,
+        vec![0x90],
+        vec![0xFF, 0xFF, 0xFF],
+    ));
+    expectations.extend(write(address, 0x90, &[0x00, 0xFC]));
+    expectations.push(I2cTransaction::write_read(
+        address,
+        vec![0x8C],
+        vec![0x00, 0x00, 0x00],
+    ));
+    expectations.extend(write(address, 0x8C, &[0xFF, 0x03]));
+    // Masking interrupts only touches the Interrupt Mask family
+    expectations.extend(write(address, 0x90, &[0xFF, 0xFF]));
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, address).unwrap();
+    // P00-P07 and P10-P11 as inputs, P00-P03 and P10 inverted; P20 is not an input and
+    // its invert bit is ignored
+    tca.configure_inputs(0x00_03FF, true, 0x01_010F).unwrap();
+    tca.configure_inputs(0x00_03FF, false, 0x01_010F).unwrap();
+
+    i2c_mock.done();
+}
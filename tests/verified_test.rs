@@ -0,0 +1,89 @@
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::errors::{Error, ErrorKind};
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_read_inputs_verified_sync() {
+    let address = 0x22;
+    let read = |values: [u8; 3]| I2cTransaction::write_read(address, vec![0x80], values.to_vec());
+
+    let expectations = [
+        // Stable on the first pair
+        read([0x01, 0x00, 0x80]),
+        read([0x01, 0x00, 0x80]),
+        // A glitch on the second read; the third agrees with it
+        read([0x01, 0x00, 0x00]),
+        read([0x03, 0x00, 0x00]),
+        read([0x03, 0x00, 0x00]),
+        // Never stable with one retry
+        read([0x00, 0x00, 0x00]),
+        read([0x01, 0x00, 0x00]),
+        read([0x00, 0x00, 0x00]),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+
+    assert_eq!(tca.read_inputs_verified(0), Ok(0x80_0001));
+    assert_eq!(tca.read_inputs_verified(2), Ok(0x00_0003));
+    let error = tca.read_inputs_verified(1).unwrap_err();
+    assert_eq!(error, Error::InputsUnstable);
+    assert_eq!(error.kind(), ErrorKind::InputsUnstable);
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_get_pin_input_stable_sync() {
+    use embedded_hal::delay::DelayNs;
+    use tca6424::{Pin, PinState};
+
+    #[derive(Default)]
+    struct CountingDelay {
+        total_us: u32,
+    }
+
+    impl DelayNs for CountingDelay {
+        fn delay_ns(&mut self, ns: u32) {
+            self.total_us += ns / 1_000;
+        }
+    }
+
+    let address = 0x22;
+    let read = |value: u8| I2cTransaction::write_read(address, vec![0x01], vec![value]);
+
+    let expectations = [
+        // Other bits may change: only P13 is compared
+        read(0x08),
+        read(0x0A),
+        // P13 glitches once
+        read(0x00),
+        read(0x08),
+        read(0x08),
+        // Oscillating
+        read(0x00),
+        read(0x08),
+    ];
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca =
+        tca6424::Tca6424::new_with_delay(&mut i2c_mock, address, CountingDelay::default()).unwrap();
+
+    assert_eq!(
+        tca.get_pin_input_stable(Pin::P13, 50, 0),
+        Ok(PinState::High)
+    );
+    assert_eq!(tca.delay().total_us, 50);
+    assert_eq!(
+        tca.get_pin_input_stable(Pin::P13, 50, 3),
+        Ok(PinState::High)
+    );
+    assert_eq!(tca.delay().total_us, 150);
+    assert_eq!(
+        tca.get_pin_input_stable(Pin::P13, 50, 0),
+        Err(Error::InputsUnstable)
+    );
+
+    i2c_mock.done();
+}
@@ -0,0 +1,164 @@
+#![cfg(feature = "helpers")]
+
+mod common;
+
+use common::write;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use tca6424::{PlayMode, StepControl, WaveformPlayer, WaveformStep, pins};
+
+static BEEPS: [WaveformStep; 3] = [
+    WaveformStep::new(pins!(P00, P10), 0, 100),
+    WaveformStep::new(0, pins!(P00), 50),
+    WaveformStep::new(0, pins!(P10), 200),
+];
+
+#[test]
+fn test_waveform_process_single_shot() {
+    let mut player = WaveformPlayer::new(&BEEPS, PlayMode::Once);
+    assert_eq!(player.pins(), pins!(P00, P10));
+
+    assert_eq!(player.process(u32::MAX - 10), Some((pins!(P00, P10), 0)));
+    assert_eq!(player.process(50), None);
+    // Timestamps wrap around; step 1 starts at 89
+    assert_eq!(player.process(89), Some((0, pins!(P00))));
+    assert_eq!(player.step_index(), 1);
+    assert_eq!(player.process(139), Some((0, pins!(P10))));
+    assert!(!player.is_finished());
+    assert_eq!(player.process(339), None);
+    assert!(player.is_finished());
+    assert_eq!(player.process(10_000), None);
+
+    player.restart();
+    assert!(!player.is_finished());
+    assert_eq!(player.process(0), Some((pins!(P00, P10), 0)));
+}
+
+#[test]
+fn test_waveform_process_loop_merges_late_steps() {
+    let mut player = WaveformPlayer::new(&BEEPS, PlayMode::Loop);
+    assert_eq!(player.process(0), Some((pins!(P00, P10), 0)));
+    // Steps 1 and 2 both started before this tick
+    assert_eq!(player.process(160), Some((0, pins!(P00, P10))));
+    assert_eq!(player.step_index(), 2);
+    // Step 0 of the next cycle started at 350, step 1 at 450
+    assert_eq!(player.process(360), Some((pins!(P00, P10), 0)));
+    assert_eq!(player.process(460), Some((0, pins!(P00))));
+    // Far behind: whole cycles are skipped, timing stays on the table's grid
+    assert!(player.process(350 * 100 + 110).is_some());
+    assert_eq!(player.step_index(), 1);
+    assert!(!player.is_finished());
+
+    static EMPTY: [WaveformStep; 0] = [];
+    let mut player = WaveformPlayer::new(&EMPTY, PlayMode::Loop);
+    assert!(player.is_finished());
+    assert_eq!(player.process(0), None);
+}
+
+#[test]
+fn test_waveform_pause_resume_abort() {
+    let mut player = WaveformPlayer::new(&BEEPS, PlayMode::Once);
+    assert!(player.process(0).is_some());
+    player.pause(60);
+    assert!(player.is_paused());
+    assert_eq!(player.process(500), None);
+    // 40 ms of step 0 were left when paused
+    player.resume(1_000);
+    assert_eq!(player.process(1_039), None);
+    assert_eq!(player.process(1_040), Some((0, pins!(P00))));
+
+    player.abort();
+    assert!(player.is_finished() && player.is_aborted());
+    assert_eq!(player.process(5_000), None);
+    player.restart();
+    assert!(!player.is_aborted());
+}
+
+#[test]
+fn test_waveform_step_gate() {
+    // Power-up sequence: step 2 waits for a power-good input
+    let mut player = WaveformPlayer::new(&BEEPS, PlayMode::Once);
+    let mut pgood = false;
+    let gate = |pgood: bool| {
+        move |step: usize| {
+            if step == 2 && !pgood {
+                StepControl::Hold
+            } else {
+                StepControl::Proceed
+            }
+        }
+    };
+    assert!(player.process_with(0, gate(pgood)).is_some());
+    assert!(player.process_with(100, gate(pgood)).is_some());
+    assert_eq!(player.process_with(150, gate(pgood)), None);
+    assert_eq!(player.process_with(400, gate(pgood)), None);
+    assert_eq!(player.step_index(), 1);
+
+    // Released at 420: step 2 is timed from there
+    pgood = true;
+    assert_eq!(player.process_with(420, gate(pgood)), Some((0, pins!(P10))));
+    assert_eq!(player.process_with(619, gate(pgood)), None);
+    assert!(!player.is_finished());
+    assert_eq!(player.process_with(620, gate(pgood)), None);
+    assert!(player.is_finished());
+
+    player.restart();
+    assert_eq!(player.process_with(0, |_| StepControl::Abort), None);
+    assert!(player.is_aborted());
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_waveform_tick_sync() {
+    let address = 0x22;
+    let mut expectations = Vec::new();
+    // init: the table pins are driven low, then become outputs
+    expectations.push(I2cTransaction::write_read(address, vec![0x04], vec![0x80]));
+    expectations.extend(write(address, 0x04, &[0x80]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x05], vec![0xFF]));
+    expectations.extend(write(address, 0x05, &[0xFE]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x0C], vec![0xFF]));
+    expectations.extend(write(address, 0x0C, &[0xFE]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x0D], vec![0xFF]));
+    expectations.extend(write(address, 0x0D, &[0xFE]));
+    // Steps merge into the cached Output registers
+    expectations.extend(write(address, 0x04, &[0x81]));
+    expectations.extend(write(address, 0x05, &[0xFF]));
+    expectations.extend(write(address, 0x04, &[0x80]));
+    expectations.extend(write(address, 0x05, &[0xFE]));
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    let mut player = WaveformPlayer::new(&BEEPS, PlayMode::Once);
+
+    player.init(&mut tca).unwrap();
+    assert!(player.tick(&mut tca, 0).unwrap());
+    assert!(!player.tick(&mut tca, 99).unwrap());
+    assert!(player.tick(&mut tca, 100).unwrap());
+    assert!(player.tick(&mut tca, 150).unwrap());
+    assert!(!player.tick(&mut tca, 400).unwrap());
+    assert!(player.is_finished());
+
+    i2c_mock.done();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_waveform_run_sync() {
+    let address = 0x22;
+    let mut expectations = Vec::new();
+    expectations.push(I2cTransaction::write_read(address, vec![0x04], vec![0x00]));
+    expectations.extend(write(address, 0x04, &[0x01]));
+    expectations.push(I2cTransaction::write_read(address, vec![0x05], vec![0x00]));
+    expectations.extend(write(address, 0x05, &[0x01]));
+    expectations.extend(write(address, 0x04, &[0x00]));
+    expectations.extend(write(address, 0x05, &[0x00]));
+
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    let mut player = WaveformPlayer::new(&BEEPS, PlayMode::Once);
+
+    player.run(&mut tca).unwrap();
+    assert!(player.is_finished());
+
+    i2c_mock.done();
+}
@@ -0,0 +1,51 @@
+#![cfg(not(feature = "async"))]
+
+mod common;
+
+use common::write;
+use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+use tca6424::errors::{Error, ErrorKind};
+use tca6424::{Pin, PinDirection, PinState, Port, Tca6424};
+
+#[test]
+fn test_write_only_never_reads() {
+    let address = 0x22;
+    let expectations: Vec<_> = [
+        write(address, 0x04, &[0x00]),
+        write(address, 0x04, &[0x08]),
+        write(address, 0x0D, &[0xFE]),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    // No write_read expectations: any read on the bus fails the test
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = Tca6424::new(&mut i2c_mock, address).unwrap();
+    tca.set_write_only(true);
+    assert!(tca.is_write_only());
+
+    // Unknown registers cannot be read
+    assert_eq!(
+        tca.set_pin_output(Pin::P03, PinState::High),
+        Err(Error::WriteOnly)
+    );
+
+    // Once written, read-modify-write is served from the cache
+    tca.set_port_output(Port::Port0, 0x00).unwrap();
+    tca.set_pin_output(Pin::P03, PinState::High).unwrap();
+    assert_eq!(tca.get_port_output_state(Port::Port0).unwrap(), 0x08);
+
+    // Inputs are never cached
+    let error = tca.get_pin_input_state(Pin::P20).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::WriteOnly);
+
+    tca.assume_power_on_state();
+    assert_eq!(
+        tca.cache().get(tca6424::RegisterKind::Output, Port::Port0),
+        Some(0xFF)
+    );
+    tca.set_pin_direction(Pin::P10, PinDirection::Output)
+        .unwrap();
+
+    i2c_mock.done();
+}
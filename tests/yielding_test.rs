@@ -0,0 +1,105 @@
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_yield_fn_runs_between_transfers_sync() {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+    use tca6424::Port;
+
+    static YIELDS: AtomicU32 = AtomicU32::new(0);
+    fn count_yield() {
+        YIELDS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    let address = 0x22;
+    let expectations = [
+        I2cTransaction::write_read(address, vec![0x00], vec![0x00]),
+        I2cTransaction::write_read(address, vec![0x01], vec![0x00]),
+        I2cTransaction::write_read(address, vec![0x02], vec![0x00]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+    let mut tca = tca6424::Tca6424::new(&mut i2c_mock, address).unwrap();
+    assert!(tca.yield_fn().is_none());
+
+    tca.get_port_input_state(Port::Port0).unwrap();
+    assert_eq!(YIELDS.load(Ordering::Relaxed), 0);
+
+    tca.set_yield_fn(Some(count_yield));
+    tca.get_port_input_state(Port::Port1).unwrap();
+    tca.get_port_input_state(Port::Port2).unwrap();
+    assert_eq!(YIELDS.load(Ordering::Relaxed), 2);
+
+    i2c_mock.done();
+}
+
+#[cfg(feature = "async")]
+mod asynchronous {
+    use std::cell::RefCell;
+
+    use embedded_hal::i2c::{ErrorKind, ErrorType, Operation, SevenBitAddress};
+    use embedded_hal_async::i2c::I2c;
+    use tca6424::{Tca6424, yield_now};
+
+    /// A bus that completes every transaction at once and logs it.
+    struct InstantBus<'a> {
+        log: &'a RefCell<Vec<&'static str>>,
+    }
+
+    impl ErrorType for InstantBus<'_> {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for InstantBus<'_> {
+        async fn transaction(
+            &mut self,
+            _address: SevenBitAddress,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            for operation in operations {
+                if let Operation::Read(buffer) = operation {
+                    buffer.fill(0);
+                }
+            }
+            self.log.borrow_mut().push("bus");
+            Ok(())
+        }
+    }
+
+    /// Takes a snapshot (five transactions) while another task ticks, and returns the log.
+    async fn snapshot_while_ticking(yielding: bool) -> Vec<&'static str> {
+        let log = RefCell::new(Vec::new());
+        let mut tca = Tca6424::new(InstantBus { log: &log }, 0x22).unwrap();
+        tca.set_yield_between_transfers(yielding);
+        assert_eq!(tca.yields_between_transfers(), yielding);
+        let (result, ()) = tokio::join!(tca.snapshot(), async {
+            for _ in 0..8 {
+                log.borrow_mut().push("tick");
+                yield_now().await;
+            }
+        });
+        result.unwrap();
+        log.into_inner()
+    }
+
+    /// Returns the length of the longest run of back-to-back transactions.
+    fn longest_burst(log: &[&str]) -> usize {
+        log.split(|&entry| entry == "tick")
+            .map(<[_]>::len)
+            .max()
+            .unwrap_or(0)
+    }
+
+    #[cfg(feature = "diagnostics")]
+    #[tokio::test]
+    async fn test_yield_between_transfers() {
+        // Without yielding the snapshot runs its five reads in one go
+        let log = snapshot_while_ticking(false).await;
+        assert_eq!(longest_burst(&log), 5);
+
+        let log = snapshot_while_ticking(true).await;
+        assert_eq!(log.iter().filter(|&&entry| entry == "bus").count(), 5);
+        // `join!` rotates which branch it polls first, so two transactions can meet around
+        // a yield, but the other task runs throughout the snapshot
+        assert!(longest_burst(&log) <= 2);
+    }
+}